@@ -0,0 +1,219 @@
+//! A task awaits a channel that never receives: the process is alive, every
+//! thread waits inside the runtime, and nothing makes progress.
+//!
+//! The runtime is a small multi-threaded executor on std alone, so the example
+//! builds without a registry. Its threads wait where tokio's do: idle workers
+//! in the scheduler (`runtime::park::wait_for_work`) and `block_on` parked on
+//! the main thread (`runtime::park::park_thread`).
+
+mod channel {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::{Arc, Mutex};
+    use std::task::{Context, Poll, Waker};
+
+    struct State<T> {
+        value: Option<T>,
+        closed: bool,
+        waker: Option<Waker>,
+    }
+
+    pub struct Sender<T>(Arc<Mutex<State<T>>>);
+
+    /// Resolves to the sent value, or `None` once the sender is dropped unsent.
+    pub struct Receiver<T>(Arc<Mutex<State<T>>>);
+
+    pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+        let state = Arc::new(Mutex::new(State { value: None, closed: false, waker: None }));
+        (Sender(Arc::clone(&state)), Receiver(state))
+    }
+
+    impl<T> Sender<T> {
+        pub fn send(self, value: T) {
+            self.0.lock().unwrap().value = Some(value);
+        }
+    }
+
+    impl<T> Drop for Sender<T> {
+        fn drop(&mut self) {
+            let mut state = self.0.lock().unwrap();
+            state.closed = true;
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+
+    impl<T> Future for Receiver<T> {
+        type Output = Option<T>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+            let mut state = self.0.lock().unwrap();
+            if let Some(value) = state.value.take() {
+                return Poll::Ready(Some(value));
+            }
+            if state.closed {
+                return Poll::Ready(None);
+            }
+            state.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+mod runtime {
+    use std::collections::VecDeque;
+    use std::future::Future;
+    use std::pin::{pin, Pin};
+    use std::sync::{Arc, Condvar, Mutex};
+    use std::task::{Context, Poll, Wake, Waker};
+    use std::thread::{self, Thread};
+
+    use crate::channel::{channel, Receiver};
+
+    type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+    /// Where the runtime's threads wait when they have nothing to run.
+    pub mod park {
+        use std::sync::{Condvar, MutexGuard};
+        use std::thread;
+
+        /// An idle worker, until a task is scheduled.
+        #[inline(never)]
+        pub fn wait_for_work<'a, T>(queue: MutexGuard<'a, T>, work: &Condvar) -> MutexGuard<'a, T> {
+            work.wait(queue).unwrap()
+        }
+
+        /// `block_on`, until the future it polls is woken.
+        #[inline(never)]
+        pub fn park_thread() {
+            thread::park();
+        }
+    }
+
+    struct Shared {
+        queue: Mutex<VecDeque<Arc<Task>>>,
+        work: Condvar,
+    }
+
+    impl Shared {
+        fn schedule(&self, task: Arc<Task>) {
+            self.queue.lock().unwrap().push_back(task);
+            self.work.notify_one();
+        }
+
+        fn next_task(&self) -> Arc<Task> {
+            let mut queue = self.queue.lock().unwrap();
+            loop {
+                if let Some(task) = queue.pop_front() {
+                    return task;
+                }
+                queue = park::wait_for_work(queue, &self.work);
+            }
+        }
+    }
+
+    struct Task {
+        future: Mutex<Option<BoxFuture>>,
+        shared: Arc<Shared>,
+    }
+
+    impl Task {
+        fn run(self: &Arc<Self>) {
+            let waker = Waker::from(Arc::clone(self));
+            let mut cx = Context::from_waker(&waker);
+            let mut slot = self.future.lock().unwrap();
+            if let Some(mut future) = slot.take() {
+                if future.as_mut().poll(&mut cx).is_pending() {
+                    *slot = Some(future);
+                }
+            }
+        }
+    }
+
+    impl Wake for Task {
+        fn wake(self: Arc<Self>) {
+            let shared = Arc::clone(&self.shared);
+            shared.schedule(self);
+        }
+    }
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn worker_loop(shared: Arc<Shared>) {
+        loop {
+            shared.next_task().run();
+        }
+    }
+
+    pub struct Runtime {
+        shared: Arc<Shared>,
+    }
+
+    impl Runtime {
+        pub fn new(workers: usize) -> Runtime {
+            let shared = Arc::new(Shared { queue: Mutex::new(VecDeque::new()), work: Condvar::new() });
+            for n in 0..workers {
+                let shared = Arc::clone(&shared);
+                thread::Builder::new()
+                    .name(format!("async-worker-{n}"))
+                    .spawn(move || worker_loop(shared))
+                    .unwrap();
+            }
+            Runtime { shared }
+        }
+
+        /// Runs `future` on the workers; the receiver resolves to its output.
+        pub fn spawn<F>(&self, future: F) -> Receiver<F::Output>
+        where
+            F: Future + Send + 'static,
+            F::Output: Send + 'static,
+        {
+            let (done, output) = channel();
+            let task = Arc::new(Task {
+                future: Mutex::new(Some(Box::pin(async move { done.send(future.await) }))),
+                shared: Arc::clone(&self.shared),
+            });
+            self.shared.schedule(task);
+            output
+        }
+
+        pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+            let mut future = pin!(future);
+            let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+            let mut cx = Context::from_waker(&waker);
+            loop {
+                if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                    return output;
+                }
+                park::park_thread();
+            }
+        }
+    }
+}
+
+use channel::channel;
+use runtime::Runtime;
+
+fn main() {
+    println!("Starting async hang demo (pid {})...", std::process::id());
+    let runtime = Runtime::new(2);
+    let (config_tx, config_rx) = channel::<String>();
+
+    let task = runtime.spawn(async move {
+        println!("task: waiting for the configuration");
+        let config = config_rx.await;
+        println!("task: got {config:?} (unexpected)");
+    });
+
+    // The sender stays alive but never sends, so the task is never woken
+    // again, and `block_on` waits for it forever.
+    runtime.block_on(task);
+    drop(config_tx);
+}
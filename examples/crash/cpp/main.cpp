@@ -0,0 +1,20 @@
+// C++ mirror of examples/crash/rust: print a warning, then write through a null int*.
+#include <cstdio>
+
+namespace crash_demo {
+
+void crash() {
+    // Both volatiles: optimized builds must reload the pointer and keep the store,
+    // so -O2 / /O2 still produce a real write fault instead of a trap or nothing.
+    volatile int *volatile ptr = nullptr;
+    std::puts("About to dereference a null pointer... this will crash.");
+    std::fflush(stdout);
+    *ptr = 42;
+}
+
+}  // namespace crash_demo
+
+int main() {
+    crash_demo::crash();
+    return 0;
+}
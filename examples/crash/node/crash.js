@@ -0,0 +1,32 @@
+#!/usr/bin/env -S node --stack-size=65500
+// A native crash triggered from JavaScript: unbounded recursion past the end of
+// the thread's stack.
+//
+// `--stack-size` tells V8 it has about 64 MiB of stack, far more than the
+// 8 MiB the OS gives the main thread, so V8's own stack check never fires and
+// no RangeError is thrown. The recursion runs off the real stack instead and
+// the process dies with SIGSEGV inside V8's generated code.
+'use strict';
+
+// A config tree nested deeper than anyone expected, as a parser might build
+// from untrusted input.
+function buildTree(depth) {
+  let node = { name: 'leaf', children: [] };
+  for (let i = 0; i < depth; i++) {
+    node = { name: `level-${i}`, children: [node] };
+  }
+  return node;
+}
+
+// Counts the nodes recursively, one JavaScript frame per level.
+function countNodes(node) {
+  let total = 1;
+  for (const child of node.children) {
+    total += countNodes(child);
+  }
+  return total;
+}
+
+console.log(`Starting node crash demo (pid ${process.pid})...`);
+const tree = buildTree(10_000_000);
+console.log(`counted ${countNodes(tree)} nodes (unexpected)`);
@@ -0,0 +1,17 @@
+#!/usr/bin/env python3
+"""Segfault inside native code called from Python: strlen(NULL) through ctypes."""
+import ctypes
+import ctypes.util
+
+
+def name_length(name):
+    libc = ctypes.CDLL(ctypes.util.find_library("c"))
+    libc.strlen.argtypes = [ctypes.c_char_p]
+    libc.strlen.restype = ctypes.c_size_t
+    # None becomes a NULL char*, which strlen() dereferences.
+    return libc.strlen(name)
+
+
+if __name__ == "__main__":
+    print("About to call strlen(NULL) through ctypes...", flush=True)
+    name_length(None)
@@ -0,0 +1,28 @@
+//! `unwrap()` on `None`: a Rust panic rather than a memory fault.
+//!
+//! The panic hook aborts after printing the message, so the process stops with
+//! SIGABRT and the panicking frames still on the stack, as under
+//! `panic = "abort"`.
+
+use std::panic;
+
+fn find_port(args: &[String]) -> Option<u16> {
+    args.iter().find_map(|arg| arg.strip_prefix("--port=")?.parse().ok())
+}
+
+#[inline(never)]
+fn crash(args: &[String]) -> u16 {
+    // The bug: no --port= argument was given, so this is `None.unwrap()`.
+    find_port(args).unwrap()
+}
+
+fn main() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        std::process::abort();
+    }));
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    println!("About to unwrap a missing --port... this will panic.");
+    println!("listening on {}", crash(&args));
+}
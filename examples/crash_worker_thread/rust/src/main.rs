@@ -0,0 +1,68 @@
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::thread;
+use std::time::Duration;
+
+const BENIGN_WORKERS: usize = 3;
+
+/// Random delay in 500..1500 ms without pulling in a rand crate; `RandomState` is
+/// seeded from the OS on every run.
+fn jitter_ms() -> u64 {
+    500 + RandomState::new().hash_one(std::process::id()) % 1000
+}
+
+fn benign(id: usize) {
+    let mut acc: u64 = id as u64;
+    loop {
+        for i in 0..10_000u64 {
+            acc = acc.wrapping_mul(6364136223846793005).wrapping_add(i);
+        }
+        std::hint::black_box(acc);
+        thread::sleep(Duration::from_millis(50));
+    }
+}
+
+#[inline(never)]
+fn faulty(delay_ms: u64) {
+    thread::sleep(Duration::from_millis(delay_ms));
+    let name = thread::current().name().unwrap_or("?").to_string();
+    println!("{name}: dereferencing a null pointer now");
+    let ptr: *mut u64 = std::ptr::null_mut();
+    unsafe {
+        std::ptr::write_volatile(ptr, 42);
+    }
+}
+
+fn main() {
+    println!(
+        "Starting {} workers (pid {})...",
+        BENIGN_WORKERS + 1,
+        std::process::id()
+    );
+
+    let mut handles = Vec::new();
+    for id in 0..BENIGN_WORKERS {
+        let name = format!("worker-{id}");
+        println!("spawning {name} (benign)");
+        handles.push(
+            thread::Builder::new()
+                .name(name)
+                .spawn(move || benign(id))
+                .expect("spawn benign worker"),
+        );
+    }
+
+    let delay_ms = jitter_ms();
+    println!("spawning worker-faulty (null write in {delay_ms} ms)");
+    handles.push(
+        thread::Builder::new()
+            .name("worker-faulty".into())
+            .spawn(move || faulty(delay_ms))
+            .expect("spawn faulty worker"),
+    );
+
+    // Block on the joins so the process stays alive (and attachable) until the fault.
+    for handle in handles {
+        let _ = handle.join();
+    }
+}
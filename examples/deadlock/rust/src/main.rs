@@ -0,0 +1,45 @@
+use std::sync::{Arc, Barrier, Mutex};
+use std::thread;
+
+fn worker(
+    name: &'static str,
+    first: Arc<Mutex<u32>>,
+    second: Arc<Mutex<u32>>,
+    order: (&str, &str),
+    ready: Arc<Barrier>,
+) {
+    println!("{name}: acquiring {} then {}", order.0, order.1);
+    let _held = first.lock().unwrap();
+    println!("{name}: holding {}, waiting for {}", order.0, order.1);
+    // Both threads hold their first lock before either asks for the second one,
+    // so the circular wait below is guaranteed rather than timing dependent.
+    ready.wait();
+    let _never = second.lock().unwrap();
+    println!("{name}: acquired both locks (unexpected)");
+}
+
+fn main() {
+    let lock_a = Arc::new(Mutex::new(0));
+    let lock_b = Arc::new(Mutex::new(0));
+    let ready = Arc::new(Barrier::new(2));
+
+    println!("Starting deadlock demo (pid {})...", std::process::id());
+
+    let one = {
+        let (a, b, ready) = (Arc::clone(&lock_a), Arc::clone(&lock_b), Arc::clone(&ready));
+        thread::Builder::new()
+            .name("worker_one".into())
+            .spawn(move || worker("worker_one", a, b, ("lock_a", "lock_b"), ready))
+            .unwrap()
+    };
+    let two = {
+        let (a, b, ready) = (Arc::clone(&lock_a), Arc::clone(&lock_b), Arc::clone(&ready));
+        thread::Builder::new()
+            .name("worker_two".into())
+            .spawn(move || worker("worker_two", b, a, ("lock_b", "lock_a"), ready))
+            .unwrap()
+    };
+
+    one.join().unwrap();
+    two.join().unwrap();
+}
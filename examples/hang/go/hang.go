@@ -2,45 +2,33 @@ package main
 
 import (
 	"fmt"
-	"sync"
 	"time"
 )
 
-var (
-	lockA sync.Mutex
-	lockB sync.Mutex
-)
-
-func workerOne(wg *sync.WaitGroup) {
-	defer wg.Done()
-	fmt.Println("workerOne locking A")
-	lockA.Lock()
-	time.Sleep(500 * time.Millisecond)
-	fmt.Println("workerOne locking B")
-	lockB.Lock()
-	fmt.Println("workerOne acquired both locks (unexpected)")
-	lockB.Unlock()
-	lockA.Unlock()
+// worker drains jobs, but only after main closes start.
+func worker(jobs <-chan int, start <-chan struct{}) {
+	<-start
+	for job := range jobs {
+		fmt.Println("worker got job", job)
+	}
 }
 
-func workerTwo(wg *sync.WaitGroup) {
-	defer wg.Done()
-	fmt.Println("workerTwo locking B")
-	lockB.Lock()
-	time.Sleep(500 * time.Millisecond)
-	fmt.Println("workerTwo locking A")
-	lockA.Lock()
-	fmt.Println("workerTwo acquired both locks (unexpected)")
-	lockA.Unlock()
-	lockB.Unlock()
+// heartbeat keeps a timer pending, so the runtime's own "all goroutines are
+// asleep" check never fires and the process hangs the way a service would.
+func heartbeat() {
+	for {
+		time.Sleep(time.Second)
+	}
 }
 
 func main() {
-	var wg sync.WaitGroup
+	jobs := make(chan int)
+	start := make(chan struct{})
 
-	fmt.Println("Go deadlock demo starting...")
-	wg.Add(2)
-	go workerOne(&wg)
-	go workerTwo(&wg)
-	wg.Wait()
+	fmt.Println("Go hang demo: sending a job before the worker is started...")
+	go heartbeat()
+	go worker(jobs, start)
+	jobs <- 1 // blocks forever: the worker is still waiting on start
+	close(start)
+	close(jobs)
 }
@@ -0,0 +1,42 @@
+//! Adds to a `u8` counter that is already at `u8::MAX`.
+//!
+//! Usage: `rust_overflow [ADDEND]` (default 1). The addend comes from argv so the
+//! compiler cannot fold the addition away. Debug builds panic with "attempt to add
+//! with overflow" at the addition; release builds wrap to a small value and carry on
+//! with a wrong result.
+
+use std::env;
+use std::process;
+
+/// Slots in a bin; a `u8` is plenty as long as nothing ever exceeds 255.
+const BIN_CAPACITY: u8 = u8::MAX;
+
+#[inline(never)]
+fn restock(level: u8, delivered: u8) -> u8 {
+    let x: u8 = level;
+    x + delivered
+}
+
+#[inline(never)]
+fn needs_reorder(level: u8) -> bool {
+    level < BIN_CAPACITY / 4
+}
+
+fn main() {
+    let delivered: u8 = match env::args().nth(1) {
+        Some(arg) => arg.parse().unwrap_or_else(|_| {
+            eprintln!("usage: rust_overflow [ADDEND 0-255]");
+            process::exit(2);
+        }),
+        None => 1,
+    };
+
+    let level = BIN_CAPACITY;
+    println!("bin holds {level}/{BIN_CAPACITY}, delivering {delivered} more");
+    let level = restock(level, delivered);
+    println!("bin now holds {level}");
+    if needs_reorder(level) {
+        // Only reachable after a wrap: the full bin now looks almost empty.
+        println!("stock low, placing reorder");
+    }
+}
@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+use std::env;
+use std::panic;
+
+/// `PANIC_MODE=abort` turns the panic into `abort()` (SIGABRT with the panicking
+/// frames still on the stack); anything else unwinds back to `main` and exits 101.
+fn install_panic_mode() -> &'static str {
+    match env::var("PANIC_MODE").as_deref() {
+        Ok("abort") => {
+            let default_hook = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                default_hook(info);
+                std::process::abort();
+            }));
+            "abort"
+        }
+        _ => "unwind",
+    }
+}
+
+fn load_config() -> HashMap<&'static str, &'static str> {
+    let mut config = HashMap::new();
+    config.insert("listen", "127.0.0.1:8080");
+    config.insert("workers", "4");
+    config
+}
+
+#[inline(never)]
+fn lookup<'a>(config: &HashMap<&str, &'a str>, key: &str) -> &'a str {
+    // The bug: "database_url" was never inserted, so this is `None.unwrap()`.
+    config.get(key).copied().unwrap()
+}
+
+#[inline(never)]
+fn connect(config: &HashMap<&str, &str>) -> usize {
+    let url = lookup(config, "database_url");
+    url.len()
+}
+
+#[inline(never)]
+fn start_service(config: &HashMap<&str, &str>) -> usize {
+    let workers: usize = lookup(config, "workers").parse().unwrap_or(1);
+    workers * connect(config)
+}
+
+fn main() {
+    let mode = install_panic_mode();
+    println!(
+        "Starting service (panic mode: {mode}, pid {})...",
+        std::process::id()
+    );
+    let config = load_config();
+    let pool = start_service(&config);
+    println!("connected pool of {pool}");
+}
@@ -0,0 +1,54 @@
+//! Recurses until the main thread runs off the end of its stack.
+//!
+//! Usage: `rust_stack_overflow [DEPTH]`. Without an argument the recursion is
+//! unbounded and the process dies on the stack guard page (SIGSEGV on Linux/macOS,
+//! `STATUS_STACK_OVERFLOW` / 0xC00000FD on Windows). With a depth the program stops
+//! after that many frames, which is handy for comparing a deep-but-healthy stack with
+//! a real overflow.
+
+use std::env;
+use std::hint::black_box;
+use std::process;
+
+/// Bytes of stack consumed by every `descend` frame; big enough to hit the guard
+/// page after a few hundred calls with the default 8 MiB main-thread stack.
+const FRAME_BYTES: usize = 32 * 1024;
+
+#[inline(never)]
+fn descend(depth: u64, limit: Option<u64>) -> u64 {
+    let mut buffer = [0u8; FRAME_BYTES];
+    // Touch the buffer so the frame is really materialized, even in release builds.
+    buffer[0] = depth as u8;
+    black_box(&mut buffer);
+    if depth.is_multiple_of(64) {
+        println!("depth {depth}: frame buffer at {:p}", buffer.as_ptr());
+    }
+    if limit.is_some_and(|max| depth >= max) {
+        return depth;
+    }
+    let reached = descend(depth + 1, limit);
+    black_box(buffer[0]);
+    reached
+}
+
+fn main() {
+    let limit = match env::args().nth(1) {
+        None => None,
+        Some(arg) => match arg.parse::<u64>() {
+            Ok(depth) => Some(depth),
+            Err(_) => {
+                eprintln!("usage: rust_stack_overflow [DEPTH]");
+                process::exit(2);
+            }
+        },
+    };
+
+    match limit {
+        Some(depth) => println!("Recursing to depth {depth} ({FRAME_BYTES} bytes per frame)..."),
+        None => println!(
+            "Recursing without a limit ({FRAME_BYTES} bytes per frame); expect a stack overflow..."
+        ),
+    }
+    let reached = descend(1, limit);
+    println!("Returned normally after {reached} frames.");
+}
@@ -0,0 +1,70 @@
+//! Compiles `use_after_free.cpp` with the system C++ compiler and links the object
+//! into the Rust binary, so `cargo build` (and `cargo xtask build-examples`) produce
+//! the C++ crash target without a separate make step.
+//!
+//! The compiler is `$CXX`, or `cl` / `c++` by default. No `cc` crate: the examples
+//! stay dependency-free so they build offline.
+
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+const SOURCE: &str = "use_after_free.cpp";
+
+fn main() {
+    println!("cargo:rerun-if-changed={SOURCE}");
+    println!("cargo:rerun-if-env-changed=CXX");
+
+    let out_dir = PathBuf::from(env::var_os("OUT_DIR").expect("cargo sets OUT_DIR"));
+    let msvc = env::var("CARGO_CFG_TARGET_ENV").as_deref() == Ok("msvc");
+    let optimize = env::var("OPT_LEVEL").is_ok_and(|level| level != "0");
+    let cxx = env::var("CXX").unwrap_or_else(|_| if msvc { "cl" } else { "c++" }.into());
+
+    let object = out_dir.join(if msvc {
+        "use_after_free.obj"
+    } else {
+        "use_after_free.o"
+    });
+    let mut cmd = Command::new(&cxx);
+    if msvc {
+        cmd.args([
+            "/nologo",
+            "/c",
+            "/Zi",
+            "/EHsc",
+            if optimize { "/O2" } else { "/Od" },
+        ])
+        .arg(format!("/Fo{}", object.display()))
+        .arg(format!(
+            "/Fd{}",
+            out_dir.join("use_after_free.pdb").display()
+        ));
+    } else {
+        cmd.args(["-c", "-g", "-fno-omit-frame-pointer", "-Wall", "-Wextra"])
+            .arg(if optimize { "-O2" } else { "-O0" })
+            .arg("-o")
+            .arg(&object);
+    }
+    cmd.arg(SOURCE);
+
+    let status = cmd.status().unwrap_or_else(|err| {
+        panic!("failed to run C++ compiler `{cxx}`: {err} (set CXX to override)")
+    });
+    assert!(
+        status.success(),
+        "`{cxx}` failed to compile {SOURCE}: {status}"
+    );
+
+    println!("cargo:rustc-link-arg={}", object.display());
+    // MSVC objects name their C++ runtime themselves; elsewhere link it explicitly.
+    if !msvc {
+        let libcxx = matches!(
+            env::var("CARGO_CFG_TARGET_OS").as_deref(),
+            Ok("macos" | "ios" | "freebsd")
+        );
+        println!(
+            "cargo:rustc-link-lib={}",
+            if libcxx { "c++" } else { "stdc++" }
+        );
+    }
+}
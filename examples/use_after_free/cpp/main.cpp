@@ -0,0 +1,6 @@
+// Standalone entry point for the Makefile build; the Cargo build calls uaf_demo_main from src/main.rs.
+extern "C" int uaf_demo_main();
+
+int main() {
+    return uaf_demo_main();
+}
@@ -0,0 +1,15 @@
+//! Cargo entry point for the C++ use-after-free example.
+//!
+//! All of the interesting code is in `use_after_free.cpp`, which `build.rs`
+//! compiles and links in. This just hands control to it, so the crash happens in
+//! C++ frames (`uaf_demo::on_retry_timer`, then the allocator) of this process.
+
+extern "C" {
+    fn uaf_demo_main() -> i32;
+}
+
+fn main() {
+    // SAFETY: `uaf_demo_main` takes no arguments and only uses the C++ runtime.
+    let code = unsafe { uaf_demo_main() };
+    std::process::exit(code);
+}
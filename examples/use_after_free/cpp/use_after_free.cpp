@@ -0,0 +1,67 @@
+// Use-after-free in C++: a request is deleted while a retry timer still points at it.
+//
+// glibc (2.32+): `delete` puts the 32-byte Request into the tcache, whose free-list
+// link lives in the first word (`retries`). The stale `retries++` corrupts that link,
+// so two allocations later malloc aborts with "malloc(): unaligned tcache chunk
+// detected" (SIGABRT). Two requests are freed first so the corrupted link is followed.
+#include <cstdint>
+#include <cstdio>
+#include <cstring>
+
+#if defined(_MSC_VER)
+#define UAF_NOINLINE __declspec(noinline)
+#else
+#define UAF_NOINLINE __attribute__((noinline))
+#endif
+
+namespace uaf_demo {
+
+struct Request {
+    std::uint64_t retries;
+    std::uint64_t bytes;
+    char path[16];
+};
+
+// A retry timer that outlives the request it was armed for.
+Request *volatile g_retry = nullptr;
+
+UAF_NOINLINE Request *open_request(const char *path) {
+    Request *req = new Request{0, 0, {}};
+    std::strncpy(req->path, path, sizeof(req->path) - 1);
+    return req;
+}
+
+UAF_NOINLINE void finish(Request *req) {
+    std::printf("finished %s after %llu retries\n", req->path, static_cast<unsigned long long>(req->retries));
+    delete req;  // bug: g_retry may still point here
+}
+
+UAF_NOINLINE void on_retry_timer() {
+    Request *req = g_retry;
+    req->retries++;  // use after free: writes into the freed chunk
+    std::printf("retry timer fired for a freed request (retries word now %#llx)\n",
+                static_cast<unsigned long long>(req->retries));
+}
+
+}  // namespace uaf_demo
+
+extern "C" int uaf_demo_main() {
+    using namespace uaf_demo;
+    std::setvbuf(stdout, nullptr, _IONBF, 0);  // no stdio allocations between the frees
+
+    Request *index = open_request("/index.html");
+    Request *upload = open_request("/upload");
+    g_retry = upload;
+    std::printf("allocated requests at %p and %p\n", static_cast<void *>(index), static_cast<void *>(upload));
+
+    finish(index);
+    finish(upload);
+    on_retry_timer();
+
+    // The first allocation gets `upload`'s chunk back; the second follows the corrupted link.
+    Request *next = open_request("/next");
+    std::printf("next request reused %p\n", static_cast<void *>(next));
+    Request *after = open_request("/after");
+    std::printf("no allocator check fired; request landed at %p\n", static_cast<void *>(after));
+    return 0;
+}
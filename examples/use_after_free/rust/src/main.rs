@@ -0,0 +1,72 @@
+//! Accesses a `Box` allocation after it has been freed.
+//!
+//! The program allocates a `Box<[u64; 4]>`, keeps its raw pointer, drops the box,
+//! and then reads and writes through the dangling pointer.
+//!
+//! What to expect:
+//!
+//! - **glibc (Linux):** the freed chunk goes into the tcache, which stores its
+//!   free-list link in the first word. The stale read shows that link instead of the
+//!   value written before the free. The next allocation of the same size gets the
+//!   freed chunk back (so the dangling pointer now aliases live data), and the one
+//!   after that follows the corrupted link and aborts with
+//!   `malloc(): unaligned tcache chunk detected` (SIGABRT).
+//! - **Windows heap:** the freed block is often reused without complaint, so the
+//!   program may exit normally. With page heap enabled (`gflags /p /enable`), the
+//!   first stale read faults with an access violation.
+//! - **Hardened allocators (ASan, scudo, hardened_malloc):** the stale access is
+//!   reported right away as a heap-use-after-free, or the freed memory is
+//!   quarantined and the access faults.
+//!
+//! Build with `--features double-free` to free the allocation a second time instead.
+//! glibc then reliably aborts with `free(): double free detected in tcache 2`, which
+//! is the deterministic variant to use under a debugger.
+
+#[inline(never)]
+fn allocate() -> *mut [u64; 4] {
+    let boxed = Box::new([0x1111_1111_1111_1111u64; 4]);
+    Box::into_raw(boxed)
+}
+
+#[inline(never)]
+fn release(ptr: *mut [u64; 4]) {
+    // SAFETY: `ptr` came from `Box::into_raw` and has not been freed yet (on the
+    // first call); the double-free build calls this twice on purpose.
+    drop(unsafe { Box::from_raw(ptr) });
+}
+
+#[inline(never)]
+fn use_after_free(ptr: *mut [u64; 4]) {
+    // SAFETY: none. This is the bug the example exists to demonstrate.
+    unsafe {
+        let stale = std::ptr::read_volatile(&(*ptr)[0]);
+        println!("stale read after free: {stale:#018x} (was 0x1111111111111111)");
+        std::ptr::write_volatile(&mut (*ptr)[0], 0xDEAD_BEEF_DEAD_BEEF);
+        println!("wrote 0xdeadbeefdeadbeef through the dangling pointer");
+    }
+}
+
+fn main() {
+    let ptr = allocate();
+    println!("allocated [u64; 4] at {ptr:p}");
+    release(ptr);
+    println!("freed {ptr:p}");
+
+    if cfg!(feature = "double-free") {
+        println!("freeing {ptr:p} a second time...");
+        release(ptr);
+    } else {
+        use_after_free(ptr);
+    }
+
+    // Reuse the same size class; a corrupted free list is usually noticed here.
+    let again = Box::new([0x2222_2222_2222_2222u64; 4]);
+    let again_ptr: *const [u64; 4] = &*again;
+    println!("next allocation of the same size landed at {again_ptr:p}");
+    if again_ptr == ptr.cast_const() {
+        println!("the freed chunk was handed out again; the dangling pointer now aliases it");
+    }
+    // The allocation after that follows the free-list link we overwrote.
+    let third = Box::new([0x3333_3333_3333_3333u64; 4]);
+    println!("one more allocation landed at {:p}", &*third);
+}
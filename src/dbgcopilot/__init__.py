@@ -4,7 +4,22 @@ This module exposes version metadata and basic placeholders.
 """
 
 __all__ = [
+    "AnalysisReport",
+    "AnalyzeRequest",
     "__version__",
+    "analyze",
+    "explain_stream",
 ]
 
 __version__ = "0.0.1-poc"
+
+_ANALYSIS_EXPORTS = {"AnalysisReport", "AnalyzeRequest", "analyze", "explain_stream"}
+
+
+def __getattr__(name: str):
+    # Imported lazily so loading the debugger plugins does not pull in the analyzers.
+    if name in _ANALYSIS_EXPORTS:
+        from dbgcopilot.analysis import api
+
+        return getattr(api, name)
+    raise AttributeError(f"module 'dbgcopilot' has no attribute {name!r}")
@@ -0,0 +1,171 @@
+"""Structured crash/hang analysis helpers.
+
+The interactive REPLs hand raw debugger text to the LLM. This package turns
+debugger output into typed frames, registers, and stop information so the
+analysis can be driven programmatically.
+"""
+from __future__ import annotations
+
+from .api import (
+    AnalysisError,
+    AnalysisReport,
+    AnalyzeRequest,
+    Outcome,
+    add_fix_suggestion,
+    analyze,
+    analyze_session,
+    explain,
+    explain_stream,
+)
+from .batch import BatchEntry, BatchGroup, BatchResult, analyze_batch
+from .cache import ExplanationCache, crash_signature
+from .conversation import ConversationState
+from .crash import CrashReport
+from .deadlock import DeadlockReport, LockRef, LockWaiter, WaitLink, detect_deadlocks, find_deadlocks, wait_chain
+from .diff import DiffVerdict, ReportDiff, diff_reports
+from .disasm import FaultContext, build_fault_context
+from .enrich import ReportEnricher, SystemModuleEnricher, register_enricher, unregister_enricher
+from .exception import ExceptionKind, ExceptionPolicy, NormalizedException, normalize_stop, si_code_name
+from .fault import FaultKind, classify_fault, fault_hint
+from .fix import FixSuggestion, suggest_fix
+from .frames import FrameRules
+from .goroutines import Goroutine, classify_goroutines, parse_goroutine_dump
+from .hang import DominantFrame, HangConfig, HangReport, Watchdog
+from .hang_kind import HangCategory, HangKind, ThreadState, ThreadSummary, analyze_hang, classify_threads
+from .heap import HeapSummary, SizeBucket
+from .language import crash_language, detect_language
+from .model import (
+    DEFAULT_LOCALS_FRAMES,
+    AccessKind,
+    Annotation,
+    CrashContext,
+    Frame,
+    Instruction,
+    Language,
+    Module,
+    Optimized,
+    PanicInfo,
+    Register,
+    StopInfo,
+    ThreadBacktrace,
+    Variable,
+)
+from .modules import ModuleConflict, find_module_conflicts
+from .panic import find_panic
+from .progress import ProgressEvent, ProgressSink, ProgressStage
+from .prompt import BuiltPrompt, FrameBudget, PromptBuilder, PromptElision
+from .redact import Redaction, RedactionRule, Redactor
+from .regions import MapEntry, MemoryRegion, RegionKind, classify_address
+from .registers import Architecture, Registers, describe_address_source
+from .render import render_report
+from .repro import Repro, build_repro
+from .store import CrashStore, StoredSession
+from .template import PromptTemplate, TemplateError
+from .v8 import EngineRole, label_engine_frames
+
+__all__ = [
+    "AccessKind",
+    "AnalysisError",
+    "AnalysisReport",
+    "AnalyzeRequest",
+    "Annotation",
+    "Architecture",
+    "BatchEntry",
+    "BatchGroup",
+    "BatchResult",
+    "BuiltPrompt",
+    "ConversationState",
+    "CrashContext",
+    "CrashReport",
+    "CrashStore",
+    "DEFAULT_LOCALS_FRAMES",
+    "DeadlockReport",
+    "DiffVerdict",
+    "DominantFrame",
+    "EngineRole",
+    "ExceptionKind",
+    "ExceptionPolicy",
+    "ExplanationCache",
+    "FaultContext",
+    "FaultKind",
+    "FixSuggestion",
+    "Frame",
+    "FrameBudget",
+    "FrameRules",
+    "Goroutine",
+    "HangCategory",
+    "HangConfig",
+    "HangKind",
+    "HangReport",
+    "HeapSummary",
+    "Instruction",
+    "Language",
+    "LockRef",
+    "LockWaiter",
+    "MapEntry",
+    "MemoryRegion",
+    "Module",
+    "ModuleConflict",
+    "NormalizedException",
+    "Optimized",
+    "Outcome",
+    "PanicInfo",
+    "ProgressEvent",
+    "ProgressSink",
+    "ProgressStage",
+    "PromptBuilder",
+    "PromptElision",
+    "PromptTemplate",
+    "Redaction",
+    "RedactionRule",
+    "Redactor",
+    "RegionKind",
+    "Register",
+    "Registers",
+    "ReportDiff",
+    "ReportEnricher",
+    "Repro",
+    "SizeBucket",
+    "StopInfo",
+    "StoredSession",
+    "SystemModuleEnricher",
+    "TemplateError",
+    "ThreadBacktrace",
+    "ThreadState",
+    "ThreadSummary",
+    "Variable",
+    "WaitLink",
+    "Watchdog",
+    "add_fix_suggestion",
+    "analyze",
+    "analyze_batch",
+    "analyze_hang",
+    "analyze_session",
+    "build_fault_context",
+    "build_repro",
+    "classify_address",
+    "classify_fault",
+    "classify_goroutines",
+    "classify_threads",
+    "crash_language",
+    "crash_signature",
+    "describe_address_source",
+    "detect_deadlocks",
+    "detect_language",
+    "diff_reports",
+    "explain",
+    "explain_stream",
+    "fault_hint",
+    "find_deadlocks",
+    "find_module_conflicts",
+    "find_panic",
+    "label_engine_frames",
+    "normalize_stop",
+    "parse_goroutine_dump",
+    "register_enricher",
+    "render_report",
+    "si_code_name",
+    "suggest_fix",
+    "unregister_enricher",
+    "wait_chain",
+]
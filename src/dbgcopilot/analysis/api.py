@@ -0,0 +1,1070 @@
+"""Programmatic entry point: analyze a binary or core file in one call.
+
+`analyze(AnalyzeRequest(...))` captures the crash (or hang), classifies it, and
+optionally asks an LLM provider for an explanation, returning everything as an
+`AnalysisReport` so callers such as test harnesses can assert on fields
+instead of scraping REPL output.
+"""
+from __future__ import annotations
+
+import json
+import logging
+import os
+import signal
+import subprocess
+import tempfile
+from dataclasses import dataclass, field
+from enum import Enum
+from typing import TYPE_CHECKING, Any, Callable, Dict, List, Optional, Sequence, Tuple, Union
+
+from dbgcopilot.utils.process import kill_tree
+from dbgcopilot.utils.trace import span
+
+from .cache import ExplanationCache, crash_signature
+from .crash import DEFAULT_MAX_FRAMES, CrashReport, _hex
+from .deadlock import wait_chain
+from .disasm import FaultContext
+from .enrich import ReportEnricher, enrich_report
+from .exception import SECOND_CHANCE_SIGNALS, ExceptionPolicy
+from .fault import FaultKind, classify_fault, fault_hint
+from .fix import FixSuggestion
+from .frames import FrameRules
+from .goroutines import Goroutine, classify_goroutines, parse_goroutine_dump
+from .hang import HangReport, Watchdog
+from .hang_kind import HangKind, classify_threads
+from .heap import HeapSummary
+from .language import detect_language, script_command
+from .model import (
+    DEFAULT_LOCALS_FRAMES,
+    Annotation,
+    CrashContext,
+    Frame,
+    Language,
+    Module,
+    PanicInfo,
+    Register,
+    StopInfo,
+    ThreadBacktrace,
+)
+from .panic import find_panic
+from .progress import ProgressSink, ProgressStage, report_progress
+from .prompt import DEFAULT_MAX_PROMPT_TOKENS, BuiltPrompt, PromptBuilder, PromptElision, prompt_fault_context
+from .redact import Redactor
+from .regions import MapEntry, MemoryRegion, fault_region
+from .render import render_report
+from .template import DEFAULT_PROMPT_TEMPLATE, template_from_config
+from .threads import DEFAULT_MAX_FRAMES_PER_THREAD, limit_threads
+
+if TYPE_CHECKING:
+    from dbgcopilot.backends.base import DebugSession, SessionBackend
+    from dbgcopilot.llm.base import Completion, LlmProvider
+    from dbgcopilot.llm.usage import Usage, UsageMeter
+    from dbgcopilot.symbols import SymbolPath
+
+    from .deadlock import LockRef, WaitLink
+
+log = logging.getLogger(__name__)
+
+# Stack samples taken once a run exceeds the hang timeout.
+HANG_SAMPLE_INTERVAL_MS = 200
+HANG_SAMPLE_DURATION_MS = 1000
+# Seconds a hung Go program gets to print its goroutines after SIGQUIT.
+GOROUTINE_DUMP_TIMEOUT = 5.0
+
+# Version of the `AnalysisReport.to_dict` layout. Adding keys keeps it;
+# renaming, removing, or retyping one bumps it.
+REPORT_SCHEMA_VERSION = 1
+
+
+class AnalysisError(RuntimeError):
+    """Raised when a crash cannot be captured (missing binary, no debugger, ...)."""
+
+
+class Outcome(str, Enum):
+    """How the analyzed program's run ended, as `AnalysisReport.outcome` reports it."""
+
+    CRASHED = "crashed"
+    HUNG = "hung"
+    # Still running at the time limit with hang sampling off (or a debugger run that never faulted).
+    NO_CRASH_WITHIN_TIMEOUT = "no_crash_within_timeout"
+    EXITED = "exited"
+
+
+@dataclass
+class AnalyzeRequest:
+    # Program to run, or whose symbols a core needs; "" for a dump read without one.
+    binary: str
+    core: Optional[str] = None
+    # Live process to attach to instead of launching ``binary``. If it has
+    # already exited, ``core`` is analyzed instead.
+    pid: Optional[int] = None
+    args: Sequence[str] = ()
+    # Seconds the program may run before it is treated as hung.
+    timeout: float = 30.0
+    # Sample a program still running at ``timeout`` as a hang; False just kills it
+    # and reports `Outcome.NO_CRASH_WITHIN_TIMEOUT`.
+    sample_hangs: bool = True
+    # "gdb" or "lldb"; None picks the first one on PATH.
+    debugger: Optional[str] = None
+    # Separate debug file of ``binary`` (stripped builds), instead of the build-id / debuglink search.
+    debug_file: Optional[str] = None
+    # Symbol path in DbgEng syntax (``/srv/symbols;srv*CACHE*https://debuginfod.example.org``): where
+    # library debug files are searched for and downloaded from; see `dbgcopilot.symbols.sympath`.
+    symbol_path: Optional[str] = None
+    # Engine that opens ``core`` instead of the built-in readers, e.g. a CdbBackend.
+    backend: Optional["SessionBackend"] = None
+    # LLM provider name (see docs/llm.md) or an LlmProvider instance; None skips the explanation.
+    provider: Union[str, "LlmProvider", None] = None
+    llm_config: Dict[str, str] = field(default_factory=dict)
+    # Called with each explanation fragment as the provider streams it.
+    on_explain_chunk: Optional[Callable[[str], None]] = None
+    # Reuse explanations of crashes with the same signature; None always asks the provider.
+    cache: Optional[ExplanationCache] = None
+    # Build the explanation prompt but never call a model (offline runs, CI); ``provider`` is ignored.
+    no_llm: bool = False
+    # After the explanation, ask the provider for a code change too (a second LLM call).
+    suggest_fix: bool = False
+    # Called with a `ProgressEvent` as each stage starts, for status bars; see `dbgcopilot.analysis.progress`.
+    progress: Optional[ProgressSink] = None
+    # Frames from the top whose arguments and locals are read when a debugger captures the crash.
+    locals_frames: int = DEFAULT_LOCALS_FRAMES
+    # Frames kept of each thread's stack, on every capture path; 0 keeps them all. A stack cut
+    # short is marked `ThreadBacktrace.truncated`.
+    max_frames_per_thread: int = DEFAULT_MAX_FRAMES_PER_THREAD
+    # Signals (``SIGSEGV``, ``segv``, or 11) the program may take and handle itself: when a debugger
+    # reruns it, these are passed to its handlers and only a fatal one is reported.
+    ignore_signals: List[str] = field(default_factory=list)
+    # Which chance of a fault a launch under a debugger captures on; see `ExceptionPolicy`. Second chance
+    # passes the faults the program has handlers for to them, and analyzes only a fatal one.
+    exception_policy: ExceptionPolicy = ExceptionPolicy.SECOND_CHANCE
+    # Launch ``binary`` under the debugger from the start, so a fault is captured on the run that
+    # hit it instead of a rerun; for faults that do not happen every time (``dbgcopilot run``).
+    under_debugger: bool = False
+    # Enrichers that annotate the report before the prompt is built; None runs the registered ones
+    # (see `dbgcopilot.analysis.enrich`), and an empty list none.
+    enrichers: Optional[Sequence[ReportEnricher]] = None
+
+
+@dataclass
+class AnalysisReport:
+    fault_kind: FaultKind
+    stop: StopInfo
+    frames: List[Frame] = field(default_factory=list)
+    registers: List[Register] = field(default_factory=list)
+    fault_hint: Optional[str] = None
+    explanation: Optional[str] = None
+    explanation_error: Optional[str] = None
+    # The explanation came from `AnalyzeRequest.cache`, not a fresh LLM call.
+    explanation_cached: bool = False
+    # ``explanation`` is only the start of an answer whose stream broke; see ``explanation_error``.
+    explanation_partial: bool = False
+    hang: Optional[HangReport] = None
+    # Deadlock / busy loop / blocked, from the sampled stacks; set with ``hang``.
+    hang_kind: Optional[HangKind] = None
+    modules: List[Module] = field(default_factory=list)
+    # Where the data came from: "core", "minidump", "gdb", "lldb", "cdb", "attach", or "run" for a clean exit.
+    source: str = ""
+    # OS thread id of the faulting thread, when the capture path reports it.
+    thread_id: Optional[int] = None
+    # Command line (argv[0] first) and environment of the analyzed process, when known.
+    args: List[str] = field(default_factory=list)
+    environ: Dict[str, str] = field(default_factory=dict)
+    language: Optional[Language] = None
+    # The redacted prompt sent to (or, with ``no_llm``, built for) the model.
+    prompt: Optional[str] = None
+    # Frames the explanation prompt summarized to fit its token budget; set with ``prompt``.
+    prompt_elision: Optional[PromptElision] = None
+    # Every thread's stack at the fault, the faulting one included, when the capture path has them.
+    threads: List[ThreadBacktrace] = field(default_factory=list)
+    # The proposed fix, with `AnalyzeRequest.suggest_fix`; why there is none is in ``fix_suggestion_error``.
+    fix_suggestion: Optional[FixSuggestion] = None
+    fix_suggestion_error: Optional[str] = None
+    # Which frames count as the program's own; `analyze` reads them from ``llm_config``, None is the defaults.
+    frame_rules: Optional[FrameRules] = None
+    # Heap statistics and corruption at the fault, from engines that report them; see `HeapSummary`.
+    heap: Optional[HeapSummary] = None
+    # The memory region the fault address is in; see `dbgcopilot.analysis.regions`.
+    fault_region: Optional[MemoryRegion] = None
+    # The registers and instructions around the faulting pc; see `dbgcopilot.analysis.disasm`.
+    fault_context: Optional[FaultContext] = None
+    # The Rust panic behind the stop, its message and location; see `dbgcopilot.analysis.panic`.
+    panic: Optional[PanicInfo] = None
+    # Notes the enrichers added; see `dbgcopilot.analysis.enrich`.
+    annotations: List[Annotation] = field(default_factory=list)
+    # Tokens and estimated cost of the explanation and fix calls; None until one is made.
+    usage: Optional["Usage"] = None
+    # What the capture could not do, such as symbolizing an image within its time limit.
+    warnings: List[str] = field(default_factory=list)
+
+    @property
+    def crashed(self) -> bool:
+        return self.stop.crashed
+
+    @property
+    def hung(self) -> bool:
+        return self.hang is not None
+
+    @property
+    def outcome(self) -> Outcome:
+        if self.hung:
+            return Outcome.HUNG
+        if self.crashed:
+            return Outcome.CRASHED
+        if self.stop.timed_out:
+            return Outcome.NO_CRASH_WITHIN_TIMEOUT
+        return Outcome.EXITED
+
+    def context(self) -> CrashContext:
+        return CrashContext(
+            stop=self.stop,
+            frames=self.frames,
+            registers=self.registers,
+            modules=self.modules,
+            args=self.args,
+            environ=self.environ,
+            language=self.language,
+            threads=self.threads,
+            heap=self.heap,
+            fault_region=self.fault_region,
+            fault_context=self.fault_context,
+            panic=self.panic,
+        )
+
+    def crash_report(self, max_frames: int = DEFAULT_MAX_FRAMES) -> CrashReport:
+        return CrashReport.from_context(
+            self.context(),
+            thread_id=self.thread_id,
+            max_frames=max_frames,
+            source=self.source,
+            fault_kind=self.fault_kind,
+            hint=self.fault_hint,
+            frame_rules=self.frame_rules,
+            hang_category=self.hang_kind.category if self.hang_kind is not None else None,
+            annotations=self.annotations,
+        )
+
+    def to_dict(self) -> Dict[str, Any]:
+        """The whole report as JSON-ready data, every captured frame included.
+
+        Frames, modules, and addresses use the `CrashReport.to_dict` layout.
+        ``version`` is `REPORT_SCHEMA_VERSION`; keys that do not apply are
+        ``None`` rather than missing, so consumers can rely on the shape.
+        """
+        data: Dict[str, Any] = {"version": REPORT_SCHEMA_VERSION, "outcome": self.outcome.value}
+        data.update(self.crash_report(max_frames=len(self.frames)).to_dict())
+        data["exit_code"] = self.stop.exit_code
+        data["hang"] = _hang_dict(self.hang, self.hang_kind) if self.hang is not None else None
+        data["explanation"] = self.explanation
+        data["explanation_error"] = self.explanation_error
+        data["explanation_cached"] = self.explanation_cached
+        data["explanation_partial"] = self.explanation_partial
+        data["fix_suggestion"] = self.fix_suggestion.to_dict() if self.fix_suggestion is not None else None
+        data["fix_suggestion_error"] = self.fix_suggestion_error
+        data["usage"] = self.usage.to_dict() if self.usage is not None else None
+        data["warnings"] = list(self.warnings)
+        data["prompt"] = self.prompt
+        data["prompt_elision"] = None if self.prompt_elision is None else {
+            "collapsed": self.prompt_elision.collapsed,
+            "dropped_system": self.prompt_elision.dropped_system,
+            "dropped": self.prompt_elision.dropped,
+            "uncaptured": self.prompt_elision.uncaptured,
+        }
+        return data
+
+    def to_json(self, indent: Optional[int] = 2) -> str:
+        return json.dumps(self.to_dict(), indent=indent)
+
+    def render(self, with_explanation: bool = True, with_modules: bool = False) -> str:
+        """Plain-text report: the crash summary, then the explanation or why there is none.
+
+        A view over `to_dict` (see `render_report`), so text and JSON never disagree.
+        """
+        return render_report(self.to_dict(), with_explanation=with_explanation, with_modules=with_modules)
+
+
+def _frame_ref(frame: Optional[Frame]) -> Optional[Dict[str, Any]]:
+    if frame is None:
+        return None
+    return {"index": frame.index, "pc": _hex(frame.pc), "symbol": frame.function, "source_file": frame.file,
+            "line": frame.line}
+
+
+def _lock_ref(lock: "LockRef") -> Dict[str, Any]:
+    return {"address": _hex(lock.address), "symbol": lock.symbol}
+
+
+def _wait_link(link: "WaitLink") -> Dict[str, Any]:
+    return {
+        "thread_id": link.thread_id,
+        "tid": link.tid,
+        "name": link.name,
+        "frame": link.frame,
+        "waits_for": _lock_ref(link.lock) if link.lock is not None else None,
+        "owner": link.owner,
+        "owner_tid": link.owner_tid,
+        "owner_source": link.owner_source,
+    }
+
+
+def _hang_dict(hang: HangReport, kind: Optional[HangKind]) -> Dict[str, Any]:
+    return {
+        "category": kind.category.value if kind is not None else None,
+        "samples": len(hang.sampled_at),
+        "thread_count": hang.thread_count,
+        "ignored_threads": hang.ignored_threads,
+        "dominant_frames": [
+            {
+                "thread_id": d.thread_id,
+                "thread_name": d.thread_name,
+                "hits": d.hits,
+                "samples": d.samples,
+                "frame": _frame_ref(d.frame),
+            }
+            for d in hang.dominant_frames
+        ],
+        "threads": [
+            {
+                "thread_id": t.thread_id,
+                "tid": t.tid,
+                "name": t.name,
+                "state": t.state.value,
+                "frame": _frame_ref(t.frame),
+                "goroutine": t.goroutine,
+                "wait_reason": t.wait_reason,
+            }
+            for t in (kind.threads if kind is not None else [])
+        ],
+        "waiters": list(kind.waiters) if kind is not None else [],
+        "deadlocks": [
+            {
+                "cycle": list(d.cycle),
+                "threads": [
+                    {
+                        "thread_id": w.thread_id,
+                        "name": w.name,
+                        "waits_for": _lock_ref(w.waits_for),
+                        "holds": [_lock_ref(lock) for lock in w.holds],
+                    }
+                    for w in d.threads
+                ],
+            }
+            for d in (kind.deadlocks if kind is not None else [])
+        ],
+        "wait_chain": [_wait_link(link) for link in (kind.wait_chain if kind is not None else [])],
+    }
+
+
+def _report_from_context(ctx: CrashContext, source: str, thread_id: Optional[int] = None) -> AnalysisReport:
+    if ctx.panic is None:
+        # Capture paths without the program's output still have the panic path on the stack.
+        ctx.panic = find_panic(ctx)
+    return AnalysisReport(
+        fault_kind=classify_fault(ctx).kind,
+        stop=ctx.stop,
+        frames=list(ctx.frames),
+        registers=list(ctx.registers),
+        modules=list(ctx.modules),
+        fault_hint=fault_hint(ctx),
+        source=source,
+        thread_id=thread_id,
+        args=list(ctx.args),
+        environ=dict(ctx.environ),
+        language=ctx.language,
+        threads=list(ctx.threads),
+        heap=ctx.heap,
+        fault_region=ctx.fault_region,
+        fault_context=ctx.fault_context,
+        panic=ctx.panic,
+    )
+
+
+def _detect_backend(request: AnalyzeRequest) -> Any:
+    from dbgcopilot.backends.batch import BackendUnavailableError, detect_crash_backend
+
+    try:
+        return detect_crash_backend(request.debugger, timeout=max(request.timeout, 1.0) + 30.0)
+    except BackendUnavailableError as exc:
+        raise AnalysisError(str(exc)) from exc
+
+
+def _symbol_path(request: AnalyzeRequest) -> Optional["SymbolPath"]:
+    from dbgcopilot.symbols import SymbolPath
+
+    if not request.symbol_path:
+        return None
+    try:
+        return SymbolPath.parse(request.symbol_path)
+    except ValueError as exc:
+        raise AnalysisError(f"symbol_path: {exc}") from exc
+
+
+def _passed_signals(request: AnalyzeRequest) -> List[str]:
+    """The signals a launch hands to the program's handlers: ``ignore_signals``, plus the faults under second chance."""
+    from dbgcopilot.backends.signal_passthrough import signal_names
+
+    try:
+        names = signal_names(request.ignore_signals)
+    except ValueError as exc:
+        raise AnalysisError(f"ignore_signals: {exc}") from exc
+    if request.core or ExceptionPolicy(request.exception_policy) is ExceptionPolicy.FIRST_CHANCE:
+        return names
+    # Go's handler turns every fault into a panic that exits the process: nothing fatal for a second chance to see.
+    if detect_language(request.binary) is Language.GO:
+        return names
+    return names + [name for name in SECOND_CHANCE_SIGNALS if name not in names]
+
+
+def _give_symbol_path(backend: Any, symbol_path: Optional["SymbolPath"]) -> None:
+    """Point a debugger at the symbol path: cdb takes its text (and downloads PDBs itself), gdb its directories."""
+    if symbol_path is None:
+        return
+    if hasattr(backend, "symbol_path") and backend.symbol_path is None:
+        backend.symbol_path = symbol_path.text
+    if hasattr(backend, "debug_dirs"):
+        backend.debug_dirs = list(symbol_path.debug_dirs)
+
+
+def _capture_with_debugger(request: AnalyzeRequest) -> AnalysisReport:
+    from dbgcopilot.symbols import resolve_sources
+
+    symbol_path = _symbol_path(request)
+    backend = _detect_backend(request)
+    if request.under_debugger and not request.core and hasattr(backend, "timeout"):
+        # The program's own limit, not the slack a rerun of a known crash gets.
+        backend.timeout = request.timeout
+    if hasattr(backend, "locals_frames"):
+        backend.locals_frames = request.locals_frames
+    _give_symbol_path(backend, symbol_path)
+    if hasattr(backend, "ignore_signals"):
+        backend.ignore_signals = _passed_signals(request)
+    if hasattr(backend, "printer_dirs") and not request.core and detect_language(request.binary) is Language.RUST:
+        from dbgcopilot.backends.pretty_printers import rust_printer_dirs
+
+        backend.printer_dirs = rust_printer_dirs()
+    command = None if request.core else script_command(request.binary)
+    program = command[0] if command else request.binary
+    if request.core:
+        report_progress(
+            request.progress,
+            ProgressStage.READING_DUMP,
+            f"Opening {os.path.basename(request.core)} with {backend.name}",
+        )
+    else:
+        report_progress(
+            request.progress,
+            ProgressStage.LAUNCHING_TARGET,
+            f"Running {os.path.basename(request.binary)} under {backend.name}",
+        )
+    with span(log, "debugger.launch", backend=backend.name, program=program, core=request.core) as stage:
+        if command is not None:
+            # Debuggers cannot run a script itself; run its interpreter on it.
+            backend.load(command[0], args=[*command[1:], *request.args])
+        else:
+            backend.load(request.binary, core=request.core, args=request.args)
+        stop = backend.run_to_fault()
+        stage["signal"] = stop.signal
+    report_progress(request.progress, ProgressStage.CAPTURING_BACKTRACE, f"Capturing the backtrace with {backend.name}")
+    with span(log, "backtrace.capture", backend=backend.name) as stage:
+        frames, registers, modules = backend.backtrace(), backend.read_registers(), backend.modules()
+        threads = backend.threads()
+        variables = backend.frame_variables() if hasattr(backend, "frame_variables") else []
+        heap = backend.heap_summary() if hasattr(backend, "heap_summary") else None
+        memory_map = backend.memory_map() if hasattr(backend, "memory_map") else []
+        fault_context = backend.fault_context() if hasattr(backend, "fault_context") else None
+        for frame, found in zip(frames, variables):
+            frame.locals = found
+        stage["frames"], stage["modules"], stage["threads"] = len(frames), len(modules), len(threads)
+        stage["locals"] = sum(len(found) for found in variables)
+    report_progress(request.progress, ProgressStage.SYMBOLIZING, f"Symbolizing {len(frames)} frames")
+    with span(log, "symbolize", frames=len(frames)) as stage:
+        missing = sum(f.file is None for f in frames)
+        # Frames the debugger printed without file:line get them from the images' own line tables.
+        warnings: List[str] = []
+        frames = resolve_sources(frames, modules, program, request.debug_file, symbol_path, warnings)
+        stage["resolved"], stage["timed_out"] = missing - sum(f.file is None for f in frames), len(warnings)
+    ctx = CrashContext(
+        stop=stop,
+        frames=frames,
+        registers=registers,
+        modules=modules,
+        threads=threads,
+        heap=heap,
+        fault_context=fault_context,
+    )
+    # The program's own output is in the debugger's, AddressSanitizer's report included.
+    ctx.fault_region = fault_region(ctx, memory_map, getattr(backend, "raw_output", ""))
+    ctx.panic = find_panic(ctx, getattr(backend, "raw_output", ""))
+    faulting = next((t for t in threads if t.faulting), None)
+    report = _report_from_context(ctx, backend.name, thread_id=faulting.tid if faulting is not None else None)
+    report.warnings = warnings
+    return report
+
+
+def _session_context(session: "DebugSession", locals_frames: int = DEFAULT_LOCALS_FRAMES) -> CrashContext:
+    stop, frames = session.stop_info(), session.stack_trace()
+    for frame in frames[:locals_frames]:
+        try:
+            frame.locals = session.frame_locals(frame_index=frame.index)
+        except (NotImplementedError, RuntimeError):
+            # Best effort: an engine without variables (the built-in readers), or a replay that never asked.
+            break
+    ctx = CrashContext(
+        stop=stop,
+        frames=frames,
+        registers=session.read_registers(),
+        modules=session.modules(),
+        threads=session.threads(),
+        heap=_session_heap(session),
+        fault_context=_session_fault_context(session),
+    )
+    ctx.fault_region = fault_region(ctx, _session_memory_map(session))
+    return ctx
+
+
+def _session_heap(session: "DebugSession") -> Optional[HeapSummary]:
+    # Sessions written before `DebugSession.heap_summary` existed have none; a replay may not have recorded it.
+    if not hasattr(session, "heap_summary"):
+        return None
+    try:
+        return session.heap_summary()
+    except (NotImplementedError, RuntimeError):
+        return None
+
+
+def _session_fault_context(session: "DebugSession") -> Optional[FaultContext]:
+    if not hasattr(session, "fault_context"):
+        return None
+    try:
+        return session.fault_context()
+    except (NotImplementedError, RuntimeError):
+        return None
+
+
+def _session_memory_map(session: "DebugSession") -> List[MapEntry]:
+    if not hasattr(session, "memory_map"):
+        return []
+    try:
+        return session.memory_map()
+    except (NotImplementedError, RuntimeError):
+        return []
+
+
+def analyze_session(
+    session: "DebugSession",
+    source: str = "",
+    max_frames: int = DEFAULT_MAX_FRAMES,
+    frame_rules: Optional[FrameRules] = None,
+) -> CrashReport:
+    """Summarize the crash an open `DebugSession` is stopped at, whichever engine backs it."""
+    return CrashReport.from_context(
+        _session_context(session),
+        thread_id=session.current_tid(),
+        max_frames=max_frames,
+        source=source,
+        frame_rules=frame_rules,
+    )
+
+
+def _analyze_core(request: AnalyzeRequest) -> AnalysisReport:
+    from dbgcopilot.dumps import DumpError, DumpSession
+
+    path = request.core or ""
+    symbol_path = _symbol_path(request)
+    if request.backend is not None:
+        _give_symbol_path(request.backend, symbol_path)
+        report_progress(
+            request.progress,
+            ProgressStage.READING_DUMP,
+            f"Opening {os.path.basename(path)} with {request.backend.name}",
+        )
+        session = request.backend.load_dump(path, program=request.binary)
+        try:
+            ctx = _session_context(session, request.locals_frames)
+            return _report_from_context(ctx, request.backend.name, session.current_tid())
+        except RuntimeError as exc:
+            raise AnalysisError(f"{request.backend.name}: {exc}") from exc
+        finally:
+            session.close()
+    report_progress(request.progress, ProgressStage.READING_DUMP, f"Reading {os.path.basename(path)}")
+    try:
+        with span(log, "dump.read", path=path) as stage, DumpSession.from_dump(
+            path,
+            program=request.binary,
+            debug_file=request.debug_file,
+            max_frames_per_thread=request.max_frames_per_thread,
+            symbol_path=symbol_path,
+        ) as dump:
+            report = _report_from_context(dump.crash_context(), dump.kind, dump.current_tid())
+            report.warnings = dump.warnings
+            stage["kind"], stage["frames"] = dump.kind, len(report.frames)
+            return report
+    except DumpError as exc:
+        if not request.binary:
+            raise AnalysisError(f"{exc}; a debugger could read it with the binary") from exc
+        # Not something the built-in reader understands; let the debugger try.
+        return _capture_with_debugger(request)
+
+
+def _sample_hang(pid: int, backend: Any) -> HangReport:
+    with span(log, "hang.sample", pid=pid, backend=getattr(backend, "name", None)) as stage:
+        hang = Watchdog(
+            pid, interval_ms=HANG_SAMPLE_INTERVAL_MS, duration_ms=HANG_SAMPLE_DURATION_MS, backend=backend
+        ).run()
+        stage["samples"], stage["threads"] = len(hang.sampled_at), hang.thread_count
+        return hang
+
+
+def _hang_report(hang: HangReport, description: str, source: str) -> AnalysisReport:
+    busiest = hang.dominant_frames[0].stack if hang.dominant_frames else []
+    threads = [ThreadBacktrace(d.thread_id, name=d.thread_name, frames=d.stack) for d in hang.dominant_frames]
+    kind = classify_threads(threads)
+    # Sampled stacks only: which lock each waiter wants, but not who holds it.
+    kind.wait_chain = wait_chain(threads, lambda _address: None)
+    return AnalysisReport(
+        fault_kind=FaultKind.HANG,
+        stop=StopInfo(description=description),
+        frames=list(busiest),
+        fault_hint=f"{kind.describe()}\n{hang.describe()}",
+        hang=hang,
+        hang_kind=kind,
+        source=source,
+    )
+
+
+def _goroutine_dump(proc: "subprocess.Popen[bytes]", stderr: Any) -> List[Goroutine]:
+    """SIGQUIT a hung Go program and parse the goroutine dump it writes to ``stderr`` as it exits."""
+    with span(log, "goroutines.dump", pid=proc.pid) as stage:
+        try:
+            proc.send_signal(signal.SIGQUIT)
+            proc.wait(timeout=GOROUTINE_DUMP_TIMEOUT)
+        except (OSError, subprocess.TimeoutExpired):
+            return []
+        stderr.seek(0)
+        goroutines = parse_goroutine_dump(stderr.read().decode("utf-8", errors="replace"))
+        stage["goroutines"] = len(goroutines)
+        return goroutines
+
+
+def _goroutine_hang_report(hang: HangReport, goroutines: List[Goroutine], description: str) -> AnalysisReport:
+    """A hang report over goroutines: the OS threads of a Go program mostly just sit in the scheduler."""
+    kind = classify_goroutines(goroutines)
+    threads = [g.to_thread() for g in goroutines if not g.runtime_owned]
+    stuck = next((t for t in threads if t.thread_id in kind.waiters), threads[0] if threads else None)
+    lines = [kind.describe(), *(f"- {t.describe()}" for t in kind.threads)]
+    if hang.sampled_at:
+        lines.append(hang.describe())
+    return AnalysisReport(
+        fault_kind=FaultKind.HANG,
+        stop=StopInfo(description=description),
+        frames=list(stuck.frames) if stuck is not None else [],
+        fault_hint="\n".join(lines),
+        hang=hang,
+        hang_kind=kind,
+        source="run",
+        language=Language.GO,
+        threads=threads,
+    )
+
+
+def _process_alive(pid: int) -> bool:
+    try:
+        os.kill(pid, 0)
+    except ProcessLookupError:
+        return False
+    except PermissionError:
+        # Someone else's process: it exists, the attach will say whether we may trace it.
+        return True
+    return True
+
+
+def _process_stopped(pid: int) -> bool:
+    """True if ``pid`` is in job-control or ptrace stop; False where /proc is unavailable."""
+    try:
+        with open(f"/proc/{pid}/stat", encoding="ascii", errors="replace") as fh:
+            stat = fh.read()
+    except OSError:
+        return False
+    # The state letter follows the parenthesized command name, which may itself contain ")".
+    fields = stat[stat.rfind(")") + 1 :].split()
+    return bool(fields) and fields[0] in ("T", "t")
+
+
+def _process_args(pid: int) -> List[str]:
+    try:
+        with open(f"/proc/{pid}/cmdline", "rb") as fh:
+            raw = fh.read()
+    except OSError:
+        return []
+    return [a.decode("utf-8", errors="replace") for a in raw.split(b"\0") if a]
+
+
+def _analyze_attach(request: AnalyzeRequest, pid: int) -> AnalysisReport:
+    backend = _detect_backend(request)
+    was_stopped = _process_stopped(pid)
+    report_progress(request.progress, ProgressStage.SAMPLING_THREADS, f"Sampling the threads of process {pid}")
+    try:
+        hang = _sample_hang(pid, backend)
+    finally:
+        # Each sample detaches, but a debugger killed mid-attach can leave the
+        # process in a group stop, possibly still settling. Resume it unless it
+        # was stopped before we came; SIGCONT is a no-op for a running process.
+        if not was_stopped:
+            try:
+                os.kill(pid, signal.SIGCONT)
+            except OSError:
+                pass
+    if not hang.sampled_at:
+        raise AnalysisError(f"could not attach to process {pid} with {getattr(backend, 'name', 'the debugger')}")
+    report = _hang_report(hang, f"attached to running process {pid}", "attach")
+    report.args = _process_args(pid)
+    report.language = detect_language(os.path.realpath(f"/proc/{pid}/exe"), report.frames)
+    return report
+
+
+def _analyze_run(request: AnalyzeRequest) -> AnalysisReport:
+    # A hung Go program is asked for its goroutines (SIGQUIT), which it prints to stderr.
+    is_go = request.sample_hangs and detect_language(request.binary) is Language.GO
+    env = None
+    if is_go and "GOTRACEBACK" not in os.environ:
+        # "system" keeps runtime.gopark and the other runtime frames in the dump.
+        env = {**os.environ, "GOTRACEBACK": "system"}
+    stderr: Any = tempfile.TemporaryFile() if is_go else subprocess.DEVNULL
+    report_progress(request.progress, ProgressStage.LAUNCHING_TARGET, f"Starting {os.path.basename(request.binary)}")
+    try:
+        # Its own session, so a timeout can kill whatever it forked along with it.
+        proc = subprocess.Popen(
+            [request.binary, *request.args],
+            stdin=subprocess.DEVNULL,
+            stdout=subprocess.DEVNULL,
+            stderr=stderr,
+            env=env,
+            start_new_session=True,
+        )
+    except OSError as exc:
+        if is_go:
+            stderr.close()
+        raise AnalysisError(f"cannot start {request.binary}: {exc}") from exc
+    try:
+        return _wait_run(request, proc, stderr if is_go else None)
+    finally:
+        if is_go:
+            stderr.close()
+
+
+def _analyze_under_debugger(request: AnalyzeRequest) -> AnalysisReport:
+    try:
+        report = _capture_with_debugger(request)
+    except AnalysisError:
+        # No debugger: the plain run still tells a crash, an exit, and a hang apart.
+        return _analyze_run(request)
+    stop = report.stop
+    if stop.timed_out and request.sample_hangs:
+        # A batch debugger cannot stop the program to sample its threads, so the run is
+        # repeated outside it and attached to at the same limit, like any other hang.
+        return _analyze_run(request)
+    if stop.timed_out or (stop.signal is None and stop.exit_code is not None):
+        return AnalysisReport(fault_kind=FaultKind.UNKNOWN, stop=stop, source="run")
+    return report
+
+
+def _wait_run(request: AnalyzeRequest, proc: "subprocess.Popen[bytes]", stderr: Any) -> AnalysisReport:
+    try:
+        code = proc.wait(timeout=request.timeout)
+    except subprocess.TimeoutExpired:
+        if not request.sample_hangs:
+            kill_tree(proc)
+            stop = StopInfo(description=f"no crash within {request.timeout:g}s; killed", timed_out=True)
+            return AnalysisReport(fault_kind=FaultKind.UNKNOWN, stop=stop, source="run")
+        goroutines: List[Goroutine] = []
+        report_progress(
+            request.progress,
+            ProgressStage.SAMPLING_THREADS,
+            f"No exit after {request.timeout:g}s; sampling its threads",
+        )
+        try:
+            hang = _sample_hang(proc.pid, _detect_backend(request))
+        except AnalysisError:
+            # Still a hang, just without stacks to show where.
+            hang = HangReport()
+        finally:
+            if stderr is not None:
+                goroutines = _goroutine_dump(proc, stderr)
+            kill_tree(proc)
+        description = f"no exit within {request.timeout:g}s"
+        if goroutines:
+            return _goroutine_hang_report(hang, goroutines, description)
+        return _hang_report(hang, description, "run")
+    if code >= 0:
+        stop = StopInfo(description=f"exited with code {code}", exit_code=code)
+        return AnalysisReport(fault_kind=FaultKind.UNKNOWN, stop=stop, source="run")
+    try:
+        name = signal.Signals(-code).name
+    except ValueError:
+        name = f"SIG{-code}"
+    # Killed by a signal: rerun under the debugger to capture the faulting state.
+    try:
+        report = _capture_with_debugger(request)
+    except AnalysisError:
+        detail = "no debugger for details"
+    else:
+        if not report.stop.timed_out:
+            return report
+        # It faulted on its own but not under the debugger: keep the first run's signal.
+        detail = f"debugger run: {report.stop.description}"
+    ctx = CrashContext(stop=StopInfo(signal=name, description=f"terminated by {name} ({detail})"))
+    return _report_from_context(ctx, "run")
+
+
+def _build_prompt(
+    report: Union[AnalysisReport, CrashReport], builder: PromptBuilder, max_frames: Optional[int] = None
+) -> BuiltPrompt:
+    # Built only from the structured report, so the same crash always yields the same prompt.
+    if isinstance(report, AnalysisReport):
+        # Every captured frame by default: the builder's token budget decides what is shown.
+        report = report.crash_report(max_frames if max_frames is not None else len(report.frames))
+    with span(log, "prompt.build", frames=len(report.frames), budget=builder.max_tokens) as stage:
+        built = builder.build(report)
+        stage["tokens"], stage["elided"] = built.tokens, built.elision.total
+        return built
+
+
+def build_explain_prompt(
+    report: Union[AnalysisReport, CrashReport],
+    max_frames: Optional[int] = None,
+    redactor: Optional[Redactor] = None,
+    max_tokens: int = DEFAULT_MAX_PROMPT_TOKENS,
+) -> str:
+    """The explanation prompt for ``report``, redacted and fitted to ``max_tokens`` by `PromptBuilder`."""
+    return _build_prompt(report, PromptBuilder(max_tokens=max_tokens, redactor=redactor), max_frames).text
+
+
+def prepare_prompt(report: AnalysisReport, llm_config: Optional[Dict[str, str]] = None) -> BuiltPrompt:
+    """Build the explanation prompt and record it on ``report``, without calling a model."""
+    built = _build_prompt(report, PromptBuilder.from_config(llm_config))
+    report.prompt, report.prompt_elision = built.text, built.elision
+    return built
+
+
+def explain_stream(
+    target: Union[AnalysisReport, CrashReport, CrashContext],
+    on_chunk: Callable[[str], None],
+    provider: Union[str, "LlmProvider", None] = None,
+    llm_config: Optional[Dict[str, str]] = None,
+) -> str:
+    """Explain a crash, calling ``on_chunk`` with each fragment as it arrives.
+
+    ``provider`` is an `LlmProvider` or a name for `resolve_provider`. Returns
+    the full explanation. A failure after output has started raises
+    `dbgcopilot.llm.sse.StreamError`, whose ``partial`` is the text already
+    delivered. Failures before any output are retried as ``max_retries`` and
+    ``base_delay`` in ``llm_config`` allow (see `RetryPolicy`). A prompt that
+    could cost more than ``max_cost`` raises `LlmBudgetError` unsent, and an
+    `AnalysisReport` gets the call's tokens and cost in ``usage``, including
+    those of a stream that broke off.
+    """
+    from dbgcopilot.llm.base import resolve_provider
+
+    report = target if isinstance(target, (AnalysisReport, CrashReport)) else _report_from_context(target, "")
+    return _stream(report, on_chunk, resolve_provider(provider, llm_config), llm_config)[0].text
+
+
+def _stream(
+    report: Union[AnalysisReport, CrashReport],
+    on_chunk: Callable[[str], None],
+    llm: "LlmProvider",
+    llm_config: Optional[Dict[str, str]],
+) -> Tuple["Completion", int]:
+    from dbgcopilot.llm.base import Prompt
+    from dbgcopilot.llm.retry import RetryPolicy
+
+    policy = RetryPolicy.from_config(llm_config)
+    if isinstance(report, AnalysisReport):
+        built = prepare_prompt(report, llm_config)
+    else:
+        built = _build_prompt(report, PromptBuilder.from_config(llm_config))
+    prompt = Prompt(built.text)
+    meter = _meter(report, llm_config)
+    try:
+        return policy.stream(llm, prompt, on_chunk, meter)
+    finally:
+        if isinstance(report, AnalysisReport) and meter.usage.calls:
+            # A stream cut short was still paid for up to where it broke.
+            report.usage = meter.usage
+
+
+def _cache_hit(usage: Optional["Usage"]) -> "Usage":
+    """``usage`` with one more explanation from the cache; the tokens it would have taken are not counted."""
+    from dbgcopilot.llm.usage import Usage
+
+    usage = usage if usage is not None else Usage()
+    usage.cached += 1
+    return usage
+
+
+def _meter(report: Union[AnalysisReport, CrashReport], llm_config: Optional[Dict[str, str]]) -> "UsageMeter":
+    """A meter adding to what ``report`` has spent so far, so ``max_cost`` covers the whole analysis."""
+    from dbgcopilot.llm.usage import UsageMeter
+
+    return UsageMeter.from_config(llm_config, report.usage if isinstance(report, AnalysisReport) else None)
+
+
+def _provider_identity(llm: "LlmProvider") -> str:
+    model = getattr(llm, "model", "")
+    return f"{llm.name}/{model}" if model else llm.name
+
+
+def explain(report: AnalysisReport, request: AnalyzeRequest) -> None:
+    """Add the LLM explanation to ``report``, as `analyze` does when ``request.provider`` is set.
+
+    Failures never raise: they are recorded in ``explanation_error``. A stream
+    cut short keeps the text already delivered and sets ``explanation_partial``.
+    Rate limits, overloaded servers, and timeouts are retried with backoff
+    first (``max_retries`` and ``base_delay`` in ``request.llm_config``).
+    The call's tokens and estimated cost are added to ``usage``; a prompt
+    that could take them over ``max_cost`` is not sent.
+    """
+    from dbgcopilot.llm.base import Prompt, resolve_provider
+    from dbgcopilot.llm.errors import LlmError
+    from dbgcopilot.llm.retry import RetryPolicy
+    from dbgcopilot.llm.sse import StreamError
+
+    cache, signature, identity = request.cache, "", ""
+    try:
+        llm = resolve_provider(request.provider, request.llm_config)
+        if cache is not None:
+            signature, identity = crash_signature(report.crash_report()), _provider_identity(llm)
+            template = template_from_config(request.llm_config)
+            if template is not DEFAULT_PROMPT_TEMPLATE:
+                # Another template asks another question; its answers are cached apart.
+                identity += f" template:{template.digest}"
+            if prompt_fault_context(request.llm_config):
+                identity += " fault-context"
+            cached = cache.get(signature, identity)
+            if cached is not None:
+                log.info("llm.call skipped: cached explanation for %s", identity)
+                report.explanation, report.explanation_cached = cached, True
+                report.usage = _cache_hit(report.usage)
+                if request.on_explain_chunk is not None:
+                    request.on_explain_chunk(cached)
+                return
+        streamed = request.on_explain_chunk is not None
+        with span(log, "llm.call", provider=_provider_identity(llm), stream=streamed) as stage:
+            try:
+                if request.on_explain_chunk is not None:
+                    completion, stage["attempts"] = _stream(report, request.on_explain_chunk, llm, request.llm_config)
+                else:
+                    policy = RetryPolicy.from_config(request.llm_config)
+                    prompt = Prompt(prepare_prompt(report, request.llm_config).text)
+                    meter = _meter(report, request.llm_config)
+                    completion, stage["attempts"] = policy.complete(llm, prompt, meter)
+                    report.usage = meter.usage
+                text = completion.text
+            except LlmError as exc:
+                stage["attempts"] = exc.attempts
+                raise
+            stage["chars"] = len(text)
+        report.explanation = text.strip()
+    except StreamError as exc:
+        # Keep what the caller already saw, but flag that it is incomplete.
+        report.explanation = exc.partial.strip() or None
+        report.explanation_partial = report.explanation is not None
+        report.explanation_error = str(exc)
+        return
+    except Exception as exc:
+        # The structured data is still useful without the prose.
+        report.explanation_error = str(exc)
+        return
+    if cache is not None and report.explanation:
+        try:
+            cache.put(signature, report.explanation, identity)
+        except OSError:
+            # A read-only or full cache directory only costs the next run a call.
+            pass
+
+
+def add_fix_suggestion(report: AnalysisReport, request: AnalyzeRequest) -> None:
+    """Ask for a fix of the explained crash, as `analyze` does with ``request.suggest_fix``.
+
+    Like `explain`, failures never raise; they end up in ``fix_suggestion_error``.
+    A missing or incomplete explanation is one: the fix is asked for in its terms.
+    The call is added to ``usage``, and ``max_cost`` covers both calls together.
+    """
+    from dbgcopilot.llm.base import resolve_provider
+
+    from .fix import suggest_fix
+
+    if not report.explanation or report.explanation_partial:
+        report.fix_suggestion_error = "no complete explanation to base a fix on"
+        return
+    meter = None
+    try:
+        llm = resolve_provider(request.provider, request.llm_config)
+        meter = _meter(report, request.llm_config)
+        with span(log, "llm.fix", provider=_provider_identity(llm)):
+            report.fix_suggestion = suggest_fix(report, report.explanation, llm, request.llm_config, meter=meter)
+    except Exception as exc:
+        report.fix_suggestion_error = str(exc)
+    if meter is not None and meter.usage.calls:
+        # An answer that could not be read was still paid for.
+        report.usage = meter.usage
+
+
+def analyze(request: AnalyzeRequest) -> AnalysisReport:
+    """Capture, classify, and (optionally) explain a crash or hang."""
+    if request.pid is not None and _process_alive(request.pid):
+        return _finish(_analyze_attach(request, request.pid), request)
+    if request.pid is not None and not request.core:
+        raise AnalysisError(f"process {request.pid} is not running, and no core file was given")
+    if not request.binary and not request.core:
+        raise AnalysisError("no binary to run")
+    # Minidumps and cores the built-in reader understands need no binary; it only adds symbols.
+    if request.binary and not os.path.exists(request.binary):
+        raise AnalysisError(f"binary not found: {request.binary}")
+    if request.core:
+        if not os.path.exists(request.core):
+            raise AnalysisError(f"core file not found: {request.core}")
+        report = _analyze_core(request)
+    else:
+        report = _analyze_under_debugger(request) if request.under_debugger else _analyze_run(request)
+        report.args = [request.binary, *request.args]
+    return _finish(report, request)
+
+
+def _finish(report: AnalysisReport, request: AnalyzeRequest) -> AnalysisReport:
+    if request.max_frames_per_thread > 0:
+        # The dump readers stop unwinding at the limit; debuggers print every frame, cut here.
+        report.frames = report.frames[: request.max_frames_per_thread]
+        report.threads = limit_threads(report.threads, request.max_frames_per_thread)
+    report.frame_rules = FrameRules.from_config(request.llm_config)
+    if report.language is None:
+        report.language = detect_language(request.binary, report.frames)
+    if report.crashed or report.hung:
+        report.annotations = enrich_report(report.crash_report(len(report.frames)), request.enrichers)
+        if request.no_llm:
+            prepare_prompt(report, request.llm_config)
+        elif request.provider:
+            model = request.provider if isinstance(request.provider, str) else request.provider.name
+            what = "hang" if report.hung else "crash"
+            report_progress(request.progress, ProgressStage.QUERYING_MODEL, f"Asking {model} to explain the {what}")
+            explain(report, request)
+            if request.suggest_fix:
+                report_progress(request.progress, ProgressStage.QUERYING_MODEL, f"Asking {model} for a fix")
+                add_fix_suggestion(report, request)
+    report_progress(request.progress, ProgressStage.DONE, f"Done: {report.outcome.value.replace('_', ' ')}")
+    return report
+
+
+__all__ = [
+    "AnalysisError",
+    "AnalysisReport",
+    "AnalyzeRequest",
+    "Outcome",
+    "REPORT_SCHEMA_VERSION",
+    "add_fix_suggestion",
+    "analyze",
+    "analyze_session",
+    "build_explain_prompt",
+    "explain",
+    "explain_stream",
+    "prepare_prompt",
+]
@@ -0,0 +1,247 @@
+"""Analyze a directory of core dumps in one run and group them by crash.
+
+After a fleet-wide incident there are hundreds of cores, mostly of a handful
+of bugs. `analyze_batch(directory, request)` runs the core path of `analyze`
+over every file in ``directory`` whose name matches `DUMP_PATTERNS`
+(``core``, ``core.1234``, ``app.core``, ``crash.dmp``, ...), in name order,
+with ``request`` as the template: its ``binary``, provider, and cache apply to
+every core. A core that cannot be read is recorded as its entry's ``error``
+and the batch goes on.
+
+`BatchResult.groups` clusters the reports by `CrashReport.bucket` (the fault
+kind and the top frames' `signature`), largest group first, and
+`BatchResult.summary` says it in a line per group ("412 cores: null_deref in
+parse_header"), then gives the tokens and cost of the LLM calls.
+`BatchResult.table` lays the groups out as signature, count, an example
+core, and a one-line cause: the first sentence of the explanation, else the
+fault hint. Explanations go through ``request.cache`` like any other,
+and without one through a cache of this batch alone, so either way each
+signature is explained once and the other cores are hits.
+"""
+from __future__ import annotations
+
+import fnmatch
+import os
+from dataclasses import dataclass, field, replace
+from typing import TYPE_CHECKING, Any, Dict, Iterable, List, Optional, Set, Tuple
+
+from .cache import ExplanationCache
+
+if TYPE_CHECKING:
+    from dbgcopilot.llm.usage import Usage
+
+    from .api import AnalysisReport, AnalyzeRequest
+
+# File names of Linux cores (core, core.<pid>, <name>.core) and Windows minidumps.
+DUMP_PATTERNS = ("core", "core.*", "*.core", "*.dmp", "*.mdmp")
+# Longest cause `BatchResult.table` prints before cutting it off.
+CAUSE_WIDTH = 100
+_TABLE_HEADER = ("SIGNATURE", "COUNT", "EXAMPLE", "CAUSE")
+
+
+@dataclass
+class BatchEntry:
+    path: str
+    report: Optional["AnalysisReport"] = None
+    # Why the core could not be analyzed; set instead of ``report``.
+    error: Optional[str] = None
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {
+            "core": self.path,
+            "error": self.error,
+            "report": self.report.to_dict() if self.report is not None else None,
+        }
+
+
+@dataclass
+class BatchGroup:
+    """Cores with the same `CrashReport.bucket`."""
+
+    bucket: Optional[str]
+    signature: Optional[str]
+    fault_kind: str
+    # The first user frame's function, else the top frame's; None without frames.
+    function: Optional[str]
+    cores: List[str] = field(default_factory=list)
+    # One line on why it crashed; see `one_line_cause`.
+    cause: Optional[str] = None
+
+    @property
+    def example(self) -> Optional[str]:
+        return self.cores[0] if self.cores else None
+
+    def describe(self) -> str:
+        noun = "core" if len(self.cores) == 1 else "cores"
+        where = f" in {self.function}" if self.function else ""
+        line = f"{len(self.cores)} {noun}: {self.fault_kind}{where}"
+        return f"{line} ({self.signature}) [{self.bucket}]" if self.signature else line
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {
+            "bucket": self.bucket,
+            "signature": self.signature,
+            "fault_kind": self.fault_kind,
+            "function": self.function,
+            "count": len(self.cores),
+            "example": self.example,
+            "cause": self.cause,
+            "cores": list(self.cores),
+        }
+
+
+@dataclass
+class BatchResult:
+    entries: List[BatchEntry] = field(default_factory=list)
+
+    @property
+    def failures(self) -> List[BatchEntry]:
+        return [e for e in self.entries if e.report is None]
+
+    def usage(self) -> Optional["Usage"]:
+        """Tokens and estimated cost of every core's LLM calls together; None when no call was made."""
+        from dbgcopilot.llm.usage import Usage
+
+        used = [e.report.usage for e in self.entries if e.report is not None and e.report.usage is not None]
+        if not used:
+            return None
+        total = Usage()
+        for usage in used:
+            total.add(usage)
+        return total
+
+    def groups(self) -> List[BatchGroup]:
+        """The analyzed cores grouped by bucket, most cores first (ties in order of first appearance)."""
+        groups: Dict[Optional[str], BatchGroup] = {}
+        explained: Set[str] = set()
+        for entry in self.entries:
+            if entry.report is None:
+                continue
+            crash = entry.report.crash_report()
+            # Without frames there is no bucket; group those by fault kind alone.
+            key = crash.bucket or f"?{crash.fault_kind.value}"
+            if key not in groups:
+                user = crash.first_user_frame() or (crash.frames[0] if crash.frames else None)
+                groups[key] = BatchGroup(
+                    bucket=crash.bucket,
+                    signature=crash.signature(),
+                    fault_kind=crash.fault_kind.value,
+                    function=user.function if user is not None else None,
+                )
+            group = groups[key]
+            group.cores.append(entry.path)
+            # The first explanation in the group says it best; the fault hint stands in until one comes.
+            if group.cause is None or (entry.report.explanation and key not in explained):
+                group.cause = one_line_cause(entry.report)
+                if entry.report.explanation:
+                    explained.add(key)
+        return sorted(groups.values(), key=lambda g: -len(g.cores))
+
+    def summary(self) -> List[str]:
+        lines = [g.describe() for g in self.groups()]
+        failures = self.failures
+        if failures:
+            noun = "core" if len(failures) == 1 else "cores"
+            lines.append(f"{len(failures)} {noun} could not be analyzed")
+        usage = self.usage()
+        if usage is not None:
+            lines.append(usage.describe())
+        return lines
+
+    def table(self) -> List[str]:
+        """`groups` as aligned columns under a header: signature, count, example core, and cause."""
+        rows: List[Tuple[str, ...]] = [_TABLE_HEADER]
+        for g in self.groups():
+            rows.append((g.signature or f"({g.fault_kind})", str(len(g.cores)), g.example or "", g.cause or ""))
+        widths = [max(len(row[i]) for row in rows) for i in range(len(_TABLE_HEADER) - 1)]
+        return ["  ".join([c.ljust(w) for c, w in zip(row, widths)] + [row[-1]]).rstrip() for row in rows]
+
+    def to_list(self) -> List[Dict[str, Any]]:
+        """One `BatchEntry.to_dict` per core, in the order they were analyzed."""
+        return [e.to_dict() for e in self.entries]
+
+    def to_dict(self) -> Dict[str, Any]:
+        """``groups`` (`BatchGroup.to_dict`, largest first), ``cores`` (`to_list`), and the total ``usage``."""
+        usage = self.usage()
+        return {
+            "groups": [g.to_dict() for g in self.groups()],
+            "cores": self.to_list(),
+            "usage": usage.to_dict() if usage is not None else None,
+        }
+
+
+def one_line_cause(report: "AnalysisReport") -> Optional[str]:
+    """The first sentence of ``report``'s explanation, else its fault hint, cut to `CAUSE_WIDTH`."""
+    text = None
+    for line in (report.explanation or "").splitlines():
+        # Markdown headings, bullets, and emphasis are not part of the sentence.
+        line = line.strip().lstrip("#*->").strip().replace("**", "").replace("`", "")
+        if line:
+            text = line
+            break
+    if text is not None:
+        end = text.find(". ")
+        text = text[: end + 1] if end >= 0 else text
+    else:
+        text = report.fault_hint or report.fault_kind.label
+    return text if len(text) <= CAUSE_WIDTH else text[: CAUSE_WIDTH - 3].rstrip() + "..."
+
+
+class _BatchCache(ExplanationCache):
+    """Explanations of this batch only, in memory, for a request without a cache."""
+
+    def __init__(self) -> None:
+        super().__init__(directory="")
+        self._answers: Dict[Tuple[str, str], str] = {}
+
+    def get(self, signature: str, provider: str = "") -> Optional[str]:
+        return self._answers.get((signature, provider))
+
+    def put(self, signature: str, explanation: str, provider: str = "") -> None:
+        self._answers[(signature, provider)] = explanation
+
+
+def find_dumps(directory: str, patterns: Iterable[str] = DUMP_PATTERNS) -> List[str]:
+    """Paths of the regular files directly in ``directory`` whose names match ``patterns``, sorted by name."""
+    patterns = list(patterns)
+    names = sorted(os.listdir(directory))
+    return [
+        os.path.join(directory, name)
+        for name in names
+        if any(fnmatch.fnmatchcase(name, p) for p in patterns) and os.path.isfile(os.path.join(directory, name))
+    ]
+
+
+def analyze_batch(
+    directory: str, request: "AnalyzeRequest", patterns: Iterable[str] = DUMP_PATTERNS
+) -> BatchResult:
+    """`analyze` every dump in ``directory`` with ``request``'s settings; see the module docstring.
+
+    Raises OSError only when ``directory`` itself cannot be listed.
+    """
+    from .api import analyze
+
+    result = BatchResult()
+    # Without a cache, a cache of this batch still explains each signature once.
+    template = request if request.cache is not None else replace(request, cache=_BatchCache())
+    for path in find_dumps(directory, patterns):
+        try:
+            report = analyze(replace(template, core=path, pid=None))
+        except Exception as exc:
+            # A reader bug on one malformed core must not end the batch either.
+            result.entries.append(BatchEntry(path, error=str(exc) or type(exc).__name__))
+            continue
+        result.entries.append(BatchEntry(path, report=report))
+    return result
+
+
+__all__ = [
+    "BatchEntry",
+    "BatchGroup",
+    "BatchResult",
+    "CAUSE_WIDTH",
+    "DUMP_PATTERNS",
+    "analyze_batch",
+    "find_dumps",
+    "one_line_cause",
+]
@@ -0,0 +1,147 @@
+"""Reuse explanations of crashes that were already explained.
+
+Re-running the same crash while iterating would otherwise pay for a fresh LLM
+call each time. `crash_signature` reduces a report to what identifies the bug:
+the fault kind and the top frames by module, function, and source line. It
+leaves out pcs, fault addresses, and argument values, which move with ASLR and
+heap layout. `ExplanationCache` stores one JSON file per signature hash in a
+local directory. A stored answer is reused only for the same provider and
+model and the same `PROMPT_VERSION`, so improving the prompt retires the
+answers to the old one. Failed or partial explanations are never stored.
+"""
+from __future__ import annotations
+
+import hashlib
+import json
+import os
+import re
+import tempfile
+import time
+from typing import Any, Dict, List, Optional, Union
+
+from dbgcopilot.utils.config import user_cache_dir
+
+from .crash import CrashReport
+from .model import CrashContext, Frame, Module
+from .prompt import PROMPT_VERSION
+
+# Frames from the top that make up the signature.
+SIGNATURE_FRAMES = 5
+# Bump when the signature or the entry layout changes; older entries then miss.
+CACHE_VERSION = 1
+CACHE_DIR_ENV_VAR = "DBGCOPILOT_CACHE_DIR"
+
+# Rust legacy-mangling hash (`::h0123456789abcdef`) changes with every rebuild.
+_RUST_HASH_RE = re.compile(r"::h[0-9a-f]{16}$")
+
+
+def default_cache_dir() -> str:
+    """``$DBGCOPILOT_CACHE_DIR``, else ``dbgcopilot/explanations`` in the platform's cache directory.
+
+    That is ``%LOCALAPPDATA%`` on Windows, ``~/Library/Caches`` on macOS, and
+    ``$XDG_CACHE_HOME`` (``~/.cache`` by default) elsewhere.
+    """
+    explicit = os.environ.get(CACHE_DIR_ENV_VAR)
+    if explicit:
+        return explicit
+    return os.path.join(user_cache_dir(), "explanations")
+
+
+def _module_of(frame: Frame, modules: List[Module]) -> Optional[Module]:
+    if frame.pc is None:
+        return None
+    return next((m for m in modules if m.base <= frame.pc < m.base + m.size), None)
+
+
+def _frame_key(frame: Frame, modules: List[Module]) -> str:
+    module = frame.module
+    image = _module_of(frame, modules)
+    if not module and image is not None:
+        module = image.name
+    module = os.path.basename(module) if module else "?"
+    if frame.function:
+        where = _RUST_HASH_RE.sub("", frame.function.strip())
+    elif image is not None and frame.pc is not None:
+        # Without symbols, the offset into the image is stable across ASLR.
+        where = f"+0x{frame.pc - image.base:x}"
+    else:
+        where = "??"
+    if frame.file:
+        where += f" {os.path.basename(frame.file)}:{frame.line or 0}"
+    return f"{module}!{where}"
+
+
+def crash_signature(report: Union[CrashReport, CrashContext], frames: int = SIGNATURE_FRAMES) -> str:
+    """Stable text identifying a crash: fault kind plus the normalized top ``frames`` frames."""
+    if isinstance(report, CrashContext):
+        report = CrashReport.from_context(report)
+    lines = [report.fault_kind.value]
+    lines.extend(_frame_key(f, report.modules) for f in report.frames[:frames])
+    return "\n".join(lines)
+
+
+def signature_hash(signature: str) -> str:
+    return hashlib.sha256(signature.encode("utf-8")).hexdigest()
+
+
+class ExplanationCache:
+    """Explanations on disk, one ``<sha256>.json`` per crash signature."""
+
+    def __init__(
+        self, directory: Optional[str] = None, refresh: bool = False, prompt_version: int = PROMPT_VERSION
+    ) -> None:
+        self.directory = directory or default_cache_dir()
+        # Never read, but still store: each crash is explained afresh and the entries replaced.
+        self.refresh = refresh
+        self.prompt_version = prompt_version
+
+    def path(self, signature: str) -> str:
+        return os.path.join(self.directory, signature_hash(signature) + ".json")
+
+    def get(self, signature: str, provider: str = "") -> Optional[str]:
+        """The stored explanation for ``signature`` from ``provider``, or None."""
+        if self.refresh:
+            return None
+        try:
+            with open(self.path(signature), encoding="utf-8") as fh:
+                entry: Dict[str, Any] = json.load(fh)
+        except (OSError, ValueError):
+            # Missing or half-written: treat it as a miss; the next put replaces it.
+            return None
+        if entry.get("version") != CACHE_VERSION or entry.get("signature") != signature:
+            return None
+        if entry.get("prompt_version") != self.prompt_version:
+            return None
+        if entry.get("provider", "") != provider or not isinstance(entry.get("explanation"), str):
+            return None
+        return entry["explanation"]
+
+    def put(self, signature: str, explanation: str, provider: str = "") -> None:
+        entry = {
+            "version": CACHE_VERSION,
+            "signature": signature,
+            "provider": provider,
+            "prompt_version": self.prompt_version,
+            "explanation": explanation,
+            "created": time.time(),
+        }
+        os.makedirs(self.directory, exist_ok=True)
+        # Write then rename, so a concurrent reader never sees a partial file.
+        fd, tmp = tempfile.mkstemp(dir=self.directory, suffix=".tmp")
+        try:
+            with os.fdopen(fd, "w", encoding="utf-8") as fh:
+                json.dump(entry, fh, indent=2)
+            os.replace(tmp, self.path(signature))
+        except BaseException:
+            os.unlink(tmp)
+            raise
+
+
+__all__ = [
+    "CACHE_DIR_ENV_VAR",
+    "ExplanationCache",
+    "SIGNATURE_FRAMES",
+    "crash_signature",
+    "default_cache_dir",
+    "signature_hash",
+]
@@ -0,0 +1,188 @@
+"""Follow-up questions about a crash that has already been captured.
+
+The first explanation rarely ends the investigation: "why is ``ptr`` null?"
+needs the same crash data plus what was said so far, not a fresh run.
+`ConversationState` keeps the captured `AnalysisReport` and the transcript
+of one session. Each question is sent with the report's prompt, the first
+explanation, and as much of the transcript as fits ``history_tokens``
+(newest turns first). A line starting with ``!`` is a debugger command
+instead: it runs against the target and its output joins the transcript, so
+the next answer can use it. Questions and debugger output are redacted like
+the report before they reach a model; the printed output is not.
+
+Many questions are about data the capture already has, which the prompt may
+have trimmed to fit its budget. A question that names a register ("why is
+rdi null?") or a frame (``#3``, "frame 3") gets that register's value or that
+frame with its locals attached from the report, without running the debugger
+again. `transcript_text` renders the session for a file.
+
+The state holds no UI, so `dbgcopilot-analyze --interactive` and tests drive
+the same `ConversationState.handle`.
+"""
+from __future__ import annotations
+
+import re
+from dataclasses import dataclass, field
+from typing import TYPE_CHECKING, Any, Callable, Dict, List, Optional
+
+from .prompt import estimate_tokens
+from .redact import Redactor
+from .registers import Registers
+
+if TYPE_CHECKING:
+    from dbgcopilot.llm.base import ChunkCallback, LlmProvider, Prompt
+
+    from .api import AnalysisReport
+
+DEBUGGER_ESCAPE = "!"
+# Transcript tokens sent with each question, on top of the crash prompt.
+HISTORY_TOKENS = 2000
+# Debugger output kept per command; `thread apply all bt` alone can run to megabytes.
+MAX_DEBUGGER_OUTPUT_CHARS = 4000
+
+FOLLOW_UP_INSTRUCTIONS = (
+    "Answer the follow-up question about this crash. Use the crash data, your earlier analysis, "
+    "and the debugger output in the conversation; say so when they do not settle the question. Be brief."
+)
+
+_WORD = re.compile(r"[A-Za-z][A-Za-z0-9]*")
+_FRAME_REF = re.compile(r"(?:#|\bframe\s+)(\d+)\b", re.IGNORECASE)
+
+
+@dataclass
+class Turn:
+    # "user", "assistant", or "debugger".
+    role: str
+    text: str
+    # The debugger command whose output ``text`` is (debugger turns only).
+    command: Optional[str] = None
+
+    def render(self) -> str:
+        if self.role == "debugger":
+            return f"Debugger output of `{self.command}`:\n{self.text}"
+        return f"{'User' if self.role == 'user' else 'Assistant'}: {self.text}"
+
+
+@dataclass
+class ConversationState:
+    """The report and transcript of one follow-up session."""
+
+    report: "AnalysisReport"
+    transcript: List[Turn] = field(default_factory=list)
+    # Session config for the prompt builder, redaction, and retries (see docs/llm.md).
+    llm_config: Optional[Dict[str, Any]] = None
+    history_tokens: int = HISTORY_TOKENS
+
+    def _history(self) -> List[str]:
+        kept: List[str] = []
+        used = 0
+        for turn in reversed(self.transcript):
+            text = turn.render()
+            used += estimate_tokens(text)
+            if used > self.history_tokens and kept:
+                break
+            kept.append(text)
+        left_out = len(self.transcript) - len(kept)
+        lines = [f"({left_out} earlier turns left out to fit the token budget)"] if left_out else []
+        return lines + kept[::-1]
+
+    def captured_data(self, question: str) -> List[str]:
+        """Lines from the report for the registers and frames ``question`` names, in the order it names them."""
+        registers = Registers.from_list(self.report.registers)
+        frames = {frame.index: frame for frame in self.report.frames}
+        lines: List[str] = []
+        names: List[str] = []
+        for word in _WORD.findall(question):
+            name = word.lower()
+            value = registers.get(name)
+            if value is not None and name not in names:
+                names.append(name)
+                lines.append(f"{name} = 0x{value:x}")
+        for index in dict.fromkeys(int(match.group(1)) for match in _FRAME_REF.finditer(question)):
+            frame = frames.get(index)
+            if frame is not None:
+                lines.append(frame.describe())
+                lines.extend(frame.describe_locals())
+        return lines
+
+    def build_prompt(self, question: str) -> "Prompt":
+        """The prompt for ``question``: crash data, the first explanation, the transcript, then the question."""
+        from dbgcopilot.llm.base import Prompt
+
+        from .api import prepare_prompt
+
+        crash = self.report.prompt or prepare_prompt(self.report, self.llm_config).text
+        parts = [crash]
+        if self.report.explanation:
+            parts.append(f"Your first analysis:\n{self.report.explanation}")
+        history = self._history()
+        if history:
+            parts.append("Conversation so far:\n" + "\n\n".join(history))
+        redactor = Redactor.from_config(self.llm_config)
+        captured = self.captured_data(question)
+        if captured:
+            data = redactor.redact_text("\n".join(captured), "captured data")
+            parts.append(f"Captured data the question refers to:\n{data}")
+        parts.append(f"Follow-up question: {redactor.redact_text(question, 'question')}")
+        return Prompt("\n\n".join(parts), system=FOLLOW_UP_INSTRUCTIONS)
+
+    def ask(self, question: str, llm: "LlmProvider", on_chunk: Optional["ChunkCallback"] = None) -> str:
+        """Answer ``question`` and add both to the transcript; LLM failures raise `LlmError`."""
+        from dbgcopilot.llm.retry import RetryPolicy
+
+        prompt = self.build_prompt(question)
+        policy = RetryPolicy.from_config(self.llm_config)
+        if on_chunk is not None:
+            completion, _ = policy.stream(llm, prompt, on_chunk)
+        else:
+            completion, _ = policy.complete(llm, prompt)
+        answer = completion.text.strip()
+        self.transcript.append(Turn("user", question))
+        self.transcript.append(Turn("assistant", answer))
+        return answer
+
+    def run_debugger(self, command: str, run_command: Callable[[str], str]) -> str:
+        """Run ``command`` with ``run_command`` and add its (redacted, capped) output to the transcript."""
+        output = run_command(command)
+        text = Redactor.from_config(self.llm_config).redact_text(output.strip(), "debugger output")
+        if len(text) > MAX_DEBUGGER_OUTPUT_CHARS:
+            text = text[:MAX_DEBUGGER_OUTPUT_CHARS] + f"\n... ({len(text) - MAX_DEBUGGER_OUTPUT_CHARS} more characters)"
+        self.transcript.append(Turn("debugger", text, command=command))
+        return output
+
+    def transcript_text(self) -> str:
+        """The first explanation and every turn, as plain text."""
+        parts = [f"First analysis:\n{self.report.explanation}"] if self.report.explanation else []
+        parts.extend(turn.render() for turn in self.transcript)
+        return "\n\n".join(parts) + "\n"
+
+    def handle(
+        self,
+        line: str,
+        llm: "LlmProvider",
+        run_command: Optional[Callable[[str], str]] = None,
+        on_chunk: Optional["ChunkCallback"] = None,
+    ) -> str:
+        """A question, or with a leading ``!`` a debugger command; returns the answer or the command's output.
+
+        Without ``run_command``, debugger commands raise `RuntimeError`.
+        """
+        line = line.strip()
+        if line.startswith(DEBUGGER_ESCAPE):
+            command = line[len(DEBUGGER_ESCAPE):].strip()
+            if not command:
+                raise ValueError(f"expected a debugger command after {DEBUGGER_ESCAPE!r}")
+            if run_command is None:
+                raise RuntimeError("no debugger is available for this report")
+            return self.run_debugger(command, run_command)
+        return self.ask(line, llm, on_chunk)
+
+
+__all__ = [
+    "ConversationState",
+    "DEBUGGER_ESCAPE",
+    "FOLLOW_UP_INSTRUCTIONS",
+    "HISTORY_TOKENS",
+    "MAX_DEBUGGER_OUTPUT_CHARS",
+    "Turn",
+]
@@ -0,0 +1,589 @@
+"""Structured crash summary that UIs, tests, and prompts consume instead of debugger text.
+
+`CrashReport` is the stable shape of "what crashed and where": the faulting
+thread, signal / exception code, fault address, the top symbolized frames, and
+the loaded modules, and the faulting thread's `Registers`. It is the same whichever engine produced it (GDB, LLDB,
+CDB, or the built-in dump readers), and `render` / `to_dict` give a
+deterministic text and JSON-ready form of it. `signature` names the crash
+site in a form that is the same on every run, for grouping duplicates.
+"""
+from __future__ import annotations
+
+import dataclasses
+import hashlib
+import os
+import re
+import time
+from dataclasses import dataclass, field
+from typing import Any, Dict, Iterable, List, Mapping, Optional
+
+from .demangle import demangle
+from .disasm import FaultContext
+from .exception import ExceptionKind, NormalizedException, normalize_stop
+from .fault import FaultKind, classify_fault, fault_hint
+from .frames import FrameRules
+from .hang_kind import HangCategory
+from .heap import HeapSummary
+from .language import crash_language
+from .model import (
+    AccessKind,
+    Annotation,
+    CrashContext,
+    Frame,
+    Language,
+    Module,
+    Optimized,
+    PanicInfo,
+    Register,
+    ThreadBacktrace,
+    Variable,
+)
+from .modules import ModuleConflict, find_module_conflicts, module_version
+from .regions import MemoryRegion
+from .registers import Registers, describe_address_source
+from .threads import OTHER_THREAD_FRAMES, find_faulting_thread, summarize_threads
+from .v8 import label_engine_frames
+
+# Frames kept by default; enough to get past panic/abort machinery to user code.
+DEFAULT_MAX_FRAMES = 16
+# The faulting frame and its first callers, as named by `CrashReport.signature`.
+SIGNATURE_DEPTH = 3
+
+# "func+0x1f" / "func+31" as some debuggers print a symbol with its offset.
+_SYMBOL_OFFSET_RE = re.compile(r"\+(?:0x[0-9a-fA-F]+|\d+)$")
+
+
+def _attribute_modules(frames: List[Frame], modules: List[Module]) -> List[Frame]:
+    """Copy ``frames``, naming the module of any frame whose pc falls inside one."""
+    out = []
+    for frame in frames:
+        if frame.module is None and frame.pc is not None:
+            owner = next((m for m in modules if m.contains(frame.pc)), None)
+            if owner is not None:
+                frame = dataclasses.replace(frame, module=owner.name)
+        out.append(frame)
+    return out
+
+
+def _signature_frame(frame: Frame, modules: List[Module], with_offset: bool) -> str:
+    image = next((m for m in modules if frame.pc is not None and m.contains(frame.pc)), None)
+    module = frame.module or (image.name if image is not None else None)
+    module = os.path.basename(module) if module else "?"
+    if frame.function:
+        name = _SYMBOL_OFFSET_RE.sub("", demangle(frame.function.strip()))
+        if with_offset and frame.offset:
+            name += f"+0x{frame.offset:x}"
+    elif image is not None and frame.pc is not None:
+        # No symbol: the offset into the image survives ASLR, the pc does not.
+        name = f"+0x{frame.pc - image.base:x}"
+    else:
+        name = "??"
+    return f"{module}!{name}"
+
+
+def _versioned(module: Module) -> Module:
+    """``module`` with the version from its file name when the capture path gave none."""
+    if module.version is not None:
+        return module
+    return dataclasses.replace(module, version=module_version(module.path or module.name))
+
+
+def _module_line(m: Module) -> str:
+    line = f"0x{m.base:x} {m.name}"
+    if m.version:
+        line += f" {m.version}"
+    if m.timestamp:
+        line += " (linked " + time.strftime("%Y-%m-%d", time.gmtime(m.timestamp)) + ")"
+    return line + (f" {m.path}" if m.path else "")
+
+
+def _hex(value: Optional[int]) -> Optional[str]:
+    return None if value is None else f"0x{value:x}"
+
+
+def _unhex(value: Optional[str]) -> Optional[int]:
+    return None if value is None else int(value, 16)
+
+
+def _frame_dict(f: Frame) -> Dict[str, Any]:
+    return {
+        "index": f.index,
+        "pc": _hex(f.pc),
+        "module": f.module,
+        "symbol": f.function,
+        "raw_symbol": f.raw_symbol,
+        "engine": f.engine,
+        "offset": f.offset,
+        "source_file": f.file,
+        "line": f.line,
+        "column": f.column,
+        "locals": [_variable_dict(v) for v in f.locals],
+    }
+
+
+def _variable_dict(v: Variable) -> Dict[str, Any]:
+    value = v.value.value if isinstance(v.value, Optimized) else v.value
+    return {
+        "name": v.name,
+        "type": v.type_name,
+        "value": value,
+        "argument": v.argument,
+        "pretty_printed": v.pretty_printed,
+    }
+
+
+def _variable_from_dict(data: Mapping[str, Any]) -> Variable:
+    value = data.get("value") or ""
+    return Variable(
+        name=data["name"],
+        type_name=data.get("type"),
+        value=Optimized.OUT if value == Optimized.OUT.value else value,
+        argument=bool(data.get("argument")),
+        pretty_printed=data.get("pretty_printed"),
+    )
+
+
+def frame_from_dict(data: Mapping[str, Any]) -> Frame:
+    """Inverse of a `CrashReport.to_dict` frame entry."""
+    return Frame(
+        index=data["index"],
+        pc=_unhex(data.get("pc")),
+        function=data.get("symbol"),
+        module=data.get("module"),
+        file=data.get("source_file"),
+        line=data.get("line"),
+        column=data.get("column"),
+        offset=data.get("offset"),
+        raw_symbol=data.get("raw_symbol"),
+        engine=data.get("engine"),
+        locals=[_variable_from_dict(v) for v in data.get("locals") or []],
+    )
+
+
+def _count_signals(signals: List[str]) -> str:
+    """``SIGSEGV x12, SIGPIPE`` for the handled signals, in order of first delivery."""
+    counts: Dict[str, int] = {}
+    for name in signals:
+        counts[name] = counts.get(name, 0) + 1
+    return ", ".join(name if n == 1 else f"{name} x{n}" for name, n in counts.items())
+
+
+@dataclass
+class CrashReport:
+    """Faulting thread, stop reason, top frames, and modules of one crash."""
+
+    fault_kind: FaultKind
+    # OS thread id of the faulting thread (LWP on Linux, TID on Windows).
+    thread_id: Optional[int] = None
+    signal: Optional[str] = None
+    # NTSTATUS or Mach exception code; None on plain POSIX signals.
+    exception_code: Optional[int] = None
+    fault_address: Optional[int] = None
+    # How the faulting instruction touched ``fault_address``, when the capture path can tell.
+    access_kind: AccessKind = AccessKind.UNKNOWN
+    # The platform-neutral kind of the signal or exception code; see `normalize_stop`.
+    exception_kind: ExceptionKind = ExceptionKind.UNKNOWN
+    # Linux ``si_code`` of the signal; None elsewhere.
+    si_code: Optional[int] = None
+    description: str = ""
+    # Signals the program's own handlers took before the fault; see `AnalyzeRequest.ignore_signals`.
+    handled_signals: List[str] = field(default_factory=list)
+    hint: Optional[str] = None
+    # How sure `classify_fault` was of ``fault_kind``, and on what evidence; None and empty when it did not
+    # decide it (a hang, or a kind the caller set).
+    fault_confidence: Optional[float] = None
+    fault_evidence: List[str] = field(default_factory=list)
+    # How a hung program was stuck; None for a crash.
+    hang_category: Optional[HangCategory] = None
+    # The top frames only; ``total_frames`` is how deep the stack really was.
+    frames: List[Frame] = field(default_factory=list)
+    total_frames: int = 0
+    modules: List[Module] = field(default_factory=list)
+    # The faulting thread's registers, by name and by role; empty when the capture path read none.
+    registers: Registers = field(default_factory=Registers)
+    source: str = ""
+    # Command line and environment of the crashed process, when the capture path knows them.
+    args: List[str] = field(default_factory=list)
+    environ: Dict[str, str] = field(default_factory=dict)
+    # The target's language, as the capture path detected it from the binary or script.
+    language: Optional[Language] = None
+    # The language of the crash itself, from the user frames and runtime markers; see `crash_language`.
+    detected_language: Language = Language.UNKNOWN
+    # Every thread's full stack, the faulting one included; empty when the capture path has only ``frames``.
+    threads: List[ThreadBacktrace] = field(default_factory=list)
+    # `Frame.index` of the innermost frame of the program itself (see `FrameRules`); None if all are runtime.
+    user_frame_index: Optional[int] = None
+    # Heap statistics and corruption at the fault; None when the capture path did not ask.
+    heap: Optional[HeapSummary] = None
+    # The memory region the fault address is in, when the capture path had a map; see `fault_region`.
+    fault_region: Optional[MemoryRegion] = None
+    # The registers and instructions around the faulting pc; see `dbgcopilot.analysis.disasm`.
+    fault_context: Optional[FaultContext] = None
+    # The Rust panic behind the stop; see `dbgcopilot.analysis.panic`.
+    panic: Optional[PanicInfo] = None
+    # What `ReportEnricher`s added (owners, known bugs, ...); see `dbgcopilot.analysis.enrich`.
+    annotations: List[Annotation] = field(default_factory=list)
+
+    def __post_init__(self) -> None:
+        # Reports built by hand, or read from JSON written before the field existed, detect it here.
+        if self.detected_language is Language.UNKNOWN:
+            self.detected_language = crash_language(self.frames, self.modules, self.language)
+
+    @classmethod
+    def from_context(
+        cls,
+        ctx: CrashContext,
+        *,
+        thread_id: Optional[int] = None,
+        max_frames: int = DEFAULT_MAX_FRAMES,
+        source: str = "",
+        fault_kind: Optional[FaultKind] = None,
+        hint: Optional[str] = None,
+        frame_rules: Optional[FrameRules] = None,
+        hang_category: Optional[HangCategory] = None,
+        annotations: Iterable[Annotation] = (),
+    ) -> "CrashReport":
+        """Summarize ``ctx``; ``fault_kind`` / ``hint`` default to `classify_fault` / `fault_hint`.
+
+        ``frame_rules`` picks the first user frame; the default rules when None.
+        ``annotations`` are the notes enrichers already added (see `AnalysisReport.annotations`).
+        """
+        frames = label_engine_frames(_attribute_modules(list(ctx.frames), ctx.modules))
+        rules = frame_rules or FrameRules()
+        user_frame = rules.first_user_frame(frames)
+        threads = [
+            dataclasses.replace(t, frames=label_engine_frames(_attribute_modules(list(t.frames), ctx.modules)))
+            for t in ctx.threads
+        ]
+        # However the faulting thread was found, the report marks it, so JSON and text agree.
+        faulting = find_faulting_thread(threads, thread_id, frames)
+        for thread in threads:
+            thread.faulting = thread is faulting
+        found = classify_fault(ctx)
+        # A caller's kind keeps the classifier's evidence only when the two agree.
+        agrees = fault_kind is None or fault_kind is found.kind
+        return cls(
+            fault_kind=fault_kind if fault_kind is not None else found.kind,
+            thread_id=thread_id,
+            signal=ctx.stop.signal,
+            exception_code=ctx.stop.exception_code,
+            fault_address=ctx.stop.fault_address,
+            access_kind=ctx.stop.access,
+            exception_kind=normalize_stop(ctx.stop).kind,
+            si_code=ctx.stop.si_code,
+            description=ctx.stop.description,
+            handled_signals=list(ctx.stop.handled_signals),
+            hint=hint if hint is not None else fault_hint(ctx),
+            fault_confidence=found.confidence if agrees else None,
+            fault_evidence=list(found.signals) if agrees else [],
+            hang_category=hang_category,
+            frames=frames[:max_frames],
+            total_frames=len(ctx.frames),
+            modules=[_versioned(m) for m in ctx.modules],
+            registers=Registers.from_list(ctx.registers),
+            source=source,
+            args=list(ctx.args),
+            environ=dict(ctx.environ),
+            language=ctx.language,
+            detected_language=crash_language(frames, ctx.modules, ctx.language, rules),
+            threads=threads,
+            user_frame_index=user_frame.index if user_frame is not None else None,
+            heap=ctx.heap.with_stack(ctx.stop, ctx.frames) if ctx.heap is not None else None,
+            fault_region=ctx.fault_region,
+            fault_context=ctx.fault_context,
+            panic=ctx.panic,
+            annotations=list(annotations),
+        )
+
+    @classmethod
+    def from_dict(cls, data: Mapping[str, Any], max_frames: Optional[int] = None) -> "CrashReport":
+        """Rebuild a report from `to_dict` output (or a superset, such as `AnalysisReport.to_dict`).
+
+        ``max_frames`` keeps only the top frames; ``total_frames`` still says how many there were.
+        """
+        frames = [frame_from_dict(f) for f in data.get("frames", [])]
+        return cls(
+            fault_kind=FaultKind(data["fault_kind"]),
+            thread_id=data.get("thread_id"),
+            signal=data.get("signal"),
+            exception_code=_unhex(data.get("exception_code")),
+            fault_address=_unhex(data.get("fault_address")),
+            access_kind=AccessKind(data.get("access_kind") or AccessKind.UNKNOWN.value),
+            exception_kind=ExceptionKind(data.get("exception_kind") or ExceptionKind.UNKNOWN.value),
+            si_code=data.get("si_code"),
+            description=data.get("description") or "",
+            handled_signals=list(data.get("handled_signals") or []),
+            hint=data.get("hint"),
+            fault_confidence=data.get("fault_confidence"),
+            fault_evidence=list(data.get("fault_evidence") or []),
+            hang_category=HangCategory(data["hang_category"]) if data.get("hang_category") else None,
+            frames=frames if max_frames is None else frames[:max_frames],
+            total_frames=data.get("total_frames", len(frames)),
+            modules=[
+                Module(
+                    name=m["name"],
+                    base=int(m["base"], 16),
+                    size=m["size"],
+                    path=m.get("path"),
+                    version=m.get("version"),
+                    timestamp=m.get("timestamp"),
+                )
+                for m in data.get("modules", [])
+            ],
+            registers=Registers.from_list(
+                Register(r["name"], int(r["value"], 16)) for r in data.get("registers") or []
+            ),
+            source=data.get("source") or "",
+            args=list(data.get("args") or []),
+            environ=dict(data.get("environ") or {}),
+            language=Language(data["language"]) if data.get("language") else None,
+            detected_language=Language(data.get("detected_language") or Language.UNKNOWN.value),
+            threads=[
+                ThreadBacktrace(
+                    thread_id=t["thread_id"],
+                    tid=t.get("tid"),
+                    name=t.get("name"),
+                    frames=[frame_from_dict(f) for f in t.get("frames", [])],
+                    truncated=bool(t.get("truncated")),
+                    faulting=bool(t.get("faulting")),
+                )
+                for t in data.get("threads") or []
+            ],
+            user_frame_index=data.get("user_frame_index"),
+            heap=HeapSummary.from_dict(data["heap"]) if data.get("heap") else None,
+            fault_region=MemoryRegion.from_dict(data["fault_region"]) if data.get("fault_region") else None,
+            fault_context=FaultContext.from_dict(data["fault_context"]) if data.get("fault_context") else None,
+            panic=PanicInfo(data["panic"].get("message"), data["panic"].get("location")) if data.get("panic") else None,
+            annotations=[Annotation(a["source"], a["text"]) for a in data.get("annotations") or []],
+        )
+
+    @property
+    def crash_site(self) -> Optional[Frame]:
+        return self.frames[0] if self.frames else None
+
+    @property
+    def faulting_thread(self) -> Optional[ThreadBacktrace]:
+        """The entry of ``threads`` that faulted, matched by ``thread_id`` or by its top frame."""
+        return find_faulting_thread(self.threads, self.thread_id, self.frames)
+
+    def first_user_frame(self) -> Optional[Frame]:
+        """The innermost frame of the program itself, below any runtime frames; None if not among ``frames``."""
+        if self.user_frame_index is None:
+            return None
+        return next((f for f in self.frames if f.index == self.user_frame_index), None)
+
+    @property
+    def exception(self) -> NormalizedException:
+        """The stop as a `NormalizedException`, the same on every platform for the same fault."""
+        return NormalizedException(
+            kind=self.exception_kind,
+            access=self.access_kind,
+            address=self.fault_address,
+            si_code=self.si_code,
+            signal=self.signal,
+        )
+
+    def module_conflicts(self) -> List[ModuleConflict]:
+        """Libraries loaded from more than one path, a common cause of crashes in them; see `find_module_conflicts`."""
+        return find_module_conflicts(self.modules)
+
+    def signature(self, depth: int = SIGNATURE_DEPTH) -> Optional[str]:
+        """The top ``depth`` frames as ``module!symbol``, innermost first, e.g. ``crash!crash+0x5 -> crash!main``.
+
+        Symbols are demangled without Rust hashes. Only the faulting frame
+        keeps its offset into the function; pcs, addresses, and pids never
+        appear, so two runs of the same crash give the same text. None
+        without frames.
+        """
+        if not self.frames:
+            return None
+        return " -> ".join(_signature_frame(f, self.modules, i == 0) for i, f in enumerate(self.frames[:depth]))
+
+    @property
+    def bucket(self) -> Optional[str]:
+        """Short hash of the fault kind and `signature`, for grouping duplicate crashes by one id."""
+        signature = self.signature()
+        if signature is None:
+            return None
+        return hashlib.sha256(f"{self.fault_kind.value}\n{signature}".encode("utf-8")).hexdigest()[:16]
+
+    def to_dict(self) -> Dict[str, Any]:
+        """JSON-ready form; addresses are ``0x`` strings so they survive 53-bit JSON numbers."""
+        faulting = self.faulting_thread
+        return {
+            "fault_kind": self.fault_kind.value,
+            "thread_id": self.thread_id,
+            "signal": self.signal,
+            "exception_code": _hex(self.exception_code),
+            "fault_address": _hex(self.fault_address),
+            "access_kind": self.access_kind.value,
+            "exception_kind": self.exception_kind.value,
+            "si_code": self.si_code,
+            "description": self.description,
+            "handled_signals": list(self.handled_signals),
+            "hint": self.hint,
+            "fault_confidence": self.fault_confidence,
+            "fault_evidence": list(self.fault_evidence),
+            "hang_category": self.hang_category.value if self.hang_category is not None else None,
+            "signature": self.signature(),
+            "bucket": self.bucket,
+            "frames": [_frame_dict(f) for f in self.frames],
+            "total_frames": self.total_frames,
+            "user_frame_index": self.user_frame_index,
+            "modules": [
+                {
+                    "name": m.name,
+                    "base": _hex(m.base),
+                    "size": m.size,
+                    "path": m.path,
+                    "version": m.version,
+                    "timestamp": m.timestamp,
+                }
+                for m in self.modules
+            ],
+            "module_conflicts": [c.to_dict() for c in self.module_conflicts()],
+            "registers": [{"name": name, "value": _hex(value)} for name, value in self.registers.values.items()],
+            "named_registers": self.registers.to_dict(),
+            "source": self.source,
+            "args": list(self.args),
+            "environ": dict(self.environ),
+            "language": self.language.value if self.language is not None else None,
+            "detected_language": self.detected_language.value,
+            "threads": [
+                {
+                    "thread_id": t.thread_id,
+                    "tid": t.tid,
+                    "name": t.name,
+                    "faulting": t is faulting,
+                    "frames": [_frame_dict(f) for f in t.frames],
+                    "truncated": t.truncated,
+                }
+                for t in self.threads
+            ],
+            "heap": self.heap.to_dict() if self.heap is not None else None,
+            "fault_region": self.fault_region.to_dict() if self.fault_region is not None else None,
+            "fault_context": self.fault_context.to_dict() if self.fault_context is not None else None,
+            "panic": dataclasses.asdict(self.panic) if self.panic is not None else None,
+            "annotations": [{"source": a.source, "text": a.text} for a in self.annotations],
+        }
+
+    def annotate(self, source: str, text: str) -> None:
+        """Add a note to the report; it is shown in the text form, the JSON, and the prompt."""
+        self.annotations.append(Annotation(source, text))
+
+    def user_frame_line(self) -> Optional[str]:
+        """``First user frame: ...``, when runtime frames sit above it; None otherwise."""
+        user = self.first_user_frame()
+        if user is None or not self.frames or user is self.frames[0]:
+            return None
+        runtime = sum(f.index < user.index for f in self.frames)
+        return f"First user frame: {user.describe()} (below {runtime} runtime frames)"
+
+    def faulting_thread_line(self) -> Optional[str]:
+        """``Faulting thread: 1235 (thread 2 "worker-a", of 4)``; which of several threads took the fault."""
+        faulting = self.faulting_thread
+        tid = self.thread_id if self.thread_id is not None else faulting.tid if faulting is not None else None
+        if faulting is None or len(self.threads) < 2:
+            return f"Faulting thread: {tid}" if tid is not None else None
+        which = f"thread {faulting.thread_id}" + (f' "{faulting.name}"' if faulting.name else "")
+        which += f", of {len(self.threads)}"
+        return f"Faulting thread: {tid} ({which})" if tid is not None else f"Faulting thread: {which}"
+
+    def summary_lines(self, with_signature: bool = True, with_user_frame: bool = True) -> List[str]:
+        """The lines `render` puts above the backtrace: the stop, the fault, its classification."""
+        stop = " ".join(p for p in (self.signal or "-", self.description) if p)
+        lines = [f"Stop: {stop}"]
+        if self.panic is not None:
+            lines.append(self.panic.describe())
+        if self.exception_code is not None:
+            lines.append(f"Exception code: 0x{self.exception_code:08x}")
+        if self.fault_address is not None:
+            lines.append(f"Fault address: 0x{self.fault_address:x}")
+        if self.access_kind is not AccessKind.UNKNOWN:
+            lines.append(f"Access: {self.access_kind.label}")
+        if self.exception_kind is not ExceptionKind.UNKNOWN:
+            lines.append(f"Exception: {self.exception.describe()}")
+        source = describe_address_source(self.registers, self.fault_address) if self.signal else None
+        if source is not None:
+            lines.append(f"Fault address from: {source}")
+        if self.fault_region is not None:
+            lines.append(self.fault_region.describe())
+        if self.registers.describe_roles():
+            lines.append(f"Registers: {self.registers.describe_roles()}")
+        thread_line = self.faulting_thread_line()
+        if thread_line is not None:
+            lines.append(thread_line)
+        if self.handled_signals:
+            lines.append(f"Handled by the program first: {_count_signals(self.handled_signals)}")
+        if self.args:
+            lines.append(f"Command line: {' '.join(self.args)}")
+        lines.append(f"Classification: {self.hint or self.fault_kind.label}")
+        user_line = self.user_frame_line() if with_user_frame else None
+        if user_line is not None:
+            lines.append(user_line)
+        faulting = self.faulting_thread
+        if faulting is not None and faulting.truncated:
+            lines.append(f"Stack cut off at {len(faulting.frames)} frames: the outermost callers were not captured")
+        signature = self.signature() if with_signature else None
+        if signature is not None:
+            lines.append(f"Signature: {signature} [{self.bucket}]")
+        if self.detected_language is not Language.UNKNOWN:
+            line = f"Language: {self.detected_language.label}"
+            if self.language not in (None, Language.UNKNOWN, self.detected_language):
+                line += f" (in a {self.language.label} target)"
+            lines.append(line)
+        lines.extend(f"Module conflict: {c.describe()}" for c in self.module_conflicts())
+        if self.heap is not None:
+            lines.extend(self.heap.describe())
+        lines.extend(a.describe() for a in self.annotations)
+        return lines
+
+    def frame_lines(self) -> List[str]:
+        """Every frame with its variables, and how many the capture left out."""
+        lines = []
+        for f in self.frames:
+            lines.append(f.describe())
+            lines.extend(f.describe_locals())
+        if self.total_frames > len(self.frames):
+            lines.append(f"... {self.total_frames - len(self.frames)} more frames")
+        return lines
+
+    def render(
+        self,
+        with_modules: bool = False,
+        backtrace: Optional[List[str]] = None,
+        with_signature: bool = True,
+        threads: Optional[List[str]] = None,
+        with_fault_context: bool = True,
+    ) -> str:
+        """Plain-text summary; the same report always renders to the same text.
+
+        ``backtrace`` replaces the frame lines, for callers that summarize the
+        stack, and ``threads`` the other threads' lines (by default
+        `summarize_threads` at ``OTHER_THREAD_FRAMES`` deep).
+        ``with_fault_context=False`` leaves out the faulting instruction and the
+        disassembly around it, as the prompt does unless asked.
+        """
+        lines = self.summary_lines(with_signature)
+        if with_fault_context and self.fault_context is not None:
+            lines.extend(self.fault_context.lines())
+        if backtrace is None and self.frames:
+            backtrace = self.frame_lines()
+        if backtrace:
+            lines.append("Backtrace:")
+            lines.extend(backtrace)
+        if threads is None:
+            threads = summarize_threads(self.threads, self.faulting_thread, OTHER_THREAD_FRAMES)
+        if threads:
+            lines.append("Other threads:")
+            lines.extend(threads)
+        if with_modules and self.modules:
+            lines.append("Modules:")
+            lines.extend(_module_line(m) for m in self.modules)
+        if self.environ:
+            lines.append("Environment:")
+            lines.extend(f"{name}={value}" for name, value in sorted(self.environ.items()))
+        return "\n".join(lines)
+
+
+__all__ = ["CrashReport", "DEFAULT_MAX_FRAMES", "SIGNATURE_DEPTH", "frame_from_dict"]
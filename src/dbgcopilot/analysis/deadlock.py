@@ -0,0 +1,374 @@
+"""Lock wait chains: what each blocked thread waits on, who owns it, and the cycles that make a deadlock.
+
+For every thread blocked acquiring a lock, `wait_chain` records a `WaitLink`
+naming the lock and, when it can be recovered, the thread that owns it. The
+lock is taken from the arguments of the outermost lock frame near the top of
+the stack, which is the object the program itself locked:
+
+- glibc: ``pthread_mutex_lock (mutex=0x...)`` or ``__lll_lock_wait (futex=0x...)``;
+- C++: ``std::mutex::lock (this=0x...)``;
+- Rust: ``std::sync::Mutex<T>::lock (self=0x...)``.
+
+The owner comes from the first of these that knows it:
+
+1. glibc's ``__data.__owner`` field in target memory (`pthread_owner_reader`);
+2. the locked critical sections CDB's ``!locks`` lists, on Windows
+   (a session's optional ``lock_owners()``);
+3. a lock guard among the locals of another blocked thread's frame that
+   called lock: a Rust ``MutexGuard {lock: 0x...}``, or a C++
+   ``std::lock_guard`` or ``std::unique_lock``. Rust std mutexes record no
+   owner, so this is what closes their chains.
+
+A link whose owner none of these recover says "owner unknown"; nothing is
+guessed. A cycle of links is a deadlock (`DeadlockReport`).
+
+The lock frames need arguments, which GDB and LLDB print when the code has
+debug info (``libc6-dbg`` for glibc's). CDB's ``kn`` prints none, so on
+Windows a waiter's lock is often unknown even where ``!locks`` names owners.
+"""
+from __future__ import annotations
+
+import re
+from dataclasses import dataclass, field
+from typing import TYPE_CHECKING, Callable, Dict, Iterable, List, Mapping, Optional, Sequence, Tuple
+
+from .hang_kind import WAIT_FRAMES, ThreadState, c_function_name, summarize_thread
+from .model import Frame, ThreadBacktrace, Variable
+
+if TYPE_CHECKING:
+    from dbgcopilot.backends.base import DebugSession
+
+# Offset of ``int __owner`` in glibc's ``struct __pthread_mutex_s`` on 64-bit
+# targets (after ``int __lock`` and ``unsigned int __count``).
+PTHREAD_MUTEX_OWNER_OFFSET = 8
+
+_PTHREAD_LOCK_FUNCTIONS = frozenset(
+    {"pthread_mutex_lock", "pthread_mutex_timedlock", "pthread_mutex_clocklock", "lll_lock_wait"}
+)
+# std::mutex::lock, std::sync::Mutex<T>::lock, std::sys::sync::mutex::futex::Mutex::lock_contended.
+_LOCK_METHOD_RE = re.compile(r"(?:^|::)(?:Mutex|(?:recursive_|timed_)?mutex)(?:<.*>)?::lock(?:_contended)?(?:<.*>)?$")
+# mutex=0x555555558040 <lock_a>   /   futex=futex@entry=0x555555558040 <lock_a>, private=0
+# this=0x7fffffffe0c0 (std::mutex)   /   self=0x5555555a2b10 (Rust)
+_LOCK_ARG_RE = re.compile(
+    r"\b(?:mutex|futex|this|self)=(?:\w+@entry=)?(?P<addr>0x[0-9a-fA-F]+)(?: <(?P<symbol>[^>+]+)>)?"
+)
+# Locals that hold a lock while they live, and the field pointing at it:
+# Rust ``MutexGuard<u32> {lock: 0x...}``, C++ ``std::lock_guard`` ``{_M_device = @0x...}``
+# and ``std::unique_lock`` ``{_M_device = 0x..., _M_owns = true}``.
+_GUARD_TYPE_RE = re.compile(r"MutexGuard|lock_guard|unique_lock")
+_GUARD_LOCK_RE = re.compile(r"\b(?:lock|_M_device)\s*[:=]\s*@?(?P<addr>0x[0-9a-fA-F]+)")
+
+OwnerReader = Callable[[int], Optional[int]]
+
+
+@dataclass(frozen=True)
+class LockRef:
+    address: int
+    # Global the debugger resolved the address to, e.g. ``lock_a``.
+    symbol: Optional[str] = None
+
+    def describe(self) -> str:
+        return f"{self.symbol} (0x{self.address:x})" if self.symbol else f"0x{self.address:x}"
+
+
+@dataclass
+class WaitLink:
+    """One blocked thread: the lock it waits for and, if known, the thread holding it.
+
+    ``lock`` is None when the stack shows a lock wait (``frame``) but not which
+    lock. ``owner`` is the holder's ``thread_id``; ``owner_tid`` is its OS
+    thread id, kept even when that thread is not in the snapshot.
+    """
+
+    thread_id: int
+    lock: Optional[LockRef] = None
+    tid: Optional[int] = None
+    name: Optional[str] = None
+    # Function of the frame the thread waits in.
+    frame: Optional[str] = None
+    owner: Optional[int] = None
+    owner_tid: Optional[int] = None
+    owner_name: Optional[str] = None
+    # Where the owner came from: "lock owner" (recorded in or for the lock itself) or "lock guard".
+    owner_source: Optional[str] = None
+
+    @property
+    def label(self) -> str:
+        return f"thread {self.thread_id}" + (f" ({self.name})" if self.name else "")
+
+    def describe(self) -> str:
+        if self.lock is not None:
+            text = f"{self.label} waits for {self.lock.describe()}"
+        else:
+            text = f"{self.label} waits for a lock its stack does not name"
+            if self.frame:
+                text += f" in `{self.frame}`"
+        if self.owner is not None:
+            holder = f"thread {self.owner}" + (f" ({self.owner_name})" if self.owner_name else "")
+            return f"{text}, held by {holder}"
+        if self.owner_tid is not None:
+            return f"{text}, held by OS thread {self.owner_tid} (not in the snapshot)"
+        return f"{text}, owner unknown"
+
+
+@dataclass
+class LockWaiter:
+    """One thread in a wait-for cycle: the lock it wants and the locks it holds."""
+
+    thread_id: int
+    waits_for: LockRef
+    tid: Optional[int] = None
+    name: Optional[str] = None
+    holds: List[LockRef] = field(default_factory=list)
+
+    @property
+    def label(self) -> str:
+        return f"thread {self.thread_id}" + (f" ({self.name})" if self.name else "")
+
+
+@dataclass
+class DeadlockReport:
+    """A wait-for cycle; ``cycle[i]`` waits for a lock held by ``cycle[i + 1]`` (wrapping)."""
+
+    cycle: List[int]
+    threads: List[LockWaiter] = field(default_factory=list)
+
+    def describe(self) -> str:
+        chain = " -> ".join(w.label for w in self.threads + self.threads[:1])
+        lines = [f"Deadlock: {chain}"]
+        for w in self.threads:
+            held = ", ".join(lock.describe() for lock in w.holds) or "nothing observed"
+            lines.append(f"- {w.label} holds {held}, waits for {w.waits_for.describe()}")
+        return "\n".join(lines)
+
+
+def _is_lock_frame(function: str) -> bool:
+    return c_function_name(function) in _PTHREAD_LOCK_FUNCTIONS or bool(_LOCK_METHOD_RE.search(function))
+
+
+def _lock_site(thread: ThreadBacktrace) -> Optional[Tuple[int, LockRef]]:
+    """Position in ``thread.frames`` of the outermost lock frame naming its lock, and that lock."""
+    site: Optional[Tuple[int, LockRef]] = None
+    for position, frame in enumerate(thread.frames[:WAIT_FRAMES]):
+        if not frame.function or not frame.args or not _is_lock_frame(frame.function):
+            continue
+        m = _LOCK_ARG_RE.search(frame.args)
+        if m:
+            site = (position, LockRef(int(m.group("addr"), 16), m.group("symbol")))
+    return site
+
+
+def blocked_on(thread: ThreadBacktrace) -> Optional[LockRef]:
+    """The mutex ``thread`` is trying to acquire, if its frames show one."""
+    site = _lock_site(thread)
+    return site[1] if site is not None else None
+
+
+def guarded_locks(variables: Sequence[Variable]) -> List[int]:
+    """Addresses of the locks that guard objects among ``variables`` hold (see the module docstring)."""
+    addresses: List[int] = []
+    for var in variables:
+        value = str(var.value)
+        if not _GUARD_TYPE_RE.search(f"{var.type_name or ''} {value}") or "_M_owns = false" in value:
+            continue
+        addresses.extend(int(m.group("addr"), 16) for m in _GUARD_LOCK_RE.finditer(value))
+    return addresses
+
+
+def wait_chain(
+    threads: Iterable[ThreadBacktrace],
+    owner_of: OwnerReader,
+    held: Optional[Mapping[int, int]] = None,
+) -> List[WaitLink]:
+    """A `WaitLink` for every thread of ``threads`` blocked on a lock, in ``threads`` order.
+
+    ``owner_of`` maps a lock address to its owner's OS thread id (None if
+    unknown or unlocked); ``held`` maps lock addresses to the ``thread_id``
+    whose lock guard holds them, for locks that record no owner.
+    """
+    threads = list(threads)
+    by_tid = {t.tid: t for t in threads if t.tid is not None}
+    by_id = {t.thread_id: t for t in threads}
+    links: List[WaitLink] = []
+    for thread in threads:
+        link = WaitLink(thread_id=thread.thread_id, tid=thread.tid, name=thread.name)
+        site = _lock_site(thread)
+        if site is None:
+            summary = summarize_thread(thread)
+            if summary.state is not ThreadState.LOCK_WAIT:
+                continue
+            link.frame = summary.frame.function if summary.frame is not None else None
+            links.append(link)
+            continue
+        position, link.lock = site
+        link.frame = thread.frames[position].function
+        owner: Optional[ThreadBacktrace] = None
+        link.owner_tid = owner_of(link.lock.address)
+        if link.owner_tid is not None:
+            owner, link.owner_source = by_tid.get(link.owner_tid), "lock owner"
+        elif held and link.lock.address in held:
+            owner, link.owner_source = by_id.get(held[link.lock.address]), "lock guard"
+            link.owner_tid = owner.tid if owner is not None else None
+        if owner is not None:
+            link.owner, link.owner_name = owner.thread_id, owner.name
+        links.append(link)
+    return links
+
+
+def deadlocks_in(links: Iterable[WaitLink]) -> List[DeadlockReport]:
+    """Each cycle of the wait-for graph ``links`` form, once."""
+    by_id: Dict[int, WaitLink] = {}
+    waits: Dict[int, LockRef] = {}
+    edges: Dict[int, int] = {}
+    holds: Dict[int, List[LockRef]] = {}
+    for link in links:
+        if link.lock is None:
+            continue
+        by_id[link.thread_id], waits[link.thread_id] = link, link.lock
+        if link.owner is not None and link.owner != link.thread_id:
+            edges[link.thread_id] = link.owner
+            holds.setdefault(link.owner, []).append(link.lock)
+
+    reports: List[DeadlockReport] = []
+    seen: set[int] = set()
+    for start in sorted(edges):
+        path: List[int] = []
+        node: Optional[int] = start
+        while node is not None and node not in seen and node not in path:
+            path.append(node)
+            node = edges.get(node)
+        seen.update(path)
+        if node is None or node not in path:
+            continue
+        cycle = path[path.index(node) :]
+        # Start at the lowest thread id so the same cycle always reads the same.
+        low = cycle.index(min(cycle))
+        cycle = cycle[low:] + cycle[:low]
+        reports.append(
+            DeadlockReport(
+                cycle=cycle,
+                threads=[
+                    LockWaiter(
+                        thread_id=tid,
+                        waits_for=waits[tid],
+                        tid=by_id[tid].tid,
+                        name=by_id[tid].name,
+                        holds=holds.get(tid, []),
+                    )
+                    for tid in cycle
+                ],
+            )
+        )
+    return reports
+
+
+def find_deadlocks(
+    threads: Iterable[ThreadBacktrace],
+    owner_of: OwnerReader,
+    held: Optional[Mapping[int, int]] = None,
+) -> List[DeadlockReport]:
+    """Build the wait-for graph of ``threads`` and return each cycle once.
+
+    Arguments as for `wait_chain`. Owners that are not among ``threads`` end the chain.
+    """
+    return deadlocks_in(wait_chain(threads, owner_of, held))
+
+
+def pthread_owner_reader(session: "DebugSession") -> OwnerReader:
+    """Read glibc mutex owners (``__data.__owner``) through ``session.read_memory``."""
+
+    def owner_of(address: int) -> Optional[int]:
+        raw = session.read_memory(address + PTHREAD_MUTEX_OWNER_OFFSET, 4)
+        if raw is None or len(raw) < 4:
+            return None
+        return int.from_bytes(raw[:4], "little") or None
+
+    return owner_of
+
+
+def _pthread_locks(threads: Iterable[ThreadBacktrace]) -> set[int]:
+    """Locks waited for through glibc, whose ``__owner`` field is worth reading."""
+    locks: set[int] = set()
+    for thread in threads:
+        lock = blocked_on(thread)
+        top = thread.frames[:WAIT_FRAMES]
+        if lock is not None and any(f.function and c_function_name(f.function) in _PTHREAD_LOCK_FUNCTIONS for f in top):
+            locks.add(lock.address)
+    return locks
+
+
+def session_owner_reader(session: "DebugSession", pthread_locks: Optional[Iterable[int]] = None) -> OwnerReader:
+    """Owners from the session's lock table (``lock_owners()``, CDB's ``!locks``), else glibc's mutex field.
+
+    With ``pthread_locks``, glibc's field is only read for those addresses: a
+    Rust mutex keeps its data where glibc keeps ``__owner``.
+    """
+    table: Dict[int, int] = session.lock_owners() if hasattr(session, "lock_owners") else {}
+    glibc = set(pthread_locks) if pthread_locks is not None else None
+    pthread = pthread_owner_reader(session)
+    cache: Dict[int, Optional[int]] = {}
+
+    def owner_of(address: int) -> Optional[int]:
+        if address not in cache:
+            if address in table:
+                cache[address] = table[address]
+            else:
+                cache[address] = pthread(address) if glibc is None or address in glibc else None
+        return cache[address]
+
+    return owner_of
+
+
+def _guard_holders(session: "DebugSession", threads: Sequence[ThreadBacktrace]) -> Dict[int, int]:
+    """Lock address -> ``thread_id`` of each blocked thread whose lock-calling frame has a guard for it."""
+    held: Dict[int, int] = {}
+    for thread in threads:
+        site = _lock_site(thread)
+        if site is None or site[0] + 1 >= len(thread.frames):
+            continue
+        caller: Frame = thread.frames[site[0] + 1]
+        try:
+            variables = session.frame_locals(thread=thread.thread_id, frame_index=caller.index)
+        except (NotImplementedError, RuntimeError, ValueError):
+            continue
+        for address in guarded_locks(variables):
+            held.setdefault(address, thread.thread_id)
+    return held
+
+
+def detect_wait_chain(session: "DebugSession", threads: Optional[List[ThreadBacktrace]] = None) -> List[WaitLink]:
+    """`wait_chain` of ``session``'s threads, with owners read from the target.
+
+    Lock guards are only looked for (one ``frame_locals`` per blocked thread)
+    when some lock's owner is still unknown.
+    """
+    if threads is None:
+        threads = session.threads()
+    owner_of = session_owner_reader(session, _pthread_locks(threads))
+    links = wait_chain(threads, owner_of)
+    if any(link.lock is not None and link.owner is None for link in links):
+        links = wait_chain(threads, owner_of, _guard_holders(session, threads))
+    return links
+
+
+def detect_deadlocks(session: "DebugSession", threads: Optional[List[ThreadBacktrace]] = None) -> List[DeadlockReport]:
+    """Wait-for cycles among ``session``'s threads (captured now unless ``threads`` is given)."""
+    return deadlocks_in(detect_wait_chain(session, threads))
+
+
+__all__ = [
+    "DeadlockReport",
+    "LockRef",
+    "LockWaiter",
+    "PTHREAD_MUTEX_OWNER_OFFSET",
+    "WaitLink",
+    "blocked_on",
+    "deadlocks_in",
+    "detect_deadlocks",
+    "detect_wait_chain",
+    "find_deadlocks",
+    "guarded_locks",
+    "pthread_owner_reader",
+    "session_owner_reader",
+    "wait_chain",
+]
@@ -0,0 +1,777 @@
+"""Turn mangled Rust and C++ symbols into readable names.
+
+Backends without language-aware demangling print ``_ZN5crash5crash17h...E``
+where the user wrote ``crash::crash``. `demangle` handles the three manglings
+a native backtrace is made of:
+
+- Rust legacy (``_ZN...17h<hash>E``), with rustc's ``$LT$``-style escapes;
+- Rust v0 (``_R...``): paths, inherent and trait impls (``<Foo as Display>``),
+  generic arguments, closures, back-references, and punycode identifiers;
+- Itanium C++ (``_Z...``): nested and local names, ``std::`` abbreviations and
+  substitutions, template arguments, operators, constructors, destructors,
+  lambdas, and thunks.
+
+Rust symbol hashes and C++ parameter lists are dropped, as are compiler clone
+suffixes (``.cold``, ``.llvm.1234``): they are noise in an explanation and
+would split one function into several signatures. Anything outside these
+grammars (C++ expressions in template arguments, vendor extensions) is
+returned unchanged rather than guessed at.
+
+`demangle_frame` applies it to a `Frame` and keeps the original name in
+``Frame.raw_symbol``.
+"""
+from __future__ import annotations
+
+import re
+from typing import Callable, List, Optional, Tuple
+
+from .model import Frame
+
+_RUST_HASH_RE = re.compile(r"^h[0-9a-f]{16}$")
+# A name a debugger already demangled, still carrying the legacy hash.
+_RUST_HASH_SUFFIX_RE = re.compile(r"::h[0-9a-f]{16}$")
+# Escapes rustc's legacy mangling uses for characters outside [A-Za-z0-9_].
+_RUST_ESCAPES = {
+    "$SP$": "@", "$BP$": "*", "$RF$": "&", "$LT$": "<", "$GT$": ">", "$LP$": "(", "$RP$": ")", "$C$": ",",
+}
+_RUST_UNICODE_ESCAPE_RE = re.compile(r"\$u([0-9a-f]{2,6})\$")
+# GCC and LLVM clones of a function: foo.cold, foo.isra.0, foo.constprop.1, foo.llvm.123456.
+_CLONE_SUFFIX_RE = re.compile(r"(?:\.(?:cold|hot|isra|constprop|part|lto_priv|clone|localalias|llvm)(?:\.\d+)*)+$")
+_BASE62 = "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ"
+_BASE36 = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ"
+# Nesting limit for back-references and recursive types, against malicious or corrupt names.
+_MAX_DEPTH = 100
+
+
+class _Unsupported(Exception):
+    """Mangling this demangler does not handle; the name passes through."""
+
+
+class _Reader:
+    def __init__(self, text: str, pos: int = 0) -> None:
+        self.text = text
+        self.pos = pos
+        self.depth = 0
+
+    def peek(self, ahead: int = 0) -> str:
+        i = self.pos + ahead
+        return self.text[i] if i < len(self.text) else ""
+
+    def eat(self, char: str) -> bool:
+        if self.peek() == char:
+            self.pos += 1
+            return True
+        return False
+
+    def expect(self, char: str) -> None:
+        if not self.eat(char):
+            raise _Unsupported
+
+    def number(self) -> int:
+        start = self.pos
+        while self.peek().isdigit():
+            self.pos += 1
+        if self.pos == start:
+            raise _Unsupported
+        return int(self.text[start : self.pos])
+
+    def nest(self) -> None:
+        self.depth += 1
+        if self.depth > _MAX_DEPTH:
+            raise _Unsupported
+
+
+# --- Rust legacy -------------------------------------------------------------------------------------------
+
+
+def _rust_legacy_part(part: str) -> str:
+    # rustc prefixes a component that would start with an escape with "_".
+    if part.startswith("_$"):
+        part = part[1:]
+    for code, char in _RUST_ESCAPES.items():
+        part = part.replace(code, char)
+    part = _RUST_UNICODE_ESCAPE_RE.sub(lambda m: chr(int(m.group(1), 16)), part)
+    return part.replace("..", "::")
+
+
+def _rust_legacy(name: str) -> Optional[str]:
+    """``_ZN<len><part>...17h<hash>E`` as a path; None when the name is not a Rust legacy symbol."""
+    r = _Reader(name, 3)
+    parts: List[str] = []
+    while r.peek().isdigit():
+        size = r.number()
+        if r.pos + size > len(name):
+            return None
+        parts.append(name[r.pos : r.pos + size])
+        r.pos += size
+    if len(parts) < 2 or r.peek() != "E" or not _RUST_HASH_RE.match(parts[-1]):
+        return None
+    return "::".join(_rust_legacy_part(p) for p in parts[:-1])
+
+
+# --- Itanium C++ -------------------------------------------------------------------------------------------
+
+_BUILTIN_TYPES = {
+    "v": "void", "w": "wchar_t", "b": "bool", "c": "char", "a": "signed char", "h": "unsigned char",
+    "s": "short", "t": "unsigned short", "i": "int", "j": "unsigned int", "l": "long", "m": "unsigned long",
+    "x": "long long", "y": "unsigned long long", "n": "__int128", "o": "unsigned __int128", "f": "float",
+    "d": "double", "e": "long double", "g": "__float128", "z": "...",
+}
+_D_TYPES = {
+    "Dn": "decltype(nullptr)", "Da": "auto", "Dc": "decltype(auto)", "Ds": "char16_t", "Di": "char32_t",
+    "Du": "char8_t", "Dh": "half", "Dd": "decimal64", "De": "decimal128", "Df": "decimal32",
+}
+_STD_ABBREVIATIONS = {
+    "a": "std::allocator", "b": "std::basic_string", "s": "std::string", "i": "std::istream",
+    "o": "std::ostream", "d": "std::iostream",
+}
+_OPERATORS = {
+    "nw": "new", "na": "new[]", "dl": "delete", "da": "delete[]", "ps": "+", "ng": "-", "ad": "&", "de": "*",
+    "co": "~", "pl": "+", "mi": "-", "ml": "*", "dv": "/", "rm": "%", "an": "&", "or": "|", "eo": "^",
+    "aS": "=", "pL": "+=", "mI": "-=", "mL": "*=", "dV": "/=", "rM": "%=", "aN": "&=", "oR": "|=", "eO": "^=",
+    "ls": "<<", "rs": ">>", "lS": "<<=", "rS": ">>=", "eq": "==", "ne": "!=", "lt": "<", "gt": ">", "le": "<=",
+    "ge": ">=", "ss": "<=>", "nt": "!", "aa": "&&", "oo": "||", "pp": "++", "mm": "--", "cm": ",", "pm": "->*",
+    "pt": "->", "cl": "()", "ix": "[]", "qu": "?", "aw": "co_await",
+}
+_LITERAL_SUFFIXES = {"j": "u", "l": "l", "m": "ul", "x": "ll", "y": "ull"}
+
+
+class _Itanium(_Reader):
+    """Recursive-descent reader of the Itanium C++ ABI name grammar (the parts a backtrace needs)."""
+
+    def __init__(self, text: str) -> None:
+        super().__init__(text, 2)
+        self.subs: List[str] = []
+        # The template arguments T_, T0_, ... refer to: the last list read outside other template arguments.
+        self.template_params: List[str] = []
+        self.arg_depth = 0
+
+    def symbol(self) -> str:
+        if self.peek() == "T":
+            return self.special_name()
+        if self.peek() == "G" and self.peek(1) == "V":
+            self.pos += 2
+            return "guard variable for " + self.name()
+        return self.name()
+
+    def special_name(self) -> str:
+        self.pos += 1
+        kind = self.peek()
+        self.pos += 1
+        if kind == "h":
+            self.call_offset_body()
+            return "non-virtual thunk to " + self.symbol()
+        if kind == "v":
+            self.call_offset_body()
+            self.call_offset_body()
+            return "virtual thunk to " + self.symbol()
+        labels = {"V": "vtable for ", "T": "VTT for ", "I": "typeinfo for ", "S": "typeinfo name for "}
+        if kind in labels:
+            return labels[kind] + self.type()
+        raise _Unsupported
+
+    def call_offset_body(self) -> None:
+        self.eat("n")
+        self.number()
+        self.expect("_")
+
+    def name(self) -> str:
+        c = self.peek()
+        if c == "N":
+            return self.nested_name()
+        if c == "Z":
+            return self.local_name()
+        if c == "S" and self.peek(1) == "t":
+            self.pos += 2
+            name = "std::" + self.unqualified_name()[0]
+        elif c == "S":
+            name = self.substitution()
+            if self.peek() != "I":
+                raise _Unsupported
+        else:
+            self.eat("L")
+            name = self.unqualified_name()[0]
+        if self.peek() == "I":
+            self.subs.append(name)
+            name += self.template_args()
+        return name
+
+    def local_name(self) -> str:
+        self.pos += 1
+        function = self.symbol()
+        # The enclosing function's parameter types, up to the E that ends its encoding.
+        while self.peek() not in ("E", ""):
+            self.type()
+        self.expect("E")
+        if self.eat("s"):
+            entity = "string literal"
+        else:
+            entity = self.name()
+        # Discriminator: _<digit> or __<number>_.
+        if self.eat("_"):
+            if self.eat("_"):
+                self.number()
+                self.expect("_")
+            else:
+                self.number()
+        return f"{function}::{entity}"
+
+    def nested_name(self) -> str:
+        self.pos += 1
+        # CV- and ref-qualifiers of a member function (_ZNK3Foo3barEv is Foo::bar() const); dropped.
+        while self.peek() in ("r", "V", "K", "R", "O") and self.peek():
+            self.pos += 1
+        prefix: Optional[str] = None
+        last = ""
+        while not self.eat("E"):
+            c = self.peek()
+            if not c:
+                raise _Unsupported
+            if c == "S" and self.peek(1) == "t":
+                self.pos += 2
+                prefix = "std"
+                continue
+            if c == "S":
+                prefix = self.substitution()
+                continue
+            if c == "I":
+                if prefix is None:
+                    raise _Unsupported
+                prefix += self.template_args()
+            elif c == "T":
+                prefix = self.template_param()
+            elif c in "CD" and self.peek(1) in "012345" and self.peek(1):
+                if not last:
+                    raise _Unsupported
+                component = ("~" if c == "D" else "") + last
+                self.pos += 2
+                prefix = component if prefix is None else f"{prefix}::{component}"
+            elif c == "L":
+                self.pos += 1
+                continue
+            else:
+                component, last_name = self.unqualified_name()
+                last = last_name or last
+                prefix = component if prefix is None else f"{prefix}::{component}"
+            if self.peek() != "E":
+                self.subs.append(prefix)
+        if prefix is None:
+            raise _Unsupported
+        return prefix
+
+    def unqualified_name(self) -> Tuple[str, str]:
+        """The component and, for class names, the plain name a constructor repeats."""
+        c = self.peek()
+        if c.isdigit():
+            name = self.source_name()
+            if name.startswith("_GLOBAL__N"):
+                name = "(anonymous namespace)"
+            result = (name, name)
+        elif c == "U":
+            result = (self.unnamed_type(), "")
+        elif c.islower() or (c and self.text[self.pos : self.pos + 2] in _OPERATORS):
+            result = (self.operator_name(), "")
+        else:
+            raise _Unsupported
+        # ABI tags ([abi:cxx11]) do not change which function it is.
+        while self.peek() == "B":
+            self.pos += 1
+            self.source_name()
+        return result
+
+    def source_name(self) -> str:
+        size = self.number()
+        if self.pos + size > len(self.text):
+            raise _Unsupported
+        name = self.text[self.pos : self.pos + size]
+        self.pos += size
+        return name
+
+    def operator_name(self) -> str:
+        code = self.text[self.pos : self.pos + 2]
+        self.pos += 2
+        if code == "cv":
+            return "operator " + self.type()
+        if code == "li":
+            return 'operator"" ' + self.source_name()
+        if code not in _OPERATORS:
+            raise _Unsupported
+        op = _OPERATORS[code]
+        return f"operator {op}" if op[0].isalpha() else f"operator{op}"
+
+    def unnamed_type(self) -> str:
+        self.pos += 1
+        kind = self.peek()
+        self.pos += 1
+        if kind == "l":
+            params = []
+            while not self.eat("E"):
+                if not self.peek():
+                    raise _Unsupported
+                params.append(self.type())
+            label = "lambda(" + ", ".join(p for p in params if p != "void") + ")"
+        elif kind == "t":
+            label = "unnamed type"
+        else:
+            raise _Unsupported
+        index = self.number() + 2 if self.peek().isdigit() else 1
+        self.expect("_")
+        return f"{{{label}#{index}}}"
+
+    def seq_id(self) -> int:
+        """``_`` is 0, otherwise a base-36 number then ``_``, plus one."""
+        if self.eat("_"):
+            return 0
+        value = 0
+        start = self.pos
+        while self.peek() and self.peek() in _BASE36:
+            value = value * 36 + _BASE36.index(self.peek())
+            self.pos += 1
+        if self.pos == start:
+            raise _Unsupported
+        self.expect("_")
+        return value + 1
+
+    def substitution(self) -> str:
+        self.pos += 1
+        c = self.peek()
+        if c in _STD_ABBREVIATIONS:
+            self.pos += 1
+            return _STD_ABBREVIATIONS[c]
+        index = self.seq_id()
+        if index >= len(self.subs):
+            raise _Unsupported
+        return self.subs[index]
+
+    def template_param(self) -> str:
+        self.pos += 1
+        index = self.seq_id()
+        if index >= len(self.template_params):
+            raise _Unsupported
+        param = self.template_params[index]
+        self.subs.append(param)
+        return param
+
+    def template_args(self) -> str:
+        self.expect("I")
+        self.arg_depth += 1
+        args: List[str] = []
+        while not self.eat("E"):
+            if not self.peek():
+                raise _Unsupported
+            args.append(self.template_arg())
+        self.arg_depth -= 1
+        if self.arg_depth == 0:
+            self.template_params = args
+        return "<" + ", ".join(args) + ">"
+
+    def template_arg(self) -> str:
+        c = self.peek()
+        if c == "L":
+            return self.literal()
+        if c == "J":
+            self.pos += 1
+            pack = []
+            while not self.eat("E"):
+                if not self.peek():
+                    raise _Unsupported
+                pack.append(self.template_arg())
+            return ", ".join(pack)
+        if c == "X":
+            # Expressions need the whole expression grammar.
+            raise _Unsupported
+        return self.type()
+
+    def literal(self) -> str:
+        self.pos += 1
+        if self.peek() == "_" and self.peek(1) == "Z":
+            raise _Unsupported
+        kind = self.peek()
+        if kind not in _BUILTIN_TYPES:
+            raise _Unsupported
+        self.pos += 1
+        negative = self.eat("n")
+        start = self.pos
+        while self.peek() and self.peek() != "E":
+            self.pos += 1
+        value = ("-" if negative else "") + self.text[start : self.pos]
+        self.expect("E")
+        if kind == "b" and value in ("0", "1"):
+            return "true" if value == "1" else "false"
+        if kind == "i":
+            return value
+        if kind in _LITERAL_SUFFIXES:
+            return value + _LITERAL_SUFFIXES[kind]
+        return f"({_BUILTIN_TYPES[kind]}){value}"
+
+    def type(self) -> str:
+        self.nest()
+        try:
+            return self._type()
+        finally:
+            self.depth -= 1
+
+    def _type(self) -> str:
+        c = self.peek()
+        if not c:
+            raise _Unsupported
+        two = self.text[self.pos : self.pos + 2]
+        if c in _BUILTIN_TYPES:
+            self.pos += 1
+            return _BUILTIN_TYPES[c]
+        if two in _D_TYPES:
+            self.pos += 2
+            return _D_TYPES[two]
+        if c == "u":
+            self.pos += 1
+            return self.source_name()
+        if two == "Dp":
+            self.pos += 2
+            result = self.type() + "..."
+        elif c in "rVK":
+            quals = []
+            while self.peek() in ("r", "V", "K") and self.peek():
+                quals.append({"r": "restrict", "V": "volatile", "K": "const"}[self.peek()])
+                self.pos += 1
+            result = self.type() + " " + " ".join(reversed(quals))
+        elif c in "PRO":
+            self.pos += 1
+            sigil = {"P": "*", "R": "&", "O": "&&"}[c]
+            if self.peek() == "F":
+                ret, params = self.function_type()
+                result = f"{ret} ({sigil})({params})"
+            else:
+                result = self.type() + sigil
+        elif c == "F":
+            ret, params = self.function_type()
+            result = f"{ret} ({params})"
+        elif c == "A":
+            self.pos += 1
+            size = str(self.number()) if self.peek().isdigit() else ""
+            self.expect("_")
+            result = f"{self.type()} [{size}]"
+        elif c == "M":
+            self.pos += 1
+            cls = self.type()
+            result = f"{self.type()} {cls}::*"
+        elif c == "T":
+            result = self.template_param()
+            if self.peek() == "I":
+                result += self.template_args()
+            else:
+                return result
+        elif c == "N":
+            result = self.nested_name()
+        elif c == "S" and self.peek(1) == "t":
+            self.pos += 2
+            result = "std::" + self.unqualified_name()[0]
+            if self.peek() == "I":
+                self.subs.append(result)
+                result += self.template_args()
+        elif c == "S":
+            result = self.substitution()
+            if self.peek() != "I":
+                return result
+            result += self.template_args()
+        elif c.isdigit():
+            result = self.source_name()
+            if self.peek() == "I":
+                self.subs.append(result)
+                result += self.template_args()
+        else:
+            raise _Unsupported
+        self.subs.append(result)
+        return result
+
+    def function_type(self) -> Tuple[str, str]:
+        self.expect("F")
+        self.eat("Y")
+        ret = self.type()
+        params = []
+        while not self.eat("E"):
+            if self.peek() in ("R", "O") and self.peek(1) == "E":
+                self.pos += 1
+                continue
+            if not self.peek():
+                raise _Unsupported
+            params.append(self.type())
+        return ret, ", ".join(p for p in params if p != "void")
+
+
+# --- Rust v0 -----------------------------------------------------------------------------------------------
+
+_V0_BASIC_TYPES = {
+    "a": "i8", "b": "bool", "c": "char", "d": "f64", "e": "str", "f": "f32", "h": "u8", "i": "isize",
+    "j": "usize", "l": "i32", "m": "u32", "n": "i128", "o": "u128", "s": "i16", "t": "u16", "u": "()",
+    "v": "...", "x": "i64", "y": "u64", "z": "!", "p": "_",
+}
+
+
+class _RustV0(_Reader):
+    """Reader of the Rust v0 symbol grammar (RFC 2603); positions are relative to after ``_R``."""
+
+    def base62(self) -> int:
+        if self.eat("_"):
+            return 0
+        value = 0
+        start = self.pos
+        while self.peek() and self.peek() in _BASE62:
+            value = value * 62 + _BASE62.index(self.peek())
+            self.pos += 1
+        if self.pos == start:
+            raise _Unsupported
+        self.expect("_")
+        return value + 1
+
+    def disambiguator(self) -> None:
+        if self.eat("s"):
+            self.base62()
+
+    def identifier(self) -> str:
+        self.disambiguator()
+        punycode = self.eat("u")
+        size = self.number()
+        self.eat("_")
+        if self.pos + size > len(self.text):
+            raise _Unsupported
+        raw = self.text[self.pos : self.pos + size]
+        self.pos += size
+        if not punycode:
+            return raw
+        # The punycode delimiter is "_" in symbols, "-" in RFC 3492.
+        basic, _, delta = raw.rpartition("_")
+        try:
+            return (f"{basic}-{delta}" if basic else delta).encode("ascii").decode("punycode")
+        except (UnicodeError, ValueError) as exc:
+            raise _Unsupported from exc
+
+    def backref(self, parse: Callable[[], str]) -> str:
+        self.pos += 1
+        target = self.base62()
+        if target >= self.pos:
+            raise _Unsupported
+        saved = self.pos
+        self.pos = target
+        self.nest()
+        try:
+            return parse()
+        finally:
+            self.depth -= 1
+            self.pos = saved
+
+    def path(self, in_value: bool = True) -> str:
+        self.nest()
+        try:
+            return self._path(in_value)
+        finally:
+            self.depth -= 1
+
+    def _path(self, in_value: bool) -> str:
+        tag = self.peek()
+        if not tag:
+            raise _Unsupported
+        self.pos += 1
+        if tag == "C":
+            return self.identifier()
+        if tag == "N":
+            namespace = self.peek()
+            self.pos += 1
+            parent = self.path(in_value)
+            ident = self.identifier()
+            if namespace == "C":
+                return f"{parent}::{{closure}}"
+            if namespace.isupper():
+                return f"{parent}::{{{ident or 'shim'}}}"
+            return f"{parent}::{ident}"
+        if tag == "M":
+            self.disambiguator()
+            self.path()
+            return f"<{self.type()}>"
+        if tag == "X":
+            self.disambiguator()
+            self.path()
+            self_type = self.type()
+            return f"<{self_type} as {self.path(in_value=False)}>"
+        if tag == "Y":
+            self_type = self.type()
+            return f"<{self_type} as {self.path(in_value=False)}>"
+        if tag == "I":
+            base = self.path(in_value)
+            args = []
+            while not self.eat("E"):
+                if not self.peek():
+                    raise _Unsupported
+                arg = self.generic_arg()
+                if arg is not None:
+                    args.append(arg)
+            if not args:
+                return base
+            return f"{base}{'::' if in_value else ''}<{', '.join(args)}>"
+        if tag == "B":
+            self.pos -= 1
+            return self.backref(lambda: self.path(in_value))
+        raise _Unsupported
+
+    def generic_arg(self) -> Optional[str]:
+        if self.eat("L"):
+            # Lifetimes are erased in symbols; printing '_ everywhere would only add noise.
+            self.base62()
+            return None
+        if self.eat("K"):
+            return self.const()
+        return self.type()
+
+    def const(self) -> str:
+        if self.peek() == "B":
+            return self.backref(self.const)
+        if self.eat("p"):
+            return "_"
+        kind = self.peek()
+        if kind not in _V0_BASIC_TYPES:
+            raise _Unsupported
+        self.pos += 1
+        negative = self.eat("n")
+        start = self.pos
+        while self.peek() and self.peek() in "0123456789abcdef":
+            self.pos += 1
+        digits = self.text[start : self.pos]
+        self.expect("_")
+        value = int(digits, 16) if digits else 0
+        if kind == "b":
+            return "true" if value else "false"
+        if kind == "c":
+            return repr(chr(value))
+        return str(-value if negative else value)
+
+    def binder(self) -> None:
+        if self.eat("G"):
+            self.base62()
+
+    def type(self) -> str:
+        self.nest()
+        try:
+            return self._type()
+        finally:
+            self.depth -= 1
+
+    def _type(self) -> str:
+        c = self.peek()
+        if not c:
+            raise _Unsupported
+        if c in _V0_BASIC_TYPES:
+            self.pos += 1
+            return _V0_BASIC_TYPES[c]
+        if c == "B":
+            return self.backref(self.type)
+        self.pos += 1
+        if c in "RQ":
+            if self.eat("L"):
+                self.base62()
+            return ("&" if c == "R" else "&mut ") + self.type()
+        if c in "PO":
+            return ("*const " if c == "P" else "*mut ") + self.type()
+        if c == "A":
+            inner = self.type()
+            return f"[{inner}; {self.const()}]"
+        if c == "S":
+            return f"[{self.type()}]"
+        if c == "T":
+            items = []
+            while not self.eat("E"):
+                if not self.peek():
+                    raise _Unsupported
+                items.append(self.type())
+            return "(" + ", ".join(items) + ("," if len(items) == 1 else "") + ")"
+        if c == "F":
+            return self.fn_sig()
+        if c == "D":
+            return self.dyn_bounds()
+        if c in "CNMXYI":
+            self.pos -= 1
+            return self.path(in_value=False)
+        raise _Unsupported
+
+    def fn_sig(self) -> str:
+        self.binder()
+        prefix = "unsafe " if self.eat("U") else ""
+        if self.eat("K"):
+            abi = "C" if self.eat("C") else self.identifier().replace("_", "-")
+            prefix += f'extern "{abi}" '
+        params = []
+        while not self.eat("E"):
+            if not self.peek():
+                raise _Unsupported
+            params.append(self.type())
+        ret = self.type()
+        return f"{prefix}fn({', '.join(params)})" + ("" if ret == "()" else f" -> {ret}")
+
+    def dyn_bounds(self) -> str:
+        self.binder()
+        traits = []
+        while not self.eat("E"):
+            if not self.peek():
+                raise _Unsupported
+            trait = self.path(in_value=False)
+            bindings = []
+            while self.eat("p"):
+                size = self.number()
+                self.eat("_")
+                name = self.text[self.pos : self.pos + size]
+                self.pos += size
+                bindings.append(f"{name} = {self.type()}")
+            if bindings:
+                joined = ", ".join(bindings)
+                trait = f"{trait[:-1]}, {joined}>" if trait.endswith(">") else f"{trait}<{joined}>"
+            traits.append(trait)
+        # The object lifetime bound.
+        if self.eat("L"):
+            self.base62()
+        return "dyn " + " + ".join(traits)
+
+
+def _rust_v0(name: str) -> str:
+    r = _RustV0(name[2:])
+    while r.peek().isdigit():
+        r.pos += 1  # encoding version
+    return r.path()
+
+
+def demangle(name: str) -> str:
+    """Readable form of a Rust (legacy or v0) or C++ symbol; other names are returned as is.
+
+    ``_ZN10rust_crash5crash17h0123456789abcdefE`` becomes ``rust_crash::crash``,
+    ``_ZNSt6vectorIiSaIiEE9push_backERKi`` ``std::vector<int, std::allocator<int>>::push_back``.
+    """
+    mangled = name[1:] if name.startswith("__Z") or name.startswith("__R") else name
+    mangled = _CLONE_SUFFIX_RE.sub("", mangled)
+    try:
+        if mangled.startswith("_ZN"):
+            legacy = _rust_legacy(mangled)
+            if legacy is not None:
+                return legacy
+        if mangled.startswith("_Z"):
+            return _Itanium(mangled).symbol()
+        if mangled.startswith("_R"):
+            return _rust_v0(mangled)
+    except (_Unsupported, IndexError, ValueError, RecursionError):
+        return name
+    return _RUST_HASH_SUFFIX_RE.sub("", name)
+
+
+def demangle_frame(frame: Frame) -> Frame:
+    """Demangle ``frame.function`` in place, keeping the original in ``raw_symbol``."""
+    if frame.function:
+        readable = demangle(frame.function)
+        if readable != frame.function:
+            frame.raw_symbol = frame.raw_symbol or frame.function
+            frame.function = readable
+    return frame
+
+
+def demangle_frames(frames: List[Frame]) -> List[Frame]:
+    for frame in frames:
+        demangle_frame(frame)
+    return frames
+
+
+__all__ = ["demangle", "demangle_frame", "demangle_frames"]
@@ -0,0 +1,305 @@
+"""Compare two reports of the same program: did a change fix the crash, or move it?
+
+In a bisect, or before and after a fix, the question is not "what crashed"
+but "is it still the same crash". `diff_reports` compares the fault kind,
+the region the fault address falls in (null page, user space, kernel, ...),
+and the top frames as `CrashReport.signature` names them, without offsets
+and addresses, so a rebuild or ASLR alone never counts as a change. The
+verdict is one of:
+
+- ``SAME`` — the same kind of fault in the same frames;
+- ``DIFFERENT_LOCATION`` — it still fails, but elsewhere or differently.
+  `ReportDiff.stack_change` says whether only the faulting frame moved
+  (the callers are unchanged) or the stack itself is different;
+- ``FIXED`` — the first report failed and the second did not.
+
+A hang counts as a failure like a crash does.
+
+When a crash starts with a release, what changed around it matters as much:
+`ReportDiff.module_changes` lists the libraries that appeared, went away, or
+changed version (matched by `logical_name`, so ``libssl.so.1.1`` and
+``libssl.so.3`` are one library at two versions), and
+`ReportDiff.divergent_frame` is the first of the top frames that differs.
+`build_diff_prompt` hands a model just those differences and the two top
+stacks, not two full reports, and `explain_diff` asks it what they mean.
+"""
+from __future__ import annotations
+
+from dataclasses import dataclass, field
+from enum import Enum
+from typing import TYPE_CHECKING, Any, Dict, List, Mapping, Optional, Union
+
+from .crash import SIGNATURE_DEPTH, CrashReport, _signature_frame
+from .fault import FaultKind
+from .model import Module
+from .modules import logical_name, module_version
+
+if TYPE_CHECKING:
+    from dbgcopilot.llm.base import LlmProvider, Prompt
+
+    from .api import AnalysisReport
+
+# Faults below this address are NULL plus a field offset.
+NULL_PAGE_SIZE = 0x1000
+# x86-64 user space ends here; kernel addresses start at _KERNEL_BASE.
+_USER_END = 0x0000_7FFF_FFFF_FFFF
+_KERNEL_BASE = 0xFFFF_8000_0000_0000
+# Frames of each stack `build_diff_prompt` shows, with their source lines.
+DIFF_PROMPT_FRAMES = 8
+DIFF_INSTRUCTIONS = (
+    "You compare two crash reports of the same program, an earlier run and a later one. "
+    "Only the differences between them are listed. Say in a few sentences what changed, "
+    "which difference most likely explains the new or moved failure, and what to check first."
+)
+
+
+class DiffVerdict(str, Enum):
+    SAME = "same"
+    DIFFERENT_LOCATION = "different_location"
+    FIXED = "fixed"
+
+    @property
+    def label(self) -> str:
+        return _VERDICT_LABELS[self]
+
+
+_VERDICT_LABELS = {
+    DiffVerdict.SAME: "same failure",
+    DiffVerdict.DIFFERENT_LOCATION: "different failure",
+    DiffVerdict.FIXED: "fixed (the second run did not fail)",
+}
+
+
+class StackChange(str, Enum):
+    """How the top frames differ between the two reports."""
+
+    NONE = "none"
+    # Only the faulting frame differs; its callers are the same.
+    LEAF = "leaf"
+    # The faulting frame is the same, some of its callers are not.
+    CALLERS = "callers"
+    # The faulting frame and its callers differ.
+    STACK = "stack"
+
+
+def address_bucket(address: Optional[int]) -> Optional[str]:
+    """Region of a fault address that survives ASLR: ``null``, ``user``, ``non-canonical``, or ``kernel``."""
+    if address is None:
+        return None
+    if address < NULL_PAGE_SIZE:
+        return "null"
+    if address <= _USER_END:
+        return "user"
+    if address < _KERNEL_BASE:
+        return "non-canonical"
+    return "kernel"
+
+
+def _failed(report: CrashReport) -> bool:
+    return report.signal is not None or report.exception_code is not None or report.fault_kind is FaultKind.HANG
+
+
+def _frame_signatures(report: CrashReport, depth: int) -> List[str]:
+    # No offsets: a fix that grows a function must not make the same frame look different.
+    return [_signature_frame(f, report.modules, with_offset=False) for f in report.frames[:depth]]
+
+
+@dataclass
+class ReportDiff:
+    verdict: DiffVerdict
+    stack_change: StackChange = StackChange.NONE
+    # What differs, one phrase each ("fault kind: null-pointer dereference -> abort", ...).
+    changes: List[str] = field(default_factory=list)
+    before_frames: List[str] = field(default_factory=list)
+    after_frames: List[str] = field(default_factory=list)
+    # Libraries that appeared, went away, or changed version, one phrase each; see `module_changes`.
+    module_changes: List[str] = field(default_factory=list)
+    # Index of the first of the compared frames that differs; None when they are the same.
+    divergent_frame: Optional[int] = None
+    # From `explain_diff`, when a model was asked; a failure is recorded instead of raised.
+    explanation: Optional[str] = None
+    explanation_error: Optional[str] = None
+
+    def describe(self) -> str:
+        lines = [f"Verdict: {self.verdict.label}"]
+        lines.extend(f"- {change}" for change in self.changes)
+        if self.before_frames or self.after_frames:
+            lines.append("Before: " + (" -> ".join(self.before_frames) or "(no frames)"))
+            lines.append("After:  " + (" -> ".join(self.after_frames) or "(no frames)"))
+        if self.divergent_frame is not None:
+            lines.append(f"First differing frame: #{self.divergent_frame}")
+        if self.module_changes:
+            lines.append("Modules:")
+            lines.extend(f"- {change}" for change in self.module_changes)
+        if self.explanation:
+            lines.append(f"Explanation:\n{self.explanation}")
+        elif self.explanation_error:
+            lines.append(f"Explanation unavailable: {self.explanation_error}")
+        return "\n".join(lines)
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {
+            "verdict": self.verdict.value,
+            "stack_change": self.stack_change.value,
+            "changes": list(self.changes),
+            "before_frames": list(self.before_frames),
+            "after_frames": list(self.after_frames),
+            "divergent_frame": self.divergent_frame,
+            "module_changes": list(self.module_changes),
+            "explanation": self.explanation,
+            "explanation_error": self.explanation_error,
+        }
+
+
+def _stack_change(before: List[str], after: List[str]) -> StackChange:
+    if before == after:
+        return StackChange.NONE
+    matches = [a == b for a, b in zip(before, after)]
+    if matches and matches[0]:
+        return StackChange.CALLERS
+    if len(before) == len(after) > 1 and all(matches[1:]):
+        return StackChange.LEAF
+    return StackChange.STACK
+
+
+def _divergence(before: List[str], after: List[str]) -> Optional[int]:
+    for i, (a, b) in enumerate(zip(before, after)):
+        if a != b:
+            return i
+    return None if len(before) == len(after) else min(len(before), len(after))
+
+
+def _versions(modules: List[Module]) -> Dict[str, Module]:
+    found: Dict[str, Module] = {}
+    for m in modules:
+        # Pseudo-images such as [vdso] and [heap] are not libraries.
+        if not m.name.startswith("[") and not (m.path or "").startswith("["):
+            found.setdefault(logical_name(m), m)
+    return found
+
+
+def _version(module: Module) -> Optional[str]:
+    return module.version or module_version(module.path or module.name)
+
+
+def module_changes(before: List[Module], after: List[Module]) -> List[str]:
+    """Libraries new in ``after``, gone from it, or at another version, in load order (new and changed first)."""
+    old, new = _versions(before), _versions(after)
+    changes = []
+    for name, m in new.items():
+        if name not in old:
+            version = _version(m)
+            changes.append(f"new: {name}" + (f" {version}" if version else "") + (f" ({m.path})" if m.path else ""))
+        elif _version(old[name]) != _version(m):
+            changes.append(f"version: {name} {_version(old[name]) or '?'} -> {_version(m) or '?'}")
+    changes.extend(f"gone: {name}" for name in old if name not in new)
+    return changes
+
+
+def _as_crash_report(report: Union[CrashReport, "AnalysisReport"]) -> CrashReport:
+    if isinstance(report, CrashReport):
+        return report
+    return report.crash_report(max_frames=len(report.frames))
+
+
+def diff_reports(
+    before: Union[CrashReport, "AnalysisReport"],
+    after: Union[CrashReport, "AnalysisReport"],
+    depth: int = SIGNATURE_DEPTH,
+) -> ReportDiff:
+    """Compare ``after`` with ``before`` over the top ``depth`` frames."""
+    old, new = _as_crash_report(before), _as_crash_report(after)
+    modules = module_changes(old.modules, new.modules)
+    if not _failed(new):
+        verdict = DiffVerdict.FIXED if _failed(old) else DiffVerdict.SAME
+        return ReportDiff(verdict, before_frames=_frame_signatures(old, depth), module_changes=modules)
+    old_frames, new_frames = _frame_signatures(old, depth), _frame_signatures(new, depth)
+    changes = []
+    if not _failed(old):
+        changes.append("the first run did not fail")
+    if old.fault_kind is not new.fault_kind:
+        changes.append(f"fault kind: {old.fault_kind.label} -> {new.fault_kind.label}")
+    old_bucket, new_bucket = address_bucket(old.fault_address), address_bucket(new.fault_address)
+    if old_bucket != new_bucket:
+        changes.append(f"fault address: {old_bucket or 'none'} -> {new_bucket or 'none'}")
+    stack_change = _stack_change(old_frames, new_frames)
+    if stack_change is StackChange.LEAF:
+        changes.append(f"only the faulting frame moved: {old_frames[0]} -> {new_frames[0]}; its callers are the same")
+    elif stack_change is StackChange.CALLERS:
+        changes.append("the faulting frame is the same, but it was called from elsewhere")
+    elif stack_change is StackChange.STACK:
+        same = sum(a == b for a, b in zip(old_frames, new_frames))
+        changes.append("the stack is entirely different" if not same else "the faulting frame and its callers differ")
+    verdict = DiffVerdict.DIFFERENT_LOCATION if changes else DiffVerdict.SAME
+    divergent = _divergence(old_frames, new_frames)
+    return ReportDiff(verdict, stack_change, changes, old_frames, new_frames, modules, divergent)
+
+
+def _stack_lines(report: CrashReport) -> List[str]:
+    stop = " ".join(p for p in (report.signal or "-", report.description) if p)
+    lines = [f"Stop: {stop}", f"Classification: {report.hint or report.fault_kind.label}"]
+    lines.extend(f.describe() for f in report.frames[:DIFF_PROMPT_FRAMES])
+    return lines
+
+
+def build_diff_prompt(
+    diff: ReportDiff,
+    before: Union[CrashReport, "AnalysisReport"],
+    after: Union[CrashReport, "AnalysisReport"],
+    llm_config: Optional[Mapping[str, Any]] = None,
+) -> "Prompt":
+    """What differs between the two reports, and their top stacks, redacted as crash prompts are."""
+    from dbgcopilot.llm.base import Prompt
+
+    from .redact import Redactor
+
+    lines = [f"Verdict: {diff.verdict.label}"]
+    lines.extend(f"- {change}" for change in diff.changes)
+    if diff.divergent_frame is not None:
+        lines.append(f"The stacks first differ at frame #{diff.divergent_frame}.")
+    if diff.module_changes:
+        lines.append("Module changes:")
+        lines.extend(f"- {change}" for change in diff.module_changes)
+    lines.append("Earlier run:")
+    lines.extend(_stack_lines(_as_crash_report(before)))
+    lines.append("Later run:")
+    lines.extend(_stack_lines(_as_crash_report(after)))
+    text = Redactor.from_config(llm_config).redact_text("\n".join(lines), "diff prompt")
+    return Prompt(text, system=DIFF_INSTRUCTIONS)
+
+
+def explain_diff(
+    diff: ReportDiff,
+    before: Union[CrashReport, "AnalysisReport"],
+    after: Union[CrashReport, "AnalysisReport"],
+    provider: Union[str, "LlmProvider", None] = None,
+    llm_config: Optional[Dict[str, Any]] = None,
+) -> None:
+    """Ask ``provider`` what the differences mean and set ``diff.explanation``; failures go to ``explanation_error``."""
+    from dbgcopilot.llm.base import resolve_provider
+    from dbgcopilot.llm.retry import RetryPolicy
+
+    try:
+        llm = resolve_provider(provider, llm_config)
+        completion, _ = RetryPolicy.from_config(llm_config).complete(
+            llm, build_diff_prompt(diff, before, after, llm_config)
+        )
+        diff.explanation = completion.text.strip()
+    except Exception as exc:
+        # The diff itself is still useful without the prose.
+        diff.explanation_error = str(exc)
+
+
+__all__ = [
+    "DIFF_INSTRUCTIONS",
+    "DIFF_PROMPT_FRAMES",
+    "DiffVerdict",
+    "NULL_PAGE_SIZE",
+    "ReportDiff",
+    "StackChange",
+    "address_bucket",
+    "build_diff_prompt",
+    "diff_reports",
+    "explain_diff",
+    "module_changes",
+]
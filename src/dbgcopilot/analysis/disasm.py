@@ -0,0 +1,177 @@
+"""The registers and instructions around the faulting pc.
+
+A frame's source line says what the program meant to do. The faulting
+instruction says what it did. The null write in ``examples/crash`` is
+``movl $0x2a,(%rax)`` with ``rax = 0x0``, and the line ``*ptr = 42`` alone
+does not show that. `FaultContext` holds the faulting thread's registers and
+a window of instructions around the pc, by default `DEFAULT_BEFORE`
+instructions before it and `DEFAULT_AFTER` after.
+
+`DebugSession.fault_context` returns one for a dump or process, and the batch
+backends capture one with the crash. gdb and lldb disassemble the faulting
+function, and the window is cut from that. cdb disassembles backwards with
+``ub``. The built-in dump readers keep the raw bytes at the pc (``code``) and
+have ``objdump`` decode them when it is installed (`dbgcopilot.dumps.objdump`).
+A thread without registers gives None, since there is no pc to centre on.
+
+`FaultContext.fault_line` names the registers in the faulting instruction's
+memory operand and their values, e.g. ``movl $0x2a,(%rax): write through rax
+= 0x0``. Reports always show the context. The prompt gets it only with
+``prompt_fault_context`` (``llm.prompt_fault_context``,
+``--prompt-fault-context``), because a dozen instructions cost tokens that
+most answers do not need. Templates can also place it with
+``{fault_context}``.
+"""
+from __future__ import annotations
+
+import re
+from dataclasses import dataclass, field
+from typing import Any, Dict, Iterable, List, Mapping, Optional, Sequence
+
+from .model import AccessKind, Instruction, Register
+from .parsers import instruction_access
+from .registers import Registers
+
+# Instructions shown before and after the faulting one.
+DEFAULT_BEFORE = 5
+DEFAULT_AFTER = 5
+# Bytes kept at the pc when there is no disassembly: the longest x86 instruction is 15.
+CODE_BYTES = 16
+
+# The memory operand: ``(%rax)``, ``0x18(%rdi,%rcx,8)``, ``[rax+18h]``, ``[x0, #8]``.
+_MEMORY_OPERAND_RE = re.compile(r"\(([^()]*)\)|\[([^\[\]]*)\]")
+_WORD_RE = re.compile(r"%?([A-Za-z][A-Za-z0-9]*)")
+
+
+def instruction_window(
+    instructions: Sequence[Instruction], pc: Optional[int], before: int = DEFAULT_BEFORE, after: int = DEFAULT_AFTER
+) -> List[Instruction]:
+    """Up to ``before`` instructions before the one at ``pc`` (or the marked one), it, and ``after`` more.
+
+    Empty when neither ``pc`` nor a mark is among ``instructions``.
+    """
+    at = next((i for i, insn in enumerate(instructions) if pc is not None and insn.address == pc), None)
+    if at is None:
+        at = next((i for i, insn in enumerate(instructions) if insn.faulting), None)
+    if at is None:
+        return []
+    return [
+        Instruction(insn.address, insn.text, insn.location, faulting=i == at)
+        for i, insn in enumerate(instructions)
+        if at - before <= i <= at + after
+    ]
+
+
+def memory_operand_registers(text: str, registers: Registers) -> List[str]:
+    """Registers in the memory operand of ``text`` that ``registers`` has, in operand order."""
+    _, _, operands = text.partition(" ")
+    names: List[str] = []
+    for match in _MEMORY_OPERAND_RE.finditer(operands):
+        for word in _WORD_RE.findall(match.group(1) or match.group(2) or ""):
+            name = word.lower()
+            if name in registers.values and name not in names:
+                names.append(name)
+    return names
+
+
+@dataclass
+class FaultContext:
+    """The faulting thread's registers and the instructions around its pc; see the module docstring."""
+
+    registers: List[Register] = field(default_factory=list)
+    # The window, the faulting instruction marked; empty when the engine could not disassemble.
+    instructions: List[Instruction] = field(default_factory=list)
+    # Raw bytes at the pc, from engines without a disassembler.
+    code: Optional[bytes] = None
+
+    @property
+    def named(self) -> Registers:
+        return Registers.from_list(self.registers)
+
+    @property
+    def pc(self) -> Optional[int]:
+        return self.named.instruction_pointer
+
+    @property
+    def faulting_instruction(self) -> Optional[Instruction]:
+        return next((insn for insn in self.instructions if insn.faulting), None)
+
+    def operands(self) -> List[Register]:
+        """The registers the faulting instruction addresses memory through, with their values."""
+        insn = self.faulting_instruction
+        if insn is None:
+            return []
+        named = self.named
+        return [Register(name, named.values[name]) for name in memory_operand_registers(insn.text, named)]
+
+    def fault_line(self) -> Optional[str]:
+        """``Faulting instruction: movl $0x2a,(%rax): write through rax = 0x0``; None without a disassembly."""
+        insn = self.faulting_instruction
+        if insn is None:
+            return None
+        line = f"Faulting instruction: {insn.text}"
+        through = ", ".join(f"{r.name} = 0x{r.value:x}" for r in self.operands())
+        if through:
+            access = instruction_access(insn.text)
+            line += f": {access.value if access is not AccessKind.UNKNOWN else 'access'} through {through}"
+        return line
+
+    def lines(self) -> List[str]:
+        """The faulting instruction and the window around it, or the bytes at the pc."""
+        fault = self.fault_line()
+        lines = [fault] if fault is not None else []
+        if self.instructions:
+            lines.append("Disassembly:")
+            lines.extend(insn.describe() for insn in self.instructions)
+        elif self.code and self.pc is not None:
+            lines.append(f"Code at 0x{self.pc:x}: {self.code.hex(' ')}")
+        return lines
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {
+            "instruction_pointer": f"0x{self.pc:x}" if self.pc is not None else None,
+            "registers": [{"name": r.name, "value": f"0x{r.value:x}"} for r in self.registers],
+            "instructions": [
+                {"address": f"0x{i.address:x}", "text": i.text, "location": i.location, "faulting": i.faulting}
+                for i in self.instructions
+            ],
+            "operands": [{"name": r.name, "value": f"0x{r.value:x}"} for r in self.operands()],
+            "code": self.code.hex() if self.code is not None else None,
+        }
+
+    @classmethod
+    def from_dict(cls, data: Mapping[str, Any]) -> "FaultContext":
+        return cls(
+            registers=[Register(r["name"], int(r["value"], 16)) for r in data.get("registers") or []],
+            instructions=[
+                Instruction(int(i["address"], 16), i["text"], i.get("location"), bool(i.get("faulting")))
+                for i in data.get("instructions") or []
+            ],
+            code=bytes.fromhex(data["code"]) if data.get("code") else None,
+        )
+
+
+def build_fault_context(
+    registers: Iterable[Register],
+    instructions: Sequence[Instruction] = (),
+    before: int = DEFAULT_BEFORE,
+    after: int = DEFAULT_AFTER,
+    code: Optional[bytes] = None,
+) -> Optional[FaultContext]:
+    """The context around the pc in ``registers``, cut from ``instructions``; None without registers."""
+    registers = list(registers)
+    if not registers:
+        return None
+    pc = Registers.from_list(registers).instruction_pointer
+    return FaultContext(registers, instruction_window(instructions, pc, before, after), code or None)
+
+
+__all__ = [
+    "CODE_BYTES",
+    "DEFAULT_AFTER",
+    "DEFAULT_BEFORE",
+    "FaultContext",
+    "build_fault_context",
+    "instruction_window",
+    "memory_operand_registers",
+]
@@ -0,0 +1,125 @@
+"""Report enrichers: a team's own knowledge, added to a report before the prompt.
+
+The analysis knows the stack, not the organization around it: that
+``libbilling.so`` belongs to the payments team, or that a signature is the
+known bug #1234. A `ReportEnricher` adds such facts to a `CrashReport` as
+`Annotation`s (``report.annotate("owners", "libbilling.so is owned by
+payments")``). They are shown under the classification in the text report,
+listed as ``annotations`` in the JSON, and sent to the model with the rest
+of the report, so the explanation can use them.
+
+Enrichers run after the capture and classification, just before the prompt
+is built. Only their annotations are kept: the analysis itself is not
+theirs to change, and an enricher that raises is logged and skipped.
+
+`analyze` runs the registered enrichers, or `AnalyzeRequest.enrichers` when
+set. `register_enricher` adds one from Python; installed packages add theirs
+through the ``dbgcopilot.enrichers`` entry point group, naming an enricher
+or a class that makes one::
+
+    [project.entry-points."dbgcopilot.enrichers"]
+    owners = "acme_crash.owners:OwnersEnricher"
+
+`SystemModuleEnricher` is built in and registered: it notes when the fault
+is inside a system library, where the bug is almost always the caller's.
+"""
+from __future__ import annotations
+
+import logging
+from importlib.metadata import entry_points
+from typing import Dict, Iterable, List, Optional, Protocol
+
+from .crash import CrashReport
+from .frames import is_system_module
+from .model import Annotation
+
+ENTRY_POINT_GROUP = "dbgcopilot.enrichers"
+
+log = logging.getLogger(__name__)
+
+
+class ReportEnricher(Protocol):
+    # Shown with each of its annotations; registering another enricher of the same name replaces it.
+    name: str
+
+    def enrich(self, report: CrashReport) -> None:  # pragma: no cover
+        """Add annotations to ``report`` with `CrashReport.annotate`."""
+        ...
+
+
+class SystemModuleEnricher:
+    """Notes a crash whose top frame is in libc, the loader, ntdll, or another system library."""
+
+    name = "system_modules"
+
+    def enrich(self, report: CrashReport) -> None:
+        top = report.frames[0] if report.frames else None
+        # A hang is usually sampled in a system call; that says nothing about who is at fault.
+        if top is None or not top.module or report.hang_category is not None or not is_system_module(top.module):
+            return
+        text = f"the fault is inside {top.module}, a system library"
+        user = report.first_user_frame()
+        if user is not None:
+            text += f"; the bug is far more likely in what frame {user.index} ({user.function or '??'}) passed it"
+        report.annotate(self.name, text)
+
+
+_registry: Dict[str, ReportEnricher] = {}
+_loaded = False
+
+
+def _load_entry_points() -> None:
+    global _loaded
+    if _loaded:
+        return
+    _loaded = True
+    _registry.setdefault(SystemModuleEnricher.name, SystemModuleEnricher())
+    for entry in entry_points(group=ENTRY_POINT_GROUP):
+        try:
+            found = entry.load()
+            enricher = found() if isinstance(found, type) else found
+        except Exception as exc:
+            log.warning("enricher %s (%s) could not be loaded: %s", entry.name, entry.value, exc)
+            continue
+        _registry.setdefault(getattr(enricher, "name", entry.name), enricher)
+
+
+def register_enricher(enricher: ReportEnricher) -> None:
+    """Run ``enricher`` in every later `analyze`, in place of any registered under its name."""
+    _load_entry_points()
+    _registry[enricher.name] = enricher
+
+
+def unregister_enricher(name: str) -> None:
+    """Stop running the enricher registered as ``name``, built-in or not; unknown names are ignored."""
+    _load_entry_points()
+    _registry.pop(name, None)
+
+
+def registered_enrichers() -> List[ReportEnricher]:
+    """The built-in, entry-point, and `register_enricher` enrichers, in the order they run."""
+    _load_entry_points()
+    return list(_registry.values())
+
+
+def enrich_report(report: CrashReport, enrichers: Optional[Iterable[ReportEnricher]] = None) -> List[Annotation]:
+    """Run ``enrichers`` (the registered ones when None) over ``report``; returns its annotations."""
+    for enricher in registered_enrichers() if enrichers is None else enrichers:
+        name = getattr(enricher, "name", type(enricher).__name__)
+        try:
+            enricher.enrich(report)
+        except Exception as exc:
+            # Someone else's plugin must not cost the crash report.
+            log.warning("enricher %s failed: %s", name, exc)
+    return report.annotations
+
+
+__all__ = [
+    "ENTRY_POINT_GROUP",
+    "ReportEnricher",
+    "SystemModuleEnricher",
+    "enrich_report",
+    "register_enricher",
+    "registered_enrichers",
+    "unregister_enricher",
+]
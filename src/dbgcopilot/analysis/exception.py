@@ -0,0 +1,206 @@
+"""One exception model for POSIX signals and Windows exception codes.
+
+The same bug stops a Linux process with ``SIGSEGV`` and a Windows one with
+``0xC0000005``; `normalize_stop` maps both to `ExceptionKind.MEMORY_ACCESS`,
+so reports from either platform can be compared (and tested) on one value:
+
+| ExceptionKind | POSIX signals | Windows codes |
+|---|---|---|
+| `memory_access` | SIGSEGV, SIGBUS | access violation, in-page error, stack overflow, misalignment |
+| `arithmetic` | SIGFPE | integer divide-by-zero and overflow, float exceptions |
+| `illegal_instruction` | SIGILL | illegal instruction, privileged instruction |
+| `abort` | SIGABRT | fail-fast (``__fastfail``), abort(), heap corruption |
+| `breakpoint` | SIGTRAP | breakpoint, single step |
+
+This is the platform's own account of the stop; `FaultKind` stays the
+heuristic reading of it (a ``memory_access`` at 0x0 is usually a
+``null_deref``; one at the stack guard page is a ``stack_overflow``). The
+exception code wins over the signal when both are present, since debuggers
+on Windows report every exception as some signal.
+
+`NormalizedException` carries the details either platform gives: the
+address, the access (a Windows access violation's parameters; the faulting
+instruction under gdb), and on Linux the ``si_code`` (``SEGV_MAPERR``: no page
+mapped there; ``SEGV_ACCERR``: mapped, but not with that permission).
+
+`ExceptionPolicy` says which chance a launch under a debugger captures on. A
+debugger sees a fault first (the first chance), before the program's own
+handler does; it is fatal only if no handler takes it (the second chance).
+Under `ExceptionPolicy.SECOND_CHANCE`, the default, each of
+`SECOND_CHANCE_SIGNALS` is passed to the program when it has a handler
+installed, the way ``AnalyzeRequest.ignore_signals`` are, so a fault the
+program handles lets the run go on and only a fatal one is analyzed.
+`ExceptionPolicy.FIRST_CHANCE` stops at the first fault, handled or not.
+"""
+from __future__ import annotations
+
+from dataclasses import dataclass
+from enum import Enum
+from typing import Optional
+
+from .fault import NULL_PAGE_LIMIT
+from .model import SI_KERNEL, AccessKind, StopInfo
+
+
+# Faults a program may catch and survive; the launch passes them to its handlers under `ExceptionPolicy.SECOND_CHANCE`.
+SECOND_CHANCE_SIGNALS = ("SIGSEGV", "SIGBUS", "SIGFPE", "SIGILL", "SIGABRT")
+
+
+class ExceptionPolicy(str, Enum):
+    # Capture every fault as the debugger sees it, before the program's handler runs.
+    FIRST_CHANCE = "first-chance"
+    # Capture only a fault the program does not handle: the one that would kill it.
+    SECOND_CHANCE = "second-chance"
+
+
+class ExceptionKind(str, Enum):
+    MEMORY_ACCESS = "memory_access"
+    ARITHMETIC = "arithmetic"
+    ILLEGAL_INSTRUCTION = "illegal_instruction"
+    ABORT = "abort"
+    BREAKPOINT = "breakpoint"
+    UNKNOWN = "unknown"
+
+    @property
+    def label(self) -> str:
+        return _LABELS[self]
+
+
+_LABELS = {
+    ExceptionKind.MEMORY_ACCESS: "memory access",
+    ExceptionKind.ARITHMETIC: "arithmetic exception",
+    ExceptionKind.ILLEGAL_INSTRUCTION: "illegal instruction",
+    ExceptionKind.ABORT: "abort",
+    ExceptionKind.BREAKPOINT: "breakpoint or trap",
+    ExceptionKind.UNKNOWN: "unknown exception",
+}
+
+_SIGNAL_KINDS = {
+    "SIGSEGV": ExceptionKind.MEMORY_ACCESS,
+    "SIGBUS": ExceptionKind.MEMORY_ACCESS,
+    "SIGFPE": ExceptionKind.ARITHMETIC,
+    "SIGILL": ExceptionKind.ILLEGAL_INSTRUCTION,
+    "SIGABRT": ExceptionKind.ABORT,
+    "SIGTRAP": ExceptionKind.BREAKPOINT,
+}
+
+_EXCEPTION_CODE_KINDS = {
+    0xC0000005: ExceptionKind.MEMORY_ACCESS,  # STATUS_ACCESS_VIOLATION
+    0xC0000006: ExceptionKind.MEMORY_ACCESS,  # STATUS_IN_PAGE_ERROR
+    0xC00000FD: ExceptionKind.MEMORY_ACCESS,  # STATUS_STACK_OVERFLOW: a guard-page hit, SIGSEGV on Linux
+    0x80000002: ExceptionKind.MEMORY_ACCESS,  # STATUS_DATATYPE_MISALIGNMENT: SIGBUS on Linux
+    0xC000008C: ExceptionKind.MEMORY_ACCESS,  # STATUS_ARRAY_BOUNDS_EXCEEDED
+    0xC0000094: ExceptionKind.ARITHMETIC,  # STATUS_INTEGER_DIVIDE_BY_ZERO
+    0xC0000095: ExceptionKind.ARITHMETIC,  # STATUS_INTEGER_OVERFLOW
+    0xC000008D: ExceptionKind.ARITHMETIC,  # STATUS_FLOAT_DENORMAL_OPERAND
+    0xC000008E: ExceptionKind.ARITHMETIC,  # STATUS_FLOAT_DIVIDE_BY_ZERO
+    0xC000008F: ExceptionKind.ARITHMETIC,  # STATUS_FLOAT_INEXACT_RESULT
+    0xC0000090: ExceptionKind.ARITHMETIC,  # STATUS_FLOAT_INVALID_OPERATION
+    0xC0000091: ExceptionKind.ARITHMETIC,  # STATUS_FLOAT_OVERFLOW
+    0xC0000092: ExceptionKind.ARITHMETIC,  # STATUS_FLOAT_STACK_CHECK
+    0xC0000093: ExceptionKind.ARITHMETIC,  # STATUS_FLOAT_UNDERFLOW
+    0xC000001D: ExceptionKind.ILLEGAL_INSTRUCTION,  # STATUS_ILLEGAL_INSTRUCTION
+    0xC0000096: ExceptionKind.ILLEGAL_INSTRUCTION,  # STATUS_PRIVILEGED_INSTRUCTION
+    0xC0000409: ExceptionKind.ABORT,  # STATUS_STACK_BUFFER_OVERRUN (__fastfail)
+    0x40000015: ExceptionKind.ABORT,  # STATUS_FATAL_APP_EXIT (abort())
+    0xC0000374: ExceptionKind.ABORT,  # STATUS_HEAP_CORRUPTION: glibc aborts on the same
+    0x80000003: ExceptionKind.BREAKPOINT,  # STATUS_BREAKPOINT
+    0x80000004: ExceptionKind.BREAKPOINT,  # STATUS_SINGLE_STEP
+}
+
+# si_code values common to every signal (<asm-generic/siginfo.h>).
+_GENERIC_SI_CODES = {0: "SI_USER", SI_KERNEL: "SI_KERNEL", -1: "SI_QUEUE", -2: "SI_TIMER", -6: "SI_TKILL"}
+# The per-signal ones, for the signals that report a fault.
+_SIGNAL_SI_CODES = {
+    "SIGSEGV": {1: "SEGV_MAPERR", 2: "SEGV_ACCERR", 3: "SEGV_BNDERR", 4: "SEGV_PKUERR"},
+    "SIGBUS": {1: "BUS_ADRALN", 2: "BUS_ADRERR", 3: "BUS_OBJERR", 4: "BUS_MCEERR_AR", 5: "BUS_MCEERR_AO"},
+    "SIGFPE": {
+        1: "FPE_INTDIV", 2: "FPE_INTOVF", 3: "FPE_FLTDIV", 4: "FPE_FLTOVF",
+        5: "FPE_FLTUND", 6: "FPE_FLTRES", 7: "FPE_FLTINV", 8: "FPE_FLTSUB",
+    },
+    "SIGILL": {
+        1: "ILL_ILLOPC", 2: "ILL_ILLOPN", 3: "ILL_ILLADR", 4: "ILL_ILLTRP",
+        5: "ILL_PRVOPC", 6: "ILL_PRVREG", 7: "ILL_COPROC", 8: "ILL_BADSTK",
+    },
+    "SIGTRAP": {1: "TRAP_BRKPT", 2: "TRAP_TRACE"},
+}
+
+
+def si_code_name(signal: Optional[str], si_code: Optional[int]) -> Optional[str]:
+    """The ``<signal.h>`` name of ``si_code`` for ``signal`` (``SEGV_MAPERR``), or None if unknown."""
+    if si_code is None:
+        return None
+    if si_code > 0 and si_code != SI_KERNEL:
+        return _SIGNAL_SI_CODES.get((signal or "").upper(), {}).get(si_code)
+    return _GENERIC_SI_CODES.get(si_code)
+
+
+@dataclass
+class NormalizedException:
+    kind: ExceptionKind
+    access: AccessKind = AccessKind.UNKNOWN
+    address: Optional[int] = None
+    # Linux only: how the kernel explains the signal; see `si_code_name`.
+    si_code: Optional[int] = None
+    signal: Optional[str] = None
+
+    @property
+    def known_address(self) -> Optional[int]:
+        """`address`, or None for a ``SI_KERNEL`` fault, whose 0 is no address at all."""
+        return self.address if self.si_code != SI_KERNEL else None
+
+    @property
+    def null(self) -> bool:
+        """A memory access in the null page: the classic null-pointer dereference."""
+        address = self.known_address
+        return self.kind is ExceptionKind.MEMORY_ACCESS and address is not None and address < NULL_PAGE_LIMIT
+
+    @property
+    def si_code_name(self) -> Optional[str]:
+        return si_code_name(self.signal, self.si_code)
+
+    def describe(self) -> str:
+        """One line, e.g. ``memory access: write at 0x0 (null page, SEGV_MAPERR)``."""
+        text = self.kind.label
+        if self.kind is ExceptionKind.MEMORY_ACCESS:
+            where = f"at 0x{self.known_address:x}" if self.known_address is not None else ""
+            what = self.access.value if self.access is not AccessKind.UNKNOWN else ""
+            detail = " ".join(p for p in (what, where) if p)
+            if detail:
+                text = f"{text}: {detail}"
+        extras = [p for p in ("null page" if self.null else None, self.si_code_name) if p]
+        if extras:
+            text = f"{text} ({', '.join(extras)})"
+        return text
+
+
+def exception_kind(signal: Optional[str], exception_code: Optional[int] = None) -> ExceptionKind:
+    """The `ExceptionKind` of a stop; the exception code, when there is one, decides."""
+    if exception_code in _EXCEPTION_CODE_KINDS:
+        return _EXCEPTION_CODE_KINDS[exception_code]
+    if exception_code is not None and exception_code & 0xC0000000 == 0xC0000000:
+        # Some other NTSTATUS error: whatever signal the debugger picked for it says nothing.
+        return ExceptionKind.UNKNOWN
+    return _SIGNAL_KINDS.get((signal or "").upper(), ExceptionKind.UNKNOWN)
+
+
+def normalize_stop(stop: StopInfo) -> NormalizedException:
+    """The platform-neutral view of ``stop``; UNKNOWN for exits, hangs, and other signals."""
+    return NormalizedException(
+        kind=exception_kind(stop.signal, stop.exception_code),
+        access=stop.access,
+        address=stop.fault_address,
+        si_code=stop.si_code,
+        signal=stop.signal,
+    )
+
+
+__all__ = [
+    "ExceptionKind",
+    "ExceptionPolicy",
+    "NormalizedException",
+    "SECOND_CHANCE_SIGNALS",
+    "exception_kind",
+    "normalize_stop",
+    "si_code_name",
+]
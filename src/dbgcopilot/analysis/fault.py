@@ -0,0 +1,321 @@
+"""Heuristic classification of why a process crashed.
+
+`classify_fault` looks at the stop signal, the faulting address, the stack
+pointer, and the top frames to label a crash. The label is a hint for the LLM,
+not a verdict: it turns "SIGSEGV, here are the registers" into "this looks like
+a null-pointer dereference", which the model can then confirm or refute.
+
+How much to trust it depends on the evidence: a fault at 0x0 is almost surely
+a null pointer, one at 0x4000 may be a wild one. So the `FaultClassification`
+carries a confidence and the evidence (``signals``) behind it. Each piece of
+evidence has a weight, and independent pieces combine (two at 0.8 give 0.96).
+A guess below `CONFIDENCE_THRESHOLD` becomes UNKNOWN instead of a label the
+model might take at its word; `fault_hint` puts both in the prompt.
+
+A SIGABRT with Rust's panic machinery on the stack is a `RUST_PANIC`, not an
+`ABORT`; its hint quotes the panic message (see `dbgcopilot.analysis.panic`).
+"""
+from __future__ import annotations
+
+import signal as signal_module
+from dataclasses import dataclass, field
+from enum import Enum
+from typing import Iterable, List, Optional, Tuple
+
+from .model import SI_KERNEL, AccessKind, CrashContext, Frame
+from .panic import PANIC_EXIT_STATUS, panic_frame
+
+# Addresses below this are treated as null-page accesses (Linux mmap_min_addr default).
+NULL_PAGE_LIMIT = 0x10000
+# A fault in the first page is a null struct pointer's field; past it, a wild pointer is as likely.
+NULL_PAGE_SIZE = 0x1000
+# A guess less sure than this is reported as UNKNOWN.
+CONFIDENCE_THRESHOLD = 0.5
+# No heuristic is certain, however much evidence agrees.
+MAX_CONFIDENCE = 0.99
+# A fault this close to the stack pointer is a guard-page hit rather than a wild write.
+STACK_GUARD_WINDOW = 0x10000
+# The same function repeated this many times on the stack means runaway recursion.
+RECURSION_THRESHOLD = 64
+# How many frames from the top are searched for allocator/runtime markers. Rust's
+# panic machinery alone can be a dozen frames deep, so this is fairly generous.
+TOP_FRAMES = 24
+
+
+class FaultKind(str, Enum):
+    NULL_DEREF = "null_deref"
+    STACK_OVERFLOW = "stack_overflow"
+    HEAP_CORRUPTION = "heap_corruption"
+    ABORT = "abort"
+    # A Rust panic (``panic!``, a failed ``unwrap()`` or ``expect()``) that aborted, or that unwound and exited 101.
+    RUST_PANIC = "rust_panic"
+    DIVIDE_BY_ZERO = "divide_by_zero"
+    ILLEGAL_INSTRUCTION = "illegal_instruction"
+    INVALID_ACCESS = "invalid_access"
+    # Set by callers that observed no progress; never returned by classify_fault.
+    HANG = "hang"
+    UNKNOWN = "unknown"
+
+    @property
+    def label(self) -> str:
+        return _LABELS[self]
+
+
+_LABELS = {
+    FaultKind.NULL_DEREF: "null-pointer dereference",
+    FaultKind.STACK_OVERFLOW: "stack overflow",
+    FaultKind.HEAP_CORRUPTION: "heap corruption detected by the allocator",
+    FaultKind.ABORT: "explicit abort (panic, assertion, or abort())",
+    FaultKind.RUST_PANIC: "Rust panic",
+    FaultKind.DIVIDE_BY_ZERO: "integer divide-by-zero",
+    FaultKind.ILLEGAL_INSTRUCTION: "illegal instruction",
+    FaultKind.INVALID_ACCESS: "invalid memory access",
+    FaultKind.HANG: "hang (no exit before the timeout)",
+    FaultKind.UNKNOWN: "unclassified stop",
+}
+
+_ACCESS_SIGNALS = {"SIGSEGV", "SIGBUS"}
+
+# Substrings matched against frame function names (case-sensitive, mangled or not).
+# Rust reports guard-page hits from std::sys::..::stack_overflow::imp::signal_handler.
+_STACK_OVERFLOW_MARKERS = ("stack_overflow::imp", "stack_overflow3imp", "__chkstk", "__rust_probestack", "_alloca_probe")
+_NULL_DEREF_MARKERS = ("panic_null_pointer_dereference",)
+_DIVIDE_MARKERS = ("panic_const_div_by_zero", "panic_const_rem_by_zero")
+_HEAP_MARKERS = (
+    "malloc_printerr",
+    "_int_free",
+    "_int_malloc",
+    "malloc_consolidate",
+    "__libc_free",
+    "__libc_malloc",
+    "__GI___libc_free",
+    "RtlReportCriticalFailure",
+    "RtlpHeapHandleError",
+    "__asan_report",
+    "__asan::ReportGenericError",
+)
+_HEAP_DESCRIPTION_MARKERS = ("double free", "free(): invalid", "corrupted", "heap corruption", "STATUS_HEAP_CORRUPTION")
+_STACK_OVERFLOW_DESCRIPTION_MARKERS = ("stack overflow", "STATUS_STACK_OVERFLOW", "0xc00000fd")
+
+# Windows NTSTATUS codes that name the fault outright. Access violations are left
+# to the address checks below, exactly like SIGSEGV.
+_EXCEPTION_CODE_KINDS = {
+    0xC00000FD: FaultKind.STACK_OVERFLOW,
+    0xC0000374: FaultKind.HEAP_CORRUPTION,
+    0xC0000094: FaultKind.DIVIDE_BY_ZERO,
+    0xC000008E: FaultKind.DIVIDE_BY_ZERO,
+    0xC000001D: FaultKind.ILLEGAL_INSTRUCTION,
+    0xC0000096: FaultKind.ILLEGAL_INSTRUCTION,
+    0xC0000409: FaultKind.ABORT,
+    0x40000015: FaultKind.ABORT,
+}
+
+
+def _functions(frames: Iterable[Frame]) -> list[str]:
+    return [f.function for f in frames if f.function]
+
+
+def _marked(names: Iterable[str], markers: Iterable[str]) -> Optional[str]:
+    """The first of ``names`` that contains one of ``markers``."""
+    markers = tuple(markers)
+    return next((name for name in names if any(m in name for m in markers)), None)
+
+
+def _recursing_function(frames: list[Frame]) -> Optional[Tuple[str, int]]:
+    """A function on the stack at least `RECURSION_THRESHOLD` times, and how many times it is there."""
+    counts: dict[str, int] = {}
+    for name in _functions(frames):
+        counts[name] = counts.get(name, 0) + 1
+    name = next((n for n, count in counts.items() if count >= RECURSION_THRESHOLD), None)
+    return (name, counts[name]) if name is not None else None
+
+
+def _stack_distance(ctx: CrashContext) -> Optional[int]:
+    """How far the fault address is from the stack pointer, if it is within `STACK_GUARD_WINDOW`."""
+    sp = ctx.stack_pointer
+    addr = ctx.stop.known_fault_address
+    if sp is None or addr is None or abs(addr - sp) > STACK_GUARD_WINDOW:
+        return None
+    return abs(addr - sp)
+
+
+def _signal_evidence(name: str) -> str:
+    try:
+        return f"{name} (signal {signal_module.Signals[name].value})"
+    except KeyError:
+        return name
+
+
+@dataclass
+class FaultClassification:
+    """What `classify_fault` concluded, how sure it is, and on what evidence."""
+
+    kind: FaultKind
+    # 0 to 1: how strongly the evidence points at ``kind`` (for UNKNOWN, at the guess that was too weak).
+    confidence: float
+    # The evidence, one short phrase each: the signal or exception code, the fault address, telling frames.
+    signals: List[str] = field(default_factory=list)
+
+    def describe(self) -> str:
+        """``confidence 0.90: SIGSEGV (signal 11); fault address 0x0 is the null pointer``."""
+        return f"confidence {self.confidence:.2f}: " + "; ".join(self.signals)
+
+
+def _classified(kind: FaultKind, signals: List[str], evidence: List[Tuple[float, str]]) -> FaultClassification:
+    # Independent pieces of evidence: each one removes part of the remaining doubt.
+    doubt = 1.0
+    for weight, _ in evidence:
+        doubt *= 1.0 - weight
+    confidence = round(min(1.0 - doubt, MAX_CONFIDENCE), 2)
+    return FaultClassification(kind, confidence, signals + [text for _, text in evidence])
+
+
+def _null_page_evidence(addr: int) -> Tuple[float, str]:
+    if addr == 0:
+        return 0.9, "fault address 0x0 is the null pointer"
+    if addr < NULL_PAGE_SIZE:
+        return 0.8, f"fault address 0x{addr:x} is in the first page: a field of a null pointer"
+    # Past the first page a large struct offset is possible, but so is a small wild pointer.
+    return 0.55, f"fault address 0x{addr:x} is below 0x{NULL_PAGE_LIMIT:x} but past the first page"
+
+
+def _panic_evidence(ctx: CrashContext) -> List[Tuple[float, str]]:
+    machinery = panic_frame(ctx.frames[:TOP_FRAMES])
+    return [(0.9, f"frame {machinery.function} is Rust's panic machinery")] if machinery is not None else []
+
+
+def _guess(ctx: CrashContext) -> FaultClassification:
+    stop = ctx.stop
+    signal = (stop.signal or "").upper()
+    description = (stop.description or "").lower()
+    top = _functions(ctx.frames[:TOP_FRAMES])
+    # A SI_KERNEL fault (a general protection fault) reports 0, which is not the null pointer.
+    addr = stop.known_fault_address
+
+    if stop.exception_code in _EXCEPTION_CODE_KINDS:
+        code = stop.exception_code
+        kind, evidence = _EXCEPTION_CODE_KINDS[code], [f"exception code 0x{code:08x}"]
+        if kind is FaultKind.ABORT and _panic_evidence(ctx):
+            # Rust on Windows aborts a panic with a fail-fast.
+            return _classified(FaultKind.RUST_PANIC, evidence, [(0.5, "a fail-fast abort")] + _panic_evidence(ctx))
+        return FaultClassification(kind, 0.95, evidence)
+    if stop.exception_code is not None and signal:
+        from .exception import ExceptionKind, exception_kind
+
+        if exception_kind(signal, stop.exception_code) is ExceptionKind.UNKNOWN:
+            # An NTSTATUS error with no known kind: the signal a reader or cdb gave it is a placeholder.
+            unknown = f"exception code 0x{stop.exception_code:08x} is no known fault; the {signal} stands in for it"
+            return FaultClassification(FaultKind.UNKNOWN, 0.0, [unknown])
+    if not signal:
+        if ctx.panic is not None and stop.exit_code == PANIC_EXIT_STATUS:
+            return FaultClassification(FaultKind.RUST_PANIC, 0.9, [f"exit status {PANIC_EXIT_STATUS} after a panic"])
+        return FaultClassification(FaultKind.UNKNOWN, 0.0, ["no signal or exception code"])
+    signals = [_signal_evidence(signal)]
+
+    overflow: List[Tuple[float, str]] = []
+    if _marked([description], (m.lower() for m in _STACK_OVERFLOW_DESCRIPTION_MARKERS)):
+        overflow.append((0.9, "the stop description reports a stack overflow"))
+    handler = _marked(top, _STACK_OVERFLOW_MARKERS)
+    if handler:
+        overflow.append((0.9, f"frame {handler} is a stack probe or overflow handler"))
+    distance = _stack_distance(ctx) if signal in _ACCESS_SIGNALS else None
+    if distance is not None:
+        overflow.append((0.7, f"fault address is 0x{distance:x} bytes from the stack pointer"))
+    recursion = _recursing_function(ctx.frames)
+    if recursion:
+        overflow.append((0.75, f"{recursion[0]} is on the stack {recursion[1]} times"))
+    if overflow:
+        return _classified(FaultKind.STACK_OVERFLOW, signals, overflow)
+
+    divide: List[Tuple[float, str]] = []
+    if signal == "SIGFPE":
+        # Also raised for INT_MIN / -1, hence not certain.
+        divide.append((0.85, "SIGFPE is an arithmetic trap"))
+    panic = _marked(top, _DIVIDE_MARKERS)
+    if panic:
+        divide.append((0.95, f"frame {panic} is the divide-by-zero panic"))
+    if divide:
+        return _classified(FaultKind.DIVIDE_BY_ZERO, signals, divide)
+
+    if signal == "SIGILL":
+        return _classified(FaultKind.ILLEGAL_INSTRUCTION, signals, [(0.8, "the CPU rejected the instruction")])
+
+    null: List[Tuple[float, str]] = []
+    panic = _marked(top, _NULL_DEREF_MARKERS)
+    if panic:
+        null.append((0.95, f"frame {panic} is the null-pointer panic"))
+    if signal in _ACCESS_SIGNALS and addr is not None and addr < NULL_PAGE_LIMIT:
+        null.append(_null_page_evidence(addr))
+    if null:
+        return _classified(FaultKind.NULL_DEREF, signals, null)
+
+    heap: List[Tuple[float, str]] = []
+    allocator = _marked(top, _HEAP_MARKERS)
+    if allocator:
+        heap.append((0.85, f"frame {allocator} is in the allocator"))
+    if _marked([description], (m.lower() for m in _HEAP_DESCRIPTION_MARKERS)):
+        heap.append((0.9, "the stop description reports heap corruption"))
+    if heap:
+        return _classified(FaultKind.HEAP_CORRUPTION, signals, heap)
+
+    if signal == "SIGABRT":
+        aborted = [(0.8, "the program aborted itself")]
+        if _panic_evidence(ctx):
+            return _classified(FaultKind.RUST_PANIC, signals, aborted + _panic_evidence(ctx))
+        return _classified(FaultKind.ABORT, signals, aborted)
+    if signal in _ACCESS_SIGNALS:
+        if addr is None and stop.si_code == SI_KERNEL:
+            # On x86-64 usually a non-canonical pointer: a wild or corrupted one, never near 0.
+            general = (0.6, "SI_KERNEL: a general protection fault, which reports no fault address")
+            return _classified(FaultKind.INVALID_ACCESS, signals, [general])
+        if addr is None:
+            return _classified(FaultKind.INVALID_ACCESS, signals, [(0.4, "the fault address is not known")])
+        outside = (0.7, f"fault address 0x{addr:x} is outside the null page")
+        return _classified(FaultKind.INVALID_ACCESS, signals, [outside])
+    return FaultClassification(FaultKind.UNKNOWN, 0.0, signals)
+
+
+def classify_fault(ctx: CrashContext) -> FaultClassification:
+    """Best-effort fault label for a crash context, with its confidence and evidence.
+
+    A guess below `CONFIDENCE_THRESHOLD` is reported as UNKNOWN, keeping its
+    confidence and evidence, rather than committed to.
+    """
+    guess = _guess(ctx)
+    if guess.kind is FaultKind.UNKNOWN or guess.confidence >= CONFIDENCE_THRESHOLD:
+        return guess
+    weak = f"too weak to call it {guess.kind.label} (below {CONFIDENCE_THRESHOLD:.2f})"
+    return FaultClassification(FaultKind.UNKNOWN, guess.confidence, guess.signals + [weak])
+
+
+def fault_hint(ctx: CrashContext) -> Optional[str]:
+    """One-line classification suitable for inclusion in an LLM prompt, with its confidence and evidence."""
+    found = classify_fault(ctx)
+    if found.kind is FaultKind.UNKNOWN and not found.confidence:
+        return None
+    panic = ctx.panic if found.kind is FaultKind.RUST_PANIC else None
+    if panic is not None and (panic.message or panic.location):
+        # What the panic said is the diagnosis; the signal is only how the runtime stopped.
+        said = [" ".join((panic.message or "").split())]
+        if panic.location:
+            said.append(f"at {panic.location}")
+        return f"{found.kind.label} ({' '.join(p for p in said if p)}); {found.describe()}"
+    details = [ctx.stop.signal or "stop"]
+    addr = ctx.stop.known_fault_address
+    if addr is not None and ctx.stop.access is not AccessKind.UNKNOWN:
+        details.append(f"{ctx.stop.access.value} of 0x{addr:x}")
+    elif addr is not None:
+        details.append(f"fault address 0x{addr:x}")
+    if ctx.frames and ctx.frames[0].function:
+        details.append(f"top frame {ctx.frames[0].function}")
+    return f"{found.kind.label} ({', '.join(details)}); {found.describe()}"
+
+
+__all__ = [
+    "CONFIDENCE_THRESHOLD",
+    "FaultClassification",
+    "FaultKind",
+    "NULL_PAGE_LIMIT",
+    "classify_fault",
+    "fault_hint",
+]
@@ -0,0 +1,145 @@
+"""A suggested code change, asked for after the explanation.
+
+The explanation says what went wrong; `suggest_fix` is a second LLM call
+that asks what to change. It sends the crash prompt and the first
+explanation again, and asks for a JSON object with three keys:
+
+- ``summary``: the change in one sentence ("check ``ptr`` for null before
+  writing through it");
+- ``rationale``: why that change removes the fault;
+- ``patch_hint``: the code-level edit, as a short snippet or diff.
+
+An answer wrapped in a Markdown code fence, or with prose around the object,
+is accepted. Anything without a ``summary`` raises `LlmResponseError`.
+
+The call costs a second request per crash, so `analyze` only makes it when
+`AnalyzeRequest.suggest_fix` is set (``--suggest-fix`` on the command line).
+"""
+from __future__ import annotations
+
+import json
+import re
+from dataclasses import dataclass
+from typing import TYPE_CHECKING, Any, Dict, List, Mapping, Optional, Union
+
+if TYPE_CHECKING:
+    from dbgcopilot.llm.base import LlmProvider, Prompt
+    from dbgcopilot.llm.usage import UsageMeter
+
+    from .api import AnalysisReport
+    from .crash import CrashReport
+    from .model import CrashContext
+
+FIX_INSTRUCTIONS = (
+    "Propose the smallest code change that fixes this crash, based on the crash data and your analysis. "
+    'Answer with only a JSON object: {"summary": "<the change, in one sentence>", '
+    '"rationale": "<why it removes the fault>", "patch_hint": "<the edit, as a short code snippet or diff>"}.'
+)
+# Room for a short patch on top of the two sentences.
+FIX_MAX_TOKENS = 1024
+
+_FENCE_RE = re.compile(r"```(?:json)?\s*(?P<body>.*?)```", re.DOTALL)
+
+
+@dataclass
+class FixSuggestion:
+    summary: str
+    rationale: str = ""
+    patch_hint: str = ""
+
+    @classmethod
+    def from_dict(cls, data: Mapping[str, Any]) -> "FixSuggestion":
+        return cls(
+            summary=str(data.get("summary") or "").strip(),
+            rationale=str(data.get("rationale") or "").strip(),
+            patch_hint=str(data.get("patch_hint") or "").strip("\n"),
+        )
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {"summary": self.summary, "rationale": self.rationale, "patch_hint": self.patch_hint}
+
+    def render(self) -> List[str]:
+        """Text lines: the summary, the rationale, then the patch hint."""
+        lines = [self.summary]
+        if self.rationale:
+            lines.append(f"Why: {self.rationale}")
+        if self.patch_hint:
+            lines += ["Patch hint:", self.patch_hint]
+        return lines
+
+
+def parse_fix_suggestion(text: str) -> FixSuggestion:
+    """The `FixSuggestion` in a model's answer; raises `LlmResponseError` without a JSON ``summary``."""
+    from dbgcopilot.llm.errors import LlmResponseError
+
+    fence = _FENCE_RE.search(text)
+    body = fence.group("body") if fence else text
+    start, end = body.find("{"), body.rfind("}")
+    data: Any = None
+    if start != -1 and end > start:
+        try:
+            data = json.loads(body[start : end + 1])
+        except ValueError:
+            data = None
+    suggestion = FixSuggestion.from_dict(data) if isinstance(data, dict) else None
+    if suggestion is None or not suggestion.summary:
+        raise LlmResponseError(f"the fix suggestion is not a JSON object with a summary: {text.strip()[:200]!r}")
+    return suggestion
+
+
+def build_fix_prompt(
+    report: Union["AnalysisReport", "CrashReport"], explanation: str, llm_config: Optional[Dict[str, Any]] = None
+) -> "Prompt":
+    """The crash prompt, then the explanation, with `FIX_INSTRUCTIONS` as the system prompt."""
+    from dbgcopilot.llm.base import Prompt
+
+    from .api import AnalysisReport, _build_prompt, prepare_prompt
+    from .prompt import PromptBuilder
+
+    if isinstance(report, AnalysisReport):
+        crash = report.prompt or prepare_prompt(report, llm_config).text
+    else:
+        crash = _build_prompt(report, PromptBuilder.from_config(llm_config)).text
+    text = f"{crash}\n\nYour analysis:\n{explanation.strip()}"
+    return Prompt(text, system=FIX_INSTRUCTIONS, max_tokens=FIX_MAX_TOKENS)
+
+
+def suggest_fix(
+    ctx: Union["AnalysisReport", "CrashReport", "CrashContext"],
+    explanation: str,
+    provider: Union[str, "LlmProvider", None] = None,
+    llm_config: Optional[Dict[str, Any]] = None,
+    meter: Optional["UsageMeter"] = None,
+) -> FixSuggestion:
+    """Ask ``provider`` for a fix of the crash ``explanation`` describes.
+
+    ``provider`` is an `LlmProvider` or a name for `resolve_provider`.
+    Transient failures are retried as for the explanation (see `RetryPolicy`);
+    the last one raises `LlmError`, as does an answer `parse_fix_suggestion`
+    cannot read. The call is counted into ``meter`` (by default one from
+    ``llm_config``), which raises `LlmBudgetError` before a prompt over its
+    ``max_cost`` is sent.
+    """
+    from dbgcopilot.llm.base import resolve_provider
+    from dbgcopilot.llm.retry import RetryPolicy
+    from dbgcopilot.llm.usage import UsageMeter
+
+    from .api import _report_from_context
+    from .model import CrashContext
+
+    report = _report_from_context(ctx, "") if isinstance(ctx, CrashContext) else ctx
+    llm = resolve_provider(provider, llm_config)
+    meter = meter if meter is not None else UsageMeter.from_config(llm_config)
+    prompt = build_fix_prompt(report, explanation, llm_config)
+    completion, _ = RetryPolicy.from_config(llm_config).complete(llm, prompt, meter)
+    return parse_fix_suggestion(completion.text)
+
+
+__all__ = [
+    "FIX_INSTRUCTIONS",
+    "FIX_MAX_TOKENS",
+    "FixSuggestion",
+    "build_fix_prompt",
+    "parse_fix_suggestion",
+    "suggest_fix",
+]
@@ -0,0 +1,137 @@
+"""Which frames are the program's own, and which belong to its runtime.
+
+A Rust panic stops dozens of frames below the code that caused it: the
+signal handler, ``std::panicking``, ``core::panicking``, ``abort`` and
+``raise`` in libc. `FrameRules` marks each frame as *user* or *runtime* so
+reports can lead with the first frame of the program itself, the "frame of
+interest", while still listing the rest.
+
+A frame is runtime when its module is a system library or its source file
+is in libc or the Rust standard library (`is_system_frame`), or when its
+function or module matches one of the ``runtime`` patterns: ``std::*``,
+``core::*``, ``__libc_*``, ``runtime.*``, and so on. Patterns are shell
+globs (`fnmatch`), matched against the symbol and, ignoring case, against
+the module name.
+
+``user`` patterns are checked first and win, for code the defaults would
+misjudge (a crate vendored under a ``std``-like path, or your own
+``libfoo.so``). Both lists come from the config (``[frames] user`` and
+``runtime``, see `FrameRules.from_config`); ``runtime`` adds to the defaults.
+A frame with neither a function nor a module cannot be told apart and is
+never the first user frame, except on a V8 stack, where it is JIT-compiled
+JavaScript: frames labelled by `label_engine_frames` are user code when they
+are the script or a native addon, and runtime otherwise.
+"""
+from __future__ import annotations
+
+import json
+from dataclasses import dataclass, field
+from fnmatch import fnmatch, fnmatchcase
+from typing import Any, Iterable, List, Mapping, Optional
+
+from .model import Frame
+from .v8 import USER_ENGINE_LABELS
+
+# Module basenames (lowercased prefixes) of system libraries.
+_SYSTEM_MODULES = (
+    "libc.so", "libc-", "libm.so", "libpthread", "ld-linux", "ld64.so", "libdl", "librt", "libgcc_s", "libstdc++",
+    "libc++", "libunwind", "linux-vdso", "ntdll", "kernel32", "kernelbase", "ucrtbase", "msvcrt", "vcruntime",
+    "libsystem_", "libdyld", "dyld",
+)
+# Source paths of libc and the Rust standard library, for frames without a module.
+_SYSTEM_FILES = ("../sysdeps/", "./nptl/", "./stdlib/", "/rustc/", "library/std/src/", "library/core/src/",
+                 "library/alloc/src/")
+
+# Symbols of language runtimes, linked into the program itself.
+DEFAULT_RUNTIME_PATTERNS = (
+    # Rust: the standard library, the panic runtime, and their mangled names when not demangled.
+    "std::*", "core::*", "alloc::*", "<std::*", "<core::*", "<alloc::*", "panic_abort::*", "panic_unwind::*",
+    "__rust_*", "rust_begin_unwind", "rust_panic", "_ZN3std*", "_ZN4core*", "_ZN5alloc*",
+    # C and C++: libc entry points and failure paths, the C++ ABI, the unwinder, sanitizers.
+    "_start", "__libc_*", "__GI_*", "__pthread_*", "pthread_kill*", "start_thread", "clone", "clone3", "__clone*",
+    "raise", "gsignal", "abort", "__restore_rt", "__assert_fail*", "__stack_chk_fail", "__fortify_fail", "__chk_fail",
+    "malloc_printerr", "_int_free", "_int_malloc", "__cxa_*", "__gxx_personality*", "_Unwind_*",
+    "__asan*", "__sanitizer*", "__interceptor_*",
+    # The MSVC startup code compiled into every executable.
+    "mainCRTStartup", "wmainCRTStartup", "invoke_main", "__scrt_*",
+    # Go.
+    "runtime.*",
+    # Node.js: V8, its builtins, Node's C++, and libuv, all linked into the node binary.
+    "v8::*", "Builtins_*", "node::*", "uv_*", "uv__*", "_ZN2v8*", "_ZN4node*",
+)
+
+
+def is_system_module(name: str) -> bool:
+    """A system library by its basename: libc, the loader, ntdll, ..."""
+    return name.lower().startswith(_SYSTEM_MODULES)
+
+
+def is_system_frame(frame: Frame) -> bool:
+    """In a system library (libc, the loader, ntdll, ...) or in libc / Rust std sources."""
+    if frame.module and is_system_module(frame.module):
+        return True
+    return bool(frame.file) and any(marker in frame.file for marker in _SYSTEM_FILES)
+
+
+def _matches(frame: Frame, patterns: Iterable[str]) -> bool:
+    module = (frame.module or "").lower()
+    for pattern in patterns:
+        if frame.function and fnmatchcase(frame.function, pattern):
+            return True
+        if module and fnmatch(module, pattern.lower()):
+            return True
+    return False
+
+
+@dataclass
+class FrameRules:
+    """User-vs-runtime classification of frames; see the module docstring."""
+
+    # Globs that always mark a frame as the program's own; checked before anything else.
+    user: List[str] = field(default_factory=list)
+    runtime: List[str] = field(default_factory=lambda: list(DEFAULT_RUNTIME_PATTERNS))
+
+    @classmethod
+    def from_config(cls, config: Optional[Mapping[str, Any]]) -> "FrameRules":
+        """Rules from the ``user_frames`` and ``runtime_frames`` keys (lists, JSON lists, or comma-separated)."""
+        config = config or {}
+        extra = _config_patterns(config.get("runtime_frames"), "runtime_frames")
+        return cls(user=_config_patterns(config.get("user_frames"), "user_frames"),
+                   runtime=list(DEFAULT_RUNTIME_PATTERNS) + extra)
+
+    def is_user(self, frame: Frame) -> bool:
+        if self.user and _matches(frame, self.user):
+            return True
+        if frame.engine is not None:
+            return frame.engine in USER_ENGINE_LABELS
+        if not (frame.function or frame.module) or is_system_frame(frame):
+            return False
+        return not _matches(frame, self.runtime)
+
+    def first_user_frame(self, frames: Iterable[Frame]) -> Optional[Frame]:
+        """The innermost frame that `is_user`, or None when the whole stack is runtime."""
+        return next((f for f in frames if self.is_user(f)), None)
+
+
+def _config_patterns(raw: Any, key: str) -> List[str]:
+    if raw is None or raw == "":
+        return []
+    if isinstance(raw, str):
+        text = raw.strip()
+        if not text.startswith("["):
+            return [part.strip() for part in text.split(",") if part.strip()]
+        try:
+            raw = json.loads(text)
+        except ValueError as exc:
+            raise ValueError(f"malformed {key} {raw!r}: {exc}") from exc
+    if not isinstance(raw, (list, tuple)) or not all(isinstance(p, str) for p in raw):
+        raise ValueError(f"{key} must be a list of patterns, got {raw!r}")
+    return [p.strip() for p in raw if p.strip()]
+
+
+__all__ = [
+    "DEFAULT_RUNTIME_PATTERNS",
+    "FrameRules",
+    "is_system_frame",
+    "is_system_module",
+]
@@ -0,0 +1,181 @@
+"""Goroutines from Go's own stack dump, for hangs of Go programs.
+
+A debugger sees a Go program's OS threads, and those mostly sit in the
+scheduler (``runtime.futex``, ``runtime.findRunnable``): the goroutines that
+are actually stuck are parked, off every thread, in ``runtime.gopark``. Go
+prints all of them when it receives ``SIGQUIT``:
+
+```
+goroutine 1 [chan send]:
+main.main()
+	/src/hang.go:31 +0x6a
+```
+
+`parse_goroutine_dump` reads that text (with or without
+``GOTRACEBACK=system``'s runtime frames and ``fp=``/``pc=`` columns), and
+`classify_goroutines` labels the hang from the goroutines instead of the
+threads. The wait reason in the header decides each goroutine's state, since
+the default traceback hides ``runtime.gopark`` itself. Goroutines the runtime
+owns (GC workers, the finalizer, ``goroutine 0``) are left out.
+
+Every goroutine that could make progress being parked, with none running or
+in I/O, is a deadlock: the runtime's own "all goroutines are asleep" rule,
+which a pending timer (a sleeping heartbeat goroutine) is enough to defeat.
+"""
+from __future__ import annotations
+
+import re
+from dataclasses import dataclass, field
+from typing import Iterable, List, Optional
+
+from .hang_kind import HangCategory, HangKind, ThreadState, ThreadSummary, classify_summaries, summarize_thread
+from .model import Frame, ThreadBacktrace
+
+# goroutine 1 [chan send, 2 minutes]:   (Go 1.23 adds gp=0x... m=nil before the bracket)
+_HEADER_RE = re.compile(r"^goroutine (?P<id>\d+)(?: \w+=\S+)* \[(?P<state>[^\]]*)\]:$")
+# 	/src/hang.go:31 +0x6a fp=0xc000070f50 sp=0xc000070ef0 pc=0x48f9ca
+_LOCATION_RE = re.compile(
+    r"^\t(?P<file>.+?):(?P<line>\d+)(?: \+0x(?P<offset>[0-9a-f]+))?(?:.*\bpc=0x(?P<pc>[0-9a-f]+))?"
+)
+_CREATED_BY_RE = re.compile(r"^created by (?P<function>\S+)(?: in goroutine (?P<parent>\d+))?$")
+_MINUTES_RE = re.compile(r"^(?P<minutes>\d+) minutes$")
+
+# Wait reasons that are not a park; the rest ("chan send", "select", "sync.WaitGroup.Wait", ...) are.
+_REASON_STATES = {
+    "running": ThreadState.RUNNING,
+    "runnable": ThreadState.RUNNING,
+    "syscall": ThreadState.IO_WAIT,
+    "IO wait": ThreadState.IO_WAIT,
+    "sleep": ThreadState.SLEEPING,
+    "sync.Mutex.Lock": ThreadState.LOCK_WAIT,
+    "sync.RWMutex.Lock": ThreadState.LOCK_WAIT,
+    "sync.RWMutex.RLock": ThreadState.LOCK_WAIT,
+}
+
+
+@dataclass
+class Goroutine:
+    goroutine_id: int
+    # Go's wait reason: "chan send", "select", "running", ...
+    wait_reason: str
+    # How long it has been waiting, when Go says (it rounds down to whole minutes).
+    wait_minutes: Optional[int] = None
+    locked_to_thread: bool = False
+    frames: List[Frame] = field(default_factory=list)
+    # The function whose ``go`` statement started it; None for the main goroutine.
+    created_by: Optional[str] = None
+
+    @property
+    def state(self) -> str:
+        """The bracketed header text, e.g. ``chan send, 2 minutes``."""
+        parts = [self.wait_reason]
+        if self.wait_minutes is not None:
+            parts.append(f"{self.wait_minutes} minutes")
+        if self.locked_to_thread:
+            parts.append("locked to thread")
+        return ", ".join(parts)
+
+    @property
+    def parked(self) -> bool:
+        """Descheduled in ``runtime.gopark``: anything but running, runnable, or in a system call."""
+        return self.wait_reason not in ("running", "runnable", "syscall")
+
+    @property
+    def runtime_owned(self) -> bool:
+        """A goroutine of Go's runtime rather than of the program (GC workers, the finalizer, g0)."""
+        if self.goroutine_id == 0 or (self.created_by or "").startswith("runtime."):
+            return True
+        return bool(self.frames) and all((f.function or "").startswith("runtime.") for f in self.frames)
+
+    @property
+    def user_frame(self) -> Optional[Frame]:
+        """The innermost frame outside the runtime: where the program itself is waiting."""
+        return next((f for f in self.frames if not (f.function or "").startswith("runtime.")), None)
+
+    def to_thread(self) -> ThreadBacktrace:
+        name = f"goroutine {self.goroutine_id} [{self.state}]"
+        return ThreadBacktrace(self.goroutine_id, name=name, frames=list(self.frames))
+
+
+def parse_goroutine_dump(text: str) -> List[Goroutine]:
+    """Every goroutine in a ``SIGQUIT`` / ``panic`` dump, in the order Go printed them."""
+    goroutines: List[Goroutine] = []
+    current: Optional[Goroutine] = None
+    function: Optional[str] = None
+    for line in (text or "").splitlines():
+        header = _HEADER_RE.match(line)
+        if header:
+            reason, *rest = header.group("state").split(", ")
+            current = Goroutine(int(header.group("id")), reason)
+            for part in rest:
+                minutes = _MINUTES_RE.match(part)
+                if minutes:
+                    current.wait_minutes = int(minutes.group("minutes"))
+                elif part == "locked to thread":
+                    current.locked_to_thread = True
+            goroutines.append(current)
+            function = None
+            continue
+        if current is None:
+            continue
+        if not line.strip():
+            # A blank line ends the goroutine; register dumps and other output may follow.
+            current = None
+            continue
+        location = _LOCATION_RE.match(line)
+        if location:
+            if function is not None:
+                current.frames.append(
+                    Frame(
+                        index=len(current.frames),
+                        pc=int(location.group("pc"), 16) if location.group("pc") else None,
+                        function=function,
+                        file=location.group("file"),
+                        line=int(location.group("line")),
+                        offset=int(location.group("offset"), 16) if location.group("offset") else None,
+                    )
+                )
+            function = None
+            continue
+        spawn = _CREATED_BY_RE.match(line)
+        if spawn:
+            # Its location line follows; it is not a frame of this goroutine.
+            current.created_by, function = spawn.group("function"), None
+        elif line.endswith(")") and "(" in line:
+            # main.(*Pool).get(0xc000010000, ...): the arguments start at the last "(".
+            function = line[: line.rindex("(")]
+    return goroutines
+
+
+def summarize_goroutine(goroutine: Goroutine) -> ThreadSummary:
+    """A `ThreadSummary` whose state comes from the wait reason, pointed at the program's own frame."""
+    by_frames = summarize_thread(goroutine.to_thread())
+    state = _REASON_STATES.get(goroutine.wait_reason)
+    if state is None:
+        # Before Go 1.22 a mutex wait is just "semacquire"; sync.(*Mutex).lockSlow gives it away.
+        state = ThreadState.LOCK_WAIT if by_frames.state is ThreadState.LOCK_WAIT else ThreadState.PARKED
+    return ThreadSummary(
+        thread_id=goroutine.goroutine_id,
+        state=state,
+        frame=goroutine.user_frame or by_frames.frame,
+        goroutine=True,
+        wait_reason=goroutine.wait_reason,
+    )
+
+
+def classify_goroutines(goroutines: Iterable[Goroutine]) -> HangKind:
+    """Label a Go hang from its goroutines; see the module docstring for the deadlock rule."""
+    summaries = [summarize_goroutine(g) for g in goroutines if not g.runtime_owned]
+    stuck = [s.thread_id for s in summaries if s.state in (ThreadState.PARKED, ThreadState.LOCK_WAIT)]
+    active = {ThreadState.RUNNING, ThreadState.IO_WAIT, ThreadState.UNKNOWN}
+    if stuck and not any(s.state in active for s in summaries):
+        return HangKind(HangCategory.DEADLOCK, waiters=sorted(stuck), threads=summaries)
+    return classify_summaries(summaries)
+
+
+__all__ = [
+    "Goroutine",
+    "classify_goroutines",
+    "parse_goroutine_dump",
+    "summarize_goroutine",
+]
@@ -0,0 +1,205 @@
+"""Sampling hang analyzer.
+
+`Watchdog` attaches to a running process every ``interval_ms``, grabs all
+thread backtraces, detaches again, and after ``duration_ms`` reports which top
+frames each thread sat in across the samples. A thread that shows the same top
+frame in nearly every sample is where the process is stuck. `HangConfig` drops
+threads parked in known idle frames (event loops, worker pools) first.
+"""
+from __future__ import annotations
+
+import time
+from collections import Counter
+from dataclasses import dataclass, field
+from typing import Any, Callable, Dict, List, Optional
+
+from .model import Frame, ThreadBacktrace
+
+Sampler = Callable[[int], List[ThreadBacktrace]]
+
+
+@dataclass
+class DominantFrame:
+    """The most frequent top frame of one thread across the samples."""
+
+    thread_id: int
+    thread_name: Optional[str]
+    frame: Frame
+    hits: int
+    samples: int
+    stack: List[Frame] = field(default_factory=list)
+
+    @property
+    def ratio(self) -> float:
+        return self.hits / self.samples if self.samples else 0.0
+
+    def describe(self) -> str:
+        who = f"thread {self.thread_id}"
+        if self.thread_name:
+            who += f" ({self.thread_name})"
+        where = self.frame.function or (f"0x{self.frame.pc:x}" if self.frame.pc is not None else "??")
+        return f"{who} is in `{where}` {self.ratio:.0%} of samples"
+
+
+@dataclass
+class HangConfig:
+    """Which threads the hang report leaves out.
+
+    A sample of a thread is skipped when its top frame's function contains any
+    of ``ignore_frame_patterns`` (case-sensitive substrings, like the fault
+    markers), e.g. ``epoll_wait``, ``futex_wait``, ``thread::sleep``.
+    """
+
+    ignore_frame_patterns: List[str] = field(default_factory=list)
+
+    def is_idle(self, thread: ThreadBacktrace) -> bool:
+        if not self.ignore_frame_patterns or not thread.frames:
+            return False
+        func = thread.frames[0].function or ""
+        return any(p in func for p in self.ignore_frame_patterns)
+
+
+@dataclass
+class HangReport:
+    dominant_frames: List[DominantFrame] = field(default_factory=list)
+    sampled_at: List[float] = field(default_factory=list)
+    # Every thread seen, including the ignored ones.
+    thread_count: int = 0
+    # Threads whose top frame matched `HangConfig.ignore_frame_patterns` in every sample.
+    ignored_threads: int = 0
+
+    def describe(self) -> str:
+        if not self.sampled_at:
+            return "No stack samples were captured."
+        threads = f"{self.thread_count} thread(s)"
+        if self.ignored_threads:
+            threads += f", {self.ignored_threads} idle not shown"
+        lines = [f"{len(self.sampled_at)} samples, {threads}:"]
+        lines.extend(f"- {d.describe()}" for d in self.dominant_frames)
+        return "\n".join(lines)
+
+
+def _frame_key(frame: Frame) -> str:
+    # Group by function so a thread spinning inside one function counts as one location.
+    if frame.function:
+        return frame.function
+    return f"0x{frame.pc:x}" if frame.pc is not None else "??"
+
+
+def summarize_samples(
+    samples: List[List[ThreadBacktrace]],
+    sampled_at: List[float],
+    config: Optional[HangConfig] = None,
+) -> HangReport:
+    """Build a HangReport from already captured samples, skipping idle threads per ``config``."""
+    config = config or HangConfig()
+    per_thread: Dict[int, Counter[str]] = {}
+    names: Dict[int, Optional[str]] = {}
+    exemplar: Dict[tuple[int, str], ThreadBacktrace] = {}
+    idle: set[int] = set()
+    thread_count = 0
+    for sample in samples:
+        thread_count = max(thread_count, len(sample))
+        for thread in sample:
+            names.setdefault(thread.thread_id, thread.name)
+            if not thread.frames:
+                continue
+            if config.is_idle(thread):
+                idle.add(thread.thread_id)
+                continue
+            key = _frame_key(thread.frames[0])
+            per_thread.setdefault(thread.thread_id, Counter())[key] += 1
+            exemplar.setdefault((thread.thread_id, key), thread)
+
+    dominant: List[DominantFrame] = []
+    for thread_id in sorted(per_thread):
+        key, hits = per_thread[thread_id].most_common(1)[0]
+        thread = exemplar[(thread_id, key)]
+        dominant.append(
+            DominantFrame(
+                thread_id=thread_id,
+                thread_name=names.get(thread_id),
+                frame=thread.frames[0],
+                hits=hits,
+                samples=len(samples),
+                stack=list(thread.frames),
+            )
+        )
+    dominant.sort(key=lambda d: (-d.ratio, d.thread_id))
+    return HangReport(
+        dominant_frames=dominant,
+        sampled_at=list(sampled_at),
+        thread_count=thread_count,
+        ignored_threads=len(idle - per_thread.keys()),
+    )
+
+
+class Watchdog:
+    """Periodically sample all thread stacks of a live process.
+
+    ``sampler`` defaults to the batch backend found by ``detect_crash_backend``
+    (GDB, then LLDB); each sample attaches, prints every backtrace, and detaches,
+    so the process keeps running between samples.
+    """
+
+    def __init__(
+        self,
+        pid: int,
+        *,
+        interval_ms: int = 500,
+        duration_ms: int = 5000,
+        sampler: Optional[Sampler] = None,
+        backend: Optional[Any] = None,
+        config: Optional[HangConfig] = None,
+        clock: Callable[[], float] = time.monotonic,
+        sleep: Callable[[float], None] = time.sleep,
+    ) -> None:
+        if interval_ms <= 0 or duration_ms <= 0:
+            raise ValueError("interval_ms and duration_ms must be positive")
+        self.pid = pid
+        self.interval_ms = interval_ms
+        self.duration_ms = duration_ms
+        self._sampler = sampler
+        self._backend = backend
+        self.config = config or HangConfig()
+        self._clock = clock
+        self._sleep = sleep
+
+    def _resolve_sampler(self) -> Sampler:
+        if self._sampler is None:
+            backend = self._backend
+            if backend is None:
+                from dbgcopilot.backends.batch import detect_crash_backend
+
+                backend = detect_crash_backend()
+            self._sampler = backend.sample_threads
+        return self._sampler
+
+    def sample(self) -> List[ThreadBacktrace]:
+        return self._resolve_sampler()(self.pid)
+
+    def run(self) -> HangReport:
+        sampler = self._resolve_sampler()
+        samples: List[List[ThreadBacktrace]] = []
+        sampled_at: List[float] = []
+        count = max(1, self.duration_ms // self.interval_ms)
+        start = self._clock()
+        for i in range(count):
+            # Schedule against the start time so slow attaches don't stretch the window.
+            delay = start + i * self.interval_ms / 1000.0 - self._clock()
+            if delay > 0:
+                self._sleep(delay)
+            threads = sampler(self.pid)
+            if threads:
+                samples.append(threads)
+                sampled_at.append(time.time())
+        return summarize_samples(samples, sampled_at, self.config)
+
+
+__all__ = [
+    "DominantFrame",
+    "HangConfig",
+    "HangReport",
+    "Watchdog",
+    "summarize_samples",
+]
@@ -0,0 +1,277 @@
+"""Classify a hang from every thread's stack: deadlock, async stall, busy loop, I/O, or sync wait.
+
+Each thread's top frames are matched against markers for lock acquisition,
+an async runtime's scheduler wait, blocking I/O, other synchronization waits
+(condvars, joins, semaphores), and sleeps. The per-thread states then decide
+the process-wide `HangKind`. Like `classify_fault`, the result is a hint for
+the LLM and the user, not a proof: stacks show who waits, not who owns what.
+
+Async tasks are state machines, not threads: a task stuck on an ``.await``
+that never completes has no stack of its own. What the debugger shows is the
+runtime's threads with nothing to run, idle workers in the scheduler and
+``block_on`` parked, which a thread-centric reading mistakes for a condvar
+wait. `HangCategory.ASYNC_STALLED` names that picture.
+"""
+from __future__ import annotations
+
+from dataclasses import dataclass, field
+from enum import Enum
+from typing import TYPE_CHECKING, FrozenSet, Iterable, List, Optional, Tuple
+
+from .hang import HangConfig
+from .model import Frame, ThreadBacktrace
+
+if TYPE_CHECKING:
+    from dbgcopilot.backends.base import DebugSession
+
+    from .deadlock import DeadlockReport, WaitLink
+
+# How deep into each stack the wait markers are searched; blocking calls sit
+# under a few libc/runtime frames (syscall, futex_wait, lock_contended, ...).
+WAIT_FRAMES = 8
+
+
+class ThreadState(str, Enum):
+    RUNNING = "running"
+    SLEEPING = "sleeping"
+    LOCK_WAIT = "lock_wait"
+    # An async runtime's thread waiting for a task to become ready: an idle worker, or `block_on`.
+    SCHEDULER_WAIT = "scheduler_wait"
+    SYNC_WAIT = "sync_wait"
+    IO_WAIT = "io_wait"
+    # A goroutine descheduled by Go's runtime (`runtime.gopark`): on a channel, select, or sync primitive.
+    PARKED = "parked"
+    UNKNOWN = "unknown"
+
+
+class HangCategory(str, Enum):
+    DEADLOCK = "deadlock"
+    ASYNC_STALLED = "async_stalled"
+    BUSY_LOOP = "busy_loop"
+    BLOCKED_ON_IO = "blocked_on_io"
+    BLOCKED_ON_SYNC = "blocked_on_sync"
+    UNKNOWN = "unknown"
+
+    @property
+    def label(self) -> str:
+        return _LABELS[self]
+
+
+_LABELS = {
+    HangCategory.DEADLOCK: "deadlock (threads or goroutines waiting on each other)",
+    HangCategory.ASYNC_STALLED: "async stall (all runtime threads idle in the scheduler; a task awaits forever)",
+    HangCategory.BUSY_LOOP: "busy loop (a thread keeps running or polling without finishing)",
+    HangCategory.BLOCKED_ON_IO: "blocked on I/O",
+    HangCategory.BLOCKED_ON_SYNC: "blocked on a condition variable, join, or semaphore",
+    HangCategory.UNKNOWN: "unclassified hang",
+}
+
+# (exact C-level names, substrings). Exact names are compared after dropping
+# glibc's ``__GI_`` / ``__libc_`` / leading-underscore decorations, so ``read``
+# matches ``__libc_read`` without also matching ``pthread_create``.
+_Markers = Tuple[FrozenSet[str], Tuple[str, ...]]
+
+_LOCK_MARKERS: _Markers = (
+    frozenset({"pthread_mutex_lock", "pthread_mutex_timedlock", "pthread_rwlock_rdlock", "pthread_rwlock_wrlock",
+               "lll_lock_wait", "RtlEnterCriticalSection", "EnterCriticalSection", "AcquireSRWLockExclusive",
+               "AcquireSRWLockShared", "RtlAcquireSRWLockExclusive", "RtlAcquireSRWLockShared"}),
+    # Rust std's futex mutex/rwlock slow paths, parking_lot, Go, macOS.
+    ("lock_contended", "read_contended", "write_contended", "lock_slow", "lockSlow", "mutex_firstfit_lock_wait"),
+)
+# tokio's worker and block_on parking (its I/O driver parks in epoll_wait under them),
+# futures' and async-io's block_on, and the `parking` crate under async-std and smol.
+_SCHEDULER_MARKERS: _Markers = (
+    frozenset(),
+    ("runtime::park::", "multi_thread::park::", "runtime::driver::Driver::park", "current_thread::Context::park",
+     "futures_executor::local_pool::run_executor", "async_io::driver::block_on", "parking::Inner::park"),
+)
+_IO_MARKERS: _Markers = (
+    frozenset({"read", "readv", "pread64", "write", "writev", "recv", "recvfrom", "recvmsg", "send", "sendto",
+               "accept", "accept4", "connect", "poll", "ppoll", "select", "pselect", "epoll_wait", "epoll_pwait",
+               "kevent", "wait4", "waitpid", "NtReadFile", "NtWriteFile", "ReadFile", "WriteFile", "WSARecv",
+               "WSAPoll", "GetQueuedCompletionStatus", "NtRemoveIoCompletion"}),
+    ("Stdin::read", "netpoll"),
+)
+_SYNC_MARKERS: _Markers = (
+    frozenset({"pthread_cond_wait", "pthread_cond_timedwait", "pthread_join", "pthread_barrier_wait", "sem_wait",
+               "sem_timedwait", "futex_wait", "futex_abstimed_wait_common", "futex_abstimed_wait_common64",
+               "WaitForSingleObject", "WaitForSingleObjectEx", "WaitForMultipleObjects", "NtWaitForSingleObject",
+               "NtWaitForMultipleObjects", "NtWaitForAlertByThreadId", "SleepConditionVariableSRW",
+               "RtlSleepConditionVariableSRW", "psynch_cvwait"}),
+    ("Condvar", "JoinInner", "Thread>::join", "thread::park", "Barrier", "semacquire", "recv_timeout"),
+)
+_SLEEP_MARKERS: _Markers = (
+    frozenset({"nanosleep", "clock_nanosleep", "usleep", "sleep", "Sleep", "SleepEx", "NtDelayExecution",
+               "sched_yield", "SwitchToThread"}),
+    ("thread::sleep", "time.Sleep"),
+)
+_PARK_MARKERS: _Markers = (frozenset({"runtime.gopark", "runtime.goparkunlock"}), ())
+# Checked in this order: a mutex slow path runs on top of futex_wait, so the
+# lock marker has to win over the generic sync one, and a runtime parks on
+# epoll_wait or a condvar, so its scheduler marker wins over both.
+_STATE_MARKERS = (
+    (ThreadState.LOCK_WAIT, _LOCK_MARKERS),
+    (ThreadState.SCHEDULER_WAIT, _SCHEDULER_MARKERS),
+    (ThreadState.IO_WAIT, _IO_MARKERS),
+    (ThreadState.SYNC_WAIT, _SYNC_MARKERS),
+    (ThreadState.SLEEPING, _SLEEP_MARKERS),
+    # Last: more specific markers (lockSlow, semacquire, netpoll, time.Sleep) sit above gopark.
+    (ThreadState.PARKED, _PARK_MARKERS),
+)
+
+
+def c_function_name(function: str) -> str:
+    """``pthread_mutex_lock`` for ``___pthread_mutex_lock``, ``read`` for ``__GI___libc_read``."""
+    name = function.split("::")[-1].split("(")[0].strip()
+    for prefix in ("__GI_", "__libc_", "__", "_"):
+        if name.startswith(prefix):
+            name = name[len(prefix) :]
+    return name.lstrip("_")
+
+
+def _matches(function: Optional[str], markers: _Markers) -> bool:
+    if not function:
+        return False
+    exact, substrings = markers
+    return c_function_name(function) in exact or any(s in function for s in substrings)
+
+
+@dataclass
+class ThreadSummary:
+    """What one thread was doing when sampled, and the frame that says so."""
+
+    thread_id: int
+    state: ThreadState
+    tid: Optional[int] = None
+    name: Optional[str] = None
+    # The frame whose function matched a wait marker, or the top frame for RUNNING.
+    frame: Optional[Frame] = None
+    # ``thread_id`` is a goroutine id, and ``wait_reason`` Go's own ("chan send", "select", ...).
+    goroutine: bool = False
+    wait_reason: Optional[str] = None
+
+    def describe(self) -> str:
+        who = f"{'goroutine' if self.goroutine else 'thread'} {self.thread_id}"
+        if self.name:
+            who += f" ({self.name})"
+        state = self.state.value + (f" ({self.wait_reason})" if self.wait_reason else "")
+        where = ""
+        if self.frame is not None:
+            where = f" in `{self.frame.function or (f'0x{self.frame.pc:x}' if self.frame.pc is not None else '??')}`"
+        return f"{who}: {state}{where}"
+
+
+def summarize_thread(thread: ThreadBacktrace) -> ThreadSummary:
+    summary = ThreadSummary(thread_id=thread.thread_id, state=ThreadState.UNKNOWN, tid=thread.tid, name=thread.name)
+    top = thread.frames[:WAIT_FRAMES]
+    for state, markers in _STATE_MARKERS:
+        hit = next((f for f in top if _matches(f.function, markers)), None)
+        if hit is not None:
+            summary.state, summary.frame = state, hit
+            return summary
+    if top and top[0].function:
+        # Not inside any known wait: executing its own code.
+        summary.state, summary.frame = ThreadState.RUNNING, top[0]
+    return summary
+
+
+@dataclass
+class HangKind:
+    """Process-wide verdict plus the per-thread evidence behind it.
+
+    ``waiters`` lists the threads (``ThreadBacktrace.thread_id``) stuck
+    acquiring locks when ``category`` is `HangCategory.DEADLOCK`. ``wait_chain``
+    says, for each of those, which lock and whose; ``deadlocks`` holds the
+    wait-for cycles those owners confirm, if any.
+    """
+
+    category: HangCategory
+    waiters: List[int] = field(default_factory=list)
+    threads: List[ThreadSummary] = field(default_factory=list)
+    deadlocks: List["DeadlockReport"] = field(default_factory=list)
+    wait_chain: List["WaitLink"] = field(default_factory=list)
+
+    def describe(self) -> str:
+        text = f"Hang classification: {self.category.label}"
+        if self.waiters:
+            unit = "goroutines" if any(t.goroutine for t in self.threads) else "threads"
+            text += f" between {unit} " + ", ".join(str(w) for w in self.waiters)
+        lines = [text] + [d.describe() for d in self.deadlocks]
+        # The cycles already say what their threads wait for.
+        in_cycles = {tid for d in self.deadlocks for tid in d.cycle}
+        rest = [link for link in self.wait_chain if link.thread_id not in in_cycles]
+        if rest:
+            lines.append("Wait chain:")
+            lines.extend(f"- {link.describe()}" for link in rest)
+        return "\n".join(lines)
+
+
+def classify_threads(threads: Iterable[ThreadBacktrace], config: Optional[HangConfig] = None) -> HangKind:
+    """Label a hang from one snapshot of all threads, after dropping ``config``'s idle ones.
+
+    Two or more threads in lock acquisition with nothing else running is a
+    deadlock; runtime threads all waiting in the scheduler, with the rest at
+    most in sync waits, is an async stall; any running or polling thread makes
+    it a busy loop; otherwise the threads are blocked on I/O, or failing that
+    on other synchronization.
+    """
+    config = config or HangConfig()
+    return classify_summaries([summarize_thread(t) for t in threads if not config.is_idle(t)])
+
+
+def classify_summaries(summaries: List[ThreadSummary]) -> HangKind:
+    """The `classify_threads` rules over already summarized threads (or goroutines)."""
+    states = [s.state for s in summaries]
+    lock_waiters = [s.thread_id for s in summaries if s.state == ThreadState.LOCK_WAIT]
+    busy = ThreadState.RUNNING in states or ThreadState.SLEEPING in states
+    if len(lock_waiters) >= 2 and ThreadState.RUNNING not in states:
+        return HangKind(HangCategory.DEADLOCK, waiters=sorted(lock_waiters), threads=summaries)
+    # Idle blocking-pool threads and joins sit in plain sync waits beside the runtime.
+    quiet = {ThreadState.SCHEDULER_WAIT, ThreadState.SYNC_WAIT, ThreadState.UNKNOWN}
+    if ThreadState.SCHEDULER_WAIT in states and set(states) <= quiet:
+        return HangKind(HangCategory.ASYNC_STALLED, threads=summaries)
+    if busy:
+        return HangKind(HangCategory.BUSY_LOOP, threads=summaries)
+    if ThreadState.IO_WAIT in states:
+        return HangKind(HangCategory.BLOCKED_ON_IO, threads=summaries)
+    waiting = (ThreadState.LOCK_WAIT, ThreadState.SCHEDULER_WAIT, ThreadState.SYNC_WAIT, ThreadState.PARKED)
+    if any(s in states for s in waiting):
+        return HangKind(HangCategory.BLOCKED_ON_SYNC, threads=summaries)
+    return HangKind(HangCategory.UNKNOWN, threads=summaries)
+
+
+def analyze_hang(session: "DebugSession", config: Optional[HangConfig] = None) -> HangKind:
+    """Classify the hang of a stopped process or dump from all of its thread stacks.
+
+    Where the session can tell lock owners, wait-for cycles in the
+    `detect_wait_chain` links make the verdict a deadlock with those exact threads.
+    """
+    from .deadlock import deadlocks_in, detect_wait_chain, wait_chain
+
+    threads = session.threads()
+    kind = classify_threads(threads, config)
+    try:
+        kind.wait_chain = detect_wait_chain(session, threads)
+    except (NotImplementedError, RuntimeError):
+        # The owners are extra evidence; the stack-based verdict stands without them.
+        kind.wait_chain = wait_chain(threads, lambda _address: None)
+        return kind
+    kind.deadlocks = deadlocks_in(kind.wait_chain)
+    if kind.deadlocks:
+        kind.category = HangCategory.DEADLOCK
+        kind.waiters = sorted({tid for d in kind.deadlocks for tid in d.cycle})
+    return kind
+
+
+__all__ = [
+    "HangCategory",
+    "HangKind",
+    "ThreadState",
+    "ThreadSummary",
+    "WAIT_FRAMES",
+    "analyze_hang",
+    "c_function_name",
+    "classify_summaries",
+    "classify_threads",
+    "summarize_thread",
+]
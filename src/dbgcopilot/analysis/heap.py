@@ -0,0 +1,287 @@
+"""Heap state at the crash: its size, what is allocated, and what the allocator noticed.
+
+A use-after-free or a double free usually crashes in the allocator, or far
+from the bug, and the stack alone does not say so. `HeapSummary` is what
+`DebugSession.heap_summary` returns, and it travels with the report (the
+``heap`` key of the JSON, ``Heap:`` lines in the text) so the explanation can
+take the heap into account:
+
+- ``reserved`` is the address space set aside for the heap, ``committed``
+  the part backed by memory, both in bytes;
+- ``buckets`` counts busy allocations by size, in power-of-two buckets;
+- ``corruption`` lists what the allocator or the engine reported as heap
+  corruption, in its own words.
+
+What each engine provides differs. CDB reads the NT heaps (``!heap -s``,
+``!heap -stat``) and the heap failures DbgEng recorded. GDB takes the
+``[heap]`` mapping as reserved, and glibc's ``main_arena.system_mem`` plus
+``mp_.mmapped_mem`` as committed when libc's symbols are available; it
+cannot count allocations. LLDB and the built-in dump readers provide no heap
+data. A number the engine did not report stays None, with the reason in
+``note``; nothing is estimated.
+
+Corruption indicators do not depend on the engine: glibc's and
+AddressSanitizer's messages in the program's output (`heap_messages`), and
+allocator failure frames on the faulting stack (`heap_corruption_indicators`).
+"""
+from __future__ import annotations
+
+import re
+from dataclasses import dataclass, field, replace
+from typing import Any, Dict, Iterable, List, Mapping, Optional, Sequence, Tuple
+
+from .model import Frame, StopInfo
+
+# Smallest bucket; blocks below it are counted with it.
+MIN_BUCKET = 16
+# STATUS_HEAP_CORRUPTION, raised by the Windows heap when it finds a damaged block.
+STATUS_HEAP_CORRUPTION = 0xC0000374
+# Rows ``!heap -stat`` prints per heap by default (its max-display).
+CDB_STAT_ROWS = 20
+
+# glibc's malloc_printerr messages: "free(): double free detected in tcache 2", "corrupted size vs. prev_size", ...
+_GLIBC_MESSAGE_RE = re.compile(
+    r"^(?P<message>(?:free|malloc|realloc|calloc|munmap_chunk|malloc_consolidate|_int_\w+|tcache_\w+)\(\): .+"
+    r"|double free or corruption \(.+\)|corrupted (?:size|double-linked list|top size).*)$"
+)
+_ASAN_RE = re.compile(r"ERROR: AddressSanitizer: (?P<kind>[\w-]+)")
+# "free(): invalid pointer" as the string argument of glibc's malloc_printerr.
+_QUOTED_RE = re.compile(r'"(?P<text>[^"]+)"')
+_ALLOCATOR_FAILURE_FRAMES = {
+    "malloc_printerr": "glibc detected heap corruption",
+    "RtlReportCriticalFailure": "the Windows heap reported a critical failure",
+    "RtlpHeapHandleError": "the Windows heap reported a handle error",
+    "RtlpLogHeapFailure": "the Windows heap logged a failure",
+}
+# gdb `info proc mappings`: "0x555555559000 0x55555557a000 0x21000 0x0 rw-p [heap]".
+_GDB_HEAP_MAPPING_RE = re.compile(
+    r"^0x(?P<start>[0-9a-fA-F]+)\s+0x(?P<end>[0-9a-fA-F]+)\s+0x[0-9a-fA-F]+\s+0x[0-9a-fA-F]+"
+    r"(?:\s+[rwxsp-]{4})?\s+\[heap\]\s*$"
+)
+_GDB_VALUE_RE = re.compile(r"^\$\d+ = (?P<value>\d+)\s*$", re.MULTILINE)
+# cdb `!heap -s`: "000001c9a5e40000 00000002    1020    348   1020 ..." (heap, flags, reserve k, commit k).
+_CDB_HEAP_ROW_RE = re.compile(
+    r"^(?P<heap>[0-9a-fA-F`]{8,17})\s+[0-9a-fA-F]{8}\s+(?P<reserve>\d+)\s+(?P<commit>\d+)\s"
+)
+_CDB_ERROR_ADDRESS_RE = re.compile(r"^Error address:\s*(?P<address>[0-9a-fA-F`]+)\s*$")
+# cdb `!heap -stat -h 0`: "    1000 1 - 1000  (34.56)" (block size, count, total; all hex).
+_CDB_STAT_ROW_RE = re.compile(r"^(?P<size>[0-9a-fA-F]+)\s+(?P<count>[0-9a-fA-F]+)\s+-\s+[0-9a-fA-F]+\s+\(")
+
+
+def format_size(size: int) -> str:
+    """``348 KiB``, ``1.3 MiB``: binary units, one decimal above KiB."""
+    if size < 1024:
+        return f"{size} B"
+    if size < 1024 * 1024:
+        return f"{size // 1024} KiB"
+    if size < 1024 ** 3:
+        return f"{size / 1024 ** 2:.1f} MiB"
+    return f"{size / 1024 ** 3:.1f} GiB"
+
+
+@dataclass
+class SizeBucket:
+    """Busy allocations of at most ``limit`` bytes (and more than the previous bucket's)."""
+
+    limit: int
+    count: int
+    total: int = 0
+
+    def describe(self) -> str:
+        return f"<={format_size(self.limit)}: {self.count}"
+
+
+def bucket_allocations(blocks: Iterable[Tuple[int, int]]) -> List[SizeBucket]:
+    """Group ``(block size, count)`` pairs into power-of-two buckets, smallest first."""
+    buckets: Dict[int, SizeBucket] = {}
+    for size, count in blocks:
+        limit = MIN_BUCKET
+        while limit < size:
+            limit *= 2
+        bucket = buckets.setdefault(limit, SizeBucket(limit, 0))
+        bucket.count += count
+        bucket.total += size * count
+    return [buckets[limit] for limit in sorted(buckets)]
+
+
+@dataclass
+class HeapSummary:
+    """The heap as one engine saw it; see the module docstring for what each field needs."""
+
+    engine: str = ""
+    reserved: Optional[int] = None
+    committed: Optional[int] = None
+    buckets: List[SizeBucket] = field(default_factory=list)
+    corruption: List[str] = field(default_factory=list)
+    # What the engine could not provide, and why; None when nothing is missing.
+    note: Optional[str] = None
+
+    @classmethod
+    def unsupported(cls, engine: str, corruption: Sequence[str] = ()) -> "HeapSummary":
+        """No heap data from ``engine``; corruption seen elsewhere (its output, the stack) is still kept."""
+        note = f"heap statistics not supported by backend {engine}"
+        return cls(engine=engine, corruption=list(corruption), note=note)
+
+    @property
+    def has_statistics(self) -> bool:
+        return self.reserved is not None or self.committed is not None or bool(self.buckets)
+
+    def with_stack(self, stop: StopInfo, frames: Sequence[Frame]) -> "HeapSummary":
+        """This summary plus the `heap_corruption_indicators` of the faulting stack, without duplicates."""
+        return replace(self, corruption=_merge(self.corruption, heap_corruption_indicators(stop, frames)))
+
+    def describe(self) -> List[str]:
+        """Report lines; none when there are neither statistics nor corruption (the note is in the JSON)."""
+        lines: List[str] = []
+        sizes = [f"{format_size(self.committed)} committed" if self.committed is not None else "",
+                 f"{format_size(self.reserved)} reserved" if self.reserved is not None else ""]
+        if any(sizes):
+            lines.append(f"Heap: {', '.join(s for s in sizes if s)} ({self.engine})")
+        if self.buckets:
+            lines.append(f"Heap allocations by size: {', '.join(b.describe() for b in self.buckets)}")
+        lines.extend(f"Heap corruption: {indicator}" for indicator in self.corruption)
+        return lines
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {
+            "engine": self.engine,
+            "reserved": self.reserved,
+            "committed": self.committed,
+            "buckets": [{"limit": b.limit, "count": b.count, "total": b.total} for b in self.buckets],
+            "corruption": list(self.corruption),
+            "note": self.note,
+        }
+
+    @classmethod
+    def from_dict(cls, data: Mapping[str, Any]) -> "HeapSummary":
+        return cls(
+            engine=data.get("engine") or "",
+            reserved=data.get("reserved"),
+            committed=data.get("committed"),
+            buckets=[SizeBucket(b["limit"], b["count"], b.get("total", 0)) for b in data.get("buckets") or []],
+            corruption=list(data.get("corruption") or []),
+            note=data.get("note"),
+        )
+
+
+def _merge(first: Sequence[str], second: Iterable[str]) -> List[str]:
+    merged = list(first)
+    merged.extend(item for item in second if item not in merged)
+    return merged
+
+
+def heap_messages(output: str) -> List[str]:
+    """Heap corruption reported in the program's own output: glibc's abort messages and AddressSanitizer errors."""
+    found: List[str] = []
+    for raw in (output or "").splitlines():
+        line = raw.strip()
+        m = _GLIBC_MESSAGE_RE.match(line)
+        if m:
+            found = _merge(found, [m.group("message")])
+            continue
+        m = _ASAN_RE.search(line)
+        if m:
+            found = _merge(found, [f"AddressSanitizer: {m.group('kind')}"])
+    return found
+
+
+def heap_corruption_indicators(stop: StopInfo, frames: Sequence[Frame]) -> List[str]:
+    """Allocator failure frames on the stack, and the Windows heap-corruption exception code."""
+    found: List[str] = []
+    if stop.exception_code == STATUS_HEAP_CORRUPTION:
+        found.append(f"STATUS_HEAP_CORRUPTION (0x{STATUS_HEAP_CORRUPTION:08x})")
+    for frame in frames:
+        what = _ALLOCATOR_FAILURE_FRAMES.get(frame.function or "")
+        if what is None:
+            continue
+        # glibc passes its message as malloc_printerr's only argument, printed in the frame line or its locals.
+        printed = [frame.args or ""] + [str(v.value) for v in frame.locals]
+        quoted = next((m.group("text") for text in printed if (m := _QUOTED_RE.search(text))), None)
+        found = _merge(found, [quoted or f"{what} ({frame.function})"])
+    return found
+
+
+def gdb_heap_summary(mappings: str, system_mem: str, mmapped_mem: str, output: str = "") -> HeapSummary:
+    """Build a summary from `info proc mappings`, ``print main_arena.system_mem``, and ``print mp_.mmapped_mem``.
+
+    ``output`` is the rest of the session's output, searched for `heap_messages`.
+    """
+    notes = ["allocation counts by size are not available from gdb"]
+    reserved = None
+    for raw in (mappings or "").splitlines():
+        m = _GDB_HEAP_MAPPING_RE.match(raw.strip())
+        if m:
+            reserved = (reserved or 0) + int(m.group("end"), 16) - int(m.group("start"), 16)
+    if reserved is None:
+        notes.insert(0, "no [heap] mapping listed")
+    arena, mmapped = _GDB_VALUE_RE.search(system_mem or ""), _GDB_VALUE_RE.search(mmapped_mem or "")
+    committed = int(arena.group("value")) + (int(mmapped.group("value")) if mmapped else 0) if arena else None
+    if committed is None:
+        notes.insert(0, "committed size needs glibc's symbols (main_arena)")
+    return HeapSummary(
+        engine="gdb",
+        reserved=reserved,
+        committed=committed,
+        corruption=heap_messages(output),
+        note="; ".join(notes),
+    )
+
+
+def cdb_heap_summary(summary: str, stat: str) -> HeapSummary:
+    """Build a summary from ``!heap -s`` (sizes and recorded failures) and ``!heap -stat -h 0`` (block sizes)."""
+    reserved = committed = error_address = None
+    corruption: List[str] = []
+    for raw in (summary or "").splitlines():
+        line = raw.strip()
+        m = _CDB_HEAP_ROW_RE.match(line)
+        if m:
+            reserved = (reserved or 0) + int(m.group("reserve")) * 1024
+            committed = (committed or 0) + int(m.group("commit")) * 1024
+        elif (found := _CDB_ERROR_ADDRESS_RE.match(line)) is not None:
+            error_address = int(found.group("address").replace("`", ""), 16)
+        elif line.startswith("Error type:"):
+            # A recorded failure prints its address, then its type.
+            where = f" at 0x{error_address:x}" if error_address is not None else ""
+            corruption.append(f"{line.split(':', 1)[1].strip()}{where}")
+            error_address = None
+    blocks: List[Tuple[int, int]] = []
+    rows = truncated = 0
+    for raw in (stat or "").splitlines():
+        line = raw.strip()
+        if line.startswith("heap @"):
+            rows = 0
+        m = _CDB_STAT_ROW_RE.match(line)
+        if m:
+            blocks.append((int(m.group("size"), 16), int(m.group("count"), 16)))
+            rows += 1
+            truncated |= rows == CDB_STAT_ROWS
+    notes = []
+    if reserved is None:
+        notes.append("no NT heaps listed by !heap -s")
+    if not blocks:
+        notes.append("no busy-block statistics from !heap -stat")
+    elif truncated:
+        notes.append(f"allocation counts cover only the {CDB_STAT_ROWS} sizes with the most busy bytes per heap")
+    return HeapSummary(
+        engine="cdb",
+        reserved=reserved,
+        committed=committed,
+        buckets=bucket_allocations(blocks),
+        corruption=corruption,
+        note="; ".join(notes) or None,
+    )
+
+
+__all__ = [
+    "CDB_STAT_ROWS",
+    "HeapSummary",
+    "MIN_BUCKET",
+    "STATUS_HEAP_CORRUPTION",
+    "SizeBucket",
+    "bucket_allocations",
+    "cdb_heap_summary",
+    "format_size",
+    "gdb_heap_summary",
+    "heap_corruption_indicators",
+    "heap_messages",
+]
@@ -0,0 +1,272 @@
+"""Work out what language a target is written in, so prompts can use its idioms.
+
+`detect_language` looks at the target file first: a ``#!`` line or ``.py``
+suffix names a script's interpreter, and an ELF executable gives itself away
+by its sections, symbols, and linked libraries (Go build info, Rust panic
+machinery, libstdc++). An interpreter binary such as ``python3`` counts as
+the language it runs. Frames are the fallback, and they win when they show
+an interpreter: a crash under ``_PyEval_EvalFrameDefault`` is a Python crash
+even when the binary is a native extension's test driver, and one under
+``v8::`` or ``node::`` frames a JavaScript one.
+
+That is the target's language. `crash_language` says what the crash itself
+is written in, which is what an explanation should speak: the language of
+most of the program's own frames (see `FrameRules`), by source suffix or by
+how the symbol is mangled (Rust legacy ``17h<hash>E`` or v0 ``_R``, Itanium
+``_Z``). A Rust program crashing in a C++ library it links is a C++ crash,
+and the reverse. When no user frame says, runtime markers on the stack or in
+the module list decide (``rust_begin_unwind``, ``libstd-<hash>.so``;
+``__cxa_throw``, ``libstdc++``). An interpreted target keeps its language,
+since its native frames are the interpreter's or an extension's.
+`CrashReport.detected_language` holds the result.
+
+`LANGUAGE_HINTS` holds the one-line guidance `PromptBuilder` adds for each
+language.
+"""
+from __future__ import annotations
+
+import os
+import re
+import shutil
+from typing import Dict, List, Optional, Sequence
+
+from .frames import FrameRules
+from .model import Frame, Language, Module
+
+# Interpreter loop and C-API entry points that appear in every CPython stack.
+_PYTHON_FRAMES = ("_PyEval_EvalFrame", "PyEval_EvalCode", "_PyObject_Call", "PyObject_Call", "_PyFunction_Vectorcall",
+                  "pymain_run_python", "Py_RunMain")
+_PYTHON_MODULE_RE = re.compile(r"^(?:libpython\d|python\d)")
+_JAVA_MODULES = ("libjvm", "libjava")
+# V8 and Node's C++, linked into the node binary; see `dbgcopilot.analysis.v8`.
+_NODE_FRAMES = ("v8::", "node::", "Builtins_", "_ZN2v8", "_ZN4node")
+_INTERPRETERS = {"python": Language.PYTHON, "java": Language.JAVA, "node": Language.JAVASCRIPT}
+_SUFFIXES = {
+    ".py": Language.PYTHON,
+    ".pyw": Language.PYTHON,
+    ".jar": Language.JAVA,
+    ".class": Language.JAVA,
+    ".js": Language.JAVASCRIPT,
+    ".mjs": Language.JAVASCRIPT,
+    ".cjs": Language.JAVASCRIPT,
+}
+
+# Source suffixes of a frame's file; headers other than C++'s say nothing.
+_SOURCE_SUFFIXES = {
+    ".rs": Language.RUST,
+    ".c": Language.C,
+    ".cc": Language.CPP,
+    ".cpp": Language.CPP,
+    ".cxx": Language.CPP,
+    ".c++": Language.CPP,
+    ".hh": Language.CPP,
+    ".hpp": Language.CPP,
+    ".hxx": Language.CPP,
+    ".go": Language.GO,
+}
+_RUST_LEGACY_RE = re.compile(r"^_ZN.*17h[0-9a-f]{16}E")
+_RUST_HASH_RE = re.compile(r"::h[0-9a-f]{16}$")
+_RUST_RUNTIME = ("rust_begin_unwind", "__rust_", "core::panicking::", "std::rt::", "_ZN4core9panicking", "_ZN3std2rt")
+_CPP_RUNTIME = ("__cxa_", "__gxx_personality", "std::terminate", "_ZSt9terminate")
+_RUST_MODULE_RE = re.compile(r"^libstd-[0-9a-f]+\.(?:so|dylib)|^std-[0-9a-f]+\.dll")
+_CPP_MODULE_RE = re.compile(r"^(?:libstdc\+\+|libc\+\+)\.|^msvcp\d+")
+# Targets whose native frames are the runtime's, whatever they are written in.
+_INTERPRETED = (Language.PYTHON, Language.JAVA, Language.JAVASCRIPT)
+
+LANGUAGE_HINTS = {
+    Language.PYTHON: (
+        "The target is a Python program. Frames such as _PyEval_EvalFrameDefault and PyObject_Call are the "
+        "interpreter running Python code; the native frames above them are the extension module or library "
+        "that faulted. Name the Python call that led into native code and the native function that crashed."
+    ),
+    Language.RUST: (
+        "The target is a Rust program. Frames in core::, std::, and alloc:: are the standard library; "
+        "the cause is usually in the first crate frame below them, often an unsafe block or an unwrap/expect. "
+        "Use Rust's terms: a panic rather than an access violation, ownership and borrows, Option and Result, "
+        "and raw pointers only inside unsafe code."
+    ),
+    Language.GO: (
+        "The target is a Go program. runtime. frames are the Go runtime; look for the first frame of the "
+        "program's own packages, and mention nil maps, nil pointers, or goroutine leaks where they fit."
+    ),
+    Language.CPP: (
+        "The target is a C++ program. Consider object lifetime (dangling references, use after free, "
+        "double delete) and exceptions escaping noexcept code. Use C++'s terms: access violations, raw and "
+        "smart pointers, RAII, and undefined behavior."
+    ),
+    Language.C: "The target is a C program. Consider NULL or dangling pointers, buffer overruns, and double free.",
+    Language.JAVA: (
+        "The target is a Java process. Native frames in libjvm are the VM; a crash there is usually JNI code "
+        "or a native library called through it."
+    ),
+    Language.JAVASCRIPT: (
+        "The target is a Node.js process. Frames marked [V8 internals], [V8 builtin], [Node.js runtime], or "
+        "[libuv event loop] are the engine and the event loop: do not explain them as the program's code. "
+        "[JavaScript] frames are the script, interpreted or JIT-compiled without symbols, and [native addon] "
+        "frames are native modules it loaded. Say what the JavaScript did to reach the crash, such as unbounded "
+        "recursion or a bad argument to an addon."
+    ),
+}
+
+
+def _interpreter_language(path: str) -> Optional[Language]:
+    name = os.path.basename(path).lower()
+    for prefix, language in _INTERPRETERS.items():
+        if name.startswith(prefix):
+            return language
+    return None
+
+
+def script_command(path: str) -> Optional[List[str]]:
+    """The interpreter command a ``#!`` script runs under, with ``/usr/bin/env`` resolved.
+
+    Debuggers need a real executable, so a script is loaded as its
+    interpreter with the script as the first argument. None if ``path`` is
+    not a script.
+    """
+    try:
+        with open(path, "rb") as fh:
+            head = fh.readline(256)
+    except OSError:
+        return None
+    if not head.startswith(b"#!"):
+        return None
+    parts = head[2:].decode("utf-8", errors="replace").split()
+    if not parts:
+        return None
+    if os.path.basename(parts[0]) == "env" and parts[1:2] == ["-S"]:
+        # ``env -S`` splits the rest of the line into the interpreter and its options.
+        parts = parts[:1] + parts[2:]
+    if os.path.basename(parts[0]) == "env" and len(parts) > 1:
+        found = shutil.which(parts[1])
+        parts = [found or parts[1]] + parts[2:]
+    return parts + [path]
+
+
+def _elf_language(path: str) -> Optional[Language]:
+    from dbgcopilot.utils.elf import ElfError, open_elf
+
+    try:
+        elf = open_elf(path)
+    except (OSError, ElfError):
+        return None
+    by_name = _interpreter_language(path)
+    if by_name is not None:
+        return by_name
+    if elf.section(".go.buildinfo") is not None or elf.section(".gopclntab") is not None:
+        return Language.GO
+    names = [s.name for s in elf.symbols()]
+    if any(n.startswith(("rust_begin_unwind", "__rust_", "_ZN4core9panicking", "_ZN3std2rt")) for n in names):
+        return Language.RUST
+    dynstr = elf.section_data(".dynstr") or b""
+    if b"libstdc++" in dynstr or b"libc++." in dynstr or any(n.startswith("_Z") for n in names):
+        return Language.CPP
+    return Language.C
+
+
+def _frames_language(frames: Sequence[Frame]) -> Optional[Language]:
+    for frame in frames:
+        module = os.path.basename(frame.module or "").lower()
+        if (frame.function and frame.function.startswith(_PYTHON_FRAMES)) or _PYTHON_MODULE_RE.match(module):
+            return Language.PYTHON
+        if module.startswith(_JAVA_MODULES):
+            return Language.JAVA
+        if frame.function and frame.function.startswith(_NODE_FRAMES):
+            return Language.JAVASCRIPT
+    return None
+
+
+def _symbol_language(frames: Sequence[Frame]) -> Optional[Language]:
+    # Without a binary to read, symbol spelling is the last clue.
+    for frame in frames:
+        function = frame.function or ""
+        if function.startswith(("core::", "std::rt::", "std::panicking::", "alloc::")) or "rust_begin_unwind" in function:
+            return Language.RUST
+        if function.startswith("runtime."):
+            return Language.GO
+    return None
+
+
+def frame_language(frame: Frame) -> Optional[Language]:
+    """The language of ``frame``'s code, from its source file or its symbol's spelling; None if neither says."""
+    by_suffix = _SOURCE_SUFFIXES.get(os.path.splitext(frame.file or "")[1].lower())
+    if by_suffix is not None:
+        return by_suffix
+    symbol = frame.raw_symbol or ""
+    if symbol.startswith("_R") or _RUST_LEGACY_RE.match(symbol):
+        return Language.RUST
+    if symbol.startswith("_Z"):
+        return Language.CPP
+    name = frame.function or ""
+    # Only Rust writes closures and trait impls this way; only C++ debuggers print parameter lists.
+    if _RUST_HASH_RE.search(name) or "{{closure}}" in name or "{closure#" in name or name.startswith("<impl "):
+        return Language.RUST
+    if name.startswith("<") and " as " in name:
+        return Language.RUST
+    if "::" in name and name.endswith(")") and "(" in name:
+        return Language.CPP
+    return None
+
+
+def user_code_language(frames: Sequence[Frame], rules: Optional[FrameRules] = None) -> Optional[Language]:
+    """The language most of the program's own frames are in; a tie goes to the one nearest the crash."""
+    rules = rules or FrameRules()
+    counts: Dict[Language, int] = {}
+    for frame in frames:
+        language = frame_language(frame) if rules.is_user(frame) else None
+        if language is not None:
+            counts[language] = counts.get(language, 0) + 1
+    # ``max`` keeps the first of equals, and frames are counted from the crash outwards.
+    return max(counts, key=counts.__getitem__) if counts else None
+
+
+def _runtime_language(frames: Sequence[Frame], modules: Sequence[Module]) -> Optional[Language]:
+    names = [name for f in frames for name in (f.function, f.raw_symbol) if name]
+    libraries = [os.path.basename(m.path or m.name).lower() for m in modules]
+    if any(n.startswith(_RUST_RUNTIME) for n in names) or any(_RUST_MODULE_RE.match(n) for n in libraries):
+        return Language.RUST
+    if any(n.startswith(_CPP_RUNTIME) for n in names) or any(_CPP_MODULE_RE.match(n) for n in libraries):
+        return Language.CPP
+    return None
+
+
+def crash_language(
+    frames: Sequence[Frame],
+    modules: Sequence[Module] = (),
+    target: Optional[Language] = None,
+    rules: Optional[FrameRules] = None,
+) -> Language:
+    """The language the crash is in: the dominant one of the user frames, then runtime markers, then ``target``."""
+    if target in _INTERPRETED:
+        return target
+    found = user_code_language(frames, rules) or _runtime_language(frames, modules)
+    return found or target or Language.UNKNOWN
+
+
+def detect_language(path: Optional[str], frames: Sequence[Frame] = ()) -> Language:
+    """Best guess at the language of the target at ``path`` (if any) that produced ``frames``."""
+    from_frames = _frames_language(frames)
+    if from_frames is not None:
+        return from_frames
+    if path:
+        command = script_command(path)
+        if command is not None:
+            return _interpreter_language(command[0]) or Language.UNKNOWN
+        by_suffix = _SUFFIXES.get(os.path.splitext(path)[1].lower())
+        if by_suffix is not None:
+            return by_suffix
+        from_elf = _elf_language(path)
+        if from_elf is not None:
+            return from_elf
+    return _symbol_language(frames) or Language.UNKNOWN
+
+
+__all__ = [
+    "LANGUAGE_HINTS",
+    "Language",
+    "crash_language",
+    "detect_language",
+    "frame_language",
+    "script_command",
+    "user_code_language",
+]
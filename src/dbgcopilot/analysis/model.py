@@ -0,0 +1,316 @@
+"""Normalized debugger data shared by every capture backend."""
+from __future__ import annotations
+
+from dataclasses import dataclass, field
+from enum import Enum
+from typing import TYPE_CHECKING, Dict, List, Optional, Union
+
+if TYPE_CHECKING:
+    from .disasm import FaultContext
+    from .heap import HeapSummary
+    from .regions import MemoryRegion
+
+# Frames from the top whose variables are captured with the backtrace.
+DEFAULT_LOCALS_FRAMES = 1
+# si_code of a fault the kernel raised without an address, such as a general protection fault; si_addr is 0.
+SI_KERNEL = 0x80
+
+
+@dataclass
+class Frame:
+    """One stack frame, independent of the debugger that produced it."""
+
+    index: int
+    pc: Optional[int] = None
+    function: Optional[str] = None
+    module: Optional[str] = None
+    file: Optional[str] = None
+    line: Optional[int] = None
+    column: Optional[int] = None
+    # Bytes from the start of ``function`` to ``pc``, when the debugger or symbol table says.
+    offset: Optional[int] = None
+    # Arguments exactly as the debugger printed them (``mutex=0x5555... <lock_a>``); None if not shown.
+    args: Optional[str] = None
+    # The symbol as the debugger or symbol table spelled it (``_ZN5crash5crash17h...E``),
+    # when ``function`` is its demangled form; None if the two are the same.
+    raw_symbol: Optional[str] = None
+    # Arguments, then locals, as the debugger evaluated them at the crash; only the top
+    # frames have them (see `DEFAULT_LOCALS_FRAMES`), and only with debug info.
+    locals: List["Variable"] = field(default_factory=list)
+    # Which part of a language engine the frame belongs to (``V8 internals``, ``JavaScript``),
+    # on stacks `label_engine_frames` recognizes; None for ordinary native code.
+    engine: Optional[str] = None
+
+    def describe(self) -> str:
+        """Return a compact single-line rendering (``#0 0x... func at file:line``)."""
+        parts = [f"#{self.index}"]
+        if self.pc is not None:
+            parts.append(f"0x{self.pc:x}")
+        label = self.function or "??"
+        if self.module:
+            label = f"{self.module}!{label}"
+        parts.append(label)
+        if self.source_location:
+            parts.append(f"at {self.source_location}")
+        if self.engine:
+            parts.append(f"[{self.engine}]")
+        return " ".join(parts)
+
+    def describe_locals(self) -> List[str]:
+        """One indented ``name = value`` line per captured variable, to print under `describe`."""
+        return [f"    {v.describe()}" for v in self.locals]
+
+    @property
+    def source_location(self) -> Optional[str]:
+        """``file:line:column`` with whichever parts are known, or None without a file."""
+        if not self.file:
+            return None
+        loc = self.file
+        if self.line is not None:
+            loc += f":{self.line}"
+            if self.column:
+                loc += f":{self.column}"
+        return loc
+
+
+@dataclass
+class ThreadBacktrace:
+    """Stack of one thread; ``thread_id`` is the debugger's index, ``tid`` the OS id."""
+
+    thread_id: int
+    tid: Optional[int] = None
+    name: Optional[str] = None
+    frames: List[Frame] = field(default_factory=list)
+    # The capture stopped at its frame limit (``max_frames_per_thread``); the stack goes deeper.
+    truncated: bool = False
+    # The engine reported this thread as the one that took the fatal signal or exception.
+    faulting: bool = False
+
+
+@dataclass
+class Module:
+    """A loaded image (executable or shared library) and its address range."""
+
+    name: str
+    base: int
+    size: int
+    path: Optional[str] = None
+    # File version ("10.0.19041.1", or "1.1" from a soname), when the capture path or file name tells.
+    version: Optional[str] = None
+    # Link time of a PE image, in seconds since the epoch; None elsewhere.
+    timestamp: Optional[int] = None
+
+    def contains(self, addr: int) -> bool:
+        return self.base <= addr < self.base + self.size
+
+
+@dataclass
+class Annotation:
+    """A note a `ReportEnricher` added to a report; ``source`` is the enricher's name."""
+
+    source: str
+    text: str
+
+    def describe(self) -> str:
+        return f"Note ({self.source}): {self.text}"
+
+
+@dataclass
+class Register:
+    name: str
+    value: int
+
+
+@dataclass
+class Instruction:
+    """One disassembled instruction, as the debugger printed it."""
+
+    address: int
+    # Mnemonic and operands in the debugger's syntax: ``movl   $0x2a,(%rax)``, ``mov dword ptr [rax],2Ah``.
+    text: str
+    # Where it is, as printed beside the address: ``crash+7``, ``+7``; None if not shown.
+    location: Optional[str] = None
+    # The instruction at the faulting pc.
+    faulting: bool = False
+
+    def describe(self) -> str:
+        where = f" <{self.location}>" if self.location else ""
+        return f"{'=>' if self.faulting else '  '} 0x{self.address:x}{where}: {self.text}"
+
+
+class Optimized(str, Enum):
+    """The value of a variable that has no location at the frame's pc (optimized out)."""
+
+    OUT = "<optimized out>"
+
+
+@dataclass
+class Variable:
+    """A local or argument of one frame, found through the debug info."""
+
+    name: str
+    # Type as the debugger spells it in the target's language (``*mut i32``, ``int *``); None if not shown.
+    type_name: Optional[str] = None
+    # Printed value (``0x0``, ``{a = 1, b = 2}``), or ``Optimized.OUT``.
+    value: Union[str, Optimized] = ""
+    argument: bool = False
+    # Whether a debugger pretty-printer rendered ``value`` (``vec![1, 2, 3]``); False when the live debugger had
+    # none for it and ``value`` is the raw struct; None when not known (cores, scalars, the built-in readers).
+    pretty_printed: Optional[bool] = None
+
+    @property
+    def optimized_out(self) -> bool:
+        return self.value is Optimized.OUT
+
+    def describe(self) -> str:
+        """``ptr = 0x0``, or ``ptr = <optimized out>``."""
+        value = self.value.value if isinstance(self.value, Optimized) else self.value
+        return f"{self.name} = {value}"
+
+
+class AccessKind(str, Enum):
+    """What a faulting instruction did to the fault address, when the platform records it."""
+
+    READ = "read"
+    WRITE = "write"
+    # A jump into non-executable memory (DEP / NX).
+    EXECUTE = "execute"
+    UNKNOWN = "unknown"
+
+    @property
+    def label(self) -> str:
+        return _ACCESS_LABELS[self]
+
+
+_ACCESS_LABELS = {
+    AccessKind.READ: "read",
+    AccessKind.WRITE: "write",
+    AccessKind.EXECUTE: "execute (non-executable memory)",
+    AccessKind.UNKNOWN: "unknown access",
+}
+
+
+@dataclass
+class StopInfo:
+    """Why the target stopped (signal, exit, or nothing observed)."""
+
+    signal: Optional[str] = None
+    description: str = ""
+    fault_address: Optional[int] = None
+    exit_code: Optional[int] = None
+    # Native exception code when the platform has one (Windows NTSTATUS, Mach EXC_*).
+    exception_code: Optional[int] = None
+    # Read, write, or execute: from a Windows access violation's parameters, or the faulting instruction.
+    access: AccessKind = AccessKind.UNKNOWN
+    # Linux ``siginfo_t.si_code`` (SEGV_MAPERR, SI_TKILL, ...), when the core or debugger records it.
+    si_code: Optional[int] = None
+    # The target was still running at the time limit and was killed.
+    timed_out: bool = False
+    # Signals handed to the program's own handlers before this stop, in order (see `AnalyzeRequest.ignore_signals`).
+    handled_signals: List[str] = field(default_factory=list)
+
+    @property
+    def crashed(self) -> bool:
+        return self.signal is not None
+
+    @property
+    def known_fault_address(self) -> Optional[int]:
+        """`fault_address`, or None when it means nothing: a ``SI_KERNEL`` fault reports 0."""
+        return self.fault_address if self.si_code != SI_KERNEL else None
+
+
+class Language(str, Enum):
+    """Source language of the target, as far as `detect_language` can tell."""
+
+    C = "c"
+    CPP = "cpp"
+    RUST = "rust"
+    GO = "go"
+    PYTHON = "python"
+    JAVA = "java"
+    JAVASCRIPT = "javascript"
+    UNKNOWN = "unknown"
+
+    @property
+    def label(self) -> str:
+        return _LANGUAGE_LABELS[self]
+
+
+_LANGUAGE_LABELS = {
+    Language.C: "C",
+    Language.CPP: "C++",
+    Language.RUST: "Rust",
+    Language.GO: "Go",
+    Language.PYTHON: "Python",
+    Language.JAVA: "Java",
+    Language.JAVASCRIPT: "JavaScript",
+    Language.UNKNOWN: "unknown",
+}
+
+
+_STACK_POINTER_NAMES = ("rsp", "sp", "esp")
+_INSTRUCTION_POINTER_NAMES = ("rip", "pc", "eip")
+
+
+@dataclass
+class PanicInfo:
+    """What a Rust panic said and where it was raised; see `dbgcopilot.analysis.panic`."""
+
+    # ``called `Option::unwrap()` on a `None` value``; None when neither the output nor the stack tells.
+    message: Optional[str] = None
+    # ``src/main.rs:31:37`` as the runtime prints it, or the first user frame's ``file:line``.
+    location: Optional[str] = None
+
+    def describe(self) -> str:
+        """``Panic: called `Option::unwrap()` on a `None` value at src/main.rs:31:37``."""
+        line = f"Panic: {self.message or 'no message captured'}"
+        return line + (f" at {self.location}" if self.location else "")
+
+
+@dataclass
+class CrashContext:
+    """Everything known about a crash: stop reason, faulting stack, registers."""
+
+    stop: StopInfo
+    frames: List[Frame] = field(default_factory=list)
+    registers: List[Register] = field(default_factory=list)
+    modules: List[Module] = field(default_factory=list)
+    # Command line (argv[0] first) and environment of the crashed process, when known.
+    args: List[str] = field(default_factory=list)
+    environ: Dict[str, str] = field(default_factory=dict)
+    # What the target is written in; None until `detect_language` has looked.
+    language: Optional[Language] = None
+    # Every thread's stack, the faulting one included, when the capture path has them.
+    threads: List[ThreadBacktrace] = field(default_factory=list)
+    # Heap statistics and corruption seen at the fault; None when the capture path did not ask.
+    heap: Optional["HeapSummary"] = None
+    # Which memory region the fault address is in; None without an address or a map to place it.
+    fault_region: Optional["MemoryRegion"] = None
+    # Registers and the instructions around the faulting pc; None when the capture path has no registers.
+    fault_context: Optional["FaultContext"] = None
+    # The message and location of the Rust panic that aborted the process; None for any other stop.
+    panic: Optional[PanicInfo] = None
+
+    def register(self, name: str) -> Optional[int]:
+        for reg in self.registers:
+            if reg.name == name:
+                return reg.value
+        return None
+
+    def _first_register(self, names: tuple[str, ...]) -> Optional[int]:
+        for name in names:
+            value = self.register(name)
+            if value is not None:
+                return value
+        return None
+
+    @property
+    def stack_pointer(self) -> Optional[int]:
+        return self._first_register(_STACK_POINTER_NAMES)
+
+    @property
+    def instruction_pointer(self) -> Optional[int]:
+        value = self._first_register(_INSTRUCTION_POINTER_NAMES)
+        if value is None and self.frames:
+            return self.frames[0].pc
+        return value
@@ -0,0 +1,112 @@
+"""Module versions, and the same library loaded twice from different places.
+
+A crash inside a third-party DLL or ``.so`` is often a version mismatch: the
+program was built against one copy and loads another, or two copies end up
+in the same process. A process usually cannot load the same file twice, so
+the trouble shows up as the same *logical* module at two paths.
+
+`module_version` reads what the file name says. That is the soname version
+of an ELF library (``libssl.so.1.1`` is 1.1, ``libc.so.6`` is 6), the release
+in ``libc-2.31.so``, or ``libz.1.2.11.dylib``'s 1.2.11 on macOS. Minidumps
+carry the real file version and link timestamp of each image (see
+`dbgcopilot.dumps.minidump`), and those take precedence.
+
+`find_module_conflicts` groups modules by `logical_name`. That is the file
+name without its version and, on Windows, without case, so ``libssl.so.1.1``
+and ``libssl.so.3`` are both ``libssl.so``. It reports each group loaded from
+more than one path.
+"""
+from __future__ import annotations
+
+import re
+from dataclasses import dataclass, field
+from pathlib import PureWindowsPath
+from typing import Any, Dict, Iterable, List, Optional, Set
+
+from .model import Module
+
+# libssl.so.1.1, libc.so.6
+_SONAME_RE = re.compile(r"^(?P<base>.+?\.so)\.(?P<version>\d+(?:\.\d+)*)$")
+# libc-2.31.so, libstdc++-6.0.30.so
+_RELEASE_RE = re.compile(r"^(?P<base>.+?)-(?P<version>\d+(?:\.\d+)+)\.so$")
+# libz.1.2.11.dylib
+_DYLIB_RE = re.compile(r"^(?P<base>[^.]+)\.(?P<version>\d+(?:\.\d+)*)\.dylib$")
+_VERSIONED = (_SONAME_RE, _RELEASE_RE, _DYLIB_RE)
+
+
+def _basename(module: Module) -> str:
+    # PureWindowsPath splits on both separators, so it names POSIX and Windows paths alike.
+    return PureWindowsPath(module.path).name if module.path else module.name
+
+
+def module_version(name_or_path: str) -> Optional[str]:
+    """The version in a library's file name, e.g. ``1.1`` for ``/usr/lib/libssl.so.1.1``; None if it has none."""
+    name = PureWindowsPath(name_or_path).name
+    for pattern in _VERSIONED:
+        m = pattern.match(name)
+        if m:
+            return m.group("version")
+    return None
+
+
+def logical_name(module: Module) -> str:
+    """The file name without its version (``libssl.so``), lowercased for Windows images."""
+    name = _basename(module)
+    for pattern in _VERSIONED:
+        m = pattern.match(name)
+        if m:
+            suffix = ".dylib" if pattern is _DYLIB_RE else ""
+            name = m.group("base") + suffix
+            break
+    if name.lower().endswith((".dll", ".exe", ".sys")):
+        name = name.lower()
+    return name
+
+
+@dataclass
+class ModuleConflict:
+    """One logical module loaded from more than one path."""
+
+    name: str
+    modules: List[Module] = field(default_factory=list)
+
+    def describe(self) -> str:
+        copies = ", ".join(
+            (m.path or m.name) + (f" ({m.version})" if m.version else "") for m in self.modules
+        )
+        return f"{self.name} is loaded from {len(self.modules)} paths: {copies}"
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {
+            "name": self.name,
+            "paths": [m.path or m.name for m in self.modules],
+            "versions": [m.version for m in self.modules],
+            "warning": self.describe(),
+        }
+
+
+def find_module_conflicts(modules: Iterable[Module]) -> List[ModuleConflict]:
+    """Logical modules loaded from two or more distinct paths, in load order; see the module docstring."""
+    groups: Dict[str, ModuleConflict] = {}
+    seen: Dict[str, Set[str]] = {}
+    for module in modules:
+        # Pseudo-images such as [vdso] and [heap] are not files.
+        if _basename(module).startswith("["):
+            continue
+        key = logical_name(module)
+        location = module.path or module.name
+        if key.endswith((".dll", ".exe", ".sys")):
+            location = location.lower()
+        if location in seen.setdefault(key, set()):
+            continue
+        seen[key].add(location)
+        groups.setdefault(key, ModuleConflict(key)).modules.append(module)
+    return [g for g in groups.values() if len(g.modules) > 1]
+
+
+__all__ = [
+    "ModuleConflict",
+    "find_module_conflicts",
+    "logical_name",
+    "module_version",
+]
@@ -0,0 +1,157 @@
+"""Rust panics, told apart from hard faults.
+
+A `panic!` or an ``unwrap()`` on `None` under ``panic = "abort"`` ends in
+SIGABRT (a fail-fast exception on Windows), like ``abort()`` in C. What
+actually happened is in the panic message, so "SIGABRT in raise" is the
+least useful way to describe it. `find_panic` recognizes the panic path on the
+faulting stack (`core::panicking`, `rust_begin_unwind`, `rust_panic`, the
+``unwrap``/``expect`` failure paths) and returns a `PanicInfo`.
+
+The message and location come from what the runtime printed,
+``thread 'main' panicked at src/main.rs:31:37:`` and the message on the next
+line (or, before Rust 1.73, ``panicked at 'message', src/main.rs:31:37``),
+when the capture path has the program's output: a launch under a debugger
+does. A core has no output, so the stack stands in for it. The failure helper
+names the message for ``unwrap()`` and bounds checks, and the first user frame
+below the panic machinery (see `FrameRules`) is the location. An unwinding
+panic that exits with status 101 is found from the output alone.
+
+`classify_fault` labels such a stop `FaultKind.RUST_PANIC`, and its hint leads
+with the message instead of the signal. The prompt adds `PANIC_HINT`, so the
+model explains the panic rather than the abort.
+"""
+from __future__ import annotations
+
+import re
+from typing import Iterable, Optional, Sequence
+
+from dbgcopilot.utils.io import strip_ansi
+
+from .frames import FrameRules
+from .model import CrashContext, Frame, PanicInfo
+
+# The exit status of a Rust program whose main thread panicked and unwound.
+PANIC_EXIT_STATUS = 101
+# Frames from the top searched for the panic path; the hook, std, and libc's abort sit above it.
+PANIC_FRAMES = 24
+# Guidance the prompt adds for a `FaultKind.RUST_PANIC`.
+PANIC_HINT = (
+    "This is a Rust panic, not a memory fault: explain it from the panic message and location. "
+    "The SIGABRT is only how the runtime stopped the process."
+)
+
+# Symbols of the panic path, demangled or (legacy) mangled. Not all of ``std::panicking``:
+# ``std::panicking::try`` is below ``main`` on every Rust stack.
+_PANIC_SYMBOLS = ("rust_panic", "rust_begin_unwind", "__rust_start_panic")
+_PANIC_PREFIXES = (
+    "core::panicking::",
+    "std::panicking::begin_panic",
+    "std::panicking::rust_panic",
+    "std::panicking::default_hook",
+    "panic_abort::",
+    "core::option::unwrap_failed",
+    "core::option::expect_failed",
+    "core::result::unwrap_failed",
+    "_ZN4core9panicking",
+    "_ZN3std9panicking11begin_panic",
+    "_ZN3std9panicking19begin_panic_handler",
+    "_ZN3std9panicking20rust_panic_with_hook",
+    "_ZN4core6option13unwrap_failed",
+    "_ZN4core6option13expect_failed",
+    "_ZN4core6result13unwrap_failed",
+)
+# What the runtime prints for the helpers whose message is fixed.
+_HELPER_MESSAGES = (
+    ("option::unwrap_failed", "called `Option::unwrap()` on a `None` value"),
+    ("option13unwrap_failed", "called `Option::unwrap()` on a `None` value"),
+    ("result::unwrap_failed", "called `Result::unwrap()` on an `Err` value"),
+    ("result13unwrap_failed", "called `Result::unwrap()` on an `Err` value"),
+    ("panic_bounds_check", "index out of bounds"),
+)
+
+_PANICKED_RE = re.compile(r"^thread '(?P<thread>[^']*)'(?: \(\d+\))? panicked at (?P<rest>.+)$")
+# Before Rust 1.73: panicked at 'message', src/main.rs:31:37
+_QUOTED_RE = re.compile(r"^'(?P<message>.*)', (?P<location>\S+:\d+(?::\d+)?)$")
+# What ends a multi-line message.
+_MESSAGE_END = ("note: ", "stack backtrace:", "thread '")
+
+
+def is_panic_frame(function: Optional[str]) -> bool:
+    """Whether ``function`` is part of Rust's panic machinery."""
+    name = function or ""
+    return name in _PANIC_SYMBOLS or name.startswith(_PANIC_PREFIXES)
+
+
+def panic_frame(frames: Sequence[Frame]) -> Optional[Frame]:
+    """The outermost panic-machinery frame among the top `PANIC_FRAMES`: the one user code called."""
+    found = [f for f in frames[:PANIC_FRAMES] if is_panic_frame(f.function)]
+    return found[-1] if found else None
+
+
+def parse_panic_output(text: str) -> Optional[PanicInfo]:
+    """The last panic the runtime printed in ``text``, or None."""
+    lines = strip_ansi(text or "").splitlines()
+    found: Optional[PanicInfo] = None
+    for i, line in enumerate(lines):
+        m = _PANICKED_RE.match(line.strip())
+        if not m:
+            continue
+        rest = m.group("rest").strip()
+        quoted = _QUOTED_RE.match(rest)
+        if quoted:
+            found = PanicInfo(quoted.group("message"), quoted.group("location"))
+            continue
+        message = []
+        for follow in lines[i + 1:]:
+            if not follow.strip() or follow.startswith(_MESSAGE_END):
+                break
+            message.append(follow.rstrip())
+        found = PanicInfo("\n".join(message) or None, rest.rstrip(":") or None)
+    return found
+
+
+def _helper_message(frames: Iterable[Frame]) -> Optional[str]:
+    for frame in frames:
+        name = frame.function or ""
+        message = next((text for marker, text in _HELPER_MESSAGES if marker in name), None)
+        if message is not None:
+            return message
+    return None
+
+
+def _user_location(frames: Sequence[Frame], below: Frame, rules: FrameRules) -> Optional[str]:
+    caller = next((f for f in frames if f.index > below.index and rules.is_user(f)), None)
+    if caller is None or caller.file is None:
+        return None
+    return f"{caller.file}:{caller.line}" if caller.line is not None else caller.file
+
+
+def find_panic(
+    ctx: CrashContext, output: str = "", rules: Optional[FrameRules] = None
+) -> Optional[PanicInfo]:
+    """The Rust panic behind ``ctx``, from the panic path on its stack and the program's ``output``.
+
+    None when the stack shows no panic path, unless the process exited with
+    `PANIC_EXIT_STATUS` after printing one.
+    """
+    printed = parse_panic_output(output)
+    machinery = panic_frame(ctx.frames)
+    if machinery is None:
+        unwound = printed is not None and not ctx.stop.signal and ctx.stop.exit_code == PANIC_EXIT_STATUS
+        return printed if unwound else None
+    printed = printed or PanicInfo()
+    return PanicInfo(
+        message=printed.message or _helper_message(ctx.frames[:PANIC_FRAMES]),
+        location=printed.location or _user_location(ctx.frames, machinery, rules or FrameRules()),
+    )
+
+
+__all__ = [
+    "PANIC_EXIT_STATUS",
+    "PANIC_FRAMES",
+    "PANIC_HINT",
+    "find_panic",
+    "is_panic_frame",
+    "panic_frame",
+    "parse_panic_output",
+]
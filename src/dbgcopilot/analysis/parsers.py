@@ -0,0 +1,762 @@
+"""Parsers that turn GDB/LLDB/CDB text output into normalized model objects."""
+from __future__ import annotations
+
+import re
+from typing import Dict, List, Optional, Union
+
+from dbgcopilot.utils.io import strip_ansi
+
+from .demangle import demangle_frames
+from .model import AccessKind, Frame, Instruction, Module, Optimized, Register, StopInfo, ThreadBacktrace, Variable
+from .regions import MapEntry
+
+
+# #1  0x000055555555911b in rust_crash::main () at src/main.rs:2
+# #3  0x00007ffff7e1b4e0 in ?? () from /lib/x86_64-linux-gnu/libc.so.6
+_GDB_FRAME_RE = re.compile(
+    r"^#(?P<index>\d+)\s+"
+    r"(?:(?P<pc>0x[0-9a-fA-F]+) in )?"
+    r"(?P<func>.+?) \((?P<args>.*?)\)"
+    r"(?: at (?P<file>.+?):(?P<line>\d+))?"
+    r"(?: from (?P<module>.+))?\s*$"
+)
+
+#   frame #0: 0x0000555555559157 rust_crash`rust_crash::crash::h0e8f at main.rs:9:9
+#   frame #2: 0x00007ffff7c29d90 libc.so.6`__libc_start_call_main + 128
+_LLDB_FRAME_RE = re.compile(
+    r"^\s*\*?\s*frame #(?P<index>\d+):\s+(?P<pc>0x[0-9a-fA-F]+)"
+    r"(?:\s+(?P<module>[^`\s]+)`(?P<func>.+?))?"
+    r"(?:\s+\+\s+(?P<offset>\d+))?"
+    r"(?:\s+at\s+(?P<file>[^\s:]+):(?P<line>\d+)(?::(?P<column>\d+))?)?\s*$"
+)
+
+_LLDB_CALL_ARGS_RE = re.compile(r"^(?P<func>[^(]+)\((?P<args>[^()]*=[^()]*)\)$")
+
+# Thread 2 (Thread 0x7ffff7d8a640 (LWP 1235) "worker-a"):
+# Thread 1 (process 4242):
+_GDB_THREAD_RE = re.compile(r"^Thread (?P<num>\d+) \((?P<desc>.*)\):\s*$")
+_GDB_THREAD_TID_RE = re.compile(r"\b(?:LWP|process) (?P<tid>\d+)")
+_GDB_THREAD_NAME_RE = re.compile(r'"(?P<name>[^"]*)"')
+
+# * thread #1, name = 'rust_deadlock', stop reason = signal SIGSTOP
+#   thread #2, tid = 0x0b3d, name = 'worker-a'
+_LLDB_THREAD_RE = re.compile(r"^(?P<selected>\*)?\s*thread #(?P<num>\d+)(?P<rest>.*)$")
+_LLDB_THREAD_TID_RE = re.compile(r"\btid = (?P<tid>0x[0-9a-fA-F]+|\d+)")
+_LLDB_THREAD_NAME_RE = re.compile(r"\bname = '(?P<name>[^']*)'")
+
+_GDB_REGISTER_RE = re.compile(r"^(?P<name>[a-z][a-z0-9_]*)\s+(?P<value>0x[0-9a-fA-F]+)\b")
+_LLDB_REGISTER_RE = re.compile(r"^\s*(?P<name>[a-z][a-z0-9_]*)\s+=\s+(?P<value>0x[0-9a-fA-F]+)\b")
+
+# Program received signal SIGSEGV, Segmentation fault.
+# Thread 2 "worker-a" received signal SIGSEGV, Segmentation fault.   (any program with threads)
+_GDB_SIGNAL_RE = re.compile(
+    r'(?:Program|Thread \d+(?: "[^"\n]*")?) (?:received|terminated with) '
+    r"signal (?P<sig>SIG[A-Z0-9]+), (?P<desc>[^.\n]+)"
+)
+_GDB_EXIT_RE = re.compile(r"\[Inferior \d+ \(process \d+\) exited (?:normally|with code (?P<code>[0-7]+))\]")
+_GDB_SIGADDR_RE = re.compile(r"^\$\d+ = \(void \*\) (?P<addr>0x[0-9a-fA-F]+)", re.MULTILINE)
+# printf "si_code=%d\n", $_siginfo.si_code
+_GDB_SICODE_RE = re.compile(r"^si_code=(?P<code>-?\d+)\s*$", re.MULTILINE)
+# x/i $pc  ->  => 0x555555559157 <_ZN10rust_crash5crash17h0e8fE+7>:	movl   $0x2a,(%rax)
+_GDB_PC_INSN_RE = re.compile(r"^=> 0x[0-9a-fA-F]+(?: <[^>\n]*>)?:\s+(?P<insn>.+?)\s*$", re.MULTILINE)
+# disassemble / x/6i $pc (gdb), disassemble --frame (lldb); the arrow marks the pc:
+# => 0x0000555555559157 <+7>:	movl   $0x2a,(%rax)
+#    0x55555555915d <_ZN10rust_crash5crash17h0e8fE+13>:	pop    %rax
+# ->  0x100003f6c <+12>: movl   $0x2a, (%rax)
+_DISASSEMBLY_RE = re.compile(
+    r"^(?P<arrow>=>|->)?\s*(?P<addr>0x[0-9a-fA-F]+)(?: <(?P<loc>[^>\n]*)>)?:\s+(?P<insn>\S.*?)\s*$"
+)
+# u / ub (cdb): address, opcode bytes, then the instruction.
+# 00007ff6`1a2b1100 c7002a000000    mov     dword ptr [rax],2Ah
+_CDB_DISASSEMBLY_RE = re.compile(r"^(?P<addr>[0-9a-fA-F]{8}(?:`[0-9a-fA-F]{8})?)\s+[0-9a-fA-F]+\s+(?P<insn>\S.*?)\s*$")
+# objdump -d --no-show-raw-insn -w: address, then the instruction.
+#   401136:	movl   $0x2a,(%rax)
+_OBJDUMP_DISASSEMBLY_RE = re.compile(r"^(?P<addr>[0-9a-fA-F]+):\s+(?P<insn>\S.*?)\s*$")
+# Mnemonics whose memory operand is only read, wherever it sits.
+_READ_ONLY_MNEMONICS = ("cmp", "test", "bt", "push", "call", "jmp", "prefetch")
+
+_LLDB_STOP_RE = re.compile(r"stop reason = (?P<reason>[^\n]+)")
+_LLDB_EXIT_RE = re.compile(r"Process \d+ exited with status = (?P<code>-?\d+)")
+_LLDB_FAULT_ADDR_RE = re.compile(r"(?:fault address|address)[:=]\s*(?P<addr>0x[0-9a-fA-F]+)")
+
+#  # Child-SP          RetAddr               Call Site
+# 00 000000a0`000ff6b8 00007ff6`1a2b1045     crash!crash_demo::crash+0x1a [C:\src\main.cpp @ 11]
+# 03 000000a0`000ff760 00000000`00000000     ntdll!RtlUserThreadStart+0x28
+_CDB_FRAME_RE = re.compile(
+    r"^(?P<index>[0-9a-fA-F]{2,})\s+(?P<sp>[0-9a-fA-F`]+)\s+(?P<ret>[0-9a-fA-F`]+)\s+"
+    r"(?P<site>\S+)(?:\s+\[(?P<file>.+?) @ (?P<line>\d+)\])?\s*$"
+)
+_CDB_SITE_RE = re.compile(r"^(?:(?P<module>[^!+\s]+)!)?(?P<func>[^+]+?)(?:\+0x(?P<offset>[0-9a-fA-F]+))?$")
+_CDB_REGISTER_RE = re.compile(r"\b(?P<name>[a-z][a-z0-9]*)=(?P<value>[0-9a-fA-F]{8,16})\b")
+_CDB_EXC_CODE_RE = re.compile(r"ExceptionCode:\s+(?P<code>[0-9a-fA-F]{8})(?:\s+\((?P<desc>[^)]*)\))?")
+_CDB_EXC_ADDR_RE = re.compile(r"ExceptionAddress:\s+(?P<addr>[0-9a-fA-F`]+)")
+#    Parameter[0]: 0000000000000001
+_CDB_EXC_PARAM_RE = re.compile(r"Parameter\[(?P<index>\d+)\]:\s+(?P<value>[0-9a-fA-F`]+)")
+_CDB_ACCESS_KINDS = {
+    "read from": AccessKind.READ,
+    "write to": AccessKind.WRITE,
+    "execute non-executable": AccessKind.EXECUTE,
+}
+_CDB_ACCESS_RE = re.compile(r"Attempt to (?P<access>read from|write to|execute non-executable) address (?P<addr>[0-9a-fA-F`]+)")
+
+# 0x00007ffff7fc5090  0x00007ffff7fee315  Yes         /lib64/ld-linux-x86-64.so.2
+_GDB_SHLIB_RE = re.compile(
+    r"^(?P<start>0x[0-9a-fA-F]+)\s+(?P<end>0x[0-9a-fA-F]+)\s+(?:Yes|No)(?: \(\*\))?\s+(?P<path>\S.*?)\s*$"
+)
+#       0x555555554000     0x555555559000     0x5000        0x0  r--p   /work/examples/bin/rust/crash
+# (GDB 12 and later print the Perms column; older versions do not.)
+_GDB_MAPPING_RE = re.compile(
+    r"^(?P<start>0x[0-9a-fA-F]+)\s+(?P<end>0x[0-9a-fA-F]+)\s+0x[0-9a-fA-F]+\s+(?P<offset>0x[0-9a-fA-F]+)"
+    r"(?:\s+[rwxsp-]{4})?\s+(?P<path>/\S.*?)\s*$"
+)
+# The same lines, anonymous and [heap]/[stack] ones included.
+_GDB_MAP_ENTRY_RE = re.compile(
+    r"^(?P<start>0x[0-9a-fA-F]+)\s+(?P<end>0x[0-9a-fA-F]+)\s+0x[0-9a-fA-F]+\s+(?P<offset>0x[0-9a-fA-F]+)"
+    r"(?:\s+(?P<perms>[rwxsp-]{4}))?(?:\s+(?P<path>\S.*?))?\s*$"
+)
+# * 1    Thread 0x7ffff7d8a740 (LWP 12345) "rust_crash" rust_crash::crash () at src/main.rs:9
+_GDB_CURRENT_THREAD_RE = re.compile(r"^\*\s+(?P<num>\d+)\s+(?P<desc>.*)$")
+# [  0] 3F1C2B7A-...-0A1B 0x0000555555554000 /work/examples/bin/rust/crash
+_LLDB_IMAGE_RE = re.compile(r"^\[\s*\d+\]\s+(?:\S+\s+)?(?P<base>0x[0-9a-fA-F]+)\s+(?P<path>\S.*?)\s*$")
+# 00007ff6`1a2b0000 00007ff6`1a2d5000   crash      (private pdb symbols)  C:\src\crash.pdb
+_CDB_MODULE_RE = re.compile(r"^(?P<start>[0-9a-fA-F`]{8,})\s+(?P<end>[0-9a-fA-F`]{8,})\s+(?P<name>[^\s(]+)")
+# .  0  Id: 1a2c.3b4c Suspend: 1 Teb: 000000a0`00123000 Unfrozen
+_CDB_CURRENT_THREAD_RE = re.compile(r"^[.#]\s+\d+\s+Id:\s+[0-9a-fA-F]+\.(?P<tid>[0-9a-fA-F]+)")
+# 0x555555558048 <lock_a+8>:	0x4a	0x14	0x00	0x00        (gdb x/Nxb)
+# 0x555555558048: 0x4a 0x14 0x00 0x00                           (lldb memory read)
+_HEX_BYTE_RE = re.compile(r"\b0x([0-9a-fA-F]{2})\b")
+# 00005555`55558048  4a 14 00 00 00 00 00 00-00 00 00 00 00 00 00 00  J...............
+_CDB_MEMORY_RE = re.compile(r"^[0-9a-fA-F`]{8,}\s+(?P<bytes>[0-9a-fA-F?]{2}(?:[ -][0-9a-fA-F?]{2})*)")
+#    1  Id: 1a2c.2d10 Suspend: 1 Teb: 000000a0`00125000 Unfrozen "worker_one"
+# "." marks the current thread, "#" the one that raised the last event when it is another.
+_CDB_THREAD_RE = re.compile(
+    r"^(?P<mark>[.#])?\s*(?P<num>\d+)\s+Id:\s+[0-9a-fA-F]+\.(?P<tid>[0-9a-fA-F]+)\b(?P<rest>.*)$"
+)
+_CDB_THREAD_NAME_RE = re.compile(r'"(?P<name>[^"]*)"\s*$')
+# CritSec app!lock_a+0 at 00007ff6`1a2d8040   (cdb !locks; OwningThread follows, as a hex OS tid)
+_CDB_CRITSEC_RE = re.compile(r"^CritSec\s+\S+\s+at\s+(?P<addr>[0-9a-fA-F`]+)")
+_CDB_OWNING_THREAD_RE = re.compile(r"^OwningThread\s+(?P<tid>[0-9a-fA-F]+)\s*$")
+
+# ptr = 0x0                                  (gdb info locals / info args; members indented below)
+_GDB_VARIABLE_RE = re.compile(r"^(?P<name>[^\s=][^=]*?) = (?P<value>.*)$")
+# type = int *                               (gdb whatis)
+_GDB_WHATIS_RE = re.compile(r"^type = (?P<type>.+?)\s*$", re.MULTILINE)
+# prv local  int * p = 0x00000000`00000000   (cdb dv /i /t)
+_CDB_VARIABLE_RE = re.compile(r"^(?:prv|pub)\s+(?P<kind>param|local)\s+(?P<decl>.+?) = (?P<value>.*)$")
+# How each engine says a variable has no location at this pc.
+_OPTIMIZED_VALUE_RE = re.compile(r"^<(?:optimized out|variable not available|value unavailable|no location[^>]*)>$")
+_SCALAR_HEX_RE = re.compile(r"^0x[0-9a-fA-F`]+$")
+
+_MACH_EXCEPTION_SIGNALS = {
+    "EXC_BAD_ACCESS": "SIGSEGV",
+    "EXC_BAD_INSTRUCTION": "SIGILL",
+    "EXC_ARITHMETIC": "SIGFPE",
+    "EXC_CRASH": "SIGABRT",
+}
+
+
+def _module_basename(path: Optional[str]) -> Optional[str]:
+    if not path:
+        return None
+    return re.split(r"[\\/]", path.strip())[-1] or None
+
+
+def parse_gdb_backtrace(text: str) -> List[Frame]:
+    frames: List[Frame] = []
+    for raw in strip_ansi(text or "").splitlines():
+        m = _GDB_FRAME_RE.match(raw.strip())
+        if not m:
+            continue
+        func = m.group("func").strip()
+        frames.append(
+            Frame(
+                index=int(m.group("index")),
+                pc=int(m.group("pc"), 16) if m.group("pc") else None,
+                function=None if func == "??" else func,
+                module=_module_basename(m.group("module")),
+                file=m.group("file"),
+                line=int(m.group("line")) if m.group("line") else None,
+                args=m.group("args").strip() or None,
+            )
+        )
+    return demangle_frames(frames)
+
+
+def parse_lldb_backtrace(text: str) -> List[Frame]:
+    frames: List[Frame] = []
+    for raw in strip_ansi(text or "").splitlines():
+        m = _LLDB_FRAME_RE.match(raw)
+        if not m:
+            continue
+        func, args = m.group("func"), None
+        # With debug info LLDB prints C arguments inline: ``___pthread_mutex_lock(mutex=0x...)``.
+        call = _LLDB_CALL_ARGS_RE.match(func or "")
+        if call:
+            func, args = call.group("func"), call.group("args")
+        frames.append(
+            Frame(
+                index=int(m.group("index")),
+                pc=int(m.group("pc"), 16),
+                function=func,
+                module=m.group("module"),
+                file=m.group("file"),
+                line=int(m.group("line")) if m.group("line") else None,
+                column=int(m.group("column")) if m.group("column") else None,
+                offset=int(m.group("offset")) if m.group("offset") else None,
+                args=args,
+            )
+        )
+    return demangle_frames(frames)
+
+
+def _split_threads(text: str, header_re: re.Pattern[str]) -> List[tuple[re.Match[str], List[str]]]:
+    blocks: List[tuple[re.Match[str], List[str]]] = []
+    for raw in strip_ansi(text or "").splitlines():
+        m = header_re.match(raw.strip())
+        if m:
+            blocks.append((m, []))
+        elif blocks:
+            blocks[-1][1].append(raw)
+    return blocks
+
+
+def _cdb_hex(value: str) -> int:
+    return int(value.replace("`", ""), 16)
+
+
+def parse_cdb_backtrace(text: str, ip: Optional[int] = None) -> List[Frame]:
+    """Parse CDB/WinDbg ``kn`` output.
+
+    ``kn`` lists each frame's return address, not its own pc, so frame N's pc is
+    taken from frame N-1's ``RetAddr``; frame 0 uses ``ip`` when given.
+    """
+    frames: List[Frame] = []
+    prev_ret: Optional[int] = ip
+    for raw in strip_ansi(text or "").splitlines():
+        m = _CDB_FRAME_RE.match(raw.strip())
+        if not m:
+            continue
+        site = m.group("site")
+        sm = _CDB_SITE_RE.match(site)
+        module = sm.group("module") if sm else None
+        func = sm.group("func") if sm else site
+        offset = int(sm.group("offset"), 16) if sm and sm.group("offset") else None
+        if sm and not module:
+            # "crash+0x1045" (no symbols): the name is the module itself.
+            module, func, offset = func, None, None
+        frames.append(
+            Frame(
+                index=int(m.group("index"), 16),
+                pc=prev_ret,
+                function=func,
+                module=module,
+                file=m.group("file"),
+                line=int(m.group("line")) if m.group("line") else None,
+                offset=offset,
+            )
+        )
+        prev_ret = _cdb_hex(m.group("ret"))
+    return demangle_frames(frames)
+
+
+def parse_cdb_registers(text: str) -> List[Register]:
+    """Parse ``r`` output (``rax=... rbx=...`` several per line)."""
+    regs: List[Register] = []
+    for raw in strip_ansi(text or "").splitlines():
+        for m in _CDB_REGISTER_RE.finditer(raw):
+            regs.append(Register(name=m.group("name"), value=int(m.group("value"), 16)))
+    return regs
+
+
+def parse_cdb_exception(text: str) -> StopInfo:
+    """Parse ``.exr -1`` output into a StopInfo with the NTSTATUS mapped to a signal."""
+    from dbgcopilot.dumps.minidump import WINDOWS_EXCEPTIONS, decode_access_violation
+
+    plain = strip_ansi(text or "")
+    m = _CDB_EXC_CODE_RE.search(plain)
+    if not m:
+        return StopInfo(description="no exception record")
+    code = int(m.group("code"), 16)
+    name, signal = WINDOWS_EXCEPTIONS.get(code, (f"0x{code:08X}", None))
+    description = name
+    if m.group("desc"):
+        description += f" ({m.group('desc')})"
+    fault_address: Optional[int] = None
+    addr = _CDB_EXC_ADDR_RE.search(plain)
+    if addr:
+        fault_address = _cdb_hex(addr.group("addr"))
+    parameters = {int(p.group("index")): _cdb_hex(p.group("value")) for p in _CDB_EXC_PARAM_RE.finditer(plain)}
+    kind, target = decode_access_violation(code, [parameters[i] for i in sorted(parameters)])
+    if target is not None:
+        fault_address = target
+    access = _CDB_ACCESS_RE.search(plain)
+    if access:
+        # The "Attempt to ..." line is cdb's own reading of the same parameters; it wins when present.
+        kind = _CDB_ACCESS_KINDS[access.group("access")]
+        fault_address = _cdb_hex(access.group("addr"))
+        description += f": {access.group('access')} 0x{fault_address:x}"
+    return StopInfo(
+        signal=signal, description=description, fault_address=fault_address, exception_code=code, access=kind
+    )
+
+
+def parse_gdb_thread_backtraces(text: str) -> List[ThreadBacktrace]:
+    """Parse `thread apply all bt` output into one entry per thread."""
+    threads: List[ThreadBacktrace] = []
+    for header, lines in _split_threads(text, _GDB_THREAD_RE):
+        desc = header.group("desc")
+        tid = _GDB_THREAD_TID_RE.search(desc)
+        name = _GDB_THREAD_NAME_RE.search(desc)
+        threads.append(
+            ThreadBacktrace(
+                thread_id=int(header.group("num")),
+                tid=int(tid.group("tid")) if tid else None,
+                name=name.group("name") if name else None,
+                frames=parse_gdb_backtrace("\n".join(lines)),
+            )
+        )
+    return threads
+
+
+def parse_lldb_thread_backtraces(text: str) -> List[ThreadBacktrace]:
+    """Parse `thread backtrace all` output into one entry per thread; the ``*`` one is marked faulting."""
+    threads: List[ThreadBacktrace] = []
+    for header, lines in _split_threads(text, _LLDB_THREAD_RE):
+        rest = header.group("rest")
+        tid = _LLDB_THREAD_TID_RE.search(rest)
+        name = _LLDB_THREAD_NAME_RE.search(rest)
+        threads.append(
+            ThreadBacktrace(
+                thread_id=int(header.group("num")),
+                tid=int(tid.group("tid"), 0) if tid else None,
+                name=name.group("name") if name else None,
+                frames=parse_lldb_backtrace("\n".join(lines)),
+                # LLDB selects the thread whose stop reason ended the run.
+                faulting=bool(header.group("selected")),
+            )
+        )
+    return threads
+
+
+def parse_gdb_shared_libraries(text: str) -> List[Module]:
+    """Parse `info sharedlibrary`; the ranges cover each library's ``.text``. Unloaded symbols are skipped."""
+    modules: List[Module] = []
+    for raw in strip_ansi(text or "").splitlines():
+        m = _GDB_SHLIB_RE.match(raw.strip())
+        if m:
+            start, end = int(m.group("start"), 16), int(m.group("end"), 16)
+            path = m.group("path")
+            modules.append(Module(name=_module_basename(path) or path, base=start, size=end - start, path=path))
+    return modules
+
+
+def parse_gdb_proc_mappings(text: str) -> List[Module]:
+    """Parse `info proc mappings` into one module per mapped file, based at its offset-0 mapping.
+
+    Unlike `parse_gdb_shared_libraries` this includes the executable, and
+    ``base`` is where the image itself was loaded.
+    """
+    ranges: dict[str, List[int]] = {}
+    for raw in strip_ansi(text or "").splitlines():
+        m = _GDB_MAPPING_RE.match(raw.strip())
+        if not m:
+            continue
+        path = m.group("path")
+        if path.startswith("/dev/") or path.startswith("/memfd:"):
+            continue
+        start, end, offset = int(m.group("start"), 16), int(m.group("end"), 16), int(m.group("offset"), 16)
+        span = ranges.setdefault(path, [start - offset, end])
+        span[0], span[1] = min(span[0], start - offset), max(span[1], end)
+    return [
+        Module(name=_module_basename(path) or path, base=base, size=end - base, path=path)
+        for path, (base, end) in ranges.items()
+    ]
+
+
+def parse_gdb_memory_map(text: str) -> List[MapEntry]:
+    """Parse `info proc mappings` into every mapped range, in address order."""
+    entries: List[MapEntry] = []
+    for raw in strip_ansi(text or "").splitlines():
+        m = _GDB_MAP_ENTRY_RE.match(raw.strip())
+        if m:
+            entries.append(
+                MapEntry(
+                    start=int(m.group("start"), 16),
+                    end=int(m.group("end"), 16),
+                    perms=m.group("perms"),
+                    path=m.group("path"),
+                    offset=int(m.group("offset"), 16),
+                )
+            )
+    return sorted(entries, key=lambda e: e.start)
+
+
+def parse_lldb_image_list(text: str) -> List[Module]:
+    """Parse `image list`. LLDB prints only load addresses, so ``size`` is 0."""
+    modules: List[Module] = []
+    for raw in strip_ansi(text or "").splitlines():
+        m = _LLDB_IMAGE_RE.match(raw.strip())
+        if m:
+            path = m.group("path")
+            modules.append(Module(name=_module_basename(path) or path, base=int(m.group("base"), 16), size=0, path=path))
+    return modules
+
+
+def parse_cdb_modules(text: str) -> List[Module]:
+    """Parse `lm` output. ``lm`` names modules without their image paths."""
+    modules: List[Module] = []
+    for raw in strip_ansi(text or "").splitlines():
+        m = _CDB_MODULE_RE.match(raw.strip())
+        if m:
+            start = _cdb_hex(m.group("start"))
+            modules.append(Module(name=m.group("name"), base=start, size=_cdb_hex(m.group("end")) - start))
+    return modules
+
+
+def parse_gdb_current_tid(text: str) -> Optional[int]:
+    """OS thread id of the ``*`` row in `info threads`."""
+    for raw in strip_ansi(text or "").splitlines():
+        m = _GDB_CURRENT_THREAD_RE.match(raw.strip())
+        if m:
+            tid = _GDB_THREAD_TID_RE.search(m.group("desc"))
+            return int(tid.group("tid")) if tid else None
+    return None
+
+
+def parse_gdb_current_thread(text: str) -> Optional[int]:
+    """GDB's number of the ``*`` row in `info threads`: after a stop, the thread that reported it."""
+    for raw in strip_ansi(text or "").splitlines():
+        m = _GDB_CURRENT_THREAD_RE.match(raw.strip())
+        if m:
+            return int(m.group("num"))
+    return None
+
+
+def parse_lldb_current_tid(text: str) -> Optional[int]:
+    """OS thread id LLDB reports in `thread info` (``tid = ...``)."""
+    m = _LLDB_THREAD_TID_RE.search(strip_ansi(text or ""))
+    return int(m.group("tid"), 0) if m else None
+
+
+def parse_cdb_current_tid(text: str) -> Optional[int]:
+    """OS thread id from `~.` output (``Id: <pid>.<tid>``, both hex)."""
+    for raw in strip_ansi(text or "").splitlines():
+        m = _CDB_CURRENT_THREAD_RE.match(raw.strip())
+        if m:
+            return int(m.group("tid"), 16)
+    return None
+
+
+def parse_cdb_thread_backtraces(text: str) -> List[ThreadBacktrace]:
+    """Parse `~*kn` output. Frame 0 of each thread has no pc (``kn`` only lists return addresses).
+
+    The thread that raised the exception is marked faulting: the ``#`` one, or
+    the current (``.``) one when CDB shows no ``#``, as it does when they are the same.
+    """
+    threads: List[ThreadBacktrace] = []
+    marks: List[Optional[str]] = []
+    for header, lines in _split_threads(text, _CDB_THREAD_RE):
+        name = _CDB_THREAD_NAME_RE.search(header.group("rest"))
+        threads.append(
+            ThreadBacktrace(
+                # CDB's own 0-based numbering, the one `~Ns` takes.
+                thread_id=int(header.group("num")),
+                tid=int(header.group("tid"), 16),
+                name=name.group("name") if name else None,
+                frames=parse_cdb_backtrace("\n".join(lines)),
+            )
+        )
+        marks.append(header.group("mark"))
+    event = "#" if "#" in marks else "."
+    for thread, mark in zip(threads, marks):
+        thread.faulting = mark == event
+    return threads
+
+
+def parse_cdb_locks(text: str) -> Dict[int, int]:
+    """Owner OS thread id of each locked critical section in `!locks` output, by address."""
+    owners: Dict[int, int] = {}
+    address: Optional[int] = None
+    for raw in strip_ansi(text or "").splitlines():
+        line = raw.strip()
+        m = _CDB_CRITSEC_RE.match(line)
+        if m:
+            address = _cdb_hex(m.group("addr"))
+            continue
+        m = _CDB_OWNING_THREAD_RE.match(line)
+        if m and address is not None and int(m.group("tid"), 16):
+            owners[address] = int(m.group("tid"), 16)
+    return owners
+
+
+def _parse_hex_dump(text: str) -> Optional[bytes]:
+    data = bytearray()
+    for raw in strip_ansi(text or "").splitlines():
+        head, sep, rest = raw.partition(":")
+        if sep and head.strip().startswith("0x"):
+            data.extend(int(b, 16) for b in _HEX_BYTE_RE.findall(rest))
+    return bytes(data) if data else None
+
+
+def parse_gdb_memory(text: str) -> Optional[bytes]:
+    """Bytes from `x/<n>xb` output; None when GDB could not access the memory."""
+    return _parse_hex_dump(text)
+
+
+def parse_lldb_memory(text: str) -> Optional[bytes]:
+    """Bytes from `memory read --size 1 --format x` output; None on a read error."""
+    return _parse_hex_dump(text)
+
+
+def parse_cdb_memory(text: str) -> Optional[bytes]:
+    """Bytes from `db` output, stopping at the first unreadable (``??``) byte."""
+    data = bytearray()
+    for raw in strip_ansi(text or "").splitlines():
+        m = _CDB_MEMORY_RE.match(raw.strip())
+        if not m:
+            continue
+        for byte in re.split(r"[ -]", m.group("bytes")):
+            if byte == "??":
+                return bytes(data) or None
+            data.append(int(byte, 16))
+    return bytes(data) or None
+
+
+def parse_disassembly(text: str) -> List[Instruction]:
+    """Instructions from gdb's ``disassemble`` or ``x/i`` output, or lldb's ``disassemble``; ``=>``/``->`` is the pc."""
+    found: List[Instruction] = []
+    for raw in strip_ansi(text or "").splitlines():
+        m = _DISASSEMBLY_RE.match(raw.strip())
+        if m:
+            insn = " ".join(m.group("insn").split())
+            found.append(Instruction(int(m.group("addr"), 16), insn, m.group("loc"), faulting=bool(m.group("arrow"))))
+    return found
+
+
+def parse_cdb_disassembly(text: str, ip: Optional[int] = None) -> List[Instruction]:
+    """Instructions from cdb's ``u`` / ``ub`` output; cdb marks no pc, so the one at ``ip`` is the faulting one."""
+    found: List[Instruction] = []
+    for raw in strip_ansi(text or "").splitlines():
+        m = _CDB_DISASSEMBLY_RE.match(raw.strip())
+        if m:
+            address = int(m.group("addr").replace("`", ""), 16)
+            found.append(Instruction(address, " ".join(m.group("insn").split()), faulting=address == ip))
+    return found
+
+
+def parse_objdump_disassembly(text: str, ip: Optional[int] = None) -> List[Instruction]:
+    """Instructions from ``objdump -d --no-show-raw-insn``; the one at ``ip`` is the faulting one."""
+    found: List[Instruction] = []
+    for raw in strip_ansi(text or "").splitlines():
+        m = _OBJDUMP_DISASSEMBLY_RE.match(raw.strip())
+        if m:
+            address = int(m.group("addr"), 16)
+            found.append(Instruction(address, " ".join(m.group("insn").split()), faulting=address == ip))
+    return found
+
+
+def parse_gdb_registers(text: str) -> List[Register]:
+    regs: List[Register] = []
+    for raw in strip_ansi(text or "").splitlines():
+        m = _GDB_REGISTER_RE.match(raw.strip())
+        if m:
+            regs.append(Register(name=m.group("name"), value=int(m.group("value"), 16)))
+    return regs
+
+
+def parse_lldb_registers(text: str) -> List[Register]:
+    regs: List[Register] = []
+    for raw in strip_ansi(text or "").splitlines():
+        m = _LLDB_REGISTER_RE.match(raw)
+        if m:
+            regs.append(Register(name=m.group("name"), value=int(m.group("value"), 16)))
+    return regs
+
+
+def instruction_access(insn: str) -> AccessKind:
+    """Whether a disassembled instruction reads or writes memory, from where its memory operand sits.
+
+    AT&T syntax (gdb's default) puts the destination last, Intel syntax first;
+    ``ld*`` / ``st*`` are Arm's loads and stores. UNKNOWN without a memory operand.
+    """
+    mnemonic, _, rest = insn.strip().partition(" ")
+    mnemonic = mnemonic.lower()
+    operands = [o.strip() for o in re.split(r",(?![^()\[\]]*[)\]])", rest) if o.strip()]
+    memory = [i for i, o in enumerate(operands) if "(" in o or "[" in o]
+    if not memory:
+        return AccessKind.UNKNOWN
+    if mnemonic.startswith(_READ_ONLY_MNEMONICS) or mnemonic.startswith("ld"):
+        return AccessKind.READ
+    if mnemonic.startswith("st"):
+        return AccessKind.WRITE
+    destination = 0 if "[" in operands[memory[0]] else len(operands) - 1
+    return AccessKind.WRITE if destination in memory else AccessKind.READ
+
+
+def parse_gdb_stop(text: str) -> StopInfo:
+    """The stop in gdb's output, with whatever ``$_siginfo`` fields and ``x/i $pc`` follow it."""
+    plain = strip_ansi(text or "")
+    m = _GDB_SIGNAL_RE.search(plain)
+    if m:
+        info = StopInfo(signal=m.group("sig"), description=m.group("desc").strip())
+        addr = _GDB_SIGADDR_RE.search(plain)
+        if addr:
+            info.fault_address = int(addr.group("addr"), 16)
+        code = _GDB_SICODE_RE.search(plain)
+        if code:
+            info.si_code = int(code.group("code"))
+        insn = _GDB_PC_INSN_RE.search(plain)
+        if insn and info.signal in ("SIGSEGV", "SIGBUS"):
+            info.access = instruction_access(insn.group("insn"))
+        return info
+    m = _GDB_EXIT_RE.search(plain)
+    if m:
+        # GDB reports the exit status in octal.
+        code = int(m.group("code"), 8) if m.group("code") else 0
+        return StopInfo(description=f"exited with code {code}", exit_code=code)
+    return StopInfo(description="no stop event observed")
+
+
+def parse_lldb_stop(text: str) -> StopInfo:
+    plain = strip_ansi(text or "")
+    m = _LLDB_STOP_RE.search(plain)
+    if m:
+        reason = m.group("reason").strip()
+        signal: Optional[str] = None
+        sig = re.match(r"signal (SIG[A-Z0-9]+)", reason)
+        if sig:
+            signal = sig.group(1)
+        else:
+            for exc, mapped in _MACH_EXCEPTION_SIGNALS.items():
+                if reason.startswith(exc):
+                    signal = mapped
+                    break
+        if signal:
+            info = StopInfo(signal=signal, description=reason)
+            addr = _LLDB_FAULT_ADDR_RE.search(reason)
+            if addr:
+                info.fault_address = int(addr.group("addr"), 16)
+            return info
+    m = _LLDB_EXIT_RE.search(plain)
+    if m:
+        code = int(m.group("code"))
+        return StopInfo(description=f"exited with code {code}", exit_code=code)
+    return StopInfo(description="no stop event observed")
+
+
+def _variable_value(lines: List[str]) -> Union[str, Optimized]:
+    value = " ".join(part.strip() for part in lines if part.strip())
+    if _OPTIMIZED_VALUE_RE.match(value):
+        return Optimized.OUT
+    # LLDB and CDB pad pointers to the word size; print them the way GDB does (``0x0``).
+    if _SCALAR_HEX_RE.match(value):
+        return f"{_cdb_hex(value[2:]):#x}"
+    if value.startswith("0n") and value[2:].lstrip("-").isdigit():
+        return value[2:]
+    return value
+
+
+def parse_gdb_variables(text: str, argument: bool = False) -> List[Variable]:
+    """Parse `info locals` or `info args` output. Types are not shown; see `parse_gdb_whatis`."""
+    found: List[tuple[str, List[str]]] = []
+    for raw in strip_ansi(text or "").splitlines():
+        m = _GDB_VARIABLE_RE.match(raw) if raw and not raw[0].isspace() and raw[0] != "}" else None
+        if m:
+            found.append((m.group("name"), [m.group("value")]))
+        elif found:
+            found[-1][1].append(raw)
+    return [Variable(name=name, value=_variable_value(lines), argument=argument) for name, lines in found]
+
+
+def parse_gdb_whatis(text: str) -> Optional[str]:
+    """The type in `whatis <expr>` output, or None when GDB could not evaluate it."""
+    m = _GDB_WHATIS_RE.search(strip_ansi(text or ""))
+    return m.group("type") if m else None
+
+
+def _lldb_declaration(line: str) -> Optional[tuple[str, str, str]]:
+    # "(void (*)(int)) handler = 0x..." : the type is the first balanced parenthesis.
+    depth = 0
+    for i, ch in enumerate(line):
+        if ch == "(":
+            depth += 1
+        elif ch == ")":
+            depth -= 1
+            if depth == 0:
+                name, sep, value = line[i + 1:].strip().partition(" = ")
+                return (line[1:i], name, value) if sep and name else None
+    return None
+
+
+def parse_lldb_variables(text: str, argument: bool = False) -> List[Variable]:
+    """Parse `frame variable` output (``(int *) p = 0x0000000000000000``); members are folded into the value."""
+    found: List[tuple[str, str, List[str]]] = []
+    for raw in strip_ansi(text or "").splitlines():
+        decl = _lldb_declaration(raw) if raw.startswith("(") else None
+        if decl:
+            found.append((decl[0], decl[1], [decl[2]]))
+        elif found:
+            found[-1][2].append(raw)
+    return [
+        Variable(name=name, type_name=type_name, value=_variable_value(lines), argument=argument)
+        for type_name, name, lines in found
+    ]
+
+
+def parse_cdb_variables(text: str) -> List[Variable]:
+    """Parse `dv /i /t` output; arguments are the ``param`` rows."""
+    found: List[tuple[re.Match[str], List[str]]] = []
+    for raw in strip_ansi(text or "").splitlines():
+        m = _CDB_VARIABLE_RE.match(raw.strip())
+        if m:
+            found.append((m, [m.group("value")]))
+        elif found:
+            found[-1][1].append(raw)
+    variables: List[Variable] = []
+    for m, lines in found:
+        parts = m.group("decl").rsplit(None, 1)
+        variables.append(
+            Variable(
+                name=parts[-1],
+                type_name=parts[0] if len(parts) > 1 else None,
+                value=_variable_value(lines),
+                argument=m.group("kind") == "param",
+            )
+        )
+    return variables
+
+
+__all__ = [
+    "instruction_access",
+    "parse_cdb_backtrace",
+    "parse_cdb_current_tid",
+    "parse_cdb_disassembly",
+    "parse_cdb_exception",
+    "parse_cdb_locks",
+    "parse_cdb_memory",
+    "parse_cdb_modules",
+    "parse_cdb_registers",
+    "parse_cdb_thread_backtraces",
+    "parse_cdb_variables",
+    "parse_disassembly",
+    "parse_gdb_backtrace",
+    "parse_gdb_current_thread",
+    "parse_gdb_current_tid",
+    "parse_gdb_memory",
+    "parse_gdb_memory_map",
+    "parse_gdb_registers",
+    "parse_gdb_shared_libraries",
+    "parse_gdb_stop",
+    "parse_gdb_thread_backtraces",
+    "parse_gdb_variables",
+    "parse_gdb_whatis",
+    "parse_lldb_backtrace",
+    "parse_lldb_current_tid",
+    "parse_lldb_image_list",
+    "parse_lldb_memory",
+    "parse_lldb_registers",
+    "parse_lldb_stop",
+    "parse_lldb_thread_backtraces",
+    "parse_lldb_variables",
+    "parse_objdump_disassembly",
+]
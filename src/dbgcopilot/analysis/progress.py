@@ -0,0 +1,61 @@
+"""Coarse progress events from `analyze`, for status bars.
+
+Running the target under a debugger and waiting for the model can take tens
+of seconds. An `AnalyzeRequest.progress` sink is called with a
+`ProgressEvent` as each stage starts:
+
+| Stage | When |
+|---|---|
+| `launching_target` | the program starts (again, under the debugger, after it crashes) |
+| `reading_dump` | a core or minidump is opened |
+| `sampling_threads` | a hung or attached process's stacks are sampled |
+| `capturing_backtrace` | the debugger is asked for stacks, registers, and modules |
+| `symbolizing` | frames without a source line are looked up in the images' line tables |
+| `querying_model` | the explanation, or the suggested fix, is asked for |
+| `done` | the report is complete |
+
+Each event's ``message`` is a short sentence ("Running rust_crash under
+gdb"). A stage may be reported more than once, and stages that do not apply
+are skipped; `done` always comes last, unless `analyze` raises. The sink runs
+on the analyzing thread, so it should return quickly. The events are
+advisory: without a sink nothing is emitted and nothing else changes.
+"""
+from __future__ import annotations
+
+from dataclasses import dataclass
+from enum import Enum
+from typing import Callable, Optional
+
+
+class ProgressStage(str, Enum):
+    LAUNCHING_TARGET = "launching_target"
+    READING_DUMP = "reading_dump"
+    SAMPLING_THREADS = "sampling_threads"
+    CAPTURING_BACKTRACE = "capturing_backtrace"
+    SYMBOLIZING = "symbolizing"
+    QUERYING_MODEL = "querying_model"
+    DONE = "done"
+
+
+@dataclass(frozen=True)
+class ProgressEvent:
+    stage: ProgressStage
+    # Short and human-readable, for a status bar.
+    message: str
+
+
+ProgressSink = Callable[[ProgressEvent], None]
+
+
+def report_progress(sink: Optional[ProgressSink], stage: ProgressStage, message: str) -> None:
+    """Send one event to ``sink``; a no-op without one."""
+    if sink is not None:
+        sink(ProgressEvent(stage, message))
+
+
+__all__ = [
+    "ProgressEvent",
+    "ProgressSink",
+    "ProgressStage",
+    "report_progress",
+]
@@ -0,0 +1,322 @@
+"""Fit a crash report into the model's context window, keeping the frames that matter.
+
+A stack overflow can leave thousands of frames; sending them all overflows the
+prompt. `FrameBudget` estimates tokens and, until the prompt fits its budget,
+elides frames in a fixed order:
+
+1. The faulting frame and its first callers (``keep_top``), the first user
+   frame (see `FrameRules`), and the outermost user frames (``keep_bottom``:
+   ``main``, the entry point) are always kept.
+2. Runs of the same frame (recursion) collapse into the first frame of the run
+   and a "... (N repeated frames of f) ..." line.
+3. Frames in system modules (libc, the loader, ntdll, Rust std, ...) are
+   dropped, deepest first.
+4. Remaining frames are dropped, deepest first.
+
+The selection depends only on the report and the budget, so the same crash
+always yields the same prompt. `BuiltPrompt.elision` records what was left
+out, so the output can say "N frames summarized". `PromptBuilder` renders the
+report around the frames its budget keeps.
+
+The faulting thread comes first. Other threads follow at ``thread_frames``
+deep (see `summarize_threads`), then one frame deep if that does not fit
+beside the faulting stack, and are left out entirely as a last resort.
+
+The faulting instruction and the disassembly around it (see
+`dbgcopilot.analysis.disasm`) are left out unless ``fault_context`` is set.
+
+The text around the report comes from a `PromptTemplate` (see
+`dbgcopilot.analysis.template`); `DEFAULT_TEMPLATE` unless one is given.
+"""
+from __future__ import annotations
+
+from dataclasses import dataclass, field
+from typing import Any, List, Mapping, Optional, Tuple
+
+from .crash import CrashReport, _module_line
+from .fault import FaultKind
+from .frames import is_system_frame
+from .language import LANGUAGE_HINTS
+from .model import Frame
+from .panic import PANIC_HINT
+from .redact import Redactor
+from .template import DEFAULT_PROMPT_TEMPLATE, PromptTemplate, template_from_config
+from .threads import OTHER_THREAD_FRAMES, summarize_threads
+
+# Conservative default for small local models; raise it for large-context ones.
+DEFAULT_MAX_PROMPT_TOKENS = 4000
+# Frames from the top (the faulting frame and its callers) that are never elided.
+KEEP_TOP_FRAMES = 4
+# Outermost non-system frames (``main``, ``_start``) that are never elided either.
+KEEP_BOTTOM_FRAMES = 2
+# Consecutive identical frames collapse once there are at least this many.
+RECURSION_RUN = 3
+# Rough tokens-per-character ratio of English and code for BPE tokenizers.
+CHARS_PER_TOKEN = 4
+# How ``prompt_fault_context`` may be spelled when it comes in as text.
+_TRUE_TEXT = ("true", "1", "yes", "on")
+
+# Bump when the instructions or the report layout change enough that answers to the
+# old prompt should not be reused; cached explanations record the version they answered.
+PROMPT_VERSION = 2
+
+
+def estimate_tokens(text: str) -> int:
+    return (len(text) + CHARS_PER_TOKEN - 1) // CHARS_PER_TOKEN
+
+
+def prompt_fault_context(config: Optional[Mapping[str, Any]]) -> bool:
+    """Whether ``config`` asks for the disassembly around the fault in the prompt (``prompt_fault_context``)."""
+    raw = (config or {}).get("prompt_fault_context")
+    return raw if isinstance(raw, bool) else str(raw or "").strip().lower() in _TRUE_TEXT
+
+
+@dataclass
+class FrameEntry:
+    """One backtrace line of the prompt: a frame, or ``count`` identical frames in a row."""
+
+    frame: Frame
+    count: int = 1
+
+    def describe(self) -> str:
+        line = "\n".join([self.frame.describe(), *self.frame.describe_locals()])
+        if self.count > 1:
+            name = self.frame.function or (f"0x{self.frame.pc:x}" if self.frame.pc is not None else "??")
+            line += f"\n... ({self.count - 1} repeated frames of {name}) ..."
+        return line
+
+
+@dataclass
+class PromptElision:
+    """Frames that are in the report but not spelled out in the prompt."""
+
+    # Frames folded into "repeated N times" entries (beyond the first of each run).
+    collapsed: int = 0
+    dropped_system: int = 0
+    dropped: int = 0
+    # Frames the capture itself cut off (``CrashReport.total_frames`` beyond ``frames``).
+    uncaptured: int = 0
+
+    @property
+    def total(self) -> int:
+        return self.collapsed + self.dropped_system + self.dropped + self.uncaptured
+
+    def describe(self) -> str:
+        parts = [
+            f"{n} {what}"
+            for n, what in (
+                (self.collapsed, "collapsed as recursion"),
+                (self.dropped_system, "in system modules dropped"),
+                (self.dropped, "dropped to fit the token budget"),
+                (self.uncaptured, "not captured"),
+            )
+            if n
+        ]
+        return f"{self.total} frames summarized ({', '.join(parts)})" if parts else "no frames summarized"
+
+
+@dataclass
+class BuiltPrompt:
+    text: str
+    # Estimated with `estimate_tokens`. Over the budget only when the header
+    # and the kept top frames alone do not fit.
+    tokens: int
+    entries: List[FrameEntry] = field(default_factory=list)
+    elision: PromptElision = field(default_factory=PromptElision)
+
+
+def collapse_recursion(frames: List[Frame], min_run: int = RECURSION_RUN) -> Tuple[List[FrameEntry], int]:
+    """Group runs of the same function (or pc, without symbols); returns (entries, frames folded)."""
+    entries: List[FrameEntry] = []
+    folded = 0
+
+    def key(f: Frame) -> Tuple[Any, ...]:
+        return (f.function, f.module) if f.function else (f.pc, f.module)
+
+    i = 0
+    while i < len(frames):
+        j = i + 1
+        while j < len(frames) and key(frames[j]) == key(frames[i]):
+            j += 1
+        if j - i >= min_run:
+            entries.append(FrameEntry(frames[i], count=j - i))
+            folded += j - i - 1
+        else:
+            entries.extend(FrameEntry(f) for f in frames[i:j])
+        i = j
+    return entries, folded
+
+
+@dataclass
+class FrameBudget:
+    """Which frames of a backtrace to spell out so a prompt stays within ``max_tokens``."""
+
+    max_tokens: int = DEFAULT_MAX_PROMPT_TOKENS
+    keep_top: int = KEEP_TOP_FRAMES
+    keep_bottom: int = KEEP_BOTTOM_FRAMES
+    min_run: int = RECURSION_RUN
+
+    def _protected_bottom(self, rest: List[FrameEntry]) -> set[int]:
+        protected: set[int] = set()
+        for i in reversed(range(len(rest))):
+            if len(protected) >= self.keep_bottom:
+                break
+            if not is_system_frame(rest[i].frame):
+                protected.add(i)
+        return protected
+
+    def fit(
+        self, frames: List[Frame], fixed_tokens: int = 0, uncaptured: int = 0, user_frame: Optional[int] = None
+    ) -> Tuple[List[FrameEntry], PromptElision]:
+        """Entries to render and what was elided, given the tokens the rest of the prompt costs.
+
+        ``user_frame`` is the `Frame.index` of the first user frame, which is kept like the top frames.
+        """
+        top = [FrameEntry(f) for f in frames[: self.keep_top]]
+        rest, collapsed = collapse_recursion(frames[self.keep_top :], self.min_run)
+        elision = PromptElision(collapsed=collapsed, uncaptured=max(uncaptured, 0))
+
+        # Price each line separately so dropping one is a subtraction, not a re-render.
+        total = sum(estimate_tokens(e.describe() + "\n") for e in top)
+        costs = [estimate_tokens(e.describe() + "\n") for e in rest]
+        total += sum(costs)
+
+        def fits() -> bool:
+            summary = estimate_tokens(f"\n... {elision.describe()}") if elision.total else 0
+            return fixed_tokens + total + summary <= self.max_tokens
+
+        protected = self._protected_bottom(rest)
+        protected.update(i for i, e in enumerate(rest) if user_frame is not None and e.frame.index == user_frame)
+        keep = [True] * len(rest)
+        for system_pass in (True, False):
+            for i in reversed(range(len(rest))):
+                if fits():
+                    break
+                if keep[i] and i not in protected and (not system_pass or is_system_frame(rest[i].frame)):
+                    keep[i] = False
+                    total -= costs[i]
+                    # A dropped run no longer counts as collapsed; all of its frames are gone.
+                    elision.collapsed -= rest[i].count - 1
+                    if system_pass:
+                        elision.dropped_system += rest[i].count
+                    else:
+                        elision.dropped += rest[i].count
+        return top + [e for e, k in zip(rest, keep) if k], elision
+
+
+class PromptBuilder:
+    """Render a `CrashReport` as an explanation prompt within ``max_tokens``."""
+
+    def __init__(
+        self,
+        max_tokens: int = DEFAULT_MAX_PROMPT_TOKENS,
+        keep_top: int = KEEP_TOP_FRAMES,
+        redactor: Optional[Redactor] = None,
+        keep_bottom: int = KEEP_BOTTOM_FRAMES,
+        thread_frames: int = OTHER_THREAD_FRAMES,
+        template: Optional[PromptTemplate] = None,
+        fault_context: bool = False,
+    ) -> None:
+        self.budget = FrameBudget(max_tokens=max_tokens, keep_top=keep_top, keep_bottom=keep_bottom)
+        self.thread_frames = thread_frames
+        # Whether the report in the prompt shows the faulting instruction and the disassembly around it.
+        self.fault_context = fault_context
+        self.template = template or DEFAULT_PROMPT_TEMPLATE
+        # Always redacted: the default rules apply even when no redactor is passed.
+        self.redactor = redactor or Redactor()
+
+    @property
+    def max_tokens(self) -> int:
+        return self.budget.max_tokens
+
+    @classmethod
+    def from_config(cls, config: Optional[Mapping[str, Any]]) -> "PromptBuilder":
+        """Budget from the ``max_prompt_tokens`` config key; redaction rules as in `Redactor.from_config`.
+
+        ``prompt_template`` names a template file; it raises `TemplateError` if it is unusable.
+        ``prompt_fault_context`` (true or false) includes the disassembly around the fault.
+        """
+        config = config or {}
+        raw = config.get("max_prompt_tokens")
+        try:
+            max_tokens = int(raw) if raw not in (None, "") else DEFAULT_MAX_PROMPT_TOKENS
+        except (TypeError, ValueError) as exc:
+            raise ValueError(f"max_prompt_tokens must be an integer, got {raw!r}") from exc
+        return cls(
+            max_tokens=max_tokens,
+            redactor=Redactor.from_config(config),
+            template=template_from_config(config),
+            fault_context=prompt_fault_context(config),
+        )
+
+    def _render(
+        self, report: CrashReport, entries: List[FrameEntry], elision: PromptElision, threads: List[str]
+    ) -> str:
+        backtrace = [e.describe() for e in entries]
+        if elision.total:
+            backtrace.append(f"... {elision.describe()}")
+        hint = LANGUAGE_HINTS.get(report.detected_language)
+        if report.fault_kind is FaultKind.RUST_PANIC:
+            hint = f"{hint} {PANIC_HINT}" if hint else PANIC_HINT
+        values = {
+            # The signature is for grouping crashes, and tells the model nothing the frames do not.
+            "report": report.render(
+                backtrace=backtrace, with_signature=False, threads=threads, with_fault_context=self.fault_context
+            ),
+            "faulting_frame": FrameEntry(report.frames[0]).describe() if report.frames else "",
+            "stack": "\n".join(backtrace),
+            "backtrace": "\n".join(backtrace),
+            "threads": "\n".join(threads),
+            "modules": "\n".join(_module_line(m) for m in report.modules),
+            "hang_kind": report.hang_category.label if report.hang_category is not None else "",
+            "signal": report.signal or "",
+            "classification": report.hint or report.fault_kind.label,
+            "fault_kind": report.fault_kind.label,
+            "registers": " ".join(f"{name}=0x{value:x}" for name, value in report.registers.values.items()),
+            "language_hint": hint or "",
+            "annotations": "\n".join(a.describe() for a in report.annotations),
+            "fault_context": "\n".join(report.fault_context.lines()) if report.fault_context is not None else "",
+        }
+        return self.template.render(values)
+
+    def _thread_lines(self, report: CrashReport, entries: List[FrameEntry], elision: PromptElision) -> List[str]:
+        faulting = report.faulting_thread
+        for depth in (self.thread_frames, 1):
+            lines = summarize_threads(report.threads, faulting, depth)
+            if estimate_tokens(self._render(report, entries, elision, lines)) <= self.max_tokens:
+                return lines
+        others = sum(t is not faulting for t in report.threads)
+        return [f"... {others} other threads left out to fit the token budget"] if others else []
+
+    def build(self, report: CrashReport) -> BuiltPrompt:
+        report = self.redactor.redact_report(report)
+        uncaptured = report.total_frames - len(report.frames)
+        # The faulting stack is fitted first; other threads get what room is left beside it.
+        entries, elision = self.budget.fit(
+            list(report.frames),
+            estimate_tokens(self._render(report, [], PromptElision(), [])),
+            uncaptured,
+            report.user_frame_index,
+        )
+        threads = self._thread_lines(report, entries, elision)
+        fixed = estimate_tokens(self._render(report, [], PromptElision(), threads))
+        entries, elision = self.budget.fit(list(report.frames), fixed, uncaptured, report.user_frame_index)
+        text = self._render(report, entries, elision, threads)
+        return BuiltPrompt(text=text, tokens=estimate_tokens(text), entries=entries, elision=elision)
+
+
+__all__ = [
+    "BuiltPrompt",
+    "DEFAULT_MAX_PROMPT_TOKENS",
+    "FrameBudget",
+    "FrameEntry",
+    "KEEP_BOTTOM_FRAMES",
+    "KEEP_TOP_FRAMES",
+    "PROMPT_VERSION",
+    "PromptBuilder",
+    "PromptElision",
+    "collapse_recursion",
+    "estimate_tokens",
+    "is_system_frame",
+    "prompt_fault_context",
+]
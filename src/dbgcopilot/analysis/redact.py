@@ -0,0 +1,322 @@
+"""Scrub secrets and personal paths out of crash data before it reaches an LLM.
+
+Crash context is often sent to a cloud model. A `Redactor` replaces anything a
+rule matches with ``<redacted>``, or with the rule's own ``replacement``. It
+covers the command line, the environment, and every string field of the stop
+info, frames (including raw debugger arguments and locals), and modules. The
+default rules turn home directories into ``<HOME>`` (``<HOME>/src/main.rs``),
+and hide values assigned to token/secret/password/key names, bearer tokens,
+well-known API key formats, URL passwords, and private key blocks.
+Environment values are hidden unless the variable is on a short allowlist of
+debugging knobs such as ``RUST_BACKTRACE``.
+
+`build_explain_prompt` and the REPL's LLM calls always redact; config can add
+rules but not remove the defaults. `Redactor.dry_run` lists what would be
+replaced without changing anything.
+"""
+from __future__ import annotations
+
+import dataclasses
+import json
+import re
+from dataclasses import dataclass
+from functools import lru_cache
+from typing import Any, Dict, Iterable, List, Mapping, Optional, Pattern, Union
+
+from .crash import CrashReport
+from .model import CrashContext, Frame, Module, PanicInfo, StopInfo, ThreadBacktrace, Variable
+
+REDACTED = "<redacted>"
+# What a home directory (``/home/alice``, ``C:\\Users\\alice``) becomes.
+HOME = "<HOME>"
+
+# Name fragments that mark a value as a credential, in assignments and CLI flags.
+_SECRET_NAME = r"(?:token|secret|passw(?:or)?d|passwd|api[_-]?key|access[_-]?key|private[_-]?key|credential|auth)"
+
+
+@dataclass(frozen=True)
+class RedactionRule:
+    name: str
+    pattern: str
+    # Named group to replace, so "API_KEY=" stays readable; None replaces the whole match.
+    group: Optional[str] = None
+    # Put in place of the match (or group); None uses the redactor's placeholder.
+    replacement: Optional[str] = None
+
+    @property
+    def regex(self) -> Pattern[str]:
+        return _compile(self.pattern)
+
+
+@lru_cache(maxsize=None)
+def _compile(pattern: str) -> Pattern[str]:
+    return re.compile(pattern)
+
+
+DEFAULT_RULES = (
+    RedactionRule("home-directory", r"(?:/home/|/Users/|[A-Za-z]:\\Users\\)[^/\\\s:'\"<>]+", replacement=HOME),
+    RedactionRule(
+        "secret-assignment",
+        r"(?i)\b[\w.-]*" + _SECRET_NAME + r"[\w.-]*[\"']?\s*(?:=|:(?!:))\s*[\"']?"
+        # "Authorization: Bearer TOKEN" hides TOKEN, not the scheme word.
+        r"(?:(?:bearer|basic)\s+)?(?P<value>(?!(?:bearer|basic)\s)[^\s\"',;&<>]+)",
+        "value",
+    ),
+    RedactionRule("secret-flag", r"(?i)--?[\w-]*" + _SECRET_NAME + r"\s+(?P<value>[^\s-][^\s]*)", "value"),
+    RedactionRule("bearer-token", r"(?i)\b(?:bearer|basic)\s+(?P<value>[A-Za-z0-9._~+/-]{8,}=*)", "value"),
+    RedactionRule(
+        "api-key",
+        r"\b(?:sk-[A-Za-z0-9_-]{16,}|gh[pousr]_[A-Za-z0-9]{20,}|github_pat_\w{20,}|xox[abprs]-[A-Za-z0-9-]{10,}"
+        r"|AKIA[0-9A-Z]{16}|AIza[0-9A-Za-z_-]{35}|eyJ[\w-]{8,}\.[\w-]{8,}\.[\w-]{8,})",
+    ),
+    RedactionRule("url-password", r"\b[a-z][a-z0-9+.-]*://[^/\s:@]+:(?P<password>[^/\s@]+)@", "password"),
+    RedactionRule("private-key", r"-----BEGIN [A-Z ]*PRIVATE KEY-----[\s\S]*?(?:-----END [A-Z ]*PRIVATE KEY-----|\Z)"),
+)
+
+# Environment variables whose values are shown: they change how the crash
+# happens and rarely hold anything personal. The text rules still apply.
+DEFAULT_KEEP_ENV = frozenset(
+    {
+        "ASAN_OPTIONS",
+        "LANG",
+        "LC_ALL",
+        "LD_BIND_NOW",
+        "MALLOC_CHECK_",
+        "MALLOC_PERTURB_",
+        "RUST_BACKTRACE",
+        "RUST_LOG",
+        "RUST_MIN_STACK",
+        "TERM",
+        "TSAN_OPTIONS",
+        "TZ",
+        "UBSAN_OPTIONS",
+    }
+)
+
+_SECRET_FLAG_RE = re.compile(r"(?i)^--?[\w-]*" + _SECRET_NAME + r"$")
+
+
+@dataclass(frozen=True)
+class Redaction:
+    """One replacement: which rule fired, in which field, on what text."""
+
+    rule: str
+    where: str
+    text: str
+
+    def describe(self) -> str:
+        return f"{self.where}: {self.rule} would hide {self.text!r}"
+
+
+class Redactor:
+    """Apply redaction rules to text, a `CrashContext`, or a `CrashReport`."""
+
+    def __init__(
+        self,
+        rules: Iterable[RedactionRule] = DEFAULT_RULES,
+        keep_env: Iterable[str] = DEFAULT_KEEP_ENV,
+        placeholder: str = REDACTED,
+    ) -> None:
+        self.rules = list(rules)
+        self.keep_env = frozenset(keep_env)
+        self.placeholder = placeholder
+        for rule in self.rules:
+            try:
+                regex = _compile(rule.pattern)
+            except re.error as exc:
+                raise ValueError(f"redaction rule {rule.name!r} has an invalid pattern: {exc}") from exc
+            if rule.group is not None and rule.group not in regex.groupindex:
+                raise ValueError(f"redaction rule {rule.name!r} has no group {rule.group!r} in its pattern")
+
+    @classmethod
+    def from_config(cls, config: Optional[Mapping[str, Any]]) -> "Redactor":
+        """Default rules plus ``redact_rules`` and ``redact_keep_env`` from a session config.
+
+        ``redact_rules`` is a list (or JSON list) of patterns or of
+        ``{"name", "pattern", "group", "replacement"}`` objects, or one bare pattern.
+        ``redact_keep_env`` lists extra variable names, as a list or a
+        comma-separated string.
+        """
+        config = config or {}
+        extra = [_rule_from_config(i, raw) for i, raw in enumerate(_config_list(config.get("redact_rules"), False))]
+        keep = _config_list(config.get("redact_keep_env"), True)
+        return cls(list(DEFAULT_RULES) + extra, DEFAULT_KEEP_ENV | {str(k).strip() for k in keep if str(k).strip()})
+
+    def redact_text(self, text: str, where: str = "text", found: Optional[List[Redaction]] = None) -> str:
+        for rule in self.rules:
+            text = rule.regex.sub(lambda m, rule=rule: self._replace(m, rule, where, found), text)
+        return text
+
+    def _replace(self, m: "re.Match[str]", rule: RedactionRule, where: str, found: Optional[List[Redaction]]) -> str:
+        group = rule.group or 0
+        secret = m.group(group)
+        replacement = self.placeholder if rule.replacement is None else rule.replacement
+        if not secret or secret in (self.placeholder, replacement):
+            return m.group(0)
+        if found is not None:
+            found.append(Redaction(rule.name, where, secret))
+        whole = m.group(0)
+        start, end = m.start(group) - m.start(), m.end(group) - m.start()
+        return whole[:start] + replacement + whole[end:]
+
+    def _optional(self, text: Optional[str], where: str, found: Optional[List[Redaction]]) -> Optional[str]:
+        return None if text is None else self.redact_text(text, where, found)
+
+    def redact_args(self, args: Iterable[str], found: Optional[List[Redaction]] = None) -> List[str]:
+        out: List[str] = []
+        hide_next = False
+        for i, arg in enumerate(args):
+            where = f"args[{i}]"
+            if hide_next and arg and not arg.startswith("-"):
+                if found is not None:
+                    found.append(Redaction("secret-flag", where, arg))
+                out.append(self.placeholder)
+            else:
+                out.append(self.redact_text(arg, where, found))
+            # "--token VALUE" as two argv entries; the text rule handles "--token=VALUE".
+            hide_next = bool(_SECRET_FLAG_RE.match(arg))
+        return out
+
+    def redact_environ(self, environ: Mapping[str, str], found: Optional[List[Redaction]] = None) -> Dict[str, str]:
+        out: Dict[str, str] = {}
+        for name, value in environ.items():
+            where = f"environ.{name}"
+            if name in self.keep_env:
+                out[name] = self.redact_text(value, where, found)
+            else:
+                if found is not None and value:
+                    found.append(Redaction("environment", where, value))
+                out[name] = self.placeholder if value else value
+        return out
+
+    def _frames(self, frames: Iterable[Frame], found: Optional[List[Redaction]], where: str = "") -> List[Frame]:
+        return [
+            dataclasses.replace(
+                f,
+                function=self._optional(f.function, f"{where}frames[{f.index}].function", found),
+                module=self._optional(f.module, f"{where}frames[{f.index}].module", found),
+                file=self._optional(f.file, f"{where}frames[{f.index}].file", found),
+                args=self._optional(f.args, f"{where}frames[{f.index}].args", found),
+                locals=self._variables(f.locals, f"{where}frames[{f.index}].locals", found),
+            )
+            for f in frames
+        ]
+
+    def _variables(self, variables: Iterable[Variable], where: str, found: Optional[List[Redaction]]) -> List[Variable]:
+        # A string local is as likely to hold a token as an argument or the environment is.
+        redacted = []
+        for v in variables:
+            if not v.optimized_out:
+                v = dataclasses.replace(v, value=self.redact_text(v.value, f"{where}.{v.name}", found))
+            redacted.append(v)
+        return redacted
+
+    def _threads(self, threads: Iterable[ThreadBacktrace], found: Optional[List[Redaction]]) -> List[ThreadBacktrace]:
+        return [
+            dataclasses.replace(
+                t,
+                name=self._optional(t.name, f"threads[{t.thread_id}].name", found),
+                frames=self._frames(t.frames, found, f"threads[{t.thread_id}]."),
+            )
+            for t in threads
+        ]
+
+    def _modules(self, modules: Iterable[Module], found: Optional[List[Redaction]]) -> List[Module]:
+        return [
+            dataclasses.replace(
+                m,
+                name=self.redact_text(m.name, f"modules[{i}].name", found),
+                path=self._optional(m.path, f"modules[{i}].path", found),
+            )
+            for i, m in enumerate(modules)
+        ]
+
+    def _stop(self, stop: StopInfo, found: Optional[List[Redaction]]) -> StopInfo:
+        return dataclasses.replace(stop, description=self.redact_text(stop.description, "stop.description", found))
+
+    def _panic(self, panic: Optional[PanicInfo], found: Optional[List[Redaction]]) -> Optional[PanicInfo]:
+        # A panic message can quote anything the program formatted into it.
+        if panic is None:
+            return None
+        return PanicInfo(
+            message=self._optional(panic.message, "panic.message", found),
+            location=self._optional(panic.location, "panic.location", found),
+        )
+
+    def redact_context(self, ctx: CrashContext, found: Optional[List[Redaction]] = None) -> CrashContext:
+        """A copy of ``ctx`` with every string field redacted; registers are numbers and pass through."""
+        return dataclasses.replace(
+            ctx,
+            stop=self._stop(ctx.stop, found),
+            frames=self._frames(ctx.frames, found),
+            modules=self._modules(ctx.modules, found),
+            args=self.redact_args(ctx.args, found),
+            environ=self.redact_environ(ctx.environ, found),
+            threads=self._threads(ctx.threads, found),
+            panic=self._panic(ctx.panic, found),
+        )
+
+    def redact_report(self, report: CrashReport, found: Optional[List[Redaction]] = None) -> CrashReport:
+        return dataclasses.replace(
+            report,
+            description=self.redact_text(report.description, "description", found),
+            hint=self._optional(report.hint, "hint", found),
+            fault_evidence=[self.redact_text(text, "fault_evidence", found) for text in report.fault_evidence],
+            frames=self._frames(report.frames, found),
+            modules=self._modules(report.modules, found),
+            args=self.redact_args(report.args, found),
+            environ=self.redact_environ(report.environ, found),
+            threads=self._threads(report.threads, found),
+            panic=self._panic(report.panic, found),
+            annotations=[
+                dataclasses.replace(a, text=self.redact_text(a.text, "annotations", found)) for a in report.annotations
+            ],
+        )
+
+    def dry_run(self, target: Union[CrashContext, CrashReport, str]) -> List[Redaction]:
+        """What redacting ``target`` would replace; ``target`` itself is never modified."""
+        found: List[Redaction] = []
+        if isinstance(target, CrashReport):
+            self.redact_report(target, found)
+        elif isinstance(target, CrashContext):
+            self.redact_context(target, found)
+        else:
+            self.redact_text(target, "text", found)
+        return found
+
+
+def _config_list(raw: Any, split_commas: bool) -> List[Any]:
+    if raw is None or raw == "":
+        return []
+    if isinstance(raw, str):
+        text = raw.strip()
+        if text.startswith("["):
+            try:
+                parsed = json.loads(text)
+            except ValueError as exc:
+                raise ValueError(f"malformed redaction config {raw!r}: {exc}") from exc
+            return list(parsed)
+        # A bare pattern may itself contain commas ({2,4}); names never do.
+        return [part for part in text.split(",") if part.strip()] if split_commas else [text]
+    return list(raw)
+
+
+def _rule_from_config(index: int, raw: Any) -> RedactionRule:
+    if isinstance(raw, str):
+        return RedactionRule(f"custom-{index}", raw)
+    if isinstance(raw, Mapping) and isinstance(raw.get("pattern"), str):
+        return RedactionRule(
+            str(raw.get("name") or f"custom-{index}"), raw["pattern"], raw.get("group"), raw.get("replacement")
+        )
+    raise ValueError(f"redact_rules[{index}] must be a pattern or an object with a 'pattern' key, got {raw!r}")
+
+
+__all__ = [
+    "DEFAULT_KEEP_ENV",
+    "DEFAULT_RULES",
+    "HOME",
+    "REDACTED",
+    "Redaction",
+    "RedactionRule",
+    "Redactor",
+]
@@ -0,0 +1,272 @@
+"""Which memory region a fault address fell in: stack, heap, a mapped file, or nothing.
+
+"SIGSEGV at 0x7f3a12c04010" says little on its own. The same address placed in
+the process's memory map says much more: in the null page, unmapped, on a
+stack, in the heap, or inside a specific module. `classify_address` reads that
+from a list of `MapEntry` ranges:
+
+- live GDB sessions use ``info proc mappings`` (``/proc/<pid>/maps``);
+- Linux cores use their ``PT_LOAD`` segments, named from ``NT_FILE``
+  (`CoreDump.memory_map`).
+
+Anonymous ranges are labelled as the kernel does (``[stack]``, ``[heap]``)
+where the map says so. A core's segments carry no such names, so there the
+stack is the segment holding a thread's stack pointer, and the heap the first
+anonymous segment above the executable, where ``brk`` puts it.
+
+A freed heap chunk looks like live heap in the map. It is only reported as
+freed (`RegionKind.FREED_HEAP`) when the allocator said so: AddressSanitizer's
+"is located N bytes inside of M-byte region [a,b)" next to "freed by thread"
+(`freed_chunks`). Without it the region is just ``heap``.
+"""
+from __future__ import annotations
+
+import os
+import re
+from dataclasses import dataclass
+from enum import Enum
+from typing import TYPE_CHECKING, Any, Dict, Iterable, List, Optional, Sequence, Tuple
+
+from .fault import NULL_PAGE_LIMIT
+
+if TYPE_CHECKING:
+    from .model import CrashContext
+
+# An unmapped fault this close below a stack mapping is reported against it.
+BELOW_STACK_WINDOW = 0x100000
+# brk starts at most this far above the executable (x86-64 randomization), which places a core's heap.
+BRK_RANDOM_RANGE = 0x2000000
+
+# 0x602000000010 is located 0 bytes inside of 40-byte region [0x602000000010,0x602000000038)
+_ASAN_REGION_RE = re.compile(
+    r"0x[0-9a-fA-F]+ is located \d+ bytes (?:inside|to the (?:left|right)) of \d+-byte region "
+    r"\[(?P<start>0x[0-9a-fA-F]+),\s*(?P<end>0x[0-9a-fA-F]+)\)"
+)
+# READ of size 4 at 0x602000000010 thread T0
+_ASAN_ACCESS_RE = re.compile(r"\b(?:READ|WRITE) of size \d+ at (?P<address>0x[0-9a-fA-F]+)")
+
+
+class RegionKind(str, Enum):
+    NULL_PAGE = "null_page"
+    UNMAPPED = "unmapped"
+    STACK = "stack"
+    # An inaccessible (``---p``) mapping, such as the guard page below a thread stack.
+    GUARD_PAGE = "guard_page"
+    HEAP = "heap"
+    FREED_HEAP = "freed_heap"
+    MODULE = "module"
+    ANONYMOUS = "anonymous"
+
+    @property
+    def label(self) -> str:
+        return _LABELS[self]
+
+
+_LABELS = {
+    RegionKind.NULL_PAGE: "unmapped, in the null page",
+    RegionKind.UNMAPPED: "unmapped",
+    RegionKind.STACK: "stack",
+    RegionKind.GUARD_PAGE: "guard page (mapped, no access)",
+    RegionKind.HEAP: "heap",
+    RegionKind.FREED_HEAP: "freed heap chunk",
+    RegionKind.MODULE: "mapped file",
+    RegionKind.ANONYMOUS: "anonymous mapping",
+}
+
+
+@dataclass(frozen=True)
+class MapEntry:
+    """One range of the process's memory map, ``[start, end)``."""
+
+    start: int
+    end: int
+    # ``r-xp`` as in /proc/<pid>/maps; None when the source does not say.
+    perms: Optional[str] = None
+    # Mapped file, or a kernel label such as ``[stack]``; None for anonymous memory.
+    path: Optional[str] = None
+    offset: int = 0
+
+    def __contains__(self, address: object) -> bool:
+        return isinstance(address, int) and self.start <= address < self.end
+
+
+@dataclass
+class MemoryRegion:
+    """Where one address falls in the memory map; see the module docstring."""
+
+    kind: RegionKind
+    address: int
+    # The mapping (or, for freed chunks, the chunk), when there is one.
+    start: Optional[int] = None
+    end: Optional[int] = None
+    perms: Optional[str] = None
+    path: Optional[str] = None
+    # Extra evidence: how the kind was decided, or what the address is close to.
+    note: Optional[str] = None
+
+    @property
+    def module(self) -> Optional[str]:
+        """Name of the mapped file, for `RegionKind.MODULE`."""
+        return os.path.basename(self.path) if self.kind is RegionKind.MODULE and self.path else None
+
+    def describe(self) -> str:
+        text = f"Fault region: 0x{self.address:x} is {self.kind.label}"
+        if self.module:
+            text += f" {self.module}"
+        if self.start is not None and self.end is not None:
+            text += f" [0x{self.start:x}-0x{self.end:x})"
+        if self.perms:
+            text += f" {self.perms}"
+        return text + (f"; {self.note}" if self.note else "")
+
+    def to_dict(self) -> Dict[str, Any]:
+        def hexed(value: Optional[int]) -> Optional[str]:
+            return f"0x{value:x}" if value is not None else None
+
+        return {
+            "kind": self.kind.value,
+            "address": hexed(self.address),
+            "start": hexed(self.start),
+            "end": hexed(self.end),
+            "perms": self.perms,
+            "path": self.path,
+            "module": self.module,
+            "note": self.note,
+        }
+
+    @classmethod
+    def from_dict(cls, data: Dict[str, Any]) -> "MemoryRegion":
+        def unhexed(value: Optional[str]) -> Optional[int]:
+            return int(value, 16) if value else None
+
+        return cls(
+            kind=RegionKind(data["kind"]),
+            address=int(data["address"], 16),
+            start=unhexed(data.get("start")),
+            end=unhexed(data.get("end")),
+            perms=data.get("perms"),
+            path=data.get("path"),
+            note=data.get("note"),
+        )
+
+
+def freed_chunks(output: str) -> List[Tuple[int, int]]:
+    """Freed ``[start, end)`` heap chunks that AddressSanitizer's report in ``output`` points into."""
+    if "freed by thread" not in (output or ""):
+        return []
+    return [(int(m.group("start"), 16), int(m.group("end"), 16)) for m in _ASAN_REGION_RE.finditer(output)]
+
+
+def _is_stack(entry: MapEntry, stack_pointers: Sequence[int]) -> bool:
+    return bool(entry.path and entry.path.startswith("[stack")) or any(sp in entry for sp in stack_pointers)
+
+
+def classify_address(
+    address: int,
+    memory_map: Sequence[MapEntry],
+    stack_pointers: Iterable[Optional[int]] = (),
+    freed: Iterable[Tuple[int, int]] = (),
+) -> MemoryRegion:
+    """Place ``address`` in ``memory_map``; ``freed`` chunks (see `freed_chunks`) take precedence.
+
+    The null page is never mapped on Linux (``mmap_min_addr``), so low
+    addresses are placed even with an empty map.
+    """
+    sps = [sp for sp in stack_pointers if sp is not None]
+    for start, end in freed:
+        if start <= address < end:
+            return MemoryRegion(RegionKind.FREED_HEAP, address, start, end, note="AddressSanitizer reported it freed")
+    entry = next((e for e in memory_map if address in e), None)
+    if entry is None:
+        if address < NULL_PAGE_LIMIT:
+            return MemoryRegion(RegionKind.NULL_PAGE, address, note="a null pointer plus a small offset")
+        region = MemoryRegion(RegionKind.UNMAPPED, address)
+        stack = next((e for e in memory_map if _is_stack(e, sps) and 0 < e.start - address <= BELOW_STACK_WINDOW), None)
+        if stack is not None:
+            region.note = f"0x{stack.start - address:x} bytes below the stack at 0x{stack.start:x}"
+        return region
+    region = MemoryRegion(RegionKind.ANONYMOUS, address, entry.start, entry.end, entry.perms, entry.path)
+    if entry.perms is not None and entry.perms.startswith("---"):
+        region.kind = RegionKind.GUARD_PAGE
+        above = next((e for e in memory_map if e.start == entry.end and _is_stack(e, sps)), None)
+        if above is not None:
+            region.note = "directly below a stack"
+    elif _is_stack(entry, sps):
+        region.kind = RegionKind.STACK
+    elif entry.path == "[heap]":
+        region.kind = RegionKind.HEAP
+    elif entry.path and not entry.path.startswith("["):
+        region.kind = RegionKind.MODULE
+    return region
+
+
+def fault_region(
+    ctx: "CrashContext",
+    memory_map: Sequence[MapEntry],
+    output: str = "",
+    stack_pointers: Iterable[Optional[int]] = (),
+) -> Optional[MemoryRegion]:
+    """Where the fault address of ``ctx`` is; None when there is no address or nothing to place it in.
+
+    AddressSanitizer aborts before the access faults, so with no fault address
+    the one its report in ``output`` names is used. An empty map (an engine
+    that does not list one) still places null-page addresses and freed chunks,
+    but nothing else: it is not evidence that the address is unmapped.
+    A ``SI_KERNEL`` fault has no address to place.
+    """
+    address = ctx.stop.known_fault_address
+    if address is None:
+        access = _ASAN_ACCESS_RE.search(output or "")
+        address = int(access.group("address"), 16) if access else None
+    if address is None:
+        return None
+    freed = freed_chunks(output)
+    region = classify_address(address, memory_map, [ctx.stack_pointer, *stack_pointers], freed)
+    if not memory_map and region.kind is RegionKind.UNMAPPED:
+        return None
+    return region
+
+
+def core_memory_map(
+    segments: Iterable[Tuple[int, int, str]],
+    files: Iterable[Tuple[int, int, int, str]],
+    executable: Optional[str] = None,
+) -> List[MapEntry]:
+    """A core's map from its ``(vaddr, memsz, perms)`` load segments and ``(start, end, offset, path)`` files.
+
+    Segments take the name of the file mapped at their start. Of the
+    anonymous ones within `BRK_RANDOM_RANGE` above ``executable``, the first
+    not directly after its image (that one is its ``.bss``) is named
+    ``[heap]``; failing that, the one directly after it.
+    """
+    named = {start: (offset, path) for start, _end, offset, path in files}
+    entries: List[MapEntry] = []
+    for vaddr, memsz, perms in sorted(segments):
+        offset, path = named.get(vaddr, (0, None))
+        entries.append(MapEntry(vaddr, vaddr + memsz, perms, path, offset))
+    image_end = max((e.end for e in entries if executable and e.path == executable), default=None)
+    if image_end is None:
+        return entries
+    candidates = [
+        i
+        for i, e in enumerate(entries)
+        if e.path is None and image_end <= e.start < image_end + BRK_RANDOM_RANGE and e.perms != "---p"
+    ]
+    heap = next((i for i in candidates if entries[i].start > image_end), candidates[0] if candidates else None)
+    if heap is not None:
+        e = entries[heap]
+        entries[heap] = MapEntry(e.start, e.end, e.perms, "[heap]", e.offset)
+    return entries
+
+
+__all__ = [
+    "BELOW_STACK_WINDOW",
+    "BRK_RANDOM_RANGE",
+    "MapEntry",
+    "MemoryRegion",
+    "RegionKind",
+    "classify_address",
+    "core_memory_map",
+    "fault_region",
+    "freed_chunks",
+]
@@ -0,0 +1,241 @@
+"""Registers by name, with the same roles on every architecture.
+
+Each capture path hands back a flat list of `Register` values, named the way
+its source names them: GDB and the core reader say ``x29`` where LLDB says
+``fp`` on AArch64. `Registers` wraps such a list. Plain names work for any
+architecture (``regs.get("rdi")``, ``regs.rdi``, ``regs.get("x0")``), and four
+roles are looked up under every architecture's names:
+
+| Role | x86-64 | AArch64 | x86 |
+| --- | --- | --- | --- |
+| `instruction_pointer` (``pc``) | ``rip`` | ``pc`` | ``eip`` |
+| `stack_pointer` (``sp``) | ``rsp`` | ``sp`` | ``esp`` |
+| `frame_pointer` (``fp``) | ``rbp`` | ``x29`` / ``fp`` | ``ebp`` |
+| `link_register` (``lr``) | — | ``x30`` / ``lr`` | — |
+
+The architecture is told from the names themselves, so the three capture
+paths need not agree on how to report it.
+
+DWARF numbers registers differently per architecture: register 7 is ``rsp`` on
+x86-64, ``x7`` on AArch64, and ``edi`` on x86. `dwarf_register_name` and
+`Registers.dwarf` map a number from a location expression or CFI rule to the
+register it means.
+
+`address_registers` names the general-purpose registers that hold the fault
+address, or a pointer just below it: a fault at 0x18 with ``rdi = 0x0`` was
+most likely an access through ``rdi`` at offset 0x18. Reports print that, and
+so the prompt can say which register the bad pointer was in.
+"""
+from __future__ import annotations
+
+from dataclasses import dataclass, field
+from enum import Enum
+from typing import Any, Dict, Iterable, List, Optional, Tuple
+
+from .model import Register
+
+# A field or array element this far past the register's value still counts as an access through it.
+MAX_FIELD_OFFSET = 0x1000
+# More registers than this holding the same value are not worth naming as the fault's source.
+MAX_TIED_REGISTERS = 3
+
+
+class Architecture(str, Enum):
+    X86_64 = "x86_64"
+    AARCH64 = "aarch64"
+    X86 = "x86"
+    UNKNOWN = "unknown"
+
+    @property
+    def label(self) -> str:
+        return _ARCH_LABELS[self]
+
+
+_ARCH_LABELS = {
+    Architecture.X86_64: "x86-64",
+    Architecture.AARCH64: "AArch64",
+    Architecture.X86: "x86",
+    Architecture.UNKNOWN: "unknown architecture",
+}
+
+# Role -> the names that hold it, in order of preference, per architecture.
+_ROLES: Dict[Architecture, Dict[str, Tuple[str, ...]]] = {
+    Architecture.X86_64: {"pc": ("rip",), "sp": ("rsp",), "fp": ("rbp",), "lr": ()},
+    Architecture.AARCH64: {"pc": ("pc",), "sp": ("sp",), "fp": ("x29", "fp"), "lr": ("x30", "lr")},
+    Architecture.X86: {"pc": ("eip",), "sp": ("esp",), "fp": ("ebp",), "lr": ()},
+    Architecture.UNKNOWN: {"pc": ("pc", "ip"), "sp": ("sp",), "fp": ("fp",), "lr": ("lr",)},
+}
+_ROLE_ALIASES = {"ip": "pc", "instruction_pointer": "pc", "stack_pointer": "sp", "frame_pointer": "fp",
+                 "link_register": "lr"}
+
+_DWARF_ROLES = {"x29": "fp", "x30": "lr"}
+
+_X86_64_GPRS = ("rax", "rbx", "rcx", "rdx", "rsi", "rdi", "rbp", "r8", "r9", "r10", "r11", "r12", "r13",
+                "r14", "r15")
+_X86_GPRS = ("eax", "ebx", "ecx", "edx", "esi", "edi", "ebp")
+_GPRS: Dict[Architecture, Tuple[str, ...]] = {
+    Architecture.X86_64: _X86_64_GPRS,
+    Architecture.AARCH64: tuple(f"x{i}" for i in range(31)) + ("fp", "lr"),
+    Architecture.X86: _X86_GPRS,
+    Architecture.UNKNOWN: (),
+}
+
+# DWARF register numbers from each psABI (the AArch64 and x86-64 ones, and i386's SysV supplement).
+_DWARF_NAMES: Dict[Architecture, Tuple[str, ...]] = {
+    Architecture.X86_64: ("rax", "rdx", "rcx", "rbx", "rsi", "rdi", "rbp", "rsp", "r8", "r9", "r10", "r11",
+                          "r12", "r13", "r14", "r15", "rip"),
+    Architecture.AARCH64: tuple(f"x{i}" for i in range(31)) + ("sp", "pc"),
+    Architecture.X86: ("eax", "ecx", "edx", "ebx", "esp", "ebp", "esi", "edi", "eip"),
+    Architecture.UNKNOWN: (),
+}
+
+
+def dwarf_register_name(arch: Architecture, number: int) -> Optional[str]:
+    """The register DWARF register ``number`` is on ``arch`` (``x29`` for 29 on AArch64); None if unmapped."""
+    names = _DWARF_NAMES[arch]
+    return names[number] if 0 <= number < len(names) else None
+
+
+def detect_architecture(names: Iterable[str]) -> Architecture:
+    """The architecture whose register file has these names."""
+    found = {n.lower() for n in names}
+    if "rip" in found or "rax" in found:
+        return Architecture.X86_64
+    if "x0" in found or "x29" in found or ("pc" in found and "cpsr" in found) or "pstate" in found:
+        return Architecture.AARCH64
+    if "eip" in found or "eax" in found:
+        return Architecture.X86
+    return Architecture.UNKNOWN
+
+
+@dataclass
+class Registers:
+    """The registers of one thread, by name; see the module docstring."""
+
+    values: Dict[str, int] = field(default_factory=dict)
+    arch: Architecture = Architecture.UNKNOWN
+
+    @classmethod
+    def from_list(cls, registers: Iterable[Register], arch: Optional[Architecture] = None) -> "Registers":
+        values: Dict[str, int] = {}
+        for reg in registers:
+            values.setdefault(reg.name.lower(), reg.value)
+        return cls(values, arch if arch is not None else detect_architecture(values))
+
+    def __getattr__(self, name: str) -> int:
+        # Only reached for names that are not fields: ``regs.rdi``.
+        values = self.__dict__.get("values") or {}
+        if name in values:
+            return values[name]
+        raise AttributeError(name)
+
+    def __bool__(self) -> bool:
+        return bool(self.values)
+
+    def get(self, name: str) -> Optional[int]:
+        """The register called ``name`` (any case), or a role: ``pc``/``ip``, ``sp``, ``fp``, ``lr``."""
+        key = name.lower()
+        if key in self.values:
+            return self.values[key]
+        role = _ROLE_ALIASES.get(key, key)
+        return self._role(role)[1] if role in _ROLES[self.arch] else None
+
+    def dwarf(self, number: int) -> Optional[int]:
+        """The value of DWARF register ``number`` under this thread's architecture."""
+        name = dwarf_register_name(self.arch, number)
+        if name is None:
+            return None
+        # LLDB names x29/x30 fp/lr, so those fall back to the role.
+        return self.values.get(name) if name in self.values else self.get(_DWARF_ROLES.get(name, name))
+
+    def _role(self, role: str) -> Tuple[Optional[str], Optional[int]]:
+        for name in _ROLES[self.arch][role]:
+            if name in self.values:
+                return name, self.values[name]
+        return None, None
+
+    def role_name(self, role: str) -> Optional[str]:
+        """The name this thread's registers give ``role`` (``rip`` for ``pc`` on x86-64), if it is present."""
+        return self._role(_ROLE_ALIASES.get(role, role))[0]
+
+    @property
+    def instruction_pointer(self) -> Optional[int]:
+        return self._role("pc")[1]
+
+    @property
+    def stack_pointer(self) -> Optional[int]:
+        return self._role("sp")[1]
+
+    @property
+    def frame_pointer(self) -> Optional[int]:
+        return self._role("fp")[1]
+
+    @property
+    def link_register(self) -> Optional[int]:
+        return self._role("lr")[1]
+
+    def address_registers(self, address: Optional[int]) -> List[Tuple[str, int]]:
+        """``(name, offset)`` of each general-purpose register at or just below ``address``, closest first."""
+        if address is None:
+            return []
+        found = []
+        for name in _GPRS[self.arch]:
+            value = self.values.get(name)
+            if value is not None and 0 <= address - value < MAX_FIELD_OFFSET:
+                found.append((name, address - value))
+        return sorted(found, key=lambda hit: hit[1])
+
+    def describe_roles(self) -> str:
+        """``rip=0x401136 rsp=0x7ffc0 rbp=0x7ffd0``: the instruction, stack, and frame pointers (and lr)."""
+        parts = []
+        for role in ("pc", "sp", "fp", "lr"):
+            name, value = self._role(role)
+            if name is not None and value is not None:
+                parts.append(f"{name}=0x{value:x}")
+        return " ".join(parts)
+
+    def to_dict(self) -> Dict[str, Any]:
+        def hexed(value: Optional[int]) -> Optional[str]:
+            return f"0x{value:x}" if value is not None else None
+
+        return {
+            "arch": self.arch.value,
+            "instruction_pointer": hexed(self.instruction_pointer),
+            "stack_pointer": hexed(self.stack_pointer),
+            "frame_pointer": hexed(self.frame_pointer),
+            "link_register": hexed(self.link_register),
+        }
+
+
+def describe_address_source(registers: Registers, address: Optional[int]) -> Optional[str]:
+    """``rdi + 0x18 (rdi = 0x0)`` for the closest registers `address_registers` finds; None without one.
+
+    Registers tied for closest are all named (``rax or rdi + 0x18 (both 0x0)``): which one the
+    instruction used is not known here. More than `MAX_TIED_REGISTERS` tied (a thread with most
+    registers zero) says nothing, and gives None.
+    """
+    hits = registers.address_registers(address)
+    if not hits:
+        return None
+    offset = hits[0][1]
+    names = [name for name, off in hits if off == offset]
+    if len(names) > MAX_TIED_REGISTERS:
+        return None
+    value = registers.values[names[0]]
+    if len(names) == 1:
+        which, held = names[0], f"{names[0]} = 0x{value:x}"
+    else:
+        which = " or ".join(names) if len(names) == 2 else ", ".join(names[:-1]) + ", or " + names[-1]
+        held = f"{'both' if len(names) == 2 else 'all'} 0x{value:x}"
+    return f"{which} + 0x{offset:x} ({held})" if offset else f"{which} ({held})"
+
+
+__all__ = [
+    "Architecture",
+    "MAX_FIELD_OFFSET",
+    "MAX_TIED_REGISTERS",
+    "Registers",
+    "describe_address_source",
+    "detect_architecture",
+    "dwarf_register_name",
+]
@@ -0,0 +1,167 @@
+"""Plain-text view of the structured analysis data.
+
+`AnalysisReport.render` goes through `render_report(report.to_dict())`, so the
+text a person reads and the JSON a script reads come from the same data and
+cannot disagree. `render_report` also works on JSON loaded back from
+``dbgcopilot-analyze --format json``.
+
+`render_terminal` is the same content split into headed sections (fault
+summary, faulting frame, faulting instruction, stack, modules, explanation)
+for a person at a terminal, colored when asked. The plain text stays the format for pipes and
+scripts: the CLI only uses the sectioned view on a TTY.
+"""
+from __future__ import annotations
+
+from dataclasses import dataclass, field
+from typing import Any, List, Mapping, Tuple
+
+from dbgcopilot.utils.io import color_text
+
+from .crash import DEFAULT_MAX_FRAMES, CrashReport, _module_line
+from .fix import FixSuggestion
+from .threads import OTHER_THREAD_FRAMES, summarize_threads
+
+# How `render_sections` colors each kind of line; "" is left as is.
+_STYLES = {
+    "fault": ("red", True),
+    "user": ("green", True),
+    "runtime": ("gray", False),
+    "error": ("yellow", False),
+}
+
+
+def render_report(data: Mapping[str, Any], with_explanation: bool = True, with_modules: bool = False) -> str:
+    """Text form of `AnalysisReport.to_dict` output: crash summary, exit code, the explanation, then the fix.
+
+    A hang's classification and samples are part of the summary, through its
+    ``hint``. ``with_modules`` lists the loaded modules with their versions.
+    """
+    lines = [CrashReport.from_dict(data, max_frames=DEFAULT_MAX_FRAMES).render(with_modules=with_modules)]
+    if data.get("exit_code") is not None and data.get("signal") is None:
+        lines.append(f"Exit code: {data['exit_code']}")
+    lines.extend(f"Warning: {warning}" for warning in data.get("warnings") or [])
+    explanation, error = data.get("explanation"), data.get("explanation_error")
+    if with_explanation and explanation:
+        lines += ["", "Explanation (incomplete):" if data.get("explanation_partial") else "Explanation:", explanation]
+    if with_explanation and error:
+        lines += ["", f"Explanation failed: {error}"]
+    fix, fix_error = data.get("fix_suggestion"), data.get("fix_suggestion_error")
+    if with_explanation and fix:
+        lines += ["", "Suggested fix:", *FixSuggestion.from_dict(fix).render()]
+    if with_explanation and fix_error:
+        lines += ["", f"Fix suggestion failed: {fix_error}"]
+    return "\n".join(lines)
+
+
+@dataclass
+class Section:
+    """One headed block of the terminal view; each line carries a style name (see `_STYLES`)."""
+
+    title: str
+    lines: List[Tuple[str, str]] = field(default_factory=list)
+
+    def add(self, text: str, style: str = "") -> None:
+        self.lines.append((text, style))
+
+
+def report_sections(
+    data: Mapping[str, Any], with_explanation: bool = True, with_modules: bool = False
+) -> List[Section]:
+    """`render_report`'s content as sections; empty ones are left out."""
+    report = CrashReport.from_dict(data, max_frames=DEFAULT_MAX_FRAMES)
+    summary = Section("Fault summary")
+    for line in report.summary_lines(with_user_frame=False):
+        summary.add(line, "fault" if line.startswith("Classification:") else "")
+    if data.get("exit_code") is not None and data.get("signal") is None:
+        summary.add(f"Exit code: {data['exit_code']}")
+    for warning in data.get("warnings") or []:
+        summary.add(f"Warning: {warning}", "error")
+
+    frame = Section("Faulting frame")
+    if report.crash_site is not None:
+        frame.add(report.crash_site.describe(), "fault")
+        for line in report.crash_site.describe_locals():
+            frame.add(line)
+    user_line = report.user_frame_line()
+    if user_line is not None:
+        frame.add(user_line, "user")
+
+    instruction = Section("Faulting instruction")
+    for line in report.fault_context.lines() if report.fault_context is not None else []:
+        instruction.add(line, "fault" if line.startswith(("Faulting instruction:", "=>")) else "")
+
+    stack = Section("Stack")
+    user_index = report.user_frame_index
+    for f in report.frames:
+        if f.index == user_index:
+            style = "user"
+        elif user_index is not None and f.index < user_index:
+            style = "runtime"
+        else:
+            style = "fault" if f.index == 0 else ""
+        stack.add(f.describe(), style)
+        for line in f.describe_locals():
+            stack.add(line)
+    if report.total_frames > len(report.frames):
+        stack.add(f"... {report.total_frames - len(report.frames)} more frames", "runtime")
+
+    threads = Section("Other threads")
+    for line in summarize_threads(report.threads, report.faulting_thread, OTHER_THREAD_FRAMES):
+        threads.add(line)
+    modules = Section("Modules")
+    for module in report.modules if with_modules else []:
+        modules.add(_module_line(module))
+    environ = Section("Environment")
+    for name, value in sorted(report.environ.items()):
+        environ.add(f"{name}={value}")
+
+    explanation = Section("AI explanation")
+    fix = Section("Suggested fix")
+    if with_explanation:
+        if data.get("explanation"):
+            if data.get("explanation_partial"):
+                explanation.add("(incomplete)", "error")
+            for line in data["explanation"].splitlines():
+                explanation.add(line)
+        if data.get("explanation_error"):
+            explanation.add(f"Explanation failed: {data['explanation_error']}", "error")
+        if data.get("fix_suggestion"):
+            for line in FixSuggestion.from_dict(data["fix_suggestion"]).render():
+                fix.add(line)
+        if data.get("fix_suggestion_error"):
+            fix.add(f"Fix suggestion failed: {data['fix_suggestion_error']}", "error")
+    sections = [summary, frame, instruction, stack, threads, modules, environ, explanation, fix]
+    return [section for section in sections if section.lines]
+
+
+def section_heading(title: str, color: bool = False) -> str:
+    return color_text(f"== {title} ==", "cyan", bold=True, enable=color)
+
+
+def render_sections(sections: List[Section], color: bool = False) -> str:
+    """Headed, indented text; ``color`` adds ANSI colors to headings and styled lines."""
+    blocks = []
+    for section in sections:
+        lines = [section_heading(section.title, color)]
+        for text, style in section.lines:
+            name, bold = _STYLES.get(style, (None, False))
+            lines.append("  " + color_text(text, name, bold=bold, enable=color))
+        blocks.append("\n".join(lines))
+    return "\n\n".join(blocks)
+
+
+def render_terminal(
+    data: Mapping[str, Any], color: bool = False, with_explanation: bool = True, with_modules: bool = False
+) -> str:
+    """`render_report`'s content in headed sections, for a terminal; see the module docstring."""
+    return render_sections(report_sections(data, with_explanation, with_modules), color)
+
+
+__all__ = [
+    "Section",
+    "render_report",
+    "render_sections",
+    "render_terminal",
+    "report_sections",
+    "section_heading",
+]
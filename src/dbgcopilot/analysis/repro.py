@@ -0,0 +1,232 @@
+"""Standalone programs that reproduce a crash's fault category.
+
+A teammate reading an explanation often wants something they can build and
+step through. `build_repro` picks a template by `FaultKind` (null dereference,
+divide by zero, stack overflow, abort) and fills it in with the name of the
+function that crashed, so the scaffold faults the same way in a function of
+the same name. Crashes in Rust get a Cargo project built with
+``panic = "abort"``; everything else gets a C file and a Makefile. Both build
+with debug info and without optimization. A Rust panic always gets the Cargo
+project, which unwraps a ``None``.
+
+This is a starting point, not a minimized test case: the templates know the
+fault category, not the program's logic. Other fault kinds (heap corruption,
+wild accesses, hangs) have no template; `build_repro` returns None for them.
+"""
+from __future__ import annotations
+
+import os
+import re
+from dataclasses import dataclass, field
+from typing import Dict, List, Optional, Tuple
+
+from .crash import CrashReport
+from .fault import FaultKind
+from .model import Frame, Language
+from .prompt import is_system_frame
+
+# Used when no frame names a function the template can reuse.
+DEFAULT_FUNCTION = "crash_site"
+
+_IDENTIFIER_RE = re.compile(r"^[A-Za-z_][A-Za-z0-9_]*$")
+# Names that would clash with the template's own entry point or the language.
+_RESERVED = {
+    "main", "abort", "exit", "fn", "let", "mut", "loop", "match", "impl", "mod", "use", "type", "ref", "move",
+    "int", "char", "void", "return", "static", "struct", "while", "for", "if", "else",
+}
+# Runtime frames above the user's code: panic and abort machinery.
+_RUNTIME_MARKERS = ("panic", "abort", "raise", "__pthread_kill", "rust_begin_unwind", "__libc_")
+
+_C_BODIES: Dict[FaultKind, Tuple[str, str]] = {
+    FaultKind.NULL_DEREF: (
+        "void",
+        "    int *volatile ptr = NULL;\n"
+        "    *ptr = 42; /* writes to address 0: SIGSEGV */\n",
+    ),
+    FaultKind.DIVIDE_BY_ZERO: (
+        "int",
+        "    volatile int divisor = 0;\n"
+        "    return 42 / divisor; /* integer division by zero: SIGFPE */\n",
+    ),
+    FaultKind.STACK_OVERFLOW: (
+        "int",
+        "    volatile char frame[4096];\n"
+        "    if (depth < 0) /* never true; a base case keeps -Winfinite-recursion quiet */\n"
+        "        return 0;\n"
+        "    frame[0] = (char)depth;\n"
+        "    return {name}(depth + 1) + frame[0]; /* unbounded recursion: SIGSEGV on the guard page */\n",
+    ),
+    FaultKind.ABORT: (
+        "void",
+        '    fprintf(stderr, "{name}: giving up\\n");\n'
+        "    abort(); /* SIGABRT */\n",
+    ),
+}
+
+_RUST_BODIES: Dict[FaultKind, Tuple[str, str]] = {
+    FaultKind.NULL_DEREF: (
+        "",
+        "    let ptr: *mut i32 = std::hint::black_box(std::ptr::null_mut());\n"
+        "    // Debug builds check the pointer and panic with \"null pointer dereference\";\n"
+        "    // release builds write to address 0 and get SIGSEGV.\n"
+        "    unsafe { *ptr = 42 };\n",
+    ),
+    FaultKind.DIVIDE_BY_ZERO: (
+        " -> i32",
+        "    let divisor = std::hint::black_box(0);\n"
+        "    // \"attempt to divide by zero\"; panic = \"abort\" turns it into SIGABRT.\n"
+        "    42 / divisor\n",
+    ),
+    FaultKind.STACK_OVERFLOW: (
+        " -> u64",
+        "    let frame = std::hint::black_box([depth as u8; 4096]);\n"
+        "    // Unbounded recursion: Rust reports the stack overflow and aborts.\n"
+        "    {name}(depth + 1) + frame[0] as u64\n",
+    ),
+    FaultKind.ABORT: (
+        "",
+        '    panic!("{name}: giving up"); // panic = "abort": SIGABRT\n',
+    ),
+    FaultKind.RUST_PANIC: (
+        " -> u32",
+        "    let value: Option<u32> = std::hint::black_box(None);\n"
+        "    // \"called `Option::unwrap()` on a `None` value\"; panic = \"abort\" turns it into SIGABRT.\n"
+        "    value.unwrap()\n",
+    ),
+}
+
+
+@dataclass
+class Repro:
+    """Files of a reproduction program, relative to the directory they go in."""
+
+    fault_kind: FaultKind
+    language: Language
+    function: str
+    files: Dict[str, str] = field(default_factory=dict)
+    # Shell command that builds and runs it from that directory.
+    command: str = ""
+
+    def write(self, directory: str) -> List[str]:
+        """Write the files under ``directory`` (created if missing); returns their paths."""
+        paths = []
+        for name, text in sorted(self.files.items()):
+            path = os.path.join(directory, name)
+            os.makedirs(os.path.dirname(path), exist_ok=True)
+            with open(path, "w", encoding="utf-8") as fh:
+                fh.write(text)
+            paths.append(path)
+        return paths
+
+
+def repro_function(frames: List[Frame]) -> str:
+    """The innermost user function's name, reduced to an identifier both templates accept."""
+    for frame in frames:
+        if not frame.function or is_system_frame(frame) or any(m in frame.function for m in _RUNTIME_MARKERS):
+            continue
+        # ``crate::module::func``, ``Class::method(int)``, ``func+0x1f``: keep ``func`` / ``method``.
+        name = re.split(r"[(<+ ]", frame.function, maxsplit=1)[0].rsplit("::", 1)[-1]
+        if _IDENTIFIER_RE.match(name) and name not in _RESERVED and not name.startswith("__"):
+            return name
+    return DEFAULT_FUNCTION
+
+
+def _origin(report: CrashReport) -> str:
+    site = report.crash_site
+    if site is None:
+        return f"Reproduces a {report.fault_kind.label}."
+    where = site.function or "??"
+    if site.source_location:
+        where += f" ({site.source_location})"
+    return f"Reproduces the {report.fault_kind.label} in {where}."
+
+
+def _c_repro(report: CrashReport, name: str) -> Repro:
+    returns, body = _C_BODIES[report.fault_kind]
+    stack_overflow = report.fault_kind is FaultKind.STACK_OVERFLOW
+    params = "int depth" if stack_overflow else "void"
+    call = f"{name}(0)" if stack_overflow else f"{name}()"
+    source = (
+        f"/* {_origin(report)} */\n"
+        "#include <stdio.h>\n"
+        "#include <stdlib.h>\n"
+        "\n"
+        f"__attribute__((noinline)) static {returns} {name}({params}) {{\n"
+        f"{body.replace('{name}', name)}"
+        "}\n"
+        "\n"
+        "int main(void) {\n"
+        f'    printf("calling {name}...\\n");\n'
+        "    fflush(stdout);\n"
+        f"    {'(void)' if returns == 'int' else ''}{call};\n"
+        "    return EXIT_SUCCESS;\n"
+        "}\n"
+    )
+    makefile = (
+        "CC ?= gcc\n"
+        "CFLAGS ?= -g -O0 -Wall -Wextra\n"
+        "\n"
+        "all: repro\n"
+        "\n"
+        "repro: repro.c\n"
+        "\t$(CC) $(CFLAGS) -o $@ $<\n"
+        "\n"
+        "run: repro\n"
+        "\t./repro\n"
+        "\n"
+        "clean:\n"
+        "\trm -f repro\n"
+        "\n"
+        ".PHONY: all run clean\n"
+    )
+    return Repro(report.fault_kind, Language.C, name, {"repro.c": source, "Makefile": makefile}, "make run")
+
+
+def _rust_repro(report: CrashReport, name: str) -> Repro:
+    returns, body = _RUST_BODIES[report.fault_kind]
+    stack_overflow = report.fault_kind is FaultKind.STACK_OVERFLOW
+    params = "depth: u32" if stack_overflow else ""
+    call = f"{name}(0)" if stack_overflow else f"{name}()"
+    source = (
+        f"//! {_origin(report)}\n"
+        "\n"
+        "fn main() {\n"
+        f'    println!("calling {name}...");\n'
+        f"    {'let _ = ' if returns else ''}{call};\n"
+        "}\n"
+        "\n"
+        "#[inline(never)]\n"
+        f"{'#[allow(unconditional_recursion)]' + chr(10) if stack_overflow else ''}"
+        f"fn {name}({params}){returns} {{\n"
+        f"{body.replace('{name}', name)}"
+        "}\n"
+    )
+    manifest = (
+        "[package]\n"
+        'name = "repro"\n'
+        'version = "0.1.0"\n'
+        'edition = "2021"\n'
+        "\n"
+        "[profile.dev]\n"
+        "debug = 2\n"
+        '# Abort instead of unwinding, so the fault stops the process where a debugger can see it.\n'
+        'panic = "abort"\n'
+        "\n"
+        "[dependencies]\n"
+    )
+    return Repro(report.fault_kind, Language.RUST, name, {"src/main.rs": source, "Cargo.toml": manifest}, "cargo run")
+
+
+def build_repro(report: CrashReport) -> Optional[Repro]:
+    """A program that faults like ``report`` does, or None when its fault kind has no template."""
+    if report.fault_kind not in _C_BODIES and report.fault_kind not in _RUST_BODIES:
+        return None
+    name = repro_function(report.frames)
+    # A Rust panic has no C equivalent, whatever language detection made of the binary.
+    language = report.language if report.language not in (None, Language.UNKNOWN) else report.detected_language
+    if language is Language.RUST or report.fault_kind not in _C_BODIES:
+        return _rust_repro(report, name)
+    return _c_repro(report, name)
+
+
+__all__ = ["DEFAULT_FUNCTION", "Repro", "build_repro", "repro_function"]
@@ -0,0 +1,239 @@
+"""Keep every analysis in a local SQLite database, to look back over past crashes.
+
+`CrashStore.add` writes one row per `AnalysisReport`: when it was analyzed,
+the program, the fault kind, the function it crashed in (the first user
+frame's, else the top frame's), and the hash of its `crash_signature`, each an
+indexed column, plus the whole `AnalysisReport.to_dict` as JSON. The
+signature hash is the one `ExplanationCache` files its answers under, so two
+sessions count as the same crash exactly when they would share an explanation.
+`CrashStore.sessions` filters on those columns, newest first, and gives each
+session ``seen``: how many stored sessions share its signature.
+
+The database is created on first use; `SCHEMA_VERSION` is kept in SQLite's
+``user_version``, and a database written by a newer version is refused rather
+than misread.
+"""
+from __future__ import annotations
+
+import json
+import os
+import sqlite3
+import time
+from dataclasses import dataclass
+from typing import TYPE_CHECKING, Any, Dict, List, Optional
+
+from dbgcopilot.utils.config import user_data_dir
+
+from .cache import crash_signature, signature_hash
+
+if TYPE_CHECKING:
+    from .api import AnalysisReport
+
+SCHEMA_VERSION = 1
+# Rows `CrashStore.sessions` returns unless told otherwise.
+DEFAULT_LIMIT = 20
+
+_SCHEMA = """
+CREATE TABLE IF NOT EXISTS sessions (
+    id INTEGER PRIMARY KEY,
+    created REAL NOT NULL,
+    program TEXT,
+    outcome TEXT NOT NULL,
+    fault_kind TEXT NOT NULL,
+    function TEXT,
+    signature_hash TEXT NOT NULL,
+    signature TEXT NOT NULL,
+    report TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS sessions_created ON sessions (created);
+CREATE INDEX IF NOT EXISTS sessions_fault_kind ON sessions (fault_kind);
+CREATE INDEX IF NOT EXISTS sessions_function ON sessions (function);
+CREATE INDEX IF NOT EXISTS sessions_signature_hash ON sessions (signature_hash);
+"""
+_COLUMNS = "id, created, program, outcome, fault_kind, function, signature_hash, signature"
+
+
+def default_store_path() -> str:
+    """``history.sqlite3`` in the platform's data directory (see `user_data_dir`)."""
+    return os.path.join(user_data_dir(), "history.sqlite3")
+
+
+class StoreError(RuntimeError):
+    """The history database cannot be opened, read, or written."""
+
+
+@dataclass
+class StoredSession:
+    id: int
+    # Seconds since the epoch.
+    created: float
+    program: Optional[str]
+    outcome: str
+    fault_kind: str
+    function: Optional[str]
+    signature_hash: str
+    signature: str
+    # Stored sessions with the same ``signature_hash``, this one included.
+    seen: int = 1
+    # `AnalysisReport.to_dict` as stored; only `CrashStore.get` loads it.
+    report: Optional[Dict[str, Any]] = None
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {
+            "id": self.id,
+            "created": self.created,
+            "program": self.program,
+            "outcome": self.outcome,
+            "fault_kind": self.fault_kind,
+            "function": self.function,
+            "signature_hash": self.signature_hash,
+            "signature": self.signature,
+            "seen": self.seen,
+            "report": self.report,
+        }
+
+
+class CrashStore:
+    """Past analyses in a SQLite database at ``path`` (default: `default_store_path`)."""
+
+    def __init__(self, path: Optional[str] = None) -> None:
+        self.path = path or default_store_path()
+        self._db: Optional[sqlite3.Connection] = None
+
+    def _connect(self) -> sqlite3.Connection:
+        if self._db is not None:
+            return self._db
+        try:
+            if os.path.dirname(self.path):
+                os.makedirs(os.path.dirname(self.path), exist_ok=True)
+            db = sqlite3.connect(self.path)
+            version = db.execute("PRAGMA user_version").fetchone()[0]
+            if version > SCHEMA_VERSION:
+                db.close()
+                raise StoreError(f"{self.path} was written by a newer dbgcopilot (schema {version})")
+            db.executescript(_SCHEMA)
+            db.execute(f"PRAGMA user_version = {SCHEMA_VERSION}")
+        except (OSError, sqlite3.Error) as exc:
+            raise StoreError(f"{self.path}: {exc}") from exc
+        self._db = db
+        return db
+
+    def close(self) -> None:
+        if self._db is not None:
+            self._db.close()
+            self._db = None
+
+    def __enter__(self) -> "CrashStore":
+        return self
+
+    def __exit__(self, *exc: Any) -> None:
+        self.close()
+
+    def add(self, report: "AnalysisReport", program: Optional[str] = None, created: Optional[float] = None) -> int:
+        """Store ``report``, analyzed from ``program``, and return its session id."""
+        crash = report.crash_report()
+        user = crash.first_user_frame() or (crash.frames[0] if crash.frames else None)
+        signature = crash_signature(crash)
+        row = (
+            time.time() if created is None else created,
+            os.path.basename(program) if program else None,
+            report.outcome.value,
+            report.fault_kind.value,
+            user.function if user is not None else None,
+            signature_hash(signature),
+            signature,
+            json.dumps(report.to_dict()),
+        )
+        db = self._connect()
+        try:
+            with db:
+                cursor = db.execute(
+                    "INSERT INTO sessions (created, program, outcome, fault_kind, function, signature_hash, "
+                    "signature, report) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+                    row,
+                )
+        except sqlite3.Error as exc:
+            raise StoreError(f"{self.path}: {exc}") from exc
+        return int(cursor.lastrowid or 0)
+
+    def _query(self, sql: str, params: List[Any]) -> List[sqlite3.Row]:
+        db = self._connect()
+        db.row_factory = sqlite3.Row
+        try:
+            return db.execute(sql, params).fetchall()
+        except sqlite3.Error as exc:
+            raise StoreError(f"{self.path}: {exc}") from exc
+
+    def sessions(
+        self,
+        fault_kind: Optional[str] = None,
+        function: Optional[str] = None,
+        program: Optional[str] = None,
+        signature: Optional[str] = None,
+        since: Optional[float] = None,
+        limit: Optional[int] = DEFAULT_LIMIT,
+        unique: bool = False,
+    ) -> List[StoredSession]:
+        """Stored sessions, newest first, with their ``seen`` counts; the reports themselves are not loaded.
+
+        ``function`` matches a part of the name, ``signature`` a prefix of the
+        hash, and ``since`` is a time in seconds since the epoch. With
+        ``unique`` only the newest session of each signature is listed. A
+        ``limit`` of None or 0 lists them all.
+        """
+        where, params = [], []
+        if fault_kind:
+            where.append("fault_kind = ?")
+            params.append(fault_kind)
+        if function:
+            where.append("function LIKE ? ESCAPE '\\'")
+            params.append("%" + function.replace("\\", "\\\\").replace("%", "\\%").replace("_", "\\_") + "%")
+        if program:
+            where.append("program = ?")
+            params.append(os.path.basename(program))
+        if signature:
+            where.append("substr(signature_hash, 1, ?) = ?")
+            params += [len(signature), signature.lower()]
+        if since is not None:
+            where.append("created >= ?")
+            params.append(since)
+        if unique:
+            where.append("id = (SELECT MAX(id) FROM sessions AS s WHERE s.signature_hash = sessions.signature_hash)")
+        sql = (
+            f"SELECT {_COLUMNS}, (SELECT COUNT(*) FROM sessions AS s WHERE s.signature_hash = sessions.signature_hash)"
+            " AS seen FROM sessions"
+        )
+        if where:
+            sql += " WHERE " + " AND ".join(where)
+        sql += " ORDER BY created DESC, id DESC"
+        if limit:
+            sql += " LIMIT ?"
+            params.append(limit)
+        return [StoredSession(**dict(row)) for row in self._query(sql, params)]
+
+    def get(self, session_id: int) -> Optional[StoredSession]:
+        """Session ``session_id`` with its report, or None."""
+        rows = self._query(
+            f"SELECT {_COLUMNS}, report, (SELECT COUNT(*) FROM sessions AS s "
+            "WHERE s.signature_hash = sessions.signature_hash) AS seen FROM sessions WHERE id = ?",
+            [session_id],
+        )
+        if not rows:
+            return None
+        data = dict(rows[0])
+        data["report"] = json.loads(data["report"])
+        return StoredSession(**data)
+
+    def seen(self, signature_hash: str) -> int:
+        """Stored sessions whose signature hashes to ``signature_hash``."""
+        return int(self._query("SELECT COUNT(*) FROM sessions WHERE signature_hash = ?", [signature_hash])[0][0])
+
+
+__all__ = [
+    "CrashStore",
+    "DEFAULT_LIMIT",
+    "SCHEMA_VERSION",
+    "StoreError",
+    "StoredSession",
+    "default_store_path",
+]
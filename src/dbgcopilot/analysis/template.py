@@ -0,0 +1,173 @@
+"""Explanation prompts from a template file with named placeholders.
+
+The built-in prompt asks for a concise root cause and fix. A team that wants
+terser answers, another language, or text that pastes into its tracker can
+write its own template instead (``llm.prompt_template`` in dbgcopilot.toml, or
+``--prompt-template``). A template is plain text. `PromptBuilder` fills its
+``{name}`` placeholders from the `CrashReport`, after redaction and within the
+token budget:
+
+| Placeholder | Filled with |
+| --- | --- |
+| ``{report}`` | the whole report, as the built-in template sends it |
+| ``{faulting_frame}`` | the top frame and its locals |
+| ``{stack}`` | the backtrace, with elided frames summarized |
+| ``{backtrace}`` | the same as ``{stack}`` |
+| ``{threads}`` | the other threads' top frames |
+| ``{modules}`` | one line per loaded module, with its version |
+| ``{hang_kind}`` | the hang classification; empty for a crash |
+| ``{signal}`` | the signal name, e.g. ``SIGSEGV`` |
+| ``{classification}`` | the fault classification |
+| ``{fault_kind}`` | the kind of fault alone, e.g. ``null-pointer dereference`` |
+| ``{registers}`` | the faulting thread's registers, ``name=0x...`` |
+| ``{fault_context}`` | the faulting instruction and the disassembly around it |
+| ``{language_hint}`` | guidance for the target's language, if there is any |
+
+A line that holds only placeholders and whitespace is left out when they are
+all empty, so ``{hang_kind}`` on a line of its own costs nothing for a crash.
+Literal braces are written ``{{`` and ``}}``.
+
+Templates are checked when they are loaded: a misspelt placeholder is an
+error that names the line and the closest known placeholder, rather than an
+empty section in every prompt, and so is a template that uses none of the
+placeholders carrying the crash (``{language_hint}`` alone sends nothing
+about it). `DEFAULT_TEMPLATE` is the built-in prompt.
+"""
+from __future__ import annotations
+
+import difflib
+import hashlib
+import string
+from dataclasses import dataclass, field
+from typing import Any, List, Mapping, Optional, Tuple
+
+PLACEHOLDERS = (
+    "annotations",
+    "backtrace",
+    "classification",
+    "fault_context",
+    "fault_kind",
+    "faulting_frame",
+    "hang_kind",
+    "language_hint",
+    "modules",
+    "registers",
+    "report",
+    "signal",
+    "stack",
+    "threads",
+)
+# Placeholders that say nothing about the crash itself.
+_GUIDANCE_PLACEHOLDERS = frozenset({"language_hint"})
+
+DEFAULT_TEMPLATE = """\
+You are a debugging copilot. Explain the most likely root cause of this failure
+and suggest a fix. Be concise and refer to the frames below; cite file:line
+where a frame has one. The classification is a heuristic; verify it against the stack.
+{language_hint}
+
+{report}"""
+
+# A template line as (literal text, placeholder or None) pieces.
+_Line = List[Tuple[str, Optional[str]]]
+
+
+class TemplateError(ValueError):
+    """A prompt template cannot be read, or uses a placeholder that does not exist."""
+
+
+@dataclass
+class PromptTemplate:
+    """A parsed template; build one with `parse` or `load` so it is validated."""
+
+    text: str
+    source: str = "<default>"
+    _lines: List[_Line] = field(default_factory=list, repr=False, compare=False)
+
+    @classmethod
+    def parse(cls, text: str, source: str = "<template>") -> "PromptTemplate":
+        """Validate ``text``; raises TemplateError naming ``source`` and the line of the first problem."""
+        lines: List[_Line] = []
+        formatter = string.Formatter()
+        for number, raw in enumerate(text.split("\n"), 1):
+            where = f"{source}, line {number}"
+            try:
+                pieces = list(formatter.parse(raw))
+            except ValueError as exc:
+                raise TemplateError(f"{where}: {exc} (write literal braces as {{{{ and }}}})") from None
+            line: _Line = []
+            for literal, name, spec, conversion in pieces:
+                if name is None:
+                    line.append((literal, None))
+                    continue
+                if not name:
+                    raise TemplateError(f"{where}: {{}} needs a placeholder name; known: {_known()}")
+                if name not in PLACEHOLDERS:
+                    close = difflib.get_close_matches(name, PLACEHOLDERS, n=1)
+                    hint = f"did you mean {{{close[0]}}}? " if close else ""
+                    raise TemplateError(f"{where}: unknown placeholder {{{name}}}; {hint}known: {_known()}")
+                if spec or conversion:
+                    raise TemplateError(f"{where}: {{{name}}} takes no format spec or conversion")
+                line.append((literal, name))
+            lines.append(line)
+        template = cls(text, source, lines)
+        if not set(template.placeholders) - _GUIDANCE_PLACEHOLDERS:
+            problem = "uses no crash placeholder, so no crash data would be sent"
+            raise TemplateError(f"{source}: {problem}; known: {_known()}")
+        return template
+
+    @classmethod
+    def load(cls, path: str) -> "PromptTemplate":
+        try:
+            with open(path, encoding="utf-8") as fh:
+                text = fh.read()
+        except (OSError, UnicodeDecodeError) as exc:
+            raise TemplateError(f"cannot read prompt template {path}: {exc}") from None
+        return cls.parse(text, path)
+
+    @property
+    def placeholders(self) -> List[str]:
+        """The placeholders used, in order of first use."""
+        used: List[str] = []
+        for line in self._lines:
+            used.extend(name for _, name in line if name is not None and name not in used)
+        return used
+
+    @property
+    def digest(self) -> str:
+        """Short hash of the text, so cached explanations are kept apart per template."""
+        return hashlib.sha256(self.text.encode("utf-8")).hexdigest()[:12]
+
+    def render(self, values: Mapping[str, str]) -> str:
+        out: List[str] = []
+        for line in self._lines:
+            filled = [(literal, values.get(name, "") if name is not None else "") for literal, name in line]
+            placeholders_only = all(not literal.strip() for literal, _ in filled)
+            if placeholders_only and any(name for _, name in line) and not any(value for _, value in filled):
+                continue
+            out.append("".join(literal + value for literal, value in filled))
+        return "\n".join(out)
+
+
+def _known() -> str:
+    return ", ".join(f"{{{name}}}" for name in PLACEHOLDERS)
+
+
+def template_from_config(config: Optional[Mapping[str, Any]]) -> PromptTemplate:
+    """The template named by the ``prompt_template`` config key, or the built-in one."""
+    path = (config or {}).get("prompt_template")
+    return PromptTemplate.load(str(path)) if path else DEFAULT_PROMPT_TEMPLATE
+
+
+# The built-in prompt, parsed.
+DEFAULT_PROMPT_TEMPLATE = PromptTemplate.parse(DEFAULT_TEMPLATE, "<default>")
+
+
+__all__ = [
+    "DEFAULT_PROMPT_TEMPLATE",
+    "DEFAULT_TEMPLATE",
+    "PLACEHOLDERS",
+    "PromptTemplate",
+    "TemplateError",
+    "template_from_config",
+]
@@ -0,0 +1,112 @@
+"""Compact text for the threads that did not fault.
+
+A deadlock or a starved worker pool is explained by the threads around the
+crash as much as by the one that faulted, but fifty full stacks drown the one
+that matters. `summarize_threads` renders every other thread at a reduced
+depth (``OTHER_THREAD_FRAMES`` by default) and folds threads whose shown
+stacks are identical, such as idle pool workers, into one entry. Reports and
+JSON keep every thread's full stack; only text and prompts are summarized.
+
+How much of each stack is captured at all is `limit_threads`' job: every
+capture path unwinds at most ``max_frames_per_thread`` frames
+(``DEFAULT_MAX_FRAMES_PER_THREAD``, 0 for no limit), and a stack cut there is
+marked `ThreadBacktrace.truncated`, so deep recursion does not flood the
+report and the prompt does not pretend the outermost frame is ``main``.
+"""
+from __future__ import annotations
+
+import dataclasses
+from typing import Any, Dict, List, Optional, Sequence, Tuple
+
+from .model import Frame, ThreadBacktrace
+
+# Frames shown for each thread other than the faulting one.
+OTHER_THREAD_FRAMES = 3
+# Frames captured per thread, the faulting one included; 0 means every frame.
+DEFAULT_MAX_FRAMES_PER_THREAD = 64
+
+
+def limit_threads(threads: Sequence[ThreadBacktrace], limit: int) -> List[ThreadBacktrace]:
+    """``threads`` with each stack cut to its top ``limit`` frames, and marked truncated where one was cut."""
+    if limit <= 0:
+        return list(threads)
+    return [
+        dataclasses.replace(t, frames=t.frames[:limit], truncated=True) if len(t.frames) > limit else t
+        for t in threads
+    ]
+
+
+def find_faulting_thread(
+    threads: Sequence[ThreadBacktrace], thread_id: Optional[int], frames: Sequence[Frame]
+) -> Optional[ThreadBacktrace]:
+    """The entry of ``threads`` that faulted.
+
+    The one the capture path marked `ThreadBacktrace.faulting` wins, then the
+    one with OS thread id ``thread_id``. Failing both, it is the thread whose
+    stack starts at ``frames[0]``, but only if no other thread's does: workers
+    running the same code stop at the same pc, and a guess between them would
+    put the wrong thread's state in front of the model.
+    """
+    marked = [t for t in threads if t.faulting]
+    if len(marked) == 1:
+        return marked[0]
+    if thread_id is not None:
+        for thread in threads:
+            if thread.tid == thread_id:
+                return thread
+    top = frames[0] if frames else None
+    if top is not None and top.pc is not None:
+        same = [t for t in threads if t.frames and t.frames[0].pc == top.pc]
+        if len(same) == 1:
+            return same[0]
+    return None
+
+
+def _stack_key(frames: Sequence[Frame]) -> Tuple[Any, ...]:
+    return tuple((f.function, f.module) if f.function else (f.pc, f.module) for f in frames)
+
+
+def _header(group: List[ThreadBacktrace]) -> str:
+    if len(group) > 1:
+        return f"Threads {', '.join(str(t.thread_id) for t in group)} ({len(group)} threads, same stack):"
+    thread = group[0]
+    details = [f"LWP {thread.tid}"] if thread.tid is not None else []
+    if thread.name:
+        details.append(f'"{thread.name}"')
+    return f"Thread {thread.thread_id}" + (f" ({', '.join(details)})" if details else "") + ":"
+
+
+def summarize_threads(
+    threads: Sequence[ThreadBacktrace],
+    faulting: Optional[ThreadBacktrace] = None,
+    depth: int = OTHER_THREAD_FRAMES,
+) -> List[str]:
+    """One header per thread (or group of identical threads) and its top ``depth`` frames, ``faulting`` left out."""
+    groups: Dict[Tuple[Any, ...], List[ThreadBacktrace]] = {}
+    for thread in threads:
+        if thread is faulting:
+            continue
+        key = (len(thread.frames), thread.truncated, _stack_key(thread.frames[:depth]))
+        groups.setdefault(key, []).append(thread)
+    lines: List[str] = []
+    for group in groups.values():
+        lines.append(_header(group))
+        frames = group[0].frames
+        lines.extend(f"  {f.describe()}" for f in frames[:depth])
+        if len(frames) > depth:
+            cut = ", and deeper ones not captured" if group[0].truncated else ""
+            lines.append(f"  ... {len(frames) - depth} more frames{cut}")
+        elif group[0].truncated:
+            lines.append("  ... deeper frames not captured")
+        elif not frames:
+            lines.append("  (no stack)")
+    return lines
+
+
+__all__ = [
+    "DEFAULT_MAX_FRAMES_PER_THREAD",
+    "OTHER_THREAD_FRAMES",
+    "find_faulting_thread",
+    "limit_threads",
+    "summarize_threads",
+]
@@ -0,0 +1,109 @@
+"""Tell V8 and Node.js internals apart from the JavaScript that called into them.
+
+A native crash in a Node process unwinds through frames of several owners:
+the V8 engine (``v8::internal::...``), its builtins and interpreter
+(``Builtins_*``, code V8 generates at build time and links into ``node``),
+Node's own C++ (``node::...``), the libuv event loop (``uv_run``), and the
+script itself. Only the script is the program's code, and the debugger cannot
+name it: JIT-compiled functions have no symbols, so their frames are bare
+addresses outside every module, and each function the interpreter runs shows
+as one ``Builtins_InterpreterEntryTrampoline`` frame.
+
+`label_engine_frames` sets `Frame.engine` to one of these owners, so the
+backtrace reads ``#4 0x... node!v8::internal::Invoke(...) [V8 internals]``
+and the prompt can tell the model which frames not to explain as user code.
+Native addons (``*.node`` modules) are labelled too: they are native code the
+program brought with it. Stacks without a V8 or Node frame are left alone, so
+a bare address in a C program stays unlabelled.
+
+Naming the JavaScript functions needs V8's own frame walker (``jst`` in its
+gdb macros) and is not attempted.
+"""
+from __future__ import annotations
+
+import dataclasses
+import os
+from enum import Enum
+from typing import Iterable, List, Optional
+
+from .model import Frame
+
+# Mangled spellings too, for frames the debugger did not demangle.
+_V8_PREFIXES = ("v8::", "_ZN2v8")
+_NODE_PREFIXES = ("node::", "_ZN4node")
+_LIBUV_PREFIXES = ("uv_", "uv__")
+_BUILTIN_PREFIX = "Builtins_"
+# One frame per JavaScript function the Ignition interpreter is running.
+_INTERPRETER_ENTRY = "Builtins_InterpreterEntryTrampoline"
+_ADDON_SUFFIX = ".node"
+
+
+class EngineRole(str, Enum):
+    V8 = "v8"
+    BUILTIN = "v8_builtin"
+    NODE = "node"
+    LIBUV = "libuv"
+    JAVASCRIPT = "javascript"
+    ADDON = "native_addon"
+
+    @property
+    def label(self) -> str:
+        return _LABELS[self]
+
+
+_LABELS = {
+    EngineRole.V8: "V8 internals",
+    EngineRole.BUILTIN: "V8 builtin",
+    EngineRole.NODE: "Node.js runtime",
+    EngineRole.LIBUV: "libuv event loop",
+    EngineRole.JAVASCRIPT: "JavaScript",
+    EngineRole.ADDON: "native addon",
+}
+# The labels of frames that are the program's own, JavaScript or native.
+USER_ENGINE_LABELS = frozenset({EngineRole.JAVASCRIPT.label, EngineRole.ADDON.label})
+# Any of these on the stack makes it a V8 stack.
+_ENGINE_ROLES = (EngineRole.V8, EngineRole.BUILTIN, EngineRole.NODE, EngineRole.JAVASCRIPT)
+
+
+def engine_role(frame: Frame) -> Optional[EngineRole]:
+    """Which part of V8 or Node ``frame`` is in, by its symbol or module; None for other code and JIT frames."""
+    function = frame.function or ""
+    if function.startswith(_INTERPRETER_ENTRY):
+        return EngineRole.JAVASCRIPT
+    if function.startswith(_BUILTIN_PREFIX):
+        return EngineRole.BUILTIN
+    if function.startswith(_V8_PREFIXES):
+        return EngineRole.V8
+    if function.startswith(_NODE_PREFIXES):
+        return EngineRole.NODE
+    if function.startswith(_LIBUV_PREFIXES):
+        return EngineRole.LIBUV
+    if os.path.basename(frame.module or "").lower().endswith(_ADDON_SUFFIX):
+        return EngineRole.ADDON
+    return None
+
+
+def label_engine_frames(frames: Iterable[Frame]) -> List[Frame]:
+    """Copy ``frames`` with `Frame.engine` set on a V8 stack; see the module docstring.
+
+    On such a stack a frame with neither a symbol nor a module is JIT code,
+    and labelled JavaScript.
+    """
+    frames = list(frames)
+    roles = [engine_role(f) for f in frames]
+    if not any(role in _ENGINE_ROLES for role in roles):
+        return frames
+    out = []
+    for frame, role in zip(frames, roles):
+        if role is None and frame.function is None and frame.module is None and frame.pc is not None:
+            role = EngineRole.JAVASCRIPT
+        out.append(dataclasses.replace(frame, engine=role.label) if role is not None else frame)
+    return out
+
+
+__all__ = [
+    "EngineRole",
+    "USER_ENGINE_LABELS",
+    "engine_role",
+    "label_engine_frames",
+]
@@ -1,7 +1,12 @@
 """Debugger backend interface (POC)."""
 from __future__ import annotations
 
-from typing import Protocol
+from typing import List, Optional, Protocol, Sequence
+
+from dbgcopilot.analysis.disasm import DEFAULT_AFTER, DEFAULT_BEFORE, FaultContext
+from dbgcopilot.analysis.heap import HeapSummary
+from dbgcopilot.analysis.model import Frame, Module, Register, StopInfo, ThreadBacktrace, Variable
+from dbgcopilot.analysis.regions import MapEntry
 
 
 class DebuggerBackend(Protocol):
@@ -10,3 +15,123 @@ class DebuggerBackend(Protocol):
 
     def initialize_session(self) -> None:  # pragma: no cover
         ...
+
+
+class CrashBackend(Protocol):
+    """Non-interactive backend that captures a crash into normalized types.
+
+    Implementations drive the debugger in batch mode so the analyzer gets the
+    same ``Frame``/``Register`` objects regardless of which engine ran.
+    """
+
+    name: str
+
+    def load(self, program: str, core: Optional[str] = None, args: Sequence[str] = ()) -> None:  # pragma: no cover
+        ...
+
+    def run_to_fault(self) -> StopInfo:  # pragma: no cover
+        ...
+
+    def backtrace(self) -> List[Frame]:  # pragma: no cover
+        ...
+
+    def read_registers(self) -> List[Register]:  # pragma: no cover
+        ...
+
+    def modules(self) -> List[Module]:  # pragma: no cover
+        """Images loaded at the fault, based where each was mapped; empty if unknown."""
+        ...
+
+    def threads(self) -> List[ThreadBacktrace]:  # pragma: no cover
+        """Every thread's stack at the fault, the faulting one included; empty if unknown."""
+        ...
+
+    def sample_threads(self, pid: int) -> List[ThreadBacktrace]:  # pragma: no cover
+        """Briefly attach to ``pid``, return all thread stacks, then detach."""
+        ...
+
+    def run_at_fault(self, commands: Sequence[str]) -> List[str]:  # pragma: no cover
+        """Run the loaded program (or open its core) to the fault, then ``commands``; one output each."""
+        ...
+
+
+class DebugSession(Protocol):
+    """A dump or process opened by a `SessionBackend`.
+
+    Unlike `DebuggerBackend` (the interactive REPL wrapper), a session returns
+    normalized types, so analysis code works the same under GDB, LLDB, or CDB.
+    """
+
+    def run_command(self, cmd: str) -> str:  # pragma: no cover
+        ...
+
+    def stack_trace(self, thread: Optional[int] = None) -> List[Frame]:  # pragma: no cover
+        """Frames of ``thread`` (debugger numbering); None means the faulting/current thread."""
+        ...
+
+    def stop_info(self) -> StopInfo:  # pragma: no cover
+        ...
+
+    def read_registers(self) -> List[Register]:  # pragma: no cover
+        ...
+
+    def threads(self) -> List[ThreadBacktrace]:  # pragma: no cover
+        """Every thread with its stack, numbered as ``stack_trace(thread=...)`` expects."""
+        ...
+
+    def frame_locals(self, thread: Optional[int] = None, frame_index: int = 0) -> List[Variable]:  # pragma: no cover
+        """Arguments, then locals, of frame ``frame_index`` of ``thread`` (numbered as in `stack_trace`).
+
+        Variables the compiler optimized out are kept, with ``value`` set to ``Optimized.OUT``.
+        """
+        ...
+
+    def read_memory(self, address: int, size: int) -> Optional[bytes]:  # pragma: no cover
+        """Target memory; None (or fewer bytes) where it is not mapped or not in the dump."""
+        ...
+
+    def current_tid(self) -> Optional[int]:  # pragma: no cover
+        """OS thread id of the faulting/current thread, if the engine reports one."""
+        ...
+
+    def modules(self) -> List[Module]:  # pragma: no cover
+        """Images loaded in the target, as far as the engine lists them."""
+        ...
+
+    def heap_summary(self) -> HeapSummary:  # pragma: no cover
+        """Heap sizes, allocations by size, and corruption the engine reports.
+
+        An engine without heap data returns `HeapSummary.unsupported`, never made-up numbers.
+        """
+        ...
+
+    def memory_map(self) -> List[MapEntry]:  # pragma: no cover
+        """The target's mapped ranges, with permissions and mapped files where the engine lists them.
+
+        Raises NotImplementedError on engines that do not list them.
+        """
+        ...
+
+    def fault_context(
+        self, before: int = DEFAULT_BEFORE, after: int = DEFAULT_AFTER
+    ) -> Optional[FaultContext]:  # pragma: no cover
+        """The faulting thread's registers and ``before``/``after`` instructions around its pc.
+
+        None when the thread has no registers; engines that cannot disassemble leave the instructions empty.
+        """
+        ...
+
+    def close(self) -> None:  # pragma: no cover
+        ...
+
+
+class SessionBackend(Protocol):
+    """Debugger engine selectable at runtime; see `backends.batch.detect_session_backend`."""
+
+    name: str
+
+    def attach(self, pid: int) -> DebugSession:  # pragma: no cover
+        ...
+
+    def load_dump(self, path: str, program: Optional[str] = None) -> DebugSession:  # pragma: no cover
+        ...
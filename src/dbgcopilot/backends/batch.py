@@ -0,0 +1,205 @@
+"""Shared helpers for batch-mode crash capture backends."""
+from __future__ import annotations
+
+import shutil
+import subprocess
+import sys
+from typing import Any, List, Optional, Sequence
+
+from dbgcopilot.analysis.disasm import DEFAULT_AFTER, DEFAULT_BEFORE, FaultContext
+from dbgcopilot.analysis.heap import HeapSummary
+from dbgcopilot.analysis.model import Frame, Module, Register, StopInfo, ThreadBacktrace, Variable
+from dbgcopilot.analysis.regions import MapEntry
+from dbgcopilot.utils.process import kill_tree
+from dbgcopilot.utils.tools import DEBUGGER_INSTALL_DOC
+
+
+class BackendUnavailableError(RuntimeError):
+    """Raised when no supported debugger executable can be found."""
+
+
+def run_batch(argv: Sequence[str], timeout: Optional[float]) -> tuple[str, bool]:
+    """Run a batch debugger invocation; return (combined output, timed_out).
+
+    On timeout the debugger and everything it started (the inferior
+    included) are killed and reaped.
+    """
+    proc = subprocess.Popen(
+        list(argv),
+        stdin=subprocess.DEVNULL,
+        stdout=subprocess.PIPE,
+        stderr=subprocess.STDOUT,
+        start_new_session=True,
+    )
+    try:
+        out, _ = proc.communicate(timeout=timeout)
+    except subprocess.TimeoutExpired:
+        kill_tree(proc)
+        out, _ = proc.communicate()
+        return (out or b"").decode("utf-8", errors="replace"), True
+    return (out or b"").decode("utf-8", errors="replace"), False
+
+
+def split_sections(text: str, marker_prefix: str) -> dict[str, str]:
+    """Split output on ``<marker_prefix><name>@@`` lines into named sections.
+
+    Text before the first marker is stored under ``"preamble"``.
+    """
+    sections: dict[str, List[str]] = {"preamble": []}
+    current = "preamble"
+    for line in (text or "").splitlines():
+        stripped = line.strip()
+        if stripped.startswith(marker_prefix) and stripped.endswith("@@"):
+            current = stripped[len(marker_prefix):-2]
+            sections.setdefault(current, [])
+            continue
+        sections[current].append(line)
+    return {name: "\n".join(lines) for name, lines in sections.items()}
+
+
+def detect_crash_backend(preferred: Optional[str] = None, timeout: float = 60.0) -> Any:
+    """Return the first available crash backend (GDB, then LLDB).
+
+    ``preferred`` may be ``"gdb"`` or ``"lldb"`` to force a specific engine.
+    Raises BackendUnavailableError with install guidance when nothing is found.
+    """
+    from .gdb_batch import GdbBatchBackend
+    from .lldb_batch import LldbBatchBackend
+
+    candidates = {"gdb": GdbBatchBackend, "lldb": LldbBatchBackend}
+    if preferred:
+        key = preferred.lower()
+        if key not in candidates:
+            raise ValueError(f"Unknown crash backend: {preferred} (expected gdb or lldb)")
+        order = [key]
+    else:
+        order = ["gdb", "lldb"]
+    for key in order:
+        cls = candidates[key]
+        if shutil.which(cls.executable):
+            return cls(timeout=timeout)
+    tried = ", ".join(candidates[k].executable for k in order)
+    raise BackendUnavailableError(
+        f"No supported debugger found on PATH (tried: {tried}). "
+        f"See {DEBUGGER_INSTALL_DOC} for install instructions."
+    )
+
+
+class BatchSession:
+    """A dump or live process that a batch debugger is re-run against per request.
+
+    Batch engines keep nothing between invocations, so each call re-opens the
+    dump (or briefly re-attaches to the pid and detaches again). Subclasses
+    provide ``_run`` plus the engine-specific stack/stop/register commands.
+    """
+
+    def __init__(
+        self,
+        backend: Any,
+        *,
+        dump: Optional[str] = None,
+        program: Optional[str] = None,
+        pid: Optional[int] = None,
+    ) -> None:
+        if (dump is None) == (pid is None):
+            raise ValueError("a session needs exactly one of dump= or pid=")
+        self.backend = backend
+        self.dump = dump
+        self.program = program
+        self.pid = pid
+        self.raw_output = ""
+        self.closed = False
+
+    def _run(self, commands: Sequence[str]) -> tuple[str, List[str]]:  # pragma: no cover
+        """Run ``commands`` in one debugger invocation; return (preamble, one output per command)."""
+        raise NotImplementedError
+
+    def run_commands(self, commands: Sequence[str]) -> List[str]:
+        if self.closed:
+            raise RuntimeError("session is closed")
+        return self._run(list(commands))[1]
+
+    def run_command(self, cmd: str) -> str:
+        return self.run_commands([cmd])[0]
+
+    def stack_trace(self, thread: Optional[int] = None) -> List[Frame]:  # pragma: no cover
+        raise NotImplementedError
+
+    def stop_info(self) -> StopInfo:  # pragma: no cover
+        raise NotImplementedError
+
+    def read_registers(self) -> List[Register]:  # pragma: no cover
+        raise NotImplementedError
+
+    def threads(self) -> List[ThreadBacktrace]:  # pragma: no cover
+        raise NotImplementedError
+
+    def frame_locals(self, thread: Optional[int] = None, frame_index: int = 0) -> List[Variable]:  # pragma: no cover
+        raise NotImplementedError
+
+    def read_memory(self, address: int, size: int) -> Optional[bytes]:  # pragma: no cover
+        raise NotImplementedError
+
+    def current_tid(self) -> Optional[int]:  # pragma: no cover
+        raise NotImplementedError
+
+    def modules(self) -> List[Module]:  # pragma: no cover
+        raise NotImplementedError
+
+    def heap_summary(self) -> HeapSummary:  # pragma: no cover
+        raise NotImplementedError
+
+    def memory_map(self) -> List[MapEntry]:
+        raise NotImplementedError("this engine does not list the memory map")
+
+    def fault_context(self, before: int = DEFAULT_BEFORE, after: int = DEFAULT_AFTER) -> Optional[FaultContext]:
+        raise NotImplementedError("this engine does not disassemble")
+
+    def close(self) -> None:
+        self.closed = True
+
+    def __enter__(self) -> "BatchSession":
+        return self
+
+    def __exit__(self, *_exc: object) -> None:
+        self.close()
+
+
+def _default_session_order() -> List[str]:
+    if sys.platform == "win32":
+        return ["cdb", "lldb", "gdb"]
+    if sys.platform == "darwin":
+        return ["lldb", "gdb"]
+    return ["gdb", "lldb"]
+
+
+def detect_session_backend(preferred: Optional[str] = None, timeout: float = 60.0) -> Any:
+    """Return the first available session backend for this platform.
+
+    Windows tries CDB (DbgEng) first, macOS LLDB, everything else GDB.
+    ``preferred`` may be ``"gdb"``, ``"lldb"``, or ``"cdb"``.
+    """
+    from .cdb_batch import CdbBackend
+    from .gdb_batch import GdbBatchBackend
+    from .lldb_batch import LldbBatchBackend
+
+    candidates = {"gdb": GdbBatchBackend, "lldb": LldbBatchBackend, "cdb": CdbBackend}
+    if preferred:
+        key = preferred.lower()
+        if key in {"windbg", "dbgeng"}:
+            key = "cdb"
+        if key not in candidates:
+            raise ValueError(f"Unknown debugger backend: {preferred} (expected gdb, lldb, or cdb)")
+        order = [key]
+    else:
+        order = _default_session_order()
+    for key in order:
+        cls = candidates[key]
+        path = cls.find_executable() if hasattr(cls, "find_executable") else shutil.which(cls.executable)
+        if path:
+            return cls(path, timeout=timeout)
+    tried = ", ".join(candidates[k].executable for k in order)
+    raise BackendUnavailableError(
+        f"No supported debugger found (tried: {tried}). "
+        f"See {DEBUGGER_INSTALL_DOC} for install instructions."
+    )
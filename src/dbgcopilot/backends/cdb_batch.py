@@ -0,0 +1,154 @@
+"""CDB (Debugging Tools for Windows / DbgEng) batch sessions.
+
+Opens a minidump with ``cdb -z`` or attaches non-invasively with ``cdb -pv -p``,
+runs a ``;``-separated command list given via ``-c``, then quits. Commands are
+bracketed with ``.echo`` markers so their outputs can be split apart.
+"""
+from __future__ import annotations
+
+import os
+import re
+import shutil
+from typing import Dict, List, Optional, Sequence
+
+from dbgcopilot.analysis.disasm import DEFAULT_AFTER, DEFAULT_BEFORE, FaultContext, build_fault_context
+from dbgcopilot.analysis.heap import HeapSummary, cdb_heap_summary
+from dbgcopilot.analysis.model import Frame, Module, Register, StopInfo, ThreadBacktrace, Variable
+from dbgcopilot.analysis.parsers import (
+    parse_cdb_backtrace,
+    parse_cdb_current_tid,
+    parse_cdb_disassembly,
+    parse_cdb_exception,
+    parse_cdb_locks,
+    parse_cdb_memory,
+    parse_cdb_modules,
+    parse_cdb_registers,
+    parse_cdb_thread_backtraces,
+    parse_cdb_variables,
+)
+from dbgcopilot.analysis.registers import Registers
+
+from .batch import BatchSession, run_batch, split_sections
+
+_MARKER = "@@dbgcopilot:"
+_IP_RE = re.compile(r"\$ip=(?P<ip>[0-9a-fA-F`]+)")
+# Frames requested from `kn`; CDB's default of 20 often cuts Rust/C++ stacks short.
+STACK_DEPTH = 100
+
+
+class CdbBackend:
+    name = "cdb"
+    executable = "cdb"
+
+    def __init__(self, cdb_path: Optional[str] = None, timeout: float = 120.0, symbol_path: Optional[str] = None) -> None:
+        self.cdb_path = cdb_path or self.find_executable() or self.executable
+        self.timeout = timeout
+        # Falls back to _NT_SYMBOL_PATH, which cdb reads itself.
+        self.symbol_path = symbol_path
+
+    @classmethod
+    def find_executable(cls) -> Optional[str]:
+        """cdb on PATH, else the default Windows SDK "Debugging Tools" install."""
+        found = shutil.which(cls.executable)
+        if found:
+            return found
+        roots = [os.environ.get("ProgramFiles(x86)"), os.environ.get("ProgramFiles")]
+        for root in filter(None, roots):
+            for arch in ("x64", "arm64", "x86"):
+                candidate = os.path.join(root, "Windows Kits", "10", "Debuggers", arch, "cdb.exe")
+                if os.path.isfile(candidate):
+                    return candidate
+        return None
+
+    def attach(self, pid: int) -> "CdbSession":
+        return CdbSession(self, pid=pid)
+
+    def load_dump(self, path: str, program: Optional[str] = None) -> "CdbSession":
+        return CdbSession(self, dump=path, program=program)
+
+
+class CdbSession(BatchSession):
+    """Minidump or live process inspected through cdb."""
+
+    backend: CdbBackend
+
+    def _run(self, commands: Sequence[str]) -> tuple[str, List[str]]:
+        argv = [self.backend.cdb_path, "-lines"]
+        if self.backend.symbol_path:
+            argv += ["-y", self.backend.symbol_path]
+        if self.pid is not None:
+            # -pv: non-invasive, the process keeps running once cdb detaches.
+            argv += ["-pv", "-p", str(self.pid)]
+            quit_cmd = "qd"
+        else:
+            if self.program:
+                argv += ["-i", os.path.dirname(os.path.abspath(self.program))]
+            argv += ["-z", self.dump or ""]
+            quit_cmd = "q"
+        script: List[str] = []
+        for i, cmd in enumerate(commands):
+            script += [f".echo {_MARKER}{i}@@", cmd]
+        script += [f".echo {_MARKER}end@@", quit_cmd]
+        argv += ["-c", "; ".join(script)]
+        output, timed_out = run_batch(argv, self.backend.timeout)
+        self.raw_output = output
+        if timed_out:
+            raise RuntimeError(f"cdb timed out after {self.backend.timeout:g}s")
+        sections = split_sections(output, _MARKER)
+        return sections.get("preamble", ""), [sections.get(str(i), "") for i in range(len(commands))]
+
+    def _context_cmd(self, thread: Optional[int]) -> str:
+        if thread is not None:
+            return f"~{thread}s"
+        # In a dump, switch to the exception context so the stack is the faulting one.
+        return ".ecxr" if self.dump else "~."
+
+    def stack_trace(self, thread: Optional[int] = None) -> List[Frame]:
+        outputs = self.run_commands([self._context_cmd(thread), "r $ip", f"kn {STACK_DEPTH}"])
+        m = _IP_RE.search(outputs[1])
+        ip = int(m.group("ip").replace("`", ""), 16) if m else None
+        return parse_cdb_backtrace(outputs[2], ip=ip)
+
+    def stop_info(self) -> StopInfo:
+        return parse_cdb_exception(self.run_command(".exr -1"))
+
+    def read_registers(self) -> List[Register]:
+        return parse_cdb_registers(self.run_commands([self._context_cmd(None), "r"])[1])
+
+    def threads(self) -> List[ThreadBacktrace]:
+        return parse_cdb_thread_backtraces(self.run_command(f"~*kn {STACK_DEPTH}"))
+
+    def frame_locals(self, thread: Optional[int] = None, frame_index: int = 0) -> List[Variable]:
+        outputs = self.run_commands([self._context_cmd(thread), f".frame {frame_index:x}", "dv /i /t"])
+        variables = parse_cdb_variables(outputs[2])
+        # `dv` lists locals and parameters in declaration order; arguments first, as elsewhere.
+        return [v for v in variables if v.argument] + [v for v in variables if not v.argument]
+
+    def read_memory(self, address: int, size: int) -> Optional[bytes]:
+        return parse_cdb_memory(self.run_command(f"db {address:x} L{size:x}"))
+
+    def current_tid(self) -> Optional[int]:
+        # A dump opens on the thread that raised the exception.
+        return parse_cdb_current_tid(self.run_command("~."))
+
+    def modules(self) -> List[Module]:
+        return parse_cdb_modules(self.run_command("lm"))
+
+    def heap_summary(self) -> HeapSummary:
+        # ``-h 0``: busy blocks grouped by size, for every heap.
+        return cdb_heap_summary(*self.run_commands(["!heap -s", "!heap -stat -h 0"]))
+
+    def fault_context(self, before: int = DEFAULT_BEFORE, after: int = DEFAULT_AFTER) -> Optional[FaultContext]:
+        # `ub` disassembles backwards from the pc, `u` forwards from it; counts are hex, like every cdb number.
+        commands = [self._context_cmd(None), "r", f"ub @$ip L{before:x}", f"u @$ip L{after + 1:x}"]
+        _, regs, preceding, following = self.run_commands(commands)
+        registers = parse_cdb_registers(regs)
+        ip = Registers.from_list(registers).instruction_pointer
+        return build_fault_context(registers, parse_cdb_disassembly(preceding + "\n" + following, ip), before, after)
+
+    def lock_owners(self) -> Dict[int, int]:
+        """Owner OS thread id of each locked critical section, by address (`dbgcopilot.analysis.deadlock`)."""
+        return parse_cdb_locks(self.run_command("!locks"))
+
+
+__all__ = ["CdbBackend", "CdbSession", "STACK_DEPTH"]
@@ -0,0 +1,340 @@
+"""GDB batch backend for one-shot crash capture.
+
+Runs `gdb --batch` once per capture and slices the output into sections
+(stop event, backtrace, registers, mappings, glibc's heap counters, the
+disassembly around the pc, the top frames' variables) that are parsed into
+normalized types. On a live launch
+the variables are also checked against GDB's pretty-printers; see
+`pretty_printers`.
+"""
+from __future__ import annotations
+
+import os
+from typing import List, Optional, Sequence
+
+from dbgcopilot.analysis.disasm import DEFAULT_AFTER, DEFAULT_BEFORE, FaultContext, build_fault_context
+from dbgcopilot.analysis.heap import HeapSummary, gdb_heap_summary
+from dbgcopilot.analysis.model import (
+    DEFAULT_LOCALS_FRAMES,
+    Frame,
+    Module,
+    Register,
+    StopInfo,
+    ThreadBacktrace,
+    Variable,
+)
+from dbgcopilot.analysis.parsers import (
+    parse_disassembly,
+    parse_gdb_backtrace,
+    parse_gdb_current_thread,
+    parse_gdb_current_tid,
+    parse_gdb_memory,
+    parse_gdb_memory_map,
+    parse_gdb_proc_mappings,
+    parse_gdb_registers,
+    parse_gdb_shared_libraries,
+    parse_gdb_stop,
+    parse_gdb_thread_backtraces,
+    parse_gdb_variables,
+    parse_gdb_whatis,
+)
+from dbgcopilot.analysis.regions import MapEntry
+
+from .batch import BatchSession, run_batch, split_sections
+from . import pretty_printers, signal_passthrough
+
+_MARKER = "@@dbgcopilot:"
+# What `parse_gdb_stop` reads after the stop line: the fault address, the si_code, and
+# the faulting instruction (whether it read or wrote), as (section name, command).
+_STOP_SECTIONS = (
+    ("siginfo", "print $_siginfo._sifields._sigfault.si_addr"),
+    ("sicode", 'printf "si_code=%d\\n", $_siginfo.si_code'),
+    ("insn", "x/i $pc"),
+)
+# What `gdb_heap_summary` reads besides the mappings; both need glibc's symbols.
+_HEAP_SECTIONS = (
+    ("arena", "print main_arena.system_mem"),
+    ("mmapped", "print mp_.mmapped_mem"),
+)
+
+def _disassembly_commands(after: int) -> List[str]:
+    # The faulting function, to cut the window from; then the instructions at the pc, for code without symbols.
+    return ["disassemble", f"x/{after + 1}i $pc"]
+
+
+_DISASSEMBLY_SECTIONS = tuple(zip(("disasm", "insns"), _disassembly_commands(DEFAULT_AFTER)))
+# What `run_to_fault` collects.
+_CAPTURE_SECTIONS = _STOP_SECTIONS + (
+    ("bt", "bt"),
+    ("threads", "thread apply all bt"),
+    # The ``*`` row is the thread that reported the stop, whichever of several running threads it was.
+    ("current", "info threads"),
+    ("regs", "info registers"),
+    ("maps", "info proc mappings"),
+) + _HEAP_SECTIONS + _DISASSEMBLY_SECTIONS
+
+
+def _fault_context(registers: str, function: str, at_pc: str, before: int, after: int) -> Optional[FaultContext]:
+    instructions = parse_disassembly(function) or parse_disassembly(at_pc)
+    return build_fault_context(parse_gdb_registers(registers), instructions, before, after)
+
+
+class GdbBatchBackend:
+    name = "gdb"
+    executable = "gdb"
+
+    def __init__(
+        self, gdb_path: Optional[str] = None, timeout: float = 60.0, locals_frames: int = DEFAULT_LOCALS_FRAMES
+    ) -> None:
+        self.gdb_path = gdb_path or self.executable
+        self.timeout = timeout
+        # Frames from the top whose arguments and locals `run_to_fault` prints.
+        self.locals_frames = locals_frames
+        # Signals passed to the program's own handlers instead of ending the run; see `signal_passthrough`.
+        self.ignore_signals: List[str] = []
+        # Directories whose .build-id trees gdb searches for separate debug files (a symbol path's); empty keeps
+        # gdb's own debug-file-directory.
+        self.debug_dirs: List[str] = []
+        # Directories of pretty-printer scripts the program's .debug_gdb_scripts names (Rust's); see `pretty_printers`.
+        self.printer_dirs: List[str] = []
+        self.program: Optional[str] = None
+        self.core: Optional[str] = None
+        self.args: List[str] = []
+        self.raw_output: str = ""
+        self._sections: Optional[dict[str, str]] = None
+        self._stop: Optional[StopInfo] = None
+
+    def load(self, program: str, core: Optional[str] = None, args: Sequence[str] = ()) -> None:
+        self.program = program
+        self.core = core
+        self.args = list(args)
+        self._sections = None
+        self._stop = None
+
+    def attach(self, pid: int) -> "GdbSession":
+        return GdbSession(self, pid=pid)
+
+    def load_dump(self, path: str, program: Optional[str] = None) -> "GdbSession":
+        return GdbSession(self, dump=path, program=program)
+
+    def _section_cmds(self, name: str, cmd: str) -> List[str]:
+        return ["-ex", f"echo \\n{_MARKER}{name}@@\\n", "-ex", cmd]
+
+    def _base_argv(self) -> List[str]:
+        argv = [self.gdb_path, "--batch", "-nx", "-q"]
+        for setting in ("set pagination off", "set confirm off", "set width 0", "set debuginfod enabled off"):
+            argv += ["-ex", setting]
+        if self.debug_dirs:
+            # Before the program is read, or its debug file is looked for in the old directories.
+            argv += ["-iex", f"set debug-file-directory {os.pathsep.join(self.debug_dirs)}"]
+        for directory in self.printer_dirs:
+            # What rust-gdb does: trust the scripts there, and find them on the source path.
+            argv += ["-iex", f"add-auto-load-safe-path {directory}", "-iex", f"directory {directory}"]
+        return argv
+
+    def _build_argv(self, sections: Sequence[tuple[str, str]] = ()) -> List[str]:
+        if not self.program:
+            raise RuntimeError("No program loaded; call load() first")
+        argv = self._base_argv()
+        if not self.core:
+            argv += self._run_cmds()
+        for name, cmd in sections or _CAPTURE_SECTIONS + self._locals_sections():
+            argv += self._section_cmds(name, cmd)
+        if not sections and not self.core and self.locals_frames:
+            argv += self._printed_cmds(self.locals_frames)
+        if self.core:
+            argv += [self.program, self.core]
+        else:
+            argv += ["--args", self.program, *self.args]
+        return argv
+
+    def _run_cmds(self) -> List[str]:
+        if not self.ignore_signals:
+            return ["-ex", "run"]
+        return [
+            "-ex", f"source {signal_passthrough.SCRIPT_PATH}",
+            "-ex", " ".join([signal_passthrough.COMMAND, *self.ignore_signals]),
+        ]
+
+    def _printed_cmds(self, frames: int) -> List[str]:
+        # Sourced inside the section, so a GDB without Python complains there and not into the last frame's locals.
+        return self._section_cmds("printed", f"source {pretty_printers.SCRIPT_PATH}") + [
+            "-ex", f"{pretty_printers.COMMAND} {frames}"
+        ]
+
+    def _locals_sections(self) -> tuple[tuple[str, str], ...]:
+        sections: tuple[tuple[str, str], ...] = ()
+        for i in range(self.locals_frames):
+            sections += ((f"frame{i}", f"frame {i}"), (f"args{i}", "info args"), (f"locals{i}", "info locals"))
+        return sections
+
+    def run_to_fault(self) -> StopInfo:
+        output, timed_out = run_batch(self._build_argv(), self.timeout)
+        self.raw_output = output
+        sections = split_sections(output, _MARKER)
+        self._sections = sections
+        if timed_out:
+            stop = StopInfo(description=f"no fault within {self.timeout:g}s; killed", timed_out=True)
+        else:
+            preamble = sections.get("preamble", "")
+            # The stops the program's handlers took come first; the one after them ended the run.
+            texts = [signal_passthrough.after_passed(preamble)] + [sections.get(name, "") for name, _ in _STOP_SECTIONS]
+            stop = parse_gdb_stop("\n".join(texts))
+            stop.handled_signals = signal_passthrough.passed_signals(preamble)
+        self._stop = stop
+        return stop
+
+    def run_at_fault(self, commands: Sequence[str]) -> List[str]:
+        """Rerun the program (or reopen its core) to the fault and return each command's output there."""
+        argv = self._build_argv([(str(i), cmd) for i, cmd in enumerate(commands)])
+        output, timed_out = run_batch(argv, self.timeout)
+        self.raw_output = output
+        if timed_out:
+            raise RuntimeError(f"gdb timed out after {self.timeout:g}s")
+        sections = split_sections(output, _MARKER)
+        return [sections.get(str(i), "") for i in range(len(commands))]
+
+    def _ensure_captured(self) -> dict[str, str]:
+        if self._sections is None:
+            self.run_to_fault()
+        return self._sections or {}
+
+    def backtrace(self) -> List[Frame]:
+        return parse_gdb_backtrace(self._ensure_captured().get("bt", ""))
+
+    def read_registers(self) -> List[Register]:
+        return parse_gdb_registers(self._ensure_captured().get("regs", ""))
+
+    def modules(self) -> List[Module]:
+        return parse_gdb_proc_mappings(self._ensure_captured().get("maps", ""))
+
+    def memory_map(self) -> List[MapEntry]:
+        return parse_gdb_memory_map(self._ensure_captured().get("maps", ""))
+
+    def threads(self) -> List[ThreadBacktrace]:
+        """Every thread's stack at the fault; the one that took the signal is marked faulting."""
+        sections = self._ensure_captured()
+        threads = parse_gdb_thread_backtraces(sections.get("threads", ""))
+        current = parse_gdb_current_thread(sections.get("current", ""))
+        for thread in threads:
+            thread.faulting = thread.thread_id == current
+        return threads
+
+    def heap_summary(self) -> HeapSummary:
+        """The heap at the fault, plus any heap corruption glibc printed before it; see `gdb_heap_summary`."""
+        sections = self._ensure_captured()
+        return gdb_heap_summary(
+            sections.get("maps", ""),
+            sections.get("arena", ""),
+            sections.get("mmapped", ""),
+            sections.get("preamble", ""),
+        )
+
+    def fault_context(self) -> Optional[FaultContext]:
+        """The registers and the instructions around the pc at the fault; see `dbgcopilot.analysis.disasm`."""
+        sections = self._ensure_captured()
+        texts = [sections.get(name, "") for name in ("regs", "disasm", "insns")]
+        return _fault_context(*texts, DEFAULT_BEFORE, DEFAULT_AFTER)
+
+    def frame_variables(self) -> List[List[Variable]]:
+        """Arguments, then locals, of each of the top ``locals_frames`` frames at the fault.
+
+        `info locals` prints no types, and asking for them would take another run; ``type_name`` stays None.
+        """
+        sections = self._ensure_captured()
+        printed = pretty_printers.parse_printed(sections.get("printed", "")) if not self.core else {}
+        frames = []
+        for i in range(self.locals_frames):
+            variables = parse_gdb_variables(sections.get(f"args{i}", ""), argument=True)
+            variables += parse_gdb_variables(sections.get(f"locals{i}", ""))
+            pretty_printers.mark_printed(variables, printed.get(i))
+            frames.append(variables)
+        return frames
+
+    def sample_threads(self, pid: int) -> List[ThreadBacktrace]:
+        """Attach to a live process, capture every thread's stack, and detach.
+
+        The process is only stopped while GDB prints the backtraces.
+        """
+        argv = self._base_argv() + ["-p", str(pid), "-ex", "thread apply all bt", "-ex", "detach"]
+        output, timed_out = run_batch(argv, self.timeout)
+        self.raw_output = output
+        if timed_out:
+            return []
+        return parse_gdb_thread_backtraces(output)
+
+
+class GdbSession(BatchSession):
+    """Core file or live process inspected through `gdb --batch`."""
+
+    backend: GdbBatchBackend
+
+    def _run(self, commands: Sequence[str]) -> tuple[str, List[str]]:
+        argv = self.backend._base_argv()
+        if self.pid is not None:
+            argv += ["-p", str(self.pid)]
+        for i, cmd in enumerate(commands):
+            argv += self.backend._section_cmds(str(i), cmd)
+        if self.pid is not None:
+            argv += ["-ex", "detach"]
+        elif self.program:
+            argv += [self.program, self.dump or ""]
+        else:
+            argv += ["-c", self.dump or ""]
+        output, timed_out = run_batch(argv, self.backend.timeout)
+        self.raw_output = output
+        if timed_out:
+            raise RuntimeError(f"gdb timed out after {self.backend.timeout:g}s")
+        sections = split_sections(output, _MARKER)
+        return sections.get("preamble", ""), [sections.get(str(i), "") for i in range(len(commands))]
+
+    def stack_trace(self, thread: Optional[int] = None) -> List[Frame]:
+        commands = ([f"thread {thread}"] if thread is not None else []) + ["bt"]
+        return parse_gdb_backtrace(self.run_commands(commands)[-1])
+
+    def stop_info(self) -> StopInfo:
+        preamble, outputs = self._run([cmd for _, cmd in _STOP_SECTIONS])
+        return parse_gdb_stop("\n".join([preamble, *outputs]))
+
+    def read_registers(self) -> List[Register]:
+        return parse_gdb_registers(self.run_command("info registers"))
+
+    def threads(self) -> List[ThreadBacktrace]:
+        return parse_gdb_thread_backtraces(self.run_command("thread apply all bt"))
+
+    def frame_locals(self, thread: Optional[int] = None, frame_index: int = 0) -> List[Variable]:
+        select = ([f"thread {thread}"] if thread is not None else []) + [f"frame {frame_index}"]
+        commands = select + ["info args", "info locals"]
+        if self.pid is not None:
+            # A live process is also checked against the pretty-printers; a core is read as it is.
+            commands += [f"source {pretty_printers.SCRIPT_PATH}", f"{pretty_printers.COMMAND} {frame_index + 1}"]
+        outputs = self.run_commands(commands)[len(select):]
+        variables = parse_gdb_variables(outputs[0], argument=True) + parse_gdb_variables(outputs[1])
+        if self.pid is not None:
+            pretty_printers.mark_printed(variables, pretty_printers.parse_printed(outputs[-1]).get(frame_index))
+        if variables:
+            # `info locals` prints no types; a second run asks for each one.
+            types = self.run_commands(select + [f"whatis {v.name}" for v in variables])[len(select):]
+            for variable, text in zip(variables, types):
+                variable.type_name = parse_gdb_whatis(text)
+        return variables
+
+    def read_memory(self, address: int, size: int) -> Optional[bytes]:
+        return parse_gdb_memory(self.run_command(f"x/{size}xb {address:#x}"))
+
+    def current_tid(self) -> Optional[int]:
+        return parse_gdb_current_tid(self.run_command("info threads"))
+
+    def modules(self) -> List[Module]:
+        # Shared libraries only; GDB does not list the executable here.
+        return parse_gdb_shared_libraries(self.run_command("info sharedlibrary"))
+
+    def memory_map(self) -> List[MapEntry]:
+        return parse_gdb_memory_map(self.run_command("info proc mappings"))
+
+    def heap_summary(self) -> HeapSummary:
+        preamble, outputs = self._run(["info proc mappings"] + [cmd for _, cmd in _HEAP_SECTIONS])
+        return gdb_heap_summary(*outputs, output=preamble)
+
+    def fault_context(self, before: int = DEFAULT_BEFORE, after: int = DEFAULT_AFTER) -> Optional[FaultContext]:
+        return _fault_context(*self.run_commands(["info registers", *_disassembly_commands(after)]), before, after)
@@ -0,0 +1,299 @@
+"""LLDB batch backend for one-shot crash capture.
+
+Runs `lldb --batch` with a fixed command list. LLDB echoes each command as
+`(lldb) <cmd>`, which is used to slice the output into sections. The
+faulting function is disassembled for `DebugSession.fault_context`. On a live
+launch the variables are also checked against LLDB's type summaries and
+synthetic children; see `pretty_printers`.
+"""
+from __future__ import annotations
+
+import os
+import shlex
+from typing import List, Optional, Sequence
+
+from dbgcopilot.analysis.disasm import DEFAULT_AFTER, DEFAULT_BEFORE, FaultContext, build_fault_context
+from dbgcopilot.analysis.heap import HeapSummary, heap_messages
+from dbgcopilot.analysis.model import (
+    DEFAULT_LOCALS_FRAMES,
+    Frame,
+    Module,
+    Register,
+    StopInfo,
+    ThreadBacktrace,
+    Variable,
+)
+from dbgcopilot.analysis.parsers import (
+    parse_disassembly,
+    parse_lldb_backtrace,
+    parse_lldb_current_tid,
+    parse_lldb_image_list,
+    parse_lldb_memory,
+    parse_lldb_registers,
+    parse_lldb_stop,
+    parse_lldb_thread_backtraces,
+    parse_lldb_variables,
+)
+
+from . import pretty_printers, signal_passthrough
+from .batch import BatchSession, run_batch
+
+
+def _slice_echoes(output: str, expected: Sequence[str]) -> tuple[List[str], List[str]]:
+    """Split on LLDB's ``(lldb) <cmd>`` echoes of ``expected``, in order: (text before the first, one output each)."""
+    chunks: List[List[str]] = [[] for _ in expected]
+    preamble: List[str] = []
+    current = -1
+    for line in output.splitlines():
+        stripped = line.strip()
+        nxt = current + 1
+        if nxt < len(expected) and stripped == f"(lldb) {expected[nxt]}":
+            current = nxt
+            continue
+        (chunks[current] if current >= 0 else preamble).append(line)
+    return preamble, ["\n".join(c) for c in chunks]
+
+
+def _printed_commands(frames: int) -> List[str]:
+    return [f"command script import {shlex.quote(pretty_printers.SCRIPT_PATH)}", f"{pretty_printers.COMMAND} {frames}"]
+
+
+class LldbBatchBackend:
+    name = "lldb"
+    executable = "lldb"
+
+    def __init__(
+        self, lldb_path: Optional[str] = None, timeout: float = 60.0, locals_frames: int = DEFAULT_LOCALS_FRAMES
+    ) -> None:
+        self.lldb_path = lldb_path or self.executable
+        self.timeout = timeout
+        # Frames from the top whose arguments and locals `run_to_fault` prints.
+        self.locals_frames = locals_frames
+        # Signals passed to the program's own handlers instead of ending the run; see `signal_passthrough`.
+        self.ignore_signals: List[str] = []
+        # Directories of Rust's lldb_lookup.py and lldb_commands, loaded before launch; see `pretty_printers`.
+        self.printer_dirs: List[str] = []
+        self.program: Optional[str] = None
+        self.core: Optional[str] = None
+        self.args: List[str] = []
+        self.raw_output: str = ""
+        self._sections: Optional[dict[str, str]] = None
+        self._variables: List[List[Variable]] = []
+        self._stop: Optional[StopInfo] = None
+
+    def load(self, program: str, core: Optional[str] = None, args: Sequence[str] = ()) -> None:
+        self.program = program
+        self.core = core
+        self.args = list(args)
+        self._sections = None
+        self._variables = []
+        self._stop = None
+
+    def attach(self, pid: int) -> "LldbSession":
+        return LldbSession(self, pid=pid)
+
+    def load_dump(self, path: str, program: Optional[str] = None) -> "LldbSession":
+        return LldbSession(self, dump=path, program=program)
+
+    def _target_commands(self) -> List[str]:
+        if not self.program:
+            raise RuntimeError("No program loaded; call load() first")
+        create = f"target create {shlex.quote(self.program)}"
+        if self.core:
+            create += f" --core {shlex.quote(self.core)}"
+        cmds = [create]
+        if not self.core:
+            for directory in self.printer_dirs:
+                # What rust-lldb does.
+                cmds.append(f"command script import {shlex.quote(os.path.join(directory, 'lldb_lookup.py'))}")
+                cmds.append(f"command source -s 0 {shlex.quote(os.path.join(directory, 'lldb_commands'))}")
+            launch = "process launch"
+            if self.ignore_signals:
+                cmds.append(f"command script import {shlex.quote(signal_passthrough.SCRIPT_PATH)}")
+                launch = " ".join([signal_passthrough.COMMAND, *self.ignore_signals])
+            if self.args:
+                launch += " -- " + " ".join(shlex.quote(a) for a in self.args)
+            cmds.append(launch)
+        return cmds
+
+    def _commands(self) -> List[str]:
+        # "bt all" is "thread backtrace all" under a name whose section key does not clash.
+        commands = self._target_commands()
+        commands += ["thread info", "thread backtrace", "bt all", "register read", "image list", "disassemble --frame"]
+        for i in range(self.locals_frames):
+            commands += [f"frame select {i}", "frame variable --no-locals", "frame variable --no-args"]
+        if not self.core and self.locals_frames:
+            commands += _printed_commands(self.locals_frames)
+        return commands
+
+    def _split(self, output: str, commands: List[str]) -> dict[str, str]:
+        sections: dict[str, List[str]] = {"preamble": []}
+        current = "preamble"
+        for line in output.splitlines():
+            stripped = line.strip()
+            if stripped.startswith("(lldb) "):
+                echoed = stripped[len("(lldb) "):].strip()
+                if echoed in commands:
+                    # Key sections by verb + noun so launch args don't leak into the name.
+                    current = " ".join(echoed.split()[:2])
+                    sections.setdefault(current, [])
+                    continue
+            sections.setdefault(current, []).append(line)
+        return {name: "\n".join(lines) for name, lines in sections.items()}
+
+    def run_to_fault(self) -> StopInfo:
+        commands = self._commands()
+        argv = [self.lldb_path, "--batch", "--no-lldbinit"]
+        for cmd in commands:
+            argv += ["-o", cmd]
+        output, timed_out = run_batch(argv, self.timeout)
+        self.raw_output = output
+        sections = self._split(output, commands)
+        self._sections = sections
+        # The per-frame commands repeat, so they are sliced by position instead of by name.
+        outputs = _slice_echoes(output, commands)[1]
+        start = commands.index("frame select 0") if self.locals_frames else len(commands)
+        printed = pretty_printers.parse_printed(outputs[-1]) if not self.core else {}
+        self._variables = []
+        for frame, i in enumerate(range(start, start + 3 * self.locals_frames, 3)):
+            variables = parse_lldb_variables(outputs[i + 1], argument=True) + parse_lldb_variables(outputs[i + 2])
+            pretty_printers.mark_printed(variables, printed.get(frame))
+            self._variables.append(variables)
+        if timed_out:
+            stop = StopInfo(description=f"no fault within {self.timeout:g}s; killed", timed_out=True)
+        else:
+            launch = "process launch"
+            if self.ignore_signals:
+                # Sections are keyed by the first two words of the command.
+                launch = f"{signal_passthrough.COMMAND} {self.ignore_signals[0]}"
+            stop_text = "\n".join(sections.get(key, "") for key in ("target create", launch, "thread info"))
+            stop = parse_lldb_stop(signal_passthrough.after_passed(stop_text))
+            stop.handled_signals = signal_passthrough.passed_signals(output)
+        self._stop = stop
+        return stop
+
+    def run_at_fault(self, commands: Sequence[str]) -> List[str]:
+        """Relaunch the program (or reopen its core) to the fault and return each command's output there."""
+        target = self._target_commands()
+        argv = [self.lldb_path, "--batch", "--no-lldbinit"]
+        for cmd in target + list(commands):
+            argv += ["-o", cmd]
+        output, timed_out = run_batch(argv, self.timeout)
+        self.raw_output = output
+        if timed_out:
+            raise RuntimeError(f"lldb timed out after {self.timeout:g}s")
+        return _slice_echoes(output, target + list(commands))[1][len(target):]
+
+    def _ensure_captured(self) -> dict[str, str]:
+        if self._sections is None:
+            self.run_to_fault()
+        return self._sections or {}
+
+    def backtrace(self) -> List[Frame]:
+        return parse_lldb_backtrace(self._ensure_captured().get("thread backtrace", ""))
+
+    def read_registers(self) -> List[Register]:
+        return parse_lldb_registers(self._ensure_captured().get("register read", ""))
+
+    def modules(self) -> List[Module]:
+        return parse_lldb_image_list(self._ensure_captured().get("image list", ""))
+
+    def threads(self) -> List[ThreadBacktrace]:
+        return parse_lldb_thread_backtraces(self._ensure_captured().get("bt all", ""))
+
+    def heap_summary(self) -> HeapSummary:
+        """No heap statistics from lldb; only heap corruption the program printed before the fault."""
+        self._ensure_captured()
+        return HeapSummary.unsupported(self.name, heap_messages(self.raw_output))
+
+    def fault_context(self) -> Optional[FaultContext]:
+        """The registers and the instructions around the pc at the fault; see `dbgcopilot.analysis.disasm`."""
+        sections = self._ensure_captured()
+        instructions = parse_disassembly(sections.get("disassemble --frame", ""))
+        return build_fault_context(parse_lldb_registers(sections.get("register read", "")), instructions)
+
+    def frame_variables(self) -> List[List[Variable]]:
+        """Arguments, then locals, of each of the top ``locals_frames`` frames at the fault."""
+        self._ensure_captured()
+        return self._variables
+
+    def sample_threads(self, pid: int) -> List[ThreadBacktrace]:
+        """Attach to a live process, capture every thread's stack, and detach."""
+        argv = [self.lldb_path, "--batch", "--no-lldbinit", "-p", str(pid)]
+        argv += ["-o", "thread backtrace all", "-o", "process detach"]
+        output, timed_out = run_batch(argv, self.timeout)
+        self.raw_output = output
+        if timed_out:
+            return []
+        return parse_lldb_thread_backtraces(output)
+
+
+class LldbSession(BatchSession):
+    """Core file or live process inspected through `lldb --batch`."""
+
+    backend: LldbBatchBackend
+
+    def _target_commands(self) -> List[str]:
+        if self.pid is not None:
+            return [f"process attach --pid {self.pid}"]
+        create = "target create"
+        if self.program:
+            create += f" {shlex.quote(self.program)}"
+        return [f"{create} --core {shlex.quote(self.dump or '')}"]
+
+    def _run(self, commands: Sequence[str]) -> tuple[str, List[str]]:
+        target = self._target_commands()
+        trailer = ["process detach"] if self.pid is not None else []
+        argv = [self.backend.lldb_path, "--batch", "--no-lldbinit"]
+        for cmd in target + list(commands) + trailer:
+            argv += ["-o", cmd]
+        output, timed_out = run_batch(argv, self.backend.timeout)
+        self.raw_output = output
+        if timed_out:
+            raise RuntimeError(f"lldb timed out after {self.backend.timeout:g}s")
+        # LLDB echoes "(lldb) <cmd>" before each command's output; slice on the echoes in order.
+        preamble, outputs = _slice_echoes(output, target + list(commands) + trailer)
+        preamble_text = "\n".join(preamble + [outputs[i] for i in range(len(target))])
+        return preamble_text, outputs[len(target) : len(target) + len(commands)]
+
+    def stack_trace(self, thread: Optional[int] = None) -> List[Frame]:
+        commands = ([f"thread select {thread}"] if thread is not None else []) + ["thread backtrace"]
+        return parse_lldb_backtrace(self.run_commands(commands)[-1])
+
+    def stop_info(self) -> StopInfo:
+        preamble, outputs = self._run(["thread info"])
+        return parse_lldb_stop(preamble + "\n" + outputs[0])
+
+    def read_registers(self) -> List[Register]:
+        return parse_lldb_registers(self.run_command("register read"))
+
+    def threads(self) -> List[ThreadBacktrace]:
+        return parse_lldb_thread_backtraces(self.run_command("thread backtrace all"))
+
+    def frame_locals(self, thread: Optional[int] = None, frame_index: int = 0) -> List[Variable]:
+        select = ([f"thread select {thread}"] if thread is not None else []) + [f"frame select {frame_index}"]
+        commands = select + ["frame variable --no-locals", "frame variable --no-args"]
+        if self.pid is not None:
+            # A live process is also checked against the type summaries; a core is read as it is.
+            commands += _printed_commands(frame_index + 1)
+        outputs = self.run_commands(commands)[len(select):]
+        variables = parse_lldb_variables(outputs[0], argument=True) + parse_lldb_variables(outputs[1])
+        if self.pid is not None:
+            pretty_printers.mark_printed(variables, pretty_printers.parse_printed(outputs[-1]).get(frame_index))
+        return variables
+
+    def read_memory(self, address: int, size: int) -> Optional[bytes]:
+        return parse_lldb_memory(self.run_command(f"memory read --size 1 --format x --count {size} {address:#x}"))
+
+    def current_tid(self) -> Optional[int]:
+        return parse_lldb_current_tid(self.run_command("thread info"))
+
+    def modules(self) -> List[Module]:
+        return parse_lldb_image_list(self.run_command("image list"))
+
+    def heap_summary(self) -> HeapSummary:
+        return HeapSummary.unsupported(self.backend.name)
+
+    def fault_context(self, before: int = DEFAULT_BEFORE, after: int = DEFAULT_AFTER) -> Optional[FaultContext]:
+        registers, function = self.run_commands(["register read", "disassemble --frame"])
+        return build_fault_context(parse_lldb_registers(registers), parse_disassembly(function), before, after)
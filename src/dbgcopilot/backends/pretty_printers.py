@@ -0,0 +1,155 @@
+"""Tell which locals of a live process the debugger's pretty-printers rendered.
+
+Against a running process GDB and LLDB print containers through their
+Python pretty-printers (GDB) or type summaries and synthetic children
+(LLDB): a Rust ``Vec<i32>`` reads ``vec![1, 2, 3]`` or ``size=3 {1, 2, 3}``,
+a ``std::vector<int>`` ``std::vector of length 3, capacity 3 = {1, 2, 3}``,
+instead of the pointer, length, and capacity the struct holds. The
+printers for libstdc++ and libc++ load on their own; Rust's ship with the
+toolchain (``rustc --print sysroot``, under ``lib/rustlib/etc``) and are
+loaded from `rust_printer_dirs` the way ``rust-gdb`` and ``rust-lldb`` do.
+
+The value printed is whatever the debugger printed. What a dump alone
+cannot say is whether a printer was behind it: a type without one falls
+back to its raw fields (``{buf = {ptr = 0x55..., cap = 3}, len = 3}``),
+which is easy to misread as the container's contents. After the locals are
+read, ``dbgcopilot-printed N`` prints one line per frame from the top::
+
+    dbgcopilot: frame 0 printed: items names
+
+naming the variables a printer applies to, which `parse_printed` reads
+back for `Variable.pretty_printed`. Only live launches and attaches ask;
+cores are read as they are.
+
+GDB ``source``s this file and LLDB loads it with ``command script import``.
+Either way it runs in the debugger's own Python, so it imports nothing from
+dbgcopilot.
+"""
+from __future__ import annotations
+
+import os
+import re
+import subprocess
+from typing import Any, Dict, Iterable, List, Optional, Set
+
+try:  # pragma: no cover - only available inside gdb
+    import gdb  # type: ignore
+except ImportError:
+    gdb = None  # type: ignore
+
+COMMAND = "dbgcopilot-printed"
+# Older GDBs do not set __file__ in a sourced script, which only needs the path from outside.
+SCRIPT_PATH = os.path.abspath(__file__) if "__file__" in globals() else ""
+
+_PRINTED_LINE = "dbgcopilot: frame {} printed: {}"
+_PRINTED_RE = re.compile(r"^dbgcopilot: frame (\d+) printed:(.*)$", re.MULTILINE)
+
+
+def parse_printed(output: str) -> Dict[int, Set[str]]:
+    """Frame index to the names of its pretty-printed variables; a frame with no line is missing."""
+    return {int(m.group(1)): set(m.group(2).split()) for m in _PRINTED_RE.finditer(output or "")}
+
+
+def mark_printed(variables: Iterable[Any], names: Optional[Set[str]]) -> None:
+    """Set ``pretty_printed`` on one frame's variables from its `parse_printed` names; None leaves them unknown.
+
+    A variable outside ``names`` is marked False only when its value is an
+    aggregate (``{...}``): a scalar needs no printer to read right.
+    """
+    if names is None:
+        return
+    for variable in variables:
+        if variable.name in names:
+            variable.pretty_printed = True
+        elif isinstance(variable.value, str) and variable.value.startswith("{"):
+            variable.pretty_printed = False
+
+
+def rust_printer_dirs() -> List[str]:
+    """The toolchain's pretty-printer directory (``<sysroot>/lib/rustlib/etc``), if rustc is installed."""
+    try:
+        sysroot = subprocess.run(
+            ["rustc", "--print", "sysroot"], capture_output=True, text=True, timeout=10, check=True
+        ).stdout.strip()
+    except (OSError, subprocess.SubprocessError):
+        return []
+    path = os.path.join(sysroot, "lib", "rustlib", "etc")
+    return [path] if sysroot and os.path.isdir(path) else []
+
+
+def _count(argument: str) -> int:
+    try:
+        return max(int(argument.strip() or "1"), 0)
+    except ValueError:
+        return 1
+
+
+def gdb_printed(count: int) -> None:  # pragma: no cover - gdb environment
+    """Print the pretty-printed variables of the selected thread's top ``count`` frames."""
+    frame = gdb.newest_frame()
+    for index in range(count):
+        if frame is None:
+            return
+        names: List[str] = []
+        seen: Set[str] = set()
+        try:
+            block = frame.block()
+        except RuntimeError:
+            block = None
+        # Innermost scope first; a shadowed name is the inner variable, as `info locals` prints it.
+        while block is not None:
+            for symbol in block:
+                if not (symbol.is_argument or symbol.is_variable) or symbol.name in seen:
+                    continue
+                seen.add(symbol.name)
+                try:
+                    if gdb.default_visualizer(symbol.value(frame)) is not None:
+                        names.append(symbol.name)
+                except (gdb.error, RuntimeError):
+                    pass
+            if block.function is not None:
+                break
+            block = block.superblock
+        gdb.write(_PRINTED_LINE.format(index, " ".join(names)) + "\n")
+        frame = frame.older()
+
+
+def lldb_printed(debugger: Any, command: str, result: Any, internal_dict: Any) -> None:  # pragma: no cover - lldb
+    """``dbgcopilot-printed N``: the variables of the top N frames a summary or synthetic provider formats."""
+    thread = debugger.GetSelectedTarget().GetProcess().GetSelectedThread()
+    for index in range(min(_count(command), thread.GetNumFrames())):
+        # Arguments, locals, and statics in scope, like `frame variable`.
+        values = thread.GetFrameAtIndex(index).GetVariables(True, True, False, True)
+        names = []
+        for i in range(values.GetSize()):
+            value = values.GetValueAtIndex(i)
+            if value.GetTypeSummary().IsValid() or value.GetTypeSynthetic().IsValid():
+                names.append(value.GetName())
+        print(_PRINTED_LINE.format(index, " ".join(names)), flush=True)
+
+
+def __lldb_init_module(debugger: Any, internal_dict: Any) -> None:  # pragma: no cover - lldb
+    debugger.HandleCommand(f"command script add -f {__name__}.lldb_printed {COMMAND}")
+
+
+if gdb is not None:  # pragma: no cover - gdb environment
+
+    class _PrintedCommand(gdb.Command):
+        """dbgcopilot-printed N: name the variables of the top N frames a pretty-printer renders."""
+
+        def __init__(self) -> None:
+            super().__init__(COMMAND, gdb.COMMAND_DATA)
+
+        def invoke(self, argument: str, from_tty: bool) -> None:
+            gdb_printed(_count(argument))
+
+    _PrintedCommand()
+
+
+__all__ = [
+    "COMMAND",
+    "SCRIPT_PATH",
+    "mark_printed",
+    "parse_printed",
+    "rust_printer_dirs",
+]
@@ -0,0 +1,223 @@
+"""Record batch debugger sessions to a file and replay them without a debugger.
+
+Live targets are nondeterministic: which thread holds which lock, and when, changes
+from run to run, so tests against ``examples/hang`` would be flaky. A
+`RecordingBackend` wraps a real backend and saves every batch invocation
+(commands in, debugger output out) of its sessions to a JSON file. A
+`ReplayBackend` reads that file back and hands out `ReplaySession`\\ s that
+answer the same commands with the recorded output, so hang and crash analysis
+run unchanged in CI with no debugger installed.
+
+Recordings are portable between machines: the target pid, the program and dump
+paths, the working directory, and the home directory are replaced by
+placeholders such as ``<dbgcopilot:pid>`` when recording, and by the replaying
+session's own values when played back.
+"""
+from __future__ import annotations
+
+import json
+import os
+import re
+from collections import deque
+from dataclasses import dataclass, field
+from typing import Any, Deque, Dict, List, Optional, Sequence, Tuple, Type
+
+from .batch import BatchSession
+from .cdb_batch import CdbSession
+from .gdb_batch import GdbSession
+from .lldb_batch import LldbSession
+
+RECORDING_VERSION = 1
+
+_SESSION_CLASSES: Dict[str, Type[BatchSession]] = {"gdb": GdbSession, "lldb": LldbSession, "cdb": CdbSession}
+
+
+class ReplayMissError(RuntimeError):
+    """Raised when a replayed session is asked for commands the recording never saw."""
+
+
+@dataclass
+class Exchange:
+    """One batch invocation: the commands sent and what the debugger printed for them."""
+
+    commands: List[str]
+    preamble: str = ""
+    outputs: List[str] = field(default_factory=list)
+    # Set instead of outputs when the invocation raised (a timeout).
+    error: Optional[str] = None
+
+    def to_dict(self) -> Dict[str, Any]:
+        data: Dict[str, Any] = {"commands": self.commands, "preamble": self.preamble, "outputs": self.outputs}
+        if self.error is not None:
+            data["error"] = self.error
+        return data
+
+    @classmethod
+    def from_dict(cls, data: Dict[str, Any]) -> "Exchange":
+        return cls(
+            commands=list(data["commands"]),
+            preamble=data.get("preamble", ""),
+            outputs=list(data.get("outputs", [])),
+            error=data.get("error"),
+        )
+
+
+@dataclass
+class Recording:
+    """Every exchange of the sessions of one backend, in the order they happened."""
+
+    engine: str
+    exchanges: List[Exchange] = field(default_factory=list)
+
+    def save(self, path: str) -> None:
+        data = {
+            "version": RECORDING_VERSION,
+            "engine": self.engine,
+            "exchanges": [e.to_dict() for e in self.exchanges],
+        }
+        with open(path, "w", encoding="utf-8") as fh:
+            json.dump(data, fh, indent=2)
+            fh.write("\n")
+
+    @classmethod
+    def load(cls, path: str) -> "Recording":
+        with open(path, encoding="utf-8") as fh:
+            data = json.load(fh)
+        if data.get("version") != RECORDING_VERSION:
+            raise ValueError(f"{path}: unsupported recording version {data.get('version')!r}")
+        if data.get("engine") not in _SESSION_CLASSES:
+            raise ValueError(f"{path}: unknown debugger engine {data.get('engine')!r}")
+        return cls(engine=data["engine"], exchanges=[Exchange.from_dict(e) for e in data.get("exchanges", [])])
+
+
+class Placeholders:
+    """Swaps a session's machine-specific values for stable placeholders, and back."""
+
+    def __init__(self, session: BatchSession) -> None:
+        paths: Dict[str, str] = {}
+        for name, value in (("program", session.program), ("dump", session.dump)):
+            if value:
+                paths[os.path.abspath(value)] = f"<dbgcopilot:{name}>"
+                paths[value] = f"<dbgcopilot:{name}>"
+        for name, value in (("cwd", os.getcwd()), ("home", os.path.expanduser("~"))):
+            # A cwd of "/" would turn every path separator into a placeholder.
+            if value and len(value) > 1:
+                paths.setdefault(value.rstrip(os.sep), f"<dbgcopilot:{name}>")
+        # Longest first, so the program path wins over the directory it lives in.
+        self.paths: List[Tuple[str, str]] = sorted(paths.items(), key=lambda kv: len(kv[0]), reverse=True)
+        self.pid = session.pid
+
+    def normalize(self, text: str) -> str:
+        for value, placeholder in self.paths:
+            text = text.replace(value, placeholder)
+        if self.pid is not None:
+            # Also catches "LWP <pid>": a process's main thread has the process's id.
+            text = re.sub(rf"(?<![0-9A-Za-z]){self.pid}(?![0-9A-Za-z])", "<dbgcopilot:pid>", text)
+        return text
+
+    def restore(self, text: str) -> str:
+        # Shortest spelling first: a relative program path comes back as the session gave it.
+        for value, placeholder in reversed(self.paths):
+            text = text.replace(placeholder, value)
+        if self.pid is not None:
+            text = text.replace("<dbgcopilot:pid>", str(self.pid))
+        return text
+
+
+class RecordingBackend:
+    """Wraps ``backend`` so every session it opens is saved to ``path`` as it runs.
+
+    The file is rewritten after each exchange, so a session that is never
+    closed (or a test that fails halfway) still leaves what it did behind.
+    """
+
+    def __init__(self, backend: Any, path: str) -> None:
+        self.backend = backend
+        self.path = path
+        self.name: str = backend.name
+        self.recording = Recording(engine=backend.name)
+
+    def _wrap(self, session: BatchSession) -> BatchSession:
+        run = session._run
+        placeholders = Placeholders(session)
+
+        def recording_run(commands: Sequence[str]) -> tuple[str, List[str]]:
+            exchange = Exchange(commands=[placeholders.normalize(c) for c in commands])
+            try:
+                preamble, outputs = run(commands)
+            except RuntimeError as exc:
+                exchange.error = placeholders.normalize(str(exc))
+                raise
+            else:
+                exchange.preamble = placeholders.normalize(preamble)
+                exchange.outputs = [placeholders.normalize(o) for o in outputs]
+                return preamble, outputs
+            finally:
+                self.recording.exchanges.append(exchange)
+                self.recording.save(self.path)
+
+        session._run = recording_run  # type: ignore[method-assign]
+        return session
+
+    def attach(self, pid: int) -> BatchSession:
+        return self._wrap(self.backend.attach(pid))
+
+    def load_dump(self, path: str, program: Optional[str] = None) -> BatchSession:
+        return self._wrap(self.backend.load_dump(path, program))
+
+
+class ReplaySession(BatchSession):
+    """Answers batch commands from a `Recording` instead of a debugger.
+
+    Mixed in ahead of the recorded engine's session class, which still parses
+    the output. The same commands asked again get the next recorded answer,
+    and the last one once they run out; commands never recorded raise
+    `ReplayMissError`.
+    """
+
+    backend: "ReplayBackend"
+
+    def _run(self, commands: Sequence[str]) -> tuple[str, List[str]]:
+        placeholders = Placeholders(self)
+        key = tuple(placeholders.normalize(c) for c in commands)
+        queue = self.backend._answers.get(key)
+        if not queue:
+            raise ReplayMissError(f"no recorded output for {list(key)!r} in {self.backend.path}")
+        exchange = queue.popleft() if len(queue) > 1 else queue[0]
+        if exchange.error is not None:
+            raise RuntimeError(placeholders.restore(exchange.error))
+        preamble, outputs = placeholders.restore(exchange.preamble), [placeholders.restore(o) for o in exchange.outputs]
+        self.raw_output = "\n".join([preamble] + outputs)
+        return preamble, outputs
+
+
+class ReplayBackend:
+    """Opens `ReplaySession`\\ s over the recording at ``path``, for any pid or dump path."""
+
+    def __init__(self, path: str) -> None:
+        self.path = path
+        self.recording = Recording.load(path)
+        self.name = self.recording.engine
+        self._answers: Dict[Tuple[str, ...], Deque[Exchange]] = {}
+        for exchange in self.recording.exchanges:
+            self._answers.setdefault(tuple(exchange.commands), deque()).append(exchange)
+        engine = _SESSION_CLASSES[self.name]
+        self._session_class = type(f"Replay{engine.__name__}", (ReplaySession, engine), {})
+
+    def attach(self, pid: int) -> BatchSession:
+        return self._session_class(self, pid=pid)
+
+    def load_dump(self, path: str, program: Optional[str] = None) -> BatchSession:
+        return self._session_class(self, dump=path, program=program)
+
+
+__all__ = [
+    "Exchange",
+    "Placeholders",
+    "RECORDING_VERSION",
+    "Recording",
+    "RecordingBackend",
+    "ReplayBackend",
+    "ReplayMissError",
+    "ReplaySession",
+]
@@ -0,0 +1,197 @@
+"""Run a program under GDB or LLDB past the signals its own handlers catch.
+
+Some programs fault on purpose: a JIT or a garbage collector takes SIGSEGV on
+guard pages and handles it, a server ignores SIGPIPE, a runtime uses SIGUSR1
+to stop its threads. Stopping at the first of these reports a crash that did
+not happen. With ``AnalyzeRequest.ignore_signals`` the batch backends launch
+the program through ``dbgcopilot-run`` instead of ``run`` / ``process launch``:
+
+* a stop on a signal that is not in the list ends the run, as before;
+* a stop on a listed signal is passed to the program when the process has a
+  handler installed for it (its ``SigCgt`` mask in ``/proc/<pid>/status``),
+  and the run goes on; where the mask cannot be read, it is passed anyway;
+* a listed signal the process does not catch is fatal, and ends the run there
+  with the program's state intact.
+
+A handler that cannot recover usually resets the default action and returns,
+or calls abort(); either way the next stop is fatal and is the one reported.
+Each signal passed on is announced with a ``dbgcopilot: passed <SIG> to the
+program's handler`` line, which `passed_signals` reads back.
+
+GDB ``source``s this file and LLDB loads it with ``command script import``.
+Either way it runs in the debugger's own Python, so it imports nothing from
+dbgcopilot.
+"""
+from __future__ import annotations
+
+import os
+import re
+import shlex
+import signal
+from typing import Any, Iterable, List, Optional, Sequence, Set
+
+try:  # pragma: no cover - only available inside gdb
+    import gdb  # type: ignore
+except ImportError:
+    gdb = None  # type: ignore
+
+COMMAND = "dbgcopilot-run"
+# Older GDBs do not set __file__ in a sourced script, which only needs the path from outside.
+SCRIPT_PATH = os.path.abspath(__file__) if "__file__" in globals() else ""
+
+_PASSED_LINE = "dbgcopilot: passed {} to the program's handler"
+_PASSED_RE = re.compile(r"^dbgcopilot: passed (SIG[A-Z0-9]+) to the program's handler\s*$", re.MULTILINE)
+_SIGCGT_RE = re.compile(r"^SigCgt:\s*([0-9a-fA-F]+)\s*$", re.MULTILINE)
+
+
+def signal_names(raw: Any) -> List[str]:
+    """``SIGSEGV``-style names for a list (or comma-separated text) of names, short names, or numbers.
+
+    ``segv``, ``SIGSEGV``, and ``11`` all give ``SIGSEGV``; duplicates are
+    dropped. Raises ValueError for a signal this platform does not have.
+    """
+    if raw is None:
+        return []
+    items: Iterable[Any] = raw.split(",") if isinstance(raw, str) else raw
+    names: List[str] = []
+    for item in items:
+        text = str(item).strip()
+        if not text:
+            continue
+        try:
+            name = signal.Signals(int(text)).name if text.isdigit() else signal.Signals[_full_name(text)].name
+        except (KeyError, ValueError):
+            raise ValueError(f"unknown signal {text!r}") from None
+        if name not in names:
+            names.append(name)
+    return names
+
+
+def _full_name(text: str) -> str:
+    upper = text.upper()
+    return upper if upper.startswith("SIG") else "SIG" + upper
+
+
+def caught_signals(status: str) -> Optional[Set[int]]:
+    """Signal numbers with a handler installed, from the text of ``/proc/<pid>/status``; None without a SigCgt line."""
+    m = _SIGCGT_RE.search(status or "")
+    if not m:
+        return None
+    mask = int(m.group(1), 16)
+    return {bit + 1 for bit in range(mask.bit_length()) if mask >> bit & 1}
+
+
+def handler_installed(pid: int, signo: int) -> Optional[bool]:
+    """Whether process ``pid`` catches ``signo``; None where that cannot be read (no /proc)."""
+    try:
+        with open(f"/proc/{pid}/status") as fh:
+            caught = caught_signals(fh.read())
+    except OSError:
+        return None
+    return None if caught is None else signo in caught
+
+
+def should_pass(signo: int, wanted: Set[int], pid: int) -> bool:
+    """Continue past ``signo``: it is listed, and the process has not been seen to leave it at its default."""
+    return signo in wanted and handler_installed(pid, signo) is not False
+
+
+def passed_signals(output: str) -> List[str]:
+    """The signals handed to the program's handlers, in order, from a debugger run's output."""
+    return _PASSED_RE.findall(output or "")
+
+
+def after_passed(output: str) -> str:
+    """``output`` after the last signal handed to the program's handler: the stop that ended the run."""
+    output = output or ""
+    last = None
+    for last in _PASSED_RE.finditer(output):
+        pass
+    return output[last.end():] if last is not None else output
+
+
+def _numbers(names: Sequence[str]) -> Set[int]:
+    return {int(signal.Signals[name]) for name in names if name in signal.Signals.__members__}
+
+
+def _signal_name(signo: int) -> str:
+    try:
+        return signal.Signals(signo).name
+    except ValueError:
+        return f"SIG{signo}"
+
+
+def gdb_run(names: Sequence[str]) -> None:  # pragma: no cover - gdb environment
+    """``run``, then ``continue`` past each listed signal the program catches; see the module docstring."""
+    wanted = _numbers(names)
+    for name in names:
+        # Stop on it so it can be inspected; `continue` then delivers it.
+        gdb.execute(f"handle {name} stop print pass", to_string=True)
+    gdb.execute("run")
+    while True:
+        pid = gdb.selected_inferior().pid
+        if not pid:
+            return
+        try:
+            signo = int(gdb.parse_and_eval("$_siginfo.si_signo"))
+        except gdb.error:
+            # A stop without a signal (a breakpoint, an exec).
+            return
+        if not should_pass(signo, wanted, pid):
+            return
+        gdb.write(_PASSED_LINE.format(_signal_name(signo)) + "\n")
+        gdb.execute("continue")
+
+
+def lldb_run(debugger: Any, command: str, result: Any, internal_dict: Any) -> None:  # pragma: no cover - lldb
+    """``dbgcopilot-run SIG... [-- ARGS]``: ``process launch``, then continue past each caught listed signal."""
+    import lldb  # type: ignore
+
+    names, _, args = command.partition("--")
+    names_list = shlex.split(names)
+    wanted = _numbers(names_list)
+    for name in names_list:
+        debugger.HandleCommand(f"process handle -s true -p true -n true {name}")
+    debugger.HandleCommand("process launch" + (f" -- {args.strip()}" if args.strip() else ""))
+    while True:
+        process = debugger.GetSelectedTarget().GetProcess()
+        if process.GetState() != lldb.eStateStopped:
+            return
+        thread = process.GetSelectedThread()
+        if thread.GetStopReason() != lldb.eStopReasonSignal:
+            return
+        signo = thread.GetStopReasonDataAtIndex(0)
+        if not should_pass(signo, wanted, process.GetProcessID()):
+            return
+        print(_PASSED_LINE.format(_signal_name(signo)), flush=True)
+        debugger.HandleCommand("process continue")
+
+
+def __lldb_init_module(debugger: Any, internal_dict: Any) -> None:  # pragma: no cover - lldb
+    debugger.HandleCommand(f"command script add -f {__name__}.lldb_run {COMMAND}")
+
+
+if gdb is not None:  # pragma: no cover - gdb environment
+
+    class _RunCommand(gdb.Command):
+        """dbgcopilot-run SIG...: run the program, passing the listed signals it catches to its handlers."""
+
+        def __init__(self) -> None:
+            super().__init__(COMMAND, gdb.COMMAND_RUNNING)
+
+        def invoke(self, argument: str, from_tty: bool) -> None:
+            gdb_run(gdb.string_to_argv(argument))
+
+    _RunCommand()
+
+
+__all__ = [
+    "COMMAND",
+    "SCRIPT_PATH",
+    "after_passed",
+    "caught_signals",
+    "handler_installed",
+    "passed_signals",
+    "should_pass",
+    "signal_names",
+]
@@ -1,11 +1,109 @@
 """Small CLI helpers for installed package.
 
 Provides a `dbgcopilot-plugin-path` console script that prints the absolute
-path to the GDB plugin file so users can source it easily from GDB.
+path to the GDB plugin file so users can source it easily from GDB, and
+`dbgcopilot-analyze`, a one-shot `analyze()` that prints the report as text
+or, with ``--format json``, as `AnalysisReport.to_json` for other tools;
+with ``--interactive`` it then answers follow-up questions about the report,
+and with ``--batch DIR`` it analyzes every core in a directory instead.
+`watch_main` is ``dbgcopilot watch``: launch a program and, if it outlives
+its hang timeout, attach and analyze the hang. `run_main` is ``dbgcopilot
+run``: launch a program under the debugger and capture its fault when it
+happens, or report its exit, or analyze it as a hang after ``--max-runtime``.
+`diff_main` is ``dbgcopilot diff``: compare two saved JSON reports or two
+dumps and say whether the crash moved, and which modules changed.
+`batch_main` is ``dbgcopilot batch``: analyze a directory of dumps and print
+one row per crash signature. `check_main` is ``dbgcopilot check``: say which
+debug sections a binary has and whether its separate debug file can be found.
+`history_main` is ``dbgcopilot history``: list and filter the crashes analyzed
+before, each with how often its signature has been seen. `last_main` is
+``dbgcopilot last``: find the newest dump a program left where the system
+keeps them (WER's LocalDumps, core_pattern's directory) and analyze it.
+`serve_main` is ``dbgcopilot serve``: the same analyses, and follow-ups on
+them, as a JSON-RPC server on stdio for editor extensions (see ``serve.py``).
+
+Every command exits with an `ExitCode`, so CI can tell a crash that was
+analyzed from one that could not be captured, and from a program that did not
+crash at all.
 """
 from __future__ import annotations
 
+import argparse
+import dataclasses
 import os
+import re
+import sys
+from enum import IntEnum
+from typing import TYPE_CHECKING, Callable, Iterable, List, Optional
+
+from dbgcopilot.utils.config import CONFIG_FILENAME
+from dbgcopilot.utils.io import COLOR_MODES, color_enabled
+
+if TYPE_CHECKING:
+    from dbgcopilot.analysis.api import AnalysisReport, AnalyzeRequest
+    from dbgcopilot.analysis.batch import BatchResult
+    from dbgcopilot.analysis.crash import CrashReport
+    from dbgcopilot.analysis.exception import ExceptionPolicy
+    from dbgcopilot.analysis.store import StoredSession
+    from dbgcopilot.utils.config import Settings
+
+
+class ExitCode(IntEnum):
+    """Exit status of ``dbgcopilot-analyze`` and the ``dbgcopilot`` subcommands, for scripts."""
+
+    # The analysis completed: a crash or hang was captured (and explained, if a provider was asked).
+    OK = 0
+    # Bad arguments or settings; argparse's own status.
+    USAGE = 2
+    # The program could not be run, the process attached to, or the dump or report read.
+    CAPTURE_FAILED = 3
+    # A report was produced, but the explanation the provider was asked for failed.
+    LLM_FAILED = 4
+    # The target exited, or was still running at the timeout without hang sampling, instead of
+    # crashing or hanging.
+    NO_CRASH = 5
+
+
+def _exit_status(report: "AnalysisReport", explained: bool) -> ExitCode:
+    if not (report.crashed or report.hung):
+        return ExitCode.NO_CRASH
+    if explained and report.explanation_error:
+        return ExitCode.LLM_FAILED
+    return ExitCode.OK
+
+
+def _add_color_option(parser: argparse.ArgumentParser) -> None:
+    parser.add_argument(
+        "--color",
+        choices=COLOR_MODES,
+        default="auto",
+        help="color the text report: on a terminal unless NO_COLOR is set (auto), always, or never. On a terminal "
+        "(or with always) the report is split into sections; piped, it is the plain report (default: auto)",
+    )
+
+
+def _report_color(mode: str) -> Optional[bool]:
+    """How to print the text report: None for the plain text, else the sectioned view and whether it is colored."""
+    if not (sys.stdout.isatty() or mode == "always"):
+        return None
+    return color_enabled(sys.stdout, mode)
+
+
+def _render_text(report: "AnalysisReport", color: Optional[bool], with_modules: bool = False) -> str:
+    if color is None:
+        return report.render(with_modules=with_modules)
+    from dbgcopilot.analysis.render import render_terminal
+
+    return render_terminal(report.to_dict(), color=color, with_modules=with_modules)
+
+
+def _heading(title: str, color: Optional[bool], plain: str) -> str:
+    """A section heading for the sectioned view, or ``plain`` for the plain text."""
+    if color is None:
+        return plain
+    from dbgcopilot.analysis.render import section_heading
+
+    return section_heading(title, color)
 
 
 def get_plugin_path() -> str:
@@ -19,5 +117,1057 @@ def print_plugin_path() -> None:
     print(get_plugin_path())
 
 
+def analyze_main(argv: Optional[list[str]] = None) -> int:
+    from dbgcopilot.analysis.api import AnalysisError, AnalyzeRequest, add_fix_suggestion, analyze, explain
+    from dbgcopilot.analysis.cache import ExplanationCache
+    from dbgcopilot.backends.signal_passthrough import signal_names
+    from dbgcopilot.utils.trace import configure_logging
+
+    parser = argparse.ArgumentParser(
+        prog="dbgcopilot-analyze",
+        description="Capture, classify, and optionally explain a crash or hang.",
+        epilog="Arguments after the binary (or after --) are passed to the program. "
+        "Set DBGCOPILOT_LOG=info (or debug) to log each stage and its duration to stderr.",
+    )
+    parser.add_argument("binary", nargs="?", default="", help="program to run, or the program that wrote --core")
+    parser.add_argument("args", nargs=argparse.REMAINDER, help="program arguments")
+    parser.add_argument("--core", help="core file or minidump to analyze instead of running the program")
+    parser.add_argument(
+        "--batch",
+        metavar="DIR",
+        help="analyze every core file or minidump in DIR and print a JSON array of reports; "
+        "a summary grouping them by crash signature goes to stderr",
+    )
+    parser.add_argument(
+        "--batch-glob",
+        action="append",
+        metavar="PATTERN",
+        help="with --batch, the file names to analyze (repeatable; default: core, core.*, *.core, *.dmp, *.mdmp)",
+    )
+    parser.add_argument(
+        "--attach",
+        type=int,
+        metavar="PID",
+        help="sample the threads of a running process and detach, leaving it running; with --core, "
+        "the core is analyzed if the process has already exited",
+    )
+    parser.add_argument("--format", choices=("text", "json"), default="text", help="output format (default: text)")
+    parser.add_argument(
+        "--timeout",
+        type=float,
+        help="seconds a run may take before it is killed and sampled as hung (default: analysis.timeout, or 30)",
+    )
+    parser.add_argument(
+        "--max-frames-per-thread",
+        type=int,
+        metavar="N",
+        help="frames of each thread's stack to capture, 0 for all (default: analysis.max_frames_per_thread, or 64)",
+    )
+    parser.add_argument(
+        "--no-hang-sampling",
+        action="store_true",
+        help="kill a run that outlives --timeout and report no_crash_within_timeout instead of sampling it",
+    )
+    parser.add_argument("--debugger", choices=("gdb", "lldb"), help="debugger for live captures")
+    parser.add_argument(
+        "--ignore-signal",
+        action="append",
+        metavar="SIG",
+        help="a signal the program handles itself (SIGSEGV, segv, or 11; repeatable): under the debugger it is "
+        "passed to the program's handler, and only a fatal one is reported (default: analysis.ignore_signals)",
+    )
+    parser.add_argument(
+        "--exception-policy",
+        choices=("first-chance", "second-chance"),
+        help="capture a fault when the debugger first sees it, or only if the program's handlers do not take it "
+        "(default: analysis.exception_policy, or second-chance)",
+    )
+    parser.add_argument(
+        "--modules",
+        action="store_true",
+        help="list the loaded modules with their versions in the text report (the JSON always has them)",
+    )
+    _add_color_option(parser)
+    parser.add_argument(
+        "--debug-file",
+        metavar="PATH",
+        help="separate debug info of a stripped binary, instead of looking it up by build-id and .gnu_debuglink",
+    )
+    parser.add_argument(
+        "--symbol-path",
+        metavar="PATH",
+        help="debug file directories and symbol servers, in DbgEng syntax: DIR;srv*CACHE*URL (default: symbols.path)",
+    )
+    parser.add_argument("--provider", help="LLM provider for the explanation (see docs/llm.md); omit to skip it")
+    parser.add_argument("--model", help="model the provider should use, instead of its default")
+    parser.add_argument("--base-url", metavar="URL", help="OpenAI-compatible endpoint to use instead of the default")
+    parser.add_argument("--config", metavar="PATH", help=f"settings file to read instead of {CONFIG_FILENAME}")
+    parser.add_argument(
+        "--prompt-template",
+        metavar="PATH",
+        help="explanation prompt with {report}, {stack}, {faulting_frame}, ... placeholders, instead of the "
+        "built-in one (default: llm.prompt_template)",
+    )
+    parser.add_argument(
+        "--prompt-fault-context",
+        action="store_true",
+        default=None,
+        help="also send the model the faulting instruction and the disassembly around it "
+        "(default: llm.prompt_fault_context)",
+    )
+    parser.add_argument(
+        "--no-llm", action="store_true", help="offline: build and print the prompt, but never call a model"
+    )
+    parser.add_argument(
+        "--dry-run",
+        action="store_true",
+        help="print only the redacted prompt the model would be sent, and exit; no network connection is made, "
+        "to the model, symbol servers, or debuginfod",
+    )
+    parser.add_argument(
+        "--max-cost",
+        type=float,
+        metavar="USD",
+        help="do not send a prompt that could take this analysis's estimated LLM cost over USD; "
+        "needs a price for the model in llm.prices (default: llm.max_cost)",
+    )
+    parser.add_argument("--no-cache", action="store_true", help="always ask the provider, ignoring cached explanations")
+    parser.add_argument("--no-history", action="store_true", help="do not save this analysis to dbgcopilot history")
+    parser.add_argument(
+        "--refresh-cache", action="store_true", help="ask the provider again and replace the cached explanation"
+    )
+    parser.add_argument(
+        "--cache-dir", help="explanation cache directory (default: dbgcopilot/explanations in the user cache directory)"
+    )
+    parser.add_argument(
+        "--emit-repro",
+        metavar="DIR",
+        help="write a small program (with a Makefile or Cargo.toml) that faults the same way, "
+        "for null dereferences, divide-by-zero, stack overflows, and aborts",
+    )
+    parser.add_argument(
+        "--suggest-fix",
+        action="store_true",
+        help="after the explanation, ask the provider for a code change that fixes the crash (a second LLM call)",
+    )
+    parser.add_argument(
+        "-i",
+        "--interactive",
+        action="store_true",
+        help="after the report, answer follow-up questions about it; a line starting with ! runs a debugger command",
+    )
+    parser.add_argument(
+        "--transcript",
+        metavar="PATH",
+        help="with --interactive, write the explanation and the follow-up session to PATH when it ends",
+    )
+    ns = parser.parse_args(argv)
+    if not ns.binary and ns.attach is None:
+        parser.error("a binary is required unless --attach is given")
+    try:
+        configure_logging()
+        settings = _settings(ns)
+        ignore_signals = signal_names(settings.get("analysis.ignore_signals"))
+    except ValueError as exc:
+        parser.error(str(exc))
+    if ns.interactive and (ns.format == "json" or ns.no_llm or not settings.get("llm.provider")):
+        parser.error("--interactive needs a provider (--provider or llm.provider), text output, and no --no-llm")
+    if ns.transcript and not ns.interactive:
+        parser.error("--transcript needs --interactive")
+    if ns.batch and (ns.core or ns.attach is not None or ns.interactive or ns.emit_repro):
+        parser.error("--batch cannot be combined with --core, --attach, --interactive, or --emit-repro")
+    if ns.batch_glob and not ns.batch:
+        parser.error("--batch-glob needs --batch")
+    if ns.no_cache and ns.refresh_cache:
+        parser.error("--no-cache and --refresh-cache cannot be combined")
+    if ns.suggest_fix and (ns.no_llm or not settings.get("llm.provider")):
+        parser.error("--suggest-fix needs a provider (--provider or llm.provider) and no --no-llm")
+    if ns.dry_run and (ns.format == "json" or ns.batch or ns.interactive or ns.suggest_fix or ns.emit_repro):
+        parser.error(
+            "--dry-run prints only the prompt: it cannot be combined with --format json, --batch, --interactive, "
+            "--suggest-fix, or --emit-repro"
+        )
+
+    args = ns.args[1:] if ns.args[:1] == ["--"] else ns.args
+    request = AnalyzeRequest(
+        binary=ns.binary,
+        core=ns.core,
+        pid=ns.attach,
+        args=args,
+        timeout=settings.get("analysis.timeout", 30.0),
+        sample_hangs=not ns.no_hang_sampling,
+        debugger=settings.get("analysis.debugger"),
+        debug_file=ns.debug_file,
+        provider=settings.get("llm.provider"),
+        llm_config=settings.llm_config(),
+        cache=None if ns.no_cache else ExplanationCache(ns.cache_dir, refresh=ns.refresh_cache),
+        no_llm=ns.no_llm,
+        suggest_fix=ns.suggest_fix,
+        ignore_signals=ignore_signals,
+        exception_policy=_exception_policy(settings),
+        max_frames_per_thread=_frame_limit(settings),
+        symbol_path=settings.get("symbols.path"),
+    )
+    if ns.batch:
+        return _run_batch(request, ns.batch, ns.batch_glob, settings)
+    if ns.dry_run:
+        return _dry_run(request)
+    try:
+        # Capture first, so the text format can show the crash before the model answers.
+        report = analyze(dataclasses.replace(request, provider=None))
+    except AnalysisError as exc:
+        print(f"dbgcopilot-analyze: {exc}", file=sys.stderr)
+        return ExitCode.CAPTURE_FAILED
+    if ns.emit_repro:
+        _emit_repro(report, ns.emit_repro)
+    wants_explanation = bool(request.provider) and not request.no_llm and (report.crashed or report.hung)
+    if ns.format == "json":
+        if wants_explanation:
+            explain(report, request)
+            if request.suggest_fix:
+                add_fix_suggestion(report, request)
+        _save_history(settings, [report], request.binary)
+        print(report.to_json())
+        _print_usage(report)
+        return _exit_status(report, wants_explanation)
+
+    color = _report_color(ns.color)
+    print(_render_text(report, color, with_modules=ns.modules), flush=True)
+    if report.prompt is not None:
+        print(f"\n{_heading('Prompt', color, 'Prompt:')}\n{report.prompt}")
+    if wants_explanation:
+        _stream_explanation(report, request, _heading("AI explanation", color, "Explanation:"))
+        if request.suggest_fix:
+            _print_fix_suggestion(report, request, _heading("Suggested fix", color, "Suggested fix:"))
+        _print_usage(report)
+    _save_history(settings, [report], request.binary)
+    if ns.interactive:
+        return _converse(report, request, ns.transcript)
+    return _exit_status(report, wants_explanation)
+
+
+def _dry_run(request: "AnalyzeRequest") -> int:
+    """``--dry-run``: capture and build the prompt with the network blocked, print it, and send nothing."""
+    from dbgcopilot.analysis.api import AnalysisError, analyze
+    from dbgcopilot.llm.base import Prompt
+    from dbgcopilot.llm.usage import prompt_tokens
+    from dbgcopilot.utils.network import no_network
+
+    offline = dataclasses.replace(request, provider=None, no_llm=True, cache=None)
+    try:
+        with no_network("--dry-run makes no network connections"):
+            report = analyze(offline)
+    except AnalysisError as exc:
+        print(f"dbgcopilot-analyze: {exc}", file=sys.stderr)
+        return ExitCode.CAPTURE_FAILED
+    if report.prompt is None:
+        print(f"dbgcopilot-analyze: no crash or hang ({report.stop.description}), so no prompt", file=sys.stderr)
+        return _exit_status(report, False)
+    print(report.prompt)
+    model = request.provider if isinstance(request.provider, str) else "no provider"
+    print(
+        f"dbgcopilot-analyze: dry run: {prompt_tokens(Prompt(report.prompt))} prompt tokens would go to {model}; "
+        "nothing was sent",
+        file=sys.stderr,
+    )
+    return ExitCode.OK
+
+
+def _run_batch(
+    request: "AnalyzeRequest", directory: str, patterns: Optional[List[str]], settings: "Settings"
+) -> int:
+    import json
+
+    from dbgcopilot.analysis.batch import DUMP_PATTERNS, analyze_batch
+
+    try:
+        result = analyze_batch(directory, request, patterns or DUMP_PATTERNS)
+    except OSError as exc:
+        print(f"dbgcopilot-analyze: cannot read {directory}: {exc}", file=sys.stderr)
+        return ExitCode.CAPTURE_FAILED
+    if not result.entries:
+        print(f"dbgcopilot-analyze: no core files in {directory}", file=sys.stderr)
+        return ExitCode.CAPTURE_FAILED
+    _save_history(settings, (e.report for e in result.entries if e.report is not None), request.binary)
+    print(json.dumps(result.to_list(), indent=2))
+    # On stderr, so the array on stdout stays parseable.
+    noun = "core" if len(result.entries) == 1 else "cores"
+    print(f"Analyzed {len(result.entries)} {noun} from {directory}:", file=sys.stderr)
+    for line in result.summary():
+        print(f"  {line}", file=sys.stderr)
+    return _batch_status(result, request)
+
+
+def _batch_status(result: "BatchResult", request: "AnalyzeRequest") -> ExitCode:
+    # Dumps that could not be read are listed in the output; the batch itself still completed.
+    explained = bool(request.provider) and not request.no_llm
+    if explained and any(e.report is not None and e.report.explanation_error for e in result.entries):
+        return ExitCode.LLM_FAILED
+    return ExitCode.OK
+
+
+def _debugger_runner(request: "AnalyzeRequest") -> Callable[[str], str]:
+    """Runs one debugger command against the live process, the core, or a rerun of the program to its fault."""
+    opened: List[Callable[[str], str]] = []
+
+    def run(command: str) -> str:
+        from dbgcopilot.backends.batch import detect_crash_backend, detect_session_backend
+
+        # Found on first use, so a missing debugger only fails the ! commands.
+        if not opened:
+            if request.pid is not None and not request.core:
+                opened.append(detect_session_backend(request.debugger).attach(request.pid).run_command)
+            else:
+                backend = detect_crash_backend(request.debugger)
+                backend.load(request.binary, core=request.core, args=request.args)
+                opened.append(lambda cmd: backend.run_at_fault([cmd])[0])
+        return opened[0](command)
+
+    return run
+
+
+def _converse(report: "AnalysisReport", request: "AnalyzeRequest", transcript: Optional[str] = None) -> int:
+    from dbgcopilot.analysis.conversation import DEBUGGER_ESCAPE, ConversationState
+    from dbgcopilot.llm.base import resolve_provider
+    from dbgcopilot.llm.errors import LlmError
+
+    try:
+        llm = resolve_provider(request.provider, request.llm_config)
+    except LlmError as exc:
+        print(f"dbgcopilot-analyze: {exc}", file=sys.stderr)
+        return ExitCode.LLM_FAILED
+    state = ConversationState(report, llm_config=request.llm_config)
+    run_command = _debugger_runner(request)
+
+    def write(chunk: str) -> None:
+        sys.stdout.write(chunk)
+        sys.stdout.flush()
+
+    print(
+        f"\nAsk a follow-up question, or run a debugger command as {DEBUGGER_ESCAPE}<command>. "
+        "End with quit or Ctrl-D."
+    )
+    try:
+        while True:
+            try:
+                line = input("copilot> ").strip()
+            except (EOFError, KeyboardInterrupt):
+                print()
+                return ExitCode.OK
+            if line in ("quit", "exit"):
+                return ExitCode.OK
+            if not line:
+                continue
+            try:
+                output = state.handle(line, llm, run_command, on_chunk=write)
+            except (RuntimeError, ValueError) as exc:
+                # LlmError and BackendUnavailableError included: the conversation goes on.
+                print(f"\nerror: {exc}")
+                continue
+            # An answer has already been streamed; debugger output is printed here.
+            print(output if line.startswith(DEBUGGER_ESCAPE) else "")
+    finally:
+        if transcript:
+            try:
+                with open(transcript, "w", encoding="utf-8") as fh:
+                    fh.write(state.transcript_text())
+            except OSError as exc:
+                print(f"dbgcopilot-analyze: cannot write the transcript: {exc}", file=sys.stderr)
+
+
+def _settings(ns: argparse.Namespace) -> "Settings":
+    """dbgcopilot.toml and DBGCOPILOT_* settings, overridden by the flags given in ``ns``.
+
+    A prompt template and the price table are loaded here, so a typo in them stops the command before any work.
+    """
+    from dbgcopilot.analysis.template import template_from_config
+    from dbgcopilot.llm.usage import UsageMeter
+    from dbgcopilot.utils.config import load_settings
+
+    flags = {
+        "llm.provider": getattr(ns, "provider", None),
+        "llm.model": getattr(ns, "model", None),
+        "llm.base_url": getattr(ns, "base_url", None),
+        "llm.prompt_template": getattr(ns, "prompt_template", None),
+        "llm.prompt_fault_context": getattr(ns, "prompt_fault_context", None),
+        "llm.max_cost": getattr(ns, "max_cost", None),
+        "analysis.debugger": getattr(ns, "debugger", None),
+        "analysis.timeout": getattr(ns, "timeout", None),
+        "analysis.ignore_signals": getattr(ns, "ignore_signal", None),
+        "analysis.max_frames_per_thread": getattr(ns, "max_frames_per_thread", None),
+        "analysis.exception_policy": getattr(ns, "exception_policy", None),
+        "symbols.path": getattr(ns, "symbol_path", None),
+        "history.enabled": False if getattr(ns, "no_history", False) else None,
+        "dumps.dirs": getattr(ns, "dir", None),
+    }
+    settings = load_settings(flags, path=ns.config)
+    template_from_config(settings.llm_config())
+    UsageMeter.from_config(settings.llm_config())
+    return settings
+
+
+def _frame_limit(settings: "Settings") -> int:
+    from dbgcopilot.analysis.threads import DEFAULT_MAX_FRAMES_PER_THREAD
+
+    return settings.get("analysis.max_frames_per_thread", DEFAULT_MAX_FRAMES_PER_THREAD)
+
+
+def _exception_policy(settings: "Settings") -> "ExceptionPolicy":
+    from dbgcopilot.analysis.exception import ExceptionPolicy
+
+    return ExceptionPolicy(settings.get("analysis.exception_policy", ExceptionPolicy.SECOND_CHANCE.value))
+
+
+def _save_history(settings: "Settings", reports: Iterable["AnalysisReport"], program: Optional[str]) -> None:
+    """Store the crashes and hangs among ``reports`` for ``dbgcopilot history``; a store that fails only warns."""
+    from dbgcopilot.analysis.store import CrashStore, StoreError
+
+    reports = [r for r in reports if r.crashed or r.hung]
+    if not reports or not settings.get("history.enabled", True):
+        return
+    try:
+        with CrashStore(settings.get("history.path")) as store:
+            for report in reports:
+                store.add(report, program)
+    except StoreError as exc:
+        print(f"dbgcopilot: not saved to history: {exc}", file=sys.stderr)
+
+
+def _emit_repro(report: "AnalysisReport", directory: str) -> None:
+    from dbgcopilot.analysis.repro import build_repro
+
+    # Notes go to stderr, so --format json output stays parseable.
+    repro = build_repro(report.crash_report()) if report.crashed else None
+    if repro is None:
+        print(f"dbgcopilot-analyze: no reproduction template for {report.fault_kind.label}", file=sys.stderr)
+        return
+    try:
+        repro.write(directory)
+    except OSError as exc:
+        print(f"dbgcopilot-analyze: cannot write the reproduction: {exc}", file=sys.stderr)
+        return
+    print(
+        f"Reproduction of the {repro.fault_kind.label} in {repro.function}() written to {directory} "
+        f"(run: cd {directory} && {repro.command})",
+        file=sys.stderr,
+    )
+
+
+def _stream_explanation(report: "AnalysisReport", request: "AnalyzeRequest", heading: str = "Explanation:") -> None:
+    from dbgcopilot.analysis.api import explain
+
+    started = False
+
+    def write(chunk: str) -> None:
+        nonlocal started
+        if not started:
+            sys.stdout.write(f"\n{heading}\n")
+            started = True
+        sys.stdout.write(chunk)
+        sys.stdout.flush()
+
+    explain(report, dataclasses.replace(request, on_explain_chunk=write))
+    if started:
+        sys.stdout.write("\n")
+    if report.explanation_partial:
+        # Already on screen; say plainly that it is not the whole answer.
+        print(f"[explanation incomplete: {report.explanation_error}]")
+    elif report.explanation_error:
+        print(f"\nExplanation failed: {report.explanation_error}")
+
+
+def _print_fix_suggestion(
+    report: "AnalysisReport", request: "AnalyzeRequest", heading: str = "Suggested fix:"
+) -> None:
+    from dbgcopilot.analysis.api import add_fix_suggestion
+
+    add_fix_suggestion(report, request)
+    if report.fix_suggestion is not None:
+        print(f"\n{heading}\n" + "\n".join(report.fix_suggestion.render()))
+    else:
+        print(f"\nFix suggestion failed: {report.fix_suggestion_error}")
+
+
+def _print_usage(report: "AnalysisReport") -> None:
+    # On stderr, so it stays out of the JSON on stdout.
+    if report.usage is not None:
+        print(report.usage.describe(), file=sys.stderr)
+
+
+_DURATION_RE = re.compile(r"^\s*(\d+(?:\.\d*)?|\.\d+)\s*(ms|s|m)?\s*$")
+_DURATION_UNITS = {"ms": 0.001, "s": 1.0, "m": 60.0}
+
+
+def parse_duration(text: str) -> float:
+    """Seconds in ``5s``, ``500ms``, ``2m``, or a bare number of seconds."""
+    match = _DURATION_RE.match(text)
+    if not match or float(match.group(1)) <= 0:
+        raise argparse.ArgumentTypeError(f"invalid duration {text!r} (expected e.g. 5s, 500ms, 2m)")
+    return float(match.group(1)) * _DURATION_UNITS[match.group(2) or "s"]
+
+
+_AGE_RE = re.compile(r"^\s*(\d+(?:\.\d*)?)\s*(m|h|d|w)\s*$")
+_AGE_UNITS = {"m": 60.0, "h": 3600.0, "d": 86400.0, "w": 604800.0}
+
+
+def parse_since(text: str, now: Optional[float] = None) -> float:
+    """Seconds since the epoch for ``7d``, ``12h``, ``30m``, or ``2w`` ago, or for a local ``2026-10-01[ 14:00]``."""
+    import datetime
+    import time
+
+    match = _AGE_RE.match(text)
+    if match:
+        return (time.time() if now is None else now) - float(match.group(1)) * _AGE_UNITS[match.group(2)]
+    try:
+        return datetime.datetime.fromisoformat(text.strip()).timestamp()
+    except ValueError:
+        raise argparse.ArgumentTypeError(f"invalid time {text!r} (expected e.g. 7d, 12h, or 2026-10-01)") from None
+
+
+def watch_main(argv: Optional[list[str]] = None) -> int:
+    from dbgcopilot.analysis.api import AnalysisError, AnalyzeRequest, analyze
+    from dbgcopilot.utils.trace import configure_logging
+
+    parser = argparse.ArgumentParser(
+        prog="dbgcopilot watch",
+        description="Launch a program; if it has not exited within the hang timeout, attach, "
+        "sample every thread's stack, and classify the hang.",
+        epilog="Arguments after -- are passed to the program. A program that exits in time is not "
+        "attached to; its exit code (or crash) is reported instead.",
+    )
+    parser.add_argument("--launch", required=True, metavar="BINARY", help="program to run and watch")
+    parser.add_argument("args", nargs=argparse.REMAINDER, help="program arguments, after --")
+    parser.add_argument(
+        "--hang-timeout",
+        type=parse_duration,
+        default=10.0,
+        metavar="DURATION",
+        help="how long the program may run before it counts as hung (default: 10s)",
+    )
+    parser.add_argument("--debugger", choices=("gdb", "lldb"), help="debugger to attach with")
+    parser.add_argument("--format", choices=("text", "json"), default="text", help="output format (default: text)")
+    parser.add_argument("--provider", help="LLM provider to explain the hang (see docs/llm.md); omit to skip it")
+    parser.add_argument("--model", help="model the provider should use, instead of its default")
+    parser.add_argument("--base-url", metavar="URL", help="OpenAI-compatible endpoint to use instead of the default")
+    parser.add_argument("--no-history", action="store_true", help="do not save this analysis to dbgcopilot history")
+    parser.add_argument("--config", metavar="PATH", help=f"settings file to read instead of {CONFIG_FILENAME}")
+    _add_color_option(parser)
+    ns = parser.parse_args(argv)
+    try:
+        configure_logging()
+        # The hang timeout is watch's own; analysis.timeout is for runs that are expected to crash.
+        settings = _settings(ns)
+    except ValueError as exc:
+        parser.error(str(exc))
+
+    args = ns.args[1:] if ns.args[:1] == ["--"] else ns.args
+    request = AnalyzeRequest(
+        binary=ns.launch,
+        args=args,
+        timeout=ns.hang_timeout,
+        debugger=settings.get("analysis.debugger"),
+        provider=settings.get("llm.provider"),
+        llm_config=settings.llm_config(),
+        max_frames_per_thread=_frame_limit(settings),
+        symbol_path=settings.get("symbols.path"),
+    )
+    try:
+        report = analyze(request)
+    except AnalysisError as exc:
+        print(f"dbgcopilot watch: {exc}", file=sys.stderr)
+        return ExitCode.CAPTURE_FAILED
+    _save_history(settings, [report], request.binary)
+    print(report.to_json() if ns.format == "json" else _render_text(report, _report_color(ns.color)))
+    _print_usage(report)
+    return _exit_status(report, bool(request.provider))
+
+
+def run_main(argv: Optional[list[str]] = None) -> int:
+    from dbgcopilot.analysis.api import AnalysisError, AnalyzeRequest, Outcome, analyze
+    from dbgcopilot.backends.signal_passthrough import signal_names
+    from dbgcopilot.utils.trace import configure_logging
+
+    parser = argparse.ArgumentParser(
+        prog="dbgcopilot run",
+        description="Launch a program under the debugger and, when it faults, capture and explain the crash "
+        "on that same run.",
+        epilog="Everything after -- is the program and its arguments. A program that exits is reported with its "
+        "exit code; one still running after --max-runtime is attached to and analyzed as a hang.",
+    )
+    parser.add_argument("command", nargs=argparse.REMAINDER, help="-- BINARY [ARGS...]")
+    parser.add_argument(
+        "--max-runtime",
+        dest="timeout",
+        type=parse_duration,
+        metavar="DURATION",
+        help="how long the program may run before it counts as hung (default: analysis.timeout, 30s)",
+    )
+    parser.add_argument("--debugger", choices=("gdb", "lldb"), help="debugger to launch under")
+    parser.add_argument("--format", choices=("text", "json"), default="text", help="output format (default: text)")
+    parser.add_argument("--provider", help="LLM provider to explain the crash (see docs/llm.md); omit to skip it")
+    parser.add_argument("--model", help="model the provider should use, instead of its default")
+    parser.add_argument("--base-url", metavar="URL", help="OpenAI-compatible endpoint to use instead of the default")
+    parser.add_argument("--no-llm", action="store_true", help="build the prompt but never call a model")
+    parser.add_argument(
+        "--exception-policy",
+        choices=("first-chance", "second-chance"),
+        help="capture a fault when the debugger first sees it, or only if the program's handlers do not take it "
+        "(default: analysis.exception_policy, or second-chance)",
+    )
+    parser.add_argument("--no-history", action="store_true", help="do not save this analysis to dbgcopilot history")
+    parser.add_argument("--config", metavar="PATH", help=f"settings file to read instead of {CONFIG_FILENAME}")
+    _add_color_option(parser)
+    ns = parser.parse_args(argv)
+    command = ns.command[1:] if ns.command[:1] == ["--"] else ns.command
+    if not command:
+        parser.error("expected the program to run after --")
+    try:
+        configure_logging()
+        settings = _settings(ns)
+        ignore_signals = signal_names(settings.get("analysis.ignore_signals"))
+    except ValueError as exc:
+        parser.error(str(exc))
+
+    request = AnalyzeRequest(
+        binary=command[0],
+        args=command[1:],
+        timeout=settings.get("analysis.timeout", 30.0),
+        debugger=settings.get("analysis.debugger"),
+        provider=settings.get("llm.provider"),
+        llm_config=settings.llm_config(),
+        no_llm=ns.no_llm,
+        ignore_signals=ignore_signals,
+        exception_policy=_exception_policy(settings),
+        max_frames_per_thread=_frame_limit(settings),
+        symbol_path=settings.get("symbols.path"),
+        under_debugger=True,
+    )
+    try:
+        report = analyze(request)
+    except AnalysisError as exc:
+        print(f"dbgcopilot run: {exc}", file=sys.stderr)
+        return ExitCode.CAPTURE_FAILED
+    _save_history(settings, [report], request.binary)
+    if ns.format == "json":
+        print(report.to_json())
+    else:
+        print(_render_text(report, _report_color(ns.color)))
+        if report.outcome is Outcome.EXITED:
+            print(f"\nNo fault: the program exited with code {report.stop.exit_code}.")
+    _print_usage(report)
+    return _exit_status(report, bool(request.provider) and not request.no_llm)
+
+
+def check_main(argv: Optional[list[str]] = None) -> int:
+    import json
+
+    from dbgcopilot.symbols import check_debug_info
+    from dbgcopilot.utils.elf import ElfError
+
+    parser = argparse.ArgumentParser(
+        prog="dbgcopilot check",
+        description="Report the debug sections a binary carries, whether it is stripped, and whether its separate "
+        "debug file can be found, i.e. how its frames will look in a crash report.",
+    )
+    parser.add_argument("binary", help="ELF executable or shared library to check")
+    parser.add_argument(
+        "--debug-file",
+        metavar="PATH",
+        help="separate debug info to verify, instead of looking it up by build-id and .gnu_debuglink",
+    )
+    parser.add_argument("--format", choices=("text", "json"), default="text", help="output format (default: text)")
+    ns = parser.parse_args(argv)
+    try:
+        check = check_debug_info(ns.binary, debug_file=ns.debug_file)
+    except (OSError, ElfError) as exc:
+        print(f"dbgcopilot check: {ns.binary}: {exc}", file=sys.stderr)
+        return ExitCode.CAPTURE_FAILED
+    print(json.dumps(check.to_dict(), indent=2) if ns.format == "json" else check.render())
+    return ExitCode.OK
+
+
+def batch_main(argv: Optional[list[str]] = None) -> int:
+    import json
+
+    from dbgcopilot.analysis.api import AnalyzeRequest
+    from dbgcopilot.analysis.batch import DUMP_PATTERNS, analyze_batch
+    from dbgcopilot.analysis.cache import ExplanationCache
+    from dbgcopilot.utils.trace import configure_logging
+
+    parser = argparse.ArgumentParser(
+        prog="dbgcopilot batch",
+        description="Analyze every core and minidump in a directory and group them by crash signature: "
+        "one row of signature, count, example dump, and cause per distinct crash.",
+        epilog="Each signature is explained once, however many dumps share it. A dump that cannot be read "
+        "is listed as a failure and the batch goes on.",
+    )
+    parser.add_argument("directory", help="directory of core files and minidumps")
+    parser.add_argument("--binary", default="", help="program that wrote the cores, for their symbols")
+    parser.add_argument(
+        "--glob",
+        action="append",
+        metavar="PATTERN",
+        help="file names to analyze (repeatable; default: core, core.*, *.core, *.dmp, *.mdmp)",
+    )
+    parser.add_argument("--format", choices=("text", "json"), default="text", help="output format (default: text)")
+    parser.add_argument(
+        "--debugger", choices=("gdb", "lldb"), help="debugger for cores the built-in reader cannot read"
+    )
+    parser.add_argument("--provider", help="LLM provider for the explanations (see docs/llm.md); omit to skip them")
+    parser.add_argument("--model", help="model the provider should use, instead of its default")
+    parser.add_argument("--base-url", metavar="URL", help="OpenAI-compatible endpoint to use instead of the default")
+    parser.add_argument("--config", metavar="PATH", help=f"settings file to read instead of {CONFIG_FILENAME}")
+    parser.add_argument("--no-cache", action="store_true", help="do not reuse explanations from earlier runs")
+    parser.add_argument("--no-history", action="store_true", help="do not save this analysis to dbgcopilot history")
+    parser.add_argument(
+        "--cache-dir", help="explanation cache directory (default: dbgcopilot/explanations in the user cache directory)"
+    )
+    ns = parser.parse_args(argv)
+    try:
+        configure_logging()
+        settings = _settings(ns)
+    except ValueError as exc:
+        parser.error(str(exc))
+
+    request = AnalyzeRequest(
+        binary=ns.binary,
+        debugger=settings.get("analysis.debugger"),
+        provider=settings.get("llm.provider"),
+        llm_config=settings.llm_config(),
+        cache=None if ns.no_cache else ExplanationCache(ns.cache_dir),
+        max_frames_per_thread=_frame_limit(settings),
+        symbol_path=settings.get("symbols.path"),
+    )
+    try:
+        result = analyze_batch(ns.directory, request, ns.glob or DUMP_PATTERNS)
+    except OSError as exc:
+        print(f"dbgcopilot batch: cannot read {ns.directory}: {exc}", file=sys.stderr)
+        return ExitCode.CAPTURE_FAILED
+    if not result.entries:
+        print(f"dbgcopilot batch: no dump files in {ns.directory}", file=sys.stderr)
+        return ExitCode.CAPTURE_FAILED
+    _save_history(settings, (e.report for e in result.entries if e.report is not None), request.binary)
+    if ns.format == "json":
+        print(json.dumps(result.to_dict(), indent=2))
+    else:
+        print("\n".join(result.table()))
+        for entry in result.failures:
+            error = entry.error or ""
+            print(f"failed: {error}" if error.startswith(entry.path) else f"failed: {entry.path}: {error}")
+    usage = result.usage()
+    if usage is not None:
+        print(usage.describe(), file=sys.stderr)
+    return _batch_status(result, request)
+
+
+def _load_for_diff(path: str, binary: str, request: "AnalyzeRequest") -> "CrashReport":
+    """A report saved with --format json, or the report of a core or minidump (``binary`` symbolizes a core)."""
+    import json
+
+    from dbgcopilot.analysis.api import analyze
+    from dbgcopilot.analysis.crash import CrashReport
+
+    with open(path, "rb") as fh:
+        head = fh.read(1)
+    if head == b"{":
+        with open(path, encoding="utf-8") as fh:
+            return CrashReport.from_dict(json.load(fh))
+    report = analyze(dataclasses.replace(request, binary=binary, core=path))
+    return report.crash_report(max_frames=len(report.frames))
+
+
+def diff_main(argv: Optional[list[str]] = None) -> int:
+    import json
+
+    from dbgcopilot.analysis.api import AnalysisError, AnalyzeRequest
+    from dbgcopilot.analysis.crash import SIGNATURE_DEPTH
+    from dbgcopilot.analysis.diff import diff_reports, explain_diff
+    from dbgcopilot.utils.trace import configure_logging
+
+    parser = argparse.ArgumentParser(
+        prog="dbgcopilot diff",
+        description="Compare two crashes, as reports saved with --format json or as core files and minidumps: "
+        "same failure, a different one, or fixed, and which modules and frames changed.",
+    )
+    parser.add_argument("before", help="report or dump of the earlier run")
+    parser.add_argument("after", help="report or dump of the later run")
+    parser.add_argument("--before-binary", default="", metavar="PATH", help="program that wrote the earlier core")
+    parser.add_argument("--after-binary", default="", metavar="PATH", help="program that wrote the later core")
+    parser.add_argument(
+        "--depth",
+        type=int,
+        default=SIGNATURE_DEPTH,
+        help=f"top frames to compare (default: {SIGNATURE_DEPTH})",
+    )
+    parser.add_argument("--format", choices=("text", "json"), default="text", help="output format (default: text)")
+    parser.add_argument(
+        "--debugger", choices=("gdb", "lldb"), help="debugger for cores the built-in reader cannot read"
+    )
+    parser.add_argument("--provider", help="LLM provider to explain the differences (see docs/llm.md); omit to skip it")
+    parser.add_argument("--model", help="model the provider should use, instead of its default")
+    parser.add_argument("--base-url", metavar="URL", help="OpenAI-compatible endpoint to use instead of the default")
+    parser.add_argument("--config", metavar="PATH", help=f"settings file to read instead of {CONFIG_FILENAME}")
+    ns = parser.parse_args(argv)
+    try:
+        configure_logging()
+        settings = _settings(ns)
+    except ValueError as exc:
+        parser.error(str(exc))
+
+    request = AnalyzeRequest(
+        binary="",
+        debugger=settings.get("analysis.debugger"),
+        max_frames_per_thread=_frame_limit(settings),
+        symbol_path=settings.get("symbols.path"),
+    )
+    reports = []
+    for path, binary in ((ns.before, ns.before_binary), (ns.after, ns.after_binary)):
+        try:
+            reports.append(_load_for_diff(path, binary, request))
+        except AnalysisError as exc:
+            print(f"dbgcopilot diff: cannot analyze {path}: {exc}", file=sys.stderr)
+            return ExitCode.CAPTURE_FAILED
+        except (OSError, ValueError, KeyError, TypeError) as exc:
+            print(f"dbgcopilot diff: cannot read report {path}: {exc}", file=sys.stderr)
+            return ExitCode.CAPTURE_FAILED
+    result = diff_reports(reports[0], reports[1], depth=ns.depth)
+    if settings.get("llm.provider"):
+        explain_diff(result, reports[0], reports[1], settings.get("llm.provider"), settings.llm_config())
+    print(json.dumps(result.to_dict(), indent=2) if ns.format == "json" else result.describe())
+    return ExitCode.LLM_FAILED if result.explanation_error else ExitCode.OK
+
+
+def history_main(argv: Optional[list[str]] = None) -> int:
+    import json
+
+    from dbgcopilot.analysis.render import render_report, render_terminal
+    from dbgcopilot.analysis.store import DEFAULT_LIMIT, CrashStore, StoreError
+
+    parser = argparse.ArgumentParser(
+        prog="dbgcopilot history",
+        description="List the crashes and hangs analyzed before, newest first, with how many times each "
+        "signature has been seen; or show one of them in full.",
+        epilog="dbgcopilot-analyze, run, watch, batch, and serve save every crash they analyze unless given "
+        "--no-history or history.enabled = false.",
+    )
+    parser.add_argument("session", nargs="?", type=int, metavar="ID", help="print this session's full report")
+    parser.add_argument("--kind", metavar="FAULT_KIND", help="only this fault kind, e.g. null_deref")
+    parser.add_argument("--function", metavar="NAME", help="only crashes in a function whose name contains NAME")
+    parser.add_argument("--program", metavar="NAME", help="only crashes of this program")
+    parser.add_argument("--signature", metavar="HASH", help="only crashes whose signature hash starts with HASH")
+    parser.add_argument("--since", type=parse_since, metavar="WHEN", help="only crashes since 7d, 12h, or 2026-10-01")
+    parser.add_argument("--unique", action="store_true", help="one row per signature: its latest session")
+    parser.add_argument(
+        "--limit",
+        type=int,
+        default=DEFAULT_LIMIT,
+        metavar="N",
+        help=f"rows to list, 0 for all (default: {DEFAULT_LIMIT})",
+    )
+    parser.add_argument("--format", choices=("text", "json"), default="text", help="output format (default: text)")
+    parser.add_argument(
+        "--db",
+        metavar="PATH",
+        help="history database (default: history.path, or history.sqlite3 in the user data directory)",
+    )
+    parser.add_argument("--config", metavar="PATH", help=f"settings file to read instead of {CONFIG_FILENAME}")
+    _add_color_option(parser)
+    ns = parser.parse_args(argv)
+    if ns.limit < 0:
+        parser.error("--limit must not be negative")
+    try:
+        settings = _settings(ns)
+    except ValueError as exc:
+        parser.error(str(exc))
+
+    try:
+        with CrashStore(ns.db or settings.get("history.path")) as store:
+            if ns.session is not None:
+                found = store.get(ns.session)
+                if found is None:
+                    print(f"dbgcopilot history: no session {ns.session} in {store.path}", file=sys.stderr)
+                    return ExitCode.CAPTURE_FAILED
+                if ns.format == "json":
+                    print(json.dumps(found.to_dict(), indent=2))
+                    return ExitCode.OK
+                seen = f"seen {_seen(found)} (signature {found.signature_hash[:12]})"
+                print(f"Session {found.id}, {_when(found)}: {seen}")
+                color = _report_color(ns.color)
+                report = found.report or {}
+                print(render_report(report) if color is None else render_terminal(report, color=color))
+                return ExitCode.OK
+            sessions = store.sessions(
+                fault_kind=ns.kind,
+                function=ns.function,
+                program=ns.program,
+                signature=ns.signature,
+                since=ns.since,
+                limit=ns.limit,
+                unique=ns.unique,
+            )
+    except StoreError as exc:
+        print(f"dbgcopilot history: {exc}", file=sys.stderr)
+        return ExitCode.CAPTURE_FAILED
+    if ns.format == "json":
+        print(json.dumps([s.to_dict() for s in sessions], indent=2))
+        return ExitCode.OK
+    if not sessions:
+        print("No matching sessions.")
+        return ExitCode.OK
+    rows = [("ID", "WHEN", "PROGRAM", "FAULT", "FUNCTION", "SIGNATURE", "SEEN")]
+    for session in sessions:
+        rows.append((
+            str(session.id), _when(session), session.program or "", session.fault_kind, session.function or "",
+            session.signature_hash[:12], _seen(session),
+        ))
+    widths = [max(len(row[i]) for row in rows) for i in range(len(rows[0]) - 1)]
+    for row in rows:
+        print("  ".join([c.ljust(w) for c, w in zip(row, widths)] + [row[-1]]).rstrip())
+    return ExitCode.OK
+
+
+def _when(session: "StoredSession") -> str:
+    """``2024-05-01 14:03``, in local time."""
+    import time
+
+    return time.strftime("%Y-%m-%d %H:%M", time.localtime(session.created))
+
+
+def _seen(session: "StoredSession") -> str:
+    """``once`` or ``3 times``."""
+    return "once" if session.seen == 1 else f"{session.seen} times"
+
+
+def _age(seconds: float) -> str:
+    """``40s``, ``12m``, ``3h``, or ``2d``."""
+    for unit, size in (("d", 86400), ("h", 3600), ("m", 60)):
+        if seconds >= size:
+            return f"{int(seconds // size)}{unit}"
+    return f"{max(int(seconds), 0)}s"
+
+
+def last_main(argv: Optional[list[str]] = None) -> int:
+    import shutil
+    import time
+
+    from dbgcopilot.dumps import DumpError
+    from dbgcopilot.dumps.discover import decompressed, dump_locations, find_dumps, latest_dump
+
+    parser = argparse.ArgumentParser(
+        prog="dbgcopilot last",
+        description="Analyze the newest dump of a program, from where the system keeps them: Windows Error "
+        "Reporting's LocalDumps folder (%%LOCALAPPDATA%%\\CrashDumps by default), or the directory "
+        "/proc/sys/kernel/core_pattern names (systemd-coredump's and apport's included).",
+        epilog="Other options (--format, --provider, --no-llm, ...) are passed on to dbgcopilot-analyze.",
+    )
+    parser.add_argument("program", help="the program whose dump to analyze: a name (crash.exe) or a path")
+    parser.add_argument(
+        "--dir",
+        action="append",
+        metavar="DIR",
+        help="search DIR first, such as a crashpad database (repeatable; default: dumps.dirs)",
+    )
+    parser.add_argument(
+        "--since",
+        type=parse_since,
+        metavar="WHEN",
+        help="only a dump written since 7d, 12h, or 2026-10-01; an older one is reported, not analyzed",
+    )
+    parser.add_argument("--list", action="store_true", help="list the dumps found, newest first, and stop")
+    parser.add_argument("--config", metavar="PATH", help=f"settings file to read instead of {CONFIG_FILENAME}")
+    ns, rest = parser.parse_known_args(argv)
+    try:
+        settings = _settings(ns)
+    except ValueError as exc:
+        parser.error(str(exc))
+
+    locations = dump_locations(ns.program, extra_dirs=settings.get("dumps.dirs") or [])
+    now = time.time()
+    if ns.list:
+        dumps = find_dumps(ns.program, locations)
+        for dump in dumps:
+            print(f"{_age(now - dump.mtime):>4} ago  {dump.path}")
+        if not dumps:
+            print(f"No dumps of {os.path.basename(ns.program)} found.")
+        return ExitCode.OK
+    try:
+        dump = latest_dump(ns.program, locations)
+        if ns.since is not None and dump.mtime < ns.since:
+            print(
+                f"dbgcopilot last: the newest dump of {os.path.basename(ns.program)}, {dump.path}, was written "
+                f"{_age(now - dump.mtime)} ago, before --since; not analyzing it",
+                file=sys.stderr,
+            )
+            return ExitCode.CAPTURE_FAILED
+        core = decompressed(dump)
+    except DumpError as exc:
+        print(f"dbgcopilot last: {exc}", file=sys.stderr)
+        return ExitCode.CAPTURE_FAILED
+    program = ns.program if os.path.exists(ns.program) else shutil.which(ns.program)
+    if program is None:
+        print(
+            f"dbgcopilot last: found {dump.path}, but not {ns.program} itself; give the program's path",
+            file=sys.stderr,
+        )
+        return ExitCode.CAPTURE_FAILED
+    print(f"dbgcopilot last: analyzing {dump.path}, written {_age(now - dump.mtime)} ago", file=sys.stderr)
+    config = ["--config", ns.config] if ns.config else []
+    return analyze_main([*rest, *config, "--core", core, program])
+
+
+def serve_main(argv: Optional[list[str]] = None) -> int:
+    from dbgcopilot.analysis.api import AnalyzeRequest
+    from dbgcopilot.analysis.cache import ExplanationCache
+    from dbgcopilot.serve import Server
+    from dbgcopilot.utils.trace import configure_logging
+
+    parser = argparse.ArgumentParser(
+        prog="dbgcopilot serve",
+        description="Answer JSON-RPC 2.0 requests on stdin (analyzeDump, analyzePid, askFollowup), framed with "
+        "Content-Length headers as in the Language Server Protocol, for editor extensions.",
+        epilog="Explanations and answers stream as explanationChunk and followupChunk notifications; "
+        "see docs/analysis.md#editor-integration.",
+    )
+    parser.add_argument("--debugger", choices=("gdb", "lldb"), help="debugger for attaching and for ! commands")
+    parser.add_argument("--provider", help="LLM provider for explanations and follow-ups (see docs/llm.md)")
+    parser.add_argument("--model", help="model the provider should use, instead of its default")
+    parser.add_argument("--base-url", metavar="URL", help="OpenAI-compatible endpoint to use instead of the default")
+    parser.add_argument("--config", metavar="PATH", help=f"settings file to read instead of {CONFIG_FILENAME}")
+    parser.add_argument("--no-llm", action="store_true", help="build the prompts but never call a model")
+    parser.add_argument("--no-cache", action="store_true", help="always ask the provider, ignoring cached explanations")
+    parser.add_argument("--no-history", action="store_true", help="do not save the analyses to dbgcopilot history")
+    ns = parser.parse_args(argv)
+    try:
+        configure_logging()
+        settings = _settings(ns)
+    except ValueError as exc:
+        parser.error(str(exc))
+
+    request = AnalyzeRequest(
+        binary="",
+        debugger=settings.get("analysis.debugger"),
+        provider=settings.get("llm.provider"),
+        llm_config=settings.llm_config(),
+        cache=None if ns.no_cache else ExplanationCache(),
+        no_llm=ns.no_llm,
+        exception_policy=_exception_policy(settings),
+        max_frames_per_thread=_frame_limit(settings),
+        symbol_path=settings.get("symbols.path"),
+    )
+    output = sys.stdout.buffer
+    # Stdout carries the protocol alone; anything else printed goes to stderr.
+    sys.stdout = sys.stderr
+    server = Server(
+        request,
+        output,
+        on_report=lambda report, used: _save_history(settings, [report], used.binary or None),
+        command_runner=_debugger_runner,
+    )
+    try:
+        return server.serve(sys.stdin.buffer)
+    finally:
+        sys.stdout = sys.__stdout__
+
+
 if __name__ == "__main__":
     print_plugin_path()
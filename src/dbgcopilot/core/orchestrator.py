@@ -7,6 +7,7 @@ and manage session state. Currently a minimal placeholder.
 from __future__ import annotations
 
 from typing import Optional, List, Any, Dict
+import logging
 import re
 from dbgcopilot.core.state import Attempt, SessionState, resolve_auto_round_limit
 from dbgcopilot.llm import providers
@@ -15,6 +16,19 @@ from pathlib import Path
 import os
 import json
 from dbgcopilot.prompts.defaults import DEFAULT_PROMPT_CONFIG
+from dbgcopilot.analysis.fault import fault_hint
+from dbgcopilot.analysis.model import CrashContext
+from dbgcopilot.analysis.parsers import (
+    parse_gdb_backtrace,
+    parse_gdb_registers,
+    parse_gdb_stop,
+    parse_lldb_backtrace,
+    parse_lldb_registers,
+    parse_lldb_stop,
+)
+from dbgcopilot.analysis.redact import Redactor
+
+log = logging.getLogger(__name__)
 
 DEFAULT_MAX_CONTEXT_CHARS = int(DEFAULT_PROMPT_CONFIG.get("max_context_chars", 16000))
 
@@ -333,6 +347,8 @@ class CopilotOrchestrator:
                 self.state.attempts.clear()
                 self.state.facts.clear()
                 self.state.last_output = ""
+                self.state.crash_context = None
+                self.state.fault_hint = None
                 if prev_summary:
                     self.state.facts.append(f"Summary: {prev_summary.splitlines()[0][:160]}")
                 return (
@@ -350,6 +366,8 @@ class CopilotOrchestrator:
                 self.state.attempts.clear()
                 self.state.facts.clear()
                 self.state.last_output = ""
+                self.state.crash_context = None
+                self.state.fault_hint = None
                 return f"Started a fresh session: {self.state.session_id}"
             return (
                 "Your session context is quite large. Would you like me to summarize the "
@@ -374,6 +392,11 @@ class CopilotOrchestrator:
 
         context_block = (
             (f"Goal: {goal}\n" if goal else "")
+            + (
+                f"Crash classification (heuristic, verify against the output): {self.state.fault_hint}\n"
+                if self.state.fault_hint
+                else ""
+            )
             + (f"Recent commands and snippets:\n{attempts_txt}\n" if attempts_txt else "")
             + (f"Last output:\n{last_out}\n" if last_out else "")
             + ("\nFull conversation so far:\n" + "\n".join(self.state.chatlog) + "\n" if self.state.chatlog else "")
@@ -677,6 +700,8 @@ def _prefix_dbg_echo(cmd: str, backend: Any, colors: bool = True) -> str:
 
 
 def _call_llm(provider_name: str, question: str, state: SessionState) -> str:
+    # Debugger output in the prompt can carry env dumps, home paths, and tokens.
+    question = Redactor.from_config(state.config).redact_text(question)
     prov = providers.get_provider(provider_name)
     if provider_name == "openrouter":
         from dbgcopilot.llm import openrouter as _or
@@ -753,6 +778,36 @@ def _execute_once(self: "CopilotOrchestrator", exec_cmd: str) -> tuple[str, bool
         if not streamed:
             self.state.pending_outputs.append(out)
         self.state.facts.append(f"O: {out.splitlines()[0]}")
+        _track_fault(self.state, out)
     return out, streamed
 
 
+def _track_fault(state: SessionState, out: str) -> None:
+    """Update the crash context/classification from a command's output.
+
+    A new signal stop replaces the context; a later backtrace or register dump
+    fills in its frames and registers.
+    """
+    try:
+        stop = parse_gdb_stop(out)
+        if not stop.crashed:
+            stop = parse_lldb_stop(out)
+        frames = parse_gdb_backtrace(out) or parse_lldb_backtrace(out)
+        registers = parse_gdb_registers(out) or parse_lldb_registers(out)
+        ctx = state.crash_context
+        if stop.crashed:
+            ctx = CrashContext(stop=stop, frames=frames)
+        elif not isinstance(ctx, CrashContext) or not (frames or registers):
+            return
+        if frames:
+            ctx.frames = frames
+        if len(registers) >= 4:  # a real register dump, not a stray "name 0x.." line
+            ctx.registers = registers
+        state.crash_context = ctx
+        state.fault_hint = fault_hint(ctx)
+    except Exception:
+        # Tracking is a side effect of showing the output; a parser tripping on
+        # odd debugger text must not fail the command, but it should be findable.
+        log.debug("could not track the fault from the command output", exc_info=True)
+
+
@@ -70,3 +70,6 @@ class SessionState:
     auto_rounds_remaining: Optional[int] = None
     auto_loop_depth: int = 0
     chat_event_sink: Optional[Callable[[Dict[str, Any]], None]] = None
+    # Structured crash data gleaned from debugger output (dbgcopilot.analysis.CrashContext)
+    crash_context: Optional[Any] = None
+    fault_hint: Optional[str] = None
@@ -0,0 +1,12 @@
+"""Postmortem dump readers that work without an external debugger."""
+from __future__ import annotations
+
+from .errors import DumpError, InvalidDumpError, TruncatedDumpError
+from .session import DumpSession
+
+__all__ = [
+    "DumpError",
+    "DumpSession",
+    "InvalidDumpError",
+    "TruncatedDumpError",
+]
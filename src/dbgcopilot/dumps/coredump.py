@@ -0,0 +1,670 @@
+"""Linux ELF core-dump reader that needs no external debugger.
+
+`CoreReader` memory-maps a core file, walks the PT_NOTE segments for
+NT_PRSTATUS / NT_PRPSINFO / NT_SIGINFO / NT_FILE, serves memory reads from the
+PT_LOAD segments (falling back to the mapped files on disk for text pages the
+kernel did not dump), and rebuilds stacks as normalized `Frame` lists.
+
+Registers and stacks are decoded for x86-64 and AArch64 cores on any host.
+Cores from other 64-bit little-endian machines still open: the thread list,
+signal, and memory are available, but threads carry no registers.
+
+Stacks are unwound with each module's call frame information (``.eh_frame``,
+or ``.debug_frame`` from its debug file) wherever a module found on disk has
+it, so builds without frame pointers unwind correctly. Where there is none
+(a module that is not available, JIT code), the frame-pointer chain takes
+over from the registers recovered so far.
+"""
+from __future__ import annotations
+
+import mmap
+import os
+import struct
+import sys
+from dataclasses import dataclass, field
+from pathlib import Path
+from typing import TYPE_CHECKING, Dict, List, Optional, Set, Tuple, Union
+
+from dbgcopilot.analysis.model import CrashContext, Frame, Module, Register, StopInfo, ThreadBacktrace
+from dbgcopilot.analysis.regions import MapEntry, core_memory_map, fault_region
+from dbgcopilot.analysis.registers import Registers
+from dbgcopilot.analysis.threads import DEFAULT_MAX_FRAMES_PER_THREAD
+from dbgcopilot.symbols import DwarfError, Symbolizer
+from dbgcopilot.symbols.cfi import RuleKind, UnwindRow
+from dbgcopilot.symbols.parallel import DEFAULT_MODULE_TIMEOUT, DEFAULT_WORKERS, ModuleFrames, symbolize_modules
+from dbgcopilot.utils.elf import (
+    ET_CORE,
+    EM_AARCH64,
+    EM_X86_64,
+    PT_LOAD,
+    PT_NOTE,
+    ElfError,
+    ElfFile,
+    open_elf,
+    parse_elf,
+)
+
+from .errors import InvalidDumpError, TruncatedDumpError
+
+if TYPE_CHECKING:
+    from dbgcopilot.symbols.sympath import SymbolPath
+
+NT_PRSTATUS = 1
+NT_PRPSINFO = 3
+NT_SIGINFO = 0x53494749
+NT_FILE = 0x46494C45
+
+PF_X = 1
+PF_W = 2
+PF_R = 4
+
+# Order of `struct user_regs_struct` as stored in elf_prstatus.pr_reg.
+X86_64_GREGS = (
+    "r15", "r14", "r13", "r12", "rbp", "rbx", "r11", "r10", "r9", "r8",
+    "rax", "rcx", "rdx", "rsi", "rdi", "orig_rax", "rip", "cs", "eflags",
+    "rsp", "ss", "fs_base", "gs_base", "ds", "es", "fs", "gs",
+)
+# `struct user_pt_regs`: x0-x30, sp, pc, pstate.
+AARCH64_GREGS = tuple(f"x{i}" for i in range(31)) + ("sp", "pc", "pstate")
+# elf_prstatus has the same 112-byte header on every 64-bit Linux architecture.
+_PRSTATUS_REG_OFFSET = 112
+_PRSTATUS_PID_OFFSET = 32
+
+_GREGS = {EM_X86_64: X86_64_GREGS, EM_AARCH64: AARCH64_GREGS}
+# (pc, sp, frame pointer) register names per architecture.
+_REG_ROLES = {EM_X86_64: ("rip", "rsp", "rbp"), EM_AARCH64: ("pc", "sp", "x29")}
+# DWARF numbers of the stack and frame pointer, and how many registers CFI can describe.
+_DWARF_SP = {EM_X86_64: 7, EM_AARCH64: 31}
+_DWARF_FP = {EM_X86_64: 6, EM_AARCH64: 29}
+_DWARF_REGISTERS = {EM_X86_64: 17, EM_AARCH64: 33}
+# AArch64 user addresses fit in 48 bits; with pointer authentication the bits above hold the
+# signature of a saved return address (x30 and each frame record's lr).
+_AARCH64_VA_MASK = (1 << 48) - 1
+
+LINUX_SIGNALS = {
+    1: "SIGHUP", 2: "SIGINT", 3: "SIGQUIT", 4: "SIGILL", 5: "SIGTRAP", 6: "SIGABRT",
+    7: "SIGBUS", 8: "SIGFPE", 9: "SIGKILL", 10: "SIGUSR1", 11: "SIGSEGV", 12: "SIGUSR2",
+    13: "SIGPIPE", 14: "SIGALRM", 15: "SIGTERM", 31: "SIGSYS",
+}
+
+_DEFAULT_MAX_FRAMES = DEFAULT_MAX_FRAMES_PER_THREAD
+
+
+@dataclass
+class CoreThread:
+    tid: int
+    signal: int
+    registers: List[Register] = field(default_factory=list)
+
+    def reg(self, name: str) -> Optional[int]:
+        for r in self.registers:
+            if r.name == name:
+                return r.value
+        return None
+
+
+@dataclass
+class CoreProcess:
+    pid: int
+    name: str
+    args: str
+
+
+@dataclass
+class SigInfo:
+    signo: int
+    code: int
+    addr: Optional[int]
+
+
+@dataclass
+class Mapping:
+    start: int
+    end: int
+    offset: int
+    path: str
+
+
+@dataclass
+class LoadSegment:
+    vaddr: int
+    memsz: int
+    offset: int
+    filesz: int
+    flags: int
+
+
+class CoreReader:
+    """Read-only view of a Linux ELF core file."""
+
+    def __init__(
+        self,
+        path: Union[str, Path],
+        *,
+        executable: Optional[Union[str, Path]] = None,
+        sysroot: Optional[Union[str, Path]] = None,
+        debug_file: Optional[Union[str, Path]] = None,
+        symbol_path: Optional["SymbolPath"] = None,
+        workers: int = DEFAULT_WORKERS,
+        module_timeout: float = DEFAULT_MODULE_TIMEOUT,
+    ) -> None:
+        self.path = Path(path)
+        self.executable = Path(executable) if executable else None
+        self.sysroot = Path(sysroot) if sysroot else None
+        # Separate debug file of ``executable``, instead of searching by build-id and debuglink.
+        self.debug_file = Path(debug_file) if debug_file else None
+        # Where the images' debug files are searched for and downloaded from; the system's directories if None.
+        self.symbol_path = symbol_path
+        # Images are symbolized in parallel, each within ``module_timeout`` seconds; see `symbolize_modules`.
+        self.workers = workers
+        self.module_timeout = module_timeout
+        # One per image that ran out of time; its frames keep only their pc and module.
+        self.warnings: List[str] = []
+        self.threads: List[CoreThread] = []
+        self.process: Optional[CoreProcess] = None
+        self.siginfo: Optional[SigInfo] = None
+        self.mappings: List[Mapping] = []
+        self.segments: List[LoadSegment] = []
+        self.arch: Optional[str] = None
+        self._machine = 0
+        self._modules: Dict[str, Optional[ElfFile]] = {}
+        self._symbolizers: Dict[str, Symbolizer] = {}
+        # Images that ran out of time once are not tried again.
+        self._timed_out: Set[str] = set()
+        self._fh = None
+        self._data: Union[bytes, mmap.mmap] = b""
+        try:
+            self._open()
+        except Exception:
+            self.close()
+            raise
+
+    # ------------------------------------------------------------------
+    # Parsing
+    def _open(self) -> None:
+        try:
+            fh = open(self.path, "rb")
+        except OSError as exc:
+            raise InvalidDumpError(f"{self.path}: {exc}") from exc
+        try:
+            size = os.fstat(fh.fileno()).st_size
+            if size < 64:
+                raise TruncatedDumpError(f"{self.path}: {size} bytes is too small for an ELF header")
+            self._data = mmap.mmap(fh.fileno(), 0, access=mmap.ACCESS_READ)
+        except Exception:
+            fh.close()
+            raise
+        self._fh = fh
+        try:
+            elf = parse_elf(self._data)
+        except ElfError as exc:
+            message = str(exc)
+            if "truncated" in message:
+                raise TruncatedDumpError(f"{self.path}: {message}") from exc
+            raise InvalidDumpError(f"{self.path}: {message}") from exc
+        if elf.e_type != ET_CORE:
+            raise InvalidDumpError(f"{self.path}: not a core file (e_type={elf.e_type})")
+        self.elf = elf
+        self.arch = elf.arch
+        self._machine = elf.machine
+
+        notes = [ph for ph in elf.program_headers if ph.p_type == PT_NOTE]
+        if not notes:
+            raise InvalidDumpError(f"{self.path}: core has no PT_NOTE segment")
+        for ph in elf.program_headers:
+            if ph.p_type == PT_LOAD:
+                self.segments.append(LoadSegment(ph.p_vaddr, ph.p_memsz, ph.p_offset, ph.p_filesz, ph.p_flags))
+        for ph in notes:
+            if ph.p_offset + ph.p_filesz > len(self._data):
+                raise TruncatedDumpError(f"{self.path}: PT_NOTE segment extends past end of file")
+            self._parse_notes(ph.p_offset, ph.p_filesz)
+        if not self.threads:
+            raise InvalidDumpError(f"{self.path}: no NT_PRSTATUS notes found")
+
+    def _parse_notes(self, offset: int, size: int) -> None:
+        data = self._data
+        pos = offset
+        end = offset + size
+        while pos + 12 <= end:
+            namesz, descsz, ntype = struct.unpack_from("<III", data, pos)
+            name_off = pos + 12
+            desc_off = name_off + _align4(namesz)
+            next_pos = desc_off + _align4(descsz)
+            if desc_off + descsz > end:
+                raise TruncatedDumpError(f"{self.path}: note at {pos:#x} is truncated")
+            desc = bytes(data[desc_off : desc_off + descsz])
+            if ntype == NT_PRSTATUS:
+                self.threads.append(self._parse_prstatus(desc))
+            elif ntype == NT_PRPSINFO and self.process is None:
+                self.process = _parse_prpsinfo(desc)
+            elif ntype == NT_SIGINFO and self.siginfo is None:
+                self.siginfo = _parse_siginfo(desc)
+            elif ntype == NT_FILE and not self.mappings:
+                self.mappings = _parse_nt_file(desc)
+            pos = next_pos
+
+    def _parse_prstatus(self, desc: bytes) -> CoreThread:
+        names = _GREGS.get(self._machine, ())
+        needed = _PRSTATUS_REG_OFFSET + 8 * len(names)
+        if len(desc) < needed:
+            raise TruncatedDumpError(f"{self.path}: NT_PRSTATUS is {len(desc)} bytes, expected {needed}")
+        cursig = struct.unpack_from("<h", desc, 12)[0]
+        tid = struct.unpack_from("<i", desc, _PRSTATUS_PID_OFFSET)[0]
+        values = struct.unpack_from(f"<{len(names)}Q", desc, _PRSTATUS_REG_OFFSET)
+        regs = [Register(name=n, value=v) for n, v in zip(names, values)]
+        return CoreThread(tid=tid, signal=cursig, registers=regs)
+
+    # ------------------------------------------------------------------
+    # Memory access
+    def read_memory(self, addr: int, size: int) -> Optional[bytes]:
+        """Read `size` bytes of target memory, or None when unavailable."""
+        for seg in self.segments:
+            if seg.vaddr <= addr and addr + size <= seg.vaddr + seg.filesz:
+                off = seg.offset + (addr - seg.vaddr)
+                return bytes(self._data[off : off + size])
+        mapping = self.mapping_for(addr)
+        if mapping is not None:
+            module = self._module(mapping.path)
+            if module is not None:
+                off = mapping.offset + (addr - mapping.start)
+                if off + size <= len(module.data):
+                    return bytes(module.data[off : off + size])
+        return None
+
+    def read_u64(self, addr: int) -> Optional[int]:
+        raw = self.read_memory(addr, 8)
+        return struct.unpack("<Q", raw)[0] if raw is not None and len(raw) == 8 else None
+
+    def mapping_for(self, addr: int) -> Optional[Mapping]:
+        for m in self.mappings:
+            if m.start <= addr < m.end:
+                return m
+        return None
+
+    @property
+    def modules(self) -> List[Module]:
+        """File-backed images from NT_FILE, one per path spanning all of its mappings."""
+        spans: Dict[str, List[int]] = {}
+        for m in self.mappings:
+            span = spans.setdefault(m.path, [m.start, m.end])
+            span[0], span[1] = min(span[0], m.start), max(span[1], m.end)
+        return [Module(name=Path(p).name, base=s, size=e - s, path=p) for p, (s, e) in spans.items()]
+
+    def memory_map(self) -> List[MapEntry]:
+        """The dumped ranges, named after the files NT_FILE maps there; see `core_memory_map`."""
+        segments = [(seg.vaddr, seg.memsz, _perms(seg.flags)) for seg in self.segments]
+        files = [(m.start, m.end, m.offset, m.path) for m in self.mappings]
+        return core_memory_map(segments, files, self._main_module_path())
+
+    def is_executable(self, addr: int) -> bool:
+        for seg in self.segments:
+            if seg.vaddr <= addr < seg.vaddr + seg.memsz:
+                return bool(seg.flags & PF_X)
+        return False
+
+    def _resolve_path(self, path: str) -> Optional[Path]:
+        if self.executable is not None:
+            # Only the main image: a library can share the (truncated) process name's prefix.
+            name = self.process.name if self.process is not None else ""
+            if path == self._main_module_path() or (name and Path(path).name == name):
+                return self.executable
+        candidates = []
+        if self.sysroot is not None:
+            candidates.append(self.sysroot / path.lstrip("/"))
+        candidates.append(Path(path))
+        for cand in candidates:
+            if cand.is_file():
+                return cand
+        return None
+
+    def _main_module_path(self) -> Optional[str]:
+        return self.mappings[0].path if self.mappings else None
+
+    def _module(self, path: str) -> Optional[ElfFile]:
+        if path in self._modules:
+            return self._modules[path]
+        resolved = self._resolve_path(path)
+        module: Optional[ElfFile] = None
+        if resolved is not None:
+            try:
+                module = open_elf(resolved)
+            except (OSError, ElfError):
+                module = None
+        self._modules[path] = module
+        return module
+
+    def _module_bias(self, path: str, module: ElfFile) -> int:
+        starts = [m.start for m in self.mappings if m.path == path and m.offset == 0]
+        base = min(starts) if starts else min(m.start for m in self.mappings if m.path == path)
+        return base - module.load_base()
+
+    # ------------------------------------------------------------------
+    # Analysis helpers
+    @property
+    def faulting_thread(self) -> CoreThread:
+        # The kernel writes the thread that took the signal first.
+        return self.threads[0]
+
+    def stop_info(self) -> StopInfo:
+        signo = self.siginfo.signo if self.siginfo else self.faulting_thread.signal
+        name = LINUX_SIGNALS.get(signo, f"SIG{signo}") if signo else None
+        addr = self.siginfo.addr if self.siginfo else None
+        return StopInfo(
+            signal=name,
+            description=f"terminated by {name}" if name else "",
+            fault_address=addr,
+            si_code=self.siginfo.code if self.siginfo else None,
+        )
+
+    @property
+    def registers_supported(self) -> bool:
+        """Whether this core's architecture has a known register layout (and so stacks)."""
+        return self._machine in _REG_ROLES
+
+    def thread_backtraces(self, max_frames: int = _DEFAULT_MAX_FRAMES) -> List[ThreadBacktrace]:
+        """Every thread's stack, at most ``max_frames`` deep (0 for no limit), marked truncated where it was cut.
+
+        The `faulting_thread` is marked faulting, unless no signal ended the process (a ``gcore`` snapshot).
+        """
+        signalled = self.stop_info().signal is not None
+        backtraces = []
+        for i, t in enumerate(self.threads):
+            frames, truncated = self._unwind(t, max_frames, symbolize=False)
+            faulting = signalled and i == 0
+            backtraces.append(
+                ThreadBacktrace(thread_id=i + 1, tid=t.tid, frames=frames, truncated=truncated, faulting=faulting)
+            )
+        # Every thread at once: an image is opened once however many stacks run through it.
+        self._symbolize_frames([f for b in backtraces for f in b.frames])
+        return backtraces
+
+    def crash_context(self, max_frames: int = _DEFAULT_MAX_FRAMES) -> CrashContext:
+        """Stop info, stack, and registers of the faulting thread, every thread's stack, and the fault's region."""
+        thread = self.faulting_thread
+        ctx = CrashContext(
+            stop=self.stop_info(),
+            frames=self.stack_frames(thread, max_frames=max_frames),
+            registers=list(thread.registers),
+            modules=self.modules,
+            # pr_psargs: the first 80 bytes of the command line, space-joined.
+            args=self.process.args.split() if self.process is not None else [],
+            threads=self.thread_backtraces(max_frames),
+        )
+        # Every thread's stack pointer marks its stack, which the segments do not name.
+        stack_pointers = [t.reg("rsp") if t.reg("rsp") is not None else t.reg("sp") for t in self.threads]
+        ctx.fault_region = fault_region(ctx, self.memory_map(), stack_pointers=stack_pointers)
+        return ctx
+
+    def _frame(self, index: int, pc: int) -> Frame:
+        mapping = self.mapping_for(pc)
+        return Frame(index=index, pc=pc, module=Path(mapping.path).name if mapping is not None else None)
+
+    def _symbolize_frames(self, frames: List[Frame]) -> None:
+        """Symbolize ``frames`` in place, one task per image (see `symbolize_modules`)."""
+        jobs: Dict[str, ModuleFrames] = {}
+        for frame in frames:
+            mapping = self.mapping_for(frame.pc) if frame.pc is not None else None
+            if mapping is None or mapping.path in self._timed_out:
+                continue
+            module = self._module(mapping.path)
+            if module is None:
+                continue
+            if mapping.path not in jobs:
+                bias = self._module_bias(mapping.path, module)
+                jobs[mapping.path] = ModuleFrames(
+                    Path(mapping.path).name,
+                    load=lambda path=mapping.path, module=module: self._symbolizer(path, module),
+                    bias=lambda _symbolizer, bias=bias: bias,
+                )
+            jobs[mapping.path].frames.append(frame)
+        for warning in symbolize_modules(list(jobs.values()), self.workers, self.module_timeout):
+            if warning not in self.warnings:
+                self.warnings.append(warning)
+        self._timed_out.update(path for path, job in jobs.items() if job.timed_out)
+
+    def symbolize(self, index: int, pc: int, *, is_return_address: bool) -> Frame:
+        frame = self._frame(index, pc)
+        mapping = self.mapping_for(pc)
+        if mapping is None:
+            return frame
+        module = self._module(mapping.path)
+        if module is None:
+            return frame
+        # Fills the function plus file/line/column when the module has DWARF line tables.
+        symbolizer = self._symbolizer(mapping.path, module)
+        return symbolizer.symbolize(frame, self._module_bias(mapping.path, module), is_return_address=is_return_address)
+
+    def _symbolizer(self, path: str, module: ElfFile) -> Symbolizer:
+        symbolizer = self._symbolizers.get(path)
+        if symbolizer is None:
+            resolved = self._resolve_path(path)
+            debug_file = self.debug_file if resolved is not None and resolved == self.executable else None
+            symbolizer = self._symbolizers[path] = Symbolizer.for_image(
+                module, str(resolved or path), debug_file, symbol_path=self.symbol_path
+            )
+        return symbolizer
+
+    def unwind_row(self, address: int) -> Optional[UnwindRow]:
+        """The call frame rules at ``address`` from its module's CFI, or None without any."""
+        mapping = self.mapping_for(address)
+        module = self._module(mapping.path) if mapping is not None else None
+        if mapping is None or module is None:
+            return None
+        symbolizer = self._symbolizer(mapping.path, module)
+        try:
+            return symbolizer.call_frames.row(address - self._module_bias(mapping.path, module))
+        except DwarfError:
+            # An FDE this decoder cannot run; the frame-pointer chain takes over.
+            return None
+
+    def stack_frames(self, thread: Optional[CoreThread] = None, max_frames: int = _DEFAULT_MAX_FRAMES) -> List[Frame]:
+        """Rebuild a thread's stack from the modules' CFI, then the frame-pointer chain.
+
+        Falls back to scanning the stack for plausible return addresses when
+        neither finds a caller (no unwind info, and no frame pointer).
+        ``max_frames`` 0 unwinds until the stack ends.
+        """
+        return self._unwind(thread or self.faulting_thread, max_frames)[0]
+
+    def _unwind(self, thread: CoreThread, max_frames: int, symbolize: bool = True) -> Tuple[List[Frame], bool]:
+        """`stack_frames`, and whether the walk stopped at ``max_frames`` with callers left.
+
+        ``symbolize=False`` leaves the frames with only their pc and module, for `_symbolize_frames` to fill.
+        """
+        if not self.registers_supported:
+            return [], False
+        pc_reg, sp_reg, fp_reg = _REG_ROLES[self._machine]
+        pc = thread.reg(pc_reg)
+        if pc is None:
+            return [], False
+        # The walkers look for one caller more than is kept, which tells a cut stack from one that ended.
+        max_frames = max_frames if max_frames > 0 else sys.maxsize
+        returns, state = self._walk_cfi(thread, pc, max_frames)
+        if returns and state is not None:
+            # The CFI ran out before the outermost frame: the frame-pointer chain goes on from there.
+            fp, sp = state.get(_DWARF_FP[self._machine]) or 0, state.get(_DWARF_SP[self._machine]) or 0
+            returns += self._walk_frame_pointers(fp, sp, max_frames - len(returns))
+        if not returns and state is not None:
+            returns = self._walk_frame_pointers(thread.reg(fp_reg) or 0, thread.reg(sp_reg) or 0, max_frames)
+            if self._machine == EM_AARCH64:
+                # A leaf function may not have stored the link register in a frame record yet.
+                lr = self._return_address(thread.reg("x30"))
+                if lr and self.is_executable(lr) and (not returns or returns[0] != lr):
+                    returns.insert(0, lr)
+        if not returns and state is not None:
+            returns = self._scan_stack(thread, max_frames)
+        frames = [self._frame(0, pc)]
+        for ret in returns[: max(0, max_frames - 1)]:
+            frames.append(self._frame(len(frames), ret))
+        if symbolize:
+            self._symbolize_frames(frames)
+        return frames, len(returns) >= max_frames
+
+    def _walk_cfi(self, thread: CoreThread, pc: int, max_frames: int) -> Tuple[List[int], Optional[Dict[int, int]]]:
+        """Return addresses found with the modules' CFI, and the registers (by DWARF number) where it stopped.
+
+        The registers are None when the CFI marked the outermost frame (its return
+        address is undefined), so there is nothing left to unwind.
+        """
+        known = Registers.from_list(thread.registers)
+        state = {n: v for n in range(_DWARF_REGISTERS[self._machine]) if (v := known.dwarf(n)) is not None}
+        sp_number = _DWARF_SP[self._machine]
+        returns: List[int] = []
+        # (return address, CFA) pairs already unwound: seeing one again means the walk is going in circles.
+        seen = set()
+        while len(returns) < max_frames:
+            # A return address can be just past the end of a function that ends in a call.
+            row = self.unwind_row(pc - 1 if returns else pc)
+            if row is None or row.cfa_register not in state:
+                return returns, state
+            cfa = state[row.cfa_register] + row.cfa_offset
+            caller = dict(state)
+            for number, rule in row.rules.items():
+                value = rule.recover(cfa, state, self.read_u64)
+                if value is None and rule.kind is not RuleKind.SAME_VALUE:
+                    caller.pop(number, None)
+                elif value is not None:
+                    caller[number] = value
+            caller[sp_number] = cfa
+            ra_rule = row.rules.get(row.return_address_register)
+            if ra_rule is not None and ra_rule.kind is RuleKind.UNDEFINED:
+                return returns, None
+            ret = self._return_address(caller.get(row.return_address_register))
+            # The stack grows down, so each caller's frame is above its callee's.
+            if not ret or not self.is_executable(ret) or cfa < state.get(sp_number, 0):
+                return returns, state
+            if (ret, cfa) in seen:
+                return returns, state
+            seen.add((ret, cfa))
+            returns.append(ret)
+            state, pc = caller, ret
+        return returns, state
+
+    def _walk_frame_pointers(self, fp: int, sp: int, max_frames: int) -> List[int]:
+        # Both ABIs link frame records the same way: [fp] = caller's fp, [fp + 8] = return address.
+        returns: List[int] = []
+        while fp and fp >= sp and len(returns) < max_frames:
+            ret = self._return_address(self.read_u64(fp + 8))
+            next_fp = self.read_u64(fp)
+            if ret is None or next_fp is None or not self.is_executable(ret):
+                break
+            returns.append(ret)
+            if next_fp <= fp:
+                break
+            fp = next_fp
+        return returns
+
+    def _return_address(self, value: Optional[int]) -> Optional[int]:
+        if value is not None and self._machine == EM_AARCH64:
+            return value & _AARCH64_VA_MASK
+        return value
+
+    def _scan_stack(self, thread: CoreThread, max_frames: int, max_words: int = 2048) -> List[int]:
+        sp = thread.reg(_REG_ROLES[self._machine][1]) or 0
+        returns: List[int] = []
+        for i in range(max_words):
+            word = self.read_u64(sp + 8 * i)
+            if word is None:
+                break
+            if self.is_executable(word) and self._follows_call(word):
+                returns.append(word)
+                if len(returns) >= max_frames:
+                    break
+        return returns
+
+    def _follows_call(self, addr: int) -> bool:
+        """Heuristic: does `addr` immediately follow a call instruction?"""
+        if self._machine == EM_AARCH64:
+            word = self.read_memory(addr - 4, 4)
+            if word is None or addr % 4:
+                return False
+            insn = struct.unpack("<I", word)[0]
+            # BL imm26 or BLR Xn.
+            return (insn & 0xFC000000) == 0x94000000 or (insn & 0xFFFFFC1F) == 0xD63F0000
+        window = self.read_memory(addr - 7, 7)
+        if window is None:
+            return False
+        if window[2] == 0xE8:  # call rel32
+            return True
+        # call r/m64 (FF /2) with 2-, 3-, 6- or 7-byte encodings
+        for length in (2, 3, 6, 7):
+            op = window[7 - length]
+            modrm = window[7 - length + 1] if length > 1 else 0
+            if op == 0xFF and (modrm >> 3) & 7 == 2:
+                return True
+        return False
+
+    def close(self) -> None:
+        for module in self._modules.values():
+            if module is not None and isinstance(module.data, mmap.mmap):
+                module.data.close()
+        self._modules.clear()
+        self._symbolizers.clear()
+        if isinstance(self._data, mmap.mmap):
+            self._data.close()
+        if self._fh is not None:
+            self._fh.close()
+            self._fh = None
+
+    def __enter__(self) -> "CoreReader":
+        return self
+
+    def __exit__(self, *_exc: object) -> None:
+        self.close()
+
+
+def _perms(flags: int) -> str:
+    """``p_flags`` spelled as in /proc/<pid>/maps; core mappings are private copies."""
+    return "".join(c if flags & bit else "-" for c, bit in (("r", PF_R), ("w", PF_W), ("x", PF_X))) + "p"
+
+
+def _align4(n: int) -> int:
+    return (n + 3) & ~3
+
+
+def _parse_prpsinfo(desc: bytes) -> CoreProcess:
+    if len(desc) < 136:
+        raise TruncatedDumpError(f"NT_PRPSINFO is {len(desc)} bytes, expected 136")
+    pid = struct.unpack_from("<i", desc, 24)[0]
+    name = desc[40:56].split(b"\x00", 1)[0].decode("utf-8", errors="replace")
+    args = desc[56:136].split(b"\x00", 1)[0].decode("utf-8", errors="replace").strip()
+    return CoreProcess(pid=pid, name=name, args=args)
+
+
+def _parse_siginfo(desc: bytes) -> SigInfo:
+    if len(desc) < 24:
+        raise TruncatedDumpError(f"NT_SIGINFO is {len(desc)} bytes, expected at least 24")
+    signo, _errno, code = struct.unpack_from("<iii", desc, 0)
+    addr: Optional[int] = None
+    # si_addr is only meaningful for fault signals (SIGILL, SIGFPE, SIGSEGV, SIGBUS).
+    if signo in (4, 7, 8, 11):
+        addr = struct.unpack_from("<Q", desc, 16)[0]
+    return SigInfo(signo=signo, code=code, addr=addr)
+
+
+def _parse_nt_file(desc: bytes) -> List[Mapping]:
+    if len(desc) < 16:
+        raise TruncatedDumpError("NT_FILE note is truncated")
+    count, page_size = struct.unpack_from("<QQ", desc, 0)
+    table_end = 16 + count * 24
+    if table_end > len(desc):
+        raise TruncatedDumpError("NT_FILE mapping table is truncated")
+    names = desc[table_end:].split(b"\x00")
+    mappings: List[Mapping] = []
+    for i in range(count):
+        start, end, pgoff = struct.unpack_from("<QQQ", desc, 16 + i * 24)
+        name = names[i].decode("utf-8", errors="replace") if i < len(names) else ""
+        mappings.append(Mapping(start=start, end=end, offset=pgoff * page_size, path=name))
+    return mappings
+
+
+__all__ = [
+    "AARCH64_GREGS",
+    "CoreProcess",
+    "CoreReader",
+    "CoreThread",
+    "LINUX_SIGNALS",
+    "Mapping",
+    "SigInfo",
+    "X86_64_GREGS",
+]
@@ -0,0 +1,315 @@
+"""Find the newest dump a program left behind, where the system put it.
+
+`dump_locations` lists the directories dumps of an executable go to, and how
+their files are named:
+
+- Windows: Windows Error Reporting's LocalDumps folder, ``DumpFolder`` under
+  ``HKLM\\SOFTWARE\\Microsoft\\Windows\\Windows Error Reporting\\LocalDumps``
+  (the program's own ``LocalDumps\\crash.exe`` key first), else
+  ``%LOCALAPPDATA%\\CrashDumps``; files are ``crash.exe.1234.dmp``.
+- Linux: wherever ``/proc/sys/kernel/core_pattern`` sends cores. A path
+  pattern (``/var/crash/core.%e.%p``) is turned into its directory and a
+  file-name regex, relative patterns (the default ``core``) resolved against
+  the current directory; ``core_uses_pid`` adds the ``.PID`` suffix. A pipe
+  to ``systemd-coredump`` means ``/var/lib/systemd/coredump``, whose cores are
+  compressed; one to apport means ``/var/lib/apport/coredump``.
+- Anywhere: extra directories (``--dir``, ``dumps.dirs``), searched first;
+  in a crashpad database its ``completed``, ``pending``, and ``reports``
+  directories are searched too.
+
+Where the file name records the program (``%e``, WER's naming) that is what
+matches. Where it does not (``core.1234``, crashpad's UUIDs), each candidate
+is opened and matched on the process name a core records, or the modules a
+minidump lists. `find_dumps` returns the matches newest first, by
+modification time; `latest_dump` the newest, and raises `NoDumpError` naming
+every place it looked when there is none, instead of falling back to an
+older or another program's dump.
+"""
+from __future__ import annotations
+
+import lzma
+import os
+import re
+import shutil
+import subprocess
+import sys
+from dataclasses import dataclass
+from typing import Iterable, List, Mapping, Optional, Pattern, Sequence
+
+from dbgcopilot.utils.config import user_cache_dir
+from dbgcopilot.utils.elf import ELF_MAGIC
+
+from .errors import DumpError
+from .minidump import MINIDUMP_SIGNATURE
+
+SYSTEMD_COREDUMP_DIR = "/var/lib/systemd/coredump"
+APPORT_COREDUMP_DIR = "/var/lib/apport/coredump"
+WER_KEY = r"SOFTWARE\Microsoft\Windows\Windows Error Reporting\LocalDumps"
+# Subdirectories of a crashpad database that hold minidumps.
+CRASHPAD_SUBDIRS = ("completed", "pending", "reports")
+# The kernel truncates a process name (%e, NT_PRPSINFO) to 15 bytes.
+COMM_LENGTH = 15
+# Suffixes of compressed cores, and the tool that expands each when Python cannot.
+_COMPRESSED = {".xz": None, ".zst": "zstd", ".lz4": "lz4"}
+# What a core_pattern specifier expands to, as a regex; %e and %f are the program's name.
+_SPECIFIERS = {**{spec: r"\d+" for spec in "pPiIugdstc"}, "h": r"[^/]+", "E": r"[^/]*"}
+_UNNAMED_DUMP = re.compile(r"^(core(\.\d+)?|.*\.(core|dmp|mdmp))$", re.IGNORECASE)
+
+
+class NoDumpError(DumpError):
+    """No dump of the program was found; the message names the places searched."""
+
+
+@dataclass(frozen=True)
+class DumpLocation:
+    directory: str
+    # A file name there that is one of the program's dumps.
+    pattern: Pattern[str]
+    # Where the location comes from, for messages: "WER LocalDumps", "core_pattern /var/crash/core.%e".
+    source: str
+    # The file name does not say which program dumped; the dump is read to tell.
+    verify: bool = False
+
+    def describe(self) -> str:
+        return f"{self.directory} ({self.source})"
+
+
+@dataclass(frozen=True)
+class FoundDump:
+    path: str
+    # Seconds since the epoch.
+    mtime: float
+    location: DumpLocation
+
+    @property
+    def compressed(self) -> bool:
+        return os.path.splitext(self.path)[1] in _COMPRESSED
+
+
+def _program_names(executable: str) -> List[str]:
+    """The names a dump may record for ``executable``: its base name, and the 15-byte one the kernel keeps."""
+    name = os.path.basename(executable)
+    return list(dict.fromkeys([name, name[:COMM_LENGTH]]))
+
+
+def _name_regex(executable: str, windows: bool) -> str:
+    names = [re.escape(n) for n in _program_names(executable)]
+    if windows and not os.path.splitext(executable)[1]:
+        names = [n + r"\.exe" for n in names]
+    return "(?:" + "|".join(names) + ")"
+
+
+def wer_dump_folders(executable: str, environ: Optional[Mapping[str, str]] = None) -> List[str]:
+    """The LocalDumps folders WER writes ``executable``'s dumps to; see the module docstring."""
+    environ = os.environ if environ is None else environ
+    folders: List[str] = []
+    try:
+        import winreg  # type: ignore
+    except ImportError:
+        winreg = None  # type: ignore
+    if winreg is not None:  # pragma: no cover - Windows only
+        name = os.path.basename(executable)
+        for key in (f"{WER_KEY}\\{name}", WER_KEY):
+            try:
+                with winreg.OpenKey(winreg.HKEY_LOCAL_MACHINE, key) as handle:
+                    folders.append(os.path.expandvars(winreg.QueryValueEx(handle, "DumpFolder")[0]))
+            except OSError:
+                continue
+    local = environ.get("LOCALAPPDATA") or os.path.join(os.path.expanduser("~"), "AppData", "Local")
+    folders.append(os.path.join(local, "CrashDumps"))
+    return list(dict.fromkeys(folders))
+
+
+def core_pattern_location(
+    executable: str, core_pattern: str, uses_pid: bool = False, cwd: Optional[str] = None
+) -> Optional[DumpLocation]:
+    """Where ``core_pattern`` puts ``executable``'s cores, or None for a pipe to a handler this does not know."""
+    pattern = core_pattern.strip()
+    name = _name_regex(executable, windows=False)
+    source = f"core_pattern {pattern}"
+    if pattern.startswith("|"):
+        handler = os.path.basename(pattern[1:].split()[0]) if pattern[1:].split() else ""
+        if handler == "systemd-coredump":
+            # core.COMM.UID.BOOT_ID.PID.USEC, compressed by default.
+            regex = rf"^core\.{name}\.\d+\.[0-9a-f]+\.\d+\.\d+(\.(xz|zst|lz4))?$"
+            return DumpLocation(SYSTEMD_COREDUMP_DIR, re.compile(regex), "systemd-coredump")
+        if handler.startswith("apport"):
+            # core._PATH_TO_EXE.UID.BOOT_ID.PID.TIME
+            regex = rf"^core\.(?:\S*_)?{name}\.\d+\.[0-9a-f-]+\.\d+\.\d+$"
+            return DumpLocation(APPORT_COREDUMP_DIR, re.compile(regex), "apport")
+        return None
+    directory, template = os.path.split(pattern)
+    if not os.path.isabs(directory):
+        # Relative to the crashing process's directory, which only the current one can stand in for.
+        directory = os.path.join(cwd or os.getcwd(), directory)
+    parts, named, has_pid = [], False, False
+    i = 0
+    while i < len(template):
+        char = template[i]
+        if char == "%" and i + 1 < len(template):
+            spec = template[i + 1]
+            i += 2
+            if spec in "ef":
+                parts.append(name)
+                named = True
+            elif spec == "%":
+                parts.append("%")
+            else:
+                has_pid = has_pid or spec == "p"
+                parts.append(_SPECIFIERS.get(spec, r".*?"))
+            continue
+        parts.append(re.escape(char))
+        i += 1
+    if uses_pid and not has_pid:
+        parts.append(r"\.\d+")
+    return DumpLocation(os.path.normpath(directory), re.compile("^" + "".join(parts) + "$"), source, verify=not named)
+
+
+def _read_proc(name: str) -> Optional[str]:
+    try:
+        with open(f"/proc/sys/kernel/{name}") as fh:
+            return fh.read().strip()
+    except OSError:
+        return None
+
+
+def dump_locations(
+    executable: str,
+    extra_dirs: Sequence[str] = (),
+    platform: Optional[str] = None,
+    environ: Optional[Mapping[str, str]] = None,
+    core_pattern: Optional[str] = None,
+    uses_pid: Optional[bool] = None,
+    cwd: Optional[str] = None,
+) -> List[DumpLocation]:
+    """Where to look for ``executable``'s dumps on this system (or ``platform``), ``extra_dirs`` first."""
+    platform = platform or sys.platform
+    locations: List[DumpLocation] = []
+    for directory in extra_dirs:
+        directory = os.path.expanduser(directory)
+        locations.append(DumpLocation(os.path.normpath(directory), _UNNAMED_DUMP, "dump directory", verify=True))
+        for sub in CRASHPAD_SUBDIRS:
+            path = os.path.join(directory, sub)
+            if os.path.isdir(path):
+                locations.append(DumpLocation(os.path.normpath(path), _UNNAMED_DUMP, "crashpad database", verify=True))
+    if platform == "win32":
+        regex = re.compile(rf"^{_name_regex(executable, windows=True)}\.\d+\.dmp$", re.IGNORECASE)
+        locations += [DumpLocation(d, regex, "WER LocalDumps") for d in wer_dump_folders(executable, environ)]
+    elif platform.startswith("linux"):
+        pattern = core_pattern if core_pattern is not None else _read_proc("core_pattern") or "core"
+        if uses_pid is None:
+            uses_pid = _read_proc("core_uses_pid") == "1"
+        location = core_pattern_location(executable, pattern, uses_pid, cwd)
+        if location is not None:
+            locations.append(location)
+    return locations
+
+
+def dump_program_names(path: str) -> List[str]:
+    """The names ``path`` records for the program that dumped: a core's process name, a minidump's modules."""
+    from .coredump import CoreReader
+    from .minidump import MinidumpReader
+
+    try:
+        with open(path, "rb") as fh:
+            magic = fh.read(4)
+        if magic == ELF_MAGIC:
+            with CoreReader(path) as core:
+                return [core.process.name] if core.process is not None else []
+        if magic == MINIDUMP_SIGNATURE:
+            with MinidumpReader(path) as dump:
+                return [re.split(r"[\\/]", m.path or m.name)[-1] for m in dump.modules]
+    except (OSError, DumpError):
+        pass
+    return []
+
+
+def _matches(executable: str, recorded: Iterable[str]) -> bool:
+    wanted = {n.lower() for n in _program_names(executable)}
+    wanted |= {n + ".exe" for n in wanted if "." not in n}
+    return any(r.lower() in wanted for r in recorded)
+
+
+def find_dumps(executable: str, locations: Optional[Sequence[DumpLocation]] = None) -> List[FoundDump]:
+    """``executable``'s dumps in ``locations`` (default: `dump_locations`), newest first."""
+    found: List[FoundDump] = []
+    seen = set()
+    for location in dump_locations(executable) if locations is None else locations:
+        try:
+            entries = list(os.scandir(location.directory))
+        except OSError:
+            continue
+        for entry in entries:
+            if entry.path in seen or not location.pattern.match(entry.name):
+                continue
+            try:
+                if not entry.is_file():
+                    continue
+                mtime = entry.stat().st_mtime
+            except OSError:
+                continue
+            if location.verify and not _matches(executable, dump_program_names(entry.path)):
+                continue
+            seen.add(entry.path)
+            found.append(FoundDump(entry.path, mtime, location))
+    found.sort(key=lambda d: d.mtime, reverse=True)
+    return found
+
+
+def latest_dump(executable: str, locations: Optional[Sequence[DumpLocation]] = None) -> FoundDump:
+    """The newest of `find_dumps`; raises `NoDumpError` when there is none."""
+    locations = dump_locations(executable) if locations is None else locations
+    found = find_dumps(executable, locations)
+    if not found:
+        searched = "; ".join(location.describe() for location in locations) or "nowhere this platform keeps dumps"
+        raise NoDumpError(f"no dump of {os.path.basename(executable)} found in {searched}")
+    return found[0]
+
+
+def decompressed(dump: FoundDump, directory: Optional[str] = None) -> str:
+    """A readable copy of a compressed core (systemd-coredump's), kept in ``directory``; the path itself otherwise."""
+    stem, suffix = os.path.splitext(dump.path)
+    if suffix not in _COMPRESSED:
+        return dump.path
+    directory = directory or os.path.join(user_cache_dir(), "dumps")
+    target = os.path.join(directory, os.path.basename(stem))
+    if os.path.isfile(target) and os.path.getmtime(target) >= dump.mtime:
+        return target
+    os.makedirs(directory, exist_ok=True)
+    tool = _COMPRESSED[suffix]
+    try:
+        if tool is None:
+            with lzma.open(dump.path) as src, open(target, "wb") as dst:
+                shutil.copyfileobj(src, dst)
+        else:
+            if shutil.which(tool) is None:
+                raise DumpError(
+                    f"{dump.path} is compressed with {tool}; install {tool}, or write it out with "
+                    "`coredumpctl dump -o core`"
+                )
+            with open(target, "wb") as dst:
+                subprocess.run([tool, "-dc", dump.path], stdout=dst, check=True)
+    except (OSError, lzma.LZMAError, subprocess.CalledProcessError) as exc:
+        if os.path.exists(target):
+            os.unlink(target)
+        raise DumpError(f"cannot decompress {dump.path}: {exc}") from exc
+    return target
+
+
+__all__ = [
+    "APPORT_COREDUMP_DIR",
+    "COMM_LENGTH",
+    "CRASHPAD_SUBDIRS",
+    "DumpLocation",
+    "FoundDump",
+    "NoDumpError",
+    "SYSTEMD_COREDUMP_DIR",
+    "WER_KEY",
+    "core_pattern_location",
+    "decompressed",
+    "dump_locations",
+    "dump_program_names",
+    "find_dumps",
+    "latest_dump",
+    "wer_dump_folders",
+]
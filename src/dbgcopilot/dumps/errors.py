@@ -0,0 +1,14 @@
+"""Typed errors raised while reading dump files."""
+from __future__ import annotations
+
+
+class DumpError(Exception):
+    """Base class for dump parsing failures."""
+
+
+class TruncatedDumpError(DumpError):
+    """The dump ends before a structure it declares."""
+
+
+class InvalidDumpError(DumpError):
+    """The file is not a dump this reader understands, or required data is missing."""
@@ -0,0 +1,466 @@
+"""Windows minidump (.dmp) reader.
+
+`MinidumpReader` reads the MINIDUMP_HEADER and stream directory, then pulls the
+exception record, thread list (with CONTEXT registers), module list, and
+memory ranges into the same normalized types used for Linux cores. Windows
+exception codes are mapped onto POSIX-style signal names so `classify_fault`
+treats both platforms alike.
+"""
+from __future__ import annotations
+
+import mmap
+import os
+import struct
+import sys
+from dataclasses import dataclass, field
+from pathlib import Path, PureWindowsPath
+from typing import Dict, List, Optional, Tuple, Union
+
+from dbgcopilot.analysis.model import AccessKind, CrashContext, Frame, Module, Register, StopInfo, ThreadBacktrace
+from dbgcopilot.analysis.threads import DEFAULT_MAX_FRAMES_PER_THREAD
+
+from .errors import InvalidDumpError, TruncatedDumpError
+
+MINIDUMP_SIGNATURE = b"MDMP"
+
+THREAD_LIST_STREAM = 3
+MODULE_LIST_STREAM = 4
+MEMORY_LIST_STREAM = 5
+EXCEPTION_STREAM = 6
+SYSTEM_INFO_STREAM = 7
+MEMORY64_LIST_STREAM = 9
+
+PROCESSOR_ARCHITECTURE_INTEL = 0
+PROCESSOR_ARCHITECTURE_AMD64 = 9
+PROCESSOR_ARCHITECTURE_ARM64 = 12
+
+# NTSTATUS -> (symbolic name, closest POSIX signal)
+WINDOWS_EXCEPTIONS: Dict[int, Tuple[str, str]] = {
+    0xC0000005: ("STATUS_ACCESS_VIOLATION", "SIGSEGV"),
+    0xC0000006: ("STATUS_IN_PAGE_ERROR", "SIGBUS"),
+    0x80000002: ("STATUS_DATATYPE_MISALIGNMENT", "SIGBUS"),
+    0x80000003: ("STATUS_BREAKPOINT", "SIGTRAP"),
+    0x80000004: ("STATUS_SINGLE_STEP", "SIGTRAP"),
+    0xC000001D: ("STATUS_ILLEGAL_INSTRUCTION", "SIGILL"),
+    0xC0000096: ("STATUS_PRIVILEGED_INSTRUCTION", "SIGILL"),
+    0xC0000094: ("STATUS_INTEGER_DIVIDE_BY_ZERO", "SIGFPE"),
+    0xC0000095: ("STATUS_INTEGER_OVERFLOW", "SIGFPE"),
+    0xC000008E: ("STATUS_FLOAT_DIVIDE_BY_ZERO", "SIGFPE"),
+    0xC00000FD: ("STATUS_STACK_OVERFLOW", "SIGSEGV"),
+    0xC0000374: ("STATUS_HEAP_CORRUPTION", "SIGABRT"),
+    0xC0000409: ("STATUS_STACK_BUFFER_OVERRUN", "SIGABRT"),  # also raised by __fastfail / Rust aborts
+    0x40000015: ("STATUS_FATAL_APP_EXIT", "SIGABRT"),  # abort()
+    0xE06D7363: ("CXX_EXCEPTION", "SIGABRT"),  # uncaught C++ throw
+}
+
+# EXCEPTION_ACCESS_VIOLATION and EXCEPTION_IN_PAGE_ERROR: parameter 0 is the access, parameter 1 the address.
+_ACCESS_VIOLATION_CODES = (0xC0000005, 0xC0000006)
+_ACCESS_KINDS = {0: AccessKind.READ, 1: AccessKind.WRITE, 8: AccessKind.EXECUTE}
+
+
+def decode_access_violation(code: int, parameters: List[int]) -> Tuple[AccessKind, Optional[int]]:
+    """The access kind and target address of an access violation's exception parameters.
+
+    ``(AccessKind.UNKNOWN, None)`` for other exceptions, or when the record
+    carries fewer than two parameters.
+    """
+    if code not in _ACCESS_VIOLATION_CODES or len(parameters) < 2:
+        return AccessKind.UNKNOWN, None
+    return _ACCESS_KINDS.get(parameters[0], AccessKind.UNKNOWN), parameters[1]
+
+
+# VS_FIXEDFILEINFO.dwSignature; the module's version resource is absent without it.
+_FIXED_FILE_INFO_SIGNATURE = 0xFEEF04BD
+
+
+def _file_version(signature: int, _struct_version: int, ms: int, ls: int) -> Optional[str]:
+    """``major.minor.build.revision`` from VS_FIXEDFILEINFO's dwFileVersionMS/LS, or None if it has none."""
+    if signature != _FIXED_FILE_INFO_SIGNATURE or not (ms or ls):
+        return None
+    return f"{ms >> 16}.{ms & 0xFFFF}.{ls >> 16}.{ls & 0xFFFF}"
+
+
+# Byte offsets inside CONTEXT (winnt.h) for the registers we report.
+_AMD64_GPRS = (
+    ("rax", 120), ("rcx", 128), ("rdx", 136), ("rbx", 144), ("rsp", 152), ("rbp", 160),
+    ("rsi", 168), ("rdi", 176), ("r8", 184), ("r9", 192), ("r10", 200), ("r11", 208),
+    ("r12", 216), ("r13", 224), ("r14", 232), ("r15", 240), ("rip", 248),
+)
+_AMD64_EFLAGS_OFFSET = 68
+_ARM64_CONTEXT_SIZE = 912
+_ARM64_X_OFFSET = 8
+_ARM64_SP_OFFSET = 256
+_ARM64_PC_OFFSET = 264
+
+_DEFAULT_MAX_FRAMES = DEFAULT_MAX_FRAMES_PER_THREAD
+
+
+@dataclass
+class MinidumpException:
+    thread_id: int
+    code: int
+    flags: int
+    address: int
+    parameters: List[int] = field(default_factory=list)
+
+    @property
+    def name(self) -> str:
+        known = WINDOWS_EXCEPTIONS.get(self.code)
+        return known[0] if known else f"0x{self.code:08X}"
+
+
+@dataclass
+class MinidumpThread:
+    thread_id: int
+    stack_start: int
+    stack_size: int
+    stack_rva: int
+    registers: List[Register] = field(default_factory=list)
+
+    def reg(self, name: str) -> Optional[int]:
+        for r in self.registers:
+            if r.name == name:
+                return r.value
+        return None
+
+
+class MinidumpReader:
+    """Read-only view of a Windows minidump file."""
+
+    def __init__(self, path: Union[str, Path]) -> None:
+        self.path = Path(path)
+        self.arch: Optional[str] = None
+        self.streams: Dict[int, Tuple[int, int]] = {}
+        self.exception: Optional[MinidumpException] = None
+        self.threads: List[MinidumpThread] = []
+        self.modules: List[Module] = []
+        # (start address, size, file offset)
+        self.memory: List[Tuple[int, int, int]] = []
+        self._fh = None
+        self._data: Union[bytes, mmap.mmap] = b""
+        try:
+            self._open()
+        except Exception:
+            self.close()
+            raise
+
+    # ------------------------------------------------------------------
+    # Parsing
+    def _open(self) -> None:
+        try:
+            fh = open(self.path, "rb")
+        except OSError as exc:
+            raise InvalidDumpError(f"{self.path}: {exc}") from exc
+        self._fh = fh
+        size = os.fstat(fh.fileno()).st_size
+        if size < 32:
+            raise TruncatedDumpError(f"{self.path}: {size} bytes is too small for a MINIDUMP_HEADER")
+        self._data = mmap.mmap(fh.fileno(), 0, access=mmap.ACCESS_READ)
+
+        if bytes(self._data[:4]) != MINIDUMP_SIGNATURE:
+            raise InvalidDumpError(f"{self.path}: missing MDMP signature")
+        _sig, _version, count, dir_rva = struct.unpack_from("<4sIII", self._data, 0)
+        self._require(dir_rva, count * 12, "stream directory")
+        for i in range(count):
+            stype, dsize, rva = struct.unpack_from("<III", self._data, dir_rva + i * 12)
+            # Later duplicates are ignored; the first stream of a type wins.
+            self.streams.setdefault(stype, (rva, dsize))
+
+        self._parse_system_info()
+        self._parse_memory()
+        self._parse_modules()
+        self._parse_threads()
+        self._parse_exception()
+
+    def _require(self, offset: int, size: int, what: str) -> None:
+        if offset + size > len(self._data):
+            raise TruncatedDumpError(f"{self.path}: {what} at {offset:#x} extends past end of file")
+
+    def _stream(self, stype: int, min_size: int = 0) -> Optional[Tuple[int, int]]:
+        loc = self.streams.get(stype)
+        if loc is None:
+            return None
+        rva, size = loc
+        self._require(rva, max(size, min_size), f"stream {stype}")
+        return loc
+
+    def _parse_system_info(self) -> None:
+        loc = self._stream(SYSTEM_INFO_STREAM, 2)
+        if loc is None:
+            return
+        arch = struct.unpack_from("<H", self._data, loc[0])[0]
+        self.arch = {
+            PROCESSOR_ARCHITECTURE_AMD64: "x86_64",
+            PROCESSOR_ARCHITECTURE_ARM64: "aarch64",
+            PROCESSOR_ARCHITECTURE_INTEL: "x86",
+        }.get(arch, f"arch-{arch}")
+
+    def _parse_memory(self) -> None:
+        loc = self._stream(MEMORY64_LIST_STREAM, 16)
+        if loc is not None:
+            count, base_rva = struct.unpack_from("<QQ", self._data, loc[0])
+            self._require(loc[0] + 16, count * 16, "memory64 list")
+            offset = base_rva
+            for i in range(count):
+                start, size = struct.unpack_from("<QQ", self._data, loc[0] + 16 + i * 16)
+                self.memory.append((start, size, offset))
+                offset += size
+        loc = self._stream(MEMORY_LIST_STREAM, 4)
+        if loc is not None:
+            count = struct.unpack_from("<I", self._data, loc[0])[0]
+            self._require(loc[0] + 4, count * 16, "memory list")
+            for i in range(count):
+                start, size, rva = struct.unpack_from("<QII", self._data, loc[0] + 4 + i * 16)
+                self.memory.append((start, size, rva))
+
+    def _parse_modules(self) -> None:
+        loc = self._stream(MODULE_LIST_STREAM, 4)
+        if loc is None:
+            return
+        count = struct.unpack_from("<I", self._data, loc[0])[0]
+        self._require(loc[0] + 4, count * 108, "module list")
+        for i in range(count):
+            off = loc[0] + 4 + i * 108
+            base, size, _checksum, stamp, name_rva = struct.unpack_from("<QIIII", self._data, off)
+            path = self._read_string(name_rva)
+            name = PureWindowsPath(path).name if path else f"module@{base:#x}"
+            self.modules.append(
+                Module(
+                    name=name,
+                    base=base,
+                    size=size,
+                    path=path or None,
+                    version=_file_version(*struct.unpack_from("<IIII", self._data, off + 24)),
+                    timestamp=stamp or None,
+                )
+            )
+        self.modules.sort(key=lambda m: m.base)
+
+    def _parse_threads(self) -> None:
+        loc = self._stream(THREAD_LIST_STREAM, 4)
+        if loc is None:
+            return
+        count = struct.unpack_from("<I", self._data, loc[0])[0]
+        self._require(loc[0] + 4, count * 48, "thread list")
+        for i in range(count):
+            off = loc[0] + 4 + i * 48
+            tid = struct.unpack_from("<I", self._data, off)[0]
+            stack_start, stack_size, stack_rva, ctx_size, ctx_rva = struct.unpack_from("<QIIII", self._data, off + 24)
+            self.threads.append(
+                MinidumpThread(
+                    thread_id=tid,
+                    stack_start=stack_start,
+                    stack_size=stack_size,
+                    stack_rva=stack_rva,
+                    registers=self._parse_context(ctx_rva, ctx_size),
+                )
+            )
+
+    def _parse_exception(self) -> None:
+        loc = self._stream(EXCEPTION_STREAM, 168)
+        if loc is None:
+            return
+        off = loc[0]
+        tid = struct.unpack_from("<I", self._data, off)[0]
+        code, flags, _record, address, nparams = struct.unpack_from("<IIQQI", self._data, off + 8)
+        nparams = min(nparams, 15)
+        params = list(struct.unpack_from(f"<{nparams}Q", self._data, off + 40)) if nparams else []
+        self.exception = MinidumpException(thread_id=tid, code=code, flags=flags, address=address, parameters=params)
+        ctx_size, ctx_rva = struct.unpack_from("<II", self._data, off + 160)
+        # The exception's own context is the faulting state; prefer it over the
+        # thread-list copy, which may show the dump writer's frames instead.
+        regs = self._parse_context(ctx_rva, ctx_size)
+        thread = self.thread(tid)
+        if regs and thread is not None:
+            thread.registers = regs
+
+    def _parse_context(self, rva: int, size: int) -> List[Register]:
+        if not rva or not size or rva + size > len(self._data):
+            return []
+        data = self._data
+        if self.arch == "aarch64" or (self.arch is None and size == _ARM64_CONTEXT_SIZE):
+            if size < _ARM64_PC_OFFSET + 8:
+                return []
+            regs = [Register(f"x{i}", struct.unpack_from("<Q", data, rva + _ARM64_X_OFFSET + i * 8)[0]) for i in range(31)]
+            regs.append(Register("sp", struct.unpack_from("<Q", data, rva + _ARM64_SP_OFFSET)[0]))
+            regs.append(Register("pc", struct.unpack_from("<Q", data, rva + _ARM64_PC_OFFSET)[0]))
+            return regs
+        if self.arch not in (None, "x86_64"):
+            # 32-bit x86 and other layouts are not decoded; the thread is still listed.
+            return []
+        if size < _AMD64_GPRS[-1][1] + 8:
+            return []
+        regs = [Register(name, struct.unpack_from("<Q", data, rva + off)[0]) for name, off in _AMD64_GPRS]
+        regs.append(Register("eflags", struct.unpack_from("<I", data, rva + _AMD64_EFLAGS_OFFSET)[0]))
+        return regs
+
+    def _read_string(self, rva: int) -> str:
+        if not rva or rva + 4 > len(self._data):
+            return ""
+        length = struct.unpack_from("<I", self._data, rva)[0]
+        if rva + 4 + length > len(self._data):
+            return ""
+        return bytes(self._data[rva + 4 : rva + 4 + length]).decode("utf-16-le", errors="replace")
+
+    # ------------------------------------------------------------------
+    # Accessors
+    def thread(self, thread_id: int) -> Optional[MinidumpThread]:
+        for t in self.threads:
+            if t.thread_id == thread_id:
+                return t
+        return None
+
+    @property
+    def faulting_thread(self) -> Optional[MinidumpThread]:
+        if self.exception is not None:
+            found = self.thread(self.exception.thread_id)
+            if found is not None:
+                return found
+        return self.threads[0] if self.threads else None
+
+    def read_memory(self, addr: int, size: int) -> Optional[bytes]:
+        for start, length, offset in self.memory:
+            if start <= addr and addr + size <= start + length:
+                off = offset + (addr - start)
+                if off + size <= len(self._data):
+                    return bytes(self._data[off : off + size])
+        for t in self.threads:
+            if t.stack_start <= addr and addr + size <= t.stack_start + t.stack_size:
+                off = t.stack_rva + (addr - t.stack_start)
+                if off + size <= len(self._data):
+                    return bytes(self._data[off : off + size])
+        return None
+
+    def read_u64(self, addr: int) -> Optional[int]:
+        raw = self.read_memory(addr, 8)
+        return struct.unpack("<Q", raw)[0] if raw is not None and len(raw) == 8 else None
+
+    def module_for(self, addr: int) -> Optional[Module]:
+        for m in self.modules:
+            if m.contains(addr):
+                return m
+        return None
+
+    def stop_info(self) -> StopInfo:
+        exc = self.exception
+        if exc is None:
+            return StopInfo(description="no exception record in dump")
+        # Other error codes get a placeholder SIGSEGV so the stop is a crash; `classify_fault` goes by the code.
+        name, signal = WINDOWS_EXCEPTIONS.get(exc.code, (exc.name, "SIGSEGV" if exc.code & 0xC0000000 == 0xC0000000 else None))
+        fault_address: Optional[int] = exc.address
+        description = name
+        access, target = decode_access_violation(exc.code, exc.parameters)
+        if target is not None:
+            fault_address = target
+            verb = access.value if access is not AccessKind.UNKNOWN else "access"
+            description = f"{name}: {verb} of 0x{fault_address:x}"
+        return StopInfo(
+            signal=signal, description=description, fault_address=fault_address, exception_code=exc.code, access=access
+        )
+
+    def _frame(self, index: int, pc: int) -> Frame:
+        module = self.module_for(pc)
+        return Frame(index=index, pc=pc, module=module.name if module else None)
+
+    def stack_frames(self, thread: Optional[MinidumpThread] = None, max_frames: int = _DEFAULT_MAX_FRAMES) -> List[Frame]:
+        """Best-effort stack for a thread.
+
+        ARM64 Windows code always keeps a frame-pointer chain, so it is followed.
+        On x64 the unwind data lives in the modules (not in the dump), so the
+        thread's stack is scanned for values that point into a loaded module.
+        ``max_frames`` 0 keeps every frame found.
+        """
+        thread = thread or self.faulting_thread
+        return self._unwind(thread, max_frames)[0] if thread is not None else []
+
+    def _unwind(self, thread: MinidumpThread, max_frames: int) -> Tuple[List[Frame], bool]:
+        """`stack_frames`, and whether the walk stopped at ``max_frames`` with callers left."""
+        pc = thread.reg("rip") if thread.reg("rip") is not None else thread.reg("pc")
+        if pc is None:
+            return [], False
+        # As in `CoreReader`, one caller more than is kept is looked for.
+        max_frames = max_frames if max_frames > 0 else sys.maxsize
+        if thread is self.faulting_thread and self.exception is not None and self.exception.address:
+            pc = self.exception.address
+        frames = [self._frame(0, pc)]
+        if thread.reg("x29") is not None:
+            returns = self._walk_frame_pointers(thread.reg("x29") or 0, max_frames)
+            lr = thread.reg("x30")
+            if lr and self.module_for(lr) and (not returns or returns[0] != lr):
+                returns.insert(0, lr)
+        else:
+            returns = self._scan_stack(thread.reg("rsp") or 0, thread, max_frames)
+        for ret in returns[: max(0, max_frames - 1)]:
+            frames.append(self._frame(len(frames), ret))
+        return frames, len(returns) >= max_frames
+
+    def _walk_frame_pointers(self, fp: int, max_frames: int) -> List[int]:
+        returns: List[int] = []
+        while fp and len(returns) < max_frames:
+            next_fp = self.read_u64(fp)
+            ret = self.read_u64(fp + 8)
+            if ret is None or next_fp is None or self.module_for(ret) is None:
+                break
+            returns.append(ret)
+            if next_fp <= fp:
+                break
+            fp = next_fp
+        return returns
+
+    def _scan_stack(self, sp: int, thread: MinidumpThread, max_frames: int) -> List[int]:
+        returns: List[int] = []
+        end = thread.stack_start + thread.stack_size
+        addr = max(sp, thread.stack_start)
+        while addr + 8 <= end and len(returns) < max_frames:
+            word = self.read_u64(addr)
+            if word is None:
+                break
+            if self.module_for(word) is not None:
+                returns.append(word)
+            addr += 8
+        return returns
+
+    def thread_backtraces(self, max_frames: int = _DEFAULT_MAX_FRAMES) -> List[ThreadBacktrace]:
+        # A dump without an exception (a hang snapshot) has no faulting thread to mark.
+        faulting = self.thread(self.exception.thread_id) if self.exception is not None else None
+        backtraces = []
+        for i, t in enumerate(self.threads):
+            frames, truncated = self._unwind(t, max_frames)
+            backtraces.append(
+                ThreadBacktrace(
+                    thread_id=i + 1, tid=t.thread_id, frames=frames, truncated=truncated, faulting=t is faulting
+                )
+            )
+        return backtraces
+
+    def crash_context(self, max_frames: int = _DEFAULT_MAX_FRAMES) -> CrashContext:
+        thread = self.faulting_thread
+        return CrashContext(
+            stop=self.stop_info(),
+            frames=self.stack_frames(thread, max_frames) if thread else [],
+            registers=list(thread.registers) if thread else [],
+            modules=list(self.modules),
+            threads=self.thread_backtraces(max_frames),
+        )
+
+    def close(self) -> None:
+        if isinstance(self._data, mmap.mmap):
+            self._data.close()
+        if self._fh is not None:
+            self._fh.close()
+            self._fh = None
+
+    def __enter__(self) -> "MinidumpReader":
+        return self
+
+    def __exit__(self, *_exc: object) -> None:
+        self.close()
+
+
+__all__ = [
+    "MinidumpException",
+    "MinidumpReader",
+    "MinidumpThread",
+    "WINDOWS_EXCEPTIONS",
+    "decode_access_violation",
+]
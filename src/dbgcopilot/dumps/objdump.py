@@ -0,0 +1,57 @@
+"""Disassembly for dump sessions: the bytes at the pc, decoded by ``objdump``.
+
+The dump readers decode no instructions themselves. A core keeps the text of
+its mapped images (or the reader finds it in the image files), so
+`disassemble` writes the bytes around the pc to a scratch file and runs GNU
+``objdump -D -b binary`` over them at their real addresses. Decoding runs
+linearly from the first byte, so the bytes must start on an instruction:
+callers start at the function's symbol, or at the pc when there is none.
+
+Without ``objdump`` on ``PATH``, or for an architecture it is not told
+about, there are no instructions, and the fault context keeps the raw bytes.
+"""
+from __future__ import annotations
+
+import logging
+import shutil
+import subprocess
+import tempfile
+from typing import List, Optional
+
+from dbgcopilot.analysis.model import Instruction
+from dbgcopilot.analysis.parsers import parse_objdump_disassembly
+
+log = logging.getLogger(__name__)
+
+OBJDUMP = "objdump"
+# Seconds before a stuck objdump is given up on.
+OBJDUMP_TIMEOUT = 10
+# The dump's architecture, as `DumpSession.arch` names it, to objdump's -m.
+_MACHINES = {"x86_64": "i386:x86-64", "aarch64": "aarch64"}
+
+
+def disassemble(code: bytes, address: int, arch: Optional[str], pc: Optional[int] = None) -> List[Instruction]:
+    """``code``, loaded at ``address``, as instructions, the one at ``pc`` marked; empty when it cannot be decoded."""
+    machine = _MACHINES.get(arch or "")
+    executable = shutil.which(OBJDUMP)
+    if not code or machine is None or executable is None:
+        return []
+    with tempfile.NamedTemporaryFile(prefix="dbgcopilot-", suffix=".bin") as scratch:
+        scratch.write(code)
+        scratch.flush()
+        argv = [
+            executable, "-D", "-b", "binary", "-m", machine, f"--adjust-vma=0x{address:x}",
+            "--no-show-raw-insn", "-w", scratch.name,
+        ]
+        try:
+            done = subprocess.run(argv, capture_output=True, text=True, timeout=OBJDUMP_TIMEOUT)
+        except (OSError, subprocess.SubprocessError) as exc:
+            log.debug("objdump: %s", exc)
+            return []
+    if done.returncode != 0:
+        log.debug("objdump: exit %d: %s", done.returncode, done.stderr.strip())
+        return []
+    return parse_objdump_disassembly(done.stdout, pc)
+
+
+__all__ = ["OBJDUMP", "OBJDUMP_TIMEOUT", "disassemble"]
@@ -0,0 +1,219 @@
+"""Offline sessions: a dump file behind the same surface as a live debugger session.
+
+`DumpSession.from_dump(path)` sniffs the file, opens it with `CoreReader` (ELF
+core) or `MinidumpReader` (Windows ``.dmp``), and exposes the thread, stack,
+register, and stop accessors of `dbgcopilot.backends.base.DebugSession`, so
+analysis code does not care whether a process is still alive.
+"""
+from __future__ import annotations
+
+from pathlib import Path
+from typing import TYPE_CHECKING, Any, List, Optional, Union
+
+from dbgcopilot.analysis.disasm import CODE_BYTES, DEFAULT_AFTER, DEFAULT_BEFORE, FaultContext, build_fault_context
+from dbgcopilot.analysis.heap import HeapSummary
+from dbgcopilot.analysis.model import CrashContext, Frame, Module, Register, StopInfo, ThreadBacktrace, Variable
+from dbgcopilot.analysis.regions import MapEntry
+from dbgcopilot.analysis.registers import Registers
+from dbgcopilot.analysis.threads import DEFAULT_MAX_FRAMES_PER_THREAD
+from dbgcopilot.utils.elf import ELF_MAGIC
+
+from .coredump import CoreReader
+from .errors import InvalidDumpError, TruncatedDumpError
+from .minidump import MINIDUMP_SIGNATURE, MinidumpReader
+from .objdump import disassemble
+
+if TYPE_CHECKING:
+    from dbgcopilot.symbols.sympath import SymbolPath
+
+# Bytes from a function's symbol to the pc beyond which decoding from the symbol is not tried.
+_MAX_FUNCTION_LEAD = 0x4000
+
+
+class DumpSession:
+    """Read-only session over a core file or minidump; no debugger involved.
+
+    Threads are numbered from 1 in file order, like `ThreadBacktrace.thread_id`.
+    ``thread=None`` means the thread that crashed.
+    """
+
+    def __init__(
+        self,
+        reader: Union[CoreReader, MinidumpReader],
+        kind: str,
+        max_frames_per_thread: int = DEFAULT_MAX_FRAMES_PER_THREAD,
+    ) -> None:
+        self.reader = reader
+        # "core" or "minidump"; also used as AnalysisReport.source.
+        self.kind = kind
+        # How deep each stack is unwound; 0 for no limit.
+        self.max_frames_per_thread = max_frames_per_thread
+
+    @classmethod
+    def from_dump(
+        cls,
+        path: Union[str, Path],
+        program: Optional[Union[str, Path]] = None,
+        *,
+        sysroot: Optional[Union[str, Path]] = None,
+        debug_file: Optional[Union[str, Path]] = None,
+        max_frames_per_thread: int = DEFAULT_MAX_FRAMES_PER_THREAD,
+        symbol_path: Optional["SymbolPath"] = None,
+    ) -> "DumpSession":
+        """Open ``path`` as an ELF core or minidump, whichever its magic says.
+
+        ``program`` / ``sysroot`` locate the executable and libraries for core
+        symbolization, and ``debug_file`` the program's separate debug file
+        (see `CoreReader`), and ``symbol_path`` where library debug files are
+        found or downloaded. ``max_frames_per_thread`` caps every stack (0 for
+        no limit). Raises `TruncatedDumpError` or `InvalidDumpError`, never a
+        bare parsing error.
+        """
+        try:
+            with open(path, "rb") as fh:
+                magic = fh.read(4)
+        except OSError as exc:
+            raise InvalidDumpError(f"{path}: {exc}") from exc
+        if magic == MINIDUMP_SIGNATURE:
+            return cls(MinidumpReader(path), "minidump", max_frames_per_thread)
+        if magic == ELF_MAGIC:
+            reader = CoreReader(
+                path, executable=program, sysroot=sysroot, debug_file=debug_file, symbol_path=symbol_path
+            )
+            return cls(reader, "core", max_frames_per_thread)
+        if len(magic) < 4:
+            raise TruncatedDumpError(f"{path}: {len(magic)} bytes is too small to be a dump")
+        raise InvalidDumpError(f"{path}: not an ELF core file or minidump (magic {magic!r})")
+
+    @property
+    def path(self) -> Path:
+        return self.reader.path
+
+    @property
+    def warnings(self) -> List[str]:
+        """What the reader could not do in time, such as symbolize an image; see `CoreReader.warnings`."""
+        return list(getattr(self.reader, "warnings", []))
+
+    @property
+    def arch(self) -> Optional[str]:
+        """Architecture the dump was written on (``x86_64``, ``aarch64``, ...), not the host's."""
+        return self.reader.arch
+
+    def _thread(self, thread: Optional[int]) -> Any:
+        if thread is None:
+            return self.reader.faulting_thread
+        if not 1 <= thread <= len(self.reader.threads):
+            raise ValueError(f"no thread {thread} in {self.path} (has {len(self.reader.threads)})")
+        return self.reader.threads[thread - 1]
+
+    def threads(self) -> List[ThreadBacktrace]:
+        """Every thread with its stack. Stacks are empty when the arch's registers are not decoded."""
+        return self.reader.thread_backtraces(self.max_frames_per_thread)
+
+    def stack_trace(self, thread: Optional[int] = None) -> List[Frame]:
+        target = self._thread(thread)
+        return self.reader.stack_frames(target, self.max_frames_per_thread) if target is not None else []
+
+    def stop_info(self) -> StopInfo:
+        return self.reader.stop_info()
+
+    def read_registers(self, thread: Optional[int] = None) -> List[Register]:
+        target = self._thread(thread)
+        return list(target.registers) if target is not None else []
+
+    def read_memory(self, address: int, size: int) -> Optional[bytes]:
+        return self.reader.read_memory(address, size)
+
+    def current_tid(self) -> Optional[int]:
+        thread = self.reader.faulting_thread
+        if thread is None:
+            return None
+        return thread.tid if isinstance(self.reader, CoreReader) else thread.thread_id
+
+    def modules(self) -> List[Module]:
+        return list(self.reader.modules)
+
+    def crash_context(self) -> CrashContext:
+        ctx = self.reader.crash_context(self.max_frames_per_thread)
+        ctx.heap = self.heap_summary()
+        ctx.fault_context = self.fault_context()
+        return ctx
+
+    def fault_context(self, before: int = DEFAULT_BEFORE, after: int = DEFAULT_AFTER) -> Optional[FaultContext]:
+        """The faulting thread's registers and the instructions around its pc, decoded by `objdump.disassemble`.
+
+        Decoding starts at the pc's function symbol, so a minidump, which has none, shows nothing
+        before the pc. Without ``objdump`` the context keeps only the bytes at the pc.
+        """
+        registers = self.read_registers()
+        pc = Registers.from_list(registers).instruction_pointer
+        if pc is None:
+            return build_fault_context(registers)
+        start = self._function_start(pc)
+        code = self._read_available(start, pc - start + (after + 1) * CODE_BYTES)
+        instructions = disassemble(code, start, self.arch, pc) if len(code) > pc - start else []
+        if start != pc and not any(insn.faulting for insn in instructions):
+            # The symbol is not where the function's instructions start; from the pc, at least.
+            start = pc
+            code = self._read_available(pc, (after + 1) * CODE_BYTES)
+            instructions = disassemble(code, pc, self.arch, pc)
+        at_pc = code[pc - start : pc - start + CODE_BYTES]
+        return build_fault_context(registers, instructions, before, after, code=at_pc)
+
+    def _function_start(self, pc: int) -> int:
+        if not isinstance(self.reader, CoreReader):
+            return pc
+        offset = self.reader.symbolize(0, pc, is_return_address=False).offset
+        return pc - offset if offset is not None and 0 <= offset <= _MAX_FUNCTION_LEAD else pc
+
+    def _read_available(self, address: int, size: int) -> bytes:
+        """Up to ``size`` bytes at ``address``, stopping where the dump's memory does."""
+        data = b""
+        while len(data) < size:
+            chunk = min(CODE_BYTES, size - len(data))
+            got = self.read_memory(address + len(data), chunk)
+            # At the end of a segment, what is left of it.
+            while got is None and chunk > 1:
+                chunk -= 1
+                got = self.read_memory(address + len(data), chunk)
+            if got is None:
+                break
+            data += got
+            if len(got) < CODE_BYTES:
+                break
+        return data
+
+    def heap_summary(self) -> HeapSummary:
+        # The readers do not decode allocator metadata; heap corruption still shows on the stack.
+        return HeapSummary.unsupported(self.kind)
+
+    def memory_map(self) -> List[MapEntry]:
+        if not isinstance(self.reader, CoreReader):
+            # A minidump's memory info list is not decoded.
+            raise NotImplementedError(f"{self.kind} sessions have no memory map")
+        return self.reader.memory_map()
+
+    def frame_locals(self, thread: Optional[int] = None, frame_index: int = 0) -> List[Variable]:
+        # Variable locations live in .debug_info, which the readers do not decode.
+        raise NotImplementedError(
+            f"{self.kind} sessions cannot read variables; "
+            "open the dump with a SessionBackend (gdb, lldb, cdb) for locals and arguments"
+        )
+
+    def run_command(self, cmd: str) -> str:
+        raise NotImplementedError(
+            f"{self.kind} sessions have no debugger to run {cmd!r}; "
+            "open the dump with a SessionBackend (gdb, lldb, cdb) for raw commands"
+        )
+
+    def close(self) -> None:
+        self.reader.close()
+
+    def __enter__(self) -> "DumpSession":
+        return self
+
+    def __exit__(self, *_exc: object) -> None:
+        self.close()
+
+
+__all__ = ["DumpSession"]
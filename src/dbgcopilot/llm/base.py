@@ -0,0 +1,251 @@
+"""LLM provider interface and the HTTP providers behind it.
+
+The analysis layer talks to models only through `LlmProvider`. `OpenAiProvider`
+and `OllamaProvider` carry their endpoint, model, and timeouts as plain fields, so
+a local model is just ``OllamaProvider(base_url="http://localhost:11434")``.
+Failures surface as `LlmError` subclasses (``errors.py``).
+`complete_stream` turns a provider's callback stream into an iterator.
+`RegistryProvider` adapts any entry of the JSON provider registry
+(``providers.py``) to the same interface.
+"""
+from __future__ import annotations
+
+import os
+import queue
+import threading
+from dataclasses import dataclass, field
+from typing import Any, Callable, Dict, Iterator, Optional, Protocol, Union
+
+from . import openai_compat
+from .errors import (
+    LlmBudgetError,
+    LlmConfigError,
+    LlmError,
+    LlmHttpError,
+    LlmResponseError,
+    LlmTransportError,
+)
+from .sse import ChunkCallback, StreamError
+
+# Environment variable naming the provider when the session config does not.
+PROVIDER_ENV_VAR = "DBGCOPILOT_LLM_PROVIDER"
+
+
+@dataclass
+class Prompt:
+    text: str
+    system: Optional[str] = None
+    max_tokens: int = 512
+    temperature: float = 0.0
+
+    def messages(self) -> list[Dict[str, str]]:
+        msgs = [{"role": "system", "content": self.system}] if self.system else []
+        msgs.append({"role": "user", "content": self.text})
+        return msgs
+
+
+@dataclass
+class Completion:
+    text: str
+    provider: str
+    model: str = ""
+    usage: Dict[str, Any] = field(default_factory=dict)
+
+
+class LlmProvider(Protocol):
+    name: str
+
+    def complete(self, prompt: Prompt) -> Completion:  # pragma: no cover
+        """Return the model's answer; failures raise `LlmError` subclasses."""
+        ...
+
+    def stream(self, prompt: Prompt, on_chunk: ChunkCallback) -> Completion:  # pragma: no cover
+        """Like `complete`, calling ``on_chunk`` with each fragment as it arrives."""
+        ...
+
+
+@dataclass
+class OpenAiProvider:
+    """Any endpoint speaking the OpenAI Chat Completions API."""
+
+    base_url: str = "https://api.openai.com"
+    model: str = "gpt-4o-mini"
+    api_key: Optional[str] = None
+    path: str = "/v1/chat/completions"
+    headers: Dict[str, str] = field(default_factory=dict)
+    name: str = "openai"
+    # Seconds to wait for the answer (when streaming, for each chunk) and to connect.
+    timeout: float = openai_compat.DEFAULT_TIMEOUT
+    connect_timeout: float = openai_compat.DEFAULT_CONNECT_TIMEOUT
+
+    def _request(self, prompt: Prompt) -> tuple[str, Dict[str, str], Dict[str, Any]]:
+        if not self.base_url:
+            raise LlmConfigError(f"{self.name}: base_url not configured", self.name)
+        url = self.base_url.rstrip("/") + (self.path if self.path.startswith("/") else "/" + self.path)
+        headers = {"Content-Type": "application/json", "Accept": "application/json"}
+        headers.update(self.headers)
+        if self.api_key and not any(h.lower() == "authorization" for h in headers):
+            headers["Authorization"] = f"Bearer {self.api_key}"
+        body: Dict[str, Any] = {
+            "model": self.model,
+            "messages": prompt.messages(),
+            "max_tokens": prompt.max_tokens,
+            "temperature": prompt.temperature,
+        }
+        return url, headers, body
+
+    def _completion(self, text: str, usage: Dict[str, Any]) -> Completion:
+        return Completion(text=text, provider=self.name, model=self.model, usage=usage)
+
+    def _timeout(self) -> tuple[float, float]:
+        return (self.connect_timeout, self.timeout)
+
+    def complete(self, prompt: Prompt) -> Completion:
+        url, headers, body = self._request(prompt)
+        text, data = openai_compat.post_chat_completion(url, headers, body, self.name, self._timeout())
+        return self._completion(text, openai_compat._extract_usage(data, self.name, self.model))
+
+    def stream(self, prompt: Prompt, on_chunk: ChunkCallback) -> Completion:
+        url, headers, body = self._request(prompt)
+        text, raw_usage = openai_compat.post_chat_completion_stream(
+            url, headers, body, self.name, on_chunk, self._timeout()
+        )
+        return self._completion(text, openai_compat._extract_usage({"usage": raw_usage}, self.name, self.model))
+
+
+@dataclass
+class OllamaProvider(OpenAiProvider):
+    """A local (or on-prem) Ollama server via its OpenAI-compatible endpoint; no API key."""
+
+    base_url: str = "http://localhost:11434"
+    model: str = "llama3.1"
+    name: str = "ollama"
+
+
+@dataclass
+class RegistryProvider:
+    """Adapter for a named entry of the JSON provider registry (openrouter, deepseek, ...)."""
+
+    name: str
+    session_config: Dict[str, Any] = field(default_factory=dict)
+    _client: Optional[Callable[[str], str]] = field(default=None, repr=False)
+
+    def _ask(self) -> Callable[[str], str]:
+        if self._client is None:
+            from . import providers
+
+            self._client = providers.create_client(self.name, self.session_config)
+        return self._client
+
+    def _completion(self, text: str) -> Completion:
+        usage = dict(getattr(self._ask(), "last_usage", {}) or {})
+        return Completion(text=text, provider=self.name, model=str(usage.get("model", "")), usage=usage)
+
+    def complete(self, prompt: Prompt) -> Completion:
+        # Registry clients take a single string; fold the system text in front.
+        text = f"{prompt.system}\n\n{prompt.text}" if prompt.system else prompt.text
+        return self._completion(self._ask()(text))
+
+    def stream(self, prompt: Prompt, on_chunk: ChunkCallback) -> Completion:
+        ask = self._ask()
+        stream = getattr(ask, "stream", None)
+        if stream is None:
+            completion = self.complete(prompt)
+            on_chunk(completion.text)
+            return completion
+        text = f"{prompt.system}\n\n{prompt.text}" if prompt.system else prompt.text
+        return self._completion(stream(text, on_chunk))
+
+
+def _from_cfg(cls: type, name: str, session_config: Optional[Dict[str, Any]]) -> OpenAiProvider:
+    # Same session-key/env precedence as the registry clients (see openai_compat).
+    cfg = openai_compat._get_cfg(name, session_config, defaults={"base_url": cls.base_url, "default_model": cls.model})
+    return cls(
+        base_url=cfg.get("base_url") or cls.base_url,
+        model=cfg.get("model") or cls.model,
+        api_key=cfg.get("api_key"),
+        path=cfg.get("path") or "/v1/chat/completions",
+        headers=dict(cfg.get("headers") or {}),
+        name=name,
+        timeout=cfg["timeout"],
+        connect_timeout=cfg["connect_timeout"],
+    )
+
+
+def resolve_provider(
+    provider: Union[str, LlmProvider, None] = None,
+    session_config: Optional[Dict[str, Any]] = None,
+) -> LlmProvider:
+    """Return an `LlmProvider` from an instance, a name, or the config/environment.
+
+    Without an explicit name, ``llm_provider`` in the session config is used,
+    then ``$DBGCOPILOT_LLM_PROVIDER``. ``openai`` and ``ollama`` map to the HTTP
+    providers above (base URL, model, key, and timeouts from ``OPENAI_*`` /
+    ``OLLAMA_*``); any other name is looked up in the provider registry.
+    """
+    if provider is not None and not isinstance(provider, str):
+        return provider
+    config = session_config or {}
+    name = provider or config.get("llm_provider") or os.environ.get(PROVIDER_ENV_VAR)
+    if not name:
+        raise LlmConfigError(f"No LLM provider configured; pass one or set {PROVIDER_ENV_VAR}")
+    if name == "openai":
+        return _from_cfg(OpenAiProvider, name, config)
+    if name == "ollama":
+        return _from_cfg(OllamaProvider, name, config)
+    return RegistryProvider(name=name, session_config=dict(config))
+
+
+_END = object()
+
+
+def complete_stream(provider: LlmProvider, prompt: Prompt) -> Iterator[str]:
+    """Yield the answer's fragments as the provider streams them.
+
+    A failure is raised from the iterator where it happened, after the
+    fragments that arrived before it: a drop mid-answer raises `StreamError`
+    with ``partial`` set, a failure before any output raises the provider's
+    `LlmError` as-is. Leaving the loop early does not cancel the request; it
+    runs to completion in the background.
+    """
+    items: "queue.Queue[Any]" = queue.Queue()
+
+    def run() -> None:
+        try:
+            provider.stream(prompt, items.put)
+            items.put(_END)
+        except BaseException as exc:
+            items.put(exc)
+
+    threading.Thread(target=run, name=f"llm-stream-{provider.name}", daemon=True).start()
+    parts: list[str] = []
+    while True:
+        item = items.get()
+        if item is _END:
+            return
+        if isinstance(item, BaseException):
+            if parts and not isinstance(item, StreamError):
+                # Fragments were yielded already; the caller must know the answer is cut short.
+                raise StreamError(f"{provider.name} stream failed: {item}", "".join(parts), provider.name) from item
+            raise item
+        parts.append(item)
+        yield item
+
+
+__all__ = [
+    "Completion",
+    "LlmBudgetError",
+    "LlmConfigError",
+    "LlmError",
+    "LlmHttpError",
+    "LlmProvider",
+    "LlmResponseError",
+    "LlmTransportError",
+    "OllamaProvider",
+    "OpenAiProvider",
+    "PROVIDER_ENV_VAR",
+    "Prompt",
+    "RegistryProvider",
+    "complete_stream",
+    "resolve_provider",
+]
@@ -0,0 +1,130 @@
+"""Typed failures of LLM requests.
+
+Every provider raises a subclass of `LlmError`, so callers can tell a
+misconfiguration (no base URL, no key) from an unreachable or slow server
+and from an endpoint that answered with an error status. `LlmError` derives
+from `RuntimeError`, which is what the providers raised before, so existing
+``except RuntimeError`` handlers keep working.
+"""
+from __future__ import annotations
+
+import time
+from email.utils import parsedate_to_datetime
+from typing import Optional
+
+
+class LlmError(RuntimeError):
+    """Base class; ``provider`` names the provider that failed.
+
+    ``attempts`` is how many times the call was made before giving up
+    (see ``retry.py``); the message mentions it once there was more than one.
+    """
+
+    def __init__(self, message: str, provider: str = "") -> None:
+        super().__init__(message)
+        self.provider = provider
+        self.attempts = 1
+
+    def __str__(self) -> str:
+        message = super().__str__()
+        return f"{message} (after {self.attempts} attempts)" if self.attempts > 1 else message
+
+
+class LlmConfigError(LlmError, ValueError):
+    """The provider is not usable as configured (missing base URL, key, or name)."""
+
+
+class LlmTransportError(LlmError):
+    """The request never got an HTTP answer: DNS, refused connection, TLS, or timeout."""
+
+    def __init__(self, message: str, provider: str = "", timed_out: bool = False) -> None:
+        super().__init__(message, provider)
+        self.timed_out = timed_out
+
+
+class LlmHttpError(LlmError):
+    """The endpoint answered with a non-2xx status; ``body`` is the start of the response.
+
+    ``retry_after`` is the wait in seconds the endpoint asked for with a
+    ``Retry-After`` header (usually on 429 and 503), or None.
+    """
+
+    def __init__(
+        self, message: str, provider: str = "", status: int = 0, body: str = "", retry_after: Optional[float] = None
+    ) -> None:
+        super().__init__(message, provider)
+        self.status = status
+        self.body = body
+        self.retry_after = retry_after
+
+
+class LlmResponseError(LlmError):
+    """A 2xx answer that is not the expected JSON."""
+
+
+class LlmBudgetError(LlmError):
+    """The prompt was not sent: its estimated cost is over ``max_cost``, or no price is known to check it.
+
+    ``estimate`` is the estimated cost in USD, None when the model has no price.
+    """
+
+    def __init__(self, message: str, provider: str = "", estimate: Optional[float] = None) -> None:
+        super().__init__(message, provider)
+        self.estimate = estimate
+
+
+def transport_error(provider: str, exc: BaseException, what: str = "request") -> LlmTransportError:
+    """Wrap an exception raised by ``requests`` before any response arrived."""
+    timed_out = False
+    try:
+        import requests
+
+        timed_out = isinstance(exc, requests.exceptions.Timeout)
+    except ImportError:
+        pass
+    reason = "timed out" if timed_out else "failed"
+    return LlmTransportError(f"{provider} {what} {reason}: {exc}", provider, timed_out=timed_out)
+
+
+def parse_retry_after(value: Optional[str], now: Optional[float] = None) -> Optional[float]:
+    """Seconds from a ``Retry-After`` header: delay-seconds or an HTTP date; None when absent or unreadable."""
+    if value is None or not value.strip():
+        return None
+    value = value.strip()
+    if value.isdigit():
+        return float(value)
+    try:
+        when = parsedate_to_datetime(value)
+    except (TypeError, ValueError):
+        return None
+    if when.tzinfo is None:
+        return None
+    return max(0.0, when.timestamp() - (time.time() if now is None else now))
+
+
+def http_error(
+    provider: str, status: int, text: Optional[str], url: str = "", retry_after: Optional[str] = None
+) -> LlmHttpError:
+    """``retry_after`` is the raw ``Retry-After`` header, if the response had one."""
+    snippet = (text or "").strip()[:200].replace("\n", " ")
+    where = f" for {url}" if url else ""
+    return LlmHttpError(
+        f"{provider} HTTP {status}{where}: {snippet}",
+        provider,
+        status=status,
+        body=snippet,
+        retry_after=parse_retry_after(retry_after),
+    )
+
+
+__all__ = [
+    "LlmBudgetError",
+    "LlmConfigError",
+    "LlmError",
+    "LlmHttpError",
+    "LlmResponseError",
+    "LlmTransportError",
+    "http_error",
+    "parse_retry_after",
+    "transport_error",
+]
@@ -12,22 +12,35 @@ Configuration precedence (per provider name, e.g., 'openai-http' or 'ollama'):
   - {name}_model
   - {name}_headers (dict or JSON string)
   - {name}_path (defaults to /v1/chat/completions)
+  - {name}_timeout / {name}_connect_timeout (seconds; default 60 / 20)
 - Environment variables (next): uppercased prefix derived from name by non-alnum->'_' mapping
-  - {PREFIX}_BASE_URL, {PREFIX}_API_KEY, {PREFIX}_MODEL, {PREFIX}_HEADERS (JSON), {PREFIX}_PATH
+  - {PREFIX}_BASE_URL, {PREFIX}_API_KEY, {PREFIX}_MODEL, {PREFIX}_HEADERS (JSON), {PREFIX}_PATH,
+    {PREFIX}_TIMEOUT, {PREFIX}_CONNECT_TIMEOUT
 - Built-in defaults (lowest):
   - For name == 'ollama': base_url=http://localhost:11434, model='llama3.1', no API key
   - Otherwise: require base_url and API key
 
-Note: This is a lightweight POC client. For production, add retries, timeouts, and robust error handling.
+Failures are raised as `dbgcopilot.llm.errors.LlmError` subclasses.
+
+Note: This is a lightweight POC client. For production, add retries.
 """
 from __future__ import annotations
 
 import os
 import json
 import re
-from typing import Optional, Dict, Any, Tuple
+from typing import Optional, Dict, Any, Tuple, Union
 
 from . import params as param_utils
+from .errors import LlmConfigError, LlmResponseError, http_error, transport_error
+from .sse import ChunkCallback, stream_chat_completion
+
+# Seconds to establish the connection, and to wait for the answer (or, when
+# streaming, for the next chunk). Overridable per provider; see _get_cfg.
+DEFAULT_CONNECT_TIMEOUT = 20.0
+DEFAULT_TIMEOUT = 60.0
+
+Timeout = Union[float, Tuple[float, float]]
 
 
 def _slug_to_env_prefix(name: str) -> str:
@@ -59,6 +72,8 @@ def _get_cfg(
     path = pick(f"{key}_path", f"{prefix}_PATH", path_default or "/v1/chat/completions")
     path_override = f"{key}_path" in sc or f"{prefix}_PATH" in os.environ
     headers_raw = pick(f"{key}_headers", f"{prefix}_HEADERS", None)
+    timeout = _seconds(name, "timeout", pick(f"{key}_timeout", f"{prefix}_TIMEOUT", None))
+    connect_timeout = _seconds(name, "connect_timeout", pick(f"{key}_connect_timeout", f"{prefix}_CONNECT_TIMEOUT", None))
 
     headers: Dict[str, str] = {}
     if headers_raw:
@@ -125,9 +140,31 @@ def _get_cfg(
         "model": model,
         "path": path or "/v1/chat/completions",
         "headers": headers,
+        "timeout": timeout if timeout is not None else DEFAULT_TIMEOUT,
+        "connect_timeout": connect_timeout if connect_timeout is not None else DEFAULT_CONNECT_TIMEOUT,
     }
 
 
+def _seconds(name: str, key: str, value: Any) -> Optional[float]:
+    if value is None:
+        return None
+    try:
+        seconds = float(value)
+    except (TypeError, ValueError):
+        seconds = -1.0
+    if seconds <= 0:
+        raise LlmConfigError(f"{name}: {key} must be a positive number of seconds, got {value!r}", name)
+    return seconds
+
+
+def request_timeout(cfg: Dict[str, Any]) -> Tuple[float, float]:
+    """The (connect, read) timeout pair `requests` takes, from a `_get_cfg` result."""
+    return (
+        float(cfg.get("connect_timeout") or DEFAULT_CONNECT_TIMEOUT),
+        float(cfg.get("timeout") or DEFAULT_TIMEOUT),
+    )
+
+
 def _extract_usage(data: Dict[str, Any], provider_name: str, model: str) -> Dict[str, Any]:
     usage: Dict[str, Any] = {
         "provider": provider_name,
@@ -161,18 +198,14 @@ def _extract_usage(data: Dict[str, Any], provider_name: str, model: str) -> Dict
     return usage
 
 
-def _ask_openai_compat(
+def _build_request(
     prompt: str,
     name: str,
     session_config: Optional[dict[str, Any]] = None,
     defaults: Optional[Dict[str, Any]] = None,
     meta: Optional[Dict[str, Any]] = None,
-) -> Tuple[str, Dict[str, Any]]:
-    try:
-        import requests
-    except Exception as e:
-        raise RuntimeError("requests library is required for OpenAI-compatible providers") from e
-
+) -> Tuple[str, Dict[str, str], Dict[str, Any], str]:
+    """Return (url, headers, body, model) for a chat completion request."""
     cfg = _get_cfg(name, session_config, defaults=defaults)
     base_url = (cfg.get("base_url") or "").rstrip("/")
     api_key = cfg.get("api_key")
@@ -185,7 +218,11 @@ def _ask_openai_compat(
         headers["Authorization"] = f"Bearer {api_key}"
 
     if not base_url:
-        raise RuntimeError(f"{name}: base_url not configured. Set {name.replace('-', '_')}_base_url in session config or { _slug_to_env_prefix(name) }_BASE_URL in env.")
+        raise LlmConfigError(
+            f"{name}: base_url not configured. Set {name.replace('-', '_')}_base_url in session config "
+            f"or {_slug_to_env_prefix(name)}_BASE_URL in env.",
+            name,
+        )
 
     url = f"{base_url}{path if path.startswith('/') else '/' + path}"
     meta = meta or {}
@@ -219,29 +256,44 @@ def _ask_openai_compat(
 
     session_params = param_utils.get_session_params(session_config or {}, name)
     body = param_utils.apply_params(body, session_params, meta, assume_canonical=True)
+    return url, headers, body, model
+
+
+def post_chat_completion(
+    url: str,
+    headers: Dict[str, str],
+    body: Dict[str, Any],
+    name: str,
+    timeout: Timeout = (DEFAULT_CONNECT_TIMEOUT, DEFAULT_TIMEOUT),
+) -> Tuple[str, Dict[str, Any]]:
+    """POST a chat completion body and return (content, raw response JSON)."""
+    try:
+        import requests
+    except Exception as e:
+        raise LlmConfigError("requests library is required for OpenAI-compatible providers", name) from e
 
     try:
-        resp = requests.post(url, headers=headers, json=body, timeout=20)
+        resp = requests.post(url, headers=headers, json=body, timeout=timeout)
     except Exception as e:
-        raise RuntimeError(f"{name} request failed: {e}") from e
+        raise transport_error(name, e) from e
 
     if not (200 <= resp.status_code < 300):
-        snippet = (resp.text or "")[:200].replace("\n", " ")
-        raise RuntimeError(f"{name} HTTP {resp.status_code} for {url}: {snippet}")
+        raise http_error(name, resp.status_code, resp.text, url, resp.headers.get("Retry-After"))
 
     content_type = resp.headers.get("Content-Type", "").lower()
     if "json" not in content_type:
         snippet = (resp.text or "")[:400].replace("\n", " ")
-        raise RuntimeError(
+        raise LlmResponseError(
             f"{name} returned non-JSON payload (content-type={content_type or 'unknown'}). "
-            f"Response snippet: {snippet}"
+            f"Response snippet: {snippet}",
+            name,
         )
 
     try:
         data = resp.json()
     except Exception as e:
         raw = (resp.text or "")[:400]
-        raise RuntimeError(f"{name} returned invalid JSON (status {resp.status_code}). Snippet: {raw}") from e
+        raise LlmResponseError(f"{name} returned invalid JSON (status {resp.status_code}). Snippet: {raw}", name) from e
 
     # Try OpenAI-like shape first
     try:
@@ -249,11 +301,71 @@ def _ask_openai_compat(
     except Exception:
         # fall back to raw json text
         content = json.dumps(data)
+    return content, data
+
 
+def _ask_openai_compat(
+    prompt: str,
+    name: str,
+    session_config: Optional[dict[str, Any]] = None,
+    defaults: Optional[Dict[str, Any]] = None,
+    meta: Optional[Dict[str, Any]] = None,
+) -> Tuple[str, Dict[str, Any]]:
+    url, headers, body, model = _build_request(prompt, name, session_config, defaults, meta)
+    timeout = request_timeout(_get_cfg(name, session_config, defaults=defaults))
+    content, data = post_chat_completion(url, headers, body, name, timeout)
     usage = _extract_usage(data, name, model)
     return content, usage
 
 
+def post_chat_completion_stream(
+    url: str,
+    headers: Dict[str, str],
+    body: Dict[str, Any],
+    name: str,
+    on_chunk: ChunkCallback,
+    timeout: Timeout = (DEFAULT_CONNECT_TIMEOUT, DEFAULT_TIMEOUT),
+) -> Tuple[str, Dict[str, Any]]:
+    """Streamed variant of `post_chat_completion`; returns (content, raw usage)."""
+    try:
+        import requests
+    except Exception as e:
+        raise LlmConfigError("requests library is required for OpenAI-compatible providers", name) from e
+
+    headers = dict(headers, Accept="text/event-stream")
+    body = dict(body, stream=True)
+    # OpenAI-style servers only send a stream's token counts when asked.
+    body.setdefault("stream_options", {"include_usage": True})
+    try:
+        # (connect, read) timeout: the read timeout applies between chunks, not to the whole answer.
+        resp = requests.post(url, headers=headers, json=body, timeout=timeout, stream=True)
+    except Exception as e:
+        raise transport_error(name, e) from e
+
+    with resp:
+        if not (200 <= resp.status_code < 300):
+            raise http_error(name, resp.status_code, resp.text, url, resp.headers.get("Retry-After"))
+        raw_usage: Dict[str, Any] = {}
+        content = stream_chat_completion(resp.iter_lines(), on_chunk, name, usage_out=raw_usage)
+    return content, raw_usage
+
+
+def _stream_openai_compat(
+    prompt: str,
+    on_chunk: ChunkCallback,
+    name: str,
+    session_config: Optional[dict[str, Any]] = None,
+    defaults: Optional[Dict[str, Any]] = None,
+    meta: Optional[Dict[str, Any]] = None,
+) -> Tuple[str, Dict[str, Any]]:
+    """Like `_ask_openai_compat`, but reads an SSE stream and reports each chunk."""
+    url, headers, body, model = _build_request(prompt, name, session_config, defaults, meta)
+    timeout = request_timeout(_get_cfg(name, session_config, defaults=defaults))
+    content, raw_usage = post_chat_completion_stream(url, headers, body, name, on_chunk, timeout)
+    usage = _extract_usage({"usage": raw_usage}, name, model)
+    return content, usage
+
+
 def create_provider(
     session_config: dict[str, Any] | None = None,
     name: str = "openai-http",
@@ -262,6 +374,9 @@ def create_provider(
 ):
     """Return an ask(prompt) function bound to session_config and provider name.
 
+    ``ask.stream(prompt, on_chunk)`` requests a streamed completion instead and
+    calls ``on_chunk`` with each text fragment as it arrives.
+
     Examples:
     - name='openai-http': use {openai_http_*} keys or OPENAI_HTTP_* env vars
     - name='ollama': use {ollama_*} keys or OLLAMA_* env vars (defaults base_url and model)
@@ -279,7 +394,20 @@ def create_provider(
         setattr(ask, "last_usage", usage)
         return content
 
+    def stream(prompt: str, on_chunk: ChunkCallback) -> str:
+        content, usage = _stream_openai_compat(
+            prompt,
+            on_chunk,
+            name=name,
+            session_config=session_config,
+            defaults=defaults,
+            meta=meta_payload,
+        )
+        setattr(ask, "last_usage", usage)
+        return content
+
     setattr(ask, "last_usage", {})
+    setattr(ask, "stream", stream)
     return ask
 
 
@@ -298,7 +426,7 @@ def list_models(
     try:
         import requests
     except Exception as e:
-        raise RuntimeError("requests library is required to list models for OpenAI-compatible providers") from e
+        raise LlmConfigError("requests library is required to list models for OpenAI-compatible providers", name) from e
 
     cfg = _get_cfg(name, session_config, defaults=defaults)
     base_url = (cfg.get("base_url") or "").rstrip("/")
@@ -308,7 +436,7 @@ def list_models(
         headers["Authorization"] = f"Bearer {api_key}"
 
     if not base_url:
-        raise RuntimeError(f"{name}: base_url not configured; cannot list models")
+        raise LlmConfigError(f"{name}: base_url not configured; cannot list models", name)
 
     # 1) Try OpenAI-compatible /v1/models (Gemini uses a different path)
     try:
@@ -6,8 +6,12 @@ This module implements a minimal client to OpenRouter's API for text generation.
 It expects an API key in the environment variable OPENROUTER_API_KEY or a key
 passed via provider.meta or session.config under 'openrouter_api_key'.
 
-Note: This is a lightweight POC. For production usage, improve error handling,
-timeouts, retries, and don't log secrets.
+Timeouts come from openrouter_timeout / openrouter_connect_timeout (or
+OPENROUTER_TIMEOUT / OPENROUTER_CONNECT_TIMEOUT), as for the OpenAI-compatible
+providers, and failures are raised as `dbgcopilot.llm.errors.LlmError`.
+
+Note: This is a lightweight POC. For production usage, add retries and don't
+log secrets.
 """
 from __future__ import annotations
 
@@ -16,6 +20,9 @@ import json
 from typing import Optional, Tuple, Dict, Any
 
 from . import params as param_utils
+from .errors import LlmConfigError, LlmResponseError, http_error, transport_error
+from .openai_compat import _get_cfg, request_timeout
+from .sse import ChunkCallback, stream_chat_completion
 
 
 def _get_api_key(meta: dict[str, Any] | None = None, session_config: dict[str, Any] | None = None) -> Optional[str]:
@@ -66,22 +73,18 @@ def _extract_usage(data: Dict[str, Any], model: str) -> Dict[str, Any]:
     return usage
 
 
-def _ask_openrouter(
+def _build_request(
     prompt: str,
     meta: dict[str, Any] | None = None,
     session_config: dict[str, Any] | None = None,
-) -> Tuple[str, Dict[str, Any]]:
-    # Lazy import to avoid adding hard runtime deps for tests
-    try:
-        import requests
-    except Exception as e:
-        raise RuntimeError("requests library is required for OpenRouter provider") from e
-
+) -> Tuple[str, Dict[str, str], Dict[str, Any], str]:
+    """Return (url, headers, body, model) for an OpenRouter chat completion."""
     key = _get_api_key(meta, session_config)
     if not key:
-        raise RuntimeError(
+        raise LlmConfigError(
             "OpenRouter API key not configured. Set OPENROUTER_API_KEY, provide openrouter_api_key via session config, "
-            "or select a different LLM provider."
+            "or select a different LLM provider.",
+            "OpenRouter",
         )
 
     # OpenRouter chat completions endpoint (OpenAI-compatible)
@@ -135,17 +138,30 @@ def _ask_openrouter(
     provider_name = str(meta.get("name") or "openrouter")
     session_params = param_utils.get_session_params(session_config or {}, provider_name)
     body = param_utils.apply_params(body, session_params, meta, assume_canonical=True)
+    return url, headers, body, model
+
 
+def _ask_openrouter(
+    prompt: str,
+    meta: dict[str, Any] | None = None,
+    session_config: dict[str, Any] | None = None,
+) -> Tuple[str, Dict[str, Any]]:
+    # Lazy import to avoid adding hard runtime deps for tests
     try:
-        resp = requests.post(url, headers=headers, json=body, timeout=20)
+        import requests
+    except Exception as e:
+        raise LlmConfigError("requests library is required for OpenRouter provider", "OpenRouter") from e
+
+    url, headers, body, model = _build_request(prompt, meta=meta, session_config=session_config)
+    timeout = request_timeout(_get_cfg("openrouter", session_config))
+    try:
+        resp = requests.post(url, headers=headers, json=body, timeout=timeout)
     except Exception as e:  # requests.RequestException in most cases
-        raise RuntimeError(f"OpenRouter request failed: {e}") from e
+        raise transport_error("OpenRouter", e) from e
 
     # If non-2xx, surface body text to aid debugging
     if not (200 <= resp.status_code < 300):
-        text = (resp.text or "").strip()
-        snippet = text[:200].replace("\n", " ")
-        raise RuntimeError(f"OpenRouter HTTP {resp.status_code}: {snippet}")
+        raise http_error("OpenRouter", resp.status_code, resp.text, retry_after=resp.headers.get("Retry-After"))
 
     # Parse JSON response; if not JSON, show the raw response body for diagnosis
     try:
@@ -153,7 +169,7 @@ def _ask_openrouter(
     except Exception as e:
         raw = resp.text or ""
         # Prefer showing full provider response to help troubleshooting
-        raise RuntimeError(f"OpenRouter returned non-JSON response:\n{raw}") from e
+        raise LlmResponseError(f"OpenRouter returned non-JSON response:\n{raw}", "OpenRouter") from e
     # Expecting standard OpenAI-like shape: choices[0].message.content
     content: str
     try:
@@ -164,6 +180,35 @@ def _ask_openrouter(
     return content, usage
 
 
+def _stream_openrouter(
+    prompt: str,
+    on_chunk: ChunkCallback,
+    meta: dict[str, Any] | None = None,
+    session_config: dict[str, Any] | None = None,
+) -> Tuple[str, Dict[str, Any]]:
+    try:
+        import requests
+    except Exception as e:
+        raise LlmConfigError("requests library is required for OpenRouter provider", "OpenRouter") from e
+
+    url, headers, body, model = _build_request(prompt, meta=meta, session_config=session_config)
+    headers["Accept"] = "text/event-stream"
+    body["stream"] = True
+    timeout = request_timeout(_get_cfg("openrouter", session_config))
+    try:
+        resp = requests.post(url, headers=headers, json=body, timeout=timeout, stream=True)
+    except Exception as e:
+        raise transport_error("OpenRouter", e) from e
+
+    with resp:
+        if not (200 <= resp.status_code < 300):
+            raise http_error("OpenRouter", resp.status_code, resp.text, retry_after=resp.headers.get("Retry-After"))
+        raw_usage: Dict[str, Any] = {}
+        content = stream_chat_completion(resp.iter_lines(), on_chunk, "OpenRouter", usage_out=raw_usage)
+    usage = _extract_usage({"usage": raw_usage}, model)
+    return content, usage
+
+
 def create_provider(session_config: dict[str, Any] | None = None, meta: dict[str, Any] | None = None):
     # Returns a callable that accepts prompt and returns string
     meta = meta or {}
@@ -173,7 +218,13 @@ def create_provider(session_config: dict[str, Any] | None = None, meta: dict[str
         setattr(ask, "last_usage", usage)
         return content
 
+    def stream(prompt: str, on_chunk: ChunkCallback) -> str:
+        content, usage = _stream_openrouter(prompt, on_chunk, meta=meta, session_config=session_config)
+        setattr(ask, "last_usage", usage)
+        return content
+
     setattr(ask, "last_usage", {})
+    setattr(ask, "stream", stream)
     return ask
 
 
@@ -185,7 +236,7 @@ def list_models(session_config: dict[str, Any] | None = None) -> list[str]:
     try:
         import requests
     except Exception as e:
-        raise RuntimeError("requests library is required to list OpenRouter models") from e
+        raise LlmConfigError("requests library is required to list OpenRouter models", "OpenRouter") from e
 
     key = _get_api_key(None, session_config)
     url = "https://openrouter.ai/api/v1/models"
@@ -198,18 +249,16 @@ def list_models(session_config: dict[str, Any] | None = None) -> list[str]:
     try:
         resp = requests.get(url, headers=headers, timeout=15)
     except Exception as e:
-        raise RuntimeError(f"OpenRouter models request failed: {e}") from e
+        raise transport_error("OpenRouter", e, "models request") from e
 
     if not (200 <= resp.status_code < 300):
-        text = (resp.text or "").strip()
-        snippet = text[:200].replace("\n", " ")
-        raise RuntimeError(f"OpenRouter HTTP {resp.status_code}: {snippet}")
+        raise http_error("OpenRouter", resp.status_code, resp.text)
 
     try:
         data = resp.json()
     except Exception as e:
         snippet = (resp.text or "")[:200].replace("\n", " ")
-        raise RuntimeError(f"OpenRouter returned non-JSON response: {snippet}") from e
+        raise LlmResponseError(f"OpenRouter returned non-JSON response: {snippet}", "OpenRouter") from e
 
     models = []
     try:
@@ -6,6 +6,7 @@ from __future__ import annotations
 import json
 from typing import Any, Callable, Dict, Optional, cast
 import os
+import re
 from pathlib import Path
 from typing import Any, Callable, Dict, Optional, cast
 
@@ -294,6 +295,17 @@ def _mock_ask(prompt: str) -> str:
     return "(mock) I suggest running 'bt' and 'info locals'."
 
 
+def _mock_stream(prompt: str, on_chunk: Callable[[str], None]) -> str:
+    # Word-sized chunks so streaming consumers can be exercised offline.
+    text = _mock_ask(prompt)
+    for piece in re.findall(r"\S+\s*", text):
+        on_chunk(piece)
+    return text
+
+
+setattr(_mock_ask, "stream", _mock_stream)
+
+
 def _provider_defaults(meta: Dict[str, Any]) -> Dict[str, Any]:
     defaults: Dict[str, Any] = {}
     for key in ("base_url", "path", "default_model", "headers", "model"):
@@ -0,0 +1,160 @@
+"""Retry transient LLM failures with exponential backoff.
+
+Busy endpoints answer 429 or 503, or stop answering for a while, and one such
+failure should not throw away a debugger run that already happened.
+`RetryPolicy` repeats a `complete` or `stream` call on rate limits, server
+overload, gateway errors, and timeouts, waiting ``base_delay * 2**n`` seconds
+(with jitter, capped at ``max_delay``) between attempts. Request errors (400,
+401, 404, ...) and misconfiguration fail at once, since they would fail the
+same way again. A stream is retried only if it failed before its first
+fragment; after that the caller has shown text a retry would repeat.
+
+When the endpoint sends ``Retry-After`` (`LlmHttpError.retry_after`), that
+wait replaces the computed backoff. It is still capped at ``max_delay``, so a
+server asking for an hour does not stall a CI job for one.
+
+The error that finally escapes has ``attempts`` set, and its message says how
+many attempts were made.
+
+Given a `UsageMeter`, `complete` and `stream` check its ``max_cost`` before
+every attempt, not just the first, and record each answer they get. That
+includes the text a stream sent before it broke: those tokens were generated
+and are paid for.
+"""
+from __future__ import annotations
+
+import logging
+import random
+import time
+from dataclasses import dataclass, field
+from typing import TYPE_CHECKING, Any, Callable, Mapping, Optional, Tuple, TypeVar
+
+from .base import Completion, LlmProvider, Prompt
+from .errors import LlmError, LlmHttpError, LlmTransportError
+from .sse import ChunkCallback, StreamError
+
+if TYPE_CHECKING:
+    from .usage import UsageMeter
+
+log = logging.getLogger(__name__)
+
+DEFAULT_MAX_RETRIES = 3
+DEFAULT_BASE_DELAY = 1.0
+DEFAULT_MAX_DELAY = 30.0
+# Request timeout, rate limit, and the server-side failures a later attempt can clear.
+RETRYABLE_STATUSES = frozenset({408, 429, 500, 502, 503, 504})
+
+T = TypeVar("T")
+
+
+def is_retryable(exc: BaseException) -> bool:
+    if isinstance(exc, StreamError):
+        return False
+    if isinstance(exc, LlmHttpError):
+        return exc.status in RETRYABLE_STATUSES
+    return isinstance(exc, LlmTransportError) and exc.timed_out
+
+
+@dataclass
+class RetryPolicy:
+    """How often and how patiently to repeat a failed LLM call; ``max_retries=0`` disables retries."""
+
+    max_retries: int = DEFAULT_MAX_RETRIES
+    base_delay: float = DEFAULT_BASE_DELAY
+    max_delay: float = DEFAULT_MAX_DELAY
+    sleep: Callable[[float], None] = field(default=time.sleep, repr=False)
+    jitter: Callable[[], float] = field(default=random.random, repr=False)
+
+    @classmethod
+    def from_config(cls, config: Optional[Mapping[str, Any]]) -> "RetryPolicy":
+        """Policy from the ``max_retries``, ``base_delay``, and ``max_delay`` config keys."""
+        config = config or {}
+        raw_retries, raw_delay, raw_max = config.get("max_retries"), config.get("base_delay"), config.get("max_delay")
+        try:
+            retries = int(raw_retries) if raw_retries not in (None, "") else DEFAULT_MAX_RETRIES
+        except (TypeError, ValueError) as exc:
+            raise ValueError(f"max_retries must be an integer, got {raw_retries!r}") from exc
+        try:
+            delay = float(raw_delay) if raw_delay not in (None, "") else DEFAULT_BASE_DELAY
+        except (TypeError, ValueError) as exc:
+            raise ValueError(f"base_delay must be a number of seconds, got {raw_delay!r}") from exc
+        try:
+            cap = float(raw_max) if raw_max not in (None, "") else DEFAULT_MAX_DELAY
+        except (TypeError, ValueError) as exc:
+            raise ValueError(f"max_delay must be a number of seconds, got {raw_max!r}") from exc
+        if retries < 0 or delay < 0 or cap < 0:
+            raise ValueError("max_retries, base_delay, and max_delay must not be negative")
+        return cls(max_retries=retries, base_delay=delay, max_delay=cap)
+
+    def delay(self, retry: int) -> float:
+        """Seconds to wait before retry number ``retry`` (1-based): half fixed, half random."""
+        backoff = min(self.base_delay * 2 ** (retry - 1), self.max_delay)
+        return backoff / 2 + backoff / 2 * self.jitter()
+
+    def call(self, fn: Callable[[], T], what: str = "LLM call") -> Tuple[T, int]:
+        """``fn()`` and the number of attempts it took; the last error is raised with ``attempts`` set."""
+        attempt = 0
+        while True:
+            attempt += 1
+            try:
+                return fn(), attempt
+            except LlmError as exc:
+                if attempt > self.max_retries or not is_retryable(exc):
+                    exc.attempts = attempt
+                    raise
+                hinted = exc.retry_after if isinstance(exc, LlmHttpError) else None
+                wait = self.delay(attempt) if hinted is None else min(hinted, self.max_delay)
+                log.info("%s attempt %d failed, retrying in %.1f s: %s", what, attempt, wait, exc)
+                self.sleep(wait)
+
+    def complete(
+        self, provider: LlmProvider, prompt: Prompt, meter: Optional["UsageMeter"] = None
+    ) -> Tuple[Completion, int]:
+        def attempt() -> Completion:
+            if meter is not None:
+                meter.check(provider, prompt)
+            completion = provider.complete(prompt)
+            if meter is not None:
+                meter.record(provider, prompt, completion)
+            return completion
+
+        return self.call(attempt, f"{provider.name} request")
+
+    def stream(
+        self, provider: LlmProvider, prompt: Prompt, on_chunk: ChunkCallback, meter: Optional["UsageMeter"] = None
+    ) -> Tuple[Completion, int]:
+        delivered: list[str] = []
+
+        def forward(chunk: str) -> None:
+            delivered.append(chunk)
+            on_chunk(chunk)
+
+        def attempt() -> Completion:
+            if meter is not None:
+                meter.check(provider, prompt)
+            try:
+                completion = provider.stream(prompt, forward)
+            except LlmError as exc:
+                if not delivered:
+                    raise
+                if meter is not None:
+                    meter.record(provider, prompt, Completion("".join(delivered), provider.name))
+                if isinstance(exc, StreamError):
+                    raise
+                # Text is on screen already; a retry would print it twice.
+                raise StreamError(str(exc), "".join(delivered), provider.name) from exc
+            if meter is not None:
+                meter.record(provider, prompt, completion)
+            return completion
+
+        return self.call(attempt, f"{provider.name} stream")
+
+
+__all__ = [
+    "DEFAULT_BASE_DELAY",
+    "DEFAULT_MAX_DELAY",
+    "DEFAULT_MAX_RETRIES",
+    "RETRYABLE_STATUSES",
+    "RetryPolicy",
+    "is_retryable",
+]
@@ -0,0 +1,108 @@
+"""Server-sent events parsing for streamed chat completions.
+
+OpenAI-compatible endpoints (and OpenRouter) answer ``"stream": true`` requests
+with ``data: {json}`` lines, each carrying a ``choices[0].delta.content``
+fragment, terminated by ``data: [DONE]``. `stream_chat_completion` feeds each
+fragment to a callback as it arrives and returns the full text.
+"""
+from __future__ import annotations
+
+import json
+from typing import Any, Callable, Dict, Iterable, Optional
+
+from .errors import LlmError
+
+ChunkCallback = Callable[[str], None]
+
+
+class StreamError(LlmError):
+    """A streamed completion failed after it had started.
+
+    ``partial`` holds the text already passed to the callback, so callers can
+    keep what was shown instead of pretending the answer was complete.
+    """
+
+    def __init__(self, message: str, partial: str = "", provider: str = "") -> None:
+        super().__init__(message, provider)
+        self.partial = partial
+
+
+def _delta_text(event: Dict[str, Any]) -> str:
+    try:
+        choice = event["choices"][0]
+    except (KeyError, IndexError, TypeError):
+        return ""
+    delta = choice.get("delta") or choice.get("message") or {}
+    content = delta.get("content") if isinstance(delta, dict) else None
+    return content if isinstance(content, str) else ""
+
+
+def _finished(event: Dict[str, Any]) -> bool:
+    try:
+        return bool(event["choices"][0].get("finish_reason"))
+    except (KeyError, IndexError, TypeError, AttributeError):
+        return False
+
+
+def stream_chat_completion(
+    lines: Iterable[Any],
+    on_chunk: ChunkCallback,
+    provider: str,
+    usage_out: Optional[Dict[str, Any]] = None,
+) -> str:
+    """Consume SSE ``lines`` (str or bytes), calling ``on_chunk`` per fragment.
+
+    Raises `StreamError` if the connection drops, the server sends an error
+    event, or the stream ends without ``[DONE]`` or a ``finish_reason``.
+    """
+    parts: list[str] = []
+    done = False
+    try:
+        for raw in lines:
+            line = raw.decode("utf-8", errors="replace") if isinstance(raw, bytes) else str(raw or "")
+            line = line.strip()
+            # Blank lines separate events; ":" lines are keep-alive comments.
+            if not line or line.startswith(":") or not line.startswith("data:"):
+                continue
+            payload = line[len("data:") :].strip()
+            if payload == "[DONE]":
+                done = True
+                break
+            try:
+                event = json.loads(payload)
+            except ValueError:
+                continue
+            if not isinstance(event, dict):
+                continue
+            if event.get("error"):
+                err = event["error"]
+                message = err.get("message") if isinstance(err, dict) else str(err)
+                raise StreamError(f"{provider} stream error: {message}", "".join(parts), provider)
+            if usage_out is not None and isinstance(event.get("usage"), dict):
+                usage_out.update(event["usage"])
+            text = _delta_text(event)
+            if text:
+                parts.append(text)
+                on_chunk(text)
+            if _finished(event):
+                done = True
+    except StreamError:
+        raise
+    except Exception as exc:
+        # Network drops surface here (requests raises from inside iter_lines).
+        partial = "".join(parts)
+        if done:
+            # The model already sent finish_reason; only the trailer was lost.
+            return partial
+        raise StreamError(f"{provider} stream interrupted after {len(partial)} chars: {exc}", partial, provider) from exc
+    partial = "".join(parts)
+    if not done:
+        raise StreamError(f"{provider} stream ended before the completion finished", partial, provider)
+    return partial
+
+
+__all__ = [
+    "ChunkCallback",
+    "StreamError",
+    "stream_chat_completion",
+]
@@ -0,0 +1,283 @@
+"""Token and cost accounting for LLM calls.
+
+Each explanation and fix call is counted into a `Usage`: prompt and
+completion tokens, the number of calls, and an estimated cost in USD. An
+explanation from the cache counts as ``cached``, with no tokens. When
+the provider reports usage (the ``usage`` object of an OpenAI-compatible
+answer), its numbers are used. When it does not, as many servers do not for
+streams, the tokens are counted locally and the usage is marked
+``estimated``. Local counts use tiktoken's ``cl100k_base`` encoding when
+tiktoken is installed, and four characters per token otherwise.
+
+The cost is the provider's own figure when it sends one (OpenRouter does).
+Otherwise it is the tokens times the model's price from the price table.
+Prices are USD per million tokens, input then output. Each is keyed by a
+glob matched against ``provider/model`` or the bare model name::
+
+    [llm]
+    prices = ["gpt-4o-mini=0.15/0.60", "openrouter/anthropic/*=3/15"]
+
+Entries from the settings are tried before `DEFAULT_PRICES`. The defaults
+only know that local servers cost nothing, because published prices change
+too often to ship. A model without a price has an unknown cost, not a zero
+one.
+
+`UsageMeter.check` is the ``max_cost`` guard (``--max-cost``). Before a
+prompt is sent, it prices the prompt's tokens plus a full ``max_tokens``
+answer and adds what the analysis has already spent. If that is over the
+budget, it raises `LlmBudgetError` instead of sending. It also raises when
+the model has no price to check against.
+"""
+from __future__ import annotations
+
+import fnmatch
+import functools
+import json
+from dataclasses import dataclass, field
+from typing import TYPE_CHECKING, Any, Dict, Iterable, List, Mapping, Optional, Union
+
+from .errors import LlmBudgetError
+
+if TYPE_CHECKING:
+    from .base import Completion, LlmProvider, Prompt
+
+CHARS_PER_TOKEN = 4
+
+
+@dataclass(frozen=True)
+class ModelPrice:
+    pattern: str
+    # USD per million prompt (input) and completion (output) tokens.
+    input: float
+    output: float
+
+    def matches(self, identity: str) -> bool:
+        """Whether ``identity`` (``provider/model``) or its model part matches `pattern`."""
+        model = identity.split("/", 1)[1] if "/" in identity else identity
+        return fnmatch.fnmatchcase(identity, self.pattern) or fnmatch.fnmatchcase(model, self.pattern)
+
+    def cost(self, prompt_tokens: int, completion_tokens: int) -> float:
+        return (prompt_tokens * self.input + completion_tokens * self.output) / 1_000_000
+
+
+# Local servers only; a provider's published prices belong in llm.prices.
+DEFAULT_PRICES = (
+    ModelPrice("ollama", 0.0, 0.0),
+    ModelPrice("ollama/*", 0.0, 0.0),
+    ModelPrice("mock-local", 0.0, 0.0),
+    ModelPrice("mock-local/*", 0.0, 0.0),
+)
+
+
+def parse_price(entry: str) -> ModelPrice:
+    """``"gpt-4o-mini=0.15/0.60"`` -> the price of that model, in USD per million input/output tokens."""
+    pattern, _, amounts = entry.partition("=")
+    input_price, _, output_price = amounts.partition("/")
+    try:
+        price = ModelPrice(pattern.strip(), float(input_price), float(output_price))
+    except ValueError:
+        price = None
+    if price is None or not price.pattern or price.input < 0 or price.output < 0:
+        raise ValueError(f"malformed price {entry!r}: expected MODEL=INPUT/OUTPUT, in USD per million tokens")
+    return price
+
+
+def price_table(config: Optional[Mapping[str, Any]]) -> List[ModelPrice]:
+    """The ``prices`` config key (a list, a JSON list, or comma-separated entries) followed by `DEFAULT_PRICES`."""
+    raw: Union[str, Iterable[str], None] = (config or {}).get("prices")
+    if isinstance(raw, str):
+        try:
+            raw = json.loads(raw) if raw.strip().startswith("[") else raw.split(",")
+        except ValueError as exc:
+            raise ValueError(f"malformed prices {raw!r}: {exc}") from exc
+    entries = [str(e).strip() for e in raw or () if str(e).strip()]
+    return [parse_price(e) for e in entries] + list(DEFAULT_PRICES)
+
+
+def price_for(identity: str, prices: Iterable[ModelPrice]) -> Optional[ModelPrice]:
+    """The first entry of ``prices`` that matches ``identity``, or None."""
+    return next((p for p in prices if p.matches(identity)), None)
+
+
+@functools.lru_cache(maxsize=1)
+def _encoding() -> Any:
+    try:
+        import tiktoken
+
+        return tiktoken.get_encoding("cl100k_base")
+    except Exception:
+        # Not installed, or its encoding files cannot be fetched: fall back to the estimate.
+        return None
+
+
+def count_tokens(text: str) -> int:
+    """Tokens in ``text``: exact for OpenAI-style tokenizers with tiktoken, roughly otherwise."""
+    encoding = _encoding()
+    if encoding is not None:
+        return len(encoding.encode(text))
+    return (len(text) + CHARS_PER_TOKEN - 1) // CHARS_PER_TOKEN
+
+
+def prompt_tokens(prompt: "Prompt") -> int:
+    return sum(count_tokens(m["content"]) for m in prompt.messages())
+
+
+@dataclass
+class Usage:
+    prompt_tokens: int = 0
+    completion_tokens: int = 0
+    # Estimated USD; None once a call went to a model with no price and no cost reported.
+    cost: Optional[float] = 0.0
+    calls: int = 0
+    # Explanations answered from the cache: no call, no tokens, no cost.
+    cached: int = 0
+    # Some token counts were counted locally because the provider did not report them.
+    estimated: bool = False
+    # ``provider/model`` of each model called, in order of first use.
+    models: List[str] = field(default_factory=list)
+
+    @property
+    def total_tokens(self) -> int:
+        return self.prompt_tokens + self.completion_tokens
+
+    def add(self, other: "Usage") -> None:
+        self.prompt_tokens += other.prompt_tokens
+        self.completion_tokens += other.completion_tokens
+        self.cost = None if self.cost is None or other.cost is None else self.cost + other.cost
+        self.calls += other.calls
+        self.cached += other.cached
+        self.estimated = self.estimated or other.estimated
+        self.models += [m for m in other.models if m not in self.models]
+
+    def describe(self) -> str:
+        """One line, e.g. ``LLM usage: 812 prompt + 143 completion tokens, ~$0.0002 (openai/gpt-4o-mini)``."""
+        text = f"LLM usage: {self.prompt_tokens} prompt + {self.completion_tokens} completion tokens"
+        if self.calls > 1:
+            text += f" in {self.calls} calls"
+        if self.cost is None:
+            text += ", cost unknown (no price in llm.prices)"
+        else:
+            text += f", ~${self.cost:.4f}" if self.cost else ", $0"
+        if self.models:
+            text += f" ({', '.join(self.models)})"
+        if self.cached:
+            hits = "explanation" if self.cached == 1 else f"{self.cached} explanations"
+            text += f"; {hits} from the cache"
+        if self.estimated:
+            text += "; token counts estimated"
+        return text
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {
+            "prompt_tokens": self.prompt_tokens,
+            "completion_tokens": self.completion_tokens,
+            "total_tokens": self.total_tokens,
+            "cost": self.cost,
+            "calls": self.calls,
+            "cached": self.cached,
+            "estimated": self.estimated,
+            "models": list(self.models),
+        }
+
+    @classmethod
+    def from_dict(cls, data: Mapping[str, Any]) -> "Usage":
+        return cls(
+            prompt_tokens=int(data.get("prompt_tokens") or 0),
+            completion_tokens=int(data.get("completion_tokens") or 0),
+            cost=None if data.get("cost") is None else float(data["cost"]),
+            calls=int(data.get("calls") or 0),
+            cached=int(data.get("cached") or 0),
+            estimated=bool(data.get("estimated")),
+            models=[str(m) for m in data.get("models") or ()],
+        )
+
+
+def _identity(provider: str, model: str) -> str:
+    return f"{provider}/{model}" if model else provider
+
+
+def _reported(usage: Mapping[str, Any], key: str, kind: type) -> Any:
+    try:
+        return None if usage.get(key) is None else kind(usage[key])
+    except (TypeError, ValueError):
+        return None
+
+
+@dataclass
+class UsageMeter:
+    """Counts calls into ``usage`` and refuses the ones that would take it over ``max_cost`` (USD)."""
+
+    prices: List[ModelPrice] = field(default_factory=lambda: list(DEFAULT_PRICES))
+    max_cost: Optional[float] = None
+    usage: Usage = field(default_factory=Usage)
+
+    @classmethod
+    def from_config(cls, config: Optional[Mapping[str, Any]], usage: Optional[Usage] = None) -> "UsageMeter":
+        """A meter from the ``prices`` and ``max_cost`` config keys, adding to ``usage`` when given."""
+        config = config or {}
+        raw = config.get("max_cost")
+        try:
+            max_cost = float(raw) if raw not in (None, "") else None
+        except (TypeError, ValueError) as exc:
+            raise ValueError(f"max_cost must be a number of USD, got {raw!r}") from exc
+        if max_cost is not None and max_cost < 0:
+            raise ValueError("max_cost must not be negative")
+        return cls(prices=price_table(config), max_cost=max_cost, usage=usage if usage is not None else Usage())
+
+    def estimate(self, llm: "LlmProvider", prompt: "Prompt") -> Optional[float]:
+        """The most sending ``prompt`` can cost: its tokens and a full ``max_tokens`` answer; None without a price."""
+        price = price_for(_identity(llm.name, getattr(llm, "model", "")), self.prices)
+        return None if price is None else price.cost(prompt_tokens(prompt), prompt.max_tokens)
+
+    def check(self, llm: "LlmProvider", prompt: "Prompt") -> None:
+        """Raise `LlmBudgetError` rather than let ``prompt`` take the spend over ``max_cost``."""
+        if self.max_cost is None:
+            return
+        identity = _identity(llm.name, getattr(llm, "model", ""))
+        estimate = self.estimate(llm, prompt)
+        if estimate is None:
+            raise LlmBudgetError(
+                f"not sending the prompt: {identity} has no price in llm.prices to check max_cost against", llm.name
+            )
+        spent = self.usage.cost or 0.0
+        if spent + estimate > self.max_cost:
+            already = f" on top of ${spent:.4f} spent" if spent else ""
+            raise LlmBudgetError(
+                f"not sending the prompt: {prompt_tokens(prompt)} prompt tokens and a {prompt.max_tokens}-token answer "
+                f"to {identity} could cost ${estimate:.4f}{already}, over max_cost ${self.max_cost:.4f}",
+                llm.name,
+                estimate,
+            )
+
+    def record(self, llm: "LlmProvider", prompt: "Prompt", completion: "Completion") -> Usage:
+        """Add the call that answered ``prompt`` with ``completion`` to ``usage``; returns that call's own usage."""
+        identity = _identity(completion.provider or llm.name, completion.model or getattr(llm, "model", ""))
+        reported = completion.usage or {}
+        prompt_count = _reported(reported, "prompt_tokens", int)
+        completion_count = _reported(reported, "completion_tokens", int)
+        call = Usage(
+            prompt_tokens=prompt_tokens(prompt) if prompt_count is None else prompt_count,
+            completion_tokens=count_tokens(completion.text) if completion_count is None else completion_count,
+            calls=1,
+            estimated=prompt_count is None or completion_count is None,
+            models=[identity],
+        )
+        call.cost = _reported(reported, "cost", float)
+        if call.cost is None:
+            price = price_for(identity, self.prices)
+            call.cost = None if price is None else price.cost(call.prompt_tokens, call.completion_tokens)
+        self.usage.add(call)
+        return call
+
+
+__all__ = [
+    "DEFAULT_PRICES",
+    "ModelPrice",
+    "Usage",
+    "UsageMeter",
+    "count_tokens",
+    "parse_price",
+    "price_for",
+    "price_table",
+    "prompt_tokens",
+]
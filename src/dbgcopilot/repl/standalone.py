@@ -6,6 +6,7 @@ Phase 2a: supports selecting GDB via a subprocess backend. LLDB will be added ne
 from __future__ import annotations
 
 import atexit
+import importlib
 import shutil
 import sys
 import uuid
@@ -17,7 +18,7 @@ try:
 except ImportError:  # pragma: no cover - optional feature
     readline = None
 
-from dbgcopilot.core.orchestrator import CopilotOrchestrator
+from dbgcopilot.core.orchestrator import CopilotOrchestrator, _track_fault
 from dbgcopilot.core.state import SessionState, Attempt, resolve_auto_round_limit
 from dbgcopilot.llm import params as _llm_params
 from dbgcopilot.utils.io import color_text
@@ -30,6 +31,18 @@ BACKEND: Optional[Any] = None
 ORCH: Optional[CopilotOrchestrator] = None
 _READLINE_CONFIGURED = False
 
+# `dbgcopilot <name> ...` runs the entry point in dbgcopilot.cli instead of the REPL.
+_SUBCOMMANDS = {
+    "batch": "batch_main",
+    "check": "check_main",
+    "diff": "diff_main",
+    "history": "history_main",
+    "last": "last_main",
+    "run": "run_main",
+    "serve": "serve_main",
+    "watch": "watch_main",
+}
+
 
 def _validate_path(path_input: str) -> tuple[str, Optional[str]]:
     candidate = Path(path_input).expanduser()
@@ -109,6 +122,56 @@ def _echo(line: str, colors: bool = True) -> None:
         pass
 
 
+def _write_chunk(chunk: str) -> None:
+    try:
+        sys.stdout.write(chunk)
+        sys.stdout.flush()
+    except Exception:
+        pass
+
+
+def _explain_crash(dry_run: bool = False) -> None:
+    """Stream an explanation of the last captured crash straight to the terminal.
+
+    With ``dry_run``, list what redaction would hide from the prompt and send nothing.
+    """
+    from dbgcopilot.analysis import Redactor, explain_stream
+    from dbgcopilot.llm.sse import StreamError
+
+    s = _ensure_session()
+    if s.crash_context is None:
+        _echo("No crash captured yet. Run the program until it stops on a signal, then /explain.")
+        return
+    if dry_run:
+        try:
+            found = Redactor.from_config(s.config).dry_run(s.crash_context)
+        except ValueError as e:
+            _echo(f"Error: {e}")
+            return
+        if not found:
+            _echo("Nothing to redact; the prompt would be sent as captured.")
+        for item in found:
+            _echo(item.describe())
+        return
+    provider = s.selected_provider or s.config.get("llm_provider")
+    if not provider:
+        _echo("No LLM provider selected. Use /llm use <name> first.")
+        return
+    try:
+        text = explain_stream(s.crash_context, _write_chunk, provider, s.config)
+    except StreamError as e:
+        _echo("")
+        _echo(f"Error: explanation incomplete: {e}")
+        if e.partial:
+            s.chatlog.append(f"Assistant: {e.partial.strip()} [incomplete]")
+        return
+    except Exception as e:
+        _echo(f"Error: {e}")
+        return
+    _echo("")
+    s.chatlog.append(f"Assistant: {text.strip()}")
+
+
 def _print_help() -> str:
     return "\n".join(
         [
@@ -129,6 +192,8 @@ def _print_help() -> str:
             "  /auto [on|off|toggle]      Control auto-approve command execution",
             "  /prompts show|reload       Show or reload prompt config",
             "  /exec <cmd>                Run a debugger command (after /use)",
+            "  /explain                   Stream an explanation of the last captured crash",
+            "  /explain --dry-run         Show what would be redacted from the crash prompt",
             "  /llm list                  List configured LLM providers",
             "  /llm use <name>            Select provider for this session",
             "  /llm models [provider]     List models (provider must support discovery)",
@@ -855,6 +920,9 @@ def _select_lldb() -> str:
 
 
 def main(argv: Optional[list[str]] = None) -> int:
+    args = sys.argv[1:] if argv is None else argv
+    if args[:1] and args[0] in _SUBCOMMANDS:
+        return getattr(importlib.import_module("dbgcopilot.cli"), _SUBCOMMANDS[args[0]])(args[1:])
     _configure_readline_history()
     warn_missing_debugger_tools("dbgcopilot")
     _ensure_session()
@@ -1026,6 +1094,8 @@ def main(argv: Optional[list[str]] = None) -> int:
                     s.attempts.append(Attempt(cmd=arg, output_snippet=(out or "")[:160]))
                     if out:
                         _echo(out)
+                        # Keep the crash context current so /explain sees manual runs too.
+                        _track_fault(s, out)
                 continue
             if verb == "/colors":
                 choice = (arg or "").strip().lower()
@@ -1052,6 +1122,9 @@ def main(argv: Optional[list[str]] = None) -> int:
             if verb == "/llm":
                 _echo(_handle_llm(arg))
                 continue
+            if verb == "/explain":
+                _explain_crash(dry_run=arg.strip() == "--dry-run")
+                continue
             _echo("Unknown slash command. Try /help")
             continue
 
@@ -0,0 +1,420 @@
+"""``dbgcopilot serve``: the analysis as a JSON-RPC service for editors.
+
+An editor extension that starts ``dbgcopilot-analyze`` for every crash pays
+for interpreter start-up and provider setup each time, and cannot ask a
+follow-up about a report it already has. `Server` stays running and speaks
+JSON-RPC 2.0 over stdio. Messages are framed as in the Language Server
+Protocol: a ``Content-Length`` header, a blank line, then the JSON body. That
+way ``vscode-jsonrpc``'s stream reader and writer work as they are.
+
+Methods (params and results are camelCase, as JSON-RPC clients expect):
+
+- ``initialize`` -> ``{"name", "version", "protocolVersion", "methods"}``
+- ``analyzeDump`` ``{"path", "binary"?, "noLlm"?}`` -> `AnalyzeResult`
+- ``analyzePid`` ``{"pid", "binary"?, "noLlm"?}`` -> `AnalyzeResult`
+- ``askFollowup`` ``{"sessionId", "question"}`` -> `FollowupResult`; a
+  question starting with ``!`` runs a debugger command, as with ``--interactive``
+- ``closeSession`` ``{"sessionId"}`` -> null; the session's report is dropped
+- ``shutdown`` -> null; the ``exit`` notification, or the end of input, then
+  stops the server
+
+Each analysis opens a session that follow-ups name by ``sessionId``. A
+server runs as long as the editor does, so it keeps at most `MAX_SESSIONS`:
+a new one closes the oldest, as ``closeSession`` would. The
+explanation and the answers stream as ``explanationChunk`` and
+``followupChunk`` notifications (`ChunkNotification`). They arrive before
+the response, which carries the whole text. The report is
+`AnalysisReport.to_dict`, the ``--format json`` layout.
+
+Errors use the JSON-RPC codes and this server's own: `ANALYSIS_FAILED` (the
+capture failed), `UNKNOWN_SESSION`, `LLM_FAILED` (a follow-up got no
+answer), and `COMMAND_FAILED` (a ``!`` command could not run). A failed
+explanation is not an error. The report says why in ``explanation_error``,
+as the CLI's does.
+"""
+from __future__ import annotations
+
+import dataclasses
+import json
+import logging
+from dataclasses import dataclass
+from typing import TYPE_CHECKING, Any, BinaryIO, Callable, Dict, Mapping, Optional
+
+from dbgcopilot import __version__
+
+if TYPE_CHECKING:
+    from dbgcopilot.analysis.api import AnalysisReport, AnalyzeRequest
+    from dbgcopilot.analysis.conversation import ConversationState
+    from dbgcopilot.llm.base import LlmProvider
+
+log = logging.getLogger(__name__)
+
+# Bump when a method, param, or result changes incompatibly.
+PROTOCOL_VERSION = 1
+# Sessions kept open; past this the oldest is closed.
+MAX_SESSIONS = 32
+
+# JSON-RPC 2.0 error codes, then this server's own from the range JSON-RPC leaves to servers.
+PARSE_ERROR = -32700
+INVALID_REQUEST = -32600
+METHOD_NOT_FOUND = -32601
+INVALID_PARAMS = -32602
+INTERNAL_ERROR = -32603
+ANALYSIS_FAILED = -32001
+UNKNOWN_SESSION = -32002
+LLM_FAILED = -32003
+COMMAND_FAILED = -32004
+
+
+class RpcError(Exception):
+    """A request failed; sent back as the response's ``error`` object."""
+
+    def __init__(self, code: int, message: str) -> None:
+        super().__init__(message)
+        self.code = code
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {"code": self.code, "message": str(self)}
+
+
+_REQUIRED = object()
+
+
+def _param(params: Mapping[str, Any], name: str, kind: type, default: Any = _REQUIRED) -> Any:
+    if name not in params or params[name] is None:
+        if default is _REQUIRED:
+            raise RpcError(INVALID_PARAMS, f"missing param {name!r}")
+        return default
+    value = params[name]
+    # JSON true is not a pid.
+    if not isinstance(value, kind) or (kind is int and isinstance(value, bool)):
+        raise RpcError(INVALID_PARAMS, f"param {name!r} must be {kind.__name__}, got {value!r}")
+    return value
+
+
+@dataclass
+class AnalyzeDumpParams:
+    path: str
+    # The program that wrote the core, for its symbols; minidumps do without.
+    binary: str = ""
+    no_llm: bool = False
+
+    @classmethod
+    def from_params(cls, params: Mapping[str, Any]) -> "AnalyzeDumpParams":
+        return cls(_param(params, "path", str), _param(params, "binary", str, ""), _param(params, "noLlm", bool, False))
+
+
+@dataclass
+class AnalyzePidParams:
+    pid: int
+    binary: str = ""
+    no_llm: bool = False
+
+    @classmethod
+    def from_params(cls, params: Mapping[str, Any]) -> "AnalyzePidParams":
+        return cls(_param(params, "pid", int), _param(params, "binary", str, ""), _param(params, "noLlm", bool, False))
+
+
+@dataclass
+class SessionParams:
+    session_id: int
+
+    @classmethod
+    def from_params(cls, params: Mapping[str, Any]) -> "SessionParams":
+        return cls(_param(params, "sessionId", int))
+
+
+@dataclass
+class FollowupParams:
+    session_id: int
+    question: str
+
+    @classmethod
+    def from_params(cls, params: Mapping[str, Any]) -> "FollowupParams":
+        return cls(_param(params, "sessionId", int), _param(params, "question", str))
+
+
+@dataclass
+class AnalyzeResult:
+    session_id: int
+    # `AnalysisReport.to_dict`.
+    report: Dict[str, Any]
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {"sessionId": self.session_id, "report": self.report}
+
+
+@dataclass
+class FollowupResult:
+    session_id: int
+    # The model's answer, or a ``!`` command's output.
+    answer: str
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {"sessionId": self.session_id, "answer": self.answer}
+
+
+@dataclass
+class ChunkNotification:
+    # "explanationChunk" or "followupChunk".
+    method: str
+    session_id: int
+    text: str
+
+    def to_message(self) -> Dict[str, Any]:
+        return {"jsonrpc": "2.0", "method": self.method, "params": {"sessionId": self.session_id, "text": self.text}}
+
+
+def read_message(stream: BinaryIO) -> Optional[Any]:
+    """The next message body from ``stream``, parsed; None at the end of input.
+
+    A body that is not JSON raises `RpcError` (`PARSE_ERROR`) after it has
+    been read, so the next message can still be read. So does a header block
+    without a usable ``Content-Length``, whose body cannot be found.
+    """
+    length: Optional[int] = None
+    headers = False
+    malformed: Optional[str] = None
+    while True:
+        line = stream.readline()
+        if not line:
+            return None
+        line = line.strip()
+        if not line:
+            if not headers:
+                continue
+            break
+        headers = True
+        name, _, value = line.decode("ascii", errors="replace").partition(":")
+        if name.strip().lower() == "content-length":
+            try:
+                length = int(value.strip())
+            except ValueError:
+                malformed = f"malformed Content-Length {value.strip()!r}"
+            else:
+                # read(-1) would wait for the end of input.
+                malformed = f"negative Content-Length {length}" if length < 0 else None
+    if malformed is not None or length is None:
+        raise RpcError(PARSE_ERROR, malformed or "headers without a Content-Length")
+    body = stream.read(length)
+    if len(body) < length:
+        return None
+    try:
+        return json.loads(body.decode("utf-8"))
+    except ValueError as exc:
+        raise RpcError(PARSE_ERROR, f"not JSON: {exc}") from None
+
+
+def write_message(stream: BinaryIO, message: Mapping[str, Any]) -> None:
+    body = json.dumps(message).encode("utf-8")
+    stream.write(b"Content-Length: %d\r\n\r\n" % len(body) + body)
+    stream.flush()
+
+
+@dataclass
+class Session:
+    """One analyzed report and its follow-up conversation."""
+
+    request: "AnalyzeRequest"
+    conversation: "ConversationState"
+    run_command: Optional[Callable[[str], str]] = None
+
+
+class Server:
+    """Answers requests read from one stream on another, analyzing with ``request`` as the template.
+
+    ``on_report`` sees every report analyzed (the CLI saves them to history),
+    and ``command_runner`` opens the debugger for a session's ``!`` commands.
+    """
+
+    def __init__(
+        self,
+        request: "AnalyzeRequest",
+        output: BinaryIO,
+        on_report: Optional[Callable[["AnalysisReport", "AnalyzeRequest"], None]] = None,
+        command_runner: Optional[Callable[["AnalyzeRequest"], Callable[[str], str]]] = None,
+    ) -> None:
+        self.request = request
+        self.output = output
+        self.on_report = on_report
+        self.command_runner = command_runner
+        self.sessions: Dict[int, Session] = {}
+        self._next_session = 1
+        self._llm: Any = None
+        self._shut_down = False
+        self._exited = False
+        self._methods: Dict[str, Callable[[Mapping[str, Any]], Any]] = {
+            "initialize": self.initialize,
+            "analyzeDump": self.analyze_dump,
+            "analyzePid": self.analyze_pid,
+            "askFollowup": self.ask_followup,
+            "closeSession": self.close_session,
+            "shutdown": self.shutdown,
+        }
+
+    def serve(self, stream: BinaryIO) -> int:
+        """Answer requests from ``stream`` until ``exit`` or the end of input.
+
+        Returns 0 when ``shutdown`` came first and 1 otherwise, as language servers do.
+        """
+        while not self._exited:
+            try:
+                message = read_message(stream)
+            except RpcError as exc:
+                self._send({"jsonrpc": "2.0", "id": None, "error": exc.to_dict()})
+                continue
+            if message is None:
+                break
+            response = self.handle(message)
+            if response is not None:
+                self._send(response)
+        return 0 if self._shut_down else 1
+
+    def handle(self, message: Any) -> Optional[Dict[str, Any]]:
+        """The response to one message, or None for a notification."""
+        if not isinstance(message, dict) or message.get("jsonrpc") != "2.0" or not isinstance(
+            message.get("method"), str
+        ):
+            request_id = message.get("id") if isinstance(message, dict) else None
+            return {"jsonrpc": "2.0", "id": request_id, "error": RpcError(INVALID_REQUEST, "not a request").to_dict()}
+        method = message["method"]
+        if method == "exit":
+            self._exited = True
+            return None
+        try:
+            handler = self._methods.get(method)
+            if handler is None:
+                raise RpcError(METHOD_NOT_FOUND, f"unknown method {method!r}")
+            if self._shut_down:
+                raise RpcError(INVALID_REQUEST, "the server is shutting down")
+            params = message.get("params") or {}
+            if not isinstance(params, dict):
+                raise RpcError(INVALID_PARAMS, "params must be an object")
+            reply: Dict[str, Any] = {"result": handler(params)}
+        except RpcError as exc:
+            reply = {"error": exc.to_dict()}
+        except Exception as exc:
+            # One bad request must not take the editor's server down with it.
+            log.exception("%s failed", method)
+            reply = {"error": RpcError(INTERNAL_ERROR, f"{type(exc).__name__}: {exc}").to_dict()}
+        if "id" not in message:
+            return None
+        return {"jsonrpc": "2.0", "id": message["id"], **reply}
+
+    def initialize(self, params: Mapping[str, Any]) -> Dict[str, Any]:
+        methods = sorted([*self._methods, "exit"])
+        return {"name": "dbgcopilot", "version": __version__, "protocolVersion": PROTOCOL_VERSION, "methods": methods}
+
+    def shutdown(self, params: Mapping[str, Any]) -> None:
+        self._shut_down = True
+
+    def analyze_dump(self, params: Mapping[str, Any]) -> Dict[str, Any]:
+        p = AnalyzeDumpParams.from_params(params)
+        request = dataclasses.replace(
+            self.request, core=p.path, pid=None, binary=p.binary, no_llm=p.no_llm or self.request.no_llm
+        )
+        return self._analyze(request).to_dict()
+
+    def analyze_pid(self, params: Mapping[str, Any]) -> Dict[str, Any]:
+        p = AnalyzePidParams.from_params(params)
+        request = dataclasses.replace(
+            self.request, core=None, pid=p.pid, binary=p.binary, no_llm=p.no_llm or self.request.no_llm
+        )
+        return self._analyze(request).to_dict()
+
+    def ask_followup(self, params: Mapping[str, Any]) -> Dict[str, Any]:
+        from dbgcopilot.analysis.conversation import DEBUGGER_ESCAPE
+        from dbgcopilot.llm.errors import LlmError
+
+        p = FollowupParams.from_params(params)
+        session = self.sessions.get(p.session_id)
+        if session is None:
+            raise RpcError(UNKNOWN_SESSION, f"no session {p.session_id}")
+        if p.question.strip().startswith(DEBUGGER_ESCAPE):
+            if session.run_command is None and self.command_runner is not None:
+                session.run_command = self.command_runner(session.request)
+            try:
+                # A command needs no model.
+                answer = session.conversation.handle(p.question, self._llm, session.run_command)
+            except ValueError as exc:
+                raise RpcError(INVALID_PARAMS, str(exc)) from None
+            except RuntimeError as exc:
+                # BackendUnavailableError included: no debugger, or it could not open the target.
+                raise RpcError(COMMAND_FAILED, str(exc)) from None
+            return FollowupResult(p.session_id, answer).to_dict()
+        if session.request.no_llm or not session.request.provider:
+            raise RpcError(LLM_FAILED, "follow-up questions need a provider and no noLlm")
+
+        def notify(text: str) -> None:
+            self._send(ChunkNotification("followupChunk", p.session_id, text).to_message())
+
+        try:
+            answer = session.conversation.handle(p.question, self._provider(session), on_chunk=notify)
+        except LlmError as exc:
+            raise RpcError(LLM_FAILED, str(exc)) from None
+        return FollowupResult(p.session_id, answer).to_dict()
+
+    def close_session(self, params: Mapping[str, Any]) -> None:
+        p = SessionParams.from_params(params)
+        if self.sessions.pop(p.session_id, None) is None:
+            raise RpcError(UNKNOWN_SESSION, f"no session {p.session_id}")
+
+    def _analyze(self, request: "AnalyzeRequest") -> AnalyzeResult:
+        from dbgcopilot.analysis.api import AnalysisError, analyze
+        from dbgcopilot.analysis.conversation import ConversationState
+
+        session_id = self._next_session
+        self._next_session += 1
+
+        def notify(text: str) -> None:
+            self._send(ChunkNotification("explanationChunk", session_id, text).to_message())
+
+        try:
+            report = analyze(dataclasses.replace(request, on_explain_chunk=notify))
+        except AnalysisError as exc:
+            raise RpcError(ANALYSIS_FAILED, str(exc)) from None
+        self.sessions[session_id] = Session(request, ConversationState(report, llm_config=request.llm_config))
+        while len(self.sessions) > MAX_SESSIONS:
+            # Oldest first: the dict keeps the order sessions were opened in.
+            del self.sessions[next(iter(self.sessions))]
+        if self.on_report is not None:
+            self.on_report(report, request)
+        return AnalyzeResult(session_id, report.to_dict())
+
+    def _provider(self, session: Session) -> "LlmProvider":
+        """The provider, resolved on the first question that needs it and kept for the next."""
+        from dbgcopilot.llm.base import resolve_provider
+
+        if self._llm is None and session.request.provider and not session.request.no_llm:
+            self._llm = resolve_provider(session.request.provider, session.request.llm_config)
+        return self._llm
+
+    def _send(self, message: Mapping[str, Any]) -> None:
+        write_message(self.output, message)
+
+
+__all__ = [
+    "ANALYSIS_FAILED",
+    "AnalyzeDumpParams",
+    "AnalyzePidParams",
+    "AnalyzeResult",
+    "COMMAND_FAILED",
+    "ChunkNotification",
+    "FollowupParams",
+    "FollowupResult",
+    "INTERNAL_ERROR",
+    "INVALID_PARAMS",
+    "INVALID_REQUEST",
+    "LLM_FAILED",
+    "MAX_SESSIONS",
+    "METHOD_NOT_FOUND",
+    "PARSE_ERROR",
+    "PROTOCOL_VERSION",
+    "RpcError",
+    "Server",
+    "Session",
+    "SessionParams",
+    "UNKNOWN_SESSION",
+    "read_message",
+    "write_message",
+]
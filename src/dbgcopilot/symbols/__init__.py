@@ -0,0 +1,34 @@
+"""Address symbolization from ELF symbol tables and DWARF line tables."""
+from __future__ import annotations
+
+from .check import DebugInfoCheck, Symbolization, check_debug_info
+from .debuginfo import DEFAULT_DEBUG_DIRS, file_crc32, find_debug_file
+from .dwarf import DwarfError, LineRow, LineTable, parse_debug_line
+from .parallel import ModuleFrames, symbolize_modules
+from .resolve import SourceResolver, resolve_sources
+from .symbolizer import SourceLocation, Symbolizer, demangle
+from .sympath import SymbolFetchError, SymbolPath, SymbolServer, default_symbol_cache
+
+__all__ = [
+    "DEFAULT_DEBUG_DIRS",
+    "DebugInfoCheck",
+    "DwarfError",
+    "LineRow",
+    "LineTable",
+    "ModuleFrames",
+    "SourceLocation",
+    "SourceResolver",
+    "SymbolFetchError",
+    "SymbolPath",
+    "SymbolServer",
+    "Symbolization",
+    "Symbolizer",
+    "check_debug_info",
+    "default_symbol_cache",
+    "demangle",
+    "file_crc32",
+    "find_debug_file",
+    "parse_debug_line",
+    "resolve_sources",
+    "symbolize_modules",
+]
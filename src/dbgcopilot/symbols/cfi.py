@@ -0,0 +1,401 @@
+"""DWARF call frame information (``.eh_frame`` and ``.debug_frame``) decoder.
+
+Optimized builds drop the frame pointer, so walking ``[fp]`` / ``[fp + 8]``
+gives up or follows garbage after the first frame. The call frame information
+the compiler emits for exception handling describes every instruction
+instead: where the canonical frame address (CFA, the caller's stack pointer)
+is, and where each callee-saved register and the return address were saved.
+`CallFrameTable` reads the CIEs and FDEs of an image and `row` runs an FDE's
+rule program up to an address, giving the `UnwindRow` that recovers the
+caller's registers there.
+
+``.eh_frame`` is always loaded (it survives ``strip``); ``.debug_frame``, with
+absolute addresses and no augmentations, is used for code ``.eh_frame`` does
+not cover. CFA and register rules given as DWARF expressions are not
+evaluated: the row says so and the caller falls back to another unwinder.
+"""
+from __future__ import annotations
+
+import bisect
+from dataclasses import dataclass, field
+from enum import Enum
+from typing import Callable, Dict, List, Optional, Tuple
+
+from .dwarf import DwarfError, _Reader
+
+# Call frame instructions; the first three carry an operand in their low six bits.
+DW_CFA_advance_loc = 0x40
+DW_CFA_offset = 0x80
+DW_CFA_restore = 0xC0
+DW_CFA_nop = 0x00
+DW_CFA_set_loc = 0x01
+DW_CFA_advance_loc1 = 0x02
+DW_CFA_advance_loc2 = 0x03
+DW_CFA_advance_loc4 = 0x04
+DW_CFA_offset_extended = 0x05
+DW_CFA_restore_extended = 0x06
+DW_CFA_undefined = 0x07
+DW_CFA_same_value = 0x08
+DW_CFA_register = 0x09
+DW_CFA_remember_state = 0x0A
+DW_CFA_restore_state = 0x0B
+DW_CFA_def_cfa = 0x0C
+DW_CFA_def_cfa_register = 0x0D
+DW_CFA_def_cfa_offset = 0x0E
+DW_CFA_def_cfa_expression = 0x0F
+DW_CFA_expression = 0x10
+DW_CFA_offset_extended_sf = 0x11
+DW_CFA_def_cfa_sf = 0x12
+DW_CFA_def_cfa_offset_sf = 0x13
+DW_CFA_val_offset = 0x14
+DW_CFA_val_offset_sf = 0x15
+DW_CFA_val_expression = 0x16
+# AArch64 pointer authentication toggles the sign state of the return address;
+# it is stripped when read instead (SPARC's DW_CFA_GNU_window_save shares the number).
+DW_CFA_AARCH64_negate_ra_state = 0x2D
+DW_CFA_GNU_args_size = 0x2E
+DW_CFA_GNU_negative_offset_extended = 0x2F
+
+# Pointer encodings (DW_EH_PE_*) used by ``.eh_frame``: the format in the low nibble,
+# what it is relative to in the high one.
+DW_EH_PE_absptr = 0x00
+DW_EH_PE_uleb128 = 0x01
+DW_EH_PE_udata2 = 0x02
+DW_EH_PE_udata4 = 0x03
+DW_EH_PE_udata8 = 0x04
+DW_EH_PE_sleb128 = 0x09
+DW_EH_PE_sdata2 = 0x0A
+DW_EH_PE_sdata4 = 0x0B
+DW_EH_PE_sdata8 = 0x0C
+DW_EH_PE_pcrel = 0x10
+DW_EH_PE_indirect = 0x80
+DW_EH_PE_omit = 0xFF
+
+
+class RuleKind(str, Enum):
+    UNDEFINED = "undefined"
+    SAME_VALUE = "same_value"
+    # Saved at CFA + offset.
+    OFFSET = "offset"
+    # Is CFA + offset.
+    VAL_OFFSET = "val_offset"
+    # Is in another register.
+    REGISTER = "register"
+    # Given by a DWARF expression, which is not evaluated.
+    EXPRESSION = "expression"
+
+
+@dataclass(frozen=True)
+class Rule:
+    kind: RuleKind
+    # The offset from the CFA, or the register number for REGISTER.
+    value: int = 0
+
+    def recover(self, cfa: int, registers: Dict[int, int], read_u64: Callable[[int], Optional[int]]) -> Optional[int]:
+        """The caller's value under this rule, given the callee's ``registers``; None when it cannot be known."""
+        if self.kind is RuleKind.OFFSET:
+            return read_u64(cfa + self.value)
+        if self.kind is RuleKind.VAL_OFFSET:
+            return cfa + self.value
+        if self.kind is RuleKind.REGISTER:
+            return registers.get(self.value)
+        return None
+
+
+@dataclass
+class UnwindRow:
+    """How to find the caller's registers at one address."""
+
+    # CFA = register + offset; None when the CFA is a DWARF expression.
+    cfa_register: Optional[int]
+    cfa_offset: int
+    # Registers without a rule keep their value in the caller.
+    rules: Dict[int, Rule]
+    return_address_register: int
+
+
+@dataclass
+class Cie:
+    code_alignment: int
+    data_alignment: int
+    return_address_register: int
+    instructions: bytes
+    fde_encoding: int = DW_EH_PE_absptr
+    has_augmentation_data: bool = False
+    address_size: int = 8
+
+
+@dataclass
+class Fde:
+    cie: Cie
+    start: int
+    end: int
+    instructions: bytes
+
+
+@dataclass
+class CallFrameTable:
+    """FDEs of one image, sorted by start address; look one up with `find`."""
+
+    _starts: List[int] = field(default_factory=list)
+    _fdes: List[Fde] = field(default_factory=list)
+
+    def add(self, fdes: List[Fde]) -> None:
+        """Add FDEs with lower priority than those already present (``.debug_frame`` after ``.eh_frame``)."""
+        merged = self._fdes + [fde for fde in fdes if fde.end > fde.start and self.find(fde.start) is None]
+        merged.sort(key=lambda fde: fde.start)
+        self._fdes = merged
+        self._starts = [fde.start for fde in merged]
+
+    def find(self, address: int) -> Optional[Fde]:
+        idx = bisect.bisect_right(self._starts, address) - 1
+        if idx < 0:
+            return None
+        fde = self._fdes[idx]
+        return fde if fde.start <= address < fde.end else None
+
+    def row(self, address: int) -> Optional[UnwindRow]:
+        """The unwind rules in effect at ``address``, or None if no FDE covers it."""
+        fde = self.find(address)
+        if fde is None:
+            return None
+        return _execute(fde, address)
+
+    def __len__(self) -> int:
+        return len(self._fdes)
+
+
+def _encoded(r: _Reader, encoding: int, address_size: int, section_address: int) -> Optional[int]:
+    if encoding == DW_EH_PE_omit:
+        return None
+    field_address = section_address + r.pos
+    fmt = encoding & 0x0F
+    if fmt == DW_EH_PE_absptr:
+        value = r.uint(address_size)
+    elif fmt == DW_EH_PE_uleb128:
+        value = r.uleb()
+    elif fmt == DW_EH_PE_sleb128:
+        value = r.sleb()
+    elif fmt in (DW_EH_PE_udata2, DW_EH_PE_udata4, DW_EH_PE_udata8):
+        value = r.uint({DW_EH_PE_udata2: 2, DW_EH_PE_udata4: 4, DW_EH_PE_udata8: 8}[fmt])
+    elif fmt in (DW_EH_PE_sdata2, DW_EH_PE_sdata4, DW_EH_PE_sdata8):
+        size = {DW_EH_PE_sdata2: 2, DW_EH_PE_sdata4: 4, DW_EH_PE_sdata8: 8}[fmt]
+        at = r.pos
+        r.skip(size)
+        value = int.from_bytes(r.data[at : at + size], "little", signed=True)
+    else:
+        raise DwarfError(f"unsupported pointer encoding {encoding:#x}")
+    application = encoding & 0x70
+    if application == DW_EH_PE_pcrel:
+        value += field_address
+    elif application:
+        # textrel / datarel / funcrel / aligned only appear on other targets.
+        raise DwarfError(f"unsupported pointer application {application:#x}")
+    return value & ((1 << (8 * address_size)) - 1)
+
+
+def _parse_cie(r: _Reader, address_size: int, section_address: int) -> Cie:
+    version = r.u8()
+    if version not in (1, 3, 4):
+        raise DwarfError(f"unsupported CIE version {version}")
+    augmentation = r.cstr()
+    if version >= 4:
+        address_size = r.u8()
+        r.skip(1)  # segment_selector_size
+    code_alignment = r.uleb()
+    data_alignment = r.sleb()
+    ra_register = r.u8() if version == 1 else r.uleb()
+    cie = Cie(code_alignment, data_alignment, ra_register, b"", address_size=address_size)
+    if augmentation.startswith("z"):
+        length = r.uleb()
+        data_end = r.pos + length
+        cie.has_augmentation_data = True
+        for char in augmentation[1:]:
+            if char == "R":
+                cie.fde_encoding = r.u8()
+            elif char == "P":
+                _encoded(r, r.u8() & ~DW_EH_PE_indirect, address_size, section_address)
+            elif char == "L":
+                r.skip(1)
+            elif char not in "SB":
+                # An augmentation this reader does not know; its data length still lets it be skipped.
+                break
+        r.pos = data_end
+    elif augmentation:
+        raise DwarfError(f"unsupported CIE augmentation {augmentation!r}")
+    cie.instructions = bytes(r.data[r.pos : r.end])
+    return cie
+
+
+def parse_call_frames(data: bytes, section_address: int = 0, eh_frame: bool = True, address_size: int = 8) -> List[Fde]:
+    """Every FDE in an ``.eh_frame`` (``eh_frame=True``) or ``.debug_frame`` section.
+
+    ``section_address`` is the section's link-time address, which ``.eh_frame``'s
+    pc-relative pointers are relative to. A malformed entry raises `DwarfError`.
+    """
+    cies: Dict[int, Cie] = {}
+    fdes: List[Fde] = []
+    r = _Reader(data)
+    while r.pos + 4 <= len(data):
+        entry = r.pos
+        length = r.u32()
+        if length == 0:
+            if eh_frame:
+                break  # the terminator
+            continue
+        offset_size = 4
+        if length == 0xFFFFFFFF:
+            length = r.u64()
+            offset_size = 8
+        start = r.pos
+        end = start + length
+        if end > len(data):
+            raise DwarfError(f"call frame entry at {entry:#x} runs past the section")
+        body = _Reader(data, start, end)
+        pointer = body.uint(offset_size)
+        is_cie = pointer == 0 if eh_frame else pointer == (1 << (8 * offset_size)) - 1
+        if is_cie:
+            cies[entry] = _parse_cie(body, address_size, section_address)
+        else:
+            # .eh_frame points back from this field; .debug_frame gives an offset into the section.
+            cie_offset = start - pointer if eh_frame else pointer
+            cie = cies.get(cie_offset)
+            if cie is None:
+                cie = cies[cie_offset] = _cie_at(data, cie_offset, address_size, section_address)
+            fdes.append(_parse_fde(body, cie, eh_frame, section_address))
+        r.pos = end
+    return fdes
+
+
+def _cie_at(data: bytes, offset: int, address_size: int, section_address: int) -> Cie:
+    r = _Reader(data, offset)
+    length = r.u32()
+    offset_size = 4
+    if length == 0xFFFFFFFF:
+        length = r.u64()
+        offset_size = 8
+    body = _Reader(data, r.pos, r.pos + length)
+    body.skip(offset_size)
+    return _parse_cie(body, address_size, section_address)
+
+
+def _parse_fde(r: _Reader, cie: Cie, eh_frame: bool, section_address: int) -> Fde:
+    encoding = cie.fde_encoding if eh_frame else DW_EH_PE_absptr
+    start = _encoded(r, encoding, cie.address_size, section_address) or 0
+    # The range is a plain size in the same format, never relative.
+    size = _encoded(r, encoding & 0x0F, cie.address_size, section_address) or 0
+    if cie.has_augmentation_data:
+        r.skip(r.uleb())
+    return Fde(cie, start, start + size, bytes(r.data[r.pos : r.end]))
+
+
+def _execute(fde: Fde, address: int) -> UnwindRow:
+    cie = fde.cie
+    row = UnwindRow(None, 0, {}, cie.return_address_register)
+    _run(cie.instructions, cie, row, None, fde.start, None)
+    initial = dict(row.rules)
+    _run(fde.instructions, cie, row, initial, fde.start, address)
+    return row
+
+
+def _run(code: bytes, cie: Cie, row: UnwindRow, initial: Optional[Dict[int, Rule]], loc: int,
+         target: Optional[int]) -> None:
+    r = _Reader(code)
+    stack: List[Tuple[Optional[int], int, Dict[int, Rule]]] = []
+    data_alignment = cie.data_alignment
+
+    def advance(delta: int) -> bool:
+        nonlocal loc
+        loc += delta * cie.code_alignment
+        return target is not None and loc > target
+
+    while r.pos < len(code):
+        op = r.u8()
+        high, low = op & 0xC0, op & 0x3F
+        if high == DW_CFA_advance_loc:
+            if advance(low):
+                return
+        elif high == DW_CFA_offset:
+            row.rules[low] = Rule(RuleKind.OFFSET, r.uleb() * data_alignment)
+        elif high == DW_CFA_restore:
+            _restore(row, initial, low)
+        elif op == DW_CFA_nop:
+            pass
+        elif op == DW_CFA_set_loc:
+            loc = r.uint(cie.address_size)
+            if target is not None and loc > target:
+                return
+        elif op in (DW_CFA_advance_loc1, DW_CFA_advance_loc2, DW_CFA_advance_loc4):
+            if advance(r.uint({DW_CFA_advance_loc1: 1, DW_CFA_advance_loc2: 2, DW_CFA_advance_loc4: 4}[op])):
+                return
+        elif op == DW_CFA_offset_extended:
+            reg = r.uleb()
+            row.rules[reg] = Rule(RuleKind.OFFSET, r.uleb() * data_alignment)
+        elif op == DW_CFA_restore_extended:
+            _restore(row, initial, r.uleb())
+        elif op == DW_CFA_undefined:
+            row.rules[r.uleb()] = Rule(RuleKind.UNDEFINED)
+        elif op == DW_CFA_same_value:
+            row.rules[r.uleb()] = Rule(RuleKind.SAME_VALUE)
+        elif op == DW_CFA_register:
+            reg = r.uleb()
+            row.rules[reg] = Rule(RuleKind.REGISTER, r.uleb())
+        elif op == DW_CFA_remember_state:
+            stack.append((row.cfa_register, row.cfa_offset, dict(row.rules)))
+        elif op == DW_CFA_restore_state:
+            if stack:
+                row.cfa_register, row.cfa_offset, row.rules = stack.pop()
+        elif op == DW_CFA_def_cfa:
+            row.cfa_register, row.cfa_offset = r.uleb(), r.uleb()
+        elif op == DW_CFA_def_cfa_sf:
+            row.cfa_register, row.cfa_offset = r.uleb(), r.sleb() * data_alignment
+        elif op == DW_CFA_def_cfa_register:
+            row.cfa_register = r.uleb()
+        elif op == DW_CFA_def_cfa_offset:
+            row.cfa_offset = r.uleb()
+        elif op == DW_CFA_def_cfa_offset_sf:
+            row.cfa_offset = r.sleb() * data_alignment
+        elif op == DW_CFA_def_cfa_expression:
+            r.skip(r.uleb())
+            row.cfa_register = None
+        elif op in (DW_CFA_expression, DW_CFA_val_expression):
+            reg = r.uleb()
+            r.skip(r.uleb())
+            row.rules[reg] = Rule(RuleKind.EXPRESSION)
+        elif op == DW_CFA_offset_extended_sf:
+            reg = r.uleb()
+            row.rules[reg] = Rule(RuleKind.OFFSET, r.sleb() * data_alignment)
+        elif op == DW_CFA_val_offset:
+            reg = r.uleb()
+            row.rules[reg] = Rule(RuleKind.VAL_OFFSET, r.uleb() * data_alignment)
+        elif op == DW_CFA_val_offset_sf:
+            reg = r.uleb()
+            row.rules[reg] = Rule(RuleKind.VAL_OFFSET, r.sleb() * data_alignment)
+        elif op == DW_CFA_GNU_args_size:
+            r.uleb()
+        elif op == DW_CFA_GNU_negative_offset_extended:
+            reg = r.uleb()
+            row.rules[reg] = Rule(RuleKind.OFFSET, -r.uleb() * data_alignment)
+        elif op == DW_CFA_AARCH64_negate_ra_state:
+            pass
+        else:
+            raise DwarfError(f"unsupported call frame instruction {op:#x}")
+
+
+def _restore(row: UnwindRow, initial: Optional[Dict[int, Rule]], reg: int) -> None:
+    rule = (initial or {}).get(reg)
+    if rule is None:
+        row.rules.pop(reg, None)
+    else:
+        row.rules[reg] = rule
+
+
+__all__ = [
+    "CallFrameTable",
+    "Cie",
+    "Fde",
+    "Rule",
+    "RuleKind",
+    "UnwindRow",
+    "parse_call_frames",
+]
@@ -0,0 +1,160 @@
+"""How well a binary will symbolize, before anything crashes.
+
+A report full of ``??`` frames usually means the binary was stripped and its
+debug file was not found, which is easier to fix before an incident than
+during one. `check_debug_info` opens an ELF image and reports what the
+symbolizer and the unwinder will have to work with: the debug sections it
+carries, whether it is stripped, and the separate debug file that
+`find_debug_file` would load for it (or why each candidate was rejected).
+``dbgcopilot check`` prints it.
+
+`DebugInfoCheck.expect` sums it up the way a backtrace will look: source
+lines, function names only, exported names only, or raw addresses.
+"""
+from __future__ import annotations
+
+from dataclasses import dataclass, field
+from enum import Enum
+from typing import Any, Dict, List, Optional, Sequence
+
+from dbgcopilot.utils.elf import ElfError, ElfFile, open_elf
+
+from .debuginfo import DEFAULT_DEBUG_DIRS, find_debug_file
+
+# Sections reported on, in the order they are printed.
+CHECKED_SECTIONS = (".debug_info", ".debug_line", ".eh_frame", ".debug_frame", ".symtab", ".gnu_debuglink")
+
+
+class Symbolization(str, Enum):
+    SOURCE_LINES = "source_lines"
+    FUNCTIONS = "functions"
+    EXPORTED_FUNCTIONS = "exported_functions"
+    RAW_ADDRESSES = "raw_addresses"
+
+    @property
+    def label(self) -> str:
+        return _SYMBOLIZATION_LABELS[self]
+
+
+_SYMBOLIZATION_LABELS = {
+    Symbolization.SOURCE_LINES: "function names and file:line",
+    Symbolization.FUNCTIONS: "function names, but no file:line",
+    Symbolization.EXPORTED_FUNCTIONS: "only exported function names (from .dynsym)",
+    Symbolization.RAW_ADDRESSES: "raw addresses",
+}
+
+
+@dataclass
+class DebugInfoCheck:
+    path: str
+    arch: str
+    build_id: Optional[str] = None
+    # File name ``.gnu_debuglink`` names, and the CRC-32 it expects.
+    debuglink: Optional[str] = None
+    debuglink_crc: Optional[int] = None
+    # Section name -> present in the binary itself.
+    sections: Dict[str, bool] = field(default_factory=dict)
+    has_dynsym: bool = False
+    # The verified separate debug file, and the sections it adds.
+    debug_file: Optional[str] = None
+    debug_file_sections: List[str] = field(default_factory=list)
+    # Why the candidates that were found could not be used.
+    debug_file_rejected: List[str] = field(default_factory=list)
+
+    @property
+    def stripped(self) -> bool:
+        """No ``.symtab`` in the binary itself (a debug file may still have one)."""
+        return not self.sections.get(".symtab")
+
+    def _available(self, name: str) -> bool:
+        return bool(self.sections.get(name)) or name in self.debug_file_sections
+
+    @property
+    def expect(self) -> Symbolization:
+        if self._available(".debug_line"):
+            return Symbolization.SOURCE_LINES
+        if self._available(".symtab"):
+            return Symbolization.FUNCTIONS
+        if self.has_dynsym:
+            return Symbolization.EXPORTED_FUNCTIONS
+        return Symbolization.RAW_ADDRESSES
+
+    @property
+    def unwinds_with_cfi(self) -> bool:
+        """Whether stacks can be unwound without frame pointers (see `dbgcopilot.symbols.cfi`)."""
+        return self._available(".eh_frame") or self._available(".debug_frame")
+
+    def to_dict(self) -> Dict[str, Any]:
+        return {
+            "path": self.path,
+            "arch": self.arch,
+            "build_id": self.build_id,
+            "debuglink": self.debuglink,
+            "debuglink_crc": f"{self.debuglink_crc:08x}" if self.debuglink_crc is not None else None,
+            "sections": dict(self.sections),
+            "stripped": self.stripped,
+            "debug_file": self.debug_file,
+            "debug_file_sections": list(self.debug_file_sections),
+            "debug_file_rejected": list(self.debug_file_rejected),
+            "expect": self.expect.value,
+            "unwinds_with_cfi": self.unwinds_with_cfi,
+        }
+
+    def render(self) -> str:
+        lines = [f"Binary: {self.path} ({self.arch})", f"Build-id: {self.build_id or 'none'}"]
+        lines.append("Stripped: yes (no .symtab)" if self.stripped else "Stripped: no")
+        lines.append("Sections:")
+        for name in CHECKED_SECTIONS:
+            state = "present" if self.sections.get(name) else "missing"
+            if name == ".gnu_debuglink" and self.debuglink:
+                state = f"{self.debuglink} (CRC {self.debuglink_crc:08x})"
+            elif name in self.debug_file_sections and not self.sections.get(name):
+                state = "in the debug file"
+            lines.append(f"  {name:<16}{state}")
+        if self.debug_file:
+            lines.append(f"Debug file: {self.debug_file}")
+        elif self.build_id or self.debuglink:
+            lines.append("Debug file: not found")
+        lines.extend(f"  rejected {reason}" for reason in self.debug_file_rejected)
+        lines.append(f"Frames will show: {self.expect.label}")
+        lines.append(f"Unwinding: {'call frame information' if self.unwinds_with_cfi else 'frame pointers only'}")
+        return "\n".join(lines)
+
+
+def _present(elf: ElfFile) -> Dict[str, bool]:
+    names = {section.name for section in elf.sections}
+    return {name: name in names for name in CHECKED_SECTIONS}
+
+
+def check_debug_info(
+    path: str,
+    debug_file: Optional[str] = None,
+    debug_dirs: Sequence[str] = DEFAULT_DEBUG_DIRS,
+) -> DebugInfoCheck:
+    """What ``path`` offers the symbolizer; raises `ElfError` (or OSError) if it is not a readable ELF file."""
+    elf = open_elf(path)
+    link = elf.debuglink()
+    check = DebugInfoCheck(
+        path=path,
+        arch=elf.arch,
+        build_id=elf.build_id(),
+        debuglink=link[0] if link else None,
+        debuglink_crc=link[1] if link else None,
+        sections=_present(elf),
+        has_dynsym=elf.section(".dynsym") is not None,
+    )
+    rejected: List[str] = []
+    found = find_debug_file(path, elf, debug_file, debug_dirs, rejected)
+    if found is not None:
+        try:
+            debug_elf = open_elf(found)
+        except (OSError, ElfError) as exc:
+            rejected.append(f"{found}: {exc}")
+        else:
+            check.debug_file = found
+            check.debug_file_sections = [name for name, present in _present(debug_elf).items() if present]
+    check.debug_file_rejected = rejected
+    return check
+
+
+__all__ = ["CHECKED_SECTIONS", "DebugInfoCheck", "Symbolization", "check_debug_info"]
@@ -0,0 +1,121 @@
+"""Find the separate debug file of a stripped image.
+
+Release builds often ship stripped, with the symbol table and DWARF moved to a
+companion ``.debug`` file (``objcopy --only-keep-debug``). The image names it
+in two ways, and `find_debug_file` tries them in GDB's order:
+
+1. the build-id: ``<debug dir>/.build-id/ab/cdef....debug``;
+2. ``.gnu_debuglink``: a file name plus the CRC-32 of its contents, looked up
+   next to the image, in its ``.debug`` subdirectory, and under each debug
+   directory followed by the image's own directory.
+
+A candidate is only used if it is the same build: its build-id must match, or
+its CRC must match the debuglink's. A stale ``.debug`` from another build
+would otherwise put every frame on the wrong line. Rejected candidates are
+reported through ``rejected`` so the reason can be shown.
+
+With a `SymbolPath`, its directories are searched instead, and when none has
+the file its servers are asked for it by build-id (see `dbgcopilot.symbols.sympath`).
+"""
+from __future__ import annotations
+
+import os
+import zlib
+from typing import TYPE_CHECKING, List, Optional, Sequence
+
+from dbgcopilot.utils.elf import ElfError, ElfFile, open_elf
+
+if TYPE_CHECKING:
+    from .sympath import SymbolPath
+
+# Where distributions install debug files (GDB's debug-file-directory).
+DEFAULT_DEBUG_DIRS = ("/usr/lib/debug",)
+
+
+def file_crc32(path: str) -> int:
+    """CRC-32 of a file's contents, as ``.gnu_debuglink`` records it."""
+    crc = 0
+    with open(path, "rb") as fh:
+        for block in iter(lambda: fh.read(1 << 20), b""):
+            crc = zlib.crc32(block, crc)
+    return crc
+
+
+def _build_id_paths(build_id: str, debug_dirs: Sequence[str]) -> List[str]:
+    if len(build_id) < 3:
+        return []
+    return [os.path.join(d, ".build-id", build_id[:2], build_id[2:] + ".debug") for d in debug_dirs]
+
+
+def _debuglink_paths(image: str, name: str, debug_dirs: Sequence[str]) -> List[str]:
+    directory = os.path.dirname(os.path.abspath(image))
+    paths = [os.path.join(directory, name), os.path.join(directory, ".debug", name)]
+    paths += [os.path.join(d, directory.lstrip(os.sep), name) for d in debug_dirs]
+    return paths
+
+
+def _matches(candidate: str, elf: ElfFile, rejected: Optional[List[str]]) -> bool:
+    """Whether ``candidate`` is the debug file of ``elf``: same build-id, else the debuglink's CRC."""
+    build_id, link = elf.build_id(), elf.debuglink()
+    try:
+        if build_id is not None:
+            theirs = open_elf(candidate).build_id()
+            if theirs == build_id:
+                return True
+            if theirs is not None:
+                if rejected is not None:
+                    rejected.append(f"{candidate}: build-id {theirs} does not match {build_id}")
+                return False
+        if link is not None:
+            crc = file_crc32(candidate)
+            if crc == link[1]:
+                return True
+            if rejected is not None:
+                rejected.append(f"{candidate}: CRC {crc:08x} does not match the debuglink's {link[1]:08x}")
+            return False
+    except (OSError, ElfError) as exc:
+        if rejected is not None:
+            rejected.append(f"{candidate}: {exc}")
+        return False
+    # Neither a build-id nor a debuglink to check against.
+    return True
+
+
+def find_debug_file(
+    image: str,
+    elf: ElfFile,
+    debug_file: Optional[str] = None,
+    debug_dirs: Sequence[str] = DEFAULT_DEBUG_DIRS,
+    rejected: Optional[List[str]] = None,
+    symbol_path: Optional["SymbolPath"] = None,
+) -> Optional[str]:
+    """Path of the verified debug file for ``image`` (parsed as ``elf``), or None.
+
+    ``debug_file`` is tried instead of searching, and gets the same checks.
+    ``symbol_path`` replaces ``debug_dirs`` and adds its servers as a last resort.
+    """
+    if debug_file is not None:
+        return debug_file if _matches(debug_file, elf, rejected) else None
+    if symbol_path is not None:
+        debug_dirs = symbol_path.debug_dirs
+    candidates: List[str] = []
+    build_id = elf.build_id()
+    if build_id:
+        candidates += _build_id_paths(build_id, debug_dirs)
+    link = elf.debuglink()
+    if link is not None:
+        candidates += _debuglink_paths(image, link[0], debug_dirs)
+    this = os.path.abspath(image)
+    for candidate in candidates:
+        if os.path.abspath(candidate) == this or not os.path.isfile(candidate):
+            continue
+        if _matches(candidate, elf, rejected):
+            return candidate
+    if build_id and symbol_path is not None:
+        fetched = symbol_path.fetch_debug_file(build_id, rejected)
+        if fetched is not None and _matches(fetched, elf, rejected):
+            return fetched
+    return None
+
+
+__all__ = ["DEFAULT_DEBUG_DIRS", "file_crc32", "find_debug_file"]
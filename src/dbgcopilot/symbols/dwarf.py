@@ -0,0 +1,333 @@
+"""DWARF ``.debug_line`` decoder (versions 2-5, 32- and 64-bit units).
+
+Runs each unit's line-number program and flattens the resulting rows into
+address ranges, which is all a pc-to-file:line lookup needs. Only the line
+tables are read; ``.debug_info`` is not.
+"""
+from __future__ import annotations
+
+import bisect
+import posixpath
+import struct
+import time
+from dataclasses import dataclass, field
+from typing import Dict, List, Optional, Tuple
+
+# Standard opcodes.
+DW_LNS_copy = 1
+DW_LNS_advance_pc = 2
+DW_LNS_advance_line = 3
+DW_LNS_set_file = 4
+DW_LNS_set_column = 5
+DW_LNS_negate_stmt = 6
+DW_LNS_set_basic_block = 7
+DW_LNS_const_add_pc = 8
+DW_LNS_fixed_advance_pc = 9
+DW_LNS_set_prologue_end = 10
+DW_LNS_set_epilogue_begin = 11
+DW_LNS_set_isa = 12
+
+# Extended opcodes.
+DW_LNE_end_sequence = 1
+DW_LNE_set_address = 2
+DW_LNE_define_file = 3
+DW_LNE_set_discriminator = 4
+
+# DWARF 5 entry-format content types and the forms they use.
+DW_LNCT_path = 1
+DW_LNCT_directory_index = 2
+
+DW_FORM_block = 0x09
+DW_FORM_block1 = 0x0A
+DW_FORM_data1 = 0x0B
+DW_FORM_data2 = 0x05
+DW_FORM_data4 = 0x06
+DW_FORM_data8 = 0x07
+DW_FORM_data16 = 0x1E
+DW_FORM_string = 0x08
+DW_FORM_strp = 0x0E
+DW_FORM_udata = 0x0F
+DW_FORM_line_strp = 0x1F
+
+
+class DwarfError(ValueError):
+    """Raised when a DWARF section is truncated or uses an unsupported encoding."""
+
+
+@dataclass
+class LineRow:
+    address: int
+    file: str
+    line: int
+    column: int = 0
+
+
+@dataclass
+class LineTable:
+    """Address ranges of every sequence in ``.debug_line``, sorted by start."""
+
+    _starts: List[int] = field(default_factory=list)
+    _ranges: List[Tuple[int, int, LineRow]] = field(default_factory=list)
+
+    def add_sequence(self, rows: List[LineRow], end: int) -> None:
+        for i, row in enumerate(rows):
+            stop = rows[i + 1].address if i + 1 < len(rows) else end
+            # Zero-length rows are superseded by the next row at the same address;
+            # line 0 marks compiler-generated code with no source line.
+            if stop > row.address and row.line:
+                self._ranges.append((row.address, stop, row))
+
+    def finish(self) -> None:
+        self._ranges.sort(key=lambda r: r[0])
+        self._starts = [r[0] for r in self._ranges]
+
+    def lookup(self, address: int) -> Optional[LineRow]:
+        idx = bisect.bisect_right(self._starts, address) - 1
+        if idx < 0:
+            return None
+        start, stop, row = self._ranges[idx]
+        return row if start <= address < stop else None
+
+    def __len__(self) -> int:
+        return len(self._ranges)
+
+
+class _Reader:
+    def __init__(self, data: bytes, offset: int = 0, end: Optional[int] = None) -> None:
+        self.data = data
+        self.pos = offset
+        self.end = len(data) if end is None else end
+
+    def _take(self, size: int) -> int:
+        pos = self.pos
+        if pos + size > self.end:
+            raise DwarfError(f"read of {size} bytes at {pos:#x} runs past the unit")
+        self.pos += size
+        return pos
+
+    def u8(self) -> int:
+        return self.data[self._take(1)]
+
+    def u16(self) -> int:
+        return struct.unpack_from("<H", self.data, self._take(2))[0]
+
+    def u32(self) -> int:
+        return struct.unpack_from("<I", self.data, self._take(4))[0]
+
+    def u64(self) -> int:
+        return struct.unpack_from("<Q", self.data, self._take(8))[0]
+
+    def i8(self) -> int:
+        return struct.unpack_from("<b", self.data, self._take(1))[0]
+
+    def uint(self, size: int) -> int:
+        return int.from_bytes(self.data[self._take(size) : self.pos], "little")
+
+    def skip(self, size: int) -> None:
+        self._take(size)
+
+    def uleb(self) -> int:
+        result = shift = 0
+        while True:
+            byte = self.u8()
+            result |= (byte & 0x7F) << shift
+            shift += 7
+            if not byte & 0x80:
+                return result
+
+    def sleb(self) -> int:
+        result = shift = 0
+        while True:
+            byte = self.u8()
+            result |= (byte & 0x7F) << shift
+            shift += 7
+            if not byte & 0x80:
+                if byte & 0x40:
+                    result -= 1 << shift
+                return result
+
+    def cstr(self) -> str:
+        end = self.data.find(b"\x00", self.pos, self.end)
+        if end < 0:
+            raise DwarfError(f"unterminated string at {self.pos:#x}")
+        text = self.data[self.pos : end].decode("utf-8", errors="replace")
+        self.pos = end + 1
+        return text
+
+
+def _cstr_at(section: Optional[bytes], offset: int, name: str) -> str:
+    if section is None or offset >= len(section):
+        raise DwarfError(f"string offset {offset:#x} is outside {name}")
+    end = section.find(b"\x00", offset)
+    return section[offset : end if end >= 0 else len(section)].decode("utf-8", errors="replace")
+
+
+def _read_form(r: _Reader, form: int, offset_size: int, strings: Dict[str, Optional[bytes]]) -> object:
+    if form == DW_FORM_string:
+        return r.cstr()
+    if form == DW_FORM_line_strp:
+        return _cstr_at(strings.get(".debug_line_str"), r.uint(offset_size), ".debug_line_str")
+    if form == DW_FORM_strp:
+        return _cstr_at(strings.get(".debug_str"), r.uint(offset_size), ".debug_str")
+    if form == DW_FORM_udata:
+        return r.uleb()
+    sizes = {DW_FORM_data1: 1, DW_FORM_data2: 2, DW_FORM_data4: 4, DW_FORM_data8: 8, DW_FORM_data16: 16}
+    if form in sizes:
+        return r.uint(sizes[form])
+    if form == DW_FORM_block:
+        r.skip(r.uleb())
+        return None
+    if form == DW_FORM_block1:
+        r.skip(r.u8())
+        return None
+    raise DwarfError(f"unsupported form {form:#x} in line table header")
+
+
+def _entries_v5(r: _Reader, offset_size: int, strings: Dict[str, Optional[bytes]]) -> List[Dict[int, object]]:
+    formats = [(r.uleb(), r.uleb()) for _ in range(r.u8())]
+    entries = []
+    for _ in range(r.uleb()):
+        entries.append({content: _read_form(r, form, offset_size, strings) for content, form in formats})
+    return entries
+
+
+def _join(directory: str, name: str) -> str:
+    if not directory or name.startswith("/") or (len(name) > 1 and name[1] == ":"):
+        return name
+    return posixpath.join(directory, name)
+
+
+def _parse_unit(r: _Reader, offset_size: int, table: LineTable, strings: Dict[str, Optional[bytes]]) -> None:
+    version = r.u16()
+    if not 2 <= version <= 5:
+        raise DwarfError(f"unsupported .debug_line version {version}")
+    if version >= 5:
+        r.skip(2)  # address_size, segment_selector_size
+    header_length = r.uint(offset_size)
+    program_start = r.pos + header_length
+    min_inst = r.u8()
+    if version >= 4:
+        r.skip(1)  # maximum_operations_per_instruction (VLIW only)
+    r.skip(1)  # default_is_stmt; every row is kept, statement or not
+    line_base = r.i8()
+    line_range = r.u8()
+    opcode_base = r.u8()
+    if not line_range:
+        raise DwarfError("line_range of 0 in line table header")
+    opcode_lengths = [r.u8() for _ in range(opcode_base - 1)]
+
+    if version >= 5:
+        dirs = [str(e.get(DW_LNCT_path, "")) for e in _entries_v5(r, offset_size, strings)]
+        files = []
+        for e in _entries_v5(r, offset_size, strings):
+            d = int(e.get(DW_LNCT_directory_index, 0) or 0)  # type: ignore[arg-type]
+            files.append(_join(dirs[d] if d < len(dirs) else "", str(e.get(DW_LNCT_path, ""))))
+        first_file = 0  # DWARF 5 file indices are 0-based
+    else:
+        dirs = [""]  # index 0 is the compilation directory, which lives in .debug_info
+        while True:
+            d = r.cstr()
+            if not d:
+                break
+            dirs.append(d)
+        files = [""]
+        while True:
+            name = r.cstr()
+            if not name:
+                break
+            d = r.uleb()
+            r.uleb()  # mtime
+            r.uleb()  # length
+            files.append(_join(dirs[d] if d < len(dirs) else "", name))
+        first_file = 1
+
+    def file_at(index: int) -> str:
+        return files[index] if first_file <= index < len(files) else f"<file {index}>"
+
+    r.pos = program_start
+    const_add = ((255 - opcode_base) // line_range) * min_inst
+
+    address, file, line, column = 0, 1, 1, 0
+    rows: List[LineRow] = []
+
+    def emit() -> None:
+        rows.append(LineRow(address=address, file=file_at(file), line=line, column=column))
+
+    while r.pos < r.end:
+        op = r.u8()
+        if op >= opcode_base:
+            adj = op - opcode_base
+            address += (adj // line_range) * min_inst
+            line += line_base + adj % line_range
+            emit()
+        elif op == 0:
+            length = r.uleb()
+            ext_end = r.pos + length
+            sub = r.u8() if length else 0
+            if sub == DW_LNE_end_sequence:
+                table.add_sequence(rows, address)
+                rows = []
+                address, file, line, column = 0, 1, 1, 0
+            elif sub == DW_LNE_set_address:
+                address = r.uint(length - 1)
+            elif sub == DW_LNE_define_file:
+                name = r.cstr()
+                d = r.uleb()
+                files.append(_join(dirs[d] if d < len(dirs) else "", name))
+            r.pos = ext_end
+        elif op == DW_LNS_copy:
+            emit()
+        elif op == DW_LNS_advance_pc:
+            address += r.uleb() * min_inst
+        elif op == DW_LNS_advance_line:
+            line += r.sleb()
+        elif op == DW_LNS_set_file:
+            file = r.uleb()
+        elif op == DW_LNS_set_column:
+            column = r.uleb()
+        elif op == DW_LNS_const_add_pc:
+            address += const_add
+        elif op == DW_LNS_fixed_advance_pc:
+            address += r.u16()
+        elif op in (DW_LNS_negate_stmt, DW_LNS_set_basic_block, DW_LNS_set_prologue_end, DW_LNS_set_epilogue_begin):
+            pass
+        else:
+            # DW_LNS_set_isa and opcodes from newer standards: skip their ULEB operands.
+            for _ in range(opcode_lengths[op - 1]):
+                r.uleb()
+
+
+def parse_debug_line(
+    data: bytes,
+    line_str: Optional[bytes] = None,
+    debug_str: Optional[bytes] = None,
+    deadline: Optional[float] = None,
+) -> LineTable:
+    """Decode every unit in a ``.debug_line`` section into one `LineTable`.
+
+    ``line_str`` / ``debug_str`` are the ``.debug_line_str`` / ``.debug_str``
+    sections that DWARF 5 headers point into. A unit that cannot be decoded
+    raises `DwarfError`, and so does still decoding when `time.monotonic`
+    passes ``deadline``.
+    """
+    strings = {".debug_line_str": line_str, ".debug_str": debug_str}
+    table = LineTable()
+    r = _Reader(data)
+    while r.pos < len(data):
+        if deadline is not None and time.monotonic() > deadline:
+            raise DwarfError(f"line table decoding ran out of time at unit {r.pos:#x}")
+        unit_length = r.u32()
+        offset_size = 4
+        if unit_length == 0xFFFFFFFF:
+            unit_length = r.u64()
+            offset_size = 8
+        unit_end = r.pos + unit_length
+        if unit_end > len(data):
+            raise DwarfError(f"line table unit at {r.pos:#x} runs past the section")
+        _parse_unit(_Reader(data, r.pos, unit_end), offset_size, table, strings)
+        r.pos = unit_end
+    table.finish()
+    return table
+
+
+__all__ = ["DwarfError", "LineRow", "LineTable", "parse_debug_line"]
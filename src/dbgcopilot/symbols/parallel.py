@@ -0,0 +1,149 @@
+"""Symbolizing many modules at once, each within a time limit.
+
+A core that maps dozens of shared libraries has frames in a handful of them.
+Symbolizing those serially means finding each one's debug file (perhaps
+downloading it from a symbol server), reading it, and decoding its line
+table, one after another. `symbolize_modules` takes the frames grouped by
+image (`ModuleFrames`). Only images that have a frame are opened, and each
+one is a task for one of `DEFAULT_WORKERS` threads, so the waits for disk
+and network overlap.
+
+Each image gets `DEFAULT_MODULE_TIMEOUT` seconds from when its task starts.
+One with a pathological ``.debug_line`` (or a symbol server that never
+answers) does not hold up the rest. Its frames keep their raw addresses, and
+a warning says so. Line-table decoding checks the deadline between units and
+stops. A stalled download cannot be interrupted, so its result is
+discarded when it arrives. The workers are daemon threads, so such a task
+does not keep the process alive after the analysis is done.
+"""
+from __future__ import annotations
+
+import dataclasses
+import logging
+import queue
+import threading
+import time
+from dataclasses import dataclass, field
+from typing import Callable, Dict, List, Optional, Tuple
+
+from dbgcopilot.analysis.model import Frame
+
+from .symbolizer import Symbolizer
+
+log = logging.getLogger(__name__)
+
+DEFAULT_WORKERS = 8
+DEFAULT_MODULE_TIMEOUT = 10.0
+# How often the pool is checked for tasks over their time.
+_POLL_SECONDS = 0.05
+
+
+@dataclass
+class ModuleFrames:
+    """The frames in one image, and how to open its `Symbolizer`: one task of `symbolize_modules`."""
+
+    name: str
+    # `load` returns None for an image that cannot be read; its frames are left alone.
+    load: Callable[[], Optional[Symbolizer]]
+    # The image's load bias, given its symbolizer.
+    bias: Callable[[Symbolizer], int]
+    frames: List[Frame] = field(default_factory=list)
+    # Set when the image ran out of time and its frames were left as they were.
+    timed_out: bool = False
+
+
+# A job as a worker finished it: its symbolized frames and time taken, or what it raised.
+_Finished = Tuple[ModuleFrames, Optional[Tuple[List[Frame], float]], Optional[Exception]]
+
+
+def _symbolize(job: ModuleFrames, timeout: float, started: Dict[int, float]) -> Tuple[List[Frame], float]:
+    begin = started.setdefault(id(job), time.monotonic())
+    symbolizer = job.load()
+    if symbolizer is None:
+        return [], time.monotonic() - begin
+    symbolizer.load_lines(deadline=begin + timeout)
+    bias = job.bias(symbolizer)
+    # Copies: a task given up on must not change frames the caller has moved on with.
+    done = [symbolizer.symbolize(dataclasses.replace(f), bias) for f in job.frames]
+    return done, time.monotonic() - begin
+
+
+def _copy_into(frame: Frame, found: Frame) -> None:
+    for name in ("function", "offset", "raw_symbol", "file", "line", "column"):
+        setattr(frame, name, getattr(found, name))
+
+
+def symbolize_modules(
+    jobs: List[ModuleFrames],
+    workers: int = DEFAULT_WORKERS,
+    timeout: float = DEFAULT_MODULE_TIMEOUT,
+) -> List[str]:
+    """Symbolize every job's frames in place, one image per task; return a warning per image that ran out of time.
+
+    ``workers`` 1 runs the jobs one after another, still on a worker thread so the time limit holds.
+    """
+    todo: queue.Queue[ModuleFrames] = queue.Queue()
+    for job in jobs:
+        todo.put(job)
+    finished: queue.Queue[_Finished] = queue.Queue()
+    started: Dict[int, float] = {}
+
+    def work() -> None:
+        while True:
+            try:
+                job = todo.get_nowait()
+            except queue.Empty:
+                return
+            try:
+                finished.put((job, _symbolize(job, timeout, started), None))
+            except Exception as exc:
+                finished.put((job, None, exc))
+
+    def spawn() -> None:
+        # Daemon threads: a task given up on (a download that never returns) must not hold up interpreter exit.
+        threading.Thread(target=work, name="symbolize", daemon=True).start()
+
+    for _ in range(min(max(workers, 1), len(jobs))):
+        spawn()
+    pending = {id(job): job for job in jobs}
+    while pending:
+        try:
+            job, result, error = finished.get(timeout=_POLL_SECONDS)
+        except queue.Empty:
+            pass
+        else:
+            if pending.pop(id(job), None) is not None:
+                if error is not None:
+                    raise error
+                found, elapsed = result or ([], 0.0)
+                if elapsed > timeout:
+                    _give_up(job, timeout)
+                else:
+                    for frame, copy in zip(job.frames, found):
+                        _copy_into(frame, copy)
+        now = time.monotonic()
+        for key, job in list(pending.items()):
+            begin = started.get(key)
+            if begin is not None and now - begin > timeout:
+                del pending[key]
+                _give_up(job, timeout)
+                # Its thread is still stuck in it: another one takes the jobs left in the queue.
+                spawn()
+    return _warnings(jobs, timeout)
+
+
+def _give_up(job: ModuleFrames, timeout: float) -> None:
+    log.warning("symbolize: gave up on %s after %gs", job.name, timeout)
+    job.timed_out = True
+
+
+def _warnings(jobs: List[ModuleFrames], timeout: float) -> List[str]:
+    # In the jobs' order, however the tasks finished.
+    return [
+        f"symbolizing {job.name} took over {timeout:g}s: its {len(job.frames)} frames keep raw addresses"
+        for job in jobs
+        if job.timed_out
+    ]
+
+
+__all__ = ["DEFAULT_MODULE_TIMEOUT", "DEFAULT_WORKERS", "ModuleFrames", "symbolize_modules"]
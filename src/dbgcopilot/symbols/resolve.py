@@ -0,0 +1,146 @@
+"""Fill in source file and line for frames a debugger reported without them.
+
+Batch debuggers print ``file:line`` only when they find the debug info
+themselves; frames from a debugger without DWARF support, or printed as a bare
+``0x... in ?? ()``, carry just a pc. `SourceResolver` finds the loaded image
+each pc falls in, opens it, and runs it through a `Symbolizer` with that
+image's load bias. A stripped image's separate debug file is found through
+its build-id or ``.gnu_debuglink`` (see `find_debug_file`), or fetched from a
+symbol server on the `SymbolPath`. Frames in images
+without line tables (stripped system libraries without debug files) keep
+``file`` and ``line`` as None; nothing is guessed.
+
+`SourceResolver.resolve_frames` opens only the images that have a frame to
+resolve, and symbolizes them in parallel, each within a time limit (see
+`dbgcopilot.symbols.parallel`); ``warnings`` collects the ones that
+ran out of time.
+"""
+from __future__ import annotations
+
+import os
+from typing import TYPE_CHECKING, Dict, Iterable, List, Optional, Sequence
+
+from dbgcopilot.analysis.model import Frame, Module
+from dbgcopilot.utils.elf import ElfError
+
+from .debuginfo import DEFAULT_DEBUG_DIRS
+from .dwarf import DwarfError
+from .parallel import DEFAULT_MODULE_TIMEOUT, DEFAULT_WORKERS, ModuleFrames, symbolize_modules
+from .symbolizer import Symbolizer
+
+if TYPE_CHECKING:
+    from .sympath import SymbolPath
+
+
+class SourceResolver:
+    """Symbolizes frames against the images in ``modules``, one `Symbolizer` per file.
+
+    ``Module.base`` must be the address the image's first ``PT_LOAD`` segment
+    was mapped at (``info proc mappings``, ``image list``). ``program`` is used
+    for a module listed without a path whose name matches its basename.
+    ``debug_file`` overrides the debug-file search for ``program``;
+    ``symbol_path``, when given, replaces ``debug_dirs`` for every image.
+    ``workers`` and ``module_timeout`` are as in `symbolize_modules`.
+    """
+
+    def __init__(
+        self,
+        modules: Sequence[Module],
+        program: Optional[str] = None,
+        debug_file: Optional[str] = None,
+        debug_dirs: Sequence[str] = DEFAULT_DEBUG_DIRS,
+        symbol_path: Optional["SymbolPath"] = None,
+        workers: int = DEFAULT_WORKERS,
+        module_timeout: float = DEFAULT_MODULE_TIMEOUT,
+    ) -> None:
+        self.modules = list(modules)
+        self.program = program
+        self.debug_file = debug_file
+        self.debug_dirs = tuple(debug_dirs)
+        self.symbol_path = symbol_path
+        self.workers = workers
+        self.module_timeout = module_timeout
+        # One per image that ran out of time in `resolve_frames`; its frames were left as they were.
+        self.warnings: List[str] = []
+        self._symbolizers: Dict[str, Optional[Symbolizer]] = {}
+
+    def _image(self, frame: Frame) -> Optional[Module]:
+        if frame.pc is None:
+            return None
+        if frame.module:
+            named = [m for m in self.modules if m.name == frame.module and m.base <= frame.pc]
+            if named:
+                return max(named, key=lambda m: m.base)
+        sized = [m for m in self.modules if m.size and m.contains(frame.pc)]
+        if sized:
+            return sized[0]
+        # LLDB lists load addresses only: the nearest image below the pc.
+        below = [m for m in self.modules if not m.size and m.base <= frame.pc]
+        return max(below, key=lambda m: m.base) if below else None
+
+    def _path(self, image: Module) -> Optional[str]:
+        if image.path:
+            return image.path
+        if self.program and os.path.basename(self.program) == image.name:
+            return self.program
+        return None
+
+    def _symbolizer(self, path: str) -> Optional[Symbolizer]:
+        if path not in self._symbolizers:
+            try:
+                is_program = self.program is not None and os.path.realpath(path) == os.path.realpath(self.program)
+                debug_file = self.debug_file if is_program else None
+                self._symbolizers[path] = Symbolizer.open(path, debug_file, self.debug_dirs, self.symbol_path)
+            except (OSError, ElfError, DwarfError):
+                # Gone, unreadable, or not ELF (a PDB-only Windows image): leave its frames alone.
+                self._symbolizers[path] = None
+        return self._symbolizers[path]
+
+    def resolve(self, frame: Frame) -> Frame:
+        """Fill ``frame``'s missing function, file, line, and column in place and return it."""
+        if frame.file is not None:
+            return frame
+        image = self._image(frame)
+        path = self._path(image) if image is not None else None
+        symbolizer = self._symbolizer(path) if path else None
+        if image is None or symbolizer is None:
+            return frame
+        return symbolizer.symbolize(frame, image.base - symbolizer.elf.load_base())
+
+    def resolve_frames(self, frames: Iterable[Frame]) -> List[Frame]:
+        """`resolve` every frame in place, one image per task; return them."""
+        frames = list(frames)
+        jobs: Dict[str, ModuleFrames] = {}
+        for frame in frames:
+            image = self._image(frame) if frame.file is None else None
+            path = self._path(image) if image is not None else None
+            if image is None or path is None:
+                continue
+            if path not in jobs:
+                jobs[path] = ModuleFrames(
+                    image.name,
+                    load=lambda path=path: self._symbolizer(path),
+                    bias=lambda symbolizer, image=image: image.base - symbolizer.elf.load_base(),
+                )
+            jobs[path].frames.append(frame)
+        self.warnings.extend(symbolize_modules(list(jobs.values()), self.workers, self.module_timeout))
+        return frames
+
+
+def resolve_sources(
+    frames: Iterable[Frame],
+    modules: Sequence[Module],
+    program: Optional[str] = None,
+    debug_file: Optional[str] = None,
+    symbol_path: Optional["SymbolPath"] = None,
+    warnings: Optional[List[str]] = None,
+) -> List[Frame]:
+    """`SourceResolver.resolve_frames` for a single backtrace; ``warnings`` collects its `SourceResolver.warnings`."""
+    resolver = SourceResolver(modules, program, debug_file, symbol_path=symbol_path)
+    frames = resolver.resolve_frames(frames)
+    if warnings is not None:
+        warnings.extend(resolver.warnings)
+    return frames
+
+
+__all__ = ["SourceResolver", "resolve_sources"]
@@ -0,0 +1,184 @@
+"""Resolve instruction pointers to function and source location from an ELF file."""
+from __future__ import annotations
+
+from dataclasses import dataclass
+from pathlib import Path
+from typing import TYPE_CHECKING, Iterable, List, Optional, Sequence, Union
+
+from dbgcopilot.analysis.demangle import demangle
+from dbgcopilot.analysis.model import Frame
+from dbgcopilot.utils.elf import ElfError, ElfFile, open_elf
+
+from .cfi import CallFrameTable, parse_call_frames
+from .debuginfo import DEFAULT_DEBUG_DIRS, find_debug_file
+from .dwarf import DwarfError, LineTable, parse_debug_line
+
+if TYPE_CHECKING:
+    from .sympath import SymbolPath
+
+@dataclass
+class SourceLocation:
+    function: Optional[str] = None
+    file: Optional[str] = None
+    line: Optional[int] = None
+    column: Optional[int] = None
+    # Offset of the looked-up address from the start of ``function``.
+    offset: Optional[int] = None
+    # The symbol-table name before `demangle`, when it differs from ``function``.
+    raw_function: Optional[str] = None
+
+
+class Symbolizer:
+    """Function, file, line, and column for addresses in one ELF image.
+
+    Functions come from the symbol table; file/line/column from running the
+    ``.debug_line`` program. Addresses are link-time addresses: subtract the
+    load bias of position-independent images before calling `locate`.
+
+    ``debug_elf`` is the image's separate debug file, if it has one (see
+    `find_debug_file`); its symbol table and line table are used in place of
+    the stripped image's. `call_frames` is the image's unwind information, for
+    stacks without frame pointers.
+    """
+
+    def __init__(self, elf: ElfFile, debug_elf: Optional[ElfFile] = None) -> None:
+        self.elf = elf
+        self.debug_elf = debug_elf
+        # Where ``debug_elf`` was loaded from, and why other candidates were not trusted.
+        self.debug_path: Optional[str] = None
+        self.debug_error: Optional[str] = None
+        self._lines: Optional[LineTable] = None
+        self.line_error: Optional[str] = None
+        self._call_frames: Optional[CallFrameTable] = None
+        self.cfi_error: Optional[str] = None
+
+    @classmethod
+    def open(
+        cls,
+        path: Union[str, Path],
+        debug_file: Optional[Union[str, Path]] = None,
+        debug_dirs: Sequence[str] = DEFAULT_DEBUG_DIRS,
+        symbol_path: Optional["SymbolPath"] = None,
+    ) -> "Symbolizer":
+        return cls.for_image(open_elf(path), str(path), debug_file, debug_dirs, symbol_path)
+
+    @classmethod
+    def for_image(
+        cls,
+        elf: ElfFile,
+        path: str,
+        debug_file: Optional[Union[str, Path]] = None,
+        debug_dirs: Sequence[str] = DEFAULT_DEBUG_DIRS,
+        symbol_path: Optional["SymbolPath"] = None,
+    ) -> "Symbolizer":
+        """A symbolizer for ``elf`` (read from ``path``) that also loads its verified separate debug file.
+
+        ``symbol_path`` is searched instead of ``debug_dirs``, and its servers asked (see `find_debug_file`).
+        """
+        rejected: List[str] = []
+        debug = str(debug_file) if debug_file else None
+        found = find_debug_file(path, elf, debug, debug_dirs, rejected, symbol_path)
+        debug_elf = None
+        if found is not None:
+            try:
+                debug_elf = open_elf(found)
+            except (OSError, ElfError) as exc:
+                rejected.append(f"{found}: {exc}")
+                found = None
+        symbolizer = cls(elf, debug_elf)
+        symbolizer.debug_path, symbolizer.debug_error = found, "; ".join(rejected) or None
+        return symbolizer
+
+    @property
+    def lines(self) -> LineTable:
+        """The decoded line table; empty (with ``line_error`` set) if it is unusable."""
+        return self.load_lines()
+
+    def load_lines(self, deadline: Optional[float] = None) -> LineTable:
+        """`lines`, decoded now if they were not yet; past `time.monotonic` ``deadline`` they stay empty."""
+        if self._lines is None:
+            try:
+                source = self.debug_elf or self.elf
+                data = source.section_data(".debug_line")
+                table = LineTable()
+                if data:
+                    table = parse_debug_line(
+                        data,
+                        line_str=source.section_data(".debug_line_str"),
+                        debug_str=source.section_data(".debug_str"),
+                        deadline=deadline,
+                    )
+            except (DwarfError, ElfError) as exc:
+                table = LineTable()
+                self.line_error = str(exc)
+            self._lines = table
+        return self._lines
+
+    @property
+    def call_frames(self) -> CallFrameTable:
+        """FDEs from ``.eh_frame``, then ``.debug_frame``; empty (with ``cfi_error`` set) if unusable."""
+        if self._call_frames is None:
+            table = CallFrameTable()
+            try:
+                eh_frame = self.elf.section(".eh_frame")
+                data = self.elf.section_data(".eh_frame")
+                if eh_frame is not None and data:
+                    table.add(parse_call_frames(data, eh_frame.sh_addr))
+                # Stripping removes .debug_frame, but the debug file keeps it.
+                for source in (self.debug_elf, self.elf):
+                    data = source.section_data(".debug_frame") if source is not None else None
+                    if data:
+                        table.add(parse_call_frames(data, eh_frame=False))
+                        break
+            except (DwarfError, ElfError) as exc:
+                self.cfi_error = str(exc)
+            self._call_frames = table
+        return self._call_frames
+
+    @property
+    def has_line_info(self) -> bool:
+        return len(self.lines) > 0
+
+    def locate(self, address: int) -> Optional[SourceLocation]:
+        loc = SourceLocation()
+        # The debug file keeps the full .symtab that stripping removed.
+        hit = self.debug_elf.lookup(address) if self.debug_elf is not None else None
+        hit = hit or self.elf.lookup(address)
+        if hit is not None:
+            loc.function, loc.offset = demangle(hit[0].name), hit[1]
+            if loc.function != hit[0].name:
+                loc.raw_function = hit[0].name
+        row = self.lines.lookup(address)
+        if row is not None:
+            loc.file, loc.line, loc.column = row.file, row.line, row.column or None
+        if loc.function is None and loc.file is None:
+            return None
+        return loc
+
+    def symbolize(self, frame: Frame, bias: int = 0, *, is_return_address: Optional[bool] = None) -> Frame:
+        """Fill ``frame``'s missing function/source fields in place and return it.
+
+        Return addresses (every frame but #0 by default) point after the call,
+        so the call instruction itself (pc - 1) is looked up instead.
+        """
+        if frame.pc is None:
+            return frame
+        if is_return_address is None:
+            is_return_address = frame.index > 0
+        back = 1 if is_return_address else 0
+        loc = self.locate(frame.pc - bias - back)
+        if loc is None:
+            return frame
+        if frame.function is None and loc.function is not None:
+            # The offset is from the real pc, as debuggers print it.
+            frame.function, frame.offset = loc.function, (loc.offset or 0) + back
+            frame.raw_symbol = loc.raw_function
+        if frame.file is None and loc.file is not None:
+            frame.file, frame.line, frame.column = loc.file, loc.line, loc.column
+        return frame
+
+    def symbolize_frames(self, frames: Iterable[Frame], bias: int = 0) -> List[Frame]:
+        return [self.symbolize(f, bias) for f in frames]
+
+
+__all__ = ["SourceLocation", "Symbolizer", "demangle"]
@@ -0,0 +1,144 @@
+"""Symbol paths: where debug files are looked for, and which servers may supply them.
+
+The syntax is DbgEng's (``_NT_SYMBOL_PATH``), so one setting serves cdb and the
+ELF readers alike. Entries are separated by ``;``:
+
+- ``/srv/symbols``: a local directory, searched like ``/usr/lib/debug``
+  (``.build-id/ab/cdef....debug`` and debuglink paths);
+- ``srv*CACHE*URL``: a symbol server, with downloads kept in ``CACHE``;
+  ``srv*URL`` keeps them in the ``cache*DIR`` entry's directory if there is
+  one, else in `default_symbol_cache`;
+- ``cache*DIR``: the cache for servers listed without one.
+
+cdb is handed the text as is and downloads PDBs itself. For ELF images the
+servers are debuginfod servers (``https://debuginfod.elfutils.org``): a
+missing debug file is fetched by build-id from ``URL/buildid/<id>/debuginfo``
+and stored in the cache under the ``.build-id`` layout, which makes the cache
+one more debug directory and the next run a local hit. A server that is down,
+slow, or does not have the file is recorded as a rejected candidate and the
+next one is tried; with none left the frame keeps its module and address.
+"""
+from __future__ import annotations
+
+import os
+import tempfile
+from dataclasses import dataclass
+from typing import List, Optional, Tuple
+
+from dbgcopilot.utils.config import user_cache_dir
+
+from .debuginfo import DEFAULT_DEBUG_DIRS
+
+# Seconds to wait for one debug file (connecting, then each chunk).
+FETCH_TIMEOUT = 30.0
+_SERVER_PREFIXES = ("srv*", "symsrv*symsrv.dll*")
+
+
+def default_symbol_cache() -> str:
+    """``dbgcopilot/symbols`` in the platform's cache directory (see `user_cache_dir`)."""
+    return os.path.join(user_cache_dir(), "symbols")
+
+
+class SymbolFetchError(RuntimeError):
+    """A symbol server did not deliver a debug file."""
+
+
+@dataclass(frozen=True)
+class SymbolServer:
+    url: str
+    # Directory downloads are kept in, laid out like a debug directory.
+    cache: str
+
+    def cached_path(self, build_id: str) -> str:
+        return os.path.join(self.cache, ".build-id", build_id[:2], build_id[2:] + ".debug")
+
+    def fetch(self, build_id: str, timeout: float = FETCH_TIMEOUT) -> str:
+        """Download the debug file of ``build_id`` into the cache and return its path; raises `SymbolFetchError`."""
+        path = self.cached_path(build_id)
+        if os.path.isfile(path):
+            return path
+        try:
+            import requests
+        except ImportError as exc:  # pragma: no cover - a dependency of the package
+            raise SymbolFetchError(f"{self.url}: the requests library is required") from exc
+        url = f"{self.url.rstrip('/')}/buildid/{build_id}/debuginfo"
+        try:
+            with requests.get(url, stream=True, timeout=timeout) as resp:
+                if resp.status_code != 200:
+                    raise SymbolFetchError(f"{url}: HTTP {resp.status_code}")
+                os.makedirs(os.path.dirname(path), exist_ok=True)
+                # Written aside and renamed, so an interrupted download never looks like a cached file.
+                fd, partial = tempfile.mkstemp(dir=os.path.dirname(path), suffix=".part")
+                try:
+                    with os.fdopen(fd, "wb") as fh:
+                        for chunk in resp.iter_content(1 << 16):
+                            fh.write(chunk)
+                    os.replace(partial, path)
+                except BaseException:
+                    os.unlink(partial)
+                    raise
+        except requests.RequestException as exc:
+            raise SymbolFetchError(f"{url}: {exc}") from exc
+        except OSError as exc:
+            raise SymbolFetchError(f"{url}: cannot store the download in {self.cache}: {exc}") from exc
+        return path
+
+
+@dataclass(frozen=True)
+class SymbolPath:
+    """A parsed symbol path; ``text`` is what it was parsed from, for cdb."""
+
+    text: str
+    directories: Tuple[str, ...] = ()
+    servers: Tuple[SymbolServer, ...] = ()
+
+    @classmethod
+    def parse(cls, text: str, default_cache: Optional[str] = None) -> "SymbolPath":
+        """Parse DbgEng syntax (see the module docstring); raises ValueError for a server without a URL."""
+        directories: List[str] = []
+        entries: List[Tuple[Optional[str], str]] = []
+        cache = None
+        for entry in (e.strip() for e in text.split(";")):
+            lowered = entry.lower()
+            if not entry:
+                continue
+            if lowered.startswith("cache*"):
+                cache = os.path.expanduser(entry[len("cache*"):]) or None
+                continue
+            prefix = next((p for p in _SERVER_PREFIXES if lowered.startswith(p)), None)
+            if prefix is None:
+                directories.append(os.path.expanduser(entry))
+                continue
+            # srv*URL or srv*CACHE*URL; a chain of caches (srv*A*B*URL) keeps the first.
+            parts = [p for p in entry[len(prefix):].split("*") if p]
+            if not parts or "://" not in parts[-1]:
+                raise ValueError(f"symbol path entry {entry!r} has no server URL (expected srv*CACHE*URL)")
+            entries.append((os.path.expanduser(parts[0]) if len(parts) > 1 else None, parts[-1]))
+        fallback = cache or default_cache or default_symbol_cache()
+        servers = tuple(SymbolServer(url, own or fallback) for own, url in entries)
+        return cls(text, tuple(directories), servers)
+
+    @property
+    def debug_dirs(self) -> Tuple[str, ...]:
+        """The directories, then the server caches, then the system's debug directories."""
+        dirs = list(self.directories) + [s.cache for s in self.servers] + list(DEFAULT_DEBUG_DIRS)
+        return tuple(dict.fromkeys(dirs))
+
+    def fetch_debug_file(self, build_id: str, rejected: Optional[List[str]] = None) -> Optional[str]:
+        """The first server's copy of ``build_id``'s debug file, or None; failures go to ``rejected``."""
+        for server in self.servers:
+            try:
+                return server.fetch(build_id)
+            except SymbolFetchError as exc:
+                if rejected is not None:
+                    rejected.append(str(exc))
+        return None
+
+
+__all__ = [
+    "FETCH_TIMEOUT",
+    "SymbolFetchError",
+    "SymbolPath",
+    "SymbolServer",
+    "default_symbol_cache",
+]
@@ -0,0 +1,275 @@
+"""Settings from ``dbgcopilot.toml``, the environment, and the command line.
+
+Provider, model, redaction rules, and timeouts tend to be the same on every
+run of a project. `load_settings` reads them in three layers, each
+overriding the one before:
+
+1. ``dbgcopilot.toml``: the file named by ``$DBGCOPILOT_CONFIG``, else
+   ``./dbgcopilot.toml``, else ``$XDG_CONFIG_HOME/dbgcopilot/dbgcopilot.toml``
+   (``~/.config`` by default). Only the first file found is read.
+2. ``DBGCOPILOT_<SECTION>_<KEY>`` environment variables, e.g.
+   ``DBGCOPILOT_LLM_MODEL`` for ``[llm] model``.
+3. Command-line flags.
+
+A value that does not fit its key raises `ConfigError`, naming the layer it
+came from and the order the layers are read in, so a stale variable is not
+mistaken for a typo in the file.
+
+```toml
+[llm]
+provider = "ollama"
+model = "llama3.1"
+base_url = "http://gpu-box.internal:11434"
+
+[analysis]
+timeout = 10
+```
+"""
+from __future__ import annotations
+
+import os
+import sys
+import tomllib
+from dataclasses import dataclass, field
+from typing import Any, Dict, List, Mapping, Optional, Tuple
+
+CONFIG_FILENAME = "dbgcopilot.toml"
+CONFIG_ENV_VAR = "DBGCOPILOT_CONFIG"
+ENV_PREFIX = "DBGCOPILOT_"
+
+PRECEDENCE = "command-line flags override DBGCOPILOT_* environment variables, which override dbgcopilot.toml"
+
+# Dotted key -> (type, choices). Lists may also be given as JSON or comma-separated text.
+_KEYS: Dict[str, Tuple[type, Tuple[str, ...]]] = {
+    "llm.provider": (str, ()),
+    "llm.model": (str, ()),
+    "llm.base_url": (str, ()),
+    "llm.max_prompt_tokens": (int, ()),
+    "llm.prompt_template": (str, ()),
+    "llm.prompt_fault_context": (bool, ()),
+    "llm.max_retries": (int, ()),
+    "llm.base_delay": (float, ()),
+    "llm.max_delay": (float, ()),
+    "llm.max_cost": (float, ()),
+    "llm.prices": (list, ()),
+    "analysis.timeout": (float, ()),
+    "analysis.debugger": (str, ("gdb", "lldb")),
+    "analysis.ignore_signals": (list, ()),
+    "analysis.max_frames_per_thread": (int, ()),
+    "analysis.exception_policy": (str, ("first-chance", "second-chance")),
+    "symbols.path": (str, ()),
+    "history.enabled": (bool, ()),
+    "history.path": (str, ()),
+    "dumps.dirs": (list, ()),
+    "redact.rules": (list, ()),
+    "redact.keep_env": (list, ()),
+    "frames.user": (list, ()),
+    "frames.runtime": (list, ()),
+}
+# Keys whose value must be an http(s) URL.
+_URL_KEYS = frozenset({"llm.base_url"})
+_EXPECTED = {str: "a string", int: "an integer", float: "a number", list: "a list", bool: "true or false"}
+_BOOL_TEXT = {"true": True, "1": True, "yes": True, "on": True, "false": False, "0": False, "no": False, "off": False}
+# Keys passed through to the LLM session config under their historical names.
+_LLM_CONFIG_KEYS = {
+    "llm.max_prompt_tokens": "max_prompt_tokens",
+    "llm.prompt_template": "prompt_template",
+    "llm.prompt_fault_context": "prompt_fault_context",
+    "llm.max_retries": "max_retries",
+    "llm.base_delay": "base_delay",
+    "llm.max_delay": "max_delay",
+    "llm.max_cost": "max_cost",
+    "llm.prices": "prices",
+    "redact.rules": "redact_rules",
+    "redact.keep_env": "redact_keep_env",
+    "frames.user": "user_frames",
+    "frames.runtime": "runtime_frames",
+}
+
+
+class ConfigError(ValueError):
+    """A setting has the wrong type or is not a known key."""
+
+
+def env_var(key: str) -> str:
+    """``llm.model`` -> ``DBGCOPILOT_LLM_MODEL``."""
+    return ENV_PREFIX + key.replace(".", "_").upper()
+
+
+def find_config_file(environ: Optional[Mapping[str, str]] = None, cwd: Optional[str] = None) -> Optional[str]:
+    """The config file `load_settings` reads, or None; ``$DBGCOPILOT_CONFIG`` must exist if set."""
+    environ = os.environ if environ is None else environ
+    explicit = environ.get(CONFIG_ENV_VAR)
+    if explicit:
+        path = os.path.abspath(os.path.expanduser(explicit))
+        if not os.path.isfile(path):
+            raise ConfigError(f"${CONFIG_ENV_VAR} names {path}, which is not a file")
+        return path
+    local = os.path.join(cwd or os.getcwd(), CONFIG_FILENAME)
+    if os.path.isfile(local):
+        return os.path.abspath(local)
+    base = environ.get("XDG_CONFIG_HOME") or os.path.join(os.path.expanduser("~"), ".config")
+    user = os.path.join(base, "dbgcopilot", CONFIG_FILENAME)
+    return user if os.path.isfile(user) else None
+
+
+def user_data_dir() -> str:
+    """``dbgcopilot`` in the platform's directory for data kept across runs.
+
+    That is ``%LOCALAPPDATA%`` on Windows, ``~/Library/Application Support`` on
+    macOS, and ``$XDG_DATA_HOME`` (``~/.local/share`` by default) elsewhere.
+    """
+    home = os.path.expanduser("~")
+    if sys.platform == "win32":
+        base = os.environ.get("LOCALAPPDATA") or os.path.join(home, "AppData", "Local")
+    elif sys.platform == "darwin":
+        base = os.path.join(home, "Library", "Application Support")
+    else:
+        base = os.environ.get("XDG_DATA_HOME") or os.path.join(home, ".local", "share")
+    return os.path.join(base, "dbgcopilot")
+
+
+def user_cache_dir() -> str:
+    """``dbgcopilot`` in the platform's cache directory.
+
+    That is ``%LOCALAPPDATA%`` on Windows, ``~/Library/Caches`` on macOS, and
+    ``$XDG_CACHE_HOME`` (``~/.cache`` by default) elsewhere.
+    """
+    home = os.path.expanduser("~")
+    if sys.platform == "win32":
+        base = os.environ.get("LOCALAPPDATA") or os.path.join(home, "AppData", "Local")
+    elif sys.platform == "darwin":
+        base = os.path.join(home, "Library", "Caches")
+    else:
+        base = os.environ.get("XDG_CACHE_HOME") or os.path.join(home, ".cache")
+    return os.path.join(base, "dbgcopilot")
+
+
+def _flatten(data: Mapping[str, Any], path: str) -> Dict[str, Any]:
+    flat: Dict[str, Any] = {}
+    for section, table in data.items():
+        if not isinstance(table, Mapping):
+            raise ConfigError(f"{path}: {section!r} must be a table such as [{section}], got {table!r}")
+        for name, value in table.items():
+            flat[f"{section}.{name}"] = value
+    unknown = sorted(set(flat) - set(_KEYS))
+    if unknown:
+        raise ConfigError(f"{path}: unknown key {unknown[0]} (known keys: {', '.join(sorted(_KEYS))})")
+    return flat
+
+
+def _coerce(kind: type, value: Any) -> Any:
+    """``value`` as ``kind``, or None if it is not one; text (from the environment) is parsed."""
+    if kind is list:
+        return value if isinstance(value, (list, str)) else None
+    if kind is str:
+        return value if isinstance(value, str) else None
+    if kind is bool:
+        if isinstance(value, str):
+            return _BOOL_TEXT.get(value.strip().lower())
+        return value if isinstance(value, bool) else None
+    if isinstance(value, str):
+        try:
+            return kind(value.strip())
+        except ValueError:
+            return None
+    if isinstance(value, bool):
+        return None
+    if isinstance(value, int) or (kind is float and isinstance(value, float)):
+        return kind(value)
+    return None
+
+
+def _convert(key: str, value: Any, source: str) -> Any:
+    kind, choices = _KEYS[key]
+    converted = _coerce(kind, value)
+    if converted is None or (choices and converted not in choices):
+        expected = " or ".join(repr(c) for c in choices) if choices else _EXPECTED[kind]
+        raise ConfigError(f"{key} must be {expected}, got {value!r} (from {source}; {PRECEDENCE})")
+    if kind in (int, float) and converted < 0:
+        raise ConfigError(f"{key} must not be negative, got {value!r} (from {source}; {PRECEDENCE})")
+    if key in _URL_KEYS and not converted.startswith(("http://", "https://")):
+        raise ConfigError(f"{key} must be an http:// or https:// URL, got {value!r} (from {source}; {PRECEDENCE})")
+    return converted
+
+
+@dataclass
+class Settings:
+    """Resolved settings; ``sources`` says which layer set each key."""
+
+    values: Dict[str, Any] = field(default_factory=dict)
+    sources: Dict[str, str] = field(default_factory=dict)
+    # The config file that was read, if any.
+    path: Optional[str] = None
+
+    def get(self, key: str, default: Any = None) -> Any:
+        return self.values.get(key, default)
+
+    def llm_config(self) -> Dict[str, Any]:
+        """The LLM session config these settings amount to (see docs/llm.md)."""
+        config: Dict[str, Any] = {}
+        provider = self.get("llm.provider")
+        if provider:
+            config["llm_provider"] = provider
+            # Providers read their model from ``<provider>_model``, and their endpoint from ``<provider>_base_url``.
+            prefix = provider.replace("-", "_")
+            if self.get("llm.model"):
+                config[f"{prefix}_model"] = self.get("llm.model")
+            if self.get("llm.base_url"):
+                config[f"{prefix}_base_url"] = self.get("llm.base_url")
+        for key, name in _LLM_CONFIG_KEYS.items():
+            if key in self.values:
+                config[name] = self.values[key]
+        return config
+
+
+def load_settings(
+    flags: Optional[Mapping[str, Any]] = None,
+    path: Optional[str] = None,
+    environ: Optional[Mapping[str, str]] = None,
+) -> Settings:
+    """Layer the config file, ``DBGCOPILOT_*`` variables, and ``flags`` (dotted keys; None means unset).
+
+    ``path`` overrides the config file search. Raises `ConfigError`.
+    """
+    environ = os.environ if environ is None else environ
+    path = path if path is not None else find_config_file(environ)
+    settings = Settings(path=path)
+    layers: List[Tuple[str, Dict[str, Any]]] = []
+    if path is not None:
+        try:
+            with open(path, "rb") as fh:
+                data = tomllib.load(fh)
+        except OSError as exc:
+            raise ConfigError(f"cannot read {path}: {exc}") from exc
+        except tomllib.TOMLDecodeError as exc:
+            raise ConfigError(f"{path} is not valid TOML: {exc}") from exc
+        layers.append((path, _flatten(data, path)))
+    for key in _KEYS:
+        raw = environ.get(env_var(key))
+        if raw:
+            layers.append((f"${env_var(key)}", {key: raw}))
+    cli = {k: v for k, v in (flags or {}).items() if v is not None}
+    unknown = sorted(set(cli) - set(_KEYS))
+    if unknown:
+        raise ConfigError(f"unknown setting {unknown[0]}")
+    layers.append(("the command line", cli))
+    for source, values in layers:
+        for key, value in values.items():
+            settings.values[key] = _convert(key, value, source)
+            settings.sources[key] = source
+    return settings
+
+
+__all__ = [
+    "CONFIG_ENV_VAR",
+    "CONFIG_FILENAME",
+    "ConfigError",
+    "PRECEDENCE",
+    "Settings",
+    "env_var",
+    "find_config_file",
+    "load_settings",
+    "user_cache_dir",
+    "user_data_dir",
+]
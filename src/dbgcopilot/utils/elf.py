@@ -0,0 +1,283 @@
+"""Minimal ELF reader used by the core-dump and symbolization paths.
+
+Only what the analyzers need is parsed: the file header, program headers,
+section headers, the symbol tables, and the build-id and debuglink that name a
+separate debug file. Supports 64-bit little-endian files.
+"""
+from __future__ import annotations
+
+import bisect
+import mmap
+import struct
+import zlib
+from dataclasses import dataclass, field
+from pathlib import Path
+from typing import List, Optional, Tuple, Union
+
+ELF_MAGIC = b"\x7fELF"
+ELFCLASS64 = 2
+ELFDATA2LSB = 1
+
+ET_EXEC = 2
+ET_DYN = 3
+ET_CORE = 4
+
+EM_386 = 3
+EM_PPC64 = 21
+EM_S390 = 22
+EM_ARM = 40
+EM_X86_64 = 62
+EM_AARCH64 = 183
+EM_RISCV = 243
+EM_LOONGARCH = 258
+
+PT_LOAD = 1
+PT_NOTE = 4
+
+SHT_SYMTAB = 2
+SHT_NOBITS = 8
+SHT_DYNSYM = 11
+
+SHF_COMPRESSED = 0x800
+ELFCOMPRESS_ZLIB = 1
+
+STT_FUNC = 2
+
+NT_GNU_BUILD_ID = 3
+
+_MACHINE_NAMES = {
+    EM_386: "i386",
+    EM_PPC64: "ppc64",
+    EM_S390: "s390x",
+    EM_ARM: "arm",
+    EM_X86_64: "x86_64",
+    EM_AARCH64: "aarch64",
+    EM_RISCV: "riscv",
+    EM_LOONGARCH: "loongarch",
+}
+
+
+def machine_name(machine: int) -> str:
+    return _MACHINE_NAMES.get(machine, f"machine-{machine}")
+
+
+class ElfError(ValueError):
+    """Raised when a file is not a supported or well-formed ELF image."""
+
+
+@dataclass
+class ProgramHeader:
+    p_type: int
+    p_flags: int
+    p_offset: int
+    p_vaddr: int
+    p_filesz: int
+    p_memsz: int
+
+
+@dataclass
+class Section:
+    name: str
+    sh_type: int
+    sh_addr: int
+    sh_offset: int
+    sh_size: int
+    sh_link: int
+    sh_entsize: int
+    sh_flags: int = 0
+
+
+@dataclass
+class Symbol:
+    name: str
+    value: int
+    size: int
+
+
+@dataclass
+class ElfFile:
+    """Parsed view over an ELF image held in memory (bytes or mmap)."""
+
+    data: Union[bytes, mmap.mmap]
+    e_type: int
+    machine: int
+    entry: int
+    program_headers: List[ProgramHeader] = field(default_factory=list)
+    sections: List[Section] = field(default_factory=list)
+    _symbols: Optional[List[Symbol]] = None
+    _symbol_addrs: Optional[List[int]] = None
+
+    @property
+    def arch(self) -> str:
+        return machine_name(self.machine)
+
+    def read(self, offset: int, size: int) -> bytes:
+        if offset < 0 or size < 0 or offset + size > len(self.data):
+            raise ElfError(f"read of {size} bytes at offset {offset:#x} is outside the file")
+        return bytes(self.data[offset : offset + size])
+
+    def section(self, name: str) -> Optional[Section]:
+        for sec in self.sections:
+            if sec.name == name:
+                return sec
+        return None
+
+    def section_data(self, name: str) -> Optional[bytes]:
+        """Contents of a section, inflated first if it is SHF_COMPRESSED (zlib)."""
+        sec = self.section(name)
+        if sec is None or sec.sh_type == SHT_NOBITS:
+            return None
+        raw = self.read(sec.sh_offset, sec.sh_size)
+        if not sec.sh_flags & SHF_COMPRESSED:
+            return raw
+        if len(raw) < 24:
+            raise ElfError(f"{name}: compression header is truncated")
+        ch_type, _reserved, ch_size, _align = struct.unpack_from("<IIQQ", raw, 0)
+        if ch_type != ELFCOMPRESS_ZLIB:
+            raise ElfError(f"{name}: unsupported compression type {ch_type}")
+        try:
+            data = zlib.decompress(raw[24:])
+        except zlib.error as exc:
+            raise ElfError(f"{name}: {exc}") from exc
+        if len(data) != ch_size:
+            raise ElfError(f"{name}: inflated to {len(data)} bytes, expected {ch_size}")
+        return data
+
+    def build_id(self) -> Optional[str]:
+        """The GNU build-id note as lowercase hex, if the image has one."""
+        data = self.section_data(".note.gnu.build-id")
+        if not data or len(data) < 12:
+            return None
+        namesz, descsz, n_type = struct.unpack_from("<III", data, 0)
+        desc = 12 + (namesz + 3) // 4 * 4
+        if n_type != NT_GNU_BUILD_ID or desc + descsz > len(data):
+            return None
+        return data[desc : desc + descsz].hex()
+
+    def debuglink(self) -> Optional[Tuple[str, int]]:
+        """(file name, CRC-32) from ``.gnu_debuglink``, naming the separate debug file."""
+        data = self.section_data(".gnu_debuglink")
+        if not data:
+            return None
+        end = data.find(b"\x00")
+        # The name is NUL-padded to a 4-byte boundary, then the CRC follows.
+        crc_at = (end + 4) // 4 * 4
+        if end <= 0 or crc_at + 4 > len(data):
+            return None
+        return data[:end].decode("utf-8", errors="replace"), struct.unpack_from("<I", data, crc_at)[0]
+
+    def load_base(self) -> int:
+        """Lowest (page-aligned) virtual address among PT_LOAD segments."""
+        loads = [ph.p_vaddr - (ph.p_offset & 0xFFF) for ph in self.program_headers if ph.p_type == PT_LOAD]
+        return min(loads) if loads else 0
+
+    def symbols(self) -> List[Symbol]:
+        """Function symbols from .symtab (preferred) or .dynsym, sorted by address."""
+        if self._symbols is not None:
+            return self._symbols
+        found: dict[Tuple[int, str], Symbol] = {}
+        for sec in self.sections:
+            if sec.sh_type not in (SHT_SYMTAB, SHT_DYNSYM) or not sec.sh_entsize:
+                continue
+            if sec.sh_link >= len(self.sections):
+                continue
+            strtab = self.sections[sec.sh_link]
+            count = sec.sh_size // sec.sh_entsize
+            for i in range(count):
+                off = sec.sh_offset + i * sec.sh_entsize
+                if off + 24 > len(self.data):
+                    break
+                st_name, st_info, _other, _shndx, st_value, st_size = struct.unpack_from("<IBBHQQ", self.data, off)
+                if (st_info & 0xF) != STT_FUNC or st_value == 0:
+                    continue
+                name = _read_cstr(self.data, strtab.sh_offset + st_name)
+                if name:
+                    found.setdefault((st_value, name), Symbol(name=name, value=st_value, size=st_size))
+        syms = sorted(found.values(), key=lambda s: (s.value, -s.size))
+        self._symbols = syms
+        self._symbol_addrs = [s.value for s in syms]
+        return syms
+
+    def lookup(self, addr: int) -> Optional[Tuple[Symbol, int]]:
+        """Return (symbol, offset) covering a link-time address, if any."""
+        syms = self.symbols()
+        addrs = self._symbol_addrs or []
+        idx = bisect.bisect_right(addrs, addr) - 1
+        if idx < 0:
+            return None
+        # Check a few neighbours so aliases/nested symbols at the same address still match.
+        for sym in reversed(syms[max(0, idx - 3) : idx + 1]):
+            if sym.size and sym.value <= addr < sym.value + sym.size:
+                return sym, addr - sym.value
+        nearest = syms[idx]
+        if nearest.size == 0:
+            return nearest, addr - nearest.value
+        return None
+
+
+def _read_cstr(data: Union[bytes, mmap.mmap], offset: int, limit: int = 4096) -> str:
+    if offset < 0 or offset >= len(data):
+        return ""
+    end = data.find(b"\x00", offset, min(len(data), offset + limit))
+    if end < 0:
+        end = min(len(data), offset + limit)
+    return bytes(data[offset:end]).decode("utf-8", errors="replace")
+
+
+def parse_elf(data: Union[bytes, mmap.mmap]) -> ElfFile:
+    if len(data) < 64:
+        raise ElfError("file is too small to contain an ELF header")
+    if bytes(data[:4]) != ELF_MAGIC:
+        raise ElfError("missing ELF magic")
+    if data[4] != ELFCLASS64 or data[5] != ELFDATA2LSB:
+        # Still name the machine so callers can report what the file is for.
+        machine = struct.unpack_from("<H" if data[5] == ELFDATA2LSB else ">H", data, 18)[0]
+        bits = 64 if data[4] == ELFCLASS64 else 32
+        order = "little" if data[5] == ELFDATA2LSB else "big"
+        raise ElfError(
+            f"only 64-bit little-endian ELF files are supported ({machine_name(machine)}, {bits}-bit {order}-endian)"
+        )
+    (e_type, e_machine, _version, e_entry, e_phoff, e_shoff, _flags, _ehsize,
+     e_phentsize, e_phnum, e_shentsize, e_shnum, e_shstrndx) = struct.unpack_from("<HHIQQQIHHHHHH", data, 16)
+    elf = ElfFile(data=data, e_type=e_type, machine=e_machine, entry=e_entry)
+
+    if e_phnum:
+        if e_phoff + e_phnum * e_phentsize > len(data):
+            raise ElfError("program header table is truncated")
+        for i in range(e_phnum):
+            p_type, p_flags, p_offset, p_vaddr, _paddr, p_filesz, p_memsz, _align = struct.unpack_from(
+                "<IIQQQQQQ", data, e_phoff + i * e_phentsize
+            )
+            elf.program_headers.append(ProgramHeader(p_type, p_flags, p_offset, p_vaddr, p_filesz, p_memsz))
+
+    if e_shnum and e_shoff and e_shoff + e_shnum * e_shentsize <= len(data):
+        raw: List[Tuple[int, ...]] = []
+        for i in range(e_shnum):
+            raw.append(struct.unpack_from("<IIQQQQIIQQ", data, e_shoff + i * e_shentsize))
+        strtab_off = raw[e_shstrndx][4] if e_shstrndx < len(raw) else 0
+        for (sh_name, sh_type, sh_flags, sh_addr, sh_offset, sh_size, sh_link, _info, _align, sh_entsize) in raw:
+            name = _read_cstr(data, strtab_off + sh_name) if strtab_off else ""
+            elf.sections.append(Section(name, sh_type, sh_addr, sh_offset, sh_size, sh_link, sh_entsize, sh_flags))
+    return elf
+
+
+def open_elf(path: Union[str, Path]) -> ElfFile:
+    """Memory-map and parse an ELF file from disk."""
+    with open(path, "rb") as fh:
+        try:
+            mapped = mmap.mmap(fh.fileno(), 0, access=mmap.ACCESS_READ)
+        except ValueError as exc:  # empty file
+            raise ElfError(f"{path}: {exc}") from exc
+    return parse_elf(mapped)
+
+
+__all__ = [
+    "ElfError",
+    "ElfFile",
+    "ProgramHeader",
+    "Section",
+    "Symbol",
+    "machine_name",
+    "open_elf",
+    "parse_elf",
+]
@@ -4,7 +4,9 @@ ANSI color helpers for optional colored output in REPL and summaries.
 """
 from __future__ import annotations
 
+import os
 import re
+from typing import Any, Mapping, Optional
 
 
 ANSI_RE = re.compile(r"\x1b(?:\[[0-9;?]*[ -/]*[@-~]|[@-~])")
@@ -48,3 +50,24 @@ def color_text(s: str, color: str | None = None, bold: bool = False, enable: boo
     prefix = (_CODES["bold"] if bold else "") + _CODES[color]
     return f"{prefix}{s}{_CODES['reset']}"
 
+
+# What ``--color`` accepts.
+COLOR_MODES = ("auto", "always", "never")
+
+
+def color_enabled(stream: Any, mode: str = "auto", environ: Optional[Mapping[str, str]] = None) -> bool:
+    """Whether output to ``stream`` should be colored.
+
+    ``always`` and ``never`` decide on their own. ``auto`` colors a terminal,
+    unless ``NO_COLOR`` is set and not empty (https://no-color.org) or ``TERM``
+    is ``dumb``.
+    """
+    if mode not in COLOR_MODES:
+        raise ValueError(f"unknown color mode {mode!r} (expected one of {', '.join(COLOR_MODES)})")
+    if mode != "auto":
+        return mode == "always"
+    env = os.environ if environ is None else environ
+    if env.get("NO_COLOR") or env.get("TERM") == "dumb":
+        return False
+    isatty = getattr(stream, "isatty", None)
+    return bool(isatty and isatty())
@@ -0,0 +1,71 @@
+"""Refusing every outbound connection for a block of code.
+
+``--dry-run`` promises that nothing leaves the machine, which ``--no-llm``
+alone does not: a symbol server or a debugger's debuginfod client would
+still download. `no_network` makes the promise hold for this process.
+While it is active, every socket connect and name lookup raises
+`NetworkBlockedError`, whichever library makes it, so a code path that
+forgets the dry run fails rather than sends. Unix sockets are still allowed,
+since they never leave the machine. ``DEBUGINFOD_URLS`` is emptied, so the
+debuggers started inside the block do not fetch debug info either.
+
+The program being debugged is not sandboxed: a run executes it as usual.
+Analyzing a core (``--core``) runs nothing.
+"""
+from __future__ import annotations
+
+import contextlib
+import os
+import socket
+from typing import Any, Iterator
+
+DEBUGINFOD_ENV = "DEBUGINFOD_URLS"
+
+
+class NetworkBlockedError(ConnectionError):
+    """An outbound connection was attempted inside `no_network`."""
+
+
+def _local(sock: socket.socket) -> bool:
+    return getattr(socket, "AF_UNIX", None) is not None and sock.family == socket.AF_UNIX
+
+
+@contextlib.contextmanager
+def no_network(reason: str = "network access is disabled") -> Iterator[None]:
+    """Within the block, raise `NetworkBlockedError` (with ``reason``) for any connect or DNS lookup."""
+    connect, connect_ex = socket.socket.connect, socket.socket.connect_ex
+    resolvers = {name: getattr(socket, name) for name in ("getaddrinfo", "gethostbyname", "create_connection")}
+    debuginfod = os.environ.get(DEBUGINFOD_ENV)
+
+    def refuse(*args: Any, **kwargs: Any) -> Any:
+        raise NetworkBlockedError(reason)
+
+    def guarded_connect(sock: socket.socket, address: Any) -> None:
+        if not _local(sock):
+            refuse()
+        connect(sock, address)
+
+    def guarded_connect_ex(sock: socket.socket, address: Any) -> int:
+        if not _local(sock):
+            refuse()
+        return connect_ex(sock, address)
+
+    socket.socket.connect = guarded_connect  # type: ignore[method-assign]
+    socket.socket.connect_ex = guarded_connect_ex  # type: ignore[method-assign]
+    for name in resolvers:
+        setattr(socket, name, refuse)
+    os.environ[DEBUGINFOD_ENV] = ""
+    try:
+        yield
+    finally:
+        socket.socket.connect = connect  # type: ignore[method-assign]
+        socket.socket.connect_ex = connect_ex  # type: ignore[method-assign]
+        for name, original in resolvers.items():
+            setattr(socket, name, original)
+        if debuginfod is None:
+            os.environ.pop(DEBUGINFOD_ENV, None)
+        else:
+            os.environ[DEBUGINFOD_ENV] = debuginfod
+
+
+__all__ = ["DEBUGINFOD_ENV", "NetworkBlockedError", "no_network"]
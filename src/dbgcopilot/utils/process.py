@@ -0,0 +1,67 @@
+"""Stopping programs that outlive their time limit, children and all.
+
+A debugger killed on timeout leaves its inferior behind, and a target that
+forks leaves its workers. `kill_tree` kills a process and every descendant it
+can find before reaping it, so a timed-out run leaks neither processes nor
+zombies. Descendants are found through ``/proc`` parent links and, for
+processes started with ``start_new_session=True``, their process group; on
+platforms without either only the process itself is killed.
+"""
+from __future__ import annotations
+
+import os
+import signal
+import subprocess
+from typing import Dict, List
+
+
+def descendants(pid: int) -> List[int]:
+    """Processes below ``pid``, parents first; empty where ``/proc`` is unavailable."""
+    children: Dict[int, List[int]] = {}
+    try:
+        entries = os.listdir("/proc")
+    except OSError:
+        return []
+    for entry in entries:
+        if not entry.isdigit():
+            continue
+        try:
+            with open(f"/proc/{entry}/stat", encoding="utf-8", errors="replace") as fh:
+                stat = fh.read()
+            # "pid (comm) state ppid ...": comm may hold spaces and parentheses.
+            ppid = int(stat[stat.rindex(")") + 2:].split()[1])
+        except (OSError, ValueError, IndexError):
+            continue
+        children.setdefault(ppid, []).append(int(entry))
+    found: List[int] = []
+    pending = [pid]
+    while pending:
+        for child in children.get(pending.pop(0), []):
+            found.append(child)
+            pending.append(child)
+    return found
+
+
+def kill_tree(proc: "subprocess.Popen[bytes]") -> None:
+    """SIGKILL ``proc`` and its descendants, then reap ``proc``."""
+    # Collected first: once the parent dies its children are re-parented out of reach.
+    targets = [proc.pid, *descendants(proc.pid)]
+    killpg = getattr(os, "killpg", None)
+    if killpg is not None:
+        try:
+            if os.getpgid(proc.pid) == proc.pid:
+                killpg(proc.pid, signal.SIGKILL)
+        except OSError:
+            pass
+    for pid in targets:
+        try:
+            if pid == proc.pid:
+                proc.kill()
+            else:
+                os.kill(pid, signal.SIGKILL)
+        except OSError:
+            pass
+    proc.wait()
+
+
+__all__ = ["descendants", "kill_tree"]
@@ -0,0 +1,98 @@
+"""Timed spans and log setup for the analysis pipeline.
+
+Each major stage (debugger launch, backtrace capture, symbolization, prompt
+build, LLM call) runs inside a `span`, which logs the stage's name, its
+fields, and how long it took under the ``dbgcopilot`` logger hierarchy. A
+stage that raises is logged with the error and re-raised, so a backend
+timeout or an HTTP 429 from the provider shows which stage it came from.
+
+Nothing is printed unless the application configures logging.
+`configure_logging` does so from ``$DBGCOPILOT_LOG``, which takes a level
+(``debug``) or comma-separated ``logger=level`` directives
+(``info,dbgcopilot.llm=debug``). Logs go to stderr, so they never mix with a
+report on stdout.
+"""
+from __future__ import annotations
+
+import logging
+import os
+import sys
+import time
+from contextlib import contextmanager
+from typing import Any, Dict, Iterator, Optional, TextIO
+
+LOG_ENV_VAR = "DBGCOPILOT_LOG"
+LOG_FORMAT = "%(asctime)s %(levelname)s %(name)s: %(message)s"
+
+_ROOT = "dbgcopilot"
+_handler: Optional[logging.Handler] = None
+
+# Without a handler anywhere, logging's last resort would print failed spans to stderr.
+logging.getLogger(_ROOT).addHandler(logging.NullHandler())
+
+
+def _fields(values: Dict[str, Any]) -> str:
+    return " ".join(f"{key}={value}" for key, value in values.items() if value is not None)
+
+
+@contextmanager
+def span(logger: logging.Logger, name: str, **fields: Any) -> Iterator[Dict[str, Any]]:
+    """Time the ``with`` body as stage ``name``; the yielded dict takes fields learned inside it.
+
+    Logs ``<name> started`` at DEBUG, then ``<name> done in <ms> ms`` with the
+    fields at INFO, or ``<name> failed after <ms> ms`` with the error at
+    WARNING. ``duration_ms`` is set in the dict once the body finishes.
+    """
+    record: Dict[str, Any] = dict(fields)
+    logger.debug("%s started %s", name, _fields(record))
+    start = time.perf_counter()
+    try:
+        yield record
+    except BaseException as exc:
+        elapsed = (time.perf_counter() - start) * 1000
+        logger.warning("%s failed after %.1f ms: %s %s", name, elapsed, exc, _fields(record))
+        record["duration_ms"] = round(elapsed, 1)
+        raise
+    elapsed = (time.perf_counter() - start) * 1000
+    logger.info("%s done in %.1f ms %s", name, elapsed, _fields(record))
+    record["duration_ms"] = round(elapsed, 1)
+
+
+def _level(name: str) -> int:
+    level = logging.getLevelName(name.strip().upper())
+    if not isinstance(level, int):
+        raise ValueError(f"unknown log level {name.strip()!r} in ${LOG_ENV_VAR}")
+    return level
+
+
+def configure_logging(spec: Optional[str] = None, stream: Optional[TextIO] = None) -> bool:
+    """Send ``dbgcopilot`` logs to ``stream`` (stderr) at the levels in ``spec`` (default ``$DBGCOPILOT_LOG``).
+
+    A bare level applies to all of ``dbgcopilot``; ``logger=level`` sets one
+    subtree, e.g. ``dbgcopilot.llm=debug``. Returns False, changing nothing,
+    when ``spec`` is empty. Raises ValueError for an unknown level.
+    """
+    spec = os.environ.get(LOG_ENV_VAR, "") if spec is None else spec
+    directives = [d for d in (part.strip() for part in spec.split(",")) if d]
+    if not directives:
+        return False
+    levels = []
+    for directive in directives:
+        target, _, level = directive.rpartition("=")
+        levels.append((target or _ROOT, _level(level)))
+    global _handler
+    root = logging.getLogger(_ROOT)
+    if _handler is not None:
+        # Reconfiguring replaces the earlier handler instead of logging everything twice.
+        root.removeHandler(_handler)
+    _handler = logging.StreamHandler(stream or sys.stderr)
+    _handler.setFormatter(logging.Formatter(LOG_FORMAT))
+    root.addHandler(_handler)
+    root.propagate = False
+    root.setLevel(logging.WARNING)
+    for target, level in levels:
+        logging.getLogger(target).setLevel(level)
+    return True
+
+
+__all__ = ["LOG_ENV_VAR", "configure_logging", "span"]
@@ -0,0 +1,113 @@
+import os
+
+import pytest
+
+from dbgcopilot.analysis import CrashContext, FaultKind, Frame, Module, StopInfo, api
+from dbgcopilot.llm.base import Completion
+
+
+@pytest.fixture(autouse=True)
+def _history_in_tmp_path(tmp_path, monkeypatch):
+    # Commands save every crash they analyze; keep the tests' out of the user's history.
+    monkeypatch.setenv("DBGCOPILOT_HISTORY_PATH", str(tmp_path / "history.sqlite3"))
+
+
+class ScriptedProvider:
+    """A provider that gives ``answers`` in order and keeps the prompts it was sent."""
+
+    name = "scripted"
+
+    def __init__(self, answers):
+        self.answers = list(answers)
+        self.prompts = []
+
+    def complete(self, prompt):
+        self.prompts.append(prompt)
+        return Completion(text=self.answers.pop(0), provider=self.name)
+
+    def stream(self, prompt, on_chunk):
+        completion = self.complete(prompt)
+        on_chunk(completion.text)
+        return completion
+
+
+class FakeGdb:
+    # What the batch debugger reports for examples/crash/c: a write through NULL in boom().
+    name = "gdb"
+
+    def load(self, binary, core=None, args=()):
+        self.binary = binary
+
+    def run_to_fault(self):
+        return StopInfo(signal="SIGSEGV", description="Segmentation fault", fault_address=0)
+
+    def backtrace(self):
+        return [
+            Frame(0, pc=0x401136, function="boom", file="crash.c", line=6),
+            Frame(1, pc=0x401160, function="main", file="crash.c", line=14),
+        ]
+
+    def read_registers(self):
+        return []
+
+    def modules(self):
+        return []
+
+    def threads(self):
+        return []
+
+
+@pytest.fixture
+def scripted_provider():
+    return ScriptedProvider
+
+
+@pytest.fixture
+def fake_gdb():
+    return FakeGdb
+
+
+@pytest.fixture
+def null_deref_report():
+    """The null write in examples/crash/rust, as `analyze` reports it; pass the explanation, if any."""
+
+    def report(explanation=None):
+        return api.AnalysisReport(
+            fault_kind=FaultKind.NULL_DEREF,
+            stop=StopInfo(signal="SIGSEGV", description="Segmentation fault", fault_address=0),
+            frames=[Frame(index=0, pc=0x5555555591A0, function="rust_crash::crash", file="src/main.rs", line=9)],
+            explanation=explanation,
+        )
+
+    return report
+
+
+@pytest.fixture
+def script(tmp_path):
+    """Writes a ``/bin/sh`` program with ``body`` as tmp_path/prog.sh and returns its path."""
+
+    def write(body):
+        path = tmp_path / "prog.sh"
+        path.write_text("#!/bin/sh\n" + body + "\n")
+        os.chmod(path, 0o755)
+        return str(path)
+
+    return write
+
+
+@pytest.fixture
+def fleet_report():
+    """A core's report from a service that faults in ``site``, loaded at ``base``, as a fleet's cores do."""
+
+    def report(site, base=0x555555554000, signal="SIGSEGV"):
+        ctx = CrashContext(
+            stop=StopInfo(signal=signal, fault_address=0),
+            frames=[
+                Frame(0, pc=base + 0x1151, function=site, offset=0x11, module="server"),
+                Frame(1, pc=base + 0x1190, function="handle_request", offset=0x3A, module="server"),
+            ],
+            modules=[Module("server", base, 0x5000)],
+        )
+        return api._report_from_context(ctx, "core")
+
+    return report
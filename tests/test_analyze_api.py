@@ -0,0 +1,321 @@
+import os
+import time
+
+import pytest
+
+from dbgcopilot.analysis import AnalysisError, AnalyzeRequest, FaultKind, Outcome, analyze
+from dbgcopilot.analysis import api
+from dbgcopilot.llm import providers
+
+
+def _no_debugger(_request):
+    raise AnalysisError("no debugger in this test")
+
+
+def test_analyze_clean_exit_and_signal(monkeypatch, script):
+    monkeypatch.setattr(api, "_detect_backend", _no_debugger)
+    report = analyze(AnalyzeRequest(binary=script("exit 3")))
+    assert not report.crashed and report.stop.exit_code == 3
+
+    report = analyze(AnalyzeRequest(binary=script("kill -SEGV $$")))
+    assert report.crashed and report.stop.signal == "SIGSEGV"
+    # A SIGSEGV with no fault address is too little to call an invalid access.
+    assert report.fault_kind is FaultKind.UNKNOWN
+
+
+def test_analyze_hang_and_explanation(monkeypatch, script):
+    monkeypatch.setattr(api, "_detect_backend", _no_debugger)
+    prompts = []
+
+    def fake_client(name, config):
+        assert name == "mock-local"
+        return lambda prompt: prompts.append(prompt) or "It never exits."
+
+    monkeypatch.setattr(providers, "create_client", fake_client)
+    request = AnalyzeRequest(binary=script("exec sleep 5"), timeout=0.2, provider="mock-local")
+    report = analyze(request)
+    assert report.hung and report.fault_kind is FaultKind.HANG
+    assert report.explanation == "It never exits."
+    assert "no exit within 0.2s" in prompts[0]
+
+
+def test_analyze_missing_binary(tmp_path):
+    with pytest.raises(AnalysisError):
+        analyze(AnalyzeRequest(binary=str(tmp_path / "missing")))
+
+
+def test_report_json_and_cli_format(tmp_path, monkeypatch, capsys, script):
+    import json
+
+    from dbgcopilot.analysis import CrashContext, Frame, StopInfo
+    from dbgcopilot.cli import ExitCode, analyze_main
+
+    ctx = CrashContext(
+        stop=StopInfo(signal="SIGSEGV", fault_address=0x10),
+        frames=[Frame(0, pc=0x401000, function="crash_demo::crash", file="src/main.rs", line=12)],
+    )
+    report = api._report_from_context(ctx, "core")
+    report.explanation = "Null pointer written in crash()."
+    data = json.loads(report.to_json())
+    assert data["version"] == api.REPORT_SCHEMA_VERSION
+    assert (data["fault_kind"], data["signal"], data["fault_address"]) == ("null_deref", "SIGSEGV", "0x10")
+    assert data["frames"][0]["symbol"] == "crash_demo::crash" and data["frames"][0]["line"] == 12
+    assert data["explanation"] == "Null pointer written in crash()." and data["hang"] is None
+
+    monkeypatch.setattr(api, "_detect_backend", _no_debugger)
+    assert analyze_main(["--format", "json", script("exit 3"), "--", "-v"]) == ExitCode.NO_CRASH
+    out = json.loads(capsys.readouterr().out)
+    assert out["exit_code"] == 3 and out["args"][1:] == ["-v"] and out["explanation"] is None
+    # Text stays the default.
+    assert analyze_main([script("exit 3")]) == ExitCode.NO_CRASH
+    assert "Exit code: 3" in capsys.readouterr().out
+    assert analyze_main([str(tmp_path / "missing")]) == ExitCode.CAPTURE_FAILED
+    assert "binary not found" in capsys.readouterr().err
+
+
+def test_text_is_rendered_from_the_json_data():
+    import json
+
+    from dbgcopilot.analysis import CrashContext, DominantFrame, Frame, HangReport, StopInfo, render_report
+
+    frames = [Frame(i, pc=0x401000 + i, function=f"f{i}", module="crash") for i in range(20)]
+    crash = api._report_from_context(CrashContext(stop=StopInfo(signal="SIGSEGV", fault_address=0x10), frames=frames), "gdb")
+    data = json.loads(crash.to_json())
+    # What `jq -r '.fault_address, .frames[0].symbol'` prints.
+    assert (data["fault_address"], data["frames"][0]["symbol"], len(data["frames"])) == ("0x10", "f0", 20)
+    assert render_report(data) == crash.render() and crash.render().endswith("... 4 more frames")
+
+    spin = Frame(0, pc=0x401200, function="spin")
+    hang = api._hang_report(HangReport([DominantFrame(1, "busy", spin, 9, 10, [spin])], [0.0] * 10, 2), "no exit", "run")
+    text = hang.render()
+    assert "Classification: Hang classification: busy loop" in text
+    assert "- thread 1 (busy) is in `spin` 90% of samples" in text
+    loaded = json.loads(hang.to_json())
+    assert loaded["hang"]["category"] == "busy_loop" and render_report(loaded) == text
+
+
+def test_no_llm_builds_the_prompt_offline(monkeypatch, capsys, script, fake_gdb):
+    import json
+
+    from dbgcopilot.cli import analyze_main
+
+    def no_network(*_args, **_kwargs):
+        raise AssertionError("--no-llm must not reach a provider")
+
+    monkeypatch.setattr(api, "_detect_backend", lambda _request: fake_gdb())
+    monkeypatch.setattr(providers, "create_client", no_network)
+    crash = script("kill -SEGV $$")
+    assert analyze_main(["--no-llm", "--provider", "mock-local", crash]) == 0
+    out = capsys.readouterr().out
+    assert "Fault address: 0x0" in out and "Classification: null-pointer dereference" in out
+    assert "\nPrompt:\nYou are a debugging copilot." in out and "#0 0x401136 boom at crash.c:6" in out
+
+    assert analyze_main(["--no-llm", "--format", "json", crash]) == 0
+    data = json.loads(capsys.readouterr().out)
+    assert (data["fault_kind"], data["fault_address"], data["explanation"]) == ("null_deref", "0x0", None)
+    assert data["prompt"].startswith("You are a debugging copilot.")
+
+
+def test_exit_codes_tell_a_crash_from_a_clean_run_and_a_failed_explanation(tmp_path, monkeypatch, script, fake_gdb):
+    from dbgcopilot.cli import ExitCode, analyze_main
+
+    def unreachable(prompt):
+        raise RuntimeError("provider unreachable")
+
+    monkeypatch.setattr(api, "_detect_backend", lambda _request: fake_gdb())
+    monkeypatch.setattr(providers, "create_client", lambda name, config: unreachable)
+    crash = script("kill -SEGV $$")
+    assert analyze_main(["--no-llm", crash]) == ExitCode.OK
+    assert analyze_main(["--format", "json", "--no-cache", "--provider", "mock-local", crash]) == ExitCode.LLM_FAILED
+    assert analyze_main(["--no-llm", script("exit 0")]) == ExitCode.NO_CRASH
+    assert analyze_main(["--no-llm", "--core", str(tmp_path / "missing.core"), crash]) == ExitCode.CAPTURE_FAILED
+    assert [int(code) for code in ExitCode] == [0, 2, 3, 4, 5]
+
+
+def test_run_captures_the_fault_on_the_run_that_hit_it(monkeypatch, capsys, script, fake_gdb):
+    from dbgcopilot.analysis import StopInfo
+    from dbgcopilot.cli import ExitCode
+    from dbgcopilot.repl.standalone import main
+
+    class _LaunchingGdb(fake_gdb):
+        timeout = 60.0
+
+        def __init__(self, stop):
+            self.stop = stop
+
+        def load(self, binary, core=None, args=()):
+            launched.append((binary, list(args), self.timeout))
+
+        def run_to_fault(self):
+            return self.stop
+
+        def sample_threads(self, pid):
+            return []
+
+    launched = []
+    stops = []
+    monkeypatch.setattr(api, "_detect_backend", lambda _request: _LaunchingGdb(stops.pop(0)))
+    monkeypatch.setattr(api, "HANG_SAMPLE_DURATION_MS", 200)
+    monkeypatch.setattr(api, "HANG_SAMPLE_INTERVAL_MS", 100)
+    # The program does not crash on its own; only the debugger's run of it does, and that run is captured.
+    prog = script("exit 0")
+    stops.append(StopInfo(signal="SIGSEGV", description="Segmentation fault", fault_address=0))
+    assert main(["run", "--max-runtime", "2s", "--", prog, "--port", "8080"]) == ExitCode.OK
+    assert "Classification: null-pointer dereference" in capsys.readouterr().out
+    # A shell script is launched as ``sh prog.sh``, within the program's own limit.
+    assert launched == [("/bin/sh", [prog, "--port", "8080"], 2.0)]
+
+    stops.append(StopInfo(description="exited with code 0", exit_code=0))
+    assert main(["run", "--", prog]) == ExitCode.NO_CRASH
+    assert capsys.readouterr().out.endswith("\nNo fault: the program exited with code 0.\n")
+
+    # Past --max-runtime the program is handed to the hang analyzer.
+    hung = script("exec sleep 5")
+    stops[:] = [StopInfo(description="no fault within 0.3s; killed", timed_out=True), StopInfo()]
+    assert main(["run", "--max-runtime", "300ms", "--format", "json", "--", hung]) == ExitCode.OK
+    assert '"outcome": "hung"' in capsys.readouterr().out
+
+
+def test_attach_samples_and_leaves_the_process_running(monkeypatch):
+    import signal
+    import subprocess
+
+    from dbgcopilot.analysis import Frame, HangCategory, ThreadBacktrace
+
+    class StopsTarget:
+        name = "gdb"
+        calls = 0
+
+        def sample_threads(self, pid):
+            StopsTarget.calls += 1
+            if StopsTarget.calls == 2:
+                # A debugger that dies mid-attach leaves the target in a group stop.
+                os.kill(pid, signal.SIGSTOP)
+            return [ThreadBacktrace(1, name="busy", frames=[Frame(0, pc=0x401000, function="spin")])]
+
+    monkeypatch.setattr(api, "HANG_SAMPLE_DURATION_MS", 400)
+    monkeypatch.setattr(api, "_detect_backend", lambda _request: StopsTarget())
+    proc = subprocess.Popen(["sleep", "30"])
+    try:
+        report = analyze(AnalyzeRequest(binary="", pid=proc.pid))
+        assert report.hung and report.source == "attach" and report.hang_kind.category is HangCategory.BUSY_LOOP
+        assert report.args == ["sleep", "30"] and "attached to running process" in report.stop.description
+        # Still alive, and not left stopped.
+        assert proc.poll() is None and not api._process_stopped(proc.pid)
+    finally:
+        proc.kill()
+        proc.wait()
+
+    # Once the process is gone, only a core can stand in for it.
+    with pytest.raises(AnalysisError, match="not running"):
+        analyze(AnalyzeRequest(binary="", pid=proc.pid))
+
+
+def test_frames_without_file_line_resolve_from_debug_info(monkeypatch, fake_gdb):
+    from dbgcopilot.analysis.parsers import parse_gdb_backtrace, parse_gdb_proc_mappings
+    from dbgcopilot.symbols import Symbolizer
+
+    root = os.path.dirname(os.path.dirname(os.path.abspath(__file__)))
+    binary = os.path.join(root, "examples", "crash", "rust", "target", "debug", "rust_crash")
+    libc = "/lib/x86_64-linux-gnu/libc.so.6"
+    if not (os.path.exists(binary) and os.path.exists(libc)):
+        pytest.skip("needs the debug build of examples/crash/rust and a glibc system")
+
+    # Where `*ptr = 42;` landed in crash(), read back from the build's own line table.
+    sym = Symbolizer.open(binary)
+    start, size = next((s.value, s.size) for s in sym.elf.symbols() if s.name.startswith("_ZN10rust_crash5crash17h"))
+    rows = ((a, sym.lines.lookup(a)) for a in range(start, start + size))
+    store = next(a for a, row in rows if row is not None and row.line == 9)
+    base = 0x555555554000
+    pc = base + store - sym.elf.load_base()
+
+    class PcOnlyGdb(fake_gdb):
+        # A debugger that found no debug info: bare pcs, plus `info proc mappings`.
+        def backtrace(self):
+            return parse_gdb_backtrace(f"#0  {pc:#018x} in ?? ()\n#1  0x00007ffff7c29d90 in ?? () from {libc}\n")
+
+        def modules(self):
+            return parse_gdb_proc_mappings(
+                f"      {base:#x}     {base + 0x13000:#x}    0x13000        0x0  r--p   {binary}\n"
+                f"      {base + 0x13000:#x}     {base + 0x52000:#x}    0x3f000    0x12000  r-xp   {binary}\n"
+                f"      0x7ffff7c00000     0x7ffff7c28000    0x28000        0x0  r--p   {libc}\n"
+                f"      0x7ffff7c28000     0x7ffff7dbd000   0x195000    0x28000  r-xp   {libc}\n"
+            )
+
+    monkeypatch.setattr(api, "_detect_backend", lambda _request: PcOnlyGdb())
+    report = analyze(AnalyzeRequest(binary=binary))
+    top = report.frames[0]
+    assert top.function == "rust_crash::crash" and (os.path.basename(top.file), top.line) == ("main.rs", 9)
+    with open(os.path.join(root, "examples", "crash", "rust", "src", "main.rs")) as fh:
+        assert fh.read().splitlines()[top.line - 1].strip() == "*ptr = 42;"
+    # libc ships without line tables: no file or line is made up for it.
+    assert report.frames[1].module == "libc.so.6" and (report.frames[1].file, report.frames[1].line) == (None, None)
+    assert [m.name for m in report.modules] == ["rust_crash", "libc.so.6"]
+
+
+def _reaped(pid, within=5.0):
+    # SIGKILL lands asynchronously; a zombie waiting on its new parent counts as dead.
+    deadline = time.monotonic() + within
+    while time.monotonic() < deadline:
+        try:
+            with open(f"/proc/{pid}/stat") as fh:
+                if fh.read().rsplit(")", 1)[1].split()[0] == "Z":
+                    return True
+        except FileNotFoundError:
+            return True
+        time.sleep(0.01)
+    return False
+
+
+def test_a_runaway_target_is_killed_with_its_children(tmp_path, monkeypatch, script):
+    if not os.path.isdir("/proc"):
+        pytest.skip("needs /proc")
+    monkeypatch.setattr(api, "_detect_backend", _no_debugger)
+    pids = tmp_path / "pids"
+    binary = script(f"sleep 100 & echo $! > {pids}; echo $$ >> {pids}; wait")
+    report = analyze(AnalyzeRequest(binary=binary, timeout=0.5, sample_hangs=False))
+    assert report.outcome is Outcome.NO_CRASH_WITHIN_TIMEOUT and not (report.crashed or report.hung)
+    assert report.stop.description == "no crash within 0.5s; killed"
+    assert report.to_dict()["outcome"] == "no_crash_within_timeout" and report.prompt is None
+    assert all(_reaped(int(pid)) for pid in pids.read_text().split())
+
+    # With sampling on, the same timeout is a hang rather than a failure.
+    report = analyze(AnalyzeRequest(binary=binary, timeout=0.5))
+    assert report.outcome is Outcome.HUNG
+    assert all(_reaped(int(pid)) for pid in pids.read_text().split())
+
+
+def test_a_debugger_run_that_never_faults_keeps_the_first_runs_signal(monkeypatch, script, fake_gdb):
+    from dbgcopilot.analysis import StopInfo
+
+    class _TimedOutGdb(fake_gdb):
+        def run_to_fault(self):
+            return StopInfo(description="no fault within 31s; killed", timed_out=True)
+
+    monkeypatch.setattr(api, "_detect_backend", lambda _request: _TimedOutGdb())
+    report = analyze(AnalyzeRequest(binary=script("kill -ABRT $$"), timeout=1))
+    assert report.outcome is Outcome.CRASHED and report.stop.signal == "SIGABRT"
+    assert report.stop.description == "terminated by SIGABRT (debugger run: no fault within 31s; killed)"
+    assert analyze(AnalyzeRequest(binary=script("exit 0"))).outcome is Outcome.EXITED
+
+
+def test_no_llm_is_deterministic_and_keeps_the_json_layout(monkeypatch, script, fake_gdb):
+    import json
+
+    monkeypatch.setattr(api, "_detect_backend", lambda _request: fake_gdb())
+
+    def no_network(*_args, **_kwargs):
+        raise AssertionError("no_llm must not reach a provider")
+
+    monkeypatch.setattr(providers, "create_client", no_network)
+    crash = script("kill -SEGV $$")
+    offline = [analyze(AnalyzeRequest(binary=crash, provider="mock-local", no_llm=True)).to_json() for _ in range(2)]
+    assert offline[0] == offline[1]
+    data = json.loads(offline[0])
+    assert data["explanation"] is None and data["explanation_error"] is None
+    assert data["fault_kind"] == "null_deref" and data["frames"][0]["symbol"] == "boom"
+
+    monkeypatch.setattr(providers, "create_client", lambda name, config: lambda prompt: "boom() writes through NULL.")
+    online = json.loads(analyze(AnalyzeRequest(binary=crash, provider="mock-local")).to_json())
+    assert online["explanation"] == "boom() writes through NULL."
+    assert sorted(online) == sorted(data)
@@ -0,0 +1,76 @@
+from dbgcopilot.analysis.parsers import (
+    parse_gdb_backtrace,
+    parse_gdb_registers,
+    parse_gdb_stop,
+    parse_lldb_backtrace,
+    parse_lldb_registers,
+    parse_lldb_stop,
+)
+
+GDB_OUTPUT = """\
+Program received signal SIGSEGV, Segmentation fault.
+rust_crash::crash () at src/main.rs:9
+9	        *ptr = 42;
+$1 = (void *) 0x0
+#0  rust_crash::crash () at src/main.rs:9
+#1  0x000055555555911b in rust_crash::main () at src/main.rs:2
+#2  0x00007ffff7e1b4e0 in ?? () from /lib/x86_64-linux-gnu/libc.so.6
+"""
+
+LLDB_OUTPUT = """\
+* thread #1, name = 'rust_crash', stop reason = signal SIGSEGV: invalid address (fault address: 0x0)
+  * frame #0: 0x0000555555559157 rust_crash`rust_crash::crash::h0e8f3c at main.rs:9:9
+    frame #1: 0x000055555555911b rust_crash`rust_crash::main::h2b1d at main.rs:2:5
+    frame #2: 0x00007ffff7c29d90 libc.so.6`__libc_start_call_main + 128
+"""
+
+
+def test_gdb_backtrace_and_stop():
+    frames = parse_gdb_backtrace(GDB_OUTPUT)
+    assert [f.index for f in frames] == [0, 1, 2]
+    assert frames[0].function == "rust_crash::crash"
+    assert (frames[0].file, frames[0].line) == ("src/main.rs", 9)
+    assert frames[1].pc == 0x55555555911B
+    assert frames[2].function is None and frames[2].module == "libc.so.6"
+    stop = parse_gdb_stop(GDB_OUTPUT)
+    assert stop.signal == "SIGSEGV" and stop.fault_address == 0
+
+
+def test_gdb_exit_code_is_octal():
+    stop = parse_gdb_stop("[Inferior 1 (process 42) exited with code 012]")
+    assert not stop.crashed and stop.exit_code == 10
+
+
+def test_lldb_backtrace_and_stop():
+    frames = parse_lldb_backtrace(LLDB_OUTPUT)
+    assert len(frames) == 3
+    assert frames[0].module == "rust_crash"
+    assert (frames[0].line, frames[0].column) == (9, 9)
+    assert frames[2].function == "__libc_start_call_main"
+    stop = parse_lldb_stop(LLDB_OUTPUT)
+    assert stop.signal == "SIGSEGV" and stop.fault_address == 0
+
+
+def test_mangled_symbols_are_demangled_and_kept_raw():
+    lldb = parse_lldb_backtrace(
+        "  * frame #0: 0x0000555555559157 rust_crash`rust_crash::crash::h0e8f3c0a1b2c3d4e at main.rs:9:9\n"
+        "    frame #1: 0x000055555555911b rust_crash`_RNvCs1a2b3c_10rust_crash4main + 11\n"
+    )
+    assert [f.function for f in lldb] == ["rust_crash::crash", "rust_crash::main"]
+    assert [f.raw_symbol for f in lldb] == ["rust_crash::crash::h0e8f3c0a1b2c3d4e", "_RNvCs1a2b3c_10rust_crash4main"]
+
+    gdb = parse_gdb_backtrace(
+        "#0  0x0000555555559157 in _ZN10rust_crash5crash17h0e8f3c0a1b2c3d4eE () at src/main.rs:9\n"
+        "#1  0x000055555555a000 in _ZNSt6vectorIiSaIiEE9push_backERKi () from /usr/lib/libstdc++.so.6\n"
+        "#2  0x000055555555911b in main () at src/main.rs:2\n"
+    )
+    assert gdb[0].function == "rust_crash::crash" and gdb[0].raw_symbol == "_ZN10rust_crash5crash17h0e8f3c0a1b2c3d4eE"
+    assert gdb[1].function == "std::vector<int, std::allocator<int>>::push_back"
+    assert gdb[1].raw_symbol == "_ZNSt6vectorIiSaIiEE9push_backERKi"
+    assert gdb[2].function == "main" and gdb[2].raw_symbol is None
+
+
+def test_register_parsers_agree():
+    gdb_regs = parse_gdb_registers("rax            0x0                 0\nrip            0x555555559157      0x555555559157 <crash+23>")
+    lldb_regs = parse_lldb_registers("General Purpose Registers:\n       rax = 0x0000000000000000\n       rip = 0x0000555555559157  rust_crash`crash + 23")
+    assert [(r.name, r.value) for r in gdb_regs] == [(r.name, r.value) for r in lldb_regs]
@@ -0,0 +1,107 @@
+import json
+
+from dbgcopilot import cli
+from dbgcopilot.analysis import AnalysisError, AnalyzeRequest, ExplanationCache, analyze_batch, api
+from dbgcopilot.llm import providers
+
+
+def _fleet(tmp_path, monkeypatch, fleet_report):
+    cores = tmp_path / "cores"
+    cores.mkdir()
+    for i, site in enumerate(["parse_header", "parse_header", "read_body", "parse_header"]):
+        (cores / f"core.{100 + i}").write_text(site)
+    (cores / "core.999").write_text("")
+    (cores / "notes.txt").write_text("not a core")
+
+    def fake_core(request):
+        site = open(request.core).read()
+        if not site:
+            raise AnalysisError(f"{request.core}: truncated core")
+        return fleet_report(site, 0x555555554000 + 0x1000000 * len(request.core))
+
+    monkeypatch.setattr(api, "_analyze_core", fake_core)
+    binary = tmp_path / "server"
+    binary.write_text("")
+    return str(cores), str(binary)
+
+
+def test_batch_groups_cores_by_signature_and_explains_each_once(tmp_path, monkeypatch, fleet_report):
+    directory, binary = _fleet(tmp_path, monkeypatch, fleet_report)
+    calls = []
+
+    def fake_client(name, config):
+        return lambda prompt: calls.append(prompt) or f"Answer {len(calls)}."
+
+    monkeypatch.setattr(providers, "create_client", fake_client)
+    request = AnalyzeRequest(binary=binary, provider="mock-local", cache=ExplanationCache(str(tmp_path / "cache")))
+    result = analyze_batch(directory, request)
+
+    assert [e.path.rsplit("/", 1)[1] for e in result.entries] == ["core.100", "core.101", "core.102", "core.103",
+                                                                   "core.999"]
+    assert [e.error for e in result.failures] == [f"{directory}/core.999: truncated core"]
+    assert len(calls) == 2
+    assert [e.report.explanation_cached for e in result.entries[:4]] == [False, True, False, True]
+
+    header, body = result.groups()
+    assert (header.function, len(header.cores), body.function, len(body.cores)) == (
+        "parse_header", 3, "read_body", 1
+    )
+    assert header.signature == "server!parse_header+0x11 -> server!handle_request"
+    assert result.summary() == [
+        f"3 cores: null_deref in parse_header ({header.signature}) [{header.bucket}]",
+        f"1 core: null_deref in read_body ({body.signature}) [{body.bucket}]",
+        "1 core could not be analyzed",
+        result.usage().describe(),
+    ]
+    # Two explanations were asked for and two came from the cache, at no cost; mock-local is free anyway.
+    assert (result.usage().calls, result.usage().cached, result.usage().cost) == (2, 2, 0)
+    assert result.summary()[-1].endswith(
+        " in 2 calls, $0 (mock-local); 2 explanations from the cache; token counts estimated"
+    )
+    assert [e.report.usage.total_tokens for e in result.entries[:4]][1::2] == [0, 0]
+
+
+def test_cli_batch_prints_a_json_array_and_the_summary(tmp_path, monkeypatch, capsys, fleet_report):
+    directory, binary = _fleet(tmp_path, monkeypatch, fleet_report)
+    monkeypatch.chdir(tmp_path)
+    assert cli.analyze_main(["--batch", directory, "--no-cache", "--no-llm", binary]) == 0
+    out, err = capsys.readouterr()
+    entries = json.loads(out)
+    assert [e["core"].rsplit("/", 1)[1] for e in entries][-1] == "core.999"
+    assert entries[0]["error"] is None and entries[0]["report"]["frames"][0]["symbol"] == "parse_header"
+    assert entries[-1]["report"] is None and "truncated core" in entries[-1]["error"]
+    assert f"Analyzed 5 cores from {directory}:" in err and "  3 cores: null_deref in parse_header" in err
+
+    # Only the matching names, and a directory without any is an error.
+    assert cli.analyze_main(["--batch", directory, "--batch-glob", "*.txt", "--no-cache", "--no-llm", binary]) == 0
+    assert [e["core"].rsplit("/", 1)[1] for e in json.loads(capsys.readouterr().out)] == ["notes.txt"]
+    assert cli.analyze_main(["--batch", str(tmp_path), "--no-cache", "--no-llm", binary]) == cli.ExitCode.CAPTURE_FAILED
+    assert "no core files in" in capsys.readouterr().err
+
+
+def test_batch_command_prints_a_table_and_explains_each_signature_once_without_a_cache(
+    tmp_path, monkeypatch, capsys, fleet_report
+):
+    directory, binary = _fleet(tmp_path, monkeypatch, fleet_report)
+    monkeypatch.chdir(tmp_path)
+    calls = []
+
+    def fake_client(name, config):
+        return lambda prompt: calls.append(prompt) or "**Null dereference** of `hdr` in the parser. It was never set."
+
+    monkeypatch.setattr(providers, "create_client", fake_client)
+    assert cli.batch_main([directory, "--binary", binary, "--provider", "mock-local", "--no-cache"]) == 0
+    assert len(calls) == 2
+    lines = capsys.readouterr().out.splitlines()
+    assert lines[0].split() == ["SIGNATURE", "COUNT", "EXAMPLE", "CAUSE"]
+    assert lines[1].startswith("server!parse_header+0x11 -> server!handle_request  3      ")
+    assert lines[1].endswith(f"{directory}/core.100  Null dereference of hdr in the parser.")
+    assert lines[-1] == f"failed: {directory}/core.999: truncated core"
+
+    assert cli.batch_main([directory, "--binary", binary, "--format", "json", "--no-cache"]) == 0
+    data = json.loads(capsys.readouterr().out)
+    assert [(g["function"], g["count"], g["example"].rsplit("/", 1)[1]) for g in data["groups"]] == [
+        ("parse_header", 3, "core.100"), ("read_body", 1, "core.102")
+    ]
+    # Without a provider, the cause is the fault hint.
+    assert data["groups"][0]["cause"].startswith("null-pointer dereference (SIGSEGV") and len(data["cores"]) == 5
@@ -0,0 +1,61 @@
+import struct
+
+import pytest
+
+from dbgcopilot.symbols import DwarfError
+from dbgcopilot.symbols.cfi import CallFrameTable, Rule, RuleKind, parse_call_frames
+
+
+def _debug_frame(cie_instructions: bytes, fdes) -> bytes:
+    """``.debug_frame`` with one version-4 AArch64 CIE (data align -8, return address in x30)."""
+    cie = struct.pack("<I", 0xFFFFFFFF) + b"\x04\x00\x08\x00\x04\x78\x1e" + cie_instructions
+    out = struct.pack("<I", len(cie)) + cie
+    for start, size, instructions in fdes:
+        body = struct.pack("<IQQ", 0, start, size) + instructions
+        out += struct.pack("<I", len(body)) + body
+    return out
+
+
+def test_debug_frame_rows_follow_the_prologue_and_epilogue():
+    # stp x29, x30, [sp, #-32]! at +4; the epilogue at +0x20 restores the entry state, and the
+    # code after it (a second exit path) uses the remembered body state again.
+    body = (
+        b"\x41\x0e\x20\x9d\x04\x9e\x03"  # +4: CFA = sp + 32; x29 at CFA-32, x30 at CFA-24
+        b"\x47\x0a"  # +0x20: remember_state
+        b"\xdd\xde\x0e\x00"  # restore x29, x30; CFA = sp + 0
+        b"\x41\x0b"  # +0x24: restore_state
+    )
+    table = CallFrameTable()
+    table.add(parse_call_frames(_debug_frame(b"\x0c\x1f\x00", [(0x1000, 0x40, body)]), eh_frame=False))
+    assert len(table) == 1 and table.find(0x1040) is None
+
+    entry = table.row(0x1000)
+    assert (entry.cfa_register, entry.cfa_offset, entry.rules, entry.return_address_register) == (31, 0, {}, 30)
+    body_row = table.row(0x1010)
+    assert body_row.cfa_offset == 32
+    assert body_row.rules == {29: Rule(RuleKind.OFFSET, -32), 30: Rule(RuleKind.OFFSET, -24)}
+    assert table.row(0x1020).rules == {} and table.row(0x1020).cfa_offset == 0
+    assert table.row(0x1030).rules == body_row.rules
+
+    # Recovering the caller's registers from the rules.
+    memory = {0x7F00: 0x7F80, 0x7F08: 0x4010}
+    registers = {29: 0x7F00, 31: 0x7F00}
+    cfa = registers[31] + body_row.cfa_offset
+    assert [body_row.rules[n].recover(cfa, registers, memory.get) for n in (29, 30)] == [0x7F80, 0x4010]
+    assert Rule(RuleKind.REGISTER, 29).recover(cfa, registers, memory.get) == 0x7F00
+    assert Rule(RuleKind.EXPRESSION).recover(cfa, registers, memory.get) is None
+
+    # .eh_frame wins where both describe the same code.
+    table.add(parse_call_frames(_debug_frame(b"\x0c\x1f\x10", [(0x1000, 0x40, b""), (0x2000, 0x10, b"")]),
+                                eh_frame=False))
+    assert table.row(0x1000).cfa_offset == 0 and table.row(0x2000).cfa_offset == 0x10
+
+
+def test_malformed_call_frames_raise_dwarf_errors():
+    with pytest.raises(DwarfError):
+        parse_call_frames(struct.pack("<I", 0x40) + b"\x00" * 8)
+    bad_op = _debug_frame(b"\x0c\x1f\x00", [(0x1000, 0x40, b"\x3f")])
+    table = CallFrameTable()
+    table.add(parse_call_frames(bad_op, eh_frame=False))
+    with pytest.raises(DwarfError):
+        table.row(0x1000)
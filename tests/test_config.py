@@ -0,0 +1,86 @@
+import pytest
+
+from dbgcopilot.analysis import api
+from dbgcopilot.cli import ExitCode, analyze_main
+from dbgcopilot.utils.config import ConfigError, find_config_file, load_settings
+
+
+def _write(path, text):
+    path.write_text(text)
+    return str(path)
+
+
+def test_cli_flags_override_the_environment_which_overrides_the_file(tmp_path):
+    path = _write(
+        tmp_path / "dbgcopilot.toml", '[llm]\nprovider = "ollama"\nmodel = "llama3.1"\n\n[analysis]\ntimeout = 10\n'
+    )
+    env = {"DBGCOPILOT_LLM_MODEL": "qwen2.5-coder", "DBGCOPILOT_ANALYSIS_TIMEOUT": "20"}
+    settings = load_settings({"analysis.timeout": 5.0, "llm.provider": None}, path=path, environ=env)
+    assert settings.values == {"llm.provider": "ollama", "llm.model": "qwen2.5-coder", "analysis.timeout": 5.0}
+    assert settings.sources == {
+        "llm.provider": path,
+        "llm.model": "$DBGCOPILOT_LLM_MODEL",
+        "analysis.timeout": "the command line",
+    }
+    assert settings.llm_config() == {"llm_provider": "ollama", "ollama_model": "qwen2.5-coder"}
+
+    settings = load_settings({"llm.provider": "openai-http"}, path=path, environ={})
+    assert settings.llm_config() == {"llm_provider": "openai-http", "openai_http_model": "llama3.1"}
+
+    env = {"DBGCOPILOT_LLM_BASE_URL": "http://gpu-box:11434"}
+    settings = load_settings({"llm.base_url": "http://localhost:8080"}, path=path, environ=env)
+    assert settings.llm_config()["ollama_base_url"] == "http://localhost:8080"
+    assert load_settings(path=path, environ=env).llm_config()["ollama_base_url"] == "http://gpu-box:11434"
+
+
+def test_invalid_values_name_their_source_and_the_precedence(tmp_path):
+    path = _write(tmp_path / "dbgcopilot.toml", '[analysis]\ntimeout = "soon"\n')
+    with pytest.raises(ConfigError, match=r"analysis.timeout must be a number, got 'soon' \(from .*dbgcopilot.toml; "
+                       r"command-line flags override DBGCOPILOT_\* environment variables"):
+        load_settings(path=path, environ={})
+    with pytest.raises(ConfigError, match=r"analysis.debugger must be 'gdb' or 'lldb', got 'windbg' "
+                       r"\(from \$DBGCOPILOT_ANALYSIS_DEBUGGER"):
+        load_settings(path=None, environ={"DBGCOPILOT_ANALYSIS_DEBUGGER": "windbg", "HOME": str(tmp_path)})
+    with pytest.raises(ConfigError, match=r"unknown key llm.modle \(known keys: analysis.debugger, "):
+        load_settings(path=_write(tmp_path / "typo.toml", '[llm]\nmodle = "x"\n'), environ={})
+    with pytest.raises(ConfigError, match=r"llm.base_url must be an http:// or https:// URL, got 'gpu-box:11434' "
+                       r"\(from the command line"):
+        load_settings({"llm.base_url": "gpu-box:11434"}, path=None, environ={"HOME": str(tmp_path)})
+    with pytest.raises(ConfigError, match="is not valid TOML"):
+        load_settings(path=_write(tmp_path / "bad.toml", "[llm\n"), environ={})
+
+
+def test_config_file_discovery(tmp_path, monkeypatch):
+    home, project = tmp_path / "home", tmp_path / "project"
+    (home / ".config" / "dbgcopilot").mkdir(parents=True)
+    project.mkdir()
+    user = _write(home / ".config" / "dbgcopilot" / "dbgcopilot.toml", "")
+    env = {"HOME": str(home)}
+    monkeypatch.setenv("HOME", str(home))
+    assert find_config_file(env, cwd=str(project)) == user
+    local = _write(project / "dbgcopilot.toml", "")
+    assert find_config_file(env, cwd=str(project)) == local
+    assert find_config_file({"DBGCOPILOT_CONFIG": user}, cwd=str(project)) == user
+    with pytest.raises(ConfigError, match="which is not a file"):
+        find_config_file({"DBGCOPILOT_CONFIG": str(tmp_path / "missing.toml")})
+
+
+def test_analyze_cli_reads_the_config(tmp_path, monkeypatch, capsys):
+    seen = []
+
+    def fake_analyze(request):
+        seen.append(request)
+        raise api.AnalysisError("stop here")
+
+    monkeypatch.setattr(api, "analyze", fake_analyze)
+    monkeypatch.chdir(tmp_path)
+    _write(tmp_path / "dbgcopilot.toml", '[llm]\nprovider = "mock-local"\nmax_retries = 1\n\n[analysis]\ntimeout = 7\n')
+    assert analyze_main(["--no-cache", "prog"]) == ExitCode.CAPTURE_FAILED
+    assert (seen[0].timeout, seen[0].llm_config) == (7.0, {"llm_provider": "mock-local", "max_retries": 1})
+    assert analyze_main(["--no-cache", "--timeout", "3", "prog"]) == ExitCode.CAPTURE_FAILED
+    assert seen[1].timeout == 3.0
+
+    monkeypatch.setenv("DBGCOPILOT_LLM_MAX_RETRIES", "lots")
+    with pytest.raises(SystemExit):
+        analyze_main(["--no-cache", "prog"])
+    assert "llm.max_retries must be an integer, got 'lots' (from $DBGCOPILOT_LLM_MAX_RETRIES" in capsys.readouterr().err
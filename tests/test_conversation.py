@@ -0,0 +1,92 @@
+import builtins
+
+from dbgcopilot import cli
+from dbgcopilot.analysis import ConversationState, Frame, Register, Variable, api
+from dbgcopilot.llm import base
+
+EXPLANATION = "ptr is written through while null."
+
+
+def test_follow_up_questions_see_the_report_history_and_debugger_output(scripted_provider, null_deref_report):
+    llm = scripted_provider(["It was never assigned.", "Initialize it with a Box."])
+    state = ConversationState(null_deref_report(EXPLANATION))
+
+    locals_output = "ptr = 0x0\ntoken = ghp_abcdefghijklmnopqrstuvwx\n"
+    output = state.handle("!info locals", llm, run_command=lambda cmd: locals_output)
+    assert output.startswith("ptr = 0x0") and state.transcript[0].command == "info locals"
+    assert state.handle("why is ptr null?", llm) == "It was never assigned."
+    assert state.handle("how do I fix it?", llm) == "Initialize it with a Box."
+
+    prompt = llm.prompts[1]
+    assert prompt.system and "follow-up question" in prompt.system
+    assert "rust_crash::crash" in prompt.text
+    assert "Your first analysis:\nptr is written through while null." in prompt.text
+    assert "Debugger output of `info locals`:\nptr = 0x0" in prompt.text
+    assert "ghp_" not in prompt.text and "<redacted>" in prompt.text
+    assert "Assistant: It was never assigned." in prompt.text
+    assert prompt.text.endswith("Follow-up question: how do I fix it?")
+    assert [t.role for t in state.transcript] == ["debugger", "user", "assistant", "user", "assistant"]
+
+    state.history_tokens = 10
+    assert "(4 earlier turns left out to fit the token budget)" in state.build_prompt("one more").text
+
+
+def test_interactive_cli_answers_until_end_of_input(monkeypatch, capsys, scripted_provider, null_deref_report):
+    report = null_deref_report(EXPLANATION)
+    report.explanation = None
+    llm = scripted_provider(["First look: a null write.", "It was never assigned."])
+    lines = iter(["!bt", "", "why is ptr null?", "quit"])
+    commands = []
+
+    def runner(request):
+        return lambda cmd: commands.append(cmd) or "#0  rust_crash::crash () at src/main.rs:9"
+
+    monkeypatch.setattr(api, "analyze", lambda request: report)
+    monkeypatch.setattr(base, "resolve_provider", lambda provider, config=None: llm)
+    monkeypatch.setattr(cli, "_debugger_runner", runner)
+    monkeypatch.setattr(builtins, "input", lambda prompt="": next(lines))
+    assert cli.analyze_main(["--no-cache", "--provider", "scripted", "-i", "rust_crash"]) == 0
+    out = capsys.readouterr().out
+    assert "Explanation:\nFirst look: a null write." in out
+    assert "#0  rust_crash::crash () at src/main.rs:9" in out and commands == ["bt"]
+    assert "It was never assigned." in out
+    assert "Debugger output of `bt`" in llm.prompts[1].text
+    assert "Your first analysis:\nFirst look: a null write." in llm.prompts[1].text
+
+
+def test_questions_pull_named_registers_and_frames_from_the_report(
+    tmp_path, monkeypatch, scripted_provider, null_deref_report
+):
+    report = null_deref_report(EXPLANATION)
+    report.registers = [Register("rip", 0x5555555591A0), Register("rdi", 0), Register("rsi", 0x7FFC3B00)]
+    report.frames.append(Frame(index=1, pc=0x555555559210, function="rust_crash::main", file="src/main.rs", line=14,
+                               locals=[Variable("buf", "*mut u8", "0x7ffc3b00")]))
+    state = ConversationState(report)
+    assert state.captured_data("why is RDI null in frame 1?") == [
+        "rdi = 0x0",
+        "#1 0x555555559210 rust_crash::main at src/main.rs:14",
+        "    buf = 0x7ffc3b00",
+    ]
+    assert state.captured_data("what does the caller do?") == []
+    prompt = state.build_prompt("where did rdi come from in #1?").text
+    assert "Captured data the question refers to:\nrdi = 0x0\n#1 0x555555559210" in prompt
+
+    # --transcript keeps the session after Ctrl-D.
+    llm = scripted_provider(["A write through a null rdi.", "From buf, which was never set."])
+    lines = iter(["where did rdi come from?"])
+
+    def read(prompt=""):
+        for line in lines:
+            return line
+        raise EOFError
+
+    monkeypatch.setattr(api, "analyze", lambda request: report)
+    monkeypatch.setattr(base, "resolve_provider", lambda provider, config=None: llm)
+    monkeypatch.setattr(builtins, "input", read)
+    path = tmp_path / "session.txt"
+    argv = ["--no-cache", "--provider", "scripted", "-i", "--transcript", str(path), "rust_crash"]
+    assert cli.analyze_main(argv) == 0
+    assert path.read_text() == (
+        "First analysis:\nA write through a null rdi.\n\n"
+        "User: where did rdi come from?\n\nAssistant: From buf, which was never set.\n"
+    )
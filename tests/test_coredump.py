@@ -0,0 +1,412 @@
+import shutil
+import struct
+from pathlib import Path
+from typing import Dict, Optional
+
+import pytest
+
+from dbgcopilot.analysis import (
+    AnalyzeRequest,
+    Architecture,
+    CrashReport,
+    FaultKind,
+    RegionKind,
+    analyze,
+    analyze_session,
+)
+from dbgcopilot.dumps import DumpSession, InvalidDumpError, TruncatedDumpError
+from dbgcopilot.dumps.coredump import (
+    AARCH64_GREGS,
+    NT_FILE,
+    NT_PRPSINFO,
+    NT_PRSTATUS,
+    NT_SIGINFO,
+    X86_64_GREGS,
+    CoreReader,
+)
+
+STACK = 0x7FFC0000
+TEXT = 0x400000
+LIB = 0x7F0000000000
+
+
+def _note(ntype: int, desc: bytes) -> bytes:
+    name = b"CORE\x00"
+    pad = lambda b: b + b"\x00" * (-len(b) % 4)  # noqa: E731
+    return struct.pack("<III", len(name), len(desc), ntype) + pad(name) + pad(desc)
+
+
+def build_core(
+    signo: int = 11,
+    fault_addr: int = 0x10,
+    regs: Optional[Dict[str, int]] = None,
+    machine: int = 62,
+    text_file: Optional[Path] = None,
+    pac: int = 0,
+    stack: Optional[bytes] = None,
+    library: Optional[Path] = None,
+    name: bytes = b"crash",
+) -> bytes:
+    """A core with two frame records on its stack; ``pac`` is OR-ed into their return addresses, as
+    pointer authentication signs them. ``text_file`` is listed in NT_FILE as the image at TEXT.
+    ``stack`` replaces the 0x100 bytes at STACK. ``library`` is listed after it as the image at LIB,
+    and ``name`` is the process name in NT_PRPSINFO."""
+    names = {62: X86_64_GREGS, 183: AARCH64_GREGS}.get(machine, ())
+    values = {name: 0 for name in names}
+    if machine == 183:
+        values.update({"pc": TEXT + 0x10, "sp": STACK, "x29": STACK + 0x20})
+    else:
+        values.update({"rip": TEXT + 0x10, "rsp": STACK, "rbp": STACK + 0x20})
+    values.update(regs or {})
+
+    prstatus = bytearray(392)
+    struct.pack_into("<h", prstatus, 12, signo)
+    struct.pack_into("<i", prstatus, 32, 4242)
+    struct.pack_into(f"<{len(names)}Q", prstatus, 112, *(values[n] for n in names))
+
+    prpsinfo = bytearray(136)
+    struct.pack_into("<i", prpsinfo, 24, 4242)
+    prpsinfo[40 : 40 + len(name)] = name
+    prpsinfo[56:70] = b"./crash --boom"
+
+    siginfo = bytearray(128)
+    struct.pack_into("<iiiiQ", siginfo, 0, signo, 0, 1, 0, fault_addr)
+
+    notes = _note(NT_PRSTATUS, bytes(prstatus)) + _note(NT_PRPSINFO, bytes(prpsinfo)) + _note(NT_SIGINFO, bytes(siginfo))
+    images = [(TEXT, path) for path in (text_file,) if path is not None]
+    images += [(LIB, path) for path in (library,) if path is not None]
+    if images:
+        files = struct.pack("<QQ", len(images), 0x1000)
+        files += b"".join(struct.pack("<QQQ", start, start + 0x2000, 0) for start, _ in images)
+        files += b"".join(str(path).encode() + b"\x00" for _, path in images)
+        notes += _note(NT_FILE, files)
+
+    if stack is None:
+        # Two frame records: rbp -> (saved rbp, return address).
+        records = bytearray(0x100)
+        struct.pack_into("<QQ", records, 0x20, STACK + 0x40, pac | TEXT + 0x1000)
+        struct.pack_into("<QQ", records, 0x40, 0, pac | TEXT + 0x1100)
+        stack = bytes(records)
+
+    phnum = 3
+    notes_off = 64 + 56 * phnum
+    stack_off = notes_off + len(notes)
+    header = bytearray(64)
+    header[:16] = b"\x7fELF\x02\x01\x01" + b"\x00" * 9
+    struct.pack_into("<HHIQQQIHHHHHH", header, 16, 4, machine, 1, 0, 64, 0, 0, 64, 56, phnum, 64, 0, 0)
+    phdrs = struct.pack("<IIQQQQQQ", 4, 4, notes_off, 0, 0, len(notes), len(notes), 4)
+    phdrs += struct.pack("<IIQQQQQQ", 1, 6, stack_off, STACK, 0, len(stack), len(stack), 0x1000)
+    phdrs += struct.pack("<IIQQQQQQ", 1, 5, stack_off + len(stack), TEXT, 0, 0, 0x2000, 0x1000)
+    return bytes(header) + phdrs + notes + stack
+
+
+def test_core_reader_threads_signal_and_stack(tmp_path):
+    path = tmp_path / "core"
+    path.write_bytes(build_core())
+    with CoreReader(path) as core:
+        assert core.process.pid == 4242
+        assert core.process.name == "crash"
+        assert core.process.args == "./crash --boom"
+        assert core.faulting_thread.tid == 4242
+        assert core.faulting_thread.reg("rip") == TEXT + 0x10
+
+        stop = core.stop_info()
+        assert stop.signal == "SIGSEGV"
+        assert stop.fault_address == 0x10
+        assert stop.si_code == 1
+
+        frames = core.stack_frames()
+        assert [f.pc for f in frames] == [TEXT + 0x10, TEXT + 0x1000, TEXT + 0x1100]
+        assert [f.index for f in frames] == [0, 1, 2]
+
+
+def test_core_reader_rejects_truncated_and_non_core(tmp_path):
+    data = build_core()
+    short = tmp_path / "short"
+    short.write_bytes(data[:200])
+    with pytest.raises(TruncatedDumpError):
+        CoreReader(short)
+
+    not_core = tmp_path / "not_core"
+    not_core.write_bytes(data[:16] + struct.pack("<H", 2) + data[18:])
+    with pytest.raises(InvalidDumpError):
+        CoreReader(not_core)
+
+
+def test_dump_session_from_dump(tmp_path):
+    path = tmp_path / "core"
+    path.write_bytes(build_core())
+    with DumpSession.from_dump(path) as dump:
+        assert (dump.kind, dump.arch) == ("core", "x86_64")
+        assert [t.tid for t in dump.threads()] == [4242]
+        assert [f.pc for f in dump.stack_trace(1)] == [TEXT + 0x10, TEXT + 0x1000, TEXT + 0x1100]
+        assert dump.stop_info().signal == "SIGSEGV"
+        assert {r.name: r.value for r in dump.read_registers()}["rip"] == TEXT + 0x10
+        # This core keeps no text and names no image: the registers, and no instructions.
+        context = dump.fault_context()
+        assert context.pc == TEXT + 0x10 and context.instructions == []
+        with pytest.raises(ValueError):
+            dump.stack_trace(2)
+
+        report = analyze_session(dump, dump.kind, max_frames=2)
+        assert (report.thread_id, report.signal, report.fault_address) == (4242, "SIGSEGV", 0x10)
+        assert report.fault_kind == FaultKind.NULL_DEREF
+        assert [f.pc for f in report.frames] == [TEXT + 0x10, TEXT + 0x1000] and report.total_frames == 3
+        assert report.render().endswith("... 1 more frames")
+
+    for name, data, error in (
+        ("empty", b"\x7fE", TruncatedDumpError),
+        ("short_core", build_core()[:200], TruncatedDumpError),
+        ("text", b"not a dump at all", InvalidDumpError),
+    ):
+        bad = tmp_path / name
+        bad.write_bytes(data)
+        with pytest.raises(error):
+            DumpSession.from_dump(bad)
+
+
+def test_core_fault_region_from_its_segments(tmp_path):
+    path = tmp_path / "core"
+    path.write_bytes(build_core(fault_addr=0x10))
+    with CoreReader(path) as core:
+        memory_map = core.memory_map()
+        assert [(e.start, e.perms) for e in memory_map] == [(TEXT, "r-xp"), (STACK, "rw-p")]
+        region = core.crash_context().fault_region
+        assert region is not None and region.kind is RegionKind.NULL_PAGE
+
+    for address, kind in ((STACK + 0x80, RegionKind.STACK), (STACK - 0x1000, RegionKind.UNMAPPED)):
+        path.write_bytes(build_core(fault_addr=address))
+        with DumpSession.from_dump(path) as dump:
+            region = dump.crash_context().fault_region
+            assert region is not None and region.kind is kind
+            report = analyze_session(dump)
+            assert report.fault_region == region
+    # The stack is the segment rsp points into; the address is just below it.
+    assert region.note == f"0x1000 bytes below the stack at 0x{STACK:x}"
+    assert f"Fault region: 0x{STACK - 0x1000:x} is unmapped; 0x1000 bytes below" in report.render()
+
+
+def test_stacks_stop_at_the_frame_limit_and_say_so(tmp_path):
+    path = tmp_path / "core"
+    path.write_bytes(build_core())
+    with CoreReader(path) as core:
+        assert [len(t.frames) for t in core.thread_backtraces(2)] == [2]
+        assert core.thread_backtraces(2)[0].truncated
+        # The kernel writes the thread that took the signal first.
+        assert core.thread_backtraces(2)[0].faulting
+        # Exactly as deep as the limit, or no limit: nothing was cut.
+        assert not core.thread_backtraces(3)[0].truncated
+        assert [(len(t.frames), t.truncated) for t in core.thread_backtraces(0)] == [(3, False)]
+    with DumpSession.from_dump(path, max_frames_per_thread=2) as dump:
+        assert len(dump.stack_trace()) == 2 and dump.threads()[0].truncated
+
+    report = analyze(AnalyzeRequest(binary="", core=str(path), max_frames_per_thread=2))
+    assert [f.pc for f in report.frames] == [TEXT + 0x10, TEXT + 0x1000]
+    assert report.threads[0].truncated and report.to_dict()["threads"][0]["truncated"] is True
+    assert "Stack cut off at 2 frames: the outermost callers were not captured" in report.render()
+    assert CrashReport.from_dict(report.to_dict()).threads[0].truncated
+    assert not analyze(AnalyzeRequest(binary="", core=str(path))).threads[0].truncated
+
+
+def test_cores_from_other_architectures(tmp_path):
+    arm = tmp_path / "core.arm64"
+    # x30 holds the caller's return address; the frame records hold the rest.
+    arm.write_bytes(build_core(machine=183, regs={"x30": TEXT + 0x1000}))
+    with DumpSession.from_dump(arm) as dump:
+        assert dump.arch == "aarch64"
+        assert [f.pc for f in dump.stack_trace()] == [TEXT + 0x10, TEXT + 0x1000, TEXT + 0x1100]
+        assert {r.name: r.value for r in dump.read_registers()}["pc"] == TEXT + 0x10
+
+    # No register layout for RISC-V: the thread list and signal still come through.
+    riscv = tmp_path / "core.riscv"
+    riscv.write_bytes(build_core(machine=243))
+    with DumpSession.from_dump(riscv) as dump:
+        assert dump.arch == "riscv"
+        assert [t.tid for t in dump.threads()] == [4242]
+        assert dump.stack_trace() == [] and dump.read_registers() == []
+        assert dump.fault_context() is None
+        assert dump.stop_info().signal == "SIGSEGV"
+
+
+def _uleb(value: int) -> bytes:
+    out = bytearray()
+    while True:
+        byte, value = value & 0x7F, value >> 7
+        out.append(byte | (0x80 if value else 0))
+        if not value:
+            return bytes(out)
+
+
+def build_aarch64_image(path: Path) -> None:
+    """An AArch64 executable loaded at TEXT with three functions and a DWARF v4 line table for src/jit.c."""
+    functions = [(b"boom", TEXT, 0x100), (b"parse", TEXT + 0xF80, 0x100), (b"main", TEXT + 0x1080, 0x100)]
+    # (address, line) rows; the bl instructions sit at 0x400ffc (line 21) and 0x4010fc (line 40).
+    rows = [(TEXT, 3), (TEXT + 0xF80, 20), (TEXT + 0xFFC, 21), (TEXT + 0x1000, 22), (TEXT + 0x1080, 39),
+            (TEXT + 0x10FC, 40)]
+    program = b"\x00\x09\x02" + struct.pack("<Q", rows[0][0]) + b"\x03" + _uleb(rows[0][1] - 1) + b"\x01"
+    for (addr, line), (prev_addr, prev_line) in zip(rows[1:], rows):
+        program += b"\x02" + _uleb(addr - prev_addr) + b"\x03" + _uleb(line - prev_line) + b"\x01"
+    program += b"\x02" + _uleb(TEXT + 0x1180 - rows[-1][0]) + b"\x00\x01\x01"
+    # min_inst=1, max_ops=1, default_is_stmt=1, line_base=-5, line_range=14, opcode_base=13
+    header = bytes([1, 1, 1, 0xFB, 14, 13, 0, 1, 1, 1, 1, 0, 0, 0, 1, 0, 0, 1])
+    header += b"src\x00\x00" + b"jit.c\x00\x01\x00\x00\x00"
+    body = struct.pack("<HI", 4, len(header)) + header + program
+    debug_line = struct.pack("<I", len(body)) + body
+
+    path.write_bytes(_image(183, functions, {".debug_line": debug_line}))
+
+
+# Where the first of `_image`'s sections is loaded: right after the ELF header and its one program header.
+FIRST_SECTION = TEXT + 64 + 56
+
+
+def _image(machine: int, functions, extra: Dict[str, bytes]) -> bytes:
+    """An executable loaded at TEXT with a symbol table for ``functions`` and the ``extra`` sections, in order."""
+    strtab, symtab = b"\x00", b"\x00" * 24
+    for name, value, size in functions:
+        symtab += struct.pack("<IBBHQQ", len(strtab), 0x12, 0, 1, value, size)
+        strtab += name + b"\x00"
+    names = [*extra, ".strtab", ".symtab", ".shstrtab"]
+    shstrtab = b"\x00" + b"".join(name.encode() + b"\x00" for name in names)
+    offsets = {name: shstrtab.index(b"\x00" + name.encode() + b"\x00") + 1 for name in names}
+
+    # (name, type, link, entsize, data)
+    sections = [(name, 1, 0, 0, blob) for name, blob in extra.items()]
+    strtab_index = len(sections) + 1
+    sections += [(".strtab", 3, 0, 0, strtab), (".symtab", 2, strtab_index, 24, symtab),
+                 (".shstrtab", 3, 0, 0, shstrtab)]
+    data = bytearray(64 + 56)
+    shdrs = b"\x00" * 64
+    for name, sh_type, link, entsize, blob in sections:
+        # Loaded sections sit where the one PT_LOAD puts their file offset; the rest have no address.
+        addr = TEXT + len(data) if name == ".eh_frame" else 0
+        shdrs += struct.pack("<IIQQQQIIQQ", offsets[name], sh_type, 0, addr, len(data), len(blob), link, 0, 1, entsize)
+        data += blob
+    shoff = len(data)
+    data += shdrs
+    data[:16] = b"\x7fELF\x02\x01\x01" + b"\x00" * 9
+    shnum = len(sections) + 1
+    struct.pack_into("<HHIQQQIHHHHHH", data, 16, 2, machine, 1, TEXT, 64, shoff, 0, 64, 56, 1, 64, shnum, shnum - 1)
+    struct.pack_into("<IIQQQQQQ", data, 64, 1, 5, 0, TEXT, TEXT, len(data), 0x2000, 0x1000)
+    return bytes(data)
+
+
+def test_arm64_core_symbolizes_against_its_debug_info(tmp_path):
+    image = tmp_path / "jit"
+    build_aarch64_image(image)
+    # Signed return addresses, in x30 and in the frame records: the signature sits above bit 48.
+    pac = 0x002A_0000_0000_0000
+    regs = {f"x{i}": 0x7FFF_0000_0000 + i for i in range(1, 29)}
+    regs.update({"x0": 0, "x30": pac | TEXT + 0x1000})
+    path = tmp_path / "core"
+    path.write_bytes(build_core(machine=183, regs=regs, text_file=image, pac=pac))
+
+    with CoreReader(path) as core:
+        report = CrashReport.from_context(core.crash_context(), source="core")
+    assert [f.pc for f in report.frames] == [TEXT + 0x10, TEXT + 0x1000, TEXT + 0x1100]
+    assert [(f.function, f.file, f.line) for f in report.frames] == [
+        ("boom", "src/jit.c", 3),
+        ("parse", "src/jit.c", 21),
+        ("main", "src/jit.c", 40),
+    ]
+    assert report.frames[1].offset == 0x80 and report.frames[0].module == "jit"
+
+    regs = report.registers
+    assert regs.arch is Architecture.AARCH64
+    assert (regs.instruction_pointer, regs.stack_pointer, regs.frame_pointer) == (TEXT + 0x10, STACK, STACK + 0x20)
+    assert regs.link_register == pac | TEXT + 0x1000
+    assert regs.dwarf(29) == STACK + 0x20 and regs.dwarf(31) == STACK and regs.dwarf(7) == 0x7FFF_0000_0007
+    assert "Fault address from: x0 + 0x10 (x0 = 0x0)\n" in report.render()
+
+
+def _eh_frame(address: int, fdes) -> bytes:
+    """``.eh_frame`` at ``address``: the x86-64 CIE GCC emits (CFA = rsp + 8, return address at CFA - 8),
+    then one FDE per (start, size, instructions), with pc-relative sdata4 addresses."""
+    def entry(body: bytes) -> bytes:
+        body += b"\x00" * (-(len(body) + 4) % 8)  # DW_CFA_nop padding
+        return struct.pack("<I", len(body)) + body
+
+    # id 0, version 1, "zR", code align 1, data align -8, RA column 16, FDE encoding pcrel|sdata4.
+    out = entry(b"\x00\x00\x00\x00\x01zR\x00\x01\x78\x10\x01\x1b" + b"\x0c\x07\x08\x90\x01")
+    for start, size, instructions in fdes:
+        at = len(out)
+        pc_begin = start - (address + at + 8)
+        out += entry(struct.pack("<Iii", at + 4, pc_begin, size) + b"\x00" + instructions)
+    return out + b"\x00\x00\x00\x00"
+
+
+def test_cfi_unwinds_frames_without_frame_pointers(tmp_path):
+    # boom is a leaf; parse does `sub $0x28, %rsp` at +4; main has `push %rbx` at +1 and, like
+    # _start, marks its return address undefined as the outermost frame.
+    fdes = [
+        (TEXT, 0x100, b""),
+        (TEXT + 0xF80, 0x100, b"\x44\x0e\x30"),
+        (TEXT + 0x1080, 0x100, b"\x41\x0e\x10\x83\x02\x07\x10"),
+    ]
+    functions = [(b"boom", TEXT, 0x100), (b"parse", TEXT + 0xF80, 0x100), (b"main", TEXT + 0x1080, 0x100)]
+    image = tmp_path / "server"
+    image.write_bytes(_image(62, functions, {".eh_frame": _eh_frame(FIRST_SECTION, fdes)}))
+
+    # No frame records: return addresses sit where the CFI says. rbp holds a stale record that
+    # the frame-pointer chain would follow to a function that is not on the stack.
+    stack = bytearray(0x100)
+    struct.pack_into("<Q", stack, 0x00, TEXT + 0x1000)
+    struct.pack_into("<QQ", stack, 0x10, 0, TEXT + 0x1500)
+    struct.pack_into("<QQ", stack, 0x30, TEXT + 0x1100, 0x1234)
+    path = tmp_path / "core"
+    path.write_bytes(build_core(regs={"rbp": STACK + 0x10}, text_file=image, stack=bytes(stack)))
+
+    with CoreReader(path) as core:
+        frames = core.stack_frames()
+        assert [(f.pc, f.function) for f in frames] == [
+            (TEXT + 0x10, "boom"),
+            (TEXT + 0x1000, "parse"),
+            (TEXT + 0x1100, "main"),
+        ]
+        row = core.unwind_row(TEXT + 0xFFF)
+        assert (row.cfa_register, row.cfa_offset, row.return_address_register) == (7, 0x30, 16)
+        assert core.unwind_row(TEXT + 0xF82).cfa_offset == 8 and core.unwind_row(TEXT + 0x1900) is None
+
+    # Without the image's CFI, the frame-pointer chain is all there is.
+    image.write_bytes(_image(62, functions, {}))
+    with CoreReader(path) as core:
+        assert [f.pc for f in core.stack_frames()] == [TEXT + 0x10, TEXT + 0x1500]
+
+
+def test_core_fault_context_disassembles_the_faulting_function(tmp_path):
+    if shutil.which("objdump") is None:
+        pytest.skip("no objdump")
+    # The null write in examples/crash: push %rbp; mov %rsp,%rbp; movq $0x0,-0x8(%rbp);
+    # mov -0x8(%rbp),%rax; movl $0x2a,(%rax); nop; pop %rbp; ret
+    code = bytes.fromhex("554889e548c745f800000000488b45f8c7002a000000905dc3")
+    image = tmp_path / "crash"
+    image.write_bytes(_image(62, [(b"crash", FIRST_SECTION, len(code))], {".text": code}))
+    path = tmp_path / "core"
+    path.write_bytes(build_core(regs={"rip": FIRST_SECTION + 16, "rax": 0}, text_file=image))
+
+    with DumpSession.from_dump(path) as dump:
+        context = dump.fault_context(before=2, after=1)
+        assert [(i.address - FIRST_SECTION, i.text) for i in context.instructions] == [
+            (4, "movq $0x0,-0x8(%rbp)"), (12, "mov -0x8(%rbp),%rax"), (16, "movl $0x2a,(%rax)"), (22, "nop")
+        ]
+        assert context.fault_line() == "Faulting instruction: movl $0x2a,(%rax): write through rax = 0x0"
+        assert len(context.code) == 16 and context.code.startswith(code[16:])
+
+    # A pc by the end of what the core holds still gets the bytes up to it.
+    path.write_bytes(build_core(regs={"rip": STACK + 0xF8}))
+    with DumpSession.from_dump(path) as dump:
+        assert len(dump.fault_context().code) == 8
+
+
+def test_only_the_main_image_resolves_to_the_executable(tmp_path):
+    # A library named like the (truncated) process name is still its own image.
+    program, library = tmp_path / "crash", tmp_path / "crash-helpers.so.1"
+    program.write_bytes(_image(62, [(b"boom", FIRST_SECTION, 0x100)], {}))
+    library.write_bytes(_image(62, [(b"helper", FIRST_SECTION, 0x100)], {}))
+    pc = LIB + FIRST_SECTION - TEXT + 0x10
+    for name in (b"crash", b""):
+        path = tmp_path / "core"
+        path.write_bytes(build_core(text_file=program, library=library, name=name))
+        with CoreReader(path, executable=program) as core:
+            assert core.symbolize(0, FIRST_SECTION + 0x10, is_return_address=False).function == "boom"
+            assert core.symbolize(1, pc, is_return_address=False).function == "helper"
@@ -0,0 +1,181 @@
+from dbgcopilot.analysis import Frame, HangCategory, ThreadBacktrace, Variable, analyze_hang, find_deadlocks, wait_chain
+from dbgcopilot.analysis.parsers import (
+    parse_cdb_locks,
+    parse_cdb_memory,
+    parse_gdb_memory,
+    parse_lldb_backtrace,
+    parse_lldb_memory,
+)
+from dbgcopilot.backends import gdb_batch
+from dbgcopilot.backends.gdb_batch import GdbBatchBackend
+
+LOCK_A, LOCK_B = 0x555555558040, 0x555555558080
+
+# Two pthreads taking lock_a/lock_b in opposite order, with libc debug info.
+GDB_PTHREAD_DEADLOCK = f"""\
+Thread 3 (Thread 0x7ffff7a89640 (LWP 7003) "worker_two"):
+#0  futex_wait (private=0, expected=2, futex_word=0x{LOCK_A:x} <lock_a>) at ../sysdeps/nptl/futex-internal.h:146
+#1  __GI___lll_lock_wait (futex=futex@entry=0x{LOCK_A:x} <lock_a>, private=0) at ./nptl/lowlevellock.c:49
+#2  0x00007ffff7e980f1 in lll_mutex_lock_optimized (mutex=0x{LOCK_A:x} <lock_a>) at ./nptl/pthread_mutex_lock.c:48
+#3  ___pthread_mutex_lock (mutex=0x{LOCK_A:x} <lock_a>) at ./nptl/pthread_mutex_lock.c:93
+#4  0x0000555555555236 in second_worker (arg=0x0) at deadlock.c:21
+
+Thread 2 (Thread 0x7ffff7c8a640 (LWP 7002) "worker_one"):
+#0  futex_wait (private=0, expected=2, futex_word=0x{LOCK_B:x} <lock_b>) at ../sysdeps/nptl/futex-internal.h:146
+#1  __GI___lll_lock_wait (futex=futex@entry=0x{LOCK_B:x} <lock_b>, private=0) at ./nptl/lowlevellock.c:49
+#2  0x00007ffff7e980f1 in lll_mutex_lock_optimized (mutex=0x{LOCK_B:x} <lock_b>) at ./nptl/pthread_mutex_lock.c:48
+#3  ___pthread_mutex_lock (mutex=0x{LOCK_B:x} <lock_b>) at ./nptl/pthread_mutex_lock.c:93
+#4  0x00005555555551e9 in first_worker (arg=0x0) at deadlock.c:13
+
+Thread 1 (Thread 0x7ffff7c8b740 (LWP 7001) "deadlock"):
+#0  __futex_abstimed_wait_common64 (private=128, cancel=true, abstime=0x0, op=265, expected=7002, futex_word=0x7ffff7c8a910) at ./nptl/futex-internal.c:57
+#1  0x00007ffff7e91624 in __pthread_clockjoin_ex (threadid=140737350510144, thread_return=0x0, clockid=0, abstime=0x0, block=true) at ./nptl/pthread_join_common.c:105
+#2  0x00005555555552a0 in main () at deadlock.c:33
+"""
+
+
+def test_pthread_wait_for_cycle(monkeypatch):
+    # __owner sits 8 bytes into the mutex: lock_a is held by LWP 7002, lock_b by 7003.
+    owners = {LOCK_A + 8: 7002, LOCK_B + 8: 7003}
+    reads = []
+
+    def fake_run_batch(argv, timeout):
+        cmd = argv[-3]  # the one command, just before "-ex detach"
+        if cmd == "thread apply all bt":
+            return "@@dbgcopilot:0@@\n" + GDB_PTHREAD_DEADLOCK, False
+        addr = int(cmd.split()[1], 16)
+        reads.append(addr)
+        raw = owners[addr].to_bytes(4, "little")
+        return "@@dbgcopilot:0@@\n" + f"0x{addr:x} <lock+8>:" + "".join(f"\t0x{b:02x}" for b in raw) + "\n", False
+
+    monkeypatch.setattr(gdb_batch, "run_batch", fake_run_batch)
+    with GdbBatchBackend("gdb", timeout=5).attach(7001) as session:
+        kind = analyze_hang(session)
+    assert sorted(reads) == [LOCK_A + 8, LOCK_B + 8]
+    assert kind.category is HangCategory.DEADLOCK and kind.waiters == [2, 3]
+    (report,) = kind.deadlocks
+    assert report.cycle == [2, 3]
+    one, two = report.threads
+    assert (one.tid, one.waits_for.symbol, [h.symbol for h in one.holds]) == (7002, "lock_b", ["lock_a"])
+    assert (two.tid, two.waits_for.symbol, [h.symbol for h in two.holds]) == (7003, "lock_a", ["lock_b"])
+    assert f"thread 2 (worker_one) holds lock_a (0x{LOCK_A:x}), waits for lock_b (0x{LOCK_B:x})" in report.describe()
+    assert report.describe().startswith("Deadlock: thread 2 (worker_one) -> thread 3 (worker_two) -> thread 2")
+
+
+def _waiter(thread_id, tid, lock):
+    frames = [Frame(0, function="pthread_mutex_lock", args=f"mutex=0x{lock:x}")]
+    return ThreadBacktrace(thread_id, tid=tid, frames=frames)
+
+
+def test_wait_chains_without_cycles_and_memory_parsers():
+    # 1 -> 2 -> 3 (3 is not waiting) is a chain, not a deadlock; an owner outside the snapshot ends it too.
+    threads = [_waiter(1, 101, 0x10), _waiter(2, 102, 0x20), ThreadBacktrace(3, tid=103), _waiter(4, 104, 0x40)]
+    owners = {0x10: 102, 0x20: 103, 0x40: 999}
+    assert find_deadlocks(threads, owners.get) == []
+    # Three-way cycle found from any starting point, reported once, lowest thread first.
+    ring = [_waiter(3, 103, 0x30), _waiter(1, 101, 0x10), _waiter(2, 102, 0x20)]
+    cycles = find_deadlocks(ring, {0x10: 102, 0x20: 103, 0x30: 101}.get)
+    assert [c.cycle for c in cycles] == [[1, 2, 3]]
+
+    frames = parse_lldb_backtrace(
+        "  * frame #0: 0x00007ffff7e980f1 libc.so.6`___pthread_mutex_lock(mutex=0x0000555555558040) at pthread_mutex_lock.c:93:11\n"
+    )
+    assert (frames[0].function, frames[0].args) == ("___pthread_mutex_lock", "mutex=0x0000555555558040")
+
+    assert parse_gdb_memory("0x555555558048 <lock_a+8>:\t0x5a\t0x1b\t0x00\t0x00\n") == bytes([0x5A, 0x1B, 0, 0])
+    assert parse_gdb_memory("Cannot access memory at address 0x10\n") is None
+    assert parse_lldb_memory("0x555555558048: 0x5a 0x1b 0x00 0x00\n") == bytes([0x5A, 0x1B, 0, 0])
+    assert parse_cdb_memory("00005555`55558048  5a 1b 00 00-?? ?? ?? ??  Z...\n") == bytes([0x5A, 0x1B, 0, 0])
+
+
+# examples/deadlock/rust: two workers locking Mutex<u32>s in opposite order. Rust's futex
+# mutex records no owner; the MutexGuard each worker already holds says who has what.
+RUST_A, RUST_B, RUST_C = 0x5555555A2B10, 0x5555555A2B30, 0x5555555A2B50
+
+
+def _rust_worker(thread_id, name, wants):
+    frames = [
+        Frame(0, function="syscall", file="syscall.S"),
+        Frame(1, function="std::sys::pal::unix::futex::futex_wait", args=f"futex=0x{wants:x}, expected=2"),
+        Frame(2, function="std::sys::sync::mutex::futex::Mutex::lock_contended", args=f"self=0x{wants:x}"),
+        Frame(3, function="std::sync::poison::mutex::Mutex<u32>::lock<u32>", args=f"self=0x{wants:x}"),
+        Frame(4, function=f"rust_deadlock::{name}", file="src/main.rs", line=14),
+    ]
+    return ThreadBacktrace(thread_id, tid=7000 + thread_id, name=name, frames=frames)
+
+
+def _guard(lock):
+    guard_type = "std::sync::poison::mutex::MutexGuard<u32>"
+    value = f"{guard_type} {{lock: 0x{lock:x}, poison: std::sync::poison::Guard {{panicking: false}}}}"
+    return Variable("guard", guard_type, value)
+
+
+class _RustSession:
+    def __init__(self):
+        self.asked = []
+        self.guards = {2: [_guard(RUST_A)], 3: [_guard(RUST_B)]}
+
+    def threads(self):
+        main = ThreadBacktrace(1, tid=7001, frames=[Frame(0, function="std::thread::JoinInner<()>::join")])
+        return [main, _rust_worker(2, "worker_one", RUST_B), _rust_worker(3, "worker_two", RUST_A),
+                _rust_worker(4, "worker_three", RUST_C)]
+
+    def frame_locals(self, thread=None, frame_index=0):
+        self.asked.append((thread, frame_index))
+        return self.guards.get(thread, [])
+
+    def read_memory(self, address, size):
+        raise AssertionError("a Rust mutex has no glibc __owner field to read")
+
+
+def test_rust_wait_chain_from_lock_guards():
+    session = _RustSession()
+    kind = analyze_hang(session)
+    # One locals read per blocked worker, of the frame that called lock.
+    assert session.asked == [(2, 4), (3, 4), (4, 4)]
+    assert kind.category is HangCategory.DEADLOCK and kind.waiters == [2, 3]
+    (report,) = kind.deadlocks
+    assert report.cycle == [2, 3]
+    assert [(w.waits_for.address, [h.address for h in w.holds]) for w in report.threads] == [
+        (RUST_B, [RUST_A]),
+        (RUST_A, [RUST_B]),
+    ]
+    one, two, three = kind.wait_chain
+    assert (one.thread_id, one.owner, one.owner_tid, one.owner_source) == (2, 3, 7003, "lock guard")
+    assert one.frame == "std::sync::poison::mutex::Mutex<u32>::lock<u32>"
+    assert two.describe() == f"thread 3 (worker_two) waits for 0x{RUST_A:x}, held by thread 2 (worker_one)"
+    # Nobody's guard holds lock C: said so, not guessed.
+    assert three.owner is None and three.describe().endswith(", owner unknown")
+    assert kind.describe().endswith(f"Wait chain:\n- thread 4 (worker_three) waits for 0x{RUST_C:x}, owner unknown")
+
+
+def test_wait_chain_owner_unknown_and_cdb_locks():
+    # Sampled stacks alone: what each thread waits for, but no owners.
+    critsec = Frame(1, function="RtlEnterCriticalSection", module="ntdll")
+    windows = ThreadBacktrace(2, tid=102, frames=[Frame(0, function="NtWaitForAlertByThreadId"), critsec])
+    threads = [_waiter(1, 101, 0x10), windows]
+    first, second = wait_chain(threads, lambda _address: None)
+    assert first.describe() == "thread 1 waits for 0x10, owner unknown"
+    assert second.lock is None
+    assert second.describe() == (
+        "thread 2 waits for a lock its stack does not name in `RtlEnterCriticalSection`, owner unknown"
+    )
+    # An owner outside the snapshot is still named.
+    (link,) = wait_chain(threads[:1], {0x10: 999}.get)
+    assert link.describe() == "thread 1 waits for 0x10, held by OS thread 999 (not in the snapshot)"
+
+    locks = """CritSec deadlock!lock_a+0 at 00007ff6`1a2d8040
+WaiterWoken        No
+LockCount          1
+RecursionCount     1
+OwningThread       1a2c
+EntryCount         0
+ContentionCount    1
+*** Locked
+
+CritSec ntdll!LdrpLoaderLock+0 at 00007ffb`2a1f65c8
+LockCount          NOT LOCKED
+RecursionCount     0
+OwningThread       0
+"""
+    assert parse_cdb_locks(locks) == {0x7FF61A2D8040: 0x1A2C}
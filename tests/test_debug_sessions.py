@@ -0,0 +1,316 @@
+from dbgcopilot.analysis import AccessKind, AnalyzeRequest, FaultKind, Optimized, analyze, analyze_session
+from dbgcopilot.backends import batch, cdb_batch, gdb_batch
+from dbgcopilot.analysis.parsers import parse_cdb_exception
+from dbgcopilot.backends.batch import detect_session_backend
+from dbgcopilot.backends.cdb_batch import CdbBackend
+from dbgcopilot.backends.gdb_batch import GdbBatchBackend
+from dbgcopilot.backends.lldb_batch import LldbBatchBackend
+
+CDB_OUTPUT = """\
+Loading Dump File [C:\\dumps\\crash.dmp]
+User Mini Dump File: Only registers, stack and portions of memory are available
+0:000> .echo @@dbgcopilot:0@@; .exr -1; .echo @@dbgcopilot:1@@; .ecxr; ...
+@@dbgcopilot:0@@
+ExceptionAddress: 00007ff6`1a2b101a (crash!crash_demo::crash+0x000000000000001a)
+   ExceptionCode: c0000005 (Access violation)
+  ExceptionFlags: 00000000
+NumberParameters: 2
+   Parameter[0]: 0000000000000001
+   Parameter[1]: 0000000000000000
+Attempt to write to address 0000000000000000
+@@dbgcopilot:1@@
+rax=0000000000000000 rbx=0000000000000000 rcx=000000000000002a
+rip=00007ff61a2b101a rsp=000000a0000ff6b8 rbp=0000000000000000
+@@dbgcopilot:2@@
+$ip=00007ff6`1a2b101a
+@@dbgcopilot:3@@
+ # Child-SP          RetAddr               Call Site
+00 000000a0`000ff6b8 00007ff6`1a2b1045     crash!crash_demo::crash+0x1a [C:\\src\\main.cpp @ 11]
+01 000000a0`000ff6c0 00007ffd`3c5e7374     crash!main+0x15 [C:\\src\\main.cpp @ 16]
+02 000000a0`000ff6f0 00000000`00000000     kernel32!BaseThreadInitThunk+0x14
+@@dbgcopilot:4@@
+.  0  Id: 1a2c.3b4c Suspend: 0 Teb: 000000a0`00123000 Unfrozen
+@@dbgcopilot:5@@
+start             end                 module name
+00007ff6`1a2b0000 00007ff6`1a2d5000   crash      (private pdb symbols)  C:\\src\\crash.pdb
+00007ffd`3c5d0000 00007ffd`3c692000   kernel32   (deferred)
+@@dbgcopilot:6@@
+prv param  int * p = 0x00000000`00000000
+prv local  int value = <value unavailable>
+@@dbgcopilot:7@@
+**************************************************************
+*                                                            *
+*                  HEAP ERROR DETECTED                       *
+*                                                            *
+**************************************************************
+
+Details:
+
+Heap address:  000001c9a5e40000
+Error address: 000001c9`a5e5a2b0
+Error type: HEAP_FAILURE_BLOCK_NOT_BUSY
+Details:    The caller performed an operation (such as a free
+            or size check) that is illegal on a free block.
+
+LFH Key                   : 0x5f4a8e1c2b3d4a10
+Termination on corruption : ENABLED
+          Heap     Flags   Reserv  Commit  Virt   Free  List   UCR  Virt  Lock  Fast
+                            (k)     (k)    (k)     (k) length      blocks cont. heap
+-------------------------------------------------------------------------------------
+000001c9a5e40000 00000002    1020    348   1020     25    11     1    0      0   LFH
+000001c9a5d10000 00008000      64      4     64      2     1     1    0      0
+-------------------------------------------------------------------------------------
+@@dbgcopilot:8@@
+ heap @ 000001c9a5e40000
+group-by: TOTSIZE max-display: 20
+    size     #blocks     total     ( %) (percent of total busy bytes)
+    1000 1 - 1000  (34.56)
+    30 2c - 840  (17.86)
+    8 4 - 20  (0.27)
+@@dbgcopilot:9@@
+crash!crash_demo::crash+0x8 [C:\\src\\main.cpp @ 10]:
+00007ff6`1a2b1008 4883ec18        sub     rsp,18h
+00007ff6`1a2b100c 48c744240800000000 mov qword ptr [rsp+8],0
+00007ff6`1a2b1015 488b442408      mov     rax,qword ptr [rsp+8]
+@@dbgcopilot:10@@
+crash!crash_demo::crash+0x1a [C:\\src\\main.cpp @ 11]:
+00007ff6`1a2b101a c7002a000000    mov     dword ptr [rax],2Ah
+00007ff6`1a2b1020 4883c418        add     rsp,18h
+@@dbgcopilot:end@@
+quit:
+"""
+
+
+def test_cdb_session_parses_dump(tmp_path, monkeypatch):
+    calls = []
+
+    def fake_run_batch(argv, timeout):
+        calls.append(list(argv))
+        return CDB_OUTPUT, False
+
+    monkeypatch.setattr(cdb_batch, "run_batch", fake_run_batch)
+    backend = CdbBackend("cdb.exe", timeout=5)
+    session = backend.load_dump("crash.dmp", program=str(tmp_path / "crash.exe"))
+
+    stop = session.stop_info()
+    assert stop.exception_code == 0xC0000005
+    assert stop.signal == "SIGSEGV" and stop.fault_address == 0
+    assert "write to" in stop.description
+    argv = calls[0]
+    assert argv[argv.index("-z") + 1] == "crash.dmp"
+    assert argv[argv.index("-i") + 1] == str(tmp_path)
+    assert argv[-1].endswith("; q")
+
+    # A session reruns cdb per request; index the canned sections to match.
+    def run_sections(commands):
+        sections = batch.split_sections(CDB_OUTPUT, "@@dbgcopilot:")
+        offset = {".exr -1": 0, ".ecxr": 1, "r $ip": 2, "kn 100": 3, "~*kn 100": 3, "r": 1, "~.": 4, "lm": 5,
+                  ".frame 0": 4, "dv /i /t": 6, "!heap -s": 7, "!heap -stat -h 0": 8, "ub @$ip L5": 9, "u @$ip L6": 10}
+        return "", [sections[str(offset[c])] for c in commands]
+
+    monkeypatch.setattr(session, "_run", run_sections)
+    frames = session.stack_trace()
+    assert [f.function for f in frames] == ["crash_demo::crash", "main", "BaseThreadInitThunk"]
+    assert frames[0].pc == 0x7FF61A2B101A and frames[1].pc == 0x7FF61A2B1045
+    assert (frames[0].file, frames[0].line) == ("C:\\src\\main.cpp", 11)
+    assert frames[2].module == "kernel32"
+    regs = {r.name: r.value for r in session.read_registers()}
+    assert regs["rcx"] == 0x2A and regs["rip"] == 0x7FF61A2B101A
+
+    report = analyze_session(session, "cdb")
+    assert report.fault_kind == FaultKind.NULL_DEREF
+    assert report.frames[0].function == "crash_demo::crash"
+    assert (report.thread_id, report.exception_code, report.fault_address) == (0x3B4C, 0xC0000005, 0)
+    assert report.access_kind is AccessKind.WRITE
+    # cdb marks no pc in a disassembly; the instruction at rip is the faulting one.
+    assert report.fault_context.fault_line() == "Faulting instruction: mov dword ptr [rax],2Ah: write through rax = 0x0"
+    assert [i.address for i in report.fault_context.instructions][2:4] == [0x7FF61A2B1015, 0x7FF61A2B101A]
+    assert [(f.module, f.offset) for f in report.frames] == [("crash", 0x1A), ("crash", 0x15), ("kernel32", 0x14)]
+    assert [m.name for m in report.modules] == ["crash", "kernel32"]
+    assert report.modules[0].contains(0x7FF61A2B101A)
+
+    data = report.to_dict()
+    assert data["frames"][0] == {
+        "index": 0,
+        "pc": "0x7ff61a2b101a",
+        "module": "crash",
+        "symbol": "crash_demo::crash",
+        "raw_symbol": None,
+        "engine": None,
+        "offset": 0x1A,
+        "source_file": "C:\\src\\main.cpp",
+        "line": 11,
+        "column": None,
+        "locals": [
+            {"name": "p", "type": "int *", "value": "0x0", "argument": True, "pretty_printed": None},
+            {"name": "value", "type": "int", "value": "<optimized out>", "argument": False, "pretty_printed": None},
+        ],
+    }
+    # Only the top frame's variables are read.
+    assert report.frames[1].locals == [] and report.frames[0].locals[1].optimized_out
+    assert data["exception_code"] == "0xc0000005" and data["thread_id"] == 0x3B4C
+    assert report.render() == analyze_session(session, "cdb").render()
+    assert "Exception code: 0xc0000005" in report.render()
+    assert "Access: write" in report.render()
+
+    heap = report.heap
+    assert (heap.engine, heap.reserved, heap.committed, heap.note) == ("cdb", 1084 * 1024, 352 * 1024, None)
+    assert [(b.limit, b.count) for b in heap.buckets] == [(16, 4), (64, 0x2C), (4096, 1)]
+    assert heap.corruption == ["HEAP_FAILURE_BLOCK_NOT_BUSY at 0x1c9a5e5a2b0"]
+    assert "\nHeap: 352 KiB committed, 1.1 MiB reserved (cdb)\n" in report.render()
+    assert "Heap allocations by size: <=16 B: 4, <=64 B: 44, <=4 KiB: 1" in report.render()
+    assert data["heap"]["corruption"] == heap.corruption
+
+    # Without cdb's "Attempt to ..." summary, the raw parameters still say it was a write to 0.
+    exr = "\n".join(line for line in batch.split_sections(CDB_OUTPUT, "@@dbgcopilot:")["0"].splitlines()
+                    if not line.startswith("Attempt"))
+    stop = parse_cdb_exception(exr)
+    assert (stop.access, stop.fault_address) == (AccessKind.WRITE, 0)
+    assert parse_cdb_exception("   ExceptionCode: c0000094 (Integer divide-by-zero)").access is AccessKind.UNKNOWN
+
+
+def test_gdb_session_attaches_and_detaches(monkeypatch):
+    calls = []
+
+    def fake_run_batch(argv, timeout):
+        calls.append(list(argv))
+        return "@@dbgcopilot:0@@\n[Switching to thread 2]\n@@dbgcopilot:1@@\n#0  worker () at src/main.rs:7\n", False
+
+    monkeypatch.setattr(gdb_batch, "run_batch", fake_run_batch)
+    with GdbBatchBackend("gdb", timeout=5).attach(4242) as session:
+        frames = session.stack_trace(thread=2)
+    assert session.closed
+    assert [f.function for f in frames] == ["worker"]
+    argv = calls[0]
+    assert argv[argv.index("-p") + 1] == "4242"
+    assert "thread 2" in argv and "bt" in argv
+    assert argv[-2:] == ["-ex", "detach"]
+
+
+def test_commands_run_at_the_fault(monkeypatch):
+    calls = []
+
+    def fake_run_batch(argv, timeout):
+        calls.append(list(argv))
+        return "Program received signal SIGSEGV\n@@dbgcopilot:0@@\nptr = 0x0\n@@dbgcopilot:1@@\n#0  crash ()\n", False
+
+    monkeypatch.setattr(gdb_batch, "run_batch", fake_run_batch)
+    backend = GdbBatchBackend("gdb", timeout=5)
+    backend.load("rust_crash", args=["--fast"])
+    assert backend.run_at_fault(["info locals", "bt"]) == ["ptr = 0x0", "#0  crash ()"]
+    argv = calls[0]
+    assert argv.index("run") < argv.index("info locals") < argv.index("bt")
+    assert argv[-3:] == ["--args", "rust_crash", "--fast"]
+
+
+def test_frame_locals_keep_optimized_out_variables(monkeypatch):
+    gdb_answers = {
+        "frame 0": "#0  rust_crash::crash () at src/main.rs:9",
+        "info args": "No arguments.",
+        "info locals": "ptr = 0x0\nsettings = {\n  retries = 3,\n  verbose = false\n}\nlen = <optimized out>",
+        "whatis ptr": "type = *mut i32",
+        "whatis settings": "type = rust_crash::Settings",
+        "whatis len": "type = usize",
+        "thread 2": "[Switching to thread 2]",
+        "frame 1": "#1  worker (id=7) at worker.c:12",
+    }
+    session = GdbBatchBackend("gdb", timeout=5).load_dump("core", program="rust_crash")
+    monkeypatch.setattr(session, "_run", lambda commands: ("", [gdb_answers.get(c, "") for c in commands]))
+    variables = session.frame_locals()
+    assert [(v.name, v.type_name, v.value) for v in variables] == [
+        ("ptr", "*mut i32", "0x0"),
+        ("settings", "rust_crash::Settings", "{ retries = 3, verbose = false }"),
+        ("len", "usize", Optimized.OUT),
+    ]
+    assert variables[0].describe() == "ptr = 0x0"
+    assert variables[2].optimized_out and variables[2].describe() == "len = <optimized out>"
+    gdb_answers.update({"info args": "id = 7", "info locals": "No locals.", "whatis id": "type = int"})
+    assert [(v.name, v.type_name, v.argument) for v in session.frame_locals(thread=2, frame_index=1)] == [
+        ("id", "int", True)
+    ]
+
+    lldb_answers = {
+        "frame variable --no-locals": "(int) id = <variable not available>\n",
+        "frame variable --no-args": "(int *) p = 0x0000000000000000\n(void (*)(int)) handler = 0x0000555555555189",
+    }
+    session = LldbBatchBackend("lldb", timeout=5).load_dump("core", program="crash")
+    monkeypatch.setattr(session, "_run", lambda commands: ("", [lldb_answers.get(c, "") for c in commands]))
+    assert [(v.name, v.type_name, v.value, v.argument) for v in session.frame_locals()] == [
+        ("id", "int", Optimized.OUT, True),
+        ("p", "int *", "0x0", False),
+        ("handler", "void (*)(int)", "0x555555555189", False),
+    ]
+
+    cdb_answer = """\
+prv local           int * p = 0x00000000`00000000
+prv param           int id = 0n7
+prv local           int count = <value unavailable>
+"""
+    session = CdbBackend("cdb.exe", timeout=5).load_dump("crash.dmp")
+    calls = []
+    monkeypatch.setattr(session, "_run", lambda commands: (calls.append(commands), ("", ["", "", cdb_answer]))[1])
+    assert [(v.name, v.type_name, v.value, v.argument) for v in session.frame_locals(frame_index=10)] == [
+        ("id", "int", "7", True),
+        ("p", "int *", "0x0", False),
+        ("count", "int", Optimized.OUT, False),
+    ]
+    assert calls == [[".ecxr", ".frame a", "dv /i /t"]]
+
+
+def test_backend_selected_at_runtime(tmp_path, monkeypatch):
+    monkeypatch.setattr(CdbBackend, "find_executable", classmethod(lambda cls: "C:\\dbg\\cdb.exe"))
+    for name in ("cdb", "windbg"):
+        backend = detect_session_backend(preferred=name)
+        assert isinstance(backend, CdbBackend) and backend.cdb_path == "C:\\dbg\\cdb.exe"
+
+    class _FakeSession:
+        closed = False
+
+        def stop_info(self):
+            from dbgcopilot.analysis import StopInfo
+
+            return StopInfo(signal="SIGFPE", description="Integer divide-by-zero", exception_code=0xC0000094)
+
+        def stack_trace(self, thread=None):
+            from dbgcopilot.analysis import Frame
+
+            # Recursion deeper than the frame limit; an engine prints all of it.
+            return [Frame(i, pc=0x1000 + i, function="divide") for i in range(5)]
+
+        def frame_locals(self, thread=None, frame_index=0):
+            raise NotImplementedError
+
+        def read_registers(self):
+            return []
+
+        def current_tid(self):
+            return 7
+
+        def modules(self):
+            return []
+
+        def threads(self):
+            from dbgcopilot.analysis import ThreadBacktrace
+
+            return [ThreadBacktrace(1, tid=7, frames=self.stack_trace()), ThreadBacktrace(2, tid=8)]
+
+        def close(self):
+            self.closed = True
+
+    class _FakeBackend:
+        name = "fake"
+
+        def load_dump(self, path, program=None):
+            self.session = _FakeSession()
+            return self.session
+
+    binary = tmp_path / "app.exe"
+    dump = tmp_path / "app.dmp"
+    binary.write_bytes(b"")
+    dump.write_bytes(b"not a dump")
+    backend = _FakeBackend()
+    report = analyze(AnalyzeRequest(binary=str(binary), core=str(dump), backend=backend, max_frames_per_thread=3))
+    assert report.source == "fake" and report.thread_id == 7
+    assert len(report.frames) == 3 and [t.truncated for t in report.threads] == [True, False]
+    assert report.fault_kind == FaultKind.DIVIDE_BY_ZERO
+    assert backend.session.closed
@@ -0,0 +1,107 @@
+import dataclasses
+import json
+
+from dbgcopilot.analysis import AnalyzeRequest, CrashReport, FaultContext, Register, analyze, api
+from dbgcopilot.analysis.disasm import build_fault_context, instruction_window
+from dbgcopilot.analysis.parsers import parse_disassembly
+from dbgcopilot.analysis.prompt import PromptBuilder
+from dbgcopilot.backends import gdb_batch
+from dbgcopilot.backends.gdb_batch import GdbBatchBackend
+
+# The null write in examples/crash: `*ptr = 42` with ptr = NULL.
+GDB_OUTPUT = """\
+
+Program received signal SIGSEGV, Segmentation fault.
+0x0000555555555139 in crash () at crash.c:4
+@@dbgcopilot:siginfo@@
+$1 = (void *) 0x0
+@@dbgcopilot:bt@@
+#0  0x0000555555555139 in crash () at crash.c:4
+#1  0x0000555555555150 in main () at crash.c:8
+@@dbgcopilot:regs@@
+rax            0x0                 0
+rbx            0x7fffffffe0b8      140737488347320
+rbp            0x7fffffffdf90      0x7fffffffdf90
+rsp            0x7fffffffdf90      0x7fffffffdf90
+rip            0x555555555139      0x555555555139 <crash+16>
+@@dbgcopilot:disasm@@
+Dump of assembler code for function crash:
+   0x0000555555555129 <+0>:\tpush   %rbp
+   0x000055555555512a <+1>:\tmov    %rsp,%rbp
+   0x000055555555512d <+4>:\tmovq   $0x0,-0x8(%rbp)
+   0x0000555555555135 <+12>:\tmov    -0x8(%rbp),%rax
+=> 0x0000555555555139 <+16>:\tmovl   $0x2a,(%rax)
+   0x000055555555513f <+22>:\tnop
+   0x0000555555555140 <+23>:\tpop    %rbp
+   0x0000555555555141 <+24>:\tret
+End of assembler dump.
+@@dbgcopilot:insns@@
+=> 0x555555555139 <crash+16>:\tmovl   $0x2a,(%rax)
+   0x55555555513f <crash+22>:\tnop
+"""
+
+
+def test_gdb_capture_shows_the_faulting_instruction_and_gates_it_in_the_prompt(tmp_path, monkeypatch):
+    monkeypatch.setattr(gdb_batch, "run_batch", lambda argv, timeout: (GDB_OUTPUT, False))
+    monkeypatch.setattr(api, "_detect_backend", lambda request: GdbBatchBackend("gdb", timeout=5))
+    (tmp_path / "crash").write_text("")
+    (tmp_path / "core").write_bytes(b"not an ELF core")
+    request = AnalyzeRequest(binary=str(tmp_path / "crash"), core=str(tmp_path / "core"), no_llm=True)
+    report = analyze(request)
+
+    context = report.fault_context
+    assert context.pc == 0x555555555139 and len(context.instructions) == 8
+    assert context.fault_line() == "Faulting instruction: movl $0x2a,(%rax): write through rax = 0x0"
+    text = report.render()
+    assert "Faulting instruction: movl $0x2a,(%rax): write through rax = 0x0\nDisassembly:\n" in text
+    assert "=> 0x555555555139 <+16>: movl $0x2a,(%rax)" in text
+    assert text.index("Disassembly:") < text.index("Backtrace:")
+    # Reports always carry it; the prompt only when asked.
+    assert "Faulting instruction" not in report.prompt
+    asked = analyze(dataclasses.replace(request, llm_config={"prompt_fault_context": "true"}))
+    assert "Faulting instruction: movl $0x2a,(%rax): write through rax = 0x0" in asked.prompt
+    assert "=> 0x555555555139 <+16>: movl $0x2a,(%rax)" in asked.prompt
+
+    data = json.loads(report.to_json())
+    assert data["fault_context"]["instruction_pointer"] == "0x555555555139"
+    assert data["fault_context"]["operands"] == [{"name": "rax", "value": "0x0"}]
+    assert [i["faulting"] for i in data["fault_context"]["instructions"]].index(True) == 4
+    assert CrashReport.from_dict(data).fault_context == context
+
+
+def test_disassembly_window_and_other_engines():
+    # lldb marks the pc with "->"; the window keeps up to `before` and `after` around it.
+    lldb = parse_disassembly(
+        "crash`crash:\n"
+        "    0x100003f60 <+0>:  pushq  %rbp\n"
+        "    0x100003f61 <+1>:  movq   %rsp, %rbp\n"
+        "    0x100003f64 <+4>:  movq   $0x0, -0x8(%rbp)\n"
+        "    0x100003f6c <+12>: movq   -0x8(%rbp), %rax\n"
+        "->  0x100003f70 <+16>: movl   $0x2a, (%rax)\n"
+        "    0x100003f76 <+22>: popq   %rbp\n"
+        "    0x100003f77 <+23>: retq\n"
+    )
+    window = instruction_window(lldb, 0x100003F70, before=1, after=1)
+    assert [(i.address, i.faulting) for i in window] == [
+        (0x100003F6C, False), (0x100003F70, True), (0x100003F76, False)
+    ]
+    registers = [Register("rax", 0), Register("rip", 0x100003F70)]
+    context = build_fault_context(registers, lldb, before=1, after=1)
+    assert context.fault_line() == "Faulting instruction: movl $0x2a, (%rax): write through rax = 0x0"
+    assert FaultContext.from_dict(context.to_dict()) == context
+
+    # A read through an AArch64 base register.
+    arm = build_fault_context(
+        [Register("x8", 0x0), Register("pc", 0x4005D4)], parse_disassembly("=> 0x4005d4 <crash+20>:\tldr\tw9, [x8]\n")
+    )
+    assert arm.fault_line() == "Faulting instruction: ldr w9, [x8]: read through x8 = 0x0"
+
+    # Raw bytes when there is no disassembler, and nothing without registers.
+    raw = build_fault_context(registers, code=bytes.fromhex("c7002a000000"))
+    assert raw.instructions == [] and raw.lines() == ["Code at 0x100003f70: c7 00 2a 00 00 00"]
+    assert build_fault_context([], lldb) is None
+    # A pc no disassembly covers (a jump into unmapped memory) gives no window.
+    assert instruction_window([dataclasses.replace(i, faulting=False) for i in lldb], 0x10) == []
+
+    assert PromptBuilder.from_config({"prompt_fault_context": True}).fault_context
+    assert not PromptBuilder.from_config({"prompt_fault_context": "false"}).fault_context
@@ -0,0 +1,85 @@
+import os
+
+import pytest
+
+from dbgcopilot import cli
+from dbgcopilot.analysis import CrashContext, Frame, StopInfo, api
+from dbgcopilot.dumps import discover
+from dbgcopilot.dumps.discover import NoDumpError, core_pattern_location, dump_locations, find_dumps, latest_dump
+
+
+def _dump(path, mtime, data=b""):
+    path.parent.mkdir(parents=True, exist_ok=True)
+    path.write_bytes(data)
+    os.utime(path, (mtime, mtime))
+    return str(path)
+
+
+def test_core_pattern_names_the_directory_and_the_files_of_one_program(tmp_path):
+    named = core_pattern_location("/srv/bin/crash", "/var/crash/core.%e.%p.%t")
+    assert named.directory == "/var/crash" and not named.verify
+    assert named.pattern.match("core.crash.4242.1800000000")
+    assert not named.pattern.match("core.other.4242.1800000000")
+    # The kernel keeps 15 bytes of the name.
+    assert core_pattern_location("a_very_long_program_name", "core.%e").pattern.match("core.a_very_long_pro")
+
+    # The default pattern does not name the program: the cores are opened to tell.
+    default = core_pattern_location("crash", "core", uses_pid=True, cwd=str(tmp_path))
+    assert default.directory == str(tmp_path) and default.verify
+    assert default.pattern.match("core.4242") and not default.pattern.match("core")
+
+    systemd = core_pattern_location("crash", "|/usr/lib/systemd/systemd-coredump %P %u %g %s %t %c %h")
+    assert systemd.directory == discover.SYSTEMD_COREDUMP_DIR
+    assert systemd.pattern.match("core.crash.1000.8f3a1b2c.4242.1800000000000000.zst")
+    assert core_pattern_location("crash", "|/usr/bin/unknown-handler %p") is None
+
+
+def test_newest_dump_of_the_program_wins_and_none_is_an_error(tmp_path, monkeypatch):
+    wer = tmp_path / "CrashDumps"
+    _dump(wer / "crash.exe.100.dmp", 1_000)
+    newest = _dump(wer / "crash.exe.200.dmp", 3_000)
+    _dump(wer / "other.exe.300.dmp", 5_000)
+    locations = dump_locations("crash.exe", platform="win32", environ={"LOCALAPPDATA": str(tmp_path)})
+    assert [d.path for d in find_dumps("crash.exe", locations)] == [newest, str(wer / "crash.exe.100.dmp")]
+    # "crash" finds crash.exe's dumps too.
+    assert latest_dump("crash", dump_locations("crash", platform="win32", environ={"LOCALAPPDATA": str(tmp_path)}))
+
+    # A crashpad database names its dumps by UUID: each one is opened to see whose it is.
+    db = tmp_path / "crashpad"
+    owners = {
+        _dump(db / "completed" / "1b4e28ba.dmp", 9_000): ["other.exe", "ntdll.dll"],
+        _dump(db / "pending" / "6fa459ea.dmp", 8_000): ["crash.exe", "ntdll.dll"],
+    }
+    monkeypatch.setattr(discover, "dump_program_names", lambda path: owners[path])
+    locations = dump_locations("crash.exe", extra_dirs=[str(db)], platform="win32", environ={"LOCALAPPDATA": "/x"})
+    assert latest_dump("crash.exe", locations).path == str(db / "pending" / "6fa459ea.dmp")
+
+    with pytest.raises(NoDumpError, match=r"no dump of missing.exe found in .*CrashDumps \(WER LocalDumps\)"):
+        latest_dump("missing.exe", locations)
+
+
+def test_last_analyzes_the_newest_dump_and_refuses_a_stale_one(tmp_path, monkeypatch, capsys):
+    analyzed = []
+
+    def analyze_core(request):
+        analyzed.append(request.core)
+        return api._report_from_context(
+            CrashContext(stop=StopInfo(signal="SIGSEGV", fault_address=0), frames=[Frame(0, pc=0x1000)]), "core"
+        )
+
+    monkeypatch.setattr(api, "_analyze_core", analyze_core)
+    monkeypatch.setattr(discover, "dump_program_names", lambda path: ["crash"])
+    program = tmp_path / "crash"
+    program.write_text("")
+    _dump(tmp_path / "dumps" / "core.1", 1_000)
+    newest = _dump(tmp_path / "dumps" / "core.2", 2_000)
+    argv = [str(program), "--dir", str(tmp_path / "dumps"), "--no-llm", "--no-history", "--format", "json"]
+
+    assert cli.last_main(argv) == cli.ExitCode.OK
+    assert analyzed == [newest] and f"analyzing {newest}, written " in capsys.readouterr().err
+
+    assert cli.last_main(argv + ["--since", "2026-10-01"]) == cli.ExitCode.CAPTURE_FAILED
+    assert "before --since; not analyzing it" in capsys.readouterr().err and analyzed == [newest]
+
+    assert cli.last_main([str(tmp_path / "nothing"), "--dir", str(tmp_path / "empty")]) == cli.ExitCode.CAPTURE_FAILED
+    assert "no dump of nothing found in " in capsys.readouterr().err
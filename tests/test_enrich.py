@@ -0,0 +1,95 @@
+import json
+
+from dbgcopilot.analysis import AnalyzeRequest, CrashContext, CrashReport, Frame, Module, StopInfo, analyze, api
+from dbgcopilot.analysis import enrich
+from dbgcopilot.analysis.enrich import SystemModuleEnricher, enrich_report, register_enricher, registered_enrichers
+
+
+class OwnersEnricher:
+    name = "owners"
+
+    def enrich(self, report):
+        for module in report.modules:
+            if module.name == "libbilling.so":
+                report.annotate(self.name, "libbilling.so is owned by the payments team (#payments-oncall)")
+
+
+class BrokenEnricher:
+    name = "broken"
+
+    def enrich(self, report):
+        raise KeyError("no such team")
+
+
+def _report():
+    return api._report_from_context(
+        CrashContext(
+            stop=StopInfo(signal="SIGSEGV", fault_address=0x0),
+            frames=[
+                Frame(0, pc=0x7FFFF7E4A000, function="__memmove_avx_unaligned_erms", module="libc.so.6"),
+                Frame(1, pc=0x7FFFF7F01200, function="charge_card", offset=0x2C, module="libbilling.so"),
+            ],
+            modules=[Module("libc.so.6", 0x7FFFF7E00000, 0x100000), Module("libbilling.so", 0x7FFFF7F00000, 0x4000)],
+        ),
+        "core",
+    )
+
+
+def test_enrichers_annotate_the_prompt_text_and_json(tmp_path, monkeypatch):
+    monkeypatch.setattr(api, "_analyze_core", lambda request: _report())
+    (tmp_path / "core").write_text("")
+    enrichers = [BrokenEnricher(), SystemModuleEnricher(), OwnersEnricher()]
+    report = analyze(AnalyzeRequest(binary="", core=str(tmp_path / "core"), no_llm=True, enrichers=enrichers))
+
+    # The broken enricher is skipped; the others still run.
+    assert [a.source for a in report.annotations] == ["system_modules", "owners"]
+    system = "Note (system_modules): the fault is inside libc.so.6, a system library; the bug is far more likely"
+    assert f"{system} in what frame 1 (charge_card) passed it" in report.prompt
+    assert "Note (owners): libbilling.so is owned by the payments team" in report.render()
+    data = json.loads(report.to_json())
+    assert data["annotations"][1] == {
+        "source": "owners", "text": "libbilling.so is owned by the payments team (#payments-oncall)"
+    }
+    assert CrashReport.from_dict(data).annotations == report.annotations
+
+    # The analysis itself is left as it was.
+    assert report.fault_kind is _report().fault_kind and report.frames == _report().frames
+    assert analyze(AnalyzeRequest(binary="", core=str(tmp_path / "core"), no_llm=True, enrichers=[])).annotations == []
+
+    # A hang's top frame is in a system call as a rule; that is not worth a note.
+    hung = CrashReport.from_dict({**data, "hang_category": "deadlock", "annotations": []})
+    assert enrich_report(hung, [SystemModuleEnricher()]) == []
+
+
+def test_registered_and_entry_point_enrichers_run_by_default(tmp_path, monkeypatch):
+    class Entry:
+        def __init__(self, name, found):
+            self.name, self.value, self.found = name, f"acme:{name}", found
+
+        def load(self):
+            if isinstance(self.found, Exception):
+                raise self.found
+            return self.found
+
+    entries = [Entry("owners", OwnersEnricher), Entry("missing", ImportError("No module named 'acme'"))]
+    monkeypatch.setattr(enrich, "_registry", {})
+    monkeypatch.setattr(enrich, "_loaded", False)
+    monkeypatch.setattr(enrich, "entry_points", lambda group: entries if group == "dbgcopilot.enrichers" else [])
+    assert [e.name for e in registered_enrichers()] == ["system_modules", "owners"]
+
+    class KnownBugs:
+        name = "known_bugs"
+
+        def enrich(self, report):
+            if report.first_user_frame() and report.first_user_frame().function == "charge_card":
+                report.annotate(self.name, "known bug BILL-1234, fixed in 2.4.1")
+
+    register_enricher(KnownBugs())
+    enrich.unregister_enricher("system_modules")
+    monkeypatch.setattr(api, "_analyze_core", lambda request: _report())
+    (tmp_path / "core").write_text("")
+    report = analyze(AnalyzeRequest(binary="", core=str(tmp_path / "core"), no_llm=True))
+    assert [a.text for a in report.annotations] == [
+        "libbilling.so is owned by the payments team (#payments-oncall)",
+        "known bug BILL-1234, fixed in 2.4.1",
+    ]
@@ -0,0 +1,101 @@
+from dbgcopilot.analysis import (
+    AccessKind,
+    CrashContext,
+    CrashReport,
+    ExceptionKind,
+    FaultKind,
+    Frame,
+    StopInfo,
+    normalize_stop,
+    si_code_name,
+)
+from dbgcopilot.analysis.parsers import instruction_access, parse_cdb_exception
+from dbgcopilot.backends import gdb_batch
+from dbgcopilot.backends.gdb_batch import GdbBatchBackend
+
+# examples/crash/rust under gdb: the stop, then the $_siginfo fields and x/i $pc.
+GDB_OUTPUT = """\
+Program received signal SIGSEGV, Segmentation fault.
+rust_crash::crash () at src/main.rs:9
+9	        *ptr = 42;
+@@dbgcopilot:siginfo@@
+$1 = (void *) 0x0
+@@dbgcopilot:sicode@@
+si_code=1
+@@dbgcopilot:insn@@
+=> 0x555555559157 <_ZN10rust_crash5crash17h0e8f3c1a2b4d5e6fE+7>:\tmovl   $0x2a,(%rax)
+@@dbgcopilot:bt@@
+#0  rust_crash::crash () at src/main.rs:9
+#1  0x000055555555911b in rust_crash::main () at src/main.rs:2
+"""
+
+# The same crash built for Windows, from cdb's `.exr -1`.
+CDB_EXR = """\
+ExceptionAddress: 00007ff61a2b101a (rust_crash!rust_crash::crash+0x000000000000000a)
+   ExceptionCode: c0000005 (Access violation)
+  ExceptionFlags: 00000000
+NumberParameters: 2
+   Parameter[0]: 0000000000000001
+   Parameter[1]: 0000000000000000
+Attempt to write to address 0000000000000000
+"""
+
+
+def _report(stop):
+    frames = [Frame(index=0, pc=0x555555559157, function="rust_crash::crash", file="src/main.rs", line=9)]
+    return CrashReport.from_context(CrashContext(stop=stop, frames=frames))
+
+
+def test_rust_crash_normalizes_to_the_same_exception_on_linux_and_windows(monkeypatch):
+    monkeypatch.setattr(gdb_batch, "run_batch", lambda argv, timeout: (GDB_OUTPUT, False))
+    backend = GdbBatchBackend(timeout=5)
+    backend.load("rust_crash")
+    linux = _report(backend.run_to_fault())
+    windows = _report(parse_cdb_exception(CDB_EXR))
+
+    for report in (linux, windows):
+        assert report.exception_kind is ExceptionKind.MEMORY_ACCESS
+        assert (report.exception.access, report.exception.null) == (AccessKind.WRITE, True)
+        assert report.fault_kind is FaultKind.NULL_DEREF
+    assert (linux.signal, linux.si_code, linux.exception.si_code_name) == ("SIGSEGV", 1, "SEGV_MAPERR")
+    assert (windows.exception_code, windows.si_code) == (0xC0000005, None)
+    assert linux.exception.describe() == "memory access: write at 0x0 (null page, SEGV_MAPERR)"
+    assert windows.exception.describe() == "memory access: write at 0x0 (null page)"
+    assert "Exception: memory access: write at 0x0 (null page, SEGV_MAPERR)" in linux.render()
+
+    data = linux.to_dict()
+    assert (data["exception_kind"], data["si_code"], data["access_kind"]) == ("memory_access", 1, "write")
+    assert CrashReport.from_dict(data).exception == linux.exception
+
+
+def test_signals_and_exception_codes_share_one_kind():
+    pairs = [
+        ("SIGSEGV", 0xC0000005, ExceptionKind.MEMORY_ACCESS),
+        ("SIGBUS", 0x80000002, ExceptionKind.MEMORY_ACCESS),
+        ("SIGFPE", 0xC0000094, ExceptionKind.ARITHMETIC),
+        ("SIGILL", 0xC000001D, ExceptionKind.ILLEGAL_INSTRUCTION),
+        ("SIGABRT", 0xC0000409, ExceptionKind.ABORT),
+        ("SIGTRAP", 0x80000003, ExceptionKind.BREAKPOINT),
+    ]
+    for signal, code, kind in pairs:
+        assert normalize_stop(StopInfo(signal=signal)).kind is kind
+        # cdb reports every NTSTATUS error as SIGSEGV; the code decides.
+        assert normalize_stop(StopInfo(signal="SIGSEGV", exception_code=code)).kind is kind
+    assert normalize_stop(StopInfo(signal="SIGSEGV", exception_code=0xC0000135)).kind is ExceptionKind.UNKNOWN
+    assert normalize_stop(StopInfo(description="exited with code 0", exit_code=0)).kind is ExceptionKind.UNKNOWN
+    assert normalize_stop(StopInfo(signal="SIGABRT", si_code=-6)).describe() == "abort (SI_TKILL)"
+    # A general protection fault: the kernel gives no address, and si_addr reads 0.
+    gp = normalize_stop(StopInfo(signal="SIGSEGV", fault_address=0, si_code=0x80))
+    assert not gp.null and gp.describe() == "memory access (SI_KERNEL)"
+    assert (si_code_name("SIGSEGV", 2), si_code_name("SIGFPE", 1), si_code_name("SIGSEGV", 0x80)) == (
+        "SEGV_ACCERR", "FPE_INTDIV", "SI_KERNEL"
+    )
+    assert si_code_name("SIGUSR1", 1) is None
+
+    assert instruction_access("mov    (%rax),%edx") is AccessKind.READ
+    assert instruction_access("movq   $0x0,0x8(%rbx,%rcx,8)") is AccessKind.WRITE
+    assert instruction_access("cmpl   $0x0,(%rdi)") is AccessKind.READ
+    assert instruction_access("mov    DWORD PTR [rax],0x2a") is AccessKind.WRITE
+    assert instruction_access("str    w8, [x0]") is AccessKind.WRITE
+    assert instruction_access("ldr    x1, [x0, #8]") is AccessKind.READ
+    assert instruction_access("ud2") is AccessKind.UNKNOWN
@@ -0,0 +1,128 @@
+import os
+import sys
+
+from dbgcopilot.analysis import AnalyzeRequest, CrashContext, CrashReport, ExplanationCache, Frame, Module, StopInfo, crash_signature
+from dbgcopilot.analysis import api
+from dbgcopilot.analysis.cache import CACHE_DIR_ENV_VAR, default_cache_dir
+from dbgcopilot.analysis.prompt import PROMPT_VERSION
+from dbgcopilot.llm import providers
+
+
+def _crash(base, fault_address, top="crash_demo::crash::h0123456789abcdef"):
+    # The same null write, loaded at a different base with a different heap pointer.
+    return CrashContext(
+        stop=StopInfo(signal="SIGSEGV", fault_address=fault_address),
+        frames=[
+            Frame(0, pc=base + 0x1234, function=top, file="/src/crash_demo/src/main.rs", line=12,
+                  args=f"p=0x{fault_address:x}"),
+            Frame(1, pc=base + 0x1300, function="crash_demo::main", file="/src/crash_demo/src/main.rs", line=20),
+            Frame(2, pc=base + 0x2000),
+        ],
+        modules=[Module("crash_demo", base, 0x10000)],
+    )
+
+
+def test_signature_ignores_addresses():
+    first = crash_signature(_crash(0x555555554000, 0x10))
+    assert first == crash_signature(_crash(0x563412340000, 0x18))
+    # The Rust symbol hash, pcs, and argument values are left out; images without symbols keep their offset.
+    assert first.splitlines() == [
+        "null_deref",
+        "crash_demo!crash_demo::crash main.rs:12",
+        "crash_demo!crash_demo::main main.rs:20",
+        "crash_demo!+0x2000",
+    ]
+    assert first != crash_signature(_crash(0x555555554000, 0x10, top="crash_demo::other"))
+
+
+def _c_crash(base, libc_base, pid, site="boom"):
+    # examples/crash/c: boom() writes through NULL, called from main.
+    return CrashContext(
+        stop=StopInfo(signal="SIGSEGV", fault_address=0, description=f"process {pid} segfaulted"),
+        frames=[
+            Frame(0, pc=base + 0x1151, function=site, offset=0x11, module="crash"),
+            Frame(1, pc=base + 0x1190, function="main", offset=0x3A, module="crash"),
+            Frame(2, pc=libc_base + 0x29D90, function="__libc_start_call_main", offset=0x80),
+        ],
+        modules=[Module("crash", base, 0x5000), Module("libc.so.6", libc_base, 0x200000)],
+    )
+
+
+def test_report_signature_is_stable_across_runs():
+    first = CrashReport.from_context(_c_crash(0x555555554000, 0x7FFFF7C00000, 4100))
+    second = CrashReport.from_context(_c_crash(0x5612AB000000, 0x7F3B12000000, 5233))
+    assert first.signature() == "crash!boom+0x11 -> crash!main -> libc.so.6!__libc_start_call_main"
+    assert second.signature() == first.signature() and second.bucket == first.bucket
+    other = CrashReport.from_context(_c_crash(0x555555554000, 0x7FFFF7C00000, 4100, site="other"))
+    assert other.signature() != first.signature() and other.bucket != first.bucket
+    assert first.signature(depth=1) == "crash!boom+0x11"
+
+    assert "0x5555" not in first.signature() and "4100" not in first.signature()
+    assert f"Signature: {first.signature()} [{first.bucket}]" in first.render()
+    data = first.to_dict()
+    assert (data["signature"], data["bucket"]) == (first.signature(), first.bucket)
+    assert len(first.bucket) == 16
+    assert CrashReport.from_context(CrashContext(stop=StopInfo(signal="SIGSEGV"))).signature() is None
+
+
+def test_cached_explanation_is_reused(tmp_path, monkeypatch):
+    calls = []
+
+    def fake_client(name, config):
+        return lambda prompt: calls.append(name) or f"Answer {len(calls)}."
+
+    monkeypatch.setattr(providers, "create_client", fake_client)
+    cache = ExplanationCache(str(tmp_path / "cache"))
+
+    def explain(ctx, provider="mock-local", cache=cache):
+        report = api._report_from_context(ctx, "core")
+        api.explain(report, AnalyzeRequest(binary="crash_demo", provider=provider, cache=cache))
+        return report
+
+    assert explain(_crash(0x555555554000, 0x10)).explanation == "Answer 1."
+    hit = explain(_crash(0x563412340000, 0x18))
+    assert (hit.explanation, hit.explanation_cached, calls) == ("Answer 1.", True, ["mock-local"])
+    assert len(os.listdir(tmp_path / "cache")) == 1
+
+    # Another provider, or no cache (--no-cache), asks again.
+    assert explain(_crash(0x555555554000, 0x10), provider="mock-other").explanation == "Answer 2."
+    fresh = explain(_crash(0x555555554000, 0x10), cache=None)
+    assert (fresh.explanation, fresh.explanation_cached) == ("Answer 3.", False)
+
+
+def test_refresh_and_a_new_prompt_version_ask_again(tmp_path, monkeypatch):
+    calls = []
+
+    def fake_client(name, config):
+        return lambda prompt: calls.append(name) or f"Answer {len(calls)}."
+
+    monkeypatch.setattr(providers, "create_client", fake_client)
+    directory = str(tmp_path / "cache")
+
+    def explain(cache):
+        report = api._report_from_context(_crash(0x555555554000, 0x10), "core")
+        api.explain(report, AnalyzeRequest(binary="crash_demo", provider="mock-local", cache=cache))
+        return report.explanation, report.explanation_cached
+
+    assert explain(ExplanationCache(directory)) == ("Answer 1.", False)
+    # --refresh-cache asks again and replaces the entry.
+    assert explain(ExplanationCache(directory, refresh=True)) == ("Answer 2.", False)
+    assert explain(ExplanationCache(directory)) == ("Answer 2.", True)
+    # Answers to an older prompt are not reused.
+    assert explain(ExplanationCache(directory, prompt_version=PROMPT_VERSION + 1)) == ("Answer 3.", False)
+    assert len(calls) == 3 and len(os.listdir(directory)) == 1
+
+
+def test_default_directory_follows_the_platform(monkeypatch):
+    monkeypatch.delenv(CACHE_DIR_ENV_VAR, raising=False)
+    monkeypatch.setenv("HOME", "/home/dev")
+    monkeypatch.setenv("XDG_CACHE_HOME", "/var/cache/dev")
+    monkeypatch.setattr(sys, "platform", "linux")
+    assert default_cache_dir() == "/var/cache/dev/dbgcopilot/explanations"
+    monkeypatch.setattr(sys, "platform", "darwin")
+    assert default_cache_dir() == "/home/dev/Library/Caches/dbgcopilot/explanations"
+    monkeypatch.setattr(sys, "platform", "win32")
+    monkeypatch.setenv("LOCALAPPDATA", "/appdata")
+    assert default_cache_dir() == os.path.join("/appdata", "dbgcopilot", "explanations")
+    monkeypatch.setenv(CACHE_DIR_ENV_VAR, "/tmp/explanations")
+    assert default_cache_dir() == "/tmp/explanations"
@@ -0,0 +1,96 @@
+import logging
+
+from dbgcopilot.analysis import (
+    CrashContext,
+    CrashReport,
+    FaultKind,
+    Frame,
+    Register,
+    StopInfo,
+    classify_fault,
+    fault_hint,
+)
+from dbgcopilot.core import orchestrator
+from dbgcopilot.core.orchestrator import _track_fault
+from dbgcopilot.core.state import SessionState
+
+
+def _ctx(signal, addr=None, functions=(), sp=None, description=""):
+    frames = [Frame(index=i, function=name) for i, name in enumerate(functions)]
+    regs = [Register("rsp", sp)] if sp is not None else []
+    return CrashContext(stop=StopInfo(signal=signal, description=description, fault_address=addr), frames=frames, registers=regs)
+
+
+def test_null_deref_and_wild_access():
+    assert classify_fault(_ctx("SIGSEGV", addr=0x0, functions=["rust_crash::crash"])).kind is FaultKind.NULL_DEREF
+    assert classify_fault(_ctx("SIGSEGV", addr=0x18)).kind is FaultKind.NULL_DEREF
+    assert classify_fault(_ctx("SIGSEGV", addr=0xDEADBEEF000)).kind is FaultKind.INVALID_ACCESS
+    hint = fault_hint(_ctx("SIGSEGV", addr=0x0, functions=["rust_crash::crash"]))
+    assert hint and hint.startswith("null-pointer dereference") and "0x0" in hint
+    # A general protection fault: the kernel gives no address, and si_addr reads 0.
+    gp = CrashContext(stop=StopInfo(signal="SIGSEGV", fault_address=0, si_code=0x80))
+    found = classify_fault(gp)
+    assert found.kind is FaultKind.INVALID_ACCESS and found.signals[-1].startswith("SI_KERNEL")
+    assert "0x0" not in fault_hint(gp)
+
+
+def test_stack_overflow_heap_abort_and_arithmetic():
+    sp = 0x7FFFF0000010
+    assert classify_fault(_ctx("SIGSEGV", addr=sp - 0x20, sp=sp)).kind is FaultKind.STACK_OVERFLOW
+    assert classify_fault(_ctx("SIGSEGV", functions=["descend"] * 80)).kind is FaultKind.STACK_OVERFLOW
+    heap = ["__pthread_kill_implementation", "raise", "abort", "__libc_message", "malloc_printerr", "_int_free"]
+    assert classify_fault(_ctx("SIGABRT", functions=heap)).kind is FaultKind.HEAP_CORRUPTION
+    assert classify_fault(_ctx("SIGABRT", functions=["raise", "abort", "std::process::abort"])).kind is FaultKind.ABORT
+    assert classify_fault(_ctx("SIGFPE")).kind is FaultKind.DIVIDE_BY_ZERO
+    assert classify_fault(_ctx("SIGILL")).kind is FaultKind.ILLEGAL_INSTRUCTION
+    assert classify_fault(_ctx(None)).kind is FaultKind.UNKNOWN
+
+
+def test_classification_carries_confidence_and_falls_back_to_unknown():
+    null = classify_fault(_ctx("SIGSEGV", addr=0x0))
+    near = classify_fault(_ctx("SIGSEGV", addr=0x800))
+    assert null.confidence > near.confidence >= 0.5
+    assert "SIGSEGV (signal 11)" in null.signals and "fault address 0x0 is the null pointer" in null.signals
+    # Evidence from two heuristics adds up, but never to certainty.
+    both = classify_fault(_ctx("SIGSEGV", addr=0x0, functions=["core::panicking::panic_null_pointer_dereference"]))
+    assert null.confidence < both.confidence < 1.0
+
+    # A SIGSEGV with no fault address could be anything, `kill -SEGV` included.
+    weak = classify_fault(_ctx("SIGSEGV"))
+    assert weak.kind is FaultKind.UNKNOWN and 0 < weak.confidence < 0.5
+    assert any(s.startswith("too weak to call it invalid memory access") for s in weak.signals)
+    hint = fault_hint(_ctx("SIGSEGV", addr=0xDEADBEEF000))
+    assert "; confidence 0.70: SIGSEGV (signal 11); fault address 0xdeadbeef000 is outside the null page" in hint
+
+    report = CrashReport.from_context(_ctx("SIGSEGV", addr=0x0))
+    assert report.to_dict()["fault_confidence"] == null.confidence
+    assert CrashReport.from_dict(report.to_dict()).fault_evidence == null.signals
+    assert CrashReport.from_context(_ctx("SIGSEGV"), fault_kind=FaultKind.HANG).fault_confidence is None
+
+
+def test_orchestrator_tracks_fault_hint_from_output():
+    state = SessionState(session_id="t")
+    _track_fault(state, "Program received signal SIGSEGV, Segmentation fault.\n$1 = (void *) 0x0\n")
+    assert state.fault_hint and state.fault_hint.startswith("null-pointer dereference")
+    _track_fault(state, "#0  rust_crash::crash () at src/main.rs:9\n#1  0x000055555555911b in rust_crash::main () at src/main.rs:2\n")
+    assert "rust_crash::crash" in state.fault_hint
+
+
+def test_orchestrator_logs_output_it_cannot_track(monkeypatch):
+    records = []
+    handler = logging.Handler(logging.DEBUG)
+    handler.emit = records.append
+    orchestrator.log.addHandler(handler)
+    monkeypatch.setattr(orchestrator.log, "level", logging.DEBUG)
+
+    def broken(_ctx):
+        raise ValueError("odd register text")
+
+    monkeypatch.setattr(orchestrator, "fault_hint", broken)
+    state = SessionState(session_id="t")
+    try:
+        _track_fault(state, "Program received signal SIGSEGV, Segmentation fault.\n")
+    finally:
+        orchestrator.log.removeHandler(handler)
+    assert state.fault_hint is None
+    assert [r.exc_info[1].args for r in records] == [("odd register text",)]
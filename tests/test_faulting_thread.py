@@ -0,0 +1,87 @@
+import json
+
+from dbgcopilot.analysis import AnalyzeRequest, CrashReport, Frame, ThreadBacktrace, analyze, api
+from dbgcopilot.analysis.parsers import parse_cdb_thread_backtraces, parse_lldb_thread_backtraces
+from dbgcopilot.analysis.threads import find_faulting_thread
+from dbgcopilot.backends import gdb_batch
+from dbgcopilot.backends.gdb_batch import GdbBatchBackend
+
+# Three threads, two of them pool workers stopped at the same pc; worker-1 took the SIGSEGV.
+GDB_OUTPUT = """\
+[New Thread 0x7ffff7d8a640 (LWP 1235)]
+[New Thread 0x7ffff7589640 (LWP 1236)]
+
+Thread 3 "worker-1" received signal SIGSEGV, Segmentation fault.
+[Switching to Thread 0x7ffff7589640 (LWP 1236)]
+0x0000555555555210 in pool::Shared::take () at src/pool.rs:88
+@@dbgcopilot:siginfo@@
+$1 = (void *) 0x18
+@@dbgcopilot:bt@@
+#0  0x0000555555555210 in pool::Shared::take () at src/pool.rs:88
+#1  0x0000555555555300 in pool::Worker::run () at src/pool.rs:41
+@@dbgcopilot:threads@@
+
+Thread 3 (Thread 0x7ffff7589640 (LWP 1236) "worker-1"):
+#0  0x0000555555555210 in pool::Shared::take () at src/pool.rs:88
+#1  0x0000555555555300 in pool::Worker::run () at src/pool.rs:41
+
+Thread 2 (Thread 0x7ffff7d8a640 (LWP 1235) "worker-0"):
+#0  0x0000555555555210 in pool::Shared::take () at src/pool.rs:88
+#1  0x0000555555555300 in pool::Worker::run () at src/pool.rs:41
+
+Thread 1 (Thread 0x7ffff7d8b740 (LWP 1234) "pool"):
+#0  0x00007ffff7e4a5d0 in __futex_abstimed_wait_common () from /lib/x86_64-linux-gnu/libc.so.6
+#1  0x0000555555555400 in pool::main () at src/main.rs:12
+@@dbgcopilot:current@@
+  Id   Target Id                                    Frame
+  1    Thread 0x7ffff7d8b740 (LWP 1234) "pool"      0x00007ffff7e4a5d0 in __futex_abstimed_wait_common ()
+  2    Thread 0x7ffff7d8a640 (LWP 1235) "worker-0"  0x0000555555555210 in pool::Shared::take ()
+* 3    Thread 0x7ffff7589640 (LWP 1236) "worker-1"  0x0000555555555210 in pool::Shared::take ()
+"""
+
+
+def test_gdb_capture_names_the_thread_that_took_the_signal(tmp_path, monkeypatch):
+    monkeypatch.setattr(gdb_batch, "run_batch", lambda argv, timeout: (GDB_OUTPUT, False))
+    monkeypatch.setattr(api, "_detect_backend", lambda request: GdbBatchBackend("gdb", timeout=5))
+    (tmp_path / "pool").write_text("")
+    # Not a core the built-in reader knows, so the debugger opens it.
+    (tmp_path / "core").write_bytes(b"not an ELF core")
+    report = analyze(AnalyzeRequest(binary=str(tmp_path / "pool"), core=str(tmp_path / "core"), no_llm=True))
+
+    # GDB names a threaded program's signal "Thread N ... received signal".
+    assert report.stop.signal == "SIGSEGV" and report.stop.fault_address == 0x18
+    assert report.thread_id == 1236
+    assert 'Faulting thread: 1236 (thread 3 "worker-1", of 3)' in report.render()
+    assert 'Faulting thread: 1236 (thread 3 "worker-1", of 3)' in report.prompt
+    data = json.loads(report.to_json())
+    assert [(t["tid"], t["faulting"]) for t in data["threads"]] == [(1236, True), (1235, False), (1234, False)]
+    assert CrashReport.from_dict(data).faulting_thread.tid == 1236
+
+
+def test_engines_mark_the_faulting_thread_and_a_shared_pc_is_no_guess():
+    lldb = parse_lldb_thread_backtraces(
+        "  thread #1, name = 'pool'\n"
+        "    frame #0: 0x00007ffff7e4a5d0 libc.so.6`__futex_abstimed_wait_common\n"
+        "* thread #3, name = 'worker-1', stop reason = signal SIGSEGV: invalid address (fault address: 0x18)\n"
+        "  * frame #0: 0x0000555555555210 pool`pool::Shared::take at pool.rs:88\n"
+    )
+    assert [(t.thread_id, t.faulting) for t in lldb] == [(1, False), (3, True)]
+
+    # CDB marks the thread that raised the exception "#" when another one is current (".").
+    cdb = parse_cdb_thread_backtraces(
+        ".  0  Id: 1a2c.3b4c Suspend: 1 Teb: 000000a0`00123000 Unfrozen\n"
+        " # Child-SP          RetAddr               Call Site\n"
+        "00 000000a0`0014f8d8 00007ff8`2c1e1234     ntdll!NtWaitForSingleObject+0x14\n"
+        "#  1  Id: 1a2c.2d10 Suspend: 1 Teb: 000000a0`00125000 Unfrozen \"worker_one\"\n"
+        " # Child-SP          RetAddr               Call Site\n"
+        "00 000000a0`0024f6a0 00007ff6`1a2b1100     app!take+0x1c\n"
+    )
+    assert [(t.tid, t.faulting) for t in cdb] == [(0x3B4C, False), (0x2D10, True)]
+    assert parse_cdb_thread_backtraces(".  0  Id: 1a2c.3b4c Suspend: 1 Teb: 000000a0`00123000 Unfrozen\n")[0].faulting
+
+    # Without a mark or a thread id, two workers at the faulting pc leave the thread unknown.
+    take = Frame(0, pc=0x555555555210, function="pool::Shared::take")
+    workers = [ThreadBacktrace(2, tid=1235, frames=[take]), ThreadBacktrace(3, tid=1236, frames=[take])]
+    assert find_faulting_thread(workers, None, [take]) is None
+    assert find_faulting_thread(workers, 1236, [take]) is workers[1]
+    assert find_faulting_thread(workers[:1], None, [take]) is workers[0]
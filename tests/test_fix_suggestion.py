@@ -0,0 +1,81 @@
+import json
+
+import pytest
+
+from dbgcopilot import cli
+from dbgcopilot.analysis import FixSuggestion, api, render_report, suggest_fix
+from dbgcopilot.analysis.fix import FIX_INSTRUCTIONS, parse_fix_suggestion
+from dbgcopilot.llm import base
+from dbgcopilot.llm.errors import LlmResponseError
+
+EXPLANATION = "crash() writes 42 through a null pointer."
+NULL_CHECK = {
+    "summary": "Check that ptr is not null before writing through it.",
+    "rationale": "crash() stores 42 through std::ptr::null_mut(), which faults at address 0.",
+    "patch_hint": "if !ptr.is_null() {\n    unsafe { *ptr = 42; }\n}",
+}
+
+
+def test_suggest_fix_asks_for_a_structured_change_after_the_explanation(scripted_provider, null_deref_report):
+    llm = scripted_provider([f"Here you go:\n```json\n{json.dumps(NULL_CHECK)}\n```"])
+    report = null_deref_report(EXPLANATION)
+    suggestion = suggest_fix(report, report.explanation, llm)
+    assert suggestion == FixSuggestion(**NULL_CHECK)
+    assert "null" in suggestion.summary and "is_null()" in suggestion.patch_hint
+
+    prompt = llm.prompts[0]
+    assert prompt.system == FIX_INSTRUCTIONS and '"patch_hint"' in prompt.system
+    assert "rust_crash::crash" in prompt.text
+    assert prompt.text.endswith("Your analysis:\ncrash() writes 42 through a null pointer.")
+
+    assert parse_fix_suggestion('{"summary": "Add a null check."}') == FixSuggestion("Add a null check.")
+    with pytest.raises(LlmResponseError, match="not a JSON object"):
+        parse_fix_suggestion("Just check the pointer.")
+    with pytest.raises(LlmResponseError, match="with a summary"):
+        parse_fix_suggestion('{"rationale": "no summary"}')
+
+
+def test_fix_suggestion_is_recorded_on_the_report_and_rendered(monkeypatch, scripted_provider, null_deref_report):
+    llm = scripted_provider([json.dumps(NULL_CHECK)])
+    monkeypatch.setattr(base, "resolve_provider", lambda provider, config=None: llm)
+    report = null_deref_report(EXPLANATION)
+    api.add_fix_suggestion(report, api.AnalyzeRequest(binary="rust_crash", provider="scripted", suggest_fix=True))
+    assert report.fix_suggestion == FixSuggestion(**NULL_CHECK) and report.fix_suggestion_error is None
+
+    data = json.loads(report.to_json())
+    assert data["fix_suggestion"] == NULL_CHECK and data["fix_suggestion_error"] is None
+    text = render_report(data)
+    assert "Suggested fix:\nCheck that ptr is not null" in text
+    assert "Patch hint:\nif !ptr.is_null() {" in text
+    assert render_report(data) == report.render()
+    assert "Suggested fix:" not in report.render(with_explanation=False)
+
+    # Failures are recorded, not raised; without an explanation there is nothing to ask about.
+    report = null_deref_report(EXPLANATION)
+    monkeypatch.setattr(base, "resolve_provider", lambda provider, config=None: scripted_provider(["no idea"]))
+    api.add_fix_suggestion(report, api.AnalyzeRequest(binary="rust_crash", provider="scripted"))
+    assert report.fix_suggestion is None and "not a JSON object" in report.fix_suggestion_error
+    assert "Fix suggestion failed:" in report.render()
+    report = null_deref_report(EXPLANATION)
+    report.explanation = None
+    api.add_fix_suggestion(report, api.AnalyzeRequest(binary="rust_crash", provider="scripted"))
+    assert report.fix_suggestion_error == "no complete explanation to base a fix on"
+    assert json.loads(null_deref_report(EXPLANATION).to_json())["fix_suggestion"] is None
+
+
+def test_suggest_fix_flag_makes_a_second_call_after_the_explanation(
+    monkeypatch, capsys, scripted_provider, null_deref_report
+):
+    report = null_deref_report(EXPLANATION)
+    report.explanation = None
+    llm = scripted_provider(["crash() writes through a null pointer.", json.dumps(NULL_CHECK)])
+    monkeypatch.setattr(api, "analyze", lambda request: report)
+    monkeypatch.setattr(base, "resolve_provider", lambda provider, config=None: llm)
+    assert cli.analyze_main(["--no-cache", "--provider", "scripted", "--suggest-fix", "rust_crash"]) == 0
+    out = capsys.readouterr().out
+    assert "Explanation:\ncrash() writes through a null pointer." in out
+    assert "Suggested fix:\nCheck that ptr is not null before writing through it." in out
+    assert len(llm.prompts) == 2 and llm.prompts[1].system == FIX_INSTRUCTIONS
+
+    with pytest.raises(SystemExit):
+        cli.analyze_main(["--no-llm", "--suggest-fix", "rust_crash"])
@@ -0,0 +1,87 @@
+from dbgcopilot.analysis import CrashContext, CrashReport, FaultKind, Frame, FrameRules, StopInfo, render_report
+from dbgcopilot.analysis.prompt import FrameBudget, PromptBuilder
+from dbgcopilot.utils.config import load_settings
+
+STD = "/rustc/90b35a6239c3d8bdabc530a6a0816f7ff89a0aaf/library/std/src"
+CORE = "/rustc/90b35a6239c3d8bdabc530a6a0816f7ff89a0aaf/library/core/src"
+
+
+def _frame(index, function, file=None, line=None, module=None):
+    return Frame(index=index, pc=0x555555560000 + index * 0x10, function=function, file=file, line=line,
+                 module=module)
+
+
+# examples/panic/rust with PANIC_MODE=abort: the panic hook aborts from under `lookup`.
+PANIC_FRAMES = [
+    _frame(0, "__pthread_kill_implementation", "./nptl/pthread_kill.c", 44, module="libc.so.6"),
+    _frame(1, "__GI_raise", "../sysdeps/posix/raise.c", 26, module="libc.so.6"),
+    _frame(2, "__GI_abort", "./stdlib/abort.c", 79, module="libc.so.6"),
+    _frame(3, "std::sys::pal::unix::abort_internal", f"{STD}/sys/pal/unix/mod.rs", 372),
+    _frame(4, "std::process::abort", f"{STD}/process.rs", 2371),
+    _frame(5, "rust_panic::install_panic_mode::{{closure}}", "src/main.rs", 13),
+    _frame(6, "<alloc::boxed::Box<F,A> as core::ops::function::Fn<Args>>::call", "alloc/src/boxed.rs", 2245),
+    _frame(7, "std::panicking::rust_panic_with_hook", f"{STD}/panicking.rs", 805),
+    _frame(8, "rust_begin_unwind", f"{STD}/panicking.rs", 665),
+    _frame(9, "core::panicking::panic_fmt", f"{CORE}/panicking.rs", 74),
+    _frame(10, "core::option::unwrap_failed", f"{CORE}/option.rs", 2015),
+    _frame(11, "rust_panic::lookup", "src/main.rs", 32),
+    _frame(12, "rust_panic::connect", "src/main.rs", 37),
+    _frame(13, "rust_panic::start_service", "src/main.rs", 44),
+    _frame(14, "rust_panic::main", "src/main.rs", 55),
+    _frame(15, "core::ops::function::FnOnce::call_once", f"{CORE}/ops/function.rs", 250),
+    _frame(16, "__libc_start_call_main", "../sysdeps/nptl/libc_start_call_main.h", 58, module="libc.so.6"),
+    _frame(17, "_start"),
+]
+
+
+def _context(frames):
+    return CrashContext(stop=StopInfo(signal="SIGABRT", description="Aborted"), frames=list(frames))
+
+
+def test_first_user_frame_skips_libc_std_and_the_panic_runtime():
+    rules = FrameRules()
+    assert [f.index for f in PANIC_FRAMES if rules.is_user(f)] == [5, 11, 12, 13, 14]
+    # The panic hook is the program's own code, but it is not where the bug is: tune the rules.
+    rules = FrameRules.from_config({"runtime_frames": "rust_panic::install_panic_mode::*"})
+    report = CrashReport.from_context(_context(PANIC_FRAMES), max_frames=len(PANIC_FRAMES), frame_rules=rules)
+    assert report.fault_kind is FaultKind.RUST_PANIC
+    assert report.first_user_frame().function == "rust_panic::lookup"
+
+    text = report.render()
+    lead = "First user frame: #11 0x5555555600b0 rust_panic::lookup at src/main.rs:32 (below 11 runtime frames)"
+    assert lead in text and text.index(lead) < text.index("Backtrace:")
+    # Runtime frames are still listed.
+    assert "#9 0x555555560090 core::panicking::panic_fmt" in text
+    data = report.to_dict()
+    assert data["user_frame_index"] == 11 and render_report(data).count("First user frame:") == 1
+
+    # No line when the crash site is already user code; None when nothing is.
+    user_top = CrashReport.from_context(_context(PANIC_FRAMES[11:]))
+    assert user_top.first_user_frame().index == 11 and "First user frame:" not in user_top.render()
+    assert CrashReport.from_context(_context(PANIC_FRAMES[:4])).first_user_frame() is None
+
+    # User patterns win over the defaults, matching modules as well as symbols.
+    vendored = [_frame(0, "std::compat::shim", module="libvendored.so"), _frame(1, "app::run")]
+    assert FrameRules(user=["libvendored*"]).first_user_frame(vendored).index == 0
+    assert FrameRules().first_user_frame(vendored).index == 1
+    assert not FrameRules().is_user(Frame(index=0, pc=0x1234))
+
+
+def test_prompt_keeps_the_first_user_frame_and_rules_come_from_the_config(tmp_path):
+    report = CrashReport.from_context(_context(PANIC_FRAMES), max_frames=len(PANIC_FRAMES))
+    assert report.first_user_frame().index == 5
+    # Tight enough that every frame but the kept ones is dropped: the top two, the user frame, and _start.
+    entries, _ = FrameBudget(max_tokens=0, keep_top=2, keep_bottom=1).fit(
+        report.frames, user_frame=report.user_frame_index
+    )
+    assert [e.frame.index for e in entries] == [0, 1, 5, 17]
+    prompt = PromptBuilder().build(report).text
+    assert "First user frame: #5 " in prompt and prompt.index("First user frame:") < prompt.index("Backtrace:")
+
+    path = tmp_path / "dbgcopilot.toml"
+    path.write_text('[frames]\nuser = ["mycrate::*"]\nruntime = ["tokio::*", "futures_util::*"]\n')
+    config = load_settings(path=str(path), environ={}).llm_config()
+    assert (config["user_frames"], config["runtime_frames"]) == (["mycrate::*"], ["tokio::*", "futures_util::*"])
+    rules = FrameRules.from_config(config)
+    assert rules.user == ["mycrate::*"] and rules.runtime[-2:] == ["tokio::*", "futures_util::*"]
+    assert not rules.is_user(_frame(0, "tokio::runtime::park::Inner::park"))
@@ -0,0 +1,161 @@
+import shutil
+import subprocess
+
+import pytest
+
+from dbgcopilot.analysis import AnalysisError, AnalyzeRequest, FaultKind, HangCategory, ThreadState, analyze, api
+from dbgcopilot.analysis.goroutines import classify_goroutines, parse_goroutine_dump
+from dbgcopilot.analysis.hang_kind import summarize_thread
+
+# examples/hang/go under GOTRACEBACK=system, after SIGQUIT.
+SIGQUIT_DUMP = """\
+Go hang demo: sending a job before the worker is started...
+SIGQUIT: quit
+PC=0x46e441 m=0 sigcode=0
+
+goroutine 0 gp=0x5a4e40 m=0 mp=0x5a5700 [idle]:
+runtime.futex(0x5a5840, 0x80, 0x0, 0x0, 0x0, 0x0)
+	/usr/lib/go/src/runtime/sys_linux_amd64.s:557 +0x21 fp=0x7ffd5b1e8e68 sp=0x7ffd5b1e8e60 pc=0x46e441
+runtime.futexsleep(0x7ffd5b1e8ee0?, 0x437a13?, 0x0?)
+	/usr/lib/go/src/runtime/lock_futex.go:69 +0x30 fp=0x7ffd5b1e8eb8 sp=0x7ffd5b1e8e68 pc=0x40d1d0
+
+goroutine 1 gp=0xc000002380 m=nil [chan send]:
+runtime.gopark(0x0?, 0x0?, 0x0?, 0x0?, 0x0?)
+	/usr/lib/go/src/runtime/proc.go:402 +0xce fp=0xc000070ec8 sp=0xc000070ea8 pc=0x43d0ae
+runtime.chansend(0xc00001e0c0, 0xc000070f48, 0x1, 0x1?)
+	/usr/lib/go/src/runtime/chan.go:259 +0x38d fp=0xc000070f38 sp=0xc000070ec8 pc=0x4069cd
+runtime.chansend1(0x0?, 0x0?)
+	/usr/lib/go/src/runtime/chan.go:145 +0x17 fp=0xc000070f68 sp=0xc000070f38 pc=0x406617
+main.main()
+	/src/examples/hang/go/hang.go:31 +0xb9 fp=0xc000070f50 sp=0xc000070ef0 pc=0x48f9ca
+runtime.main()
+	/usr/lib/go/src/runtime/proc.go:271 +0x29d fp=0xc000070fe0 sp=0xc000070f80 pc=0x43cc7d
+
+goroutine 2 gp=0xc000002e00 m=nil [force gc (idle)]:
+runtime.gopark(0x0?, 0x0?, 0x0?, 0x0?, 0x0?)
+	/usr/lib/go/src/runtime/proc.go:402 +0xce fp=0xc000060fa8 sp=0xc000060f88 pc=0x43d0ae
+created by runtime.init.6 in goroutine 1
+	/usr/lib/go/src/runtime/proc.go:314 +0x1a
+
+goroutine 5 gp=0xc000007500 m=nil [sleep]:
+runtime.gopark(0x1b6a5e2a2b6?, 0x0?, 0x0?, 0x0?, 0x0?)
+	/usr/lib/go/src/runtime/proc.go:402 +0xce fp=0xc000062f40 sp=0xc000062f20 pc=0x43d0ae
+time.Sleep(0x3b9aca00)
+	/usr/lib/go/src/runtime/time.go:195 +0x115 fp=0xc000062f80 sp=0xc000062f40 pc=0x46a795
+main.heartbeat()
+	/src/examples/hang/go/hang.go:21 +0x1a fp=0xc000062fa0 sp=0xc000062f80 pc=0x48f8da
+created by main.main in goroutine 1
+	/src/examples/hang/go/hang.go:29 +0x7d
+
+goroutine 6 gp=0xc0000076c0 m=nil [chan receive, 2 minutes]:
+runtime.gopark(0x0?, 0x0?, 0x0?, 0x0?, 0x0?)
+	/usr/lib/go/src/runtime/proc.go:402 +0xce fp=0xc000063ee0 sp=0xc000063ec0 pc=0x43d0ae
+runtime.chanrecv(0xc00001e120, 0x0, 0x1)
+	/usr/lib/go/src/runtime/chan.go:583 +0x3bf fp=0xc000063f58 sp=0xc000063ee0 pc=0x4075df
+runtime.chanrecv1(0x0?, 0x0?)
+	/usr/lib/go/src/runtime/chan.go:442 +0x12 fp=0xc000063f80 sp=0xc000063f58 pc=0x4071f2
+main.worker(0xc00001e0c0, 0xc00001e120)
+	/src/examples/hang/go/hang.go:10 +0x2c fp=0xc000063fc0 sp=0xc000063f80 pc=0x48f76c
+created by main.main in goroutine 1
+	/src/examples/hang/go/hang.go:30 +0x9f
+
+rax    0xca
+rbx    0x0
+"""
+
+# The same hang with the default GOTRACEBACK: runtime frames and columns left out.
+DEFAULT_DUMP = """\
+goroutine 1 [chan send]:
+main.main()
+	/src/examples/hang/go/hang.go:31 +0xb9
+
+goroutine 7 [semacquire]:
+sync.runtime_SemacquireMutex(0xc000012018?, 0x0?, 0x1?)
+	/usr/lib/go/src/runtime/sema.go:77 +0x25
+sync.(*Mutex).lockSlow(0x5a4f60)
+	/usr/lib/go/src/sync/mutex.go:171 +0x15d
+main.(*Cache).get(...)
+	/src/cache.go:12
+created by main.main
+	/src/main.go:20 +0x45
+
+goroutine 8 [IO wait, locked to thread]:
+internal/poll.runtime_pollWait(0x7f6a1c3e2e28, 0x72)
+	/usr/lib/go/src/runtime/netpoll.go:345 +0x85
+"""
+
+
+def test_goroutine_dump_is_parsed_with_parked_goroutines():
+    goroutines = parse_goroutine_dump(SIGQUIT_DUMP)
+    assert [g.goroutine_id for g in goroutines] == [0, 1, 2, 5, 6]
+    main, worker = goroutines[1], goroutines[4]
+    assert (main.wait_reason, main.created_by, main.parked) == ("chan send", None, True)
+    assert [f.function for f in main.frames] == [
+        "runtime.gopark", "runtime.chansend", "runtime.chansend1", "main.main", "runtime.main"
+    ]
+    site = main.frames[3]
+    assert (site.file, site.line, site.offset, site.pc) == ("/src/examples/hang/go/hang.go", 31, 0xB9, 0x48F9CA)
+    assert (worker.wait_reason, worker.wait_minutes, worker.state) == ("chan receive", 2, "chan receive, 2 minutes")
+    assert worker.created_by == "main.main" and worker.user_frame.function == "main.worker"
+    assert [g.runtime_owned for g in goroutines] == [True, False, True, False, False]
+    # runtime.gopark on top is a parked goroutine even without the header.
+    assert summarize_thread(main.to_thread()).state is ThreadState.PARKED
+
+    kind = classify_goroutines(goroutines)
+    assert kind.category is HangCategory.DEADLOCK and kind.waiters == [1, 6]
+    assert [(s.thread_id, s.state) for s in kind.threads] == [
+        (1, ThreadState.PARKED), (5, ThreadState.SLEEPING), (6, ThreadState.PARKED)
+    ]
+    assert "between goroutines 1, 6" in kind.describe()
+    assert kind.threads[0].describe() == "goroutine 1: parked (chan send) in `main.main`"
+
+    default = parse_goroutine_dump(DEFAULT_DUMP)
+    assert [(g.goroutine_id, g.wait_reason) for g in default] == [(1, "chan send"), (7, "semacquire"), (8, "IO wait")]
+    assert default[2].locked_to_thread and default[1].frames[2].function == "main.(*Cache).get"
+    states = [s.state for s in classify_goroutines(default).threads]
+    assert states == [ThreadState.PARKED, ThreadState.LOCK_WAIT, ThreadState.IO_WAIT]
+    # A goroutine in I/O may still wake the others: not a deadlock.
+    assert classify_goroutines(default).category is HangCategory.BLOCKED_ON_IO
+
+
+def test_hung_go_program_is_reported_by_goroutine(tmp_path, monkeypatch):
+    if shutil.which("cc") is None:
+        pytest.skip("no C compiler")
+    # A stand-in for a Go binary: a .go.buildinfo section, and Go's SIGQUIT behaviour.
+    dump = tmp_path / "dump.txt"
+    dump.write_text(SIGQUIT_DUMP)
+    src = tmp_path / "fake_go.c"
+    src.write_text(
+        """
+#include <signal.h>
+#include <stdio.h>
+#include <unistd.h>
+__attribute__((used, section(".go.buildinfo"))) static const char buildinfo[] = "\\xff Go buildinf:";
+static void quit(int sig) {
+    char buf[8192];
+    FILE *fh = fopen("%s", "r");
+    size_t n = fread(buf, 1, sizeof buf, fh);
+    if (write(2, buf, n) < 0) {}
+    _exit(2);
+}
+int main(void) {
+    signal(SIGQUIT, quit);
+    for (;;) pause();
+}
+""" % dump
+    )
+    subprocess.run(["cc", "-o", str(tmp_path / "hang"), str(src)], check=True)
+
+    def no_debugger(_request):
+        raise AnalysisError("no debugger in this test")
+
+    monkeypatch.setattr(api, "_detect_backend", no_debugger)
+    report = analyze(AnalyzeRequest(binary=str(tmp_path / "hang"), timeout=0.5))
+    assert report.fault_kind is FaultKind.HANG and report.hung
+    assert report.hang_kind.category is HangCategory.DEADLOCK and report.hang_kind.waiters == [1, 6]
+    assert [t.thread_id for t in report.threads] == [1, 5, 6]
+    assert report.frames[3].function == "main.main"
+    assert "goroutine 6: parked (chan receive) in `main.worker`" in report.fault_hint
+    data = report.to_dict()
+    assert data["hang"]["threads"][0]["goroutine"] and data["hang"]["threads"][0]["wait_reason"] == "chan send"
+    assert data["language"] == "go"
@@ -0,0 +1,163 @@
+from dbgcopilot.analysis import (
+    Frame,
+    HangCategory,
+    HangConfig,
+    ThreadBacktrace,
+    ThreadState,
+    analyze_hang,
+    classify_threads,
+)
+from dbgcopilot.analysis.parsers import parse_cdb_thread_backtraces
+from dbgcopilot.backends import gdb_batch
+from dbgcopilot.backends.gdb_batch import GdbBatchBackend
+
+# examples/hang/rust: one thread sleeping in a loop.
+GDB_HANG = """\
+Thread 1 (Thread 0x7ffff7d8b780 (LWP 5100) "rust_hang"):
+#0  0x00007ffff7e9a7f8 in __GI___clock_nanosleep (clock_id=0, flags=0, req=0x7fffffffd9f0, rem=0x7fffffffd9f0) at ../sysdeps/unix/sysv/linux/clock_nanosleep.c:78
+#1  0x00007ffff7e9f677 in __GI___nanosleep (req=<optimized out>, rem=<optimized out>) at ../sysdeps/unix/sysv/linux/nanosleep.c:25
+#2  0x000055555556b3c1 in std::thread::sleep () at library/std/src/thread/mod.rs:880
+#3  0x000055555555a0f4 in rust_hang::main () at src/main.rs:7
+"""
+
+# examples/deadlock/rust: both workers hold one mutex and want the other.
+GDB_DEADLOCK = """\
+Thread 3 (Thread 0x7ffff7a89640 (LWP 5203) "worker_two"):
+#0  syscall () at ../sysdeps/unix/sysv/linux/x86_64/syscall.S:38
+#1  0x000055555556c0ab in std::sys::pal::unix::futex::futex_wait () at library/std/src/sys/pal/unix/futex.rs:72
+#2  0x000055555556e5a0 in std::sys::sync::mutex::futex::Mutex::lock_contended () at library/std/src/sys/sync/mutex/futex.rs:62
+#3  0x000055555555b1de in rust_deadlock::worker () at src/main.rs:17
+
+Thread 2 (Thread 0x7ffff7c8a640 (LWP 5202) "worker_one"):
+#0  syscall () at ../sysdeps/unix/sysv/linux/x86_64/syscall.S:38
+#1  0x000055555556c0ab in std::sys::pal::unix::futex::futex_wait () at library/std/src/sys/pal/unix/futex.rs:72
+#2  0x000055555556e5a0 in std::sys::sync::mutex::futex::Mutex::lock_contended () at library/std/src/sys/sync/mutex/futex.rs:62
+#3  0x000055555555b1de in rust_deadlock::worker () at src/main.rs:17
+
+Thread 1 (Thread 0x7ffff7c8b780 (LWP 5201) "rust_deadlock"):
+#0  0x00007ffff7e91117 in __futex_abstimed_wait_common64 (private=128, cancel=true, abstime=0x0, op=265, expected=5202, futex_word=0x7ffff7c8a910) at ./nptl/futex-internal.c:57
+#1  0x00007ffff7e96624 in __pthread_clockjoin_ex (threadid=140737350510144, thread_return=0x0, clockid=0, abstime=0x0, block=true) at ./nptl/pthread_join_common.c:105
+#2  0x000055555556f02e in std::sys::thread::unix::Thread::join () at library/std/src/sys/thread/unix.rs:134
+#3  0x000055555555b7a2 in rust_deadlock::main () at src/main.rs:44
+"""
+
+# examples/async_hang/rust: the task awaits a channel that never receives.
+GDB_ASYNC_HANG = """\
+Thread 3 (Thread 0x7ffff7a89640 (LWP 5303) "async-worker-1"):
+#0  syscall () at ../sysdeps/unix/sysv/linux/x86_64/syscall.S:38
+#1  0x000055555556c0ab in std::sys::pal::unix::futex::futex_wait () at library/std/src/sys/pal/unix/futex.rs:72
+#2  0x000055555556d1b2 in std::sys::sync::condvar::futex::Condvar::wait_optional_timeout () at library/std/src/sys/sync/condvar/futex.rs:50
+#3  0x000055555555e40c in std::sync::poison::condvar::Condvar::wait<alloc::collections::vec_deque::VecDeque<alloc::sync::Arc<rust_async_hang::runtime::Task>>> () at library/std/src/sync/poison/condvar.rs:191
+#4  0x0000555555578bf5 in rust_async_hang::runtime::park::wait_for_work<alloc::collections::vec_deque::VecDeque<alloc::sync::Arc<rust_async_hang::runtime::Task>>> () at src/main.rs:82
+#5  0x0000555555573370 in rust_async_hang::runtime::Shared::next_task () at src/main.rs:107
+#6  0x0000555555572c50 in rust_async_hang::runtime::worker_loop () at src/main.rs:148
+
+Thread 2 (Thread 0x7ffff7c8a640 (LWP 5302) "async-worker-0"):
+#0  syscall () at ../sysdeps/unix/sysv/linux/x86_64/syscall.S:38
+#1  0x000055555556c0ab in std::sys::pal::unix::futex::futex_wait () at library/std/src/sys/pal/unix/futex.rs:72
+#2  0x000055555556d1b2 in std::sys::sync::condvar::futex::Condvar::wait_optional_timeout () at library/std/src/sys/sync/condvar/futex.rs:50
+#3  0x000055555555e40c in std::sync::poison::condvar::Condvar::wait<alloc::collections::vec_deque::VecDeque<alloc::sync::Arc<rust_async_hang::runtime::Task>>> () at library/std/src/sync/poison/condvar.rs:191
+#4  0x0000555555578bf5 in rust_async_hang::runtime::park::wait_for_work<alloc::collections::vec_deque::VecDeque<alloc::sync::Arc<rust_async_hang::runtime::Task>>> () at src/main.rs:82
+#5  0x0000555555573370 in rust_async_hang::runtime::Shared::next_task () at src/main.rs:107
+#6  0x0000555555572c50 in rust_async_hang::runtime::worker_loop () at src/main.rs:148
+
+Thread 1 (Thread 0x7ffff7c8b780 (LWP 5301) "rust_async_hang"):
+#0  syscall () at ../sysdeps/unix/sysv/linux/x86_64/syscall.S:38
+#1  0x000055555556c0ab in std::sys::pal::unix::futex::futex_wait () at library/std/src/sys/pal/unix/futex.rs:72
+#2  0x000055555556b7e4 in std::sys::sync::thread_parking::futex::Parker::park () at library/std/src/sys/sync/thread_parking/futex.rs:55
+#3  0x000055555556a2f1 in std::thread::park () at library/std/src/thread/mod.rs:1100
+#4  0x0000555555577b40 in rust_async_hang::runtime::park::park_thread () at src/main.rs:88
+#5  0x00005555555723c0 in rust_async_hang::runtime::Runtime::block_on<rust_async_hang::channel::Receiver<()>> () at src/main.rs:190
+#6  0x0000555555571a20 in rust_async_hang::main () at src/main.rs:211
+"""
+
+CDB_ALL_THREADS = """\
+.  0  Id: 1a2c.3b4c Suspend: 0 Teb: 000000a0`00123000 Unfrozen
+ # Child-SP          RetAddr               Call Site
+00 000000a0`000ff6b8 00007ffd`3c5e1001     ntdll!NtReadFile+0x14
+01 000000a0`000ff6c0 00007ff6`1a2b1045     KERNELBASE!ReadFile+0x73
+02 000000a0`000ff6f0 00000000`00000000     server!main+0x45 [C:\\src\\server.cpp @ 30]
+
+   1  Id: 1a2c.2d10 Suspend: 1 Teb: 000000a0`00125000 Unfrozen "pool"
+ # Child-SP          RetAddr               Call Site
+00 000000a0`001ff6b8 00007ffd`3c5e2002     ntdll!NtWaitForAlertByThreadId+0x14
+01 000000a0`001ff6c0 00000000`00000000     ntdll!RtlSleepConditionVariableSRW+0x131
+"""
+
+
+def _stack(*functions):
+    return [Frame(i, function=f) for i, f in enumerate(functions)]
+
+
+def test_shipped_examples_classify(monkeypatch):
+    outputs = iter([GDB_HANG, GDB_DEADLOCK, GDB_ASYNC_HANG])
+
+    def fake_run_batch(argv, timeout):
+        assert "thread apply all bt" in argv
+        return "@@dbgcopilot:0@@\n" + next(outputs), False
+
+    monkeypatch.setattr(gdb_batch, "run_batch", fake_run_batch)
+    backend = GdbBatchBackend("gdb", timeout=5)
+    with backend.attach(5100) as session:
+        hang = analyze_hang(session)
+    assert hang.category is HangCategory.BUSY_LOOP
+    assert [(t.state, t.frame.function) for t in hang.threads] == [(ThreadState.SLEEPING, "__GI___clock_nanosleep")]
+
+    with backend.attach(5201) as session:
+        deadlock = analyze_hang(session)
+    assert deadlock.category is HangCategory.DEADLOCK
+    assert deadlock.waiters == [2, 3]
+    states = {t.thread_id: t.state for t in deadlock.threads}
+    assert states == {1: ThreadState.SYNC_WAIT, 2: ThreadState.LOCK_WAIT, 3: ThreadState.LOCK_WAIT}
+    assert "between threads 2, 3" in deadlock.describe()
+
+    # No thread is in user code: the runtime's workers and block_on all wait for a wakeup.
+    with backend.attach(5301) as session:
+        stalled = analyze_hang(session)
+    assert stalled.category is HangCategory.ASYNC_STALLED
+    assert {t.state for t in stalled.threads} == {ThreadState.SCHEDULER_WAIT}
+    assert stalled.threads[-1].frame.function == "rust_async_hang::runtime::park::park_thread"
+    assert stalled.describe().startswith("Hang classification: async stall")
+
+
+def test_blocked_and_unknown_hangs():
+    threads = parse_cdb_thread_backtraces(CDB_ALL_THREADS)
+    assert [(t.thread_id, t.tid, t.name) for t in threads] == [(0, 0x3B4C, None), (1, 0x2D10, "pool")]
+    assert classify_threads(threads).category is HangCategory.BLOCKED_ON_IO
+    assert classify_threads(threads[1:]).category is HangCategory.BLOCKED_ON_SYNC
+
+    # A lone lock waiter next to a thread in epoll is not a deadlock; filtering the poller leaves sync.
+    mixed = [
+        ThreadBacktrace(1, frames=_stack("__lll_lock_wait", "pthread_mutex_lock", "handle_request")),
+        ThreadBacktrace(2, frames=_stack("epoll_wait", "event_loop")),
+    ]
+    assert classify_threads(mixed).category is HangCategory.BLOCKED_ON_IO
+    only_lock = classify_threads(mixed, HangConfig(ignore_frame_patterns=["epoll_wait"]))
+    assert only_lock.category is HangCategory.BLOCKED_ON_SYNC and only_lock.waiters == []
+
+    # Exact-name markers: pthread_create does not count as read().
+    assert classify_threads([ThreadBacktrace(1, frames=_stack("pthread_create"))]).category is HangCategory.BUSY_LOOP
+
+    # tokio: an idle worker parked in its I/O driver is the scheduler's wait, not I/O.
+    tokio_worker = _stack(
+        "epoll_wait",
+        "mio::poll::Poll::poll",
+        "tokio::runtime::io::driver::Driver::turn",
+        "tokio::runtime::driver::Driver::park",
+        "tokio::runtime::scheduler::multi_thread::park::Inner::park_driver",
+    )
+    tokio_main = _stack(
+        "futex_wait", "tokio::runtime::park::Inner::park", "tokio::runtime::park::CachedParkThread::block_on"
+    )
+    blocking_pool = _stack("pthread_cond_timedwait", "std::sync::poison::condvar::Condvar::wait_timeout")
+    tokio = [ThreadBacktrace(1, frames=tokio_main), ThreadBacktrace(2, frames=tokio_worker),
+             ThreadBacktrace(3, frames=blocking_pool)]
+    assert classify_threads(tokio).category is HangCategory.ASYNC_STALLED
+    # A task spinning on a worker is a busy loop, whatever the other workers do.
+    run_task = "tokio::runtime::scheduler::multi_thread::worker::Context::run_task"
+    spinning = ThreadBacktrace(4, frames=_stack("app::checksum", run_task))
+    assert classify_threads(tokio + [spinning]).category is HangCategory.BUSY_LOOP
+
+    unknown = classify_threads([ThreadBacktrace(1, frames=[Frame(0, pc=0x1000)]), ThreadBacktrace(2)])
+    assert unknown.category is HangCategory.UNKNOWN
+    assert [t.describe() for t in unknown.threads] == ["thread 1: unknown", "thread 2: unknown"]
@@ -0,0 +1,114 @@
+from dbgcopilot.analysis import Frame, HangConfig, ThreadBacktrace, Watchdog
+from dbgcopilot.analysis.hang import summarize_samples
+from dbgcopilot.analysis.parsers import parse_gdb_thread_backtraces, parse_lldb_thread_backtraces
+
+GDB_ALL_THREADS = """\
+Thread 2 (Thread 0x7ffff7d8a640 (LWP 1235) "worker"):
+#0  0x00007ffff7e9a7f8 in clock_nanosleep () from /lib/x86_64-linux-gnu/libc.so.6
+#1  0x000055555555a1b2 in std::thread::sleep () at library/std/src/thread/mod.rs:880
+#2  0x0000555555559123 in rust_hang::main () at src/main.rs:6
+
+Thread 1 (Thread 0x7ffff7d8b780 (LWP 1234) "rust_hang"):
+#0  0x00007ffff7e5d117 in __futex_abstimed_wait_common () from /lib/x86_64-linux-gnu/libc.so.6
+"""
+
+LLDB_ALL_THREADS = """\
+* thread #1, name = 'rust_hang', stop reason = signal SIGSTOP
+  * frame #0: 0x00007ffff7e9a7f8 libc.so.6`clock_nanosleep + 200
+    frame #1: 0x000055555555a1b2 rust_hang`std::thread::sleep::h1 at mod.rs:880:5
+  thread #2, tid = 0x04d3, name = 'worker'
+    frame #0: 0x00007ffff7e5d117 libc.so.6`__futex_abstimed_wait_common + 87
+"""
+
+
+def test_parse_all_thread_backtraces():
+    gdb = parse_gdb_thread_backtraces(GDB_ALL_THREADS)
+    assert [(t.thread_id, t.tid, t.name) for t in gdb] == [(2, 1235, "worker"), (1, 1234, "rust_hang")]
+    assert [f.function for f in gdb[0].frames] == ["clock_nanosleep", "std::thread::sleep", "rust_hang::main"]
+
+    lldb = parse_lldb_thread_backtraces(LLDB_ALL_THREADS)
+    assert [(t.thread_id, t.tid, t.name) for t in lldb] == [(1, None, "rust_hang"), (2, 0x4D3, "worker")]
+    assert lldb[0].frames[1].function == "std::thread::sleep::h1"
+
+
+def test_watchdog_reports_dominant_frames():
+    now = [0.0]
+    samples = []
+
+    def sampler(pid):
+        assert pid == 99
+        n = len(samples)
+        samples.append(n)
+        sleeper = ThreadBacktrace(1, name="main", frames=[Frame(0, pc=0x10, function="clock_nanosleep")])
+        spinner_fn = "spin" if n % 4 else "hash"
+        spinner = ThreadBacktrace(2, name="busy", frames=[Frame(0, pc=0x20 + n, function=spinner_fn)])
+        return [sleeper, spinner]
+
+    def sleep(seconds):
+        now[0] += seconds
+
+    report = Watchdog(99, interval_ms=100, duration_ms=1000, sampler=sampler, clock=lambda: now[0], sleep=sleep).run()
+    assert len(samples) == 10 and len(report.sampled_at) == 10
+    assert report.thread_count == 2
+    top = report.dominant_frames
+    assert (top[0].thread_id, top[0].frame.function, top[0].ratio) == (1, "clock_nanosleep", 1.0)
+    assert (top[1].thread_id, top[1].frame.function, top[1].hits) == (2, "spin", 7)
+    assert "thread 1 (main) is in `clock_nanosleep` 100% of samples" in report.describe()
+
+
+def test_hang_config_skips_idle_threads():
+    def sample(n):
+        idle = [ThreadBacktrace(t, frames=[Frame(0, function="epoll_wait")]) for t in (1, 2)]
+        pool = ThreadBacktrace(3, frames=[Frame(0, function="std::thread::sleep" if n else "run_job")])
+        stuck = ThreadBacktrace(4, name="db", frames=[Frame(0, function="pthread_mutex_lock")])
+        return idle + [pool, stuck]
+
+    samples = [sample(n) for n in range(4)]
+    config = HangConfig(ignore_frame_patterns=["epoll_wait", "thread::sleep"])
+    report = summarize_samples(samples, [0.0] * 4, config)
+    assert (report.thread_count, report.ignored_threads) == (4, 2)
+    assert [(d.thread_id, d.frame.function, d.hits) for d in report.dominant_frames] == [
+        (4, "pthread_mutex_lock", 4),
+        (3, "run_job", 1),
+    ]
+    assert report.describe().startswith("4 samples, 4 thread(s), 2 idle not shown:")
+    assert len(summarize_samples(samples, [0.0] * 4).dominant_frames) == 4
+
+
+def test_watch_attaches_only_when_the_program_outlives_the_timeout(tmp_path, monkeypatch, capsys):
+    from dbgcopilot.analysis import api
+    from dbgcopilot.analysis.api import AnalysisError
+    from dbgcopilot.cli import parse_duration
+    from dbgcopilot.cli import ExitCode
+    from dbgcopilot.repl.standalone import main
+
+    sampled = []
+
+    class _Sampler:
+        name = "gdb"
+
+        def sample_threads(self, pid):
+            sampled.append(pid)
+            return parse_gdb_thread_backtraces(GDB_ALL_THREADS)[:1]
+
+    monkeypatch.setattr(api, "_detect_backend", lambda _request: _Sampler())
+    monkeypatch.setattr(api, "HANG_SAMPLE_DURATION_MS", 200)
+    monkeypatch.setattr(api, "HANG_SAMPLE_INTERVAL_MS", 100)
+    script = tmp_path / "prog.sh"
+    script.write_text("#!/bin/sh\nexec sleep 5\n")
+    script.chmod(0o755)
+    assert main(["watch", "--launch", str(script), "--hang-timeout", "300ms"]) == 0
+    out = capsys.readouterr().out
+    assert len(sampled) == 2
+    assert "no exit within 0.3s" in out and "Hang classification: busy loop" in out
+
+    def no_attach(_request):
+        raise AnalysisError("must not attach to a program that exited")
+
+    monkeypatch.setattr(api, "_detect_backend", no_attach)
+    script.write_text("#!/bin/sh\nexit 3\n")
+    # Exiting in time is not the hang watch was looking for.
+    assert main(["watch", "--launch", str(script), "--hang-timeout", "5s", "--format", "json"]) == ExitCode.NO_CRASH
+    assert '"exit_code": 3' in capsys.readouterr().out
+
+    assert [parse_duration(d) for d in ("5s", "500ms", "2m", "1.5")] == [5.0, 0.5, 120.0, 1.5]
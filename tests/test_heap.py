@@ -0,0 +1,97 @@
+from dbgcopilot.analysis import CrashContext, CrashReport, Frame, StopInfo, Variable
+from dbgcopilot.analysis.heap import HeapSummary, bucket_allocations, heap_corruption_indicators, heap_messages
+from dbgcopilot.analysis.render import render_report
+from dbgcopilot.backends import gdb_batch, lldb_batch
+from dbgcopilot.backends.gdb_batch import GdbBatchBackend
+from dbgcopilot.backends.lldb_batch import LldbBatchBackend
+
+# examples/use_after_free/cpp freeing its node twice: glibc aborts from free().
+GDB_OUTPUT = """\
+free(): double free detected in tcache 2
+
+Program received signal SIGABRT, Aborted.
+__pthread_kill_implementation (no_tid=0, signo=6, threadid=<optimized out>) at ./nptl/pthread_kill.c:44
+@@dbgcopilot:siginfo@@
+$1 = (void *) 0x3e800001a2b
+@@dbgcopilot:sicode@@
+si_code=-6
+@@dbgcopilot:bt@@
+#0  __pthread_kill_implementation (no_tid=0, signo=6, threadid=<optimized out>) at ./nptl/pthread_kill.c:44
+#1  0x00007ffff7c4526e in __GI_raise (sig=sig@entry=6) at ../sysdeps/posix/raise.c:26
+#2  0x00007ffff7c288ff in __GI_abort () at ./stdlib/abort.c:79
+#3  0x00007ffff7c297b6 in __libc_message_impl (fmt=fmt@entry=0x7ffff7dce8d7 "%s\\n") at ../sysdeps/posix/libc_fatal.c:134
+#4  0x00007ffff7ca8fe5 in malloc_printerr (str=str@entry=0x7ffff7dd1818 "free(): double free detected in tcache 2") at ./malloc/malloc.c:5772
+#5  0x00007ffff7cab55f in _int_free (av=0x7ffff7e03ac0 <main_arena>, p=0x55555556b2a0, have_lock=0) at ./malloc/malloc.c:4541
+#6  0x00005555555552f1 in main () at use_after_free.cpp:21
+@@dbgcopilot:maps@@
+          Start Addr           End Addr       Size     Offset  Perms  objfile
+      0x555555554000     0x555555555000     0x1000        0x0  r--p   /srv/use_after_free
+      0x555555559000     0x55555557a000    0x21000        0x0  rw-p   [heap]
+@@dbgcopilot:arena@@
+$2 = 135168
+@@dbgcopilot:mmapped@@
+$3 = 0
+"""
+
+
+def test_gdb_reports_heap_size_and_glibc_corruption(monkeypatch):
+    monkeypatch.setattr(gdb_batch, "run_batch", lambda argv, timeout: (GDB_OUTPUT, False))
+    backend = GdbBatchBackend("gdb", timeout=5)
+    backend.load("/srv/use_after_free")
+    assert backend.run_to_fault().signal == "SIGABRT"
+    heap = backend.heap_summary()
+    assert (heap.engine, heap.reserved, heap.committed, heap.buckets) == ("gdb", 0x21000, 135168, [])
+    assert heap.corruption == ["free(): double free detected in tcache 2"]
+    assert heap.note == "allocation counts by size are not available from gdb"
+
+    # The malloc_printerr frame says the same; the report lists it once.
+    frames = backend.backtrace()
+    ctx = CrashContext(stop=StopInfo(signal="SIGABRT"), frames=frames, heap=heap)
+    report = CrashReport.from_context(ctx)
+    assert report.heap.corruption == ["free(): double free detected in tcache 2"]
+    text = report.render()
+    assert "\nHeap: 132 KiB committed, 132 KiB reserved (gdb)\n" in text
+    assert "\nHeap corruption: free(): double free detected in tcache 2\n" in text
+    assert render_report(report.to_dict(), with_explanation=False) == text
+
+    # Without glibc's symbols, gdb says so instead of a committed size.
+    stripped = GDB_OUTPUT.replace("$2 = 135168", 'No symbol "main_arena" in current context.')
+    monkeypatch.setattr(gdb_batch, "run_batch", lambda argv, timeout: (stripped, False))
+    backend.load("/srv/use_after_free")
+    heap = backend.heap_summary()
+    assert heap.committed is None and heap.reserved == 0x21000
+    assert heap.note.startswith("committed size needs glibc's symbols (main_arena); ")
+
+
+def test_engines_without_heap_data_say_so(monkeypatch):
+    output = "(lldb) process launch\nERROR: AddressSanitizer: heap-use-after-free on address 0x602000000010\n"
+    monkeypatch.setattr(lldb_batch, "run_batch", lambda argv, timeout: (output, False))
+    backend = LldbBatchBackend(timeout=5)
+    backend.load("crash")
+    heap = backend.heap_summary()
+    assert not heap.has_statistics and heap.note == "heap statistics not supported by backend lldb"
+    assert heap.corruption == ["AddressSanitizer: heap-use-after-free"]
+
+    quiet = HeapSummary.unsupported("core")
+    assert quiet.describe() == [] and quiet.to_dict()["note"] == "heap statistics not supported by backend core"
+    assert HeapSummary.from_dict(quiet.to_dict()) == quiet
+
+    # Stack and exception code evidence, whichever engine captured them.
+    message = Variable("str", "const char *", '0x7ffff7dd1818 "corrupted size vs. prev_size"')
+    printerr = Frame(3, function="malloc_printerr", locals=[message])
+    assert heap_corruption_indicators(StopInfo(signal="SIGABRT"), [printerr]) == ["corrupted size vs. prev_size"]
+    windows = heap_corruption_indicators(
+        StopInfo(exception_code=0xC0000374), [Frame(0, function="RtlReportCriticalFailure")]
+    )
+    assert windows == [
+        "STATUS_HEAP_CORRUPTION (0xc0000374)",
+        "the Windows heap reported a critical failure (RtlReportCriticalFailure)",
+    ]
+    assert heap_messages("double free or corruption (!prev)\nAborted (core dumped)\n") == [
+        "double free or corruption (!prev)"
+    ]
+    assert [(b.limit, b.count) for b in bucket_allocations([(8, 2), (24, 1), (32, 3), (33, 1)])] == [
+        (16, 2),
+        (32, 4),
+        (64, 1),
+    ]
@@ -0,0 +1,106 @@
+import datetime
+import json
+import os
+import sqlite3
+
+import pytest
+
+from dbgcopilot import cli
+from dbgcopilot.analysis import CrashStore, api
+from dbgcopilot.analysis.cache import crash_signature, signature_hash
+from dbgcopilot.analysis.store import StoreError
+from dbgcopilot.utils.config import load_settings
+
+DAY = 86400.0
+
+
+def test_store_filters_sessions_and_counts_each_signature(tmp_path, fleet_report):
+    now = 1_800_000_000.0
+    with CrashStore(str(tmp_path / "db" / "history.sqlite3")) as store:
+        # The same parser bug from three runs at different load addresses, and one other crash.
+        for i, site in enumerate(["parse_header", "read_body", "parse_header", "parse_header"]):
+            report = fleet_report(site, base=0x555555554000 + i * 0x1000000)
+            store.add(report, "/srv/bin/server", created=now - (3 - i) * DAY)
+        store.add(fleet_report("run_task", signal="SIGABRT"), "worker", created=now)
+
+        sessions = store.sessions()
+        assert [(s.id, s.program, s.function, s.seen) for s in sessions] == [
+            (5, "worker", "run_task", 1),
+            (4, "server", "parse_header", 3),
+            (3, "server", "parse_header", 3),
+            (2, "server", "read_body", 1),
+            (1, "server", "parse_header", 3),
+        ]
+        assert sessions[1].signature_hash == signature_hash(crash_signature(fleet_report("parse_header")))
+        assert sessions[1].report is None and sessions[0].fault_kind == "abort"
+
+        assert [s.id for s in store.sessions(fault_kind="null_deref", function="header")] == [4, 3, 1]
+        assert [s.id for s in store.sessions(function="parse_%")] == []
+        assert [s.id for s in store.sessions(program="worker")] == [5]
+        assert [s.id for s in store.sessions(since=now - 1.5 * DAY)] == [5, 4, 3]
+        assert [s.id for s in store.sessions(signature=sessions[1].signature_hash[:8].upper())] == [4, 3, 1]
+        assert [s.id for s in store.sessions(unique=True)] == [5, 4, 2]
+        assert [s.id for s in store.sessions(limit=2)] == [5, 4]
+        assert len(store.sessions(limit=0)) == 5
+        assert store.seen(sessions[1].signature_hash) == 3
+
+        full = store.get(2)
+        assert full.report["frames"][0]["symbol"] == "read_body" and full.report["outcome"] == "crashed"
+        assert store.get(99) is None
+
+    # A database from a newer version is refused, not misread.
+    newer = tmp_path / "newer.sqlite3"
+    sqlite3.connect(newer).execute("PRAGMA user_version = 99").connection.close()
+    with pytest.raises(StoreError, match="newer dbgcopilot"):
+        CrashStore(str(newer)).sessions()
+
+
+def test_commands_save_crashes_and_history_lists_them(tmp_path, monkeypatch, capsys, fleet_report):
+    sites = iter(["parse_header", "parse_header", "read_body"])
+    monkeypatch.setattr(api, "_analyze_core", lambda request: fleet_report(next(sites)))
+    (tmp_path / "core").write_text("")
+    (tmp_path / "server").write_text("")
+    argv = ["--core", str(tmp_path / "core"), "--no-cache", "--no-llm", "--format", "json", str(tmp_path / "server")]
+    for _ in range(3):
+        assert cli.analyze_main(argv) == 0
+    capsys.readouterr()
+
+    assert cli.history_main([]) == 0
+    lines = capsys.readouterr().out.splitlines()
+    assert lines[0].split() == ["ID", "WHEN", "PROGRAM", "FAULT", "FUNCTION", "SIGNATURE", "SEEN"]
+    rows = [line.split()[4:6] for line in lines[1:]]
+    assert rows == [["null_deref", "read_body"], ["null_deref", "parse_header"], ["null_deref", "parse_header"]]
+    assert lines[2].endswith("2 times") and lines[1].endswith("once")
+
+    assert cli.history_main(["--function", "header", "--unique", "--format", "json"]) == 0
+    [latest] = json.loads(capsys.readouterr().out)
+    assert (latest["id"], latest["seen"], latest["program"]) == (2, 2, "server")
+
+    assert cli.history_main(["1"]) == 0
+    out = capsys.readouterr().out
+    assert out.startswith("Session 1, ") and ": seen 2 times (signature " in out
+    assert "server!parse_header" in out
+    assert cli.history_main(["7"]) == cli.ExitCode.CAPTURE_FAILED
+    assert "no session 7" in capsys.readouterr().err
+
+    # --no-history, or history.enabled = false, saves nothing.
+    before = len(CrashStore(os.environ["DBGCOPILOT_HISTORY_PATH"]).sessions(limit=0))
+    sites = iter(["parse_header"])
+    assert cli.analyze_main(["--no-history"] + argv) == 0
+    monkeypatch.setenv("DBGCOPILOT_HISTORY_ENABLED", "false")
+    sites = iter(["parse_header"])
+    assert cli.analyze_main(argv) == 0
+    assert len(CrashStore(os.environ["DBGCOPILOT_HISTORY_PATH"]).sessions(limit=0)) == before
+
+
+def test_since_takes_an_age_or_a_date_and_history_settings_are_checked():
+    assert cli.parse_since("7d", now=10 * DAY) == 3 * DAY
+    assert cli.parse_since("12h", now=DAY) == DAY / 2
+    assert cli.parse_since("2026-10-01") == datetime.datetime(2026, 10, 1).timestamp()
+    with pytest.raises(Exception, match="invalid time"):
+        cli.parse_since("last week")
+    environ = {"DBGCOPILOT_HISTORY_ENABLED": "off", "DBGCOPILOT_HISTORY_PATH": "/tmp/h.sqlite3"}
+    settings = load_settings({}, path=None, environ=environ)
+    assert settings.get("history.enabled") is False and settings.get("history.path") == "/tmp/h.sqlite3"
+    with pytest.raises(ValueError, match="history.enabled must be true or false"):
+        load_settings({}, path=None, environ={"DBGCOPILOT_HISTORY_ENABLED": "sometimes"})
@@ -0,0 +1,130 @@
+import os
+import shutil
+import subprocess
+import sys
+
+import pytest
+
+from dbgcopilot.analysis import (
+    AnalysisError,
+    AnalyzeRequest,
+    CrashContext,
+    CrashReport,
+    Frame,
+    Language,
+    Module,
+    PromptBuilder,
+    StopInfo,
+)
+from dbgcopilot.analysis import api, analyze, detect_language
+from dbgcopilot.analysis.language import script_command
+
+EXAMPLES = os.path.join(os.path.dirname(__file__), "..", "examples")
+NATIVE_CRASH = os.path.join(EXAMPLES, "crash", "python", "native_crash.py")
+NODE_CRASH = os.path.join(EXAMPLES, "crash", "node", "crash.js")
+
+
+def test_detects_scripts_interpreters_and_native_binaries(tmp_path):
+    assert detect_language(NATIVE_CRASH) is Language.PYTHON
+    command = script_command(NATIVE_CRASH)
+    assert command[-1] == NATIVE_CRASH and os.path.basename(command[0]).startswith("python")
+    assert detect_language(sys.executable) is Language.PYTHON
+    assert detect_language(str(tmp_path / "tool.py")) is Language.PYTHON
+
+    if shutil.which("cc"):
+        src = tmp_path / "crash.c"
+        src.write_text("int main(void) { return *(volatile int *)0; }\n")
+        subprocess.run(["cc", "-o", str(tmp_path / "crash"), str(src)], check=True)
+        assert detect_language(str(tmp_path / "crash")) is Language.C
+
+    # A native driver whose stack runs through the interpreter is a Python crash.
+    frames = [Frame(0, function="strlen", module="libc.so.6"), Frame(1, function="ffi_call"),
+              Frame(2, function="_PyEval_EvalFrameDefault", module="python3.11")]
+    assert detect_language(None, frames) is Language.PYTHON
+    assert detect_language(None, [Frame(0, function="core::panicking::panic_fmt")]) is Language.RUST
+    assert detect_language("/opt/node/bin/node", [Frame(0, function="v8::internal::Invoke")]) is Language.JAVASCRIPT
+    assert detect_language(None, [Frame(0, function="main")]) is Language.UNKNOWN
+
+
+def test_python_hint_reaches_the_prompt():
+    ctx = CrashContext(
+        stop=StopInfo(signal="SIGSEGV", fault_address=0),
+        frames=[Frame(0, pc=0x7F0000001000, function="__strlen_avx2", module="libc.so.6"),
+                Frame(1, pc=0x7F0000002000, function="_PyEval_EvalFrameDefault", module="libpython3.11.so")],
+        language=Language.PYTHON,
+    )
+    text = PromptBuilder().build(api._report_from_context(ctx, "core").crash_report()).text
+    assert "Language: Python" in text
+    assert "interpreter running Python code; the native frames above them" in text
+
+
+def test_native_crash_example_is_a_python_segfault(monkeypatch):
+    def no_debugger(_request):
+        raise AnalysisError("no debugger in this test")
+
+    monkeypatch.setattr(api, "_detect_backend", no_debugger)
+    if script_command(NATIVE_CRASH)[0] == "python3":
+        pytest.skip("python3 is not on PATH")
+    report = analyze(AnalyzeRequest(binary=NATIVE_CRASH))
+    assert report.crashed and report.stop.signal == "SIGSEGV"
+    assert report.language is Language.PYTHON and report.to_dict()["language"] == "python"
+
+
+def test_node_script_runs_under_node_with_its_shebang_options(monkeypatch):
+    # ``#!/usr/bin/env -S node --stack-size=...``: env's -S passes the options on.
+    command = script_command(NODE_CRASH)
+    assert os.path.basename(command[0]) == "node" and command[1:] == ["--stack-size=65500", NODE_CRASH]
+    assert detect_language(NODE_CRASH) is Language.JAVASCRIPT
+    assert detect_language("server.mjs") is Language.JAVASCRIPT
+
+    def no_debugger(_request):
+        raise AnalysisError("no debugger in this test")
+
+    monkeypatch.setattr(api, "_detect_backend", no_debugger)
+    if shutil.which("node") is None:
+        pytest.skip("node is not on PATH")
+    report = analyze(AnalyzeRequest(binary=NODE_CRASH, timeout=60))
+    assert report.crashed and report.stop.signal == "SIGSEGV"
+    assert report.language is Language.JAVASCRIPT
+
+
+def _crash(frames, language=None, modules=()):
+    ctx = CrashContext(stop=StopInfo(signal="SIGSEGV", fault_address=0), frames=frames, modules=list(modules),
+                       language=language)
+    return api._report_from_context(ctx, "core").crash_report()
+
+
+def test_crash_language_is_the_dominant_language_of_the_user_frames():
+    # examples/crash/rust and examples/crash/cpp.
+    rust = _crash([Frame(0, pc=0x1000, function="rust_crash::crash", file="src/main.rs", line=8),
+                   Frame(1, pc=0x1100, function="rust_crash::main", file="src/main.rs", line=2),
+                   Frame(2, pc=0x1200, function="std::rt::lang_start::{{closure}}")], Language.RUST)
+    assert rust.detected_language is Language.RUST
+    assert "a panic rather than an access violation, ownership and borrows" in PromptBuilder().build(rust).text
+    cpp = _crash([Frame(0, pc=0x1000, function="crash_demo::crash()", file="main.cpp", line=12),
+                  Frame(1, pc=0x1100, function="main", file="main.cpp", line=18)])
+    assert cpp.detected_language is Language.CPP and "Language: C++\n" in cpp.render()
+    assert "Use C++'s terms: access violations" in PromptBuilder().build(cpp).text
+
+    # A Rust binary faulting in the C++ library it links is a C++ crash.
+    mixed = _crash([Frame(0, pc=0x1000, function="Codec::decode(char const*)", module="libcodec.so"),
+                    Frame(1, pc=0x1100, raw_symbol="_ZN5Codec4feedEPKcm", function="Codec::feed", module="libcodec.so"),
+                    Frame(2, pc=0x1200, function="codec_feed", file="bridge.cpp", line=9),
+                    Frame(3, pc=0x1300, function="app::ingest", file="src/ingest.rs", line=40)], Language.RUST)
+    assert mixed.detected_language is Language.CPP and mixed.language is Language.RUST
+    assert "Language: C++ (in a Rust target)" in mixed.render()
+    data = mixed.to_dict()
+    assert (data["language"], data["detected_language"]) == ("rust", "cpp")
+    assert CrashReport.from_dict(data).detected_language is Language.CPP
+
+    # Symbols alone: mangling style, then runtime markers on the stack or in the module list.
+    legacy = Frame(0, pc=0x1000, function="app::parse", raw_symbol="_ZN3app5parse17h0123456789abcdefE")
+    assert _crash([legacy]).detected_language is Language.RUST
+    assert _crash([Frame(0, pc=0x1000, function="__cxa_throw"), Frame(1, pc=0x1100)]).detected_language is Language.CPP
+    dylib = _crash([Frame(0, pc=0x1000)], modules=[Module("libstd-8a2b.so", 0x7F0000000000, 0x1000)])
+    assert dylib.detected_language is Language.RUST
+    # An interpreter's native frames do not make a Python crash a C one.
+    python = _crash([Frame(0, pc=0x1000, function="decode", file="ext.c", line=3),
+                     Frame(1, pc=0x1100, function="_PyEval_EvalFrameDefault", module="libpython3.11.so")],
+                    Language.PYTHON)
+    assert python.detected_language is Language.PYTHON
@@ -0,0 +1,114 @@
+from dataclasses import dataclass, field
+
+import pytest
+import requests
+
+from dbgcopilot.analysis import CrashContext, StopInfo, explain_stream
+from dbgcopilot.llm.base import (
+    PROVIDER_ENV_VAR,
+    Completion,
+    LlmConfigError,
+    LlmHttpError,
+    LlmTransportError,
+    OllamaProvider,
+    OpenAiProvider,
+    Prompt,
+    RegistryProvider,
+    resolve_provider,
+)
+
+
+class _FakeJsonResponse:
+    status_code = 200
+    headers = {"Content-Type": "application/json"}
+    text = ""
+
+    def json(self):
+        return {"choices": [{"message": {"content": "local answer"}}], "usage": {"total_tokens": 7}}
+
+
+def test_resolve_provider_reads_config_and_env(monkeypatch):
+    monkeypatch.setenv(PROVIDER_ENV_VAR, "ollama")
+    monkeypatch.setenv("OLLAMA_BASE_URL", "http://gpu-box:11434")
+    monkeypatch.setenv("OLLAMA_MODEL", "qwen2.5-coder")
+    llm = resolve_provider()
+    assert isinstance(llm, OllamaProvider)
+    assert (llm.base_url, llm.model, llm.api_key) == ("http://gpu-box:11434", "qwen2.5-coder", None)
+
+    # The session config wins over the environment.
+    assert isinstance(resolve_provider(None, {"llm_provider": "openai", "openai_api_key": "sk-x"}), OpenAiProvider)
+    assert isinstance(resolve_provider("openrouter"), RegistryProvider)
+
+    sent = {}
+
+    def fake_post(url, headers=None, json=None, timeout=None):
+        sent.update(url=url, headers=headers, body=json, timeout=timeout)
+        return _FakeJsonResponse()
+
+    monkeypatch.setattr(requests, "post", fake_post)
+    completion = llm.complete(Prompt("why did it crash?", system="be brief"))
+    assert completion.text == "local answer"
+    assert completion.provider == "ollama"
+    assert completion.usage["total_tokens"] == 7
+    assert sent["url"] == "http://gpu-box:11434/v1/chat/completions"
+    assert "Authorization" not in sent["headers"]
+    assert [m["role"] for m in sent["body"]["messages"]] == ["system", "user"]
+    assert sent["timeout"] == (20.0, 60.0)
+
+
+class _FakeErrorResponse:
+    status_code = 503
+    headers = {"Content-Type": "text/plain"}
+    text = "model is loading\n"
+
+
+def test_provider_timeouts_and_typed_errors(monkeypatch):
+    monkeypatch.setenv("OLLAMA_TIMEOUT", "300")
+    monkeypatch.setenv("OLLAMA_CONNECT_TIMEOUT", "2.5")
+    llm = resolve_provider("ollama")
+    assert (llm.timeout, llm.connect_timeout) == (300.0, 2.5)
+
+    def slow_post(url, headers=None, json=None, timeout=None, stream=False):
+        assert timeout == (2.5, 300.0)
+        raise requests.exceptions.ConnectTimeout("connect timed out")
+
+    monkeypatch.setattr(requests, "post", slow_post)
+    with pytest.raises(LlmTransportError) as info:
+        llm.complete(Prompt("hi"))
+    assert info.value.timed_out and info.value.provider == "ollama"
+    assert "ollama request timed out" in str(info.value)
+
+    monkeypatch.setattr(requests, "post", lambda *a, **kw: _FakeErrorResponse())
+    with pytest.raises(LlmHttpError) as info:
+        OpenAiProvider(api_key="sk-x").complete(Prompt("hi"))
+    assert (info.value.status, info.value.body, info.value.provider) == (503, "model is loading", "openai")
+
+    monkeypatch.setenv("OLLAMA_TIMEOUT", "soon")
+    with pytest.raises(LlmConfigError):
+        resolve_provider("ollama")
+    with pytest.raises(LlmConfigError):
+        OpenAiProvider(base_url="").complete(Prompt("hi"))
+
+
+@dataclass
+class _RecordingProvider:
+    name: str = "recording"
+    prompts: list = field(default_factory=list)
+
+    def complete(self, prompt):
+        self.prompts.append(prompt.text)
+        return Completion(text="answer", provider=self.name)
+
+    def stream(self, prompt, on_chunk):
+        completion = self.complete(prompt)
+        on_chunk(completion.text)
+        return completion
+
+
+def test_explain_stream_accepts_any_llm_provider():
+    llm = _RecordingProvider()
+    seen = []
+    ctx = CrashContext(stop=StopInfo(signal="SIGABRT", description="double free detected in tcache 2"))
+    assert explain_stream(ctx, seen.append, llm) == "answer"
+    assert seen == ["answer"]
+    assert "SIGABRT" in llm.prompts[0]
@@ -0,0 +1,174 @@
+import json
+import threading
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+import pytest
+
+from dbgcopilot.analysis import AnalyzeRequest, FaultKind, StopInfo
+from dbgcopilot.analysis.api import AnalysisReport, explain
+from dbgcopilot.llm.base import Completion, LlmHttpError, LlmTransportError, OpenAiProvider, Prompt
+from dbgcopilot.llm.errors import http_error, parse_retry_after
+from dbgcopilot.llm.retry import RetryPolicy
+from dbgcopilot.llm.sse import StreamError
+
+
+class _FlakyProvider:
+    name = "flaky"
+
+    def __init__(self, failures):
+        self.failures = list(failures)
+        self.calls = 0
+
+    def complete(self, prompt):
+        self.calls += 1
+        if self.failures:
+            raise self.failures.pop(0)
+        return Completion(text="answer", provider=self.name)
+
+    def stream(self, prompt, on_chunk):
+        completion = self.complete(prompt)
+        on_chunk(completion.text)
+        return completion
+
+
+def _policy(**kwargs):
+    waits = []
+    return RetryPolicy(sleep=waits.append, jitter=lambda: 1.0, **kwargs), waits
+
+
+def test_transient_failures_are_retried_with_backoff():
+    llm = _FlakyProvider([http_error("flaky", 429, "slow down"), http_error("flaky", 503, "overloaded")])
+    policy, waits = _policy(base_delay=0.5)
+    completion, attempts = policy.complete(llm, None)
+    assert (completion.text, attempts, llm.calls) == ("answer", 3, 3)
+    assert waits == [0.5, 1.0]
+
+    timeout = LlmTransportError("flaky request timed out: read", "flaky", timed_out=True)
+    llm = _FlakyProvider([timeout])
+    assert policy.stream(llm, None, lambda chunk: None)[1] == 2
+
+    # Half the backoff is fixed, half random, and it never exceeds max_delay.
+    capped = RetryPolicy(base_delay=4, max_delay=10, jitter=lambda: 0.0)
+    assert [capped.delay(n) for n in (1, 2, 3)] == [2.0, 4.0, 5.0]
+
+
+def test_request_errors_fail_at_once_and_the_last_error_counts_attempts():
+    policy, waits = _policy(max_retries=2)
+    llm = _FlakyProvider([http_error("flaky", 400, "bad model")])
+    with pytest.raises(LlmHttpError) as info:
+        policy.complete(llm, None)
+    assert llm.calls == 1 and waits == [] and info.value.attempts == 1
+    assert "attempts" not in str(info.value)
+
+    refused = LlmTransportError("flaky request failed: connection refused", "flaky")
+    with pytest.raises(LlmTransportError):
+        policy.complete(_FlakyProvider([refused]), None)
+
+    llm = _FlakyProvider([http_error("flaky", 503, "overloaded")] * 5)
+    with pytest.raises(LlmHttpError, match=r"HTTP 503: overloaded \(after 3 attempts\)") as info:
+        policy.complete(llm, None)
+    assert llm.calls == 3 and info.value.attempts == 3 and len(waits) == 2
+
+    policy, waits = _policy(max_retries=0)
+    with pytest.raises(LlmHttpError):
+        policy.complete(_FlakyProvider([http_error("flaky", 503, "overloaded")]), None)
+    assert waits == []
+
+
+def test_a_stream_that_started_is_not_retried():
+    class _Dropping(_FlakyProvider):
+        def stream(self, prompt, on_chunk):
+            self.calls += 1
+            on_chunk("The pointer ")
+            raise LlmTransportError("flaky stream timed out", "flaky", timed_out=True)
+
+    policy, waits = _policy()
+    llm, seen = _Dropping([]), []
+    with pytest.raises(StreamError) as info:
+        policy.stream(llm, None, seen.append)
+    assert llm.calls == 1 and waits == [] and seen == ["The pointer "]
+    assert info.value.partial == "The pointer "
+
+
+def test_config_keys_and_the_explanation_error():
+    assert RetryPolicy.from_config({"max_retries": "5", "base_delay": "0.25"}) == RetryPolicy(5, 0.25)
+    assert RetryPolicy.from_config(None) == RetryPolicy()
+    with pytest.raises(ValueError, match="max_retries must be an integer"):
+        RetryPolicy.from_config({"max_retries": "many"})
+    with pytest.raises(ValueError, match="must not be negative"):
+        RetryPolicy.from_config({"base_delay": "-1"})
+
+    report = AnalysisReport(fault_kind=FaultKind.INVALID_ACCESS, stop=StopInfo(signal="SIGSEGV"))
+    llm = _FlakyProvider([http_error("flaky", 502, "bad gateway")] * 3)
+    request = AnalyzeRequest(binary="crash", provider=llm, llm_config={"max_retries": "2", "base_delay": "0"})
+    explain(report, request)
+    assert report.explanation is None and llm.calls == 3
+    assert report.explanation_error == "flaky HTTP 502: bad gateway (after 3 attempts)"
+
+    llm = _FlakyProvider([http_error("flaky", 429, "slow down")])
+    explain(report, AnalyzeRequest(binary="crash", provider=llm, llm_config={"base_delay": "0"}))
+    assert report.explanation == "answer"
+
+
+def _serve(responses):
+    """A chat-completions endpoint answering with ``responses`` in turn: (status, headers, body)."""
+    seen = []
+
+    class Handler(BaseHTTPRequestHandler):
+        def do_POST(self):
+            seen.append(json.loads(self.rfile.read(int(self.headers["Content-Length"]))))
+            status, headers, body = responses.pop(0)
+            payload = body.encode()
+            self.send_response(status)
+            for name, value in {"Content-Type": "application/json", **headers}.items():
+                self.send_header(name, value)
+            self.send_header("Content-Length", str(len(payload)))
+            self.end_headers()
+            self.wfile.write(payload)
+
+        def log_message(self, *args):
+            pass
+
+    server = HTTPServer(("127.0.0.1", 0), Handler)
+    threading.Thread(target=server.serve_forever, daemon=True).start()
+    return server, seen
+
+
+def test_a_rate_limited_endpoint_is_retried_after_its_retry_after():
+    answer = json.dumps({"choices": [{"message": {"content": "a null write"}}]})
+    limited = (429, {"Retry-After": "2"}, '{"error": "rate limited"}')
+    server, seen = _serve([limited, limited, (200, {}, answer)])
+    try:
+        llm = OpenAiProvider(base_url=f"http://127.0.0.1:{server.server_port}", name="mock")
+        policy, waits = _policy(base_delay=0.1)
+        completion, attempts = policy.complete(llm, Prompt("why?"))
+    finally:
+        server.shutdown()
+    assert (completion.text, attempts, len(seen)) == ("a null write", 3, 3)
+    # The server's wait, not the 0.1 s backoff.
+    assert waits == [2.0, 2.0]
+
+    server, seen = _serve([(401, {}, '{"error": "invalid api key"}')])
+    try:
+        llm = OpenAiProvider(base_url=f"http://127.0.0.1:{server.server_port}", name="mock")
+        with pytest.raises(LlmHttpError) as info:
+            policy.complete(llm, Prompt("why?"))
+    finally:
+        server.shutdown()
+    assert (info.value.status, info.value.attempts, len(seen), waits) == (401, 1, 1, [2.0, 2.0])
+
+
+def test_retry_after_forms_and_the_max_delay_cap():
+    assert parse_retry_after("120") == 120.0
+    assert parse_retry_after("Wed, 21 Oct 2015 07:28:10 GMT", now=1445412480.0) == 10.0
+    assert parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT", now=1445412490.0) == 0.0
+    assert parse_retry_after("soon") is None and parse_retry_after(None) is None
+    assert http_error("mock", 503, "busy", retry_after="7").retry_after == 7.0
+
+    policy, waits = _policy(max_delay=5)
+    llm = _FlakyProvider([http_error("flaky", 429, "slow down", retry_after="3600")])
+    assert policy.complete(llm, None)[1] == 2 and waits == [5]
+
+    assert RetryPolicy.from_config({"max_retries": "0", "max_delay": "2.5"}) == RetryPolicy(0, max_delay=2.5)
+    with pytest.raises(ValueError, match="max_delay must be a number"):
+        RetryPolicy.from_config({"max_delay": "forever"})
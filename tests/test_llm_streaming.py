@@ -0,0 +1,122 @@
+import json
+
+import pytest
+import requests
+
+from dbgcopilot.analysis import CrashContext, Frame, StopInfo, explain_stream
+from dbgcopilot.llm.sse import StreamError, stream_chat_completion
+
+
+def _event(text=None, finish=None):
+    choice = {"delta": {"content": text} if text is not None else {}, "finish_reason": finish}
+    return "data: " + json.dumps({"choices": [choice]})
+
+
+def test_stream_chat_completion_emits_chunks_in_order():
+    lines = [": keep-alive", _event("The "), "", _event("null "), _event("write."), _event(finish="stop"), "data: [DONE]"]
+    seen = []
+    text = stream_chat_completion(iter(lines), seen.append, "test")
+    assert seen == ["The ", "null ", "write."]
+    assert text == "The null write."
+
+
+def test_stream_errors_after_partial_output_raise():
+    def dropped():
+        yield _event("The crash ")
+        yield _event("is in ")
+        raise requests.exceptions.ChunkedEncodingError("connection reset")
+
+    seen = []
+    with pytest.raises(StreamError) as info:
+        stream_chat_completion(dropped(), seen.append, "test")
+    assert seen == ["The crash ", "is in "]
+    assert info.value.partial == "The crash is in "
+    assert "connection reset" in str(info.value)
+
+    # A stream that just stops without [DONE]/finish_reason is also incomplete.
+    with pytest.raises(StreamError) as info:
+        stream_chat_completion(iter([_event("half")]), seen.append, "test")
+    assert info.value.partial == "half"
+
+
+class _FakeStreamResponse:
+    status_code = 200
+    text = ""
+
+    def __init__(self, lines):
+        self._lines = lines
+
+    def iter_lines(self):
+        return iter(self._lines)
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, *_exc):
+        return False
+
+
+def test_explain_stream_uses_openai_compatible_sse(monkeypatch):
+    sent = {}
+
+    def fake_post(url, headers=None, json=None, timeout=None, stream=False):
+        sent.update(url=url, body=json, stream=stream, accept=headers.get("Accept"))
+        return _FakeStreamResponse([_event("Null "), _event("deref in main."), "data: [DONE]"])
+
+    monkeypatch.setattr(requests, "post", fake_post)
+    ctx = CrashContext(
+        stop=StopInfo(signal="SIGSEGV", description="Segmentation fault", fault_address=0),
+        frames=[Frame(index=0, pc=0x401000, function="main")],
+    )
+    seen = []
+    text = explain_stream(ctx, seen.append, "ollama", {"ollama_base_url": "http://127.0.0.1:9"})
+    assert text == "Null deref in main."
+    assert seen == ["Null ", "deref in main."]
+    assert sent["stream"] is True
+    assert sent["body"]["stream"] is True
+    assert sent["accept"] == "text/event-stream"
+    assert sent["url"] == "http://127.0.0.1:9/v1/chat/completions"
+    assert "SIGSEGV" in sent["body"]["messages"][0]["content"]
+
+
+class _DroppingProvider:
+    name = "dropping"
+
+    def stream(self, prompt, on_chunk):
+        on_chunk("The crash ")
+        on_chunk("is in ")
+        raise requests.exceptions.ChunkedEncodingError("connection reset")
+
+
+def test_complete_stream_and_cli_mark_incomplete_output(tmp_path, monkeypatch, capsys):
+    from dbgcopilot.analysis import AnalysisError, api
+    from dbgcopilot.cli import ExitCode, analyze_main
+    from dbgcopilot.llm import providers
+    from dbgcopilot.llm.base import Prompt, complete_stream
+
+    seen = []
+    with pytest.raises(StreamError) as info:
+        for chunk in complete_stream(_DroppingProvider(), Prompt("why?")):
+            seen.append(chunk)
+    # The error arrives after the fragments that made it through, and carries them.
+    assert seen == ["The crash ", "is in "] and info.value.partial == "The crash is in "
+
+    def ask(prompt):
+        raise AssertionError("the CLI should stream")
+
+    def stream(prompt, on_chunk):
+        on_chunk("Null write ")
+        raise StreamError("mock stream interrupted after 11 chars: reset", "Null write ", "mock")
+
+    def no_debugger(_request):
+        raise AnalysisError("no debugger in this test")
+
+    ask.stream = stream
+    monkeypatch.setattr(providers, "create_client", lambda name, config: ask)
+    monkeypatch.setattr(api, "_detect_backend", no_debugger)
+    script = tmp_path / "crash.sh"
+    script.write_text("#!/bin/sh\nkill -SEGV $$\n")
+    script.chmod(0o755)
+    assert analyze_main(["--provider", "mock", "--no-cache", str(script)]) == ExitCode.LLM_FAILED
+    out = capsys.readouterr().out
+    assert out.index("Stop: SIGSEGV") < out.index("Explanation:\nNull write \n[explanation incomplete: mock stream")
@@ -0,0 +1,180 @@
+from dbgcopilot.analysis import AnalyzeRequest, CrashReport, Optimized, analyze, render_report
+from dbgcopilot.analysis import api
+from dbgcopilot.analysis.crash import frame_from_dict
+from dbgcopilot.analysis.prompt import PromptBuilder
+from dbgcopilot.backends import gdb_batch, lldb_batch, pretty_printers
+from dbgcopilot.backends.gdb_batch import GdbBatchBackend
+from dbgcopilot.backends.lldb_batch import LldbBatchBackend
+
+# examples/crash/c built -O1: `boom` writes through a null `slot`; `len` lives only in a register it reused.
+GDB_OUTPUT = """\
+Program received signal SIGSEGV, Segmentation fault.
+boom (slot=0x0, label=0x402004 "config") at crash.c:6
+6	    *slot = len;
+@@dbgcopilot:siginfo@@
+$1 = (void *) 0x0
+@@dbgcopilot:bt@@
+#0  boom (slot=0x0, label=0x402004 "config") at crash.c:6
+#1  0x0000000000401160 in main () at crash.c:14
+@@dbgcopilot:frame0@@
+#0  boom (slot=0x0, label=0x402004 "config") at crash.c:6
+6	    *slot = len;
+@@dbgcopilot:args0@@
+slot = 0x0
+label = 0x402004 "config"
+@@dbgcopilot:locals0@@
+len = <optimized out>
+copy = {x = 1, y = 2}
+@@dbgcopilot:frame1@@
+#1  0x0000000000401160 in main () at crash.c:14
+14	    boom(NULL, "config");
+@@dbgcopilot:args1@@
+No arguments.
+@@dbgcopilot:locals1@@
+No locals.
+"""
+
+
+def test_gdb_reads_the_top_frames_variables_into_the_report_and_prompt(tmp_path, monkeypatch):
+    argvs = []
+
+    def fake_run_batch(argv, timeout):
+        argvs.append(argv)
+        return GDB_OUTPUT, False
+
+    monkeypatch.setattr(gdb_batch, "run_batch", fake_run_batch)
+    monkeypatch.setattr(api, "_detect_backend", lambda _request: GdbBatchBackend(timeout=5))
+    # The program has to crash on its own first; a script that kills itself stands in for it.
+    script = tmp_path / "crash.sh"
+    script.write_text("#!/bin/sh\nkill -SEGV $$\n")
+    script.chmod(0o755)
+    report = analyze(AnalyzeRequest(binary=str(script), locals_frames=2))
+    assert "info locals" in argvs[0] and argvs[0].count("info args") == 2
+
+    top = report.frames[0]
+    assert [(v.name, v.argument) for v in top.locals] == [
+        ("slot", True), ("label", True), ("len", False), ("copy", False)
+    ]
+    assert top.locals[2].value is Optimized.OUT and top.locals[3].value == "{x = 1, y = 2}"
+    assert report.frames[1].locals == []
+
+    text = report.render()
+    assert "#0 boom at crash.c:6\n    slot = 0x0\n" in text and "    len = <optimized out>\n" in text
+    data = report.to_dict()
+    assert data["frames"][0]["locals"][2] == {
+        "name": "len", "type": None, "value": "<optimized out>", "argument": False, "pretty_printed": None
+    }
+    assert render_report(data) == text
+    assert CrashReport.from_dict(data).frames[0].locals == top.locals
+
+    prompt = PromptBuilder().build(report.crash_report()).text
+    assert "    slot = 0x0\n    label = 0x402004 \"config\"\n    len = <optimized out>" in prompt
+
+
+def test_lldb_slices_the_repeated_frame_commands_by_position(monkeypatch):
+    output = """\
+(lldb) target create crash
+Current executable set to 'crash' (x86_64).
+(lldb) process launch
+Process 4242 stopped
+* thread #1, name = 'crash', stop reason = signal SIGSEGV: invalid address (fault address: 0x0)
+(lldb) thread info
+thread #1: tid = 4242, 0x0000000000401136 crash`boom(slot=0x0000000000000000, label="config") at crash.c:6:11, stop reason = signal SIGSEGV: invalid address (fault address: 0x0)
+(lldb) thread backtrace
+* thread #1, name = 'crash', stop reason = signal SIGSEGV: invalid address (fault address: 0x0)
+  * frame #0: 0x0000000000401136 crash`boom(slot=0x0000000000000000, label="config") at crash.c:6:11
+    frame #1: 0x0000000000401160 crash`main at crash.c:14:5
+(lldb) bt all
+(lldb) register read
+(lldb) image list
+(lldb) disassemble --frame
+(lldb) frame select 0
+frame #0: 0x0000000000401136 crash`boom(slot=0x0000000000000000, label="config") at crash.c:6:11
+(lldb) frame variable --no-locals
+(int *) slot = 0x0000000000000000
+(const char *) label = 0x0000000000402004 "config"
+(lldb) frame variable --no-args
+(int) len = <variable not available>
+
+(lldb) frame select 1
+frame #1: 0x0000000000401160 crash`main at crash.c:14:5
+(lldb) frame variable --no-locals
+(lldb) frame variable --no-args
+(int) tries = 3
+"""
+    monkeypatch.setattr(lldb_batch, "run_batch", lambda argv, timeout: (output, False))
+    backend = LldbBatchBackend(timeout=5, locals_frames=2)
+    backend.load("crash")
+    assert backend.run_to_fault().signal == "SIGSEGV"
+    top, caller = backend.frame_variables()
+    assert [(v.type_name, v.name, v.value) for v in top] == [
+        ("int *", "slot", "0x0"),
+        ("const char *", "label", '0x0000000000402004 "config"'),
+        ("int", "len", Optimized.OUT),
+    ]
+    assert [(v.name, v.value, v.argument) for v in caller] == [("tries", "3", False)]
+
+
+def test_live_locals_are_checked_against_the_pretty_printers(monkeypatch):
+    # examples/crash/rust: `items` has the std printers behind it, `raw` is a struct without one.
+    gdb_output = """\
+Program received signal SIGSEGV, Segmentation fault.
+@@dbgcopilot:args0@@
+count = 3
+@@dbgcopilot:locals0@@
+items = vec![1, 2, 3]
+raw = {buf = {ptr = 0x5555555a2b10, cap = 3}, len = 3}
+@@dbgcopilot:printed@@
+dbgcopilot: frame 0 printed: items
+"""
+    argvs = []
+
+    def fake_run_batch(argv, timeout):
+        argvs.append(argv)
+        return gdb_output, False
+
+    monkeypatch.setattr(gdb_batch, "run_batch", fake_run_batch)
+    backend = GdbBatchBackend(timeout=5)
+    backend.printer_dirs = ["/rust/lib/rustlib/etc"]
+    backend.load("app")
+    backend.run_to_fault()
+    [variables] = backend.frame_variables()
+    assert [(v.name, v.pretty_printed) for v in variables] == [("count", None), ("items", True), ("raw", False)]
+    assert f"source {pretty_printers.SCRIPT_PATH}" in argvs[0] and "dbgcopilot-printed 1" in argvs[0]
+    assert "add-auto-load-safe-path /rust/lib/rustlib/etc" in argvs[0]
+    data = {"index": 0, "locals": [{"name": "raw", "value": "{len = 3}", "pretty_printed": False}]}
+    assert frame_from_dict(data).locals[0].pretty_printed is False
+
+    # A core is read as it is: no printer check, nothing flagged.
+    backend.load("app", core="core")
+    backend.run_to_fault()
+    assert "dbgcopilot-printed 1" not in argvs[-1]
+    assert all(v.pretty_printed is None for v in backend.frame_variables()[0])
+
+    lldb_output = """\
+(lldb) target create app
+(lldb) process launch
+(lldb) thread info
+(lldb) thread backtrace
+(lldb) bt all
+(lldb) register read
+(lldb) image list
+(lldb) disassemble --frame
+(lldb) frame select 0
+(lldb) frame variable --no-locals
+(lldb) frame variable --no-args
+(alloc::vec::Vec<int, alloc::alloc::Global>) items = size=3 {
+  [0] = 1
+}
+(app::Raw) raw = {
+  len = 3
+}
+(lldb) command script import %s
+(lldb) dbgcopilot-printed 1
+dbgcopilot: frame 0 printed: items
+""" % pretty_printers.SCRIPT_PATH
+    monkeypatch.setattr(lldb_batch, "run_batch", lambda argv, timeout: (lldb_output, False))
+    backend = LldbBatchBackend(timeout=5)
+    backend.load("app")
+    backend.run_to_fault()
+    assert [(v.name, v.pretty_printed) for v in backend.frame_variables()[0]] == [("items", True), ("raw", False)]
@@ -0,0 +1,167 @@
+import struct
+
+import pytest
+
+from dbgcopilot.analysis import AccessKind, CrashReport, FaultKind, classify_fault
+from dbgcopilot.dumps import InvalidDumpError, TruncatedDumpError
+from dbgcopilot.dumps.minidump import (
+    EXCEPTION_STREAM,
+    MEMORY_LIST_STREAM,
+    MODULE_LIST_STREAM,
+    SYSTEM_INFO_STREAM,
+    THREAD_LIST_STREAM,
+    MinidumpReader,
+    decode_access_violation,
+)
+
+IMAGE = 0x7FF6_0000_0000
+STACK = 0x0000_00A0_0000_0000
+TID = 0x1A2C
+
+
+def build_minidump(code: int = 0xC0000005, params=(1, 0x0), rip: int = IMAGE + 0x1040) -> bytes:
+    """Tiny x64 minidump: one thread, one module, its stack, and an exception."""
+    blobs = []
+    offset = 32 + 5 * 12
+
+    def add(data: bytes) -> int:
+        nonlocal offset
+        rva = offset
+        blobs.append(data)
+        offset += len(data)
+        return rva
+
+    context = bytearray(1232)
+    struct.pack_into("<I", context, 68, 0x10246)
+    struct.pack_into("<Q", context, 152, STACK + 0x100)  # rsp
+    struct.pack_into("<Q", context, 248, rip)
+    ctx_rva = add(bytes(context))
+
+    stack = bytearray(0x200)
+    struct.pack_into("<QQQ", stack, 0x100, 0x1234, IMAGE + 0x2010, IMAGE + 0x3020)
+    stack_rva = add(bytes(stack))
+
+    name = "C:\\app\\crash.exe".encode("utf-16-le")
+    name_rva = add(struct.pack("<I", len(name)) + name)
+
+    sysinfo_rva = add(struct.pack("<H", 9) + b"\x00" * 54)
+
+    thread = struct.pack("<IIIIQ", TID, 0, 0, 0, 0) + struct.pack("<QII", STACK, len(stack), stack_rva)
+    thread += struct.pack("<II", len(context), ctx_rva)
+    threads_rva = add(struct.pack("<I", 1) + thread)
+
+    # Linked 2024-03-01, file version 1.2.3.4 in its VS_FIXEDFILEINFO.
+    module = struct.pack("<QIIII", IMAGE, 0x10000, 0, 1709251200, name_rva)
+    module += struct.pack("<IIII", 0xFEEF04BD, 0x10000, 0x10002, 0x30004) + b"\x00" * 68
+    modules_rva = add(struct.pack("<I", 1) + module)
+
+    memory_rva = add(struct.pack("<I", 1) + struct.pack("<QII", STACK, len(stack), stack_rva))
+
+    exc = bytearray(168)
+    struct.pack_into("<IIIIQQI", exc, 0, TID, 0, code, 0, 0, rip, len(params))
+    struct.pack_into(f"<{len(params)}Q", exc, 40, *params)
+    struct.pack_into("<II", exc, 160, len(context), ctx_rva)
+    exc_rva = add(bytes(exc))
+
+    directory = [
+        (SYSTEM_INFO_STREAM, 56, sysinfo_rva),
+        (THREAD_LIST_STREAM, 4 + 48, threads_rva),
+        (MODULE_LIST_STREAM, 4 + 108, modules_rva),
+        (MEMORY_LIST_STREAM, 4 + 16, memory_rva),
+        (EXCEPTION_STREAM, 168, exc_rva),
+    ]
+    header = struct.pack("<4sIIIIIQ", b"MDMP", 0xA793, len(directory), 32, 0, 0, 0)
+    entries = b"".join(struct.pack("<III", *d) for d in directory)
+    return header + entries + b"".join(blobs)
+
+
+def test_minidump_access_violation_maps_to_null_deref(tmp_path):
+    path = tmp_path / "crash.dmp"
+    path.write_bytes(build_minidump())
+    with MinidumpReader(path) as dump:
+        assert dump.arch == "x86_64"
+        assert [(m.name, m.version, m.timestamp) for m in dump.modules] == [("crash.exe", "1.2.3.4", 1709251200)]
+        assert dump.faulting_thread.thread_id == TID
+
+        stop = dump.stop_info()
+        assert stop.signal == "SIGSEGV"
+        assert stop.exception_code == 0xC0000005
+        assert stop.fault_address == 0
+        assert "write" in stop.description
+
+        ctx = dump.crash_context()
+        assert ctx.instruction_pointer == IMAGE + 0x1040
+        assert [f.pc for f in ctx.frames] == [IMAGE + 0x1040, IMAGE + 0x2010, IMAGE + 0x3020]
+        assert all(f.module == "crash.exe" for f in ctx.frames)
+        assert classify_fault(ctx).kind is FaultKind.NULL_DEREF
+
+
+def test_minidump_stacks_stop_at_the_frame_limit(tmp_path):
+    path = tmp_path / "crash.dmp"
+    path.write_bytes(build_minidump())
+    with MinidumpReader(path) as dump:
+        (thread,) = dump.thread_backtraces(2)
+        assert [f.pc for f in thread.frames] == [IMAGE + 0x1040, IMAGE + 0x2010] and thread.truncated
+        assert thread.faulting and thread.tid == dump.exception.thread_id
+        assert [(len(t.frames), t.truncated) for t in dump.thread_backtraces(0)] == [(3, False)]
+        assert len(dump.crash_context(2).frames) == 2
+
+
+def test_minidump_access_violation_records_the_access_kind(tmp_path):
+    path = tmp_path / "crash.dmp"
+    path.write_bytes(build_minidump())
+    with MinidumpReader(path) as dump:
+        ctx = dump.crash_context()
+    assert (ctx.stop.access, ctx.stop.fault_address) == (AccessKind.WRITE, 0)
+    report = CrashReport.from_context(ctx, source="minidump")
+    assert report.access_kind is AccessKind.WRITE
+    assert "Access: write" in report.render() and "write of 0x0" in report.hint
+    assert CrashReport.from_dict(report.to_dict()).access_kind is AccessKind.WRITE
+
+    assert decode_access_violation(0xC0000005, [0, 0x10]) == (AccessKind.READ, 0x10)
+    assert decode_access_violation(0xC0000005, [8, IMAGE]) == (AccessKind.EXECUTE, IMAGE)
+    # Unknown access types, short records, and other exceptions fall back to UNKNOWN.
+    assert decode_access_violation(0xC0000005, [3, 0x10]) == (AccessKind.UNKNOWN, 0x10)
+    assert decode_access_violation(0xC0000005, [1]) == (AccessKind.UNKNOWN, None)
+    assert decode_access_violation(0xC0000094, [1, 0]) == (AccessKind.UNKNOWN, None)
+
+    path.write_bytes(build_minidump(code=0xC0000094, params=()))
+    with MinidumpReader(path) as dump:
+        report = CrashReport.from_context(dump.crash_context())
+    assert report.access_kind is AccessKind.UNKNOWN and "Access:" not in report.render()
+    assert CrashReport.from_dict({"fault_kind": "unknown"}).access_kind is AccessKind.UNKNOWN
+
+
+def test_minidump_exception_codes_map_to_fault_kinds(tmp_path):
+    expected = {
+        0xC00000FD: FaultKind.STACK_OVERFLOW,
+        0xC0000094: FaultKind.DIVIDE_BY_ZERO,
+        0xC0000374: FaultKind.HEAP_CORRUPTION,
+        0xC000001D: FaultKind.ILLEGAL_INSTRUCTION,
+        0xC0000409: FaultKind.ABORT,
+    }
+    for code, kind in expected.items():
+        path = tmp_path / f"{code:08x}.dmp"
+        path.write_bytes(build_minidump(code=code, params=()))
+        with MinidumpReader(path) as dump:
+            assert classify_fault(dump.crash_context()).kind is kind, hex(code)
+    # STATUS_ASSERTION_FAILURE: the reader's SIGSEGV is a placeholder, not a memory fault at 0.
+    path = tmp_path / "assertion.dmp"
+    path.write_bytes(build_minidump(code=0xC0000420, params=()))
+    with MinidumpReader(path) as dump:
+        found = classify_fault(dump.crash_context())
+        assert dump.stop_info().crashed and found.kind is FaultKind.UNKNOWN and not found.confidence
+        assert found.signals == ["exception code 0xc0000420 is no known fault; the SIGSEGV stands in for it"]
+
+
+def test_minidump_rejects_truncated_and_foreign_files(tmp_path):
+    data = build_minidump()
+    short = tmp_path / "short.dmp"
+    short.write_bytes(data[:100])
+    with pytest.raises(TruncatedDumpError):
+        MinidumpReader(short)
+
+    other = tmp_path / "core"
+    other.write_bytes(b"\x7fELF" + data[4:])
+    with pytest.raises(InvalidDumpError):
+        MinidumpReader(other)
@@ -0,0 +1,62 @@
+from dbgcopilot.analysis import CrashContext, CrashReport, Frame, Module, StopInfo, render_report
+from dbgcopilot.analysis.modules import find_module_conflicts, logical_name, module_version
+
+
+def test_versions_come_from_library_file_names():
+    assert module_version("/usr/lib/x86_64-linux-gnu/libssl.so.1.1") == "1.1"
+    assert module_version("/lib/x86_64-linux-gnu/libc.so.6") == "6"
+    assert module_version("/lib/libc-2.31.so") == "2.31"
+    assert module_version("/usr/lib/libz.1.2.11.dylib") == "1.2.11"
+    assert module_version("C:\\Windows\\System32\\ntdll.dll") is None
+    assert module_version("/srv/app/server") is None
+    assert logical_name(Module("libssl.so.3", 0, 0, path="/opt/app/lib/libssl.so.3")) == "libssl.so"
+    assert logical_name(Module("libz.1.2.11.dylib", 0, 0)) == "libz.dylib"
+    assert logical_name(Module("ZLIB1.DLL", 0, 0, path="C:\\app\\ZLIB1.DLL")) == "zlib1.dll"
+
+
+def test_a_library_loaded_from_two_paths_is_flagged_in_text_and_json():
+    modules = [
+        Module("server", 0x555555554000, 0x5000, path="/srv/app/server"),
+        Module("libssl.so.3", 0x7FFFF7A00000, 0x80000, path="/opt/app/lib/libssl.so.3"),
+        Module("libssl.so.1.1", 0x7FFFF7800000, 0x80000, path="/usr/lib/x86_64-linux-gnu/libssl.so.1.1"),
+        Module("libc.so.6", 0x7FFFF7C00000, 0x200000, path="/lib/x86_64-linux-gnu/libc.so.6"),
+        Module("[vdso]", 0x7FFFF7FC1000, 0x2000),
+    ]
+    ctx = CrashContext(
+        stop=StopInfo(signal="SIGSEGV", fault_address=0x18),
+        frames=[Frame(0, pc=0x7FFFF7A01234, function="SSL_get_peer_certificate")],
+        modules=modules,
+    )
+    report = CrashReport.from_context(ctx)
+    assert [m.version for m in report.modules] == [None, "3", "1.1", "6", None]
+    (conflict,) = report.module_conflicts()
+    warning = (
+        "libssl.so is loaded from 2 paths: /opt/app/lib/libssl.so.3 (3), /usr/lib/x86_64-linux-gnu/libssl.so.1.1 (1.1)"
+    )
+    assert conflict.describe() == warning
+    assert f"Module conflict: {warning}" in report.render()
+    assert "0x7ffff7c00000 libc.so.6 6 /lib/x86_64-linux-gnu/libc.so.6" in report.render(with_modules=True)
+
+    data = report.to_dict()
+    assert data["module_conflicts"] == [{
+        "name": "libssl.so",
+        "paths": ["/opt/app/lib/libssl.so.3", "/usr/lib/x86_64-linux-gnu/libssl.so.1.1"],
+        "versions": ["3", "1.1"],
+        "warning": warning,
+    }]
+    assert data["modules"][3]["version"] == "6" and data["modules"][3]["timestamp"] is None
+    assert f"Module conflict: {warning}" in render_report(data)
+    assert CrashReport.from_dict(data).modules == report.modules
+
+    # Windows images match without case; the same file listed twice is not a conflict.
+    dlls = [
+        Module("zlib1.dll", 0x10000, 0x1000, path="C:\\app\\zlib1.dll"),
+        Module("ZLIB1.DLL", 0x10000, 0x1000, path="C:\\APP\\ZLIB1.DLL"),
+        Module("crash.exe", 0x400000, 0x1000, path="C:\\app\\crash.exe", version="1.2.3.4", timestamp=1709251200),
+    ]
+    assert find_module_conflicts(dlls) == []
+    dlls.append(Module("zlib1.dll", 0x20000, 0x1000, path="C:\\Windows\\System32\\zlib1.dll"))
+    assert [c.name for c in find_module_conflicts(dlls)] == ["zlib1.dll"]
+    report = CrashReport.from_context(CrashContext(stop=StopInfo(signal="SIGSEGV"), modules=dlls))
+    text = report.render(with_modules=True)
+    assert "0x400000 crash.exe 1.2.3.4 (linked 2024-03-01) C:\\app\\crash.exe" in text
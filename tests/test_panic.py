@@ -0,0 +1,129 @@
+import json
+
+from dbgcopilot.analysis import (
+    CrashContext,
+    CrashReport,
+    FaultKind,
+    Frame,
+    Language,
+    PanicInfo,
+    StopInfo,
+    api,
+    build_repro,
+)
+from dbgcopilot.analysis.fault import classify_fault, fault_hint
+from dbgcopilot.analysis.panic import PANIC_HINT, find_panic, is_panic_frame, parse_panic_output
+from dbgcopilot.analysis.prompt import PromptBuilder
+
+CORE = "/rustc/90b35a6239c3d8bdabc530a6a0816f7ff89a0aaf/library/core/src"
+
+
+def _frames(*functions):
+    return [
+        Frame(index=i, pc=0x555555560000 + i * 0x10, function=name, file=file, line=line)
+        for i, (name, file, line) in enumerate(functions)
+    ]
+
+
+# examples/crash/rust/examples/unwrap_none.rs: the hook aborts from under `crash`.
+UNWRAP_NONE = _frames(
+    ("__pthread_kill_implementation", "./nptl/pthread_kill.c", 44),
+    ("__GI_raise", "../sysdeps/posix/raise.c", 26),
+    ("__GI_abort", "./stdlib/abort.c", 79),
+    ("std::process::abort", None, None),
+    ("unwrap_none::main::{{closure}}", "examples/unwrap_none.rs", 23),
+    ("std::panicking::rust_panic_with_hook", None, None),
+    ("rust_begin_unwind", None, None),
+    ("core::panicking::panic_fmt", f"{CORE}/panicking.rs", 74),
+    ("core::panicking::panic", f"{CORE}/panicking.rs", 145),
+    ("core::option::unwrap_failed", f"{CORE}/option.rs", 2015),
+    ("unwrap_none::crash", "examples/unwrap_none.rs", 16),
+    ("unwrap_none::main", "examples/unwrap_none.rs", 27),
+    ("std::panicking::try", None, None),
+    ("main", None, None),
+)
+
+OUTPUT = """\
+About to unwrap a missing --port... this will panic.
+
+thread 'main' (10341) panicked at examples/unwrap_none.rs:16:21:
+called `Option::unwrap()` on a `None` value
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+"""
+
+
+def _abort(frames):
+    return CrashContext(stop=StopInfo(signal="SIGABRT", description="Aborted"), frames=list(frames))
+
+
+def test_an_aborting_unwrap_is_a_rust_panic_explained_from_its_message():
+    # A core: no output, so the failure helper and the first user frame below it stand in.
+    report = api._report_from_context(_abort(UNWRAP_NONE), "core")
+    assert report.panic == PanicInfo("called `Option::unwrap()` on a `None` value", "examples/unwrap_none.rs:16")
+    assert report.fault_kind is FaultKind.RUST_PANIC
+    assert report.fault_hint.startswith(
+        "Rust panic (called `Option::unwrap()` on a `None` value at examples/unwrap_none.rs:16); confidence 0.98"
+    )
+    text = report.render()
+    lead = "Stop: SIGABRT Aborted\nPanic: called `Option::unwrap()` on a `None` value at examples/unwrap_none.rs:16\n"
+    assert lead in text
+    prompt = PromptBuilder().build(report.crash_report()).text
+    assert PANIC_HINT in prompt and "Panic: called `Option::unwrap()`" in prompt
+
+    data = json.loads(report.to_json())
+    assert data["panic"] == {"message": "called `Option::unwrap()` on a `None` value",
+                             "location": "examples/unwrap_none.rs:16"}
+    assert CrashReport.from_dict(data).panic == report.panic
+
+    # What the runtime printed wins, column included.
+    ctx = _abort(UNWRAP_NONE)
+    assert find_panic(ctx, OUTPUT).location == "examples/unwrap_none.rs:16:21"
+    # A message can quote anything; it is redacted like the rest of the prompt.
+    ctx.panic = PanicInfo("no config at /home/alice/.config/app.toml", "src/main.rs:9:5")
+    prompt = PromptBuilder().build(api._report_from_context(ctx, "core").crash_report()).text
+    assert "alice" not in prompt and "no config at <HOME>/.config/app.toml" in prompt
+
+
+def test_printed_panics_in_every_format():
+    assert parse_panic_output(OUTPUT) == PanicInfo(
+        "called `Option::unwrap()` on a `None` value", "examples/unwrap_none.rs:16:21"
+    )
+    # Before Rust 1.73, and a multi-line message; the last panic is the one that stopped the process.
+    text = (
+        "thread 'worker' panicked at 'index out of bounds: the len is 3 but the index is 7', src/pool.rs:40:9\n"
+        "thread 'main' panicked at src/main.rs:12:5:\n"
+        "bad config:\n"
+        "  missing key `port`\n"
+        "\n"
+    )
+    assert parse_panic_output(text.splitlines()[0]) == PanicInfo(
+        "index out of bounds: the len is 3 but the index is 7", "src/pool.rs:40:9"
+    )
+    assert parse_panic_output(text) == PanicInfo("bad config:\n  missing key `port`", "src/main.rs:12:5")
+    assert parse_panic_output("Segmentation fault (core dumped)\n") is None
+
+    # An unwinding panic leaves no stack, only exit status 101 after the message.
+    exited = CrashContext(stop=StopInfo(exit_code=101), frames=[])
+    exited.panic = find_panic(exited, OUTPUT)
+    assert classify_fault(exited).kind is FaultKind.RUST_PANIC
+    assert find_panic(CrashContext(stop=StopInfo(exit_code=1), frames=[]), OUTPUT) is None
+
+
+def test_other_rust_crashes_are_not_panics():
+    # `std::panicking::try` is under `main` on every Rust stack, and a crate may be named rust_panic.
+    assert not is_panic_frame("std::panicking::try") and not is_panic_frame("rust_panic::lookup")
+    assert is_panic_frame("rust_panic") and is_panic_frame("_ZN4core9panicking9panic_fmt17h0123456789abcdefE")
+    segv = CrashContext(
+        stop=StopInfo(signal="SIGSEGV", fault_address=0),
+        frames=_frames(("rust_crash::crash", "src/main.rs", 8), ("std::panicking::try", None, None)),
+    )
+    assert find_panic(segv) is None and classify_fault(segv).kind is FaultKind.NULL_DEREF
+    aborted = _abort(_frames(("__GI_abort", None, None), ("std::process::abort", None, None), ("app::run", None, None)))
+    assert find_panic(aborted) is None and classify_fault(aborted).kind is FaultKind.ABORT
+    assert fault_hint(aborted).startswith("explicit abort")
+
+    repro = build_repro(api._report_from_context(_abort(UNWRAP_NONE), "core").crash_report())
+    # Whatever language detection made of the binary, a panic gets the Cargo project.
+    assert repro.fault_kind is FaultKind.RUST_PANIC and repro.language is Language.RUST
+    assert "black_box(None)" in repro.files["src/main.rs"]
+    assert "fn crash() -> u32" in repro.files["src/main.rs"]
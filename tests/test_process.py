@@ -0,0 +1,48 @@
+import os
+import subprocess
+import time
+
+import pytest
+
+from dbgcopilot.backends.batch import run_batch
+from dbgcopilot.utils.process import descendants, kill_tree
+
+
+def _gone(pid, within=5.0):
+    # SIGKILL lands asynchronously; a zombie waiting on its new parent counts as dead.
+    deadline = time.monotonic() + within
+    while time.monotonic() < deadline:
+        try:
+            with open(f"/proc/{pid}/stat") as fh:
+                if fh.read().rsplit(")", 1)[1].split()[0] == "Z":
+                    return True
+        except FileNotFoundError:
+            return True
+        time.sleep(0.01)
+    return False
+
+
+def test_kill_tree_reaches_grandchildren():
+    if not os.path.isdir("/proc"):
+        pytest.skip("needs /proc")
+    # A child that forks a worker of its own, like a debugger and its inferior.
+    proc = subprocess.Popen(["sh", "-c", "sleep 100 & sleep 100"], start_new_session=True)
+    deadline = time.monotonic() + 5
+    while len(descendants(proc.pid)) < 2 and time.monotonic() < deadline:
+        time.sleep(0.01)
+    below = descendants(proc.pid)
+    assert len(below) == 2
+    kill_tree(proc)
+    assert proc.returncode is not None
+    assert all(_gone(pid) for pid in [proc.pid, *below])
+
+
+def test_run_batch_kills_the_debugger_and_its_inferior_on_timeout():
+    if not os.path.isdir("/proc"):
+        pytest.skip("needs /proc")
+    start = time.monotonic()
+    output, timed_out = run_batch(["sh", "-c", "echo started; sleep 100 & echo $!; wait"], 0.5)
+    assert timed_out and time.monotonic() - start < 10
+    first, inferior = output.split()
+    assert first == "started" and _gone(int(inferior))
+    assert run_batch(["sh", "-c", "echo done"], 5) == ("done\n", False)
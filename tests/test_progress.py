@@ -0,0 +1,42 @@
+from dbgcopilot.analysis import AnalysisError, AnalyzeRequest, ProgressStage, analyze
+from dbgcopilot.analysis import api
+
+
+def test_crash_analysis_reports_each_stage_in_order(monkeypatch, script, fake_gdb, scripted_provider):
+    monkeypatch.setattr(api, "_detect_backend", lambda _request: fake_gdb())
+    events = []
+    llm = scripted_provider(["boom() writes through a null pointer."])
+    request = AnalyzeRequest(binary=script("kill -SEGV $$"), provider=llm, progress=events.append)
+    report = analyze(request)
+    assert report.explanation == "boom() writes through a null pointer."
+    assert [e.stage for e in events] == [
+        ProgressStage.LAUNCHING_TARGET,
+        ProgressStage.LAUNCHING_TARGET,
+        ProgressStage.CAPTURING_BACKTRACE,
+        ProgressStage.SYMBOLIZING,
+        ProgressStage.QUERYING_MODEL,
+        ProgressStage.DONE,
+    ]
+    assert [e.message for e in events] == [
+        "Starting prog.sh",
+        "Running prog.sh under gdb",
+        "Capturing the backtrace with gdb",
+        "Symbolizing 2 frames",
+        "Asking scripted to explain the crash",
+        "Done: crashed",
+    ]
+
+
+def test_no_sink_changes_nothing_and_clean_exits_still_finish(monkeypatch, script):
+    def no_debugger(_request):
+        raise AnalysisError("no debugger in this test")
+
+    monkeypatch.setattr(api, "_detect_backend", no_debugger)
+    events = []
+    report = analyze(AnalyzeRequest(binary=script("exit 3"), progress=events.append))
+    assert [(e.stage, e.message) for e in events] == [
+        (ProgressStage.LAUNCHING_TARGET, "Starting prog.sh"),
+        (ProgressStage.DONE, "Done: exited"),
+    ]
+    quiet = analyze(AnalyzeRequest(binary=script("exit 3")))
+    assert quiet.to_dict() == report.to_dict()
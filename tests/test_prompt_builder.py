@@ -0,0 +1,128 @@
+from dbgcopilot.analysis import CrashContext, CrashReport, FaultKind, Frame, FrameBudget, PromptBuilder, StopInfo
+from dbgcopilot.analysis.api import _report_from_context, build_explain_prompt
+from dbgcopilot.analysis.prompt import estimate_tokens
+
+
+def _overflow_report(depth=5000):
+    # examples/stack_overflow: descend() recursing until it hits the guard page.
+    frames = [Frame(0, pc=0x401000, function="__memset_avx2_unaligned_erms", module="libc.so.6")]
+    frames += [Frame(i, pc=0x401100, function="rust_stack_overflow::descend", file="src/main.rs", line=9)
+               for i in range(1, depth)]
+    frames += [
+        Frame(depth, pc=0x401200, function="rust_stack_overflow::main", file="src/main.rs", line=20),
+        Frame(depth + 1, pc=0x7FFF0100, function="__libc_start_call_main", module="libc.so.6"),
+        Frame(depth + 2, pc=0x402000, function="_start"),
+    ]
+    ctx = CrashContext(stop=StopInfo(signal="SIGSEGV", fault_address=0x7FFFFF7FEFF8), frames=frames)
+    return _report_from_context(ctx, "core")
+
+
+def test_deep_recursion_fits_the_budget():
+    report = _overflow_report()
+    assert report.fault_kind is FaultKind.STACK_OVERFLOW
+    built = PromptBuilder(max_tokens=600).build(report.crash_report(max_frames=len(report.frames)))
+    assert built.tokens <= 600
+    lines = [e.describe() for e in built.entries]
+    # The faulting frame and its first callers stay verbatim, then the rest of the run collapses.
+    assert lines[0] == "#0 0x401000 libc.so.6!__memset_avx2_unaligned_erms"
+    assert lines[1] == "#1 0x401100 rust_stack_overflow::descend at src/main.rs:9"
+    assert (
+        "#4 0x401100 rust_stack_overflow::descend at src/main.rs:9\n"
+        "... (4995 repeated frames of rust_stack_overflow::descend) ..."
+    ) in lines
+    assert "#5000 0x401200 rust_stack_overflow::main at src/main.rs:20" in lines
+    assert (built.elision.collapsed, built.elision.dropped) == (4995, 0)
+    assert "... 4995 frames summarized (4995 collapsed as recursion)" in built.text
+
+    # A tighter budget drops the libc frame before user code and before _start.
+    tight = PromptBuilder(max_tokens=200, keep_top=2).build(report.crash_report(max_frames=len(report.frames)))
+    assert tight.elision.dropped_system == 1
+    assert not any("__libc_start_call_main" in e.describe() for e in tight.entries)
+    assert "#5000 0x401200 rust_stack_overflow::main at src/main.rs:20" in tight.text
+
+    # The default prompt path sees every captured frame, not just the top 16.
+    assert build_explain_prompt(report) == PromptBuilder().build(report.crash_report(len(report.frames))).text
+    assert "... (4995 repeated frames of rust_stack_overflow::descend) ..." in build_explain_prompt(report)
+
+
+def test_budget_keeps_entry_frames_and_trims_adaptively():
+    frames = [Frame(0, pc=0x401000, function="fault", file="src/main.rs", line=3)]
+    frames += [Frame(i, pc=0x401100 + 0x10 * i, function=f"helper{i}", file="src/lib.rs", line=i) for i in range(1, 60)]
+    frames += [
+        Frame(60, pc=0x401200, function="main", file="src/main.rs", line=20),
+        Frame(61, pc=0x7FFF0100, function="__libc_start_main", module="libc.so.6"),
+        Frame(62, pc=0x402000, function="_start"),
+    ]
+    budget = FrameBudget(max_tokens=150, keep_top=2, keep_bottom=2)
+    entries, elision = budget.fit(frames)
+    kept = [e.frame.function for e in entries]
+    # The middle goes, deepest first; main and _start survive, the libc frame between them does not.
+    assert kept[:2] == ["fault", "helper1"] and kept[-2:] == ["main", "_start"]
+    assert "__libc_start_main" not in kept and elision.dropped_system == 1
+    assert sum(estimate_tokens(e.describe() + "\n") for e in entries) <= 150
+    # A looser budget keeps more of the middle.
+    assert len(FrameBudget(max_tokens=400, keep_top=2).fit(frames)[0]) > len(entries)
+
+
+def test_small_stacks_are_unchanged_and_budget_is_configurable():
+    frames = [Frame(i, function=f"f{i}") for i in range(8)]
+    report = CrashReport(fault_kind=FaultKind.ABORT, signal="SIGABRT", frames=frames, total_frames=40)
+    built = PromptBuilder().build(report)
+    assert [e.frame.index for e in built.entries] == list(range(8))
+    # Frames the capture already cut off still count as summarized.
+    assert built.elision.uncaptured == 32 and "32 frames summarized (32 not captured)" in built.text
+
+    # Over budget: the frames between the kept top and the kept bottom go, deepest first.
+    clipped = PromptBuilder.from_config({"max_prompt_tokens": "100"}).build(report)
+    assert clipped.elision.dropped == 2
+    assert [e.frame.index for e in clipped.entries] == [0, 1, 2, 3, 6, 7]
+
+
+def test_other_threads_are_summarized_but_kept_in_json():
+    from dbgcopilot.analysis import ThreadBacktrace
+
+    def worker_stack():
+        return [Frame(0, pc=0x7FFF0200, function="__futex_abstimed_wait_common", module="libc.so.6"),
+                Frame(1, pc=0x7FFF0300, function="pthread_cond_wait", module="libc.so.6"),
+                Frame(2, pc=0x401500, function="pool::Worker::run", file="src/pool.rs", line=41),
+                Frame(3, pc=0x401600, function="std::sys::thread::start", file="library/std/src/thread.rs", line=7)]
+
+    crash = [Frame(0, pc=0x401100, function="pool::Shared::take", file="src/pool.rs", line=88),
+             Frame(1, pc=0x401200, function="pool::main", file="src/main.rs", line=12)]
+    threads = [
+        ThreadBacktrace(1, tid=100, name="pool", frames=list(crash)),
+        ThreadBacktrace(2, tid=101, name="worker-0", frames=worker_stack()),
+        ThreadBacktrace(3, tid=102, name="worker-1", frames=worker_stack()),
+        ThreadBacktrace(4, tid=103, name="reaper", frames=[Frame(0, pc=0x7FFF0400, function="__GI___clock_nanosleep", module="libc.so.6")]),
+    ]
+    ctx = CrashContext(stop=StopInfo(signal="SIGSEGV", fault_address=0x18), frames=crash, threads=threads)
+    report = _report_from_context(ctx, "gdb").crash_report()
+    assert report.faulting_thread.tid == 100 and report.faulting_thread.faulting
+
+    text = PromptBuilder().build(report).text
+    assert "#1 0x401200 pool::main at src/main.rs:12" in text
+    assert (
+        "Other threads:\n"
+        "Threads 2, 3 (2 threads, same stack):\n"
+        "  #0 0x7fff0200 libc.so.6!__futex_abstimed_wait_common\n"
+        "  #1 0x7fff0300 libc.so.6!pthread_cond_wait\n"
+        "  #2 0x401500 pool::Worker::run at src/pool.rs:41\n"
+        "  ... 1 more frames\n"
+        'Thread 4 (LWP 103, "reaper"):\n'
+        "  #0 0x7fff0400 libc.so.6!__GI___clock_nanosleep"
+    ) in text
+
+    # Short of room, other threads shrink to their top frame, then give way to the faulting stack.
+    full = estimate_tokens(text)
+    shallow = PromptBuilder(max_tokens=full - 20).build(report).text
+    assert "  #0 0x7fff0200 libc.so.6!__futex_abstimed_wait_common\n  ... 3 more frames" in shallow
+    stack_only = PromptBuilder(max_tokens=full - 80).build(report)
+    assert "... 3 other threads left out to fit the token budget" in stack_only.text
+    assert stack_only.elision.total == 0
+
+    # The structured report keeps every frame of every thread.
+    data = report.to_dict()
+    assert [(t["tid"], t["faulting"], len(t["frames"])) for t in data["threads"]] == [
+        (100, True, 2), (101, False, 4), (102, False, 4), (103, False, 1)
+    ]
+    assert CrashReport.from_dict(data).threads == report.threads
@@ -0,0 +1,108 @@
+import json
+
+import pytest
+
+from dbgcopilot import cli
+from dbgcopilot.analysis import (
+    CrashContext,
+    CrashReport,
+    Frame,
+    HangCategory,
+    Module,
+    PromptBuilder,
+    PromptTemplate,
+    Register,
+    StopInfo,
+    TemplateError,
+    api,
+)
+from dbgcopilot.analysis.template import DEFAULT_TEMPLATE
+
+
+def _report():
+    ctx = CrashContext(
+        stop=StopInfo(signal="SIGSEGV", fault_address=0),
+        frames=[
+            Frame(0, pc=0x401136, function="parse_header", file="parse.c", line=42, module="server"),
+            Frame(1, pc=0x401190, function="main", file="main.c", line=9, module="server"),
+        ],
+        modules=[Module("server", 0x400000, 0x5000, path="/srv/server"), Module("libssl.so.3", 0x7F0000, 0x1000)],
+    )
+    return CrashReport.from_context(ctx)
+
+
+def test_the_built_in_prompt_is_the_default_template():
+    report = _report()
+    default = PromptBuilder().build(report).text
+    assert PromptBuilder(template=PromptTemplate.parse(DEFAULT_TEMPLATE)).build(report).text == default
+    assert default.startswith("You are a debugging copilot.") and "\n\nStop: SIGSEGV" in default
+
+
+def test_a_template_is_filled_from_the_report(tmp_path):
+    path = tmp_path / "terse.txt"
+    path.write_text(
+        "Root cause in one sentence, then a {{code}} fix.\n"
+        "{signal} in {faulting_frame}\n"
+        "{hang_kind}\n"
+        "Stack:\n{stack}\n"
+        "Modules:\n{modules}\n"
+    )
+    template = PromptTemplate.load(str(path))
+    assert template.placeholders == ["signal", "faulting_frame", "hang_kind", "stack", "modules"]
+    text = PromptBuilder(template=template).build(_report()).text
+    assert text == (
+        "Root cause in one sentence, then a {code} fix.\n"
+        "SIGSEGV in #0 0x401136 server!parse_header at parse.c:42\n"
+        "Stack:\n#0 0x401136 server!parse_header at parse.c:42\n#1 0x401190 server!main at main.c:9\n"
+        "Modules:\n0x400000 server /srv/server\n0x7f0000 libssl.so.3 3\n"
+    )
+
+    hung = _report()
+    hung.hang_category = HangCategory.DEADLOCK
+    text = PromptBuilder(template=template).build(hung).text
+    assert "\ndeadlock (threads or goroutines waiting on each other)\n" in text
+
+    audited = PromptTemplate.parse("Approved preamble.\n{fault_kind} at {registers}\n{backtrace}")
+    report = _report()
+    report.registers = report.registers.from_list([Register("rip", 0x401136), Register("rsp", 0x7FFC0)])
+    assert PromptBuilder(template=audited).build(report).text == (
+        "Approved preamble.\nnull-pointer dereference at rip=0x401136 rsp=0x7ffc0\n"
+        "#0 0x401136 server!parse_header at parse.c:42\n#1 0x401190 server!main at main.c:9"
+    )
+
+
+def test_typos_in_a_template_are_reported_when_it_is_loaded(tmp_path, monkeypatch, capsys):
+    path = tmp_path / "prompt.txt"
+    path.write_text("Explain this crash.\nStack:\n{stak}\n")
+    with pytest.raises(TemplateError) as excinfo:
+        PromptTemplate.load(str(path))
+    assert str(excinfo.value).startswith(f"{path}, line 3: unknown placeholder {{stak}}; did you mean {{stack}}?")
+    for bad, message in (
+        ("{}", "needs a placeholder name"),
+        ("{stack!r}", "takes no format spec"),
+        ("{", "braces"),
+        ("Explain this crash.\n{language_hint}", "uses no crash placeholder"),
+    ):
+        with pytest.raises(TemplateError) as excinfo:
+            PromptTemplate.parse(bad)
+        assert message in str(excinfo.value)
+    with pytest.raises(TemplateError):
+        PromptTemplate.load(str(tmp_path / "missing.txt"))
+
+    monkeypatch.chdir(tmp_path)
+    with pytest.raises(SystemExit):
+        cli.analyze_main(["--prompt-template", str(path), "--no-llm", "prog"])
+    assert "did you mean {stack}?" in capsys.readouterr().err
+
+    # From the settings file, as llm.prompt_template.
+    path.write_text("Be terse.\n{report}\n")
+    (tmp_path / "dbgcopilot.toml").write_text(f'[llm]\nprompt_template = "{path}"\n')
+    monkeypatch.setattr(api, "_analyze_core", lambda request: api._report_from_context(_report_context(), "core"))
+    (tmp_path / "core").write_text("")
+    (tmp_path / "server").write_text("")
+    assert cli.analyze_main(["--no-llm", "--format", "json", "--core", "core", "server"]) == 0
+    assert json.loads(capsys.readouterr().out)["prompt"].startswith("Be terse.\nStop: SIGSEGV")
+
+
+def _report_context():
+    return CrashContext(stop=StopInfo(signal="SIGSEGV", fault_address=0), frames=[Frame(0, pc=0x401136)])
@@ -0,0 +1,136 @@
+from dbgcopilot.analysis import CrashContext, CrashReport, Register, StopInfo
+from dbgcopilot.analysis.parsers import parse_gdb_memory_map
+from dbgcopilot.analysis.regions import (
+    MapEntry,
+    MemoryRegion,
+    RegionKind,
+    classify_address,
+    core_memory_map,
+    fault_region,
+    freed_chunks,
+)
+from dbgcopilot.backends import gdb_batch
+from dbgcopilot.backends.gdb_batch import GdbBatchBackend
+
+MAPS = """\
+          Start Addr           End Addr       Size     Offset  Perms  objfile
+      0x555555554000     0x555555555000     0x1000        0x0  r--p   /srv/crash
+      0x555555555000     0x555555556000     0x1000     0x1000  r-xp   /srv/crash
+      0x555555559000     0x55555557a000    0x21000        0x0  rw-p   [heap]
+      0x7ffff7a00000     0x7ffff7a01000     0x1000        0x0  ---p
+      0x7ffff7a01000     0x7ffff7c00000   0x1ff000        0x0  rw-p
+      0x7ffff7c00000     0x7ffff7c28000    0x28000        0x0  r--p   /usr/lib/x86_64-linux-gnu/libc.so.6
+      0x7ffffffde000     0x7ffffffff000    0x21000        0x0  rw-p   [stack]
+"""
+
+# examples/use_after_free/cpp built with -fsanitize=address, reading the node it freed.
+ASAN_OUTPUT = """\
+==4242==ERROR: AddressSanitizer: heap-use-after-free on address 0x602000000010 at pc 0x55555555a2f1
+READ of size 4 at 0x602000000010 thread T0
+    #0 0x55555555a2f0 in main use_after_free.cpp:21
+0x602000000010 is located 0 bytes inside of 16-byte region [0x602000000010,0x602000000020)
+freed by thread T0 here:
+    #0 0x7ffff7ab31a8 in operator delete(void*, unsigned long)
+"""
+
+
+def test_gdb_memory_map_places_heap_stack_module_and_guard():
+    memory_map = parse_gdb_memory_map(MAPS)
+    assert len(memory_map) == 7
+    assert memory_map[1] == MapEntry(0x555555555000, 0x555555556000, "r-xp", "/srv/crash", 0x1000)
+    assert memory_map[3].path is None
+
+    thread_stack = 0x7ffff7c00000 - 0x100
+    kind = lambda address: classify_address(address, memory_map, [thread_stack]).kind  # noqa: E731
+    assert kind(0x555555560010) is RegionKind.HEAP
+    assert kind(0x7fffffffe000) is RegionKind.STACK
+    assert kind(thread_stack - 0x1000) is RegionKind.STACK
+    assert kind(0x7ffff7a00800) is RegionKind.GUARD_PAGE
+    assert kind(0x10) is RegionKind.NULL_PAGE
+    assert kind(0x1000000) is RegionKind.UNMAPPED
+
+    module = classify_address(0x555555555123, memory_map)
+    assert module.kind is RegionKind.MODULE and module.module == "crash"
+    assert module.describe() == "Fault region: 0x555555555123 is mapped file crash [0x555555555000-0x555555556000) r-xp"
+    guard = classify_address(0x7ffff7a00800, memory_map, [thread_stack])
+    assert guard.note == "directly below a stack"
+    below = classify_address(0x7ffffffdd000, memory_map)
+    assert below.kind is RegionKind.UNMAPPED and below.note == "0x1000 bytes below the stack at 0x7ffffffde000"
+
+
+def test_null_page_needs_no_map_but_other_addresses_do():
+    null = fault_region(CrashContext(stop=StopInfo(signal="SIGSEGV", fault_address=0x18)), [])
+    assert null is not None and null.kind is RegionKind.NULL_PAGE
+    assert null.describe() == "Fault region: 0x18 is unmapped, in the null page; a null pointer plus a small offset"
+    assert fault_region(CrashContext(stop=StopInfo(signal="SIGSEGV", fault_address=0x7f0000001000)), []) is None
+    assert fault_region(CrashContext(stop=StopInfo(signal="SIGSEGV")), []) is None
+    # A general protection fault: the kernel gives no address, and si_addr reads 0.
+    assert fault_region(CrashContext(stop=StopInfo(signal="SIGSEGV", fault_address=0, si_code=0x80)), []) is None
+
+
+def test_asan_freed_chunk_is_reported_as_freed_heap():
+    assert freed_chunks(ASAN_OUTPUT) == [(0x602000000010, 0x602000000020)]
+    # A heap overflow names a region too, but one that is still allocated.
+    assert freed_chunks(ASAN_OUTPUT.replace("freed by thread", "allocated by thread")) == []
+
+    heap = [MapEntry(0x602000000000, 0x602000010000, "rw-p")]
+    # ASan aborts instead of letting the access fault, so the address comes from its report.
+    ctx = CrashContext(stop=StopInfo(signal="SIGABRT"))
+    region = fault_region(ctx, heap, ASAN_OUTPUT)
+    assert region is not None and region.kind is RegionKind.FREED_HEAP
+    assert (region.address, region.start, region.end) == (0x602000000010, 0x602000000010, 0x602000000020)
+    assert fault_region(ctx, [], ASAN_OUTPUT).kind is RegionKind.FREED_HEAP
+
+
+def test_core_memory_map_names_files_and_the_heap_above_the_executable():
+    segments = [
+        (0x7ffc0000, 0x21000, "rw-p"),
+        (0x400000, 0x1000, "r-xp"),
+        (0x401000, 0x1000, "rw-p"),
+        (0x1200000, 0x21000, "rw-p"),
+        (0x7f0000000000, 0x1000, "---p"),
+    ]
+    files = [(0x400000, 0x401000, 0, "/srv/crash")]
+    memory_map = core_memory_map(segments, files, "/srv/crash")
+    assert [e.start for e in memory_map] == sorted(s[0] for s in segments)
+    assert memory_map[0].path == "/srv/crash"
+    # Directly after the image is its .bss; brk starts further up.
+    assert memory_map[1].path is None
+    assert memory_map[2].path == "[heap]"
+    assert classify_address(0x1200010, memory_map).kind is RegionKind.HEAP
+    # Without the executable's name nothing is guessed to be the heap.
+    assert all(e.path != "[heap]" for e in core_memory_map(segments, files))
+
+
+def test_report_renders_and_round_trips_the_region():
+    ctx = CrashContext(
+        stop=StopInfo(signal="SIGSEGV", fault_address=0x555555560010),
+        registers=[Register("rsp", 0x7fffffffe000)],
+    )
+    ctx.fault_region = fault_region(ctx, parse_gdb_memory_map(MAPS))
+    report = CrashReport.from_context(ctx)
+    assert "Fault region: 0x555555560010 is heap [0x555555559000-0x55555557a000) rw-p" in report.render()
+    data = report.to_dict()
+    assert data["fault_region"]["kind"] == "heap" and data["fault_region"]["start"] == "0x555555559000"
+    assert CrashReport.from_dict(data).fault_region == report.fault_region
+    assert MemoryRegion.from_dict(data["fault_region"]) == ctx.fault_region
+    assert CrashReport.from_dict({**data, "fault_region": None}).fault_region is None
+
+
+def test_gdb_capture_attributes_the_fault_to_its_mapping(monkeypatch):
+    output = (
+        "\nProgram received signal SIGSEGV, Segmentation fault.\n"
+        "0x0000555555555123 in main () at crash.c:5\n"
+        "@@dbgcopilot:siginfo@@\n$1 = (void *) 0x7ffff7a00800\n"
+        "@@dbgcopilot:bt@@\n#0  main () at crash.c:5\n"
+        "@@dbgcopilot:regs@@\nrsp            0x7ffff7bfff00      0x7ffff7bfff00\n"
+        "@@dbgcopilot:maps@@\n" + MAPS
+    )
+    monkeypatch.setattr(gdb_batch, "run_batch", lambda argv, timeout: (output, False))
+    backend = GdbBatchBackend("gdb", timeout=5)
+    backend.load("/srv/crash")
+    stop = backend.run_to_fault()
+    ctx = CrashContext(stop=stop, registers=backend.read_registers())
+    region = fault_region(ctx, backend.memory_map(), backend.raw_output)
+    assert region is not None and region.kind is RegionKind.GUARD_PAGE
+    assert region.note == "directly below a stack"
@@ -0,0 +1,75 @@
+from dbgcopilot.analysis import (
+    Architecture,
+    CrashContext,
+    CrashReport,
+    Frame,
+    PromptBuilder,
+    Register,
+    Registers,
+    StopInfo,
+    describe_address_source,
+    render_report,
+)
+from dbgcopilot.analysis.registers import dwarf_register_name
+from dbgcopilot.dumps.coredump import AARCH64_GREGS
+
+
+def test_roles_are_found_under_each_capture_paths_names():
+    x86 = Registers.from_list([Register("RAX", 0), Register("rdi", 0x10), Register("rip", 0x401136),
+                               Register("rsp", 0x7FFC3A10), Register("rbp", 0x7FFC3A30)])
+    assert x86.arch is Architecture.X86_64
+    assert (x86.get("rax"), x86.rdi, x86.get("PC"), x86.get("sp")) == (0, 0x10, 0x401136, 0x7FFC3A10)
+    assert (x86.instruction_pointer, x86.frame_pointer, x86.link_register) == (0x401136, 0x7FFC3A30, None)
+    assert x86.role_name("ip") == "rip" and x86.get("r15") is None
+
+    # The core reader's AArch64 names, and LLDB's (fp/lr instead of x29/x30).
+    core = Registers.from_list(Register(name, i) for i, name in enumerate(AARCH64_GREGS))
+    lldb = Registers.from_list([Register("x0", 0), Register("fp", 0x29), Register("lr", 0x30),
+                                Register("sp", 0x31), Register("pc", 0x32), Register("cpsr", 0)])
+    for regs in (core, lldb):
+        assert regs.arch is Architecture.AARCH64
+        assert regs.get("fp") is not None and regs.link_register == regs.get("lr")
+    assert (core.frame_pointer, core.link_register, core.stack_pointer, core.instruction_pointer) == (29, 30, 31, 32)
+    assert lldb.to_dict() == {"arch": "aarch64", "instruction_pointer": "0x32", "stack_pointer": "0x31",
+                              "frame_pointer": "0x29", "link_register": "0x30"}
+    assert not Registers() and Registers().get("pc") is None
+
+
+def test_the_register_that_held_the_fault_address_is_named():
+    regs = Registers.from_list([Register("rax", 0x1000), Register("rdi", 0), Register("rsi", 0), Register("rip", 1)])
+    assert describe_address_source(regs, 0x18) == "rsi or rdi + 0x18 (both 0x0)"
+    assert describe_address_source(regs, 0x1008) == "rax + 0x8 (rax = 0x1000)"
+    assert describe_address_source(regs, 0x1000) == "rax (rax = 0x1000)"
+    assert describe_address_source(regs, 0x7FFF0000) is None and describe_address_source(regs, None) is None
+    # Four or more registers at zero do not point at any one of them.
+    zeros = Registers.from_list(Register(name, 0) for name in ("rax", "rcx", "rsi", "rdi"))
+    assert describe_address_source(zeros, 0x18) is None
+
+
+def test_dwarf_register_numbers_differ_per_architecture():
+    assert dwarf_register_name(Architecture.X86_64, 7) == "rsp"
+    assert dwarf_register_name(Architecture.AARCH64, 7) == "x7"
+    assert dwarf_register_name(Architecture.X86, 7) == "edi"
+    assert dwarf_register_name(Architecture.AARCH64, 99) is None
+    lldb = Registers.from_list([Register("x0", 1), Register("fp", 0x29), Register("lr", 0x30), Register("sp", 2)])
+    assert (lldb.dwarf(29), lldb.dwarf(30), lldb.dwarf(31), lldb.dwarf(5)) == (0x29, 0x30, 2, None)
+
+
+def test_reports_and_prompts_reference_named_registers():
+    ctx = CrashContext(
+        stop=StopInfo(signal="SIGSEGV", fault_address=0x18),
+        frames=[Frame(0, pc=0x401136, function="parse_header", file="parse.c", line=42)],
+        registers=[Register("rip", 0x401136), Register("rsp", 0x7FFC3A10), Register("rbp", 0x7FFC3A30),
+                   Register("rdi", 0), Register("rsi", 0x7FFC3B00)],
+    )
+    report = CrashReport.from_context(ctx)
+    text = report.render()
+    assert "Fault address from: rdi + 0x18 (rdi = 0x0)\n" in text
+    assert "Registers: rip=0x401136 rsp=0x7ffc3a10 rbp=0x7ffc3a30\n" in text
+    assert "Fault address from: rdi + 0x18" in PromptBuilder().build(report).text
+
+    data = report.to_dict()
+    assert data["registers"][0] == {"name": "rip", "value": "0x401136"}
+    assert data["named_registers"]["stack_pointer"] == "0x7ffc3a10"
+    assert CrashReport.from_dict(data).registers == report.registers
+    assert render_report(data) == text
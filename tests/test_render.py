@@ -0,0 +1,75 @@
+import io
+
+import pytest
+
+from dbgcopilot.analysis import CrashContext, Frame, StopInfo
+from dbgcopilot.analysis import api
+from dbgcopilot.analysis.render import render_report, render_terminal, report_sections
+from dbgcopilot.utils.io import ANSI_RE, color_enabled
+
+FRAMES = [
+    Frame(0, pc=0x7FFFF7E2A000, function="__memmove_avx_unaligned_erms", module="libc.so.6"),
+    Frame(1, pc=0x401136, function="copy_name", file="crash.c", line=9),
+    Frame(2, pc=0x401150, function="main", file="crash.c", line=14),
+]
+
+
+class _Tty(io.StringIO):
+    def isatty(self):
+        return True
+
+
+def _data():
+    ctx = CrashContext(stop=StopInfo(signal="SIGSEGV", fault_address=0x10), frames=FRAMES)
+    report = api._report_from_context(ctx, "core")
+    report.explanation = "copy_name() reads through a null name."
+    return report.to_dict()
+
+
+def test_sections_follow_the_report_and_color_only_when_asked():
+    data = _data()
+    sections = report_sections(data)
+    assert [s.title for s in sections] == ["Fault summary", "Faulting frame", "Stack", "AI explanation"]
+    stack = dict((text, style) for text, style in sections[2].lines)
+    assert stack["#1 0x401136 copy_name at crash.c:9"] == "user"
+    assert stack["#0 0x7ffff7e2a000 libc.so.6!__memmove_avx_unaligned_erms"] == "runtime"
+    assert [s.title for s in report_sections(data, with_explanation=False)][-1] == "Stack"
+
+    plain = render_terminal(data)
+    assert not ANSI_RE.search(plain)
+    assert plain.startswith("== Fault summary ==\n  Stop: SIGSEGV")
+    assert "\n\n== Stack ==\n  #0 " in plain
+    colored = render_terminal(data, color=True)
+    assert "\x1b[" in colored and ANSI_RE.sub("", colored) == plain
+    # Every line of the plain report is still there; its own headings become the sections'.
+    body = {line.strip() for line in plain.splitlines()}
+    assert all(line in body for line in render_report(data).splitlines() if line and not line.endswith(":"))
+
+
+def test_color_follows_the_mode_no_color_and_the_terminal():
+    tty = _Tty()
+    assert color_enabled(tty, "auto", {})
+    assert not color_enabled(io.StringIO(), "auto", {})
+    assert not color_enabled(tty, "auto", {"NO_COLOR": "1"})
+    assert color_enabled(tty, "auto", {"NO_COLOR": ""})
+    assert not color_enabled(tty, "auto", {"TERM": "dumb"})
+    assert color_enabled(io.StringIO(), "always", {"NO_COLOR": "1"})
+    assert not color_enabled(tty, "never", {})
+    with pytest.raises(ValueError):
+        color_enabled(tty, "sometimes")
+
+
+def test_cli_prints_the_plain_report_when_piped(tmp_path, monkeypatch, capsys):
+    from dbgcopilot.cli import analyze_main
+
+    (tmp_path / "prog.sh").write_text("#!/bin/sh\nexit 3\n")
+    (tmp_path / "prog.sh").chmod(0o755)
+    monkeypatch.setattr(api, "_detect_backend", lambda _request: (_ for _ in ()).throw(api.AnalysisError("none")))
+    analyze_main([str(tmp_path / "prog.sh")])
+    out = capsys.readouterr().out
+    assert "Exit code: 3" in out and "==" not in out
+    analyze_main(["--color", "always", str(tmp_path / "prog.sh")])
+    out = capsys.readouterr().out
+    assert "\x1b[" in out and "Exit code: 3" in ANSI_RE.sub("", out)
+    analyze_main(["--color", "never", str(tmp_path / "prog.sh")])
+    assert "==" not in capsys.readouterr().out
@@ -0,0 +1,88 @@
+import json
+import os
+
+import pytest
+
+from dbgcopilot.analysis import HangCategory, analyze_hang
+from dbgcopilot.backends import gdb_batch
+from dbgcopilot.backends.gdb_batch import GdbBatchBackend
+from dbgcopilot.backends.replay import RecordingBackend, ReplayBackend, ReplayMissError
+
+LOCK_A, LOCK_B = 0x555555558040, 0x555555558080
+
+
+def _hang_output(pid, src):
+    # examples/hang/c/hang.c as GDB prints it once both workers are stuck.
+    return f"""\
+[New LWP {pid + 1}]
+[New LWP {pid + 2}]
+Attaching to process {pid}
+
+Thread 3 (Thread 0x7ffff7a89640 (LWP {pid + 2}) "hang"):
+#0  futex_wait (private=0, expected=2, futex_word=0x{LOCK_A:x} <lock_a>) at ../sysdeps/nptl/futex-internal.h:146
+#1  __GI___lll_lock_wait (futex=futex@entry=0x{LOCK_A:x} <lock_a>, private=0) at ./nptl/lowlevellock.c:49
+#2  0x00007ffff7e980f1 in ___pthread_mutex_lock (mutex=0x{LOCK_A:x} <lock_a>) at ./nptl/pthread_mutex_lock.c:93
+#3  0x0000555555555298 in worker_two (arg=0x0) at {src}:27
+
+Thread 2 (Thread 0x7ffff7c8a640 (LWP {pid + 1}) "hang"):
+#0  futex_wait (private=0, expected=2, futex_word=0x{LOCK_B:x} <lock_b>) at ../sysdeps/nptl/futex-internal.h:146
+#1  __GI___lll_lock_wait (futex=futex@entry=0x{LOCK_B:x} <lock_b>, private=0) at ./nptl/lowlevellock.c:49
+#2  0x00007ffff7e980f1 in ___pthread_mutex_lock (mutex=0x{LOCK_B:x} <lock_b>) at ./nptl/pthread_mutex_lock.c:93
+#3  0x000055555555520e in worker_one (arg=0x0) at {src}:14
+
+Thread 1 (Thread 0x7ffff7c8b740 (LWP {pid}) "hang"):
+#0  __futex_abstimed_wait_common64 (private=128, cancel=true, abstime=0x0, op=265, expected={pid + 1}, futex_word=0x7ffff7c8a910) at ./nptl/futex-internal.c:57
+#1  0x00007ffff7e91624 in __pthread_clockjoin_ex (threadid=140737350510144, thread_return=0x0, clockid=0, abstime=0x0, block=true) at ./nptl/pthread_join_common.c:105
+#2  0x00005555555552f3 in main () at {src}:43
+"""
+
+
+def test_recorded_hang_replays_without_a_debugger(tmp_path, monkeypatch):
+    monkeypatch.chdir(tmp_path)
+    src = os.path.join(str(tmp_path), "examples", "hang", "c", "hang.c")
+    # __owner sits 8 bytes into the mutex: worker_one holds lock_a, worker_two lock_b.
+    owners = {LOCK_A + 8: 1, LOCK_B + 8: 2}
+    pid = 7001
+
+    def fake_run_batch(argv, timeout):
+        assert argv[argv.index("-p") + 1] == str(pid)
+        cmd = argv[-3]
+        if cmd == "thread apply all bt":
+            preamble, _, threads = _hang_output(pid, src).partition("\n\n")
+            return preamble + "\n@@dbgcopilot:0@@\n" + threads, False
+        addr = int(cmd.split()[1], 16)
+        raw = (pid + owners[addr]).to_bytes(4, "little")
+        return "@@dbgcopilot:0@@\n" + f"0x{addr:x} <lock+8>:" + "".join(f"\t0x{b:02x}" for b in raw) + "\n", False
+
+    path = str(tmp_path / "hang.gdb.json")
+    monkeypatch.setattr(gdb_batch, "run_batch", fake_run_batch)
+    with RecordingBackend(GdbBatchBackend("gdb", timeout=5), path).attach(pid) as session:
+        recorded = analyze_hang(session)
+    assert recorded.category is HangCategory.DEADLOCK
+
+    text = open(path).read()
+    assert str(tmp_path) not in text and "7001" not in text
+    data = json.loads(text)
+    assert data["engine"] == "gdb" and len(data["exchanges"]) == 3
+    assert "worker_one (arg=0x0) at <dbgcopilot:cwd>/examples/hang/c/hang.c:14" in data["exchanges"][0]["outputs"][0]
+
+    # Elsewhere: another pid, another directory, and no debugger to run.
+    def no_debugger(argv, timeout):
+        raise AssertionError("replay must not run a debugger")
+
+    monkeypatch.setattr(gdb_batch, "run_batch", no_debugger)
+    elsewhere = tmp_path / "ci"
+    elsewhere.mkdir()
+    monkeypatch.chdir(elsewhere)
+    backend = ReplayBackend(path)
+    assert backend.name == "gdb"
+    with backend.attach(9001) as session:
+        kind = analyze_hang(session)
+        (thread,) = [t for t in session.threads() if t.thread_id == 2]
+        with pytest.raises(ReplayMissError, match="info registers"):
+            session.read_registers()
+    assert kind.category is HangCategory.DEADLOCK and kind.waiters == [2, 3]
+    (report,) = kind.deadlocks
+    # Only the main thread's LWP is the pid; the workers keep their recorded ids.
+    assert [(t.tid, t.waits_for.symbol) for t in report.threads] == [(7002, "lock_b"), (7003, "lock_a")]
+    assert thread.frames[-1].file == os.path.join(str(elsewhere), "examples", "hang", "c", "hang.c")
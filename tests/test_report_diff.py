@@ -0,0 +1,118 @@
+import json
+
+from dbgcopilot.analysis import CrashContext, DiffVerdict, Frame, Module, StopInfo, api, diff_reports
+from dbgcopilot.analysis.api import _report_from_context
+from dbgcopilot.analysis.diff import DIFF_INSTRUCTIONS, StackChange, address_bucket, build_diff_prompt
+from dbgcopilot.cli import ExitCode
+from dbgcopilot.llm import providers
+from dbgcopilot.repl.standalone import main
+
+
+def _crash(*functions, base=0x555555554000, fault_address=0x8, signal="SIGSEGV"):
+    # The same binary loaded at ``base``: pcs move with ASLR, offsets into functions with rebuilds.
+    frames = [Frame(i, pc=base + 0x1100 + i * 0x40, function=f, offset=0x10 + i, file="src/parse.c", line=10 + i)
+              for i, f in enumerate(functions)]
+    stop = StopInfo(signal=signal, fault_address=fault_address)
+    return _report_from_context(CrashContext(stop=stop, frames=frames, modules=[Module("parse", base, 0x5000)]), "gdb")
+
+
+def test_same_crash_after_a_rebuild_and_aslr():
+    before = _crash("read_field", "parse_record", "main")
+    after = _crash("read_field", "parse_record", "main", base=0x7F0000000000, fault_address=0x10)
+    after.frames[0].offset = 0x24
+    diff = diff_reports(before, after)
+    assert (diff.verdict, diff.stack_change, diff.changes) == (DiffVerdict.SAME, StackChange.NONE, [])
+    assert diff.before_frames == ["parse!read_field", "parse!parse_record", "parse!main"]
+
+
+def test_a_moved_leaf_and_a_different_stack_are_told_apart():
+    before = _crash("read_field", "parse_record", "main")
+    leaf = diff_reports(before, _crash("read_header", "parse_record", "main"))
+    assert (leaf.verdict, leaf.stack_change) == (DiffVerdict.DIFFERENT_LOCATION, StackChange.LEAF)
+    assert leaf.changes == [
+        "only the faulting frame moved: parse!read_field -> parse!read_header; its callers are the same"
+    ]
+
+    other = diff_reports(before, _crash("free_list", "shutdown", "atexit_run", fault_address=0xDEAD0000, signal="SIGABRT"))
+    assert other.stack_change is StackChange.STACK
+    assert other.changes == [
+        "fault kind: null-pointer dereference -> explicit abort (panic, assertion, or abort())",
+        "fault address: null -> user",
+        "the stack is entirely different",
+    ]
+    assert diff_reports(before, _crash("read_field", "load", "main")).stack_change is StackChange.CALLERS
+    assert address_bucket(0xFFFF800000001000) == "kernel" and address_bucket(None) is None
+
+
+def test_cli_diffs_saved_reports(tmp_path, capsys):
+    before, after = tmp_path / "before.json", tmp_path / "after.json"
+    before.write_text(_crash("read_field", "parse_record", "main").to_json())
+    after.write_text(_report_from_context(CrashContext(stop=StopInfo(description="exited with code 0", exit_code=0)), "run").to_json())
+    assert main(["diff", str(before), str(after)]) == 0
+    assert capsys.readouterr().out.startswith("Verdict: fixed (the second run did not fail)\n")
+
+    after.write_text(_crash("read_header", "parse_record", "main").to_json())
+    assert main(["diff", "--format", "json", str(before), str(after)]) == 0
+    data = json.loads(capsys.readouterr().out)
+    assert (data["verdict"], data["stack_change"]) == ("different_location", "leaf")
+
+    (tmp_path / "bad.json").write_text("{}")
+    assert main(["diff", str(before), str(tmp_path / "bad.json")]) == ExitCode.CAPTURE_FAILED
+    assert "cannot read report" in capsys.readouterr().err
+
+
+def _with_modules(report, *libraries):
+    report.modules += [Module(path.rsplit("/", 1)[1], 0x7F0000000000 + i * 0x100000, 0x10000, path=path)
+                       for i, path in enumerate(libraries)]
+    return report
+
+
+def test_module_changes_divergent_frames_and_the_diff_prompt():
+    before = _with_modules(_crash("read_field", "parse_record", "main"),
+                           "/usr/lib/libssl.so.1.1", "/usr/lib/libz.so.1", "/home/alice/lib/libplug.so")
+    # Built at -O2, parse_record is inlined into main.
+    after = _with_modules(_crash("read_field", "main"), "/usr/lib/libssl.so.3", "/opt/libfoo.so.2",
+                          "/home/alice/lib/libplug.so")
+    diff = diff_reports(before, after)
+    assert diff.module_changes == [
+        "version: libssl.so 1.1 -> 3", "new: libfoo.so 2 (/opt/libfoo.so.2)", "gone: libz.so"
+    ]
+    assert diff.divergent_frame == 1
+    text = diff.describe()
+    assert "First differing frame: #1\nModules:\n- version: libssl.so 1.1 -> 3\n" in text
+    assert diff.to_dict()["divergent_frame"] == 1
+
+    prompt = build_diff_prompt(diff, before, after)
+    assert prompt.system == DIFF_INSTRUCTIONS
+    assert "The stacks first differ at frame #1.\nModule changes:\n- version: libssl.so 1.1 -> 3" in prompt.text
+    assert "Earlier run:\nStop: SIGSEGV\n" in prompt.text and "Later run:\nStop: SIGSEGV\n" in prompt.text
+    assert "#1 0x555555555140 parse!main at src/parse.c:11" in prompt.text
+    assert "Registers" not in prompt.text and "libplug" not in prompt.text
+
+
+def test_cli_diffs_two_dumps_and_explains_the_differences(tmp_path, monkeypatch, capsys):
+    monkeypatch.chdir(tmp_path)
+    before, after = tmp_path / "good.core", tmp_path / "bad.core"
+    before.write_bytes(b"\x7fELF")
+    after.write_bytes(b"\x7fELF")
+    reports = {
+        str(before): _with_modules(_crash("read_field", "parse_record", "main"), "/usr/lib/libssl.so.1.1"),
+        str(after): _with_modules(_crash("read_header", "parse_record", "main"), "/usr/lib/libssl.so.3"),
+    }
+    monkeypatch.setenv("DBGCOPILOT_SYMBOLS_PATH", str(tmp_path / "symbols"))
+    monkeypatch.setenv("DBGCOPILOT_ANALYSIS_MAX_FRAMES_PER_THREAD", "12")
+    requests = []
+    monkeypatch.setattr(api, "_analyze_core", lambda request: requests.append(request) or reports[request.core])
+    prompts = []
+
+    def fake_client(name, config):
+        return lambda prompt: prompts.append(prompt) or "The libssl upgrade changed the header layout."
+
+    monkeypatch.setattr(providers, "create_client", fake_client)
+    assert main(["diff", str(before), str(after), "--provider", "mock-local"]) == 0
+    out = capsys.readouterr().out
+    assert "- version: libssl.so 1.1 -> 3\n" in out
+    assert out.endswith("Explanation:\nThe libssl upgrade changed the header layout.\n")
+    assert len(prompts) == 1 and "Module changes:" in prompts[0]
+    # Both dumps are captured as `analyze` would, with the configured symbol path and frame limit.
+    assert [(r.symbol_path, r.max_frames_per_thread) for r in requests] == [(str(tmp_path / "symbols"), 12)] * 2
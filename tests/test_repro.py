@@ -0,0 +1,67 @@
+import os
+import shutil
+import signal
+import subprocess
+
+import pytest
+
+from dbgcopilot.analysis import CrashReport, FaultKind, Frame, Language, build_repro
+from dbgcopilot.analysis import api
+from dbgcopilot.analysis.api import AnalysisError
+from dbgcopilot.analysis.repro import DEFAULT_FUNCTION, repro_function
+from dbgcopilot.cli import analyze_main
+
+
+def _report(kind, language, *functions):
+    frames = [Frame(i, function=f, file="src/main.rs", line=9) for i, f in enumerate(functions)]
+    return CrashReport(fault_kind=kind, language=language, frames=frames)
+
+
+def test_rust_null_deref_reuses_the_crashing_function(tmp_path):
+    repro = build_repro(_report(FaultKind.NULL_DEREF, Language.RUST, "rust_crash::crash", "rust_crash::main"))
+    assert (repro.language, repro.function, repro.command) == (Language.RUST, "crash", "cargo run")
+    paths = repro.write(str(tmp_path / "repro"))
+    assert sorted(os.path.relpath(p, tmp_path / "repro") for p in paths) == ["Cargo.toml", os.path.join("src", "main.rs")]
+    source = (tmp_path / "repro" / "src" / "main.rs").read_text()
+    assert "fn crash() {" in source and "crash();" in source and "*ptr = 42" in source
+    assert "null-pointer dereference in rust_crash::crash (src/main.rs:9)" in source
+    assert 'panic = "abort"' in (tmp_path / "repro" / "Cargo.toml").read_text()
+
+
+def test_function_names_skip_runtime_frames_and_unusable_names():
+    assert repro_function([Frame(0, function="__GI_raise"), Frame(1, function="core::panicking::panic_const_div_by_zero"),
+                           Frame(2, function="Parser::checked_div(int)")]) == "checked_div"
+    assert repro_function([Frame(0, function="main"), Frame(1, pc=0x1000)]) == DEFAULT_FUNCTION
+    assert build_repro(_report(FaultKind.HEAP_CORRUPTION, Language.C, "free_twice")) is None
+
+
+def test_c_divide_by_zero_builds_and_faults(tmp_path):
+    if shutil.which("cc") is None:
+        pytest.skip("no C compiler")
+    repro = build_repro(_report(FaultKind.DIVIDE_BY_ZERO, Language.C, "average"))
+    repro.write(str(tmp_path))
+    subprocess.run(["cc", "-g", "-O0", "-Wall", "-Wextra", "-Werror", "-o", "repro", "repro.c"], cwd=tmp_path, check=True)
+    result = subprocess.run(["./repro"], cwd=tmp_path, capture_output=True, text=True)
+    assert result.returncode == -signal.SIGFPE
+    assert result.stdout == "calling average...\n"
+
+
+def test_cli_emits_the_repro(tmp_path, monkeypatch, capsys):
+    def no_debugger(_request):
+        raise AnalysisError("no debugger in this test")
+
+    monkeypatch.setattr(api, "_detect_backend", no_debugger)
+    script = tmp_path / "prog.sh"
+    script.write_text("#!/bin/sh\nkill -FPE $$\n")
+    os.chmod(script, 0o755)
+    out = tmp_path / "out"
+    assert analyze_main(["--no-llm", "--no-cache", "--format", "json", "--emit-repro", str(out), str(script)]) == 0
+    captured = capsys.readouterr()
+    assert sorted(os.listdir(out)) == ["Makefile", "repro.c"]
+    assert f"written to {out} (run: cd {out} && make run)" in captured.err
+    assert captured.out.lstrip().startswith("{")
+
+    script.write_text("#!/bin/sh\nexec sleep 5\n")
+    assert analyze_main(["--no-llm", "--timeout", "0.2", "--emit-repro", str(tmp_path / "none"), str(script)]) == 0
+    assert "no reproduction template for hang" in capsys.readouterr().err
+    assert not (tmp_path / "none").exists()
@@ -0,0 +1,181 @@
+import json
+import os
+import subprocess
+import sys
+import threading
+
+from dbgcopilot import serve
+from dbgcopilot.analysis import AnalyzeRequest, CrashContext, Frame, StopInfo, api
+from dbgcopilot.llm import providers
+from dbgcopilot.serve import Server, read_message
+
+SRC = os.path.join(os.path.dirname(os.path.dirname(os.path.abspath(__file__))), "src")
+
+
+class _Client:
+    """The editor's end of two pipes to a `Server` running in a thread."""
+
+    def __init__(self, request):
+        to_server, self._requests = os.pipe()
+        self._responses, from_server = os.pipe()
+        self.writer, self.reader = os.fdopen(self._requests, "wb"), os.fdopen(self._responses, "rb")
+        server_in, server_out = os.fdopen(to_server, "rb"), os.fdopen(from_server, "wb")
+        self.status = []
+
+        def run():
+            with server_in, server_out:
+                self.status.append(Server(request, server_out).serve(server_in))
+
+        self.thread = threading.Thread(target=run, daemon=True)
+        self.thread.start()
+        self.next_id = 0
+
+    def send(self, message):
+        serve.write_message(self.writer, message)
+
+    def call(self, method, params=None):
+        """The notifications the request produced, then its response."""
+        self.next_id += 1
+        self.send({"jsonrpc": "2.0", "id": self.next_id, "method": method, "params": params or {}})
+        notes = []
+        while True:
+            message = read_message(self.reader)
+            if "id" in message:
+                assert message["id"] == self.next_id
+                return notes, message
+            notes.append(message)
+
+
+def _crash(request):
+    return api._report_from_context(
+        CrashContext(
+            stop=StopInfo(signal="SIGSEGV", fault_address=0),
+            frames=[Frame(0, pc=0x5555555591A0, function="rust_crash::crash", file="src/main.rs", line=9)],
+        ),
+        "core",
+    )
+
+
+def test_server_analyzes_streams_and_answers_follow_ups_over_a_pipe(tmp_path, monkeypatch):
+    prompts = []
+    answers = [("crash() writes ", "through a null pointer."), ("ptr is null because ", "crash() never allocates it.")]
+
+    def fake_client(name, config):
+        def ask(prompt):
+            return stream(prompt, lambda chunk: None)
+
+        def stream(prompt, on_chunk):
+            prompts.append(prompt)
+            chunks = answers.pop(0)
+            for chunk in chunks:
+                on_chunk(chunk)
+            return "".join(chunks)
+
+        ask.stream = stream
+        return ask
+
+    monkeypatch.setattr(api, "_analyze_core", _crash)
+    monkeypatch.setattr(providers, "create_client", fake_client)
+    (tmp_path / "core").write_text("")
+    client = _Client(AnalyzeRequest(binary="", provider="mock-local"))
+
+    _, hello = client.call("initialize")
+    assert hello["result"]["protocolVersion"] == serve.PROTOCOL_VERSION
+    assert {"analyzeDump", "analyzePid", "askFollowup", "shutdown"} <= set(hello["result"]["methods"])
+
+    # The explanation streams as notifications; the response has the whole report.
+    notes, analyzed = client.call("analyzeDump", {"path": str(tmp_path / "core")})
+    assert [n["method"] for n in notes] == ["explanationChunk", "explanationChunk"]
+    assert [n["params"] for n in notes] == [
+        {"sessionId": 1, "text": "crash() writes "}, {"sessionId": 1, "text": "through a null pointer."}
+    ]
+    result = analyzed["result"]
+    assert result["sessionId"] == 1 and result["report"]["fault_kind"] == "null_deref"
+    assert result["report"]["explanation"] == "crash() writes through a null pointer."
+    assert result["report"]["frames"][0]["symbol"] == "rust_crash::crash"
+
+    notes, answer = client.call("askFollowup", {"sessionId": 1, "question": "why is ptr null?"})
+    assert answer["result"] == {"sessionId": 1, "answer": "ptr is null because crash() never allocates it."}
+    assert [n["method"] for n in notes] == ["followupChunk", "followupChunk"]
+    assert "why is ptr null?" in prompts[-1] and "rust_crash::crash" in prompts[-1]
+
+    # Without a model, the report still comes back, with the prompt that would have been sent.
+    _, offline = client.call("analyzeDump", {"path": str(tmp_path / "core"), "noLlm": True})
+    assert offline["result"]["sessionId"] == 2 and offline["result"]["report"]["explanation"] is None
+    assert "rust_crash::crash" in offline["result"]["report"]["prompt"]
+    _, refused = client.call("askFollowup", {"sessionId": 2, "question": "and now?"})
+    assert refused["error"]["code"] == serve.LLM_FAILED
+
+    client.send({"jsonrpc": "2.0", "method": "exit"})
+    client.thread.join(5)
+    assert client.status == [1]
+
+
+def test_server_reports_protocol_errors_and_keeps_serving(tmp_path, monkeypatch):
+    monkeypatch.setattr(api, "_analyze_core", _crash)
+    client = _Client(AnalyzeRequest(binary="", no_llm=True))
+
+    assert client.call("explainEverything")[1]["error"]["code"] == serve.METHOD_NOT_FOUND
+    assert client.call("analyzeDump", {})[1]["error"] == {
+        "code": serve.INVALID_PARAMS, "message": "missing param 'path'"
+    }
+    assert client.call("analyzePid", {"pid": True})[1]["error"]["code"] == serve.INVALID_PARAMS
+    missing = client.call("analyzeDump", {"path": str(tmp_path / "gone")})[1]["error"]
+    assert missing["code"] == serve.ANALYSIS_FAILED and "core file not found" in missing["message"]
+    assert client.call("askFollowup", {"sessionId": 7, "question": "?"})[1]["error"]["code"] == serve.UNKNOWN_SESSION
+
+    # A body that is not JSON is answered with a null id, and the next request still works.
+    client.writer.write(b"Content-Length: 9\r\n\r\n{not json")
+    client.writer.flush()
+    error = read_message(client.reader)
+    assert error["id"] is None and error["error"]["code"] == serve.PARSE_ERROR
+    assert client.call("initialize")[1]["result"]["name"] == "dbgcopilot"
+    # So are headers with no length to read, rather than waited on until the end of input.
+    for headers in (b"Content-Length: -1\r\n\r\n", b"Content-Type: application/json\r\n\r\n"):
+        client.writer.write(headers)
+        client.writer.flush()
+        assert read_message(client.reader)["error"]["code"] == serve.PARSE_ERROR
+    assert client.call("initialize")[1]["result"]["name"] == "dbgcopilot"
+
+    # Sessions close on request, and the oldest when there are too many.
+    monkeypatch.setattr(serve, "MAX_SESSIONS", 2)
+    (tmp_path / "core").write_bytes(b"")
+    opened = [client.call("analyzeDump", {"path": str(tmp_path / "core")})[1]["result"]["sessionId"] for _ in "abc"]
+    assert client.call("askFollowup", {"sessionId": opened[0], "question": "?"})[1]["error"]["code"] == (
+        serve.UNKNOWN_SESSION
+    )
+    assert client.call("closeSession", {"sessionId": opened[1]})[1]["result"] is None
+    assert client.call("closeSession", {"sessionId": opened[1]})[1]["error"]["code"] == serve.UNKNOWN_SESSION
+
+    assert client.call("shutdown")[1]["result"] is None
+    assert client.call("initialize")[1]["error"]["code"] == serve.INVALID_REQUEST
+    client.send({"jsonrpc": "2.0", "method": "exit"})
+    client.thread.join(5)
+    assert client.status == [0]
+
+
+def test_dbgcopilot_serve_speaks_the_protocol_on_stdio(tmp_path):
+    messages = [
+        {"jsonrpc": "2.0", "id": 1, "method": "initialize"},
+        {"jsonrpc": "2.0", "id": 2, "method": "analyzeDump", "params": {"path": str(tmp_path / "missing.dmp")}},
+        {"jsonrpc": "2.0", "id": 3, "method": "shutdown"},
+        {"jsonrpc": "2.0", "method": "exit"},
+    ]
+    stdin = b"".join(b"Content-Length: %d\r\n\r\n%s" % (len(body), body) for body in (
+        json.dumps(m).encode() for m in messages
+    ))
+    env = dict(os.environ, PYTHONPATH=SRC, HOME=str(tmp_path), XDG_CONFIG_HOME=str(tmp_path))
+    proc = subprocess.run(
+        [sys.executable, "-c", "import sys; from dbgcopilot.repl.standalone import main; sys.exit(main())",
+         "serve", "--no-llm", "--no-history", "--no-cache"],
+        input=stdin, capture_output=True, env=env, cwd=str(tmp_path), timeout=60,
+    )
+    assert proc.returncode == 0, proc.stderr
+    out = open(tmp_path / "out", "w+b")
+    out.write(proc.stdout)
+    out.seek(0)
+    replies = [read_message(out) for _ in range(3)]
+    assert read_message(out) is None
+    assert [r["id"] for r in replies] == [1, 2, 3]
+    assert replies[0]["result"]["version"] and replies[2]["result"] is None
+    assert replies[1]["error"]["code"] == serve.ANALYSIS_FAILED
@@ -0,0 +1,142 @@
+import os
+import signal
+
+import pytest
+
+from dbgcopilot import cli
+from dbgcopilot.analysis import AnalyzeRequest, CrashReport, ExceptionPolicy, analyze, render_report
+from dbgcopilot.analysis import api
+from dbgcopilot.backends import gdb_batch, lldb_batch, signal_passthrough
+from dbgcopilot.backends.gdb_batch import GdbBatchBackend
+from dbgcopilot.backends.lldb_batch import LldbBatchBackend
+from dbgcopilot.backends.signal_passthrough import caught_signals, handler_installed, signal_names
+
+# A JIT that takes two SIGSEGVs on its guard page and handles them, then faults for real.
+GDB_OUTPUT = """\
+Program received signal SIGSEGV, Segmentation fault.
+0x00007ffff7fc1000 in ?? ()
+dbgcopilot: passed SIGSEGV to the program's handler
+
+Program received signal SIGSEGV, Segmentation fault.
+0x00007ffff7fc1040 in ?? ()
+dbgcopilot: passed SIGSEGV to the program's handler
+
+Program received signal SIGSEGV, Segmentation fault.
+0x0000000000401136 in run_compiled (code=0x0) at jit.c:42
+42	    return code->entry();
+@@dbgcopilot:siginfo@@
+$1 = (void *) 0x8
+@@dbgcopilot:bt@@
+#0  0x0000000000401136 in run_compiled (code=0x0) at jit.c:42
+#1  0x0000000000401180 in main () at jit.c:60
+"""
+
+
+def test_signal_names_and_the_handler_mask():
+    assert signal_names("segv, SIGPIPE,11") == ["SIGSEGV", "SIGPIPE"]
+    assert signal_names(["usr1", 2]) == ["SIGUSR1", "SIGINT"]
+    with pytest.raises(ValueError):
+        signal_names(["SIGNOPE"])
+
+    # Bit n-1 of SigCgt is signal n: 0x4402 is SIGINT, SIGSEGV, and SIGTERM.
+    assert caught_signals("Name:\tjit\nSigCgt:\t0000000000004402\n") == {2, 11, 15}
+    assert caught_signals("Name:\tjit\n") is None
+
+    previous = signal.signal(signal.SIGUSR2, signal.SIG_DFL)
+    try:
+        assert handler_installed(os.getpid(), signal.SIGUSR2) is False
+        signal.signal(signal.SIGUSR2, lambda *_: None)
+        assert handler_installed(os.getpid(), signal.SIGUSR2) is True
+    finally:
+        signal.signal(signal.SIGUSR2, previous)
+    assert handler_installed(-1, signal.SIGUSR2) is None
+
+
+def test_gdb_passes_handled_signals_and_reports_the_fatal_one(tmp_path, monkeypatch):
+    argvs = []
+
+    def fake_run_batch(argv, timeout):
+        argvs.append(argv)
+        return GDB_OUTPUT, False
+
+    monkeypatch.setattr(gdb_batch, "run_batch", fake_run_batch)
+    monkeypatch.setattr(api, "_detect_backend", lambda _request: GdbBatchBackend(timeout=5))
+    script = tmp_path / "jit.sh"
+    script.write_text("#!/bin/sh\nkill -SEGV $$\n")
+    script.chmod(0o755)
+    report = analyze(AnalyzeRequest(binary=str(script), ignore_signals=["segv", "pipe"]))
+
+    argv = argvs[0]
+    assert "run" not in argv
+    # The listed signals, then the other faults a second-chance capture lets the program handle.
+    expected = "dbgcopilot-run SIGSEGV SIGPIPE SIGBUS SIGFPE SIGILL SIGABRT"
+    assert argv[argv.index(f"source {signal_passthrough.SCRIPT_PATH}") + 2] == expected
+    assert report.stop.signal == "SIGSEGV" and report.stop.fault_address == 0x8
+    assert report.stop.handled_signals == ["SIGSEGV", "SIGSEGV"]
+    assert report.frames[0].function == "run_compiled"
+
+    text = report.render()
+    assert "Handled by the program first: SIGSEGV x2\n" in text
+    data = report.to_dict()
+    assert data["handled_signals"] == ["SIGSEGV", "SIGSEGV"]
+    assert CrashReport.from_dict(data).handled_signals == ["SIGSEGV", "SIGSEGV"]
+    assert render_report(data) == text
+
+    # Without the list, the plain `run` stops at the first fault as before.
+    backend = GdbBatchBackend(timeout=5)
+    backend.load("jit")
+    assert "run" in backend._build_argv()
+
+
+def test_exception_policy_picks_the_chance_a_launch_captures_on(tmp_path, monkeypatch):
+    passed = lambda **kw: api._passed_signals(AnalyzeRequest(binary=str(tmp_path / "crash"), **kw))  # noqa: E731
+    # Second chance by default: a fault the program handles lets the run go on; one it does not is analyzed.
+    assert passed() == ["SIGSEGV", "SIGBUS", "SIGFPE", "SIGILL", "SIGABRT"]
+    assert passed(ignore_signals=["pipe", "segv"]) == ["SIGPIPE", "SIGSEGV", "SIGBUS", "SIGFPE", "SIGILL", "SIGABRT"]
+    # First chance stops at every fault, and still passes the signals asked for.
+    assert passed(exception_policy=ExceptionPolicy.FIRST_CHANCE) == []
+    assert passed(exception_policy="first-chance", ignore_signals=["usr1"]) == ["SIGUSR1"]
+    # Nothing is launched for a core.
+    assert passed(core=str(tmp_path / "core")) == []
+    # Go's runtime handles every fault and exits, so there is no second chance to wait for.
+    monkeypatch.setattr(api, "detect_language", lambda path: api.Language.GO)
+    assert passed() == []
+
+    monkeypatch.chdir(tmp_path)
+    with pytest.raises(SystemExit):
+        cli.analyze_main(["--exception-policy", "third-chance", "--no-llm", "prog"])
+
+
+def test_lldb_launches_through_the_passthrough_command(monkeypatch):
+    output = """\
+(lldb) target create jit
+Current executable set to 'jit' (x86_64).
+(lldb) command script import {script}
+(lldb) dbgcopilot-run SIGSEGV -- --warm
+Process 4242 stopped
+* thread #1, name = 'jit', stop reason = signal SIGSEGV: invalid address (fault address: 0x7ffff7fc1000)
+dbgcopilot: passed SIGSEGV to the program's handler
+Process 4242 stopped
+* thread #1, name = 'jit', stop reason = signal SIGSEGV: invalid address (fault address: 0x8)
+(lldb) thread info
+thread #1: tid = 4242, 0x0000000000401136 jit`run_compiled at jit.c:42:12, stop reason = signal SIGSEGV: invalid address (fault address: 0x8)
+(lldb) thread backtrace
+(lldb) bt all
+(lldb) register read
+(lldb) image list
+""".format(script=signal_passthrough.SCRIPT_PATH)
+    argvs = []
+    monkeypatch.setattr(lldb_batch, "run_batch", lambda argv, timeout: argvs.append(argv) or (output, False))
+    backend = LldbBatchBackend(timeout=5, locals_frames=0)
+    backend.ignore_signals = ["SIGSEGV"]
+    backend.load("jit", args=["--warm"])
+    stop = backend.run_to_fault()
+    assert "dbgcopilot-run SIGSEGV -- --warm" in argvs[0] and "process launch" not in argvs[0]
+    assert (stop.signal, stop.fault_address, stop.handled_signals) == ("SIGSEGV", 0x8, ["SIGSEGV"])
+
+
+def test_cli_rejects_an_unknown_signal(tmp_path, monkeypatch, capsys):
+    monkeypatch.chdir(tmp_path)
+    with pytest.raises(SystemExit):
+        cli.analyze_main(["--ignore-signal", "SIGNOPE", "--no-llm", "prog"])
+    assert "unknown signal 'SIGNOPE'" in capsys.readouterr().err
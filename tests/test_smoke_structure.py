@@ -10,3 +10,9 @@ def test_structure_exists():
     assert importlib.import_module("dbgcopilot.backends.lldb_rust_api")
     assert importlib.import_module("dbgcopilot.backends.java_jdb")
     assert importlib.import_module("dbgcopilot.backends.rust_gdb")
+    assert importlib.import_module("dbgcopilot.backends.gdb_batch")
+    assert importlib.import_module("dbgcopilot.backends.lldb_batch")
+    assert importlib.import_module("dbgcopilot.backends.cdb_batch")
+    assert importlib.import_module("dbgcopilot.dumps.coredump")
+    assert importlib.import_module("dbgcopilot.dumps.minidump")
+    assert importlib.import_module("dbgcopilot.symbols")
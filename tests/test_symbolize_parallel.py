@@ -0,0 +1,106 @@
+import threading
+import time
+
+import pytest
+
+from dbgcopilot.analysis import CrashContext, Frame, Module, StopInfo, api
+from dbgcopilot.symbols import DwarfError, ModuleFrames, Symbolizer, parse_debug_line, resolve, symbolize_modules
+from dbgcopilot.utils.elf import ElfFile
+
+# 48 mapped libraries, as in a core of a large service; the threads' stacks run through 44 of them.
+MODULES = [Module(f"lib{i}.so", 0x7F0000000000 + i * 0x100000, 0x100000, f"/usr/lib/lib{i}.so") for i in range(48)]
+FRAMES = [Frame(i, pc=MODULES[i].base + 0x40) for i in range(44)]
+
+
+class _Image:
+    def load_base(self):
+        return 0
+
+
+class _SlowSymbolizer:
+    """Finding and reading a debug file: time spent waiting, not computing."""
+
+    opened = []
+    delays = {}
+
+    def __init__(self, path):
+        self.path, self.elf = path, _Image()
+
+    @classmethod
+    def open(cls, path, debug_file=None, debug_dirs=(), symbol_path=None):
+        cls.opened.append(path)
+        time.sleep(cls.delays.get(path, 0.02))
+        return cls(path)
+
+    def load_lines(self, deadline=None):
+        return None
+
+    def symbolize(self, frame, bias=0):
+        frame.function, frame.file, frame.line = f"{self.path[9:-3]}_fn", f"{self.path[9:-3]}.c", 7
+        return frame
+
+
+def _slow_symbolizer(monkeypatch):
+    monkeypatch.setattr(resolve, "Symbolizer", _SlowSymbolizer)
+    _SlowSymbolizer.opened, _SlowSymbolizer.delays = [], {}
+    return _SlowSymbolizer
+
+
+def _resolve(workers, timeout=10.0):
+    frames = [Frame(f.index, pc=f.pc) for f in FRAMES]
+    resolver = resolve.SourceResolver(MODULES, workers=workers, module_timeout=timeout)
+    start = time.monotonic()
+    resolver.resolve_frames(frames)
+    return frames, resolver, time.monotonic() - start
+
+
+def test_only_images_with_frames_are_opened_and_in_parallel(monkeypatch):
+    slow_symbolizer = _slow_symbolizer(monkeypatch)
+    frames, resolver, serial = _resolve(workers=1)
+    assert sorted(slow_symbolizer.opened) == sorted(f"/usr/lib/lib{i}.so" for i in range(44))
+    assert [(f.function, f.file) for f in frames[:2]] == [("lib0_fn", "lib0.c"), ("lib1_fn", "lib1.c")]
+    parallel_frames, resolver, parallel = _resolve(workers=8)
+    assert [f.function for f in parallel_frames] == [f.function for f in frames] and not resolver.warnings
+    # 44 images of 20 ms: about 0.9 s one after another, six rounds of 8 workers in parallel.
+    assert serial > 0.85 and parallel < serial / 3
+
+
+def test_an_image_over_its_time_keeps_raw_addresses_and_warns(monkeypatch):
+    slow_symbolizer = _slow_symbolizer(monkeypatch)
+    slow_symbolizer.delays["/usr/lib/lib8.so"] = 1.0
+    frames, resolver, elapsed = _resolve(workers=8, timeout=0.3)
+    assert elapsed < 0.9
+    assert frames[8].function is None and frames[8].file is None and frames[8].pc == FRAMES[8].pc
+    assert all(f.function is not None for f in frames if f is not frames[8])
+    assert resolver.warnings == ["symbolizing lib8.so took over 0.3s: its 1 frames keep raw addresses"]
+
+    # A lone image, or one worker, is held to the limit too, and the stuck thread does not block exit.
+    late = ModuleFrames("huge.so", load=lambda: _SlowSymbolizer.open("/usr/lib/huge.so"), bias=lambda s: 0,
+                        frames=[Frame(0, pc=0x1000)])
+    slow_symbolizer.delays["/usr/lib/huge.so"] = 1.0
+    start = time.monotonic()
+    warnings = symbolize_modules([late], workers=1, timeout=0.1)
+    assert time.monotonic() - start < 0.5
+    assert late.timed_out and late.frames[0].function is None
+    assert all(t.daemon for t in threading.enumerate() if t.name == "symbolize")
+    report = api._report_from_context(CrashContext(stop=StopInfo(signal="SIGSEGV"), frames=late.frames), "core")
+    report.warnings = warnings
+    assert "Warning: symbolizing huge.so took over 0.1s: its 1 frames keep raw addresses" in report.render()
+    assert report.to_dict()["warnings"] == warnings
+
+
+def test_line_tables_stop_decoding_at_the_deadline():
+    # Any unit will do: the deadline is checked before each one.
+    with pytest.raises(DwarfError, match="ran out of time"):
+        parse_debug_line(b"\x00" * 16, deadline=time.monotonic() - 1)
+
+    class _Lines(ElfFile):
+        def __init__(self):
+            pass
+
+        def section_data(self, name):
+            return b"\x00" * 16 if name == ".debug_line" else None
+
+    symbolizer = Symbolizer(_Lines())
+    assert len(symbolizer.load_lines(deadline=time.monotonic() - 1)) == 0
+    assert "ran out of time" in symbolizer.line_error
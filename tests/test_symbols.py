@@ -0,0 +1,226 @@
+import json
+import shutil
+import struct
+import subprocess
+import zlib
+
+import pytest
+
+from dbgcopilot import cli
+from dbgcopilot.analysis.model import Frame
+from dbgcopilot.symbols import Symbolization, Symbolizer, check_debug_info, demangle, find_debug_file, parse_debug_line
+from dbgcopilot.utils.elf import SHF_COMPRESSED, ElfFile, Section
+
+# min_inst=1, default_is_stmt=1, line_base=-5, line_range=14, opcode_base=13
+_OPCODE_LENGTHS = bytes([0, 1, 1, 1, 1, 0, 0, 0, 1, 0, 0, 1])
+
+
+def _unit(version: int, tables: bytes, program: bytes) -> bytes:
+    fields = bytes([1]) + (bytes([1]) if version >= 4 else b"") + bytes([1, 0xFB, 14, 13]) + _OPCODE_LENGTHS
+    header = fields + tables
+    prefix = struct.pack("<H", version) + (bytes([8, 0]) if version >= 5 else b"")
+    body = prefix + struct.pack("<I", len(header)) + header + program
+    return struct.pack("<I", len(body)) + body
+
+
+def _program(file_op: bytes = b"") -> bytes:
+    return (
+        b"\x00\x09\x02" + struct.pack("<Q", 0x1000)  # DW_LNE_set_address 0x1000
+        + file_op
+        + b"\x03\x04"  # advance_line +4 -> 5
+        + b"\x05\x09"  # set_column 9
+        + b"\x01"  # copy
+        + bytes([13 + (1 + 5) + 14 * 4])  # special: address +4, line +1
+        + b"\x02\x08"  # advance_pc 8 -> 0x100c
+        + b"\x00\x01\x01"  # DW_LNE_end_sequence
+    )
+
+
+def test_debug_line_v5_rows_and_ranges():
+    line_str = b"/work/app\x00src/main.c\x00"
+    tables = (
+        b"\x01\x01\x1f" + b"\x01" + struct.pack("<I", 0)  # dirs: path as line_strp; [/work/app]
+        + b"\x02\x01\x1f\x02\x0b" + b"\x01" + struct.pack("<I", 10) + b"\x00"  # files: [src/main.c in dir 0]
+    )
+    table = parse_debug_line(_unit(5, tables, _program(file_op=b"\x04\x00")), line_str=line_str)
+    row = table.lookup(0x1003)
+    assert (row.file, row.line, row.column) == ("/work/app/src/main.c", 5, 9)
+    assert table.lookup(0x1004).line == 6
+    assert table.lookup(0x100B).line == 6
+    assert table.lookup(0x100C) is None and table.lookup(0xFFF) is None
+
+
+def _elf_with(sections: list, blobs: list) -> ElfFile:
+    data = b""
+    placed = []
+    for sec, blob in zip(sections, blobs):
+        sec.sh_offset, sec.sh_size = len(data), len(blob)
+        placed.append(sec)
+        data += blob
+    return ElfFile(data=data, e_type=3, machine=62, entry=0, sections=placed)
+
+
+def test_symbolizer_fills_frames_from_compressed_v4_line_table():
+    tables = b"src\x00\x00" + b"main.rs\x00\x01\x00\x00" + b"\x00"
+    line = _unit(4, tables, _program())
+    zline = struct.pack("<IIQQ", 1, 0, len(line), 1) + zlib.compress(line)
+    name = b"_ZN10rust_crash5crash17h0123456789abcdefE"
+    strtab = b"\x00" + name + b"\x00"
+    symtab = b"\x00" * 24 + struct.pack("<IBBHQQ", 1, 0x12, 0, 1, 0x1000, 0x0C)
+    elf = _elf_with(
+        [
+            Section(".debug_line", 1, 0, 0, 0, 0, 0, SHF_COMPRESSED),
+            Section(".strtab", 3, 0, 0, 0, 0, 0),
+            Section(".symtab", 2, 0, 0, 0, 1, 24),
+        ],
+        [zline, strtab, symtab],
+    )
+    sym = Symbolizer(elf)
+    assert sym.has_line_info and sym.line_error is None
+
+    bias = 0x5555_0000_0000
+    frames = sym.symbolize_frames([Frame(index=0, pc=bias + 0x1000), Frame(index=1, pc=bias + 0x1005)], bias)
+    assert frames[0].function == "rust_crash::crash"
+    assert frames[0].raw_symbol == "_ZN10rust_crash5crash17h0123456789abcdefE"
+    assert frames[0].source_location == "src/main.rs:5:9"
+    # A return address resolves to the call before it (0x1004, line 6).
+    assert frames[1].line == 6
+    assert frames[1].describe() == f"#1 0x{bias + 0x1005:x} rust_crash::crash at src/main.rs:6:9"
+
+    # Fields a debugger already reported are kept.
+    keep = sym.symbolize(Frame(index=0, pc=0x1000, function="crash", file="main.rs", line=9))
+    assert (keep.function, keep.line) == ("crash", 9)
+
+    # Offsets past the section surface as line_error instead of raising.
+    broken = Symbolizer(_elf_with([Section(".debug_line", 1, 0, 0, 0, 0, 0)], [line[:-40]]))
+    assert broken.locate(0x1000) is None and broken.line_error
+
+
+def test_stripped_binary_symbolizes_from_its_verified_debug_file(tmp_path):
+    if not all(shutil.which(tool) for tool in ("cc", "objcopy", "strip")):
+        pytest.skip("needs cc, objcopy, and strip")
+    source = "int add(int a, int b) {\n    return a + b;\n}\nint main(void) { return add(1, 2); }\n"
+    (tmp_path / "app.c").write_text(source)
+    full, app = tmp_path / "full", tmp_path / "app"
+    subprocess.run(["cc", "-g", "-O0", "-Wl,--build-id", "-o", str(full), str(tmp_path / "app.c")], check=True)
+    subprocess.run(["objcopy", "--only-keep-debug", str(full), str(tmp_path / "app.debug")], check=True)
+    subprocess.run(["strip", "--strip-debug", "--strip-unneeded", "-o", str(app), str(full)], check=True)
+    subprocess.run(["objcopy", "--add-gnu-debuglink=app.debug", str(app)], cwd=tmp_path, check=True)
+    add = next(s for s in Symbolizer.open(full).elf.symbols() if s.name == "add")
+
+    inline = Symbolizer.open(full).locate(add.value)
+    stripped = Symbolizer.open(app, debug_dirs=())
+    assert stripped.debug_path == str(tmp_path / "app.debug") and stripped.debug_error is None
+    assert stripped.locate(add.value) == inline
+    assert (inline.function, inline.line) == ("add", 1)
+
+    # Found by build-id alone, in a debug directory.
+    build_id = stripped.elf.build_id()
+    by_id = tmp_path / "debug" / ".build-id" / build_id[:2] / f"{build_id[2:]}.debug"
+    by_id.parent.mkdir(parents=True)
+    (tmp_path / "app.debug").rename(by_id)
+    assert find_debug_file(str(app), stripped.elf, debug_dirs=[str(tmp_path / "debug")]) == str(by_id)
+
+    # A debug file from another build is refused: its CRC does not match the debuglink.
+    other = tmp_path / "other.debug"
+    other.write_bytes(by_id.read_bytes() + b"\0")
+    no_id = tmp_path / "no-id"
+    subprocess.run(["objcopy", "--remove-section=.note.gnu.build-id", str(app), str(no_id)], check=True)
+    shutil.copy(other, tmp_path / "app.debug")
+    refused = Symbolizer.open(no_id, debug_dirs=())
+    assert refused.debug_elf is None and "does not match the debuglink" in refused.debug_error
+    assert refused.locate(add.value) is None
+    assert Symbolizer.open(no_id, debug_file=by_id).locate(add.value) == inline
+
+
+def test_check_reports_debug_sections_and_the_debug_file(tmp_path, capsys):
+    if not all(shutil.which(tool) for tool in ("cc", "objcopy", "strip")):
+        pytest.skip("needs cc, objcopy, and strip")
+    (tmp_path / "app.c").write_text("int main(void) { return 0; }\n")
+    full, app = tmp_path / "full", tmp_path / "app"
+    subprocess.run(["cc", "-g", "-Wl,--build-id", "-o", str(full), str(tmp_path / "app.c")], check=True)
+    subprocess.run(["objcopy", "--only-keep-debug", str(full), str(tmp_path / "app.debug")], check=True)
+    subprocess.run(["strip", "--strip-debug", "--strip-unneeded", "-o", str(app), str(full)], check=True)
+    subprocess.run(["objcopy", "--add-gnu-debuglink=app.debug", str(app)], cwd=tmp_path, check=True)
+
+    inline = check_debug_info(str(full), debug_dirs=())
+    assert not inline.stripped and inline.sections[".debug_line"] and inline.debug_file is None
+    assert inline.expect is Symbolization.SOURCE_LINES
+
+    stripped = check_debug_info(str(app), debug_dirs=())
+    assert stripped.stripped and not stripped.sections[".debug_info"] and stripped.debuglink == "app.debug"
+    assert stripped.debug_file == str(tmp_path / "app.debug") and ".debug_line" in stripped.debug_file_sections
+    assert stripped.expect is Symbolization.SOURCE_LINES and stripped.arch == inline.arch
+
+    assert cli.check_main([str(app)]) == 0
+    out = capsys.readouterr().out
+    assert "Stripped: yes (no .symtab)" in out and "  .debug_line     in the debug file" in out
+    assert f"Debug file: {tmp_path / 'app.debug'}" in out and "Frames will show: function names and file:line" in out
+
+    # A debug file from another build is rejected, and the frames fall back to exported names.
+    (tmp_path / "other.c").write_text("int main(void) { return 1; }\n")
+    other = tmp_path / "other"
+    subprocess.run(["cc", "-g", "-Wl,--build-id", "-o", str(other), str(tmp_path / "other.c")], check=True)
+    assert cli.check_main(["--format", "json", "--debug-file", str(other), str(app)]) == 0
+    data = json.loads(capsys.readouterr().out)
+    assert data["debug_file"] is None and data["stripped"] and data["sections"][".gnu_debuglink"]
+    assert data["expect"] in ("exported_functions", "raw_addresses") and len(data["debug_file_rejected"]) == 1
+
+    (tmp_path / "notes.txt").write_text("not a binary")
+    assert cli.check_main([str(tmp_path / "notes.txt")]) == cli.ExitCode.CAPTURE_FAILED
+    assert "dbgcopilot check:" in capsys.readouterr().err
+
+
+def test_demangle_nested_names():
+    assert demangle("_ZN4core3ptr13drop_in_place17h9f1e2d3c4b5a6978E") == "core::ptr::drop_in_place"
+    assert demangle("_ZN10crash_demo5crashEv") == "crash_demo::crash"
+    assert demangle("_ZN5alloc3vec12Vec$LT$T$GT$4push17h0011223344556677E") == "alloc::vec::Vec<T>::push"
+    assert demangle("main") == "main"
+    assert demangle("_ZN3fooE") == "foo"
+
+
+def test_demangle_v0_and_unnested_names():
+    assert demangle("_RNvNtCs1234_5crash3foo3bar") == "crash::foo::bar"
+    assert demangle("_RNCNvCs1_5crash4main0B3_") == "crash::main::{closure}"
+    assert demangle("__ZN5crash5crash17h0123456789abcdefE") == "crash::crash"
+    assert demangle("_Z3fooi") == "foo" and demangle("_ZNK3Foo3barEv") == "Foo::bar"
+    assert demangle("_ZN3FooD1Ev") == "Foo::~Foo"
+    assert demangle("_RINvCs1_5crash4sizelEB2_") == "crash::size::<i32>"
+    assert demangle("_RNvNtCs1_4core3ptru8gdel_5qa6escape") == "core::ptr::gödel"
+
+
+def test_demangle_rust_impls_generics_and_escapes():
+    assert demangle("_RNvMNtCs1234_7mycrate3fooNtB2_3Bar3new") == "<mycrate::foo::Bar>::new"
+    display = "_RNvXs_NtCs1234_7mycrate3fooNtB4_3BarNtNtCs5678_4core3fmt7Display3fmt"
+    assert demangle(display) == "<mycrate::foo::Bar as core::fmt::Display>::fmt"
+    iterator = "_RNvXNtCs1_3app4iterINtB2_4WrapRShEINtNtNtCs2_4core4iter6traits8IteratorE4next"
+    assert demangle(iterator) == "<app::iter::Wrap<&[u8]> as core::iter::traits::Iterator>::next"
+    assert demangle("_RINvCs1_3app3runDNtNtCs2_4core3any3AnyEL_EB2_") == "app::run::<dyn core::any::Any>"
+    # Legacy: a component that starts with an escape gets a "_" in front; LLVM's suffix is not part of the name.
+    call = (
+        "_ZN86_$LT$alloc..boxed..Box$LT$F$C$A$GT$$u20$as$u20$core..ops..function..Fn$LT$Args$GT$$GT$"
+        "4call17h0123456789abcdefE"
+    )
+    assert demangle(call) == "<alloc::boxed::Box<F,A> as core::ops::function::Fn<Args>>::call"
+    assert demangle("_ZN4core9panicking5panic17h0123456789abcdefE.llvm.12345") == "core::panicking::panic"
+
+
+def test_demangle_cpp_templates_substitutions_and_operators():
+    assert demangle("_ZNSt6vectorIiSaIiEE9push_backERKi") == "std::vector<int, std::allocator<int>>::push_back"
+    libcxx = "_ZNSt3__16vectorIiNS_9allocatorIiEEE9push_backEOi"
+    assert demangle(libcxx) == "std::__1::vector<int, std::__1::allocator<int>>::push_back"
+    assert demangle("_ZNSt10unique_ptrI3FooSt14default_deleteIS0_EED2Ev") == (
+        "std::unique_ptr<Foo, std::default_delete<Foo>>::~unique_ptr"
+    )
+    assert demangle("_ZNKSt7__cxx1112basic_stringIcSt11char_traitsIcESaIcEE4sizeEv") == (
+        "std::__cxx11::basic_string<char, std::char_traits<char>, std::allocator<char>>::size"
+    )
+    assert demangle("_ZNKSt8functionIFvvEEclEv") == "std::function<void ()>::operator()"
+    assert demangle("_ZN3FooplERKS_") == "Foo::operator+" and demangle("_ZdlPv") == "operator delete"
+    assert demangle("_Z3maxIiET_S0_S0_") == "max<int>" and demangle("_ZSt9terminatev") == "std::terminate"
+    assert demangle("_ZZ4mainENKUlvE_clEv") == "main::{lambda()#1}::operator()"
+    assert demangle("_ZN12_GLOBAL__N_13fooEv") == "(anonymous namespace)::foo"
+    assert demangle("_ZThn8_N3Foo3barEv") == "non-virtual thunk to Foo::bar"
+    assert demangle("_ZN3foo3barB5cxx11Ev.cold") == "foo::bar"
+    # Expressions in template arguments are not handled and come back as given.
+    assert demangle("_ZN1AIXadL_Z1fvEEE1gEv") == "_ZN1AIXadL_Z1fvEEE1gEv"
@@ -0,0 +1,107 @@
+import shutil
+import subprocess
+
+import pytest
+import requests
+
+from dbgcopilot.analysis import AnalysisError, AnalyzeRequest, analyze
+from dbgcopilot.symbols import SymbolFetchError, SymbolPath, SymbolServer, Symbolizer, find_debug_file
+
+
+class _Response:
+    def __init__(self, status_code: int, body: bytes = b"") -> None:
+        self.status_code = status_code
+        self.body = body
+
+    def iter_content(self, size):
+        yield from (self.body[i:i + size] for i in range(0, len(self.body), size))
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, *exc):
+        return False
+
+
+def test_symbol_path_parses_directories_servers_and_caches(tmp_path):
+    path = SymbolPath.parse(
+        f"/srv/symbols; srv*{tmp_path}/own*https://debuginfod.example.org;cache*{tmp_path}/shared;"
+        "SRV*http://mirror.example.org/",
+        default_cache="/unused",
+    )
+    assert path.directories == ("/srv/symbols",)
+    assert path.servers == (
+        SymbolServer("https://debuginfod.example.org", f"{tmp_path}/own"),
+        SymbolServer("http://mirror.example.org/", f"{tmp_path}/shared"),
+    )
+    assert path.debug_dirs == ("/srv/symbols", f"{tmp_path}/own", f"{tmp_path}/shared", "/usr/lib/debug")
+    assert SymbolPath.parse("srv*https://a.example.org", default_cache="/c").servers[0].cache == "/c"
+
+    with pytest.raises(ValueError, match="no server URL"):
+        SymbolPath.parse("srv*/only/a/cache")
+    (tmp_path / "core").write_bytes(b"\x7fELF")
+    with pytest.raises(AnalysisError, match="symbol_path"):
+        analyze(AnalyzeRequest(binary=None, core=str(tmp_path / "core"), symbol_path="srv*"))
+
+
+def test_downloads_land_in_the_cache_and_failures_are_recorded(tmp_path, monkeypatch):
+    calls = []
+
+    def get(url, stream, timeout):
+        calls.append(url)
+        return _Response(200, b"debug info") if "good" in url else _Response(404)
+
+    monkeypatch.setattr(requests, "get", get)
+    path = SymbolPath.parse("srv*https://bad.example.org;srv*https://good.example.org/", default_cache=str(tmp_path))
+    rejected = []
+    fetched = path.fetch_debug_file("abcdef", rejected)
+    assert fetched == str(tmp_path / ".build-id" / "ab" / "cdef.debug")
+    assert open(fetched, "rb").read() == b"debug info"
+    assert rejected == ["https://bad.example.org/buildid/abcdef/debuginfo: HTTP 404"]
+    assert calls[-1] == "https://good.example.org/buildid/abcdef/debuginfo"
+    assert list((tmp_path / ".build-id" / "ab").iterdir()) == [tmp_path / ".build-id" / "ab" / "cdef.debug"]
+
+    # A cached file is not downloaded again.
+    calls.clear()
+    assert path.servers[1].fetch("abcdef") == fetched and calls == []
+
+    def down(url, stream, timeout):
+        raise requests.ConnectionError("connection refused")
+
+    monkeypatch.setattr(requests, "get", down)
+    with pytest.raises(SymbolFetchError, match="connection refused"):
+        path.servers[0].fetch("123456")
+    rejected = []
+    assert path.fetch_debug_file("123456", rejected) is None and len(rejected) == 2
+
+
+def test_stripped_library_symbolizes_from_a_symbol_server(tmp_path, monkeypatch):
+    if not all(shutil.which(tool) for tool in ("cc", "objcopy", "strip")):
+        pytest.skip("needs cc, objcopy, and strip")
+    (tmp_path / "app.c").write_text("int add(int a, int b) {\n    return a + b;\n}\nint main(void) { return 0; }\n")
+    full, app = tmp_path / "full", tmp_path / "app"
+    subprocess.run(["cc", "-g", "-O0", "-Wl,--build-id", "-o", str(full), str(tmp_path / "app.c")], check=True)
+    subprocess.run(["objcopy", "--only-keep-debug", str(full), str(tmp_path / "app.debug")], check=True)
+    subprocess.run(["strip", "--strip-debug", "--strip-unneeded", "-o", str(app), str(full)], check=True)
+    debug = (tmp_path / "app.debug").read_bytes()
+    (tmp_path / "app.debug").unlink()
+    monkeypatch.setattr(requests, "get", lambda url, stream, timeout: _Response(200, debug))
+
+    cache = tmp_path / "cache"
+    symbol_path = SymbolPath.parse(f"{tmp_path}/empty;srv*{cache}*https://debuginfod.example.org")
+    stripped = Symbolizer.open(app, symbol_path=symbol_path)
+    build_id = stripped.elf.build_id()
+    assert stripped.debug_path == str(cache / ".build-id" / build_id[:2] / f"{build_id[2:]}.debug")
+    add = next(s for s in Symbolizer.open(full).elf.symbols() if s.name == "add")
+    assert stripped.locate(add.value).function == "add"
+
+    # The next run finds the cached copy as a local candidate, with the server down.
+    monkeypatch.setattr(requests, "get", None)
+    assert find_debug_file(str(app), stripped.elf, symbol_path=symbol_path) == stripped.debug_path
+
+    # A server's file from another build is refused, and the frame keeps its module and address.
+    shutil.rmtree(cache)
+    monkeypatch.setattr(requests, "get", lambda url, stream, timeout: _Response(200, b"\x7fELF not this one"))
+    rejected = []
+    assert find_debug_file(str(app), stripped.elf, rejected=rejected, symbol_path=symbol_path) is None
+    assert rejected
@@ -0,0 +1,71 @@
+import io
+import logging
+
+import pytest
+
+from dbgcopilot.analysis import AnalysisReport, AnalyzeRequest, CrashContext, Frame, StopInfo, explain
+from dbgcopilot.analysis import api
+from dbgcopilot.llm import providers
+from dbgcopilot.utils import trace
+
+
+def _capture(spec):
+    stream = io.StringIO()
+    assert trace.configure_logging(spec, stream)
+    return stream
+
+
+def _reset():
+    root = logging.getLogger("dbgcopilot")
+    root.removeHandler(trace._handler)
+    trace._handler = None
+    for name in ("dbgcopilot", "dbgcopilot.analysis", "dbgcopilot.llm"):
+        logging.getLogger(name).setLevel(logging.NOTSET)
+    root.propagate = True
+
+
+def test_spans_log_duration_fields_and_failures():
+    try:
+        stream = _capture("info")
+        log = logging.getLogger("dbgcopilot.analysis.api")
+        with trace.span(log, "prompt.build", frames=3) as stage:
+            stage["tokens"] = 120
+        with pytest.raises(TimeoutError):
+            with trace.span(log, "debugger.launch", backend="gdb"):
+                raise TimeoutError("gdb timed out after 60s")
+        lines = stream.getvalue().splitlines()
+        assert "prompt.build done in " in lines[0] and lines[0].endswith(" ms frames=3 tokens=120")
+        assert "WARNING dbgcopilot.analysis.api: debugger.launch failed after " in lines[1]
+        assert lines[1].endswith(": gdb timed out after 60s backend=gdb")
+
+        # Per-logger directives: only the LLM layer at debug, everything else at warning.
+        stream = _capture("warning,dbgcopilot.llm=debug")
+        with trace.span(log, "symbolize"):
+            pass
+        with trace.span(logging.getLogger("dbgcopilot.llm.base"), "llm.request"):
+            pass
+        out = stream.getvalue()
+        assert "symbolize" not in out and "llm.request started" in out and "llm.request done" in out
+        assert not trace.configure_logging("")
+        with pytest.raises(ValueError, match="unknown log level 'loud'"):
+            trace.configure_logging("loud")
+    finally:
+        _reset()
+
+
+def test_llm_call_span_reports_provider_and_latency(monkeypatch):
+    def fake_client(name, config):
+        return lambda prompt: "Null pointer written in boom()."
+
+    monkeypatch.setattr(providers, "create_client", fake_client)
+    ctx = CrashContext(stop=StopInfo(signal="SIGSEGV", fault_address=0), frames=[Frame(0, pc=0x401136, function="boom")])
+    report: AnalysisReport = api._report_from_context(ctx, "gdb")
+    try:
+        stream = _capture("info")
+        explain(report, AnalyzeRequest(binary="crash", provider="mock-local"))
+        out = stream.getvalue()
+        assert report.explanation == "Null pointer written in boom()."
+        assert "prompt.build done in " in out and "llm.call done in " in out
+        assert "provider=mock-local stream=False attempts=1 chars=31" in out
+    finally:
+        _reset()
@@ -0,0 +1,201 @@
+import json
+
+import pytest
+import requests
+
+from dbgcopilot import cli
+from dbgcopilot.analysis import ExplanationCache, api
+from dbgcopilot.llm import base
+from dbgcopilot.llm.base import Completion, Prompt
+from dbgcopilot.llm.errors import LlmHttpError
+from dbgcopilot.llm.retry import RetryPolicy
+from dbgcopilot.llm.sse import StreamError
+from dbgcopilot.llm.usage import ModelPrice, UsageMeter, count_tokens, parse_price, price_for, price_table
+
+PRICES = ["gpt-4o-mini=0.15/0.60"]
+
+
+class _MeteredProvider:
+    name = "openai"
+    model = "gpt-4o-mini"
+
+    def __init__(self, answers, usages):
+        self.answers, self.usages = list(answers), list(usages)
+        self.prompts = []
+
+    def complete(self, prompt):
+        self.prompts.append(prompt)
+        return Completion(self.answers.pop(0), self.name, self.model, self.usages.pop(0))
+
+    def stream(self, prompt, on_chunk):
+        completion = self.complete(prompt)
+        on_chunk(completion.text)
+        return completion
+
+
+def test_reported_tokens_are_priced_and_missing_ones_estimated(null_deref_report):
+    fix = '{"summary": "Check ptr for null before writing through it."}'
+    llm = _MeteredProvider(
+        ["crash() writes through a null pointer.", fix],
+        [{"prompt_tokens": 1200, "completion_tokens": 300}, {}],
+    )
+    request = api.AnalyzeRequest(binary="rust_crash", provider=llm, llm_config={"prices": PRICES})
+    report = null_deref_report()
+    api.explain(report, request)
+    assert (report.usage.prompt_tokens, report.usage.completion_tokens) == (1200, 300)
+    assert report.usage.cost == pytest.approx((1200 * 0.15 + 300 * 0.60) / 1_000_000)
+    assert report.usage.describe() == (
+        "LLM usage: 1200 prompt + 300 completion tokens, ~$0.0004 (openai/gpt-4o-mini)"
+    )
+
+    # This answer came without usage: its tokens are counted here.
+    api.add_fix_suggestion(report, request)
+    assert report.fix_suggestion is not None and report.usage.calls == 2 and report.usage.estimated
+    assert report.usage.prompt_tokens > 1200 and report.usage.completion_tokens > 300
+    data = json.loads(report.to_json())["usage"]
+    assert data["calls"] == 2 and data["total_tokens"] == report.usage.total_tokens
+    assert data["models"] == ["openai/gpt-4o-mini"] and data["cost"] == pytest.approx(report.usage.cost)
+
+    # A provider's own cost (OpenRouter's) wins; an unpriced model's stays unknown.
+    other = UsageMeter.from_config({})
+    other.record(llm, Prompt("x"), Completion("y", "openrouter", "acme/big", {"cost": 0.25}))
+    assert other.usage.cost == 0.25
+    other.record(llm, Prompt("x"), Completion("y", "acme", "big"))
+    assert other.usage.cost is None and "cost unknown" in other.usage.describe()
+    assert json.loads(null_deref_report().to_json())["usage"] is None
+
+
+def test_max_cost_refuses_a_prompt_that_could_cost_more(null_deref_report):
+    llm = _MeteredProvider(["unused"], [{}])
+    # 512 answer tokens at $0.60 per million alone are $0.0003.
+    config = {"prices": PRICES, "max_cost": "0.0002"}
+    report = null_deref_report()
+    api.explain(report, api.AnalyzeRequest(binary="rust_crash", provider=llm, llm_config=config))
+    assert llm.prompts == [] and report.explanation is None and report.usage is None
+    assert report.explanation_error.startswith("not sending the prompt: ")
+    assert report.explanation_error.endswith(
+        "-token answer to openai/gpt-4o-mini could cost $0.0003, over max_cost $0.0002"
+    )
+
+    # Without a price the budget cannot be checked, so nothing is sent either.
+    report = null_deref_report()
+    api.explain(report, api.AnalyzeRequest(binary="rust_crash", provider=llm, llm_config={"max_cost": 1}))
+    assert llm.prompts == [] and "openai/gpt-4o-mini has no price in llm.prices" in report.explanation_error
+
+    assert price_table({"prices": "gpt-4o=2.5/10, claude-*=3/15"})[:2] == [
+        ModelPrice("gpt-4o", 2.5, 10.0), ModelPrice("claude-*", 3.0, 15.0)
+    ]
+    assert price_for("ollama/llama3.1", price_table({})).cost(10**6, 10**6) == 0
+    with pytest.raises(ValueError, match="expected MODEL=INPUT/OUTPUT"):
+        parse_price("gpt-4o=2.5")
+
+
+def test_every_attempt_is_checked_and_a_broken_stream_is_still_counted(null_deref_report):
+    class _FlakyProvider(_MeteredProvider):
+        def complete(self, prompt):
+            if not self.prompts:
+                self.prompts.append(prompt)
+                raise LlmHttpError("503 overloaded", self.name, status=503)
+            return super().complete(prompt)
+
+        def stream(self, prompt, on_chunk):
+            on_chunk("crash() writes through")
+            raise StreamError("connection reset", "crash() writes through", self.name)
+
+    checks = []
+
+    class _CountingMeter(UsageMeter):
+        def check(self, llm, prompt):
+            checks.append(prompt)
+            super().check(llm, prompt)
+
+    # The retry after the 503 is checked against max_cost again; only the answer is counted.
+    llm = _FlakyProvider(["crash() writes through a null pointer."], [{"prompt_tokens": 50, "completion_tokens": 9}])
+    meter = _CountingMeter.from_config({"prices": PRICES, "max_cost": 1})
+    completion, attempts = RetryPolicy(sleep=lambda _s: None).complete(llm, Prompt("why?"), meter)
+    assert attempts == 2 and len(checks) == 2
+    assert (meter.usage.calls, meter.usage.prompt_tokens, meter.usage.completion_tokens) == (1, 50, 9)
+
+    # The tokens a stream generated before it broke were paid for.
+    report = null_deref_report()
+    request = api.AnalyzeRequest(
+        binary="rust_crash", provider=llm, llm_config={"prices": PRICES}, on_explain_chunk=lambda chunk: None
+    )
+    api.explain(report, request)
+    assert report.explanation_partial and report.explanation == "crash() writes through"
+    assert report.usage.calls == 1 and report.usage.estimated and report.usage.cost > 0
+    assert report.usage.completion_tokens == count_tokens("crash() writes through")
+
+
+def test_cli_prints_the_usage_line_and_honours_max_cost(tmp_path, monkeypatch, capsys, null_deref_report):
+    monkeypatch.chdir(tmp_path)
+    monkeypatch.setenv("DBGCOPILOT_LLM_PRICES", ",".join(PRICES))
+    report = null_deref_report()
+    usage = {"prompt_tokens": 900, "completion_tokens": 40}
+    llm = _MeteredProvider(["crash() writes through a null pointer."], [usage])
+    monkeypatch.setattr(api, "analyze", lambda request: report)
+    monkeypatch.setattr(base, "resolve_provider", lambda provider, config=None: llm)
+    argv = ["--no-cache", "--no-history", "--provider", "openai", "--format", "json"]
+
+    assert cli.analyze_main(argv + ["--max-cost", "0.01", "rust_crash"]) == cli.ExitCode.OK
+    out, err = capsys.readouterr()
+    assert json.loads(out)["usage"]["prompt_tokens"] == 900
+    assert "LLM usage: 900 prompt + 40 completion tokens, ~$0.0002 (openai/gpt-4o-mini)" in err
+
+    report.explanation, report.usage = None, None
+    assert cli.analyze_main(argv + ["--max-cost", "0.0001", "rust_crash"]) == cli.ExitCode.LLM_FAILED
+    out, err = capsys.readouterr()
+    assert "over max_cost $0.0001" in json.loads(out)["explanation_error"] and "LLM usage" not in err
+
+
+def _sse(event):
+    return "data: " + json.dumps(event)
+
+
+class _StreamResponse:
+    status_code = 200
+    text = ""
+
+    def __init__(self, lines):
+        self.lines = lines
+
+    def iter_lines(self):
+        return iter(self.lines)
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, *_exc):
+        return False
+
+
+def test_streams_ask_for_usage_and_a_cache_hit_counts_no_tokens(tmp_path, monkeypatch, null_deref_report):
+    sent = []
+    answer = {"choices": [{"delta": {"content": "Null write in crash()."}, "finish_reason": "stop"}]}
+    usage = {"choices": [], "usage": {"prompt_tokens": 321, "completion_tokens": 7}}
+
+    def fake_post(url, headers=None, json=None, timeout=None, stream=False):
+        sent.append(json)
+        return _StreamResponse([_sse(answer), _sse(usage), "data: [DONE]"])
+
+    monkeypatch.setattr(requests, "post", fake_post)
+    request = api.AnalyzeRequest(
+        binary="rust_crash",
+        provider="ollama",
+        llm_config={"ollama_base_url": "http://127.0.0.1:9"},
+        cache=ExplanationCache(str(tmp_path / "cache")),
+        on_explain_chunk=lambda chunk: None,
+    )
+    report = null_deref_report()
+    api.explain(report, request)
+    assert sent[0]["stream_options"] == {"include_usage": True}
+    assert (report.usage.prompt_tokens, report.usage.completion_tokens, report.usage.cost) == (321, 7, 0)
+    assert not report.usage.estimated and report.usage.cached == 0
+
+    # The same crash again: answered from the cache, so no tokens and no cost, but the hit is on record.
+    again = null_deref_report()
+    api.explain(again, request)
+    assert len(sent) == 1 and again.explanation_cached
+    assert (again.usage.total_tokens, again.usage.calls, again.usage.cost, again.usage.cached) == (0, 0, 0, 1)
+    assert again.usage.describe() == "LLM usage: 0 prompt + 0 completion tokens, $0; explanation from the cache"
+    assert json.loads(again.to_json())["usage"]["cached"] == 1
@@ -0,0 +1,49 @@
+from dbgcopilot.analysis import CrashContext, CrashReport, Frame, Language, PromptBuilder, StopInfo
+from dbgcopilot.analysis.crash import frame_from_dict
+from dbgcopilot.analysis.v8 import EngineRole, engine_role, label_engine_frames
+
+# The top and bottom of examples/crash/node's stack in the core node 20 leaves: its
+# recursion runs off the stack inside a bytecode handler. The JIT frame at #2 is
+# how a compiled function shows: an address outside every module.
+FRAMES = [
+    Frame(0, pc=0x1A0ED07, function="Builtins_GetNamedPropertyHandler", module="node"),
+    Frame(1, pc=0x18CFD1C, function="Builtins_InterpreterEntryTrampoline", module="node"),
+    Frame(2, pc=0x3E9C0FA4B1C2),
+    Frame(3, pc=0x18CE0DC, function="Builtins_JSEntryTrampoline", module="node"),
+    Frame(4, pc=0x10494DB, function="v8::internal::(anonymous namespace)::Invoke", module="node"),
+    Frame(5, pc=0xF0C01D, function="v8::Function::Call", module="node"),
+    Frame(6, pc=0xC8FC8D, function="node::builtins::BuiltinLoader::CompileAndCall", module="node"),
+    Frame(7, pc=0xC6853F, function="node::Start", module="node"),
+    Frame(8, pc=0x7FDC4B22524A, module="libc.so.6"),
+]
+
+
+def test_engine_frames_are_labelled_and_javascript_is_the_user_frame():
+    frames = label_engine_frames(FRAMES)
+    assert [f.engine for f in frames] == [
+        "V8 builtin", "JavaScript", "JavaScript", "V8 builtin", "V8 internals", "V8 internals",
+        "Node.js runtime", "Node.js runtime", None,
+    ]
+    assert frames[0].describe() == "#0 0x1a0ed07 node!Builtins_GetNamedPropertyHandler [V8 builtin]"
+    assert FRAMES[0].engine is None
+    assert engine_role(Frame(0, function="uv_run", module="node")) is EngineRole.LIBUV
+    assert engine_role(Frame(0, function="Parse", module="/srv/build/Release/parser.node")) is EngineRole.ADDON
+
+    # Nothing from V8 on the stack: a bare address stays unlabelled, and so does libuv in a C program.
+    plain = [Frame(0, pc=0x1234), Frame(1, function="uv_run", module="libuv.so.1"), Frame(2, function="main")]
+    assert all(f.engine is None for f in label_engine_frames(plain))
+
+    report = CrashReport.from_context(CrashContext(stop=StopInfo(signal="SIGSEGV", fault_address=0), frames=FRAMES))
+    assert report.first_user_frame().index == 1
+    assert report.frames[4].engine == "V8 internals"
+    data = report.to_dict()
+    assert data["frames"][2]["engine"] == "JavaScript"
+    assert frame_from_dict(data["frames"][6]).engine == "Node.js runtime"
+
+
+def test_node_prompt_tells_the_model_which_frames_are_the_engine():
+    ctx = CrashContext(stop=StopInfo(signal="SIGSEGV", fault_address=0), frames=FRAMES, language=Language.JAVASCRIPT)
+    text = PromptBuilder().build(CrashReport.from_context(ctx)).text
+    assert "Language: JavaScript" in text
+    assert "#4 0x10494db node!v8::internal::(anonymous namespace)::Invoke [V8 internals]" in text
+    assert "do not explain them as the program's code" in text
@@ -0,0 +1,323 @@
+//! Repository automation. Run with `cargo xtask <command>` from the repo root.
+//!
+//! `build-examples` discovers every Cargo project under `examples/<scenario>/<lang>/`,
+//! builds it in each requested profile, and prints a JSON summary of the produced
+//! binaries on stdout (progress goes to stderr).
+
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode};
+
+const USAGE: &str = "\
+usage: cargo xtask build-examples [--profile debug|release]... [--json <path>] [--root <dir>]
+
+  --profile   profile to build (repeatable, default: debug and release)
+  --json      also write the JSON summary to <path>
+  --root      repository root (default: parent of the xtask crate)";
+
+struct Options {
+    profiles: Vec<String>,
+    json_path: Option<PathBuf>,
+    root: PathBuf,
+}
+
+struct Example {
+    /// Path relative to `examples/`, e.g. `crash/rust`.
+    name: String,
+    dir: PathBuf,
+    package: String,
+    binaries: Vec<String>,
+}
+
+struct BuildResult {
+    example: String,
+    package: String,
+    profile: String,
+    ok: bool,
+    binaries: Vec<PathBuf>,
+    error: Option<String>,
+}
+
+fn main() -> ExitCode {
+    let mut args = env::args().skip(1);
+    match args.next().as_deref() {
+        Some("build-examples") => match parse_options(args) {
+            Ok(opts) => build_examples(&opts),
+            Err(msg) => {
+                eprintln!("{msg}\n{USAGE}");
+                ExitCode::from(2)
+            }
+        },
+        Some("-h") | Some("--help") => {
+            println!("{USAGE}");
+            ExitCode::SUCCESS
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let default_root = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."));
+    let mut opts = Options {
+        profiles: Vec::new(),
+        json_path: None,
+        root: default_root,
+    };
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| args.next().ok_or_else(|| format!("{flag} expects a value"));
+        match arg.as_str() {
+            "--profile" => {
+                let profile = value("--profile")?;
+                if profile != "debug" && profile != "release" {
+                    return Err(format!("unknown profile '{profile}'"));
+                }
+                opts.profiles.push(profile);
+            }
+            "--json" => opts.json_path = Some(PathBuf::from(value("--json")?)),
+            "--root" => opts.root = PathBuf::from(value("--root")?),
+            other => return Err(format!("unknown argument '{other}'")),
+        }
+    }
+    if opts.profiles.is_empty() {
+        opts.profiles = vec!["debug".into(), "release".into()];
+    }
+    Ok(opts)
+}
+
+fn build_examples(opts: &Options) -> ExitCode {
+    let examples = match discover(&opts.root.join("examples")) {
+        Ok(found) => found,
+        Err(err) => {
+            eprintln!("failed to scan examples: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if examples.is_empty() {
+        eprintln!(
+            "no Cargo examples found under {}",
+            opts.root.join("examples").display()
+        );
+    }
+
+    let mut results = Vec::new();
+    for example in &examples {
+        for profile in &opts.profiles {
+            eprintln!("building {} ({profile})", example.name);
+            let result = build_one(example, profile);
+            match &result.error {
+                None => {
+                    for bin in &result.binaries {
+                        eprintln!("  ok  {}", bin.display());
+                    }
+                }
+                Some(err) => eprintln!("  FAILED: {err}"),
+            }
+            results.push(result);
+        }
+    }
+
+    let summary = render_json(&results);
+    println!("{summary}");
+    if let Some(path) = &opts.json_path {
+        if let Err(err) = fs::write(path, format!("{summary}\n")) {
+            eprintln!("failed to write {}: {err}", path.display());
+            return ExitCode::FAILURE;
+        }
+    }
+    if results.iter().all(|r| r.ok) {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Find `examples/<scenario>/<lang>/Cargo.toml`, sorted for stable output.
+fn discover(examples_dir: &Path) -> std::io::Result<Vec<Example>> {
+    let mut found = Vec::new();
+    for scenario in sorted_dirs(examples_dir)? {
+        for lang in sorted_dirs(&scenario)? {
+            let manifest = lang.join("Cargo.toml");
+            let Ok(text) = fs::read_to_string(&manifest) else {
+                continue;
+            };
+            let Some((package, binaries)) = parse_manifest(&text) else {
+                continue;
+            };
+            let name = lang
+                .strip_prefix(examples_dir)
+                .unwrap_or(&lang)
+                .to_string_lossy()
+                .replace('\\', "/");
+            found.push(Example {
+                name,
+                dir: lang,
+                package,
+                binaries,
+            });
+        }
+    }
+    Ok(found)
+}
+
+fn sorted_dirs(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut dirs: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir() && path.file_name().is_some_and(|n| n != "target"))
+        .collect();
+    dirs.sort();
+    Ok(dirs)
+}
+
+/// Pull the package name and binary names out of a manifest.
+///
+/// Only the handful of keys we need are read, which avoids a TOML dependency.
+fn parse_manifest(text: &str) -> Option<(String, Vec<String>)> {
+    let mut section = String::new();
+    let mut package = None;
+    let mut bins = Vec::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            section = line
+                .trim_matches(|c| c == '[' || c == ']')
+                .trim()
+                .to_string();
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        if key.trim() != "name" {
+            continue;
+        }
+        let value = value.trim().trim_matches('"').to_string();
+        match section.as_str() {
+            "package" => package = Some(value),
+            "bin" => bins.push(value),
+            _ => {}
+        }
+    }
+    let package = package?;
+    if bins.is_empty() {
+        bins.push(package.clone());
+    }
+    Some((package, bins))
+}
+
+fn build_one(example: &Example, profile: &str) -> BuildResult {
+    let mut result = BuildResult {
+        example: example.name.clone(),
+        package: example.package.clone(),
+        profile: profile.to_string(),
+        ok: false,
+        binaries: Vec::new(),
+        error: None,
+    };
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".into());
+    let mut cmd = Command::new(cargo);
+    cmd.arg("build")
+        .arg("--manifest-path")
+        .arg(example.dir.join("Cargo.toml"));
+    if profile == "release" {
+        cmd.arg("--release");
+    }
+    let output = match cmd.output() {
+        Ok(output) => output,
+        Err(err) => {
+            result.error = Some(format!("failed to run cargo: {err}"));
+            return result;
+        }
+    };
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let tail: Vec<&str> = stderr.lines().rev().take(5).collect();
+        let tail: Vec<&str> = tail.into_iter().rev().collect();
+        result.error = Some(format!(
+            "cargo exited with {}: {}",
+            output.status,
+            tail.join(" | ")
+        ));
+        return result;
+    }
+
+    let target_dir = env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| example.dir.join("target"));
+    for bin in &example.binaries {
+        let path = target_dir
+            .join(profile)
+            .join(format!("{bin}{}", env::consts::EXE_SUFFIX));
+        if path.is_file() {
+            result
+                .binaries
+                .push(fs::canonicalize(&path).unwrap_or(path));
+        } else {
+            result.error = Some(format!("build succeeded but {} is missing", path.display()));
+            return result;
+        }
+    }
+    result.ok = true;
+    result
+}
+
+fn render_json(results: &[BuildResult]) -> String {
+    let succeeded = results.iter().filter(|r| r.ok).count();
+    let mut out = String::from("{\n  \"artifacts\": [");
+    for (i, r) in results.iter().enumerate() {
+        let binaries: Vec<String> = r
+            .binaries
+            .iter()
+            .map(|p| json_str(&p.to_string_lossy()))
+            .collect();
+        let error = r
+            .error
+            .as_deref()
+            .map_or_else(|| "null".to_string(), json_str);
+        let _ = write!(
+            out,
+            "{}\n    {{\"example\": {}, \"package\": {}, \"profile\": {}, \"ok\": {}, \"binaries\": [{}], \"error\": {}}}",
+            if i == 0 { "" } else { "," },
+            json_str(&r.example),
+            json_str(&r.package),
+            json_str(&r.profile),
+            r.ok,
+            binaries.join(", "),
+            error,
+        );
+    }
+    let _ = write!(
+        out,
+        "\n  ],\n  \"succeeded\": {succeeded},\n  \"failed\": {}\n}}",
+        results.len() - succeeded
+    );
+    out
+}
+
+fn json_str(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}